@@ -48,5 +48,100 @@ fn bench_hc_compress(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_hc_compress);
+/// Streams `data` through one HC stream in `block_size` blocks and returns
+/// the compressed size.  With `ext_dict`, each block is copied into a reused
+/// buffer and the history saved aside with `save_dict_hc`, so every block
+/// after the first is compressed against an external dictionary.
+fn stream_hc(
+    data: &[u8],
+    block_size: usize,
+    level: i32,
+    ext_dict: bool,
+    input: &mut [u8],
+    history: &mut [u8],
+    dst: &mut [u8],
+) -> usize {
+    use lz4::hc::api::{compress_hc_continue, reset_stream_hc, save_dict_hc, Lz4StreamHc};
+
+    let mut stream = Lz4StreamHc::create().expect("alloc");
+    reset_stream_hc(&mut stream, level);
+    let mut total = 0;
+    for block in data.chunks(block_size) {
+        let src = if ext_dict {
+            input[..block.len()].copy_from_slice(block);
+            &input[..block.len()]
+        } else {
+            block
+        };
+        // SAFETY: `data`, `input` and `history` outlive the stream; `input`
+        // is rewritten only after the history has been saved out of it.
+        let n = unsafe {
+            let n = compress_hc_continue(
+                &mut stream,
+                src.as_ptr(),
+                dst.as_mut_ptr(),
+                src.len() as i32,
+                dst.len() as i32,
+            );
+            if ext_dict {
+                save_dict_hc(&mut stream, history.as_mut_ptr(), history.len() as i32);
+            }
+            n
+        };
+        assert!(n > 0, "compress_hc_continue returned 0");
+        total += n as usize;
+    }
+    total
+}
+
+/// Contiguous versus ext-dict streaming, with the compressed size of each
+/// printed once so ratio changes show up next to the timings.
+fn bench_hc_ext_dict(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hc_stream");
+
+    let block_size = 65_536usize;
+    let data = lz4::corpus::log_lines(1 << 20, 1);
+    let mut input = vec![0u8; block_size];
+    let mut history = vec![0u8; 65_536];
+    let mut dst = vec![0u8; lz4::hc::compress_hc_bound(block_size)];
+
+    for &level in &[2i32, 4, 9, 12] {
+        for (name, ext_dict) in [("contiguous", false), ("ext_dict", true)] {
+            let size = stream_hc(
+                &data,
+                block_size,
+                level,
+                ext_dict,
+                &mut input,
+                &mut history,
+                &mut dst,
+            );
+            eprintln!(
+                "hc_stream/{name}/{level}: {} -> {} bytes ({:.3}%)",
+                data.len(),
+                size,
+                size as f64 * 100.0 / data.len() as f64
+            );
+
+            group.throughput(Throughput::Bytes(data.len() as u64));
+            group.bench_with_input(BenchmarkId::new(name, level), &data, |b, data| {
+                b.iter(|| {
+                    stream_hc(
+                        data,
+                        block_size,
+                        level,
+                        ext_dict,
+                        &mut input,
+                        &mut history,
+                        &mut dst,
+                    )
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_hc_compress, bench_hc_ext_dict);
 criterion_main!(benches);
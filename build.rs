@@ -5,7 +5,24 @@
 // platform.h's SET_SPARSE_FILE_MODE (which is a no-op on POSIX but an
 // IOCTL on Windows). On Unix, sparse files are supported natively by the OS;
 // on Windows, explicit DeviceIoControl(FSCTL_SET_SPARSE) is needed instead.
+//
+// Emits `cargo:rustc-cfg=wasm_unknown` for `wasm32-unknown-unknown`, which has
+// no files, clocks or processes: the `io` and `cli` modules are compiled out
+// and the `lz4` binary only reports that it is unavailable.
+//
+// Checks `LZ4R_BUFFER_ALIGN`, the alignment of the frame contexts' staging
+// buffers (see src/frame/aligned.rs), so a bad value fails with a readable
+// message instead of a const-evaluation error.
 fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=LZ4R_BUFFER_ALIGN");
+    if let Ok(align) = std::env::var("LZ4R_BUFFER_ALIGN") {
+        match align.parse::<usize>() {
+            Ok(n) if n.is_power_of_two() => {}
+            _ => panic!("LZ4R_BUFFER_ALIGN must be a power of two, got {align:?}"),
+        }
+    }
+
     // Sparse file support: available on Unix-like targets (Linux, macOS, BSDs, etc.)
     // SET_SPARSE_FILE_MODE in platform.h is a no-op on POSIX — the OS handles it.
     // On Windows, explicit IOCTL calls are required (handled separately).
@@ -29,4 +46,9 @@ fn main() {
     if unix_targets.contains(&target_os.as_str()) || std::env::var("CARGO_CFG_UNIX").is_ok() {
         println!("cargo:rustc-cfg=has_sparse_files");
     }
+
+    let target_arch = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    if target_arch == "wasm32" && target_os == "unknown" {
+        println!("cargo:rustc-cfg=wasm_unknown");
+    }
 }
@@ -11,6 +11,8 @@
 //! These tests verify that the Rust port produces correct results matching
 //! the LZ4 specification.
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 extern crate lz4;
 
 use lz4::{
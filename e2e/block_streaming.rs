@@ -8,6 +8,8 @@
 //! - Dictionary usage with streaming
 //! - Ring buffer size calculation
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 use lz4::block::decompress_api::{
     decoder_ring_buffer_size, decompress_safe, decompress_safe_continue, set_stream_decode,
     Lz4StreamDecode,
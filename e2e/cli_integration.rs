@@ -4,9 +4,11 @@
 // Covers argument parsing, compress/decompress dispatch, exit codes, test mode,
 // and list mode.
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tempfile::TempDir;
 
@@ -270,3 +272,1220 @@ fn test_cli_list_mode() {
         "--list stdout should contain file info; got empty output"
     );
 }
+
+#[test]
+fn test_cli_list_verbose_shows_block_stats() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("mixed.bin");
+    let mut data = b"compressible text, ".repeat(4000);
+    let mut seed = 12345u32;
+    data.extend((0..70_000).map(|_| {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (seed >> 16) as u8
+    }));
+    fs::write(&input, &data).unwrap();
+    let compressed = dir.path().join("mixed.lz4");
+    let status = Command::new(lz4_bin())
+        .args(["-q", "-f", "-B4", "-BX"])
+        .arg(&input)
+        .arg(&compressed)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(lz4_bin())
+        .args(["--list", "-v"])
+        .arg(&compressed)
+        .output()
+        .expect("failed to run lz4 --list -v");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Three 64 KiB blocks; the last holds only random bytes and is stored.
+    assert!(
+        stdout.contains(&format!(
+            "blocks: 3 (1 stored), checksums: 4, decoded: {} bytes",
+            data.len()
+        )),
+        "got {:?}",
+        stdout
+    );
+
+    // The plain listing is unchanged.
+    let output = Command::new(lz4_bin())
+        .arg("--list")
+        .arg(&compressed)
+        .output()
+        .unwrap();
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("blocks:"));
+}
+
+// ── 10. --log-format=json ─────────────────────────────────────────────────────
+
+#[test]
+fn test_cli_log_format_json() {
+    let dir = TempDir::new().unwrap();
+    let output = Command::new(lz4_bin())
+        .args([
+            "--log-format=json",
+            "/nonexistent_path_abc123_lz4test",
+            "/tmp/out_lz4_test.lz4",
+        ])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run lz4 --log-format=json");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let line = stderr
+        .lines()
+        .find(|l| l.contains("nonexistent_path_abc123_lz4test"))
+        .expect("error line should mention the missing file");
+    assert!(
+        line.starts_with("{\"level\":\"error\",\"message\":\""),
+        "expected a JSON error line; got {:?}",
+        line
+    );
+    assert!(line.ends_with("\"}"), "got {:?}", line);
+}
+
+#[test]
+fn test_cli_log_format_rejects_unknown() {
+    let output = Command::new(lz4_bin())
+        .args(["--log-format=xml", "-t", "/nonexistent_path_abc123_lz4test"])
+        .output()
+        .expect("failed to run lz4 --log-format=xml");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--log-format"), "got {:?}", stderr);
+}
+
+// ── 11. --list reports skippable frames ──────────────────────────────────────
+
+#[test]
+fn test_cli_list_reports_skippable_frames() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("meta.lz4");
+
+    let payload = b"application index";
+    let mut skippable = vec![0u8; payload.len() + 8];
+    lz4::frame::write_skippable_frame(&mut skippable, 3, payload).unwrap();
+    let mut file = fs::File::create(&path).unwrap();
+    file.write_all(&lz4::frame::compress_frame_to_vec(b"hello hello hello"))
+        .unwrap();
+    file.write_all(&skippable).unwrap();
+    drop(file);
+
+    let output = Command::new(lz4_bin())
+        .args(["--list", path.to_str().unwrap()])
+        .output()
+        .expect("failed to run lz4 --list");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("1 skippable frame(s)"),
+        "--list should report the skippable frame; got {:?}",
+        stdout
+    );
+
+    let output = Command::new(lz4_bin())
+        .args(["--list", "-v", path.to_str().unwrap()])
+        .output()
+        .expect("failed to run lz4 --list -v");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("SkippableFrame"), "got {:?}", stdout);
+    assert!(stdout.contains("m3"), "got {:?}", stdout);
+}
+
+// ── 12. --join / --extract ────────────────────────────────────────────────────
+
+#[test]
+fn test_cli_join_extract_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let a = dir.path().join("a.txt");
+    let b = dir.path().join("b.bin");
+    let a_data = b"first member, first member, first member".repeat(100);
+    let b_data: Vec<u8> = (0..300_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    fs::write(&a, &a_data).unwrap();
+    fs::write(&b, &b_data).unwrap();
+    let archive = dir.path().join("all.lz4");
+
+    let status = Command::new(lz4_bin())
+        .args(["-q", "-m", "--join"])
+        .arg(&archive)
+        .arg(&a)
+        .arg(&b)
+        .status()
+        .expect("failed to run lz4 --join");
+    assert!(status.success(), "lz4 --join should succeed");
+
+    // Plain decoding yields the concatenation; the manifest frame is skipped.
+    let output = Command::new(lz4_bin())
+        .args(["-dc"])
+        .arg(&archive)
+        .output()
+        .expect("failed to run lz4 -dc");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, [a_data.as_slice(), &b_data].concat());
+
+    let out_dir = dir.path().join("out");
+    let status = Command::new(lz4_bin())
+        .args(["-q", "--extract"])
+        .arg(&archive)
+        .arg(&out_dir)
+        .status()
+        .expect("failed to run lz4 --extract");
+    assert!(status.success(), "lz4 --extract should succeed");
+    assert_eq!(fs::read(out_dir.join("a.txt")).unwrap(), a_data);
+    assert_eq!(fs::read(out_dir.join("b.bin")).unwrap(), b_data);
+}
+
+#[test]
+fn test_cli_extract_rejects_plain_frame() {
+    let (dir, input) = make_temp_input();
+    let compressed = dir.path().join("plain.lz4");
+    let status = Command::new(lz4_bin())
+        .args(["-q"])
+        .arg(&input)
+        .arg(&compressed)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+
+    let output = Command::new(lz4_bin())
+        .args(["--extract"])
+        .arg(&compressed)
+        .arg(dir.path().join("out"))
+        .output()
+        .expect("failed to run lz4 --extract");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("not an lz4 archive"), "got {:?}", stderr);
+}
+
+// ── 13. --info quick check ────────────────────────────────────────────────────
+
+#[test]
+fn test_cli_info_checks_first_and_last_block() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("big.bin");
+    let data: Vec<u8> = (0..1_000_000u32).map(|i| (i * 13 % 241) as u8).collect();
+    fs::write(&input, &data).unwrap();
+    let compressed = dir.path().join("big.bin.lz4");
+    let status = Command::new(lz4_bin())
+        .args(["-q", "-B4", "-BX"])
+        .arg(&input)
+        .arg(&compressed)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+
+    let output = Command::new(lz4_bin())
+        .arg("--info")
+        .arg(&compressed)
+        .output()
+        .expect("failed to run lz4 --info");
+    assert!(output.status.success(), "status: {}", output.status);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Frames"),
+        "list table missing; got {:?}",
+        stdout
+    );
+    assert!(
+        stdout.contains("big.bin.lz4: OK (1 frame(s), 2 block(s) decoded, 0 skipped)"),
+        "got {:?}",
+        stdout
+    );
+
+    // Corrupt a byte near the end: the last block no longer checks out.
+    let mut bytes = fs::read(&compressed).unwrap();
+    let idx = bytes.len() - 20;
+    bytes[idx] ^= 0xFF;
+    fs::write(&compressed, &bytes).unwrap();
+    let output = Command::new(lz4_bin())
+        .arg("--info")
+        .arg(&compressed)
+        .output()
+        .expect("failed to run lz4 --info");
+    assert!(!output.status.success());
+}
+
+// ── 14. --preset archive ──────────────────────────────────────────────────────
+
+#[test]
+fn test_cli_preset_archive_header() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("doc.txt");
+    let data = b"archive me ".repeat(10_000);
+    fs::write(&input, &data).unwrap();
+    let compressed = dir.path().join("doc.txt.lz4");
+    let status = Command::new(lz4_bin())
+        .args(["-q", "--preset", "archive"])
+        .arg(&input)
+        .arg(&compressed)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+
+    let bytes = fs::read(&compressed).unwrap();
+    // FLG: version 01, independent blocks, block checksum, content size, content checksum.
+    assert_eq!(bytes[4], 0x40 | 0x20 | 0x10 | 0x08 | 0x04);
+    // BD: 256 KB maximum block size.
+    assert_eq!(bytes[5], 0x50);
+    assert_eq!(
+        u64::from_le_bytes(bytes[6..14].try_into().unwrap()),
+        data.len() as u64
+    );
+}
+
+// ── 15. Exit codes ────────────────────────────────────────────────────────────
+
+#[test]
+fn test_cli_exit_codes_distinguish_failures() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("data.bin");
+    let data: Vec<u8> = (0..200_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    fs::write(&input, &data).unwrap();
+    let compressed = dir.path().join("data.bin.lz4");
+    let status = Command::new(lz4_bin())
+        .arg("-q")
+        .arg(&input)
+        .arg(&compressed)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+    let frame = fs::read(&compressed).unwrap();
+
+    let decode = |path: &Path| {
+        Command::new(lz4_bin())
+            .args(["-q", "-t"])
+            .arg(path)
+            .status()
+            .expect("failed to run lz4")
+            .code()
+    };
+
+    // Usage error.
+    let status = Command::new(lz4_bin())
+        .arg("--no-such-option")
+        .status()
+        .expect("failed to run lz4");
+    assert_eq!(status.code(), Some(1));
+
+    // Missing input: I/O error.
+    assert_eq!(decode(&dir.path().join("missing.lz4")), Some(2));
+
+    // Truncated frame: corrupt data.
+    let truncated = dir.path().join("truncated.lz4");
+    fs::write(&truncated, &frame[..frame.len() / 2]).unwrap();
+    assert_eq!(decode(&truncated), Some(3));
+
+    // Damaged content checksum: checksum mismatch.
+    let mut bad_sum = frame.clone();
+    let last = bad_sum.len() - 1;
+    bad_sum[last] ^= 0xFF;
+    let bad_sum_path = dir.path().join("badsum.lz4");
+    fs::write(&bad_sum_path, &bad_sum).unwrap();
+    assert_eq!(decode(&bad_sum_path), Some(4));
+}
+
+// ── 16. --flush-every to stdout ───────────────────────────────────────────────
+
+#[test]
+fn test_cli_flush_every_stdout() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("log.txt");
+    let data: Vec<u8> = (0..50_000u32)
+        .flat_map(|i| format!("line {i}\n").into_bytes())
+        .collect();
+    fs::write(&input, &data).unwrap();
+    let compressed = dir.path().join("log.txt.lz4");
+    let status = Command::new(lz4_bin())
+        .arg("-q")
+        .arg(&input)
+        .arg(&compressed)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+
+    let out = Command::new(lz4_bin())
+        .args(["-d", "-c", "--flush-every", "4K"])
+        .arg(&compressed)
+        .output()
+        .expect("failed to run lz4");
+    assert!(out.status.success());
+    assert_eq!(out.stdout, data);
+}
+
+// ── 17. Automatic block size ──────────────────────────────────────────────────
+
+#[test]
+fn test_cli_auto_block_size() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("big.txt");
+    let data: Vec<u8> = (0..200_000u32)
+        .flat_map(|i| format!("row {i}\n").into_bytes())
+        .collect();
+    assert!(data.len() > 1 << 20);
+    fs::write(&input, &data).unwrap();
+
+    // Block size ID from the BD byte of the frame header.
+    let bsid = |args: &[&str]| {
+        let out = Command::new(lz4_bin())
+            .args(["-q", "-c"])
+            .args(args)
+            .arg(&input)
+            .output()
+            .expect("failed to run lz4");
+        assert!(out.status.success());
+        (out.stdout[5] >> 4) & 7
+    };
+    // Over 1 MB: 4 MB blocks at every level, as the reference `lz4` picks.
+    assert_eq!(bsid(&["-1"]), 7);
+    assert_eq!(bsid(&["-12"]), 7);
+    assert_eq!(bsid(&["-12", "-B6"]), 6);
+    assert_eq!(bsid(&["-1", "-B5"]), 5);
+}
+
+// ── 18. --files-from ──────────────────────────────────────────────────────────
+
+#[test]
+fn test_cli_files_from() {
+    let dir = TempDir::new().unwrap();
+    let names = ["a.txt", "with space.txt", "c.txt"];
+    for name in names {
+        fs::write(dir.path().join(name), format!("{name}\n").repeat(100)).unwrap();
+    }
+
+    // NUL-separated list on stdin, as `find -print0` writes it.
+    let mut child = Command::new(lz4_bin())
+        .args(["-q", "-m", "--files-from=-"])
+        .current_dir(dir.path())
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .expect("failed to run lz4");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"a.txt\0with space.txt\0")
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+    assert!(dir.path().join("a.txt.lz4").exists());
+    assert!(dir.path().join("with space.txt.lz4").exists());
+    assert!(!dir.path().join("c.txt.lz4").exists());
+
+    // Newline-separated list file, combined with a name on the command line.
+    fs::write(dir.path().join("list"), "a.txt.lz4\n\nwith space.txt.lz4\n").unwrap();
+    fs::remove_file(dir.path().join("a.txt")).unwrap();
+    let status = Command::new(lz4_bin())
+        .args(["-q", "-d", "--files-from", "list", "c.txt"])
+        .current_dir(dir.path())
+        .status()
+        .expect("failed to run lz4");
+    assert!(!status.success(), "c.txt is not an lz4 file");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+        "a.txt\n".repeat(100)
+    );
+
+    let out = Command::new(lz4_bin())
+        .args(["-q", "--files-from", "missing-list"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run lz4");
+    assert!(!out.status.success());
+}
+
+// ── 19. --no-restore-times / --touch ─────────────────────────────────────────
+
+#[test]
+fn test_cli_no_restore_times() {
+    let (dir, input) = make_temp_input();
+    let old = filetime::FileTime::from_unix_time(1_000_000_000, 0);
+    filetime::set_file_mtime(&input, old).unwrap();
+    let mtime =
+        |p: &Path| filetime::FileTime::from_last_modification_time(&fs::metadata(p).unwrap());
+
+    let restored = dir.path().join("restored.lz4");
+    let touched = dir.path().join("touched.lz4");
+    for (flag, out) in [(None, &restored), (Some("--touch"), &touched)] {
+        let mut cmd = Command::new(lz4_bin());
+        cmd.arg("-q");
+        cmd.args(flag);
+        let status = cmd
+            .arg(&input)
+            .arg(out)
+            .status()
+            .expect("failed to run lz4");
+        assert!(status.success());
+    }
+    assert_eq!(mtime(&restored), old);
+    assert!(mtime(&touched) > old);
+
+    let decoded = dir.path().join("decoded.txt");
+    let status = Command::new(lz4_bin())
+        .args(["-q", "-d", "--no-restore-times"])
+        .arg(&restored)
+        .arg(&decoded)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+    assert!(mtime(&decoded) > old);
+}
+
+// ── 20. --no-crc on decompression ─────────────────────────────────────────────
+
+#[test]
+fn test_cli_no_crc_skips_checksum_verification() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("data.bin");
+    let data: Vec<u8> = (0..200_000u32).map(|i| (i * 13 % 241) as u8).collect();
+    fs::write(&input, &data).unwrap();
+    let compressed = dir.path().join("data.bin.lz4");
+    let status = Command::new(lz4_bin())
+        .args(["-q", "-BX"])
+        .arg(&input)
+        .arg(&compressed)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+
+    // Damage the content checksum; the blocks themselves are intact.
+    let mut frame = fs::read(&compressed).unwrap();
+    let last = frame.len() - 1;
+    frame[last] ^= 0xFF;
+    fs::write(&compressed, &frame).unwrap();
+
+    let restored = dir.path().join("restored.bin");
+    let decode = |extra: &[&str]| {
+        Command::new(lz4_bin())
+            .args(["-q", "-f", "-d"])
+            .args(extra)
+            .arg(&compressed)
+            .arg(&restored)
+            .status()
+            .expect("failed to run lz4")
+    };
+    assert_eq!(decode(&[]).code(), Some(4));
+    assert!(decode(&["--no-crc"]).success());
+    assert_eq!(fs::read(&restored).unwrap(), data);
+}
+
+// ── 21. Block size downgrade notice ──────────────────────────────────────────
+
+#[test]
+fn test_cli_reports_lowered_block_size() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("small.txt");
+    fs::write(&input, b"short input ".repeat(1000)).unwrap();
+
+    let output = dir.path().join("small.txt.lz4");
+    let run = |args: &[&str]| {
+        let out = Command::new(lz4_bin())
+            .args(["-f"])
+            .args(args)
+            .arg(&input)
+            .arg(&output)
+            .output()
+            .expect("failed to run lz4");
+        assert!(out.status.success());
+        (
+            (fs::read(&output).unwrap()[5] >> 4) & 7,
+            String::from_utf8_lossy(&out.stderr).into_owned(),
+        )
+    };
+    let (bsid, stderr) = run(&["-B7"]);
+    assert_eq!(bsid, 4);
+    assert!(
+        stderr.contains("block size lowered from 4096 KB to 64 KB"),
+        "{stderr}"
+    );
+    // Automatic sizing is expected to follow the input: no notice.
+    let (_, stderr) = run(&[]);
+    assert!(!stderr.contains("block size lowered"), "{stderr}");
+    // -q silences it.
+    let (_, stderr) = run(&["-q", "-B7"]);
+    assert!(stderr.is_empty(), "{stderr}");
+    // An empty input has no block to lower.
+    fs::write(&input, b"").unwrap();
+    let (_, stderr) = run(&["-B7"]);
+    assert!(!stderr.contains("block size lowered"), "{stderr}");
+}
+
+// ── 22. -f / --force ─────────────────────────────────────────────────────────
+
+#[test]
+fn test_cli_refuses_overwrite_without_prompting_off_a_terminal() {
+    let (dir, input) = make_temp_input();
+    let output = dir.path().join("input.txt.lz4");
+    fs::write(&output, b"keep me").unwrap();
+
+    // Verbose, but stdin is not a terminal: refuse rather than prompt.
+    let out = Command::new(lz4_bin())
+        .arg("-v")
+        .arg(&input)
+        .arg(&output)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("failed to run lz4");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(!out.status.success());
+    assert!(
+        stderr.contains("already exists; not overwritten"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("do you want to overwrite"), "{stderr}");
+    assert_eq!(fs::read(&output).unwrap(), b"keep me");
+
+    // Decompression takes the same decision.
+    let restored = dir.path().join("restored.txt");
+    fs::write(&restored, b"keep me too").unwrap();
+    let status = Command::new(lz4_bin())
+        .args(["-q", "-f"])
+        .arg(&input)
+        .arg(&output)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+    let status = Command::new(lz4_bin())
+        .args(["-d", "-v"])
+        .arg(&output)
+        .arg(&restored)
+        .stdin(std::process::Stdio::null())
+        .status()
+        .expect("failed to run lz4");
+    assert!(!status.success());
+    assert_eq!(fs::read(&restored).unwrap(), b"keep me too");
+
+    for force in ["-f", "--force"] {
+        let status = Command::new(lz4_bin())
+            .args(["-d", "-q", force])
+            .arg(&output)
+            .arg(&restored)
+            .status()
+            .expect("failed to run lz4");
+        assert!(status.success(), "{force}");
+        assert_eq!(fs::read(&restored).unwrap(), fs::read(&input).unwrap());
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_streams_fifos_and_needs_force_for_other_special_files() {
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join("sock");
+    let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+    let refused = dir.path().join("sock.lz4");
+
+    let out = Command::new(lz4_bin())
+        .arg(&socket)
+        .arg(&refused)
+        .output()
+        .expect("failed to run lz4");
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("not a regular file"));
+    assert!(!refused.exists());
+
+    let fifo = dir.path().join("pipe");
+    assert!(Command::new("mkfifo")
+        .arg(&fifo)
+        .status()
+        .expect("mkfifo")
+        .success());
+    let output = dir.path().join("pipe.lz4");
+    let data = b"streamed through a fifo\n".repeat(500);
+    let writer = {
+        let (fifo, data) = (fifo.clone(), data.clone());
+        std::thread::spawn(move || fs::write(fifo, data).unwrap())
+    };
+    let status = Command::new(lz4_bin())
+        .args(["-q", "--rm"])
+        .arg(&fifo)
+        .arg(&output)
+        .status()
+        .expect("failed to run lz4");
+    writer.join().unwrap();
+    assert!(status.success());
+    assert!(fifo.exists(), "--rm must not remove a FIFO");
+
+    let decoded = Command::new(lz4_bin())
+        .args(["-d", "-c"])
+        .arg(&output)
+        .output()
+        .expect("failed to run lz4");
+    assert_eq!(decoded.stdout, data);
+}
+
+// ── 23. Reader closes stdout early ───────────────────────────────────────────
+
+/// Runs `lz4 args`, reads 16 bytes of its stdout and closes the pipe.
+#[cfg(unix)]
+fn run_with_early_close(args: &[&std::ffi::OsStr]) -> std::process::Output {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let mut child = Command::new(lz4_bin())
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run lz4");
+    let mut stdout = child.stdout.take().unwrap();
+    let mut head = [0u8; 16];
+    stdout.read_exact(&mut head).unwrap();
+    drop(stdout);
+    child.wait_with_output().unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_exits_quietly_when_stdout_is_closed() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("big.log");
+    // Several MB of output: far more than a pipe buffer holds.
+    fs::write(&input, lz4::corpus::log_lines(8 << 20, 1)).unwrap();
+    let compressed = dir.path().join("big.log.lz4");
+    assert!(Command::new(lz4_bin())
+        .args(["-q", "-f"])
+        .arg(&input)
+        .arg(&compressed)
+        .status()
+        .expect("failed to run lz4")
+        .success());
+
+    let cases: [Vec<&std::ffi::OsStr>; 3] = [
+        vec!["-dc".as_ref(), compressed.as_os_str()],
+        vec!["-c".as_ref(), input.as_os_str()],
+        vec![
+            "-dc".as_ref(),
+            "-m".as_ref(),
+            compressed.as_os_str(),
+            compressed.as_os_str(),
+        ],
+    ];
+    for args in cases {
+        let out = run_with_early_close(&args);
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(out.status.success(), "{args:?}: {:?} {stderr}", out.status);
+        assert!(stderr.is_empty(), "{args:?}: {stderr}");
+    }
+    assert!(compressed.exists(), "source must not be removed");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_list_ends_on_sigpipe_without_panicking() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let dir = TempDir::new().unwrap();
+    let compressed = dir.path().join("small.lz4");
+    fs::write(&compressed, lz4::frame::compress_frame_to_vec(b"x")).unwrap();
+    // Enough lines to fill the pipe after the reader has gone.
+    let mut args: Vec<&std::ffi::OsStr> = vec!["--list".as_ref()];
+    args.extend(std::iter::repeat_n(compressed.as_os_str(), 3000));
+    let out = run_with_early_close(&args);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert_eq!(out.status.signal(), Some(libc::SIGPIPE), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+}
+
+// ── 24. --recover ────────────────────────────────────────────────────────────
+
+#[test]
+fn test_cli_recover_skips_damaged_block() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("data.bin");
+    let data: Vec<u8> = (0..200_000u32).map(|i| (i * 13 % 241) as u8).collect();
+    fs::write(&input, &data).unwrap();
+    let compressed = dir.path().join("data.bin.lz4");
+    let status = Command::new(lz4_bin())
+        .args(["-q", "-BX", "-B4"])
+        .arg(&input)
+        .arg(&compressed)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+
+    // Flip a byte inside the second block: skip the 7-byte header and the
+    // first block (size word, data, checksum).
+    let mut frame = fs::read(&compressed).unwrap();
+    let first = u32::from_le_bytes(frame[7..11].try_into().unwrap()) & 0x7FFF_FFFF;
+    let second = 7 + 4 + first as usize + 4;
+    frame[second + 8] ^= 0xFF;
+    fs::write(&compressed, &frame).unwrap();
+
+    let restored = dir.path().join("restored.bin");
+    let decode = |extra: &[&str]| {
+        Command::new(lz4_bin())
+            .args(["-f", "-d"])
+            .args(extra)
+            .arg(&compressed)
+            .arg(&restored)
+            .output()
+            .expect("failed to run lz4")
+    };
+    assert_eq!(decode(&[]).status.code(), Some(4));
+
+    let out = decode(&["--recover", "--rm"]);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert_eq!(out.status.code(), Some(5), "{stderr}");
+    assert!(stderr.contains("damaged block skipped"), "{stderr}");
+    let recovered = fs::read(&restored).unwrap();
+    assert_eq!(recovered.len(), data.len() - 64 * 1024);
+    assert_eq!(recovered[..64 * 1024], data[..64 * 1024]);
+    assert_eq!(recovered[64 * 1024..], data[128 * 1024..]);
+    assert!(compressed.exists(), "a damaged source must not be removed");
+}
+
+// ── 25. Benchmark --synthetic ────────────────────────────────────────────────
+
+#[test]
+fn test_cli_bench_synthetic_entropy() {
+    let out = Command::new(lz4_bin())
+        .args(["-b1", "-i0", "--synthetic=0.5,64K"])
+        .output()
+        .expect("failed to run lz4");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(out.status.success(), "{stderr}");
+    assert!(stderr.contains("Entropy 0.50"), "{stderr}");
+    assert!(stderr.contains("65536 ->"), "{stderr}");
+
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("data.bin");
+    fs::write(&input, b"data").unwrap();
+    let status = Command::new(lz4_bin())
+        .args(["-b1", "-i0", "--synthetic=0.5"])
+        .arg(&input)
+        .status()
+        .expect("failed to run lz4");
+    assert!(!status.success(), "files and --synthetic are exclusive");
+}
+
+#[test]
+fn test_cli_bench_dict_report() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("records.txt");
+    let dict = dir.path().join("dict");
+    let record =
+        |i: u32| format!("{{\"id\": {i}, \"status\": \"active\", \"region\": \"eu-west\"}}\n");
+    fs::write(&dict, (0..50).map(record).collect::<String>()).unwrap();
+    fs::write(&input, (1000..1040).map(record).collect::<String>()).unwrap();
+
+    let out = Command::new(lz4_bin())
+        .args(["-b1", "-e2", "-i0", "--dict-report", "-D"])
+        .arg(&dict)
+        .arg(&input)
+        .output()
+        .expect("failed to run lz4");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(out.status.success(), "{stderr}");
+    assert!(stderr.contains("Dictionary benefit"), "{stderr}");
+    for level in [1, 2] {
+        let line = stderr
+            .lines()
+            .find(|l| l.starts_with(&format!("level {level:>2} : ratio")))
+            .unwrap_or_else(|| panic!("no level {level} line: {stderr}"));
+        assert!(
+            line.contains(" without, ") && line.contains(" with (") && line.contains("% size)"),
+            "{line}"
+        );
+    }
+
+    let status = Command::new(lz4_bin())
+        .args(["-b1", "-i0", "--dict-report"])
+        .arg(&input)
+        .status()
+        .expect("failed to run lz4");
+    assert!(!status.success(), "--dict-report needs a dictionary");
+}
+
+// ── 26. Config file ──────────────────────────────────────────────────────────
+
+#[test]
+fn test_cli_config_file_defaults() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("data.txt");
+    let data = b"config file defaults ".repeat(5000);
+    fs::write(&input, &data).unwrap();
+    fs::write(dir.path().join(".lz4rc"), "# defaults\n-9 --content-size\n").unwrap();
+    let compress = |extra: &[&str]| {
+        let out = dir.path().join("data.txt.lz4");
+        let status = Command::new(lz4_bin())
+            .env("HOME", dir.path())
+            .args(["-q", "-f"])
+            .args(extra)
+            .arg(&input)
+            .arg(&out)
+            .status()
+            .expect("failed to run lz4");
+        assert!(status.success());
+        fs::read(&out).unwrap()
+    };
+
+    // --content-size from ~/.lz4rc sets the FLG content-size bit.
+    let from_rc = compress(&[]);
+    assert_ne!(from_rc[4] & 0x08, 0);
+    assert_eq!(compress(&["-9", "--content-size"]), from_rc);
+    // Arguments override the file, and --no-config ignores it.
+    assert_eq!(compress(&["--no-content-size"])[4] & 0x08, 0);
+    assert_eq!(compress(&["--no-config"])[4] & 0x08, 0);
+
+    // --config names another file.
+    let other = dir.path().join("other.rc");
+    fs::write(&other, "--no-frame-crc\n").unwrap();
+    let frame = compress(&["--config", other.to_str().unwrap()]);
+    assert_eq!(frame[4] & 0x0C, 0, "no content size, no content checksum");
+}
+
+// ── 27. Oversized dictionary warning ─────────────────────────────────────────
+
+#[test]
+fn test_cli_warns_about_truncated_dictionary() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("data.txt");
+    fs::write(&input, b"dictionary warning ".repeat(2000)).unwrap();
+    let small = dir.path().join("small.dict");
+    fs::write(&small, b"dictionary warning ".repeat(100)).unwrap();
+    let big = dir.path().join("big.dict");
+    fs::write(&big, vec![b'd'; 200_000]).unwrap();
+
+    let run = |args: &[&str]| {
+        let out = Command::new(lz4_bin())
+            .arg("-f")
+            .args(args)
+            .output()
+            .expect("failed to run lz4");
+        assert!(out.status.success(), "{args:?}");
+        String::from_utf8_lossy(&out.stderr).into_owned()
+    };
+    let compressed = dir.path().join("data.txt.lz4");
+    let (input, compressed, big_s) = (
+        input.to_str().unwrap(),
+        compressed.to_str().unwrap(),
+        big.to_str().unwrap(),
+    );
+
+    let stderr = run(&["-D", big_s, input, compressed]);
+    assert!(
+        stderr.contains("dictionary is 200000 bytes; only its last 65536 bytes are used"),
+        "{stderr}"
+    );
+    let restored = dir.path().join("restored.txt");
+    let stderr = run(&["-d", "-D", big_s, compressed, restored.to_str().unwrap()]);
+    assert!(stderr.contains("only its last 65536 bytes"), "{stderr}");
+    assert_eq!(fs::read(&restored).unwrap(), fs::read(input).unwrap());
+
+    let stderr = run(&["-D", small.to_str().unwrap(), input, compressed]);
+    assert!(!stderr.contains("dictionary is"), "{stderr}");
+    let stderr = run(&["-q", "-D", big_s, input, compressed]);
+    assert!(stderr.is_empty(), "{stderr}");
+}
+
+// ── 28. Atomic output ────────────────────────────────────────────────────────
+
+#[test]
+fn test_cli_failed_decompression_leaves_no_partial_output() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("data.bin");
+    let data: Vec<u8> = (0..400_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 7) as u8)
+        .collect();
+    fs::write(&input, &data).unwrap();
+    let frame = dir.path().join("data.bin.lz4");
+    let status = Command::new(lz4_bin())
+        .args(["-q", "-f", "-B4"])
+        .arg(&input)
+        .arg(&frame)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+    // Cut the frame in the middle of its blocks.
+    let compressed = fs::read(&frame).unwrap();
+    fs::write(&frame, &compressed[..compressed.len() / 2]).unwrap();
+
+    let out = dir.path().join("restored.bin");
+    let decompress = |extra: &[&str]| {
+        fs::write(&out, b"previous output").unwrap();
+        let status = Command::new(lz4_bin())
+            .args(["-q", "-d", "-f"])
+            .args(extra)
+            .arg(&frame)
+            .arg(&out)
+            .status()
+            .expect("failed to run lz4");
+        assert!(!status.success(), "a truncated frame must fail");
+        fs::read(&out).unwrap()
+    };
+
+    // The previous output survives and no temporary file is left behind.
+    assert_eq!(decompress(&[]), b"previous output");
+    let mut names: Vec<_> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["data.bin", "data.bin.lz4", "restored.bin"]);
+
+    // --no-atomic writes in place, so the decoded prefix is there.
+    let partial = decompress(&["--no-atomic"]);
+    assert!(!partial.is_empty() && partial.len() < data.len());
+    assert_eq!(partial, &data[..partial.len()]);
+}
+
+// ── 29. Progress stays on stderr ─────────────────────────────────────────────
+
+#[test]
+fn test_cli_progress_never_reaches_stdout() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("data.txt");
+    let data: Vec<u8> = (0..1_500_000u32)
+        .map(|i| b'a' + (i.wrapping_mul(2_654_435_761) >> 28) as u8)
+        .collect();
+    fs::write(&input, &data).unwrap();
+
+    let compress = |extra: &[&str]| {
+        let out = Command::new(lz4_bin())
+            .args(["-c", "-B4"])
+            .args(extra)
+            .arg(&input)
+            .output()
+            .expect("failed to run lz4");
+        assert!(out.status.success());
+        (
+            out.stdout,
+            String::from_utf8_lossy(&out.stderr).into_owned(),
+        )
+    };
+    let decompress = |frame: &[u8], extra: &[&str]| {
+        let mut child = Command::new(lz4_bin())
+            .args(["-d", "-c"])
+            .args(extra)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .expect("failed to run lz4");
+        let mut stdin = child.stdin.take().unwrap();
+        let frame = frame.to_vec();
+        let writer = std::thread::spawn(move || stdin.write_all(&frame));
+        let out = child.wait_with_output().unwrap();
+        writer.join().unwrap().unwrap();
+        assert!(out.status.success());
+        (
+            out.stdout,
+            String::from_utf8_lossy(&out.stderr).into_owned(),
+        )
+    };
+
+    // Verbose progress and --progress go to stderr; stdout is the frame.
+    for extra in [&["-v"][..], &["--progress"][..]] {
+        let (frame, stderr) = compress(extra);
+        assert!(stderr.contains("Read : "), "{extra:?}: {stderr}");
+        let (restored, _) = decompress(&frame, &[]);
+        assert_eq!(restored, data, "{extra:?}");
+    }
+
+    let (frame, stderr) = compress(&["-v"]);
+    let (restored, dstderr) = decompress(&frame, &["-v"]);
+    assert_eq!(restored, data);
+    assert!(dstderr.contains("Decompressed : "), "{dstderr}");
+    assert!(stderr.contains("Compressed 1500000 bytes"), "{stderr}");
+
+    // --quiet-progress drops the progress lines but keeps the summaries.
+    let (quiet_frame, stderr) = compress(&["-v", "--quiet-progress"]);
+    assert_eq!(quiet_frame, frame);
+    assert!(!stderr.contains("Read : "), "{stderr}");
+    assert!(stderr.contains("Compressed 1500000 bytes"), "{stderr}");
+    let (_, dstderr) = decompress(&frame, &["-v", "--quiet-progress"]);
+    assert!(!dstderr.contains("Decompressed : "), "{dstderr}");
+    assert!(dstderr.contains("decoded 1500000 bytes"), "{dstderr}");
+    let (_, stderr) = compress(&["--progress", "--quiet-progress"]);
+    assert!(!stderr.contains("Read : "), "{stderr}");
+}
+
+// ── 30. gzip-compatible defaults ─────────────────────────────────────────────
+
+#[test]
+fn test_cli_gzip_compat_removes_sources_and_refuses_overwrite() {
+    let (dir, input) = make_temp_input();
+    let data = fs::read(&input).unwrap();
+    let packed = dir.path().join("input.txt.lz4");
+    let run = |args: &[&str], file: &Path| {
+        Command::new(lz4_bin())
+            .args(args)
+            .arg(file)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .expect("failed to run lz4")
+    };
+
+    // The source goes away once compression succeeds.
+    assert!(run(&["-q", "--gzip-compat"], &input).status.success());
+    assert!(!input.exists());
+    assert!(packed.exists());
+
+    // An existing output is refused without -f, and the source stays.
+    fs::write(&input, &data).unwrap();
+    let out = run(&["-q", "--gzip-compat"], &input);
+    assert!(!out.status.success());
+    assert!(input.exists());
+    assert!(run(&["-q", "--gzip-compat", "-f"], &input).status.success());
+    assert!(!input.exists());
+
+    // -c keeps the source, as `gzip -c` does.
+    let out = run(&["-q", "--gzip-compat", "-d", "-c"], &packed);
+    assert!(out.status.success());
+    assert_eq!(out.stdout, data);
+    assert!(packed.exists());
+
+    // -k wins over the profile; decompression removes the frame otherwise.
+    assert!(run(&["-q", "--gzip-compat", "-d", "-k"], &packed)
+        .status
+        .success());
+    assert!(packed.exists());
+    fs::remove_file(&input).unwrap();
+    assert!(run(&["-q", "--gzip-compat", "-d"], &packed)
+        .status
+        .success());
+    assert!(!packed.exists());
+    assert_eq!(fs::read(&input).unwrap(), data);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_lz4gz_alias_uses_gzip_compat() {
+    let (dir, input) = make_temp_input();
+    let alias = dir.path().join("lz4gz");
+    std::os::unix::fs::symlink(lz4_bin(), &alias).unwrap();
+    let status = Command::new(&alias)
+        .arg("-q")
+        .arg(&input)
+        .status()
+        .expect("failed to run lz4gz");
+    assert!(status.success());
+    assert!(!input.exists());
+    assert!(dir.path().join("input.txt.lz4").exists());
+}
+
+// ── 31. Conformance vectors ──────────────────────────────────────────────────
+
+#[test]
+fn test_cli_self_test_writes_and_checks_vectors() {
+    let dir = TempDir::new().unwrap();
+    let vectors = dir.path().join("vectors");
+
+    let out = Command::new(lz4_bin())
+        .arg("--self-test")
+        .output()
+        .expect("failed to run lz4");
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("256 vectors passed"));
+
+    let status = Command::new(lz4_bin())
+        .args(["-q", "--self-test", "--output-dir"])
+        .arg(&vectors)
+        .status()
+        .expect("failed to run lz4");
+    assert!(status.success());
+    assert!(vectors
+        .join("b7-indep-bc1-cc1-sz1-dict1-skip1.lz4")
+        .exists());
+
+    fs::write(
+        vectors.join("b4-linked-bc0-cc0-sz0-dict0-skip0.lz4"),
+        b"junk",
+    )
+    .unwrap();
+    let out = Command::new(lz4_bin())
+        .arg("--self-test")
+        .arg(&vectors)
+        .output()
+        .expect("failed to run lz4");
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("b4-linked-bc0-cc0-sz0-dict0-skip0"));
+}
+
+// ── 32. Incremental compression ──────────────────────────────────────────────
+
+#[test]
+fn test_cli_mtime_window_skips_up_to_date_outputs() {
+    let dir = TempDir::new().unwrap();
+    let a = dir.path().join("a.txt");
+    let b = dir.path().join("b.txt");
+    fs::write(&a, "alpha ".repeat(500)).unwrap();
+    fs::write(&b, "bravo ".repeat(500)).unwrap();
+    let run = || {
+        Command::new(lz4_bin())
+            .args(["-q", "-f", "-m", "--mtime-window", "0"])
+            .arg(&a)
+            .arg(&b)
+            .status()
+            .expect("failed to run lz4")
+    };
+    assert!(run().success());
+
+    // Mark a.txt.lz4 with a later mtime: a rebuild would copy a.txt's back.
+    let a_lz4 = dir.path().join("a.txt.lz4");
+    let src_mtime = filetime::FileTime::from_last_modification_time(&fs::metadata(&a).unwrap());
+    let marker = filetime::FileTime::from_unix_time(src_mtime.unix_seconds() + 100, 0);
+    filetime::set_file_mtime(&a_lz4, marker).unwrap();
+    // Change b.txt's size but keep its mtime, so only the size gives it away.
+    let b_mtime = filetime::FileTime::from_last_modification_time(&fs::metadata(&b).unwrap());
+    fs::write(&b, "bravo! ".repeat(500)).unwrap();
+    filetime::set_file_mtime(&b, b_mtime).unwrap();
+
+    assert!(run().success());
+    let a_lz4_mtime =
+        filetime::FileTime::from_last_modification_time(&fs::metadata(&a_lz4).unwrap());
+    assert_eq!(a_lz4_mtime, marker, "up-to-date output was rewritten");
+
+    let out = Command::new(lz4_bin())
+        .args(["-d", "-c"])
+        .arg(dir.path().join("b.txt.lz4"))
+        .output()
+        .expect("failed to run lz4");
+    assert!(out.status.success());
+    assert_eq!(out.stdout, "bravo! ".repeat(500).into_bytes());
+}
+
+// ── 33. Environment defaults ─────────────────────────────────────────────────
+
+#[test]
+fn test_cli_env_compression_level() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("data.txt");
+    fs::write(&input, b"environment level defaults ".repeat(5000)).unwrap();
+    let compress = |clevel: &str, args: &[&str]| {
+        let mut cmd = Command::new(lz4_bin());
+        match clevel {
+            "" => cmd.env_remove("LZ4_CLEVEL"),
+            _ => cmd.env("LZ4_CLEVEL", clevel),
+        };
+        let output = cmd
+            .env("HOME", dir.path())
+            .args(["-c"])
+            .args(args)
+            .arg(&input)
+            .output()
+            .expect("failed to run lz4");
+        assert!(output.status.success());
+        (
+            output.stdout,
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        )
+    };
+    let level = |n: &str| compress("", &[n]).0;
+
+    assert_eq!(compress("9", &[]).0, level("-9"));
+    // Arguments, then ~/.lz4rc, override the variable.
+    assert_eq!(compress("9", &["-3"]).0, level("-3"));
+    fs::write(dir.path().join(".lz4rc"), "-2\n").unwrap();
+    assert_eq!(compress("9", &[]).0, level("-2"));
+    fs::remove_file(dir.path().join(".lz4rc")).unwrap();
+
+    // A value with trailing text is ignored with a warning.
+    let (frame, stderr) = compress("9x", &[]);
+    assert_eq!(frame, level("-1"));
+    assert!(stderr.contains("LZ4_CLEVEL=9x"), "stderr: {stderr}");
+}
@@ -9,6 +9,8 @@
 //! - Edge cases (empty buffers, zero acceleration, max input size validation)
 //! - Partial decompression edge cases
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 use lz4::frame::decompress::Lz4FDCtx;
 use lz4::frame::types::{Lz4FError, LZ4F_VERSION};
 use lz4::{
@@ -155,6 +157,7 @@ fn test_compress_fast_zero_acceleration() {
         Err(Lz4Error::OutputTooSmall) | Err(Lz4Error::InputTooLarge) => {
             // Also acceptable error types
         }
+        Err(Lz4Error::InvalidMaxDistance) => unreachable!("no window was configured"),
     }
 
     // Key assertion: should not panic
@@ -6,6 +6,8 @@
 //! Tests use in-memory `std::io::Cursor<Vec<u8>>` for deterministic I/O without
 //! requiring actual filesystem access.
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 use lz4::file::{lz4_read_frame, lz4_write_frame, Lz4ReadFile, Lz4WriteFile};
 use std::io::{Cursor, Read, Write};
 
@@ -201,10 +203,7 @@ impl Write for FailingWriter {
         }
 
         // Fail all subsequent writes
-        Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "intentional write failure",
-        ))
+        Err(std::io::Error::other("intentional write failure"))
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
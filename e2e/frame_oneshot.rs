@@ -11,6 +11,8 @@
 //! These tests verify that the Rust port correctly implements the LZ4 Frame
 //! format specification (one-shot API).
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 extern crate lz4;
 
 use lz4::frame::{
@@ -14,6 +14,8 @@
 //! These tests verify that the Rust port correctly implements the LZ4 Frame
 //! streaming API for both compression and decompression.
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 extern crate lz4;
 
 use lz4::frame::header::lz4f_compress_frame_bound;
@@ -445,7 +447,7 @@ fn test_context_create_drop() {
     // Create decompression context
     let dctx = lz4f_create_decompression_context(100).expect("create dctx");
     // Use it minimally
-    let _version = dctx.version;
+    let _version = dctx.version();
     // Drop happens automatically (RAII)
     drop(dctx);
 
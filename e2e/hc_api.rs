@@ -4,6 +4,8 @@
 //! These tests validate that HC-compressed data can be decompressed correctly
 //! using the standard block decompression API.
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 use lz4::hc::{
     compress_hc, compress_hc_continue, reset_stream_hc, Lz4StreamHc, LZ4HC_CLEVEL_DEFAULT,
     LZ4HC_CLEVEL_MAX, LZ4HC_CLEVEL_MIN,
@@ -38,6 +38,7 @@ fn system_lz4() -> Option<String> {
 }
 
 /// Path to our Rust `lz4` binary (set by Cargo at compile time).
+#[cfg(all(feature = "cli", not(feature = "forbid-unsafe")))]
 fn rust_lz4() -> &'static str {
     env!("CARGO_BIN_EXE_lz4")
 }
@@ -151,6 +152,7 @@ fn c_frame_compress_rust_decompress() {
 // ─────────────────────────────────────────────────────────────────────────────
 
 #[test]
+#[cfg(all(feature = "cli", not(feature = "forbid-unsafe")))]
 fn rust_cli_compress_c_decompress() {
     let lz4_bin = match system_lz4() {
         Some(p) => p,
@@ -202,6 +204,7 @@ fn rust_cli_compress_c_decompress() {
 // ─────────────────────────────────────────────────────────────────────────────
 
 #[test]
+#[cfg(all(feature = "cli", not(feature = "forbid-unsafe")))]
 fn c_compress_rust_cli_decompress() {
     let lz4_bin = match system_lz4() {
         Some(p) => p,
@@ -4,6 +4,8 @@
 //! functions using real temp files.  Corresponds to `LZ4IO_compressFilename`
 //! / `LZ4IO_decompressFilename` in the original C lz4 codebase.
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 use lz4::io::prefs::{set_notification_level, Prefs};
 use lz4::io::{
     compress_filename, compress_multiple_filenames, decompress_filename, LEGACY_MAGICNUMBER,
@@ -115,7 +117,7 @@ fn test_compress_high_level() {
     let out = dir.path().join("repetitive_out.bin");
 
     // 32 KB of highly repetitive data — ideal for compression.
-    let original: Vec<u8> = std::iter::repeat(b'A').take(32 * 1024).collect();
+    let original: Vec<u8> = std::iter::repeat_n(b'A', 32 * 1024).collect();
     fs::write(&src, &original).unwrap();
 
     let prefs = silent_prefs();
@@ -336,3 +338,76 @@ fn test_set_notification_level_zero() {
     assert_eq!(returned, 0);
     assert_eq!(DISPLAY_LEVEL.load(Ordering::Relaxed), 0);
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Test 11: Independent-block frames decode identically with several workers
+// Validates: parallel block decode with per-block and content checksums
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_decompress_multithread_independent_blocks() {
+    let dir = TempDir::new().unwrap();
+    let src = dir.path().join("data.bin");
+    let lz4 = dir.path().join("data.bin.lz4");
+    let original = lz4::lorem::gen_buffer(3 * 1024 * 1024, 11);
+    fs::write(&src, &original).unwrap();
+
+    set_notification_level(0);
+    let prefs = Prefs::builder()
+        .block_size_id(4)
+        .block_mode(lz4::io::BlockMode::Independent)
+        .block_checksum(true)
+        .stream_checksum(true)
+        .content_size(true)
+        .build();
+    compress_filename(src.to_str().unwrap(), lz4.to_str().unwrap(), 1, &prefs)
+        .expect("compress should succeed");
+
+    for workers in [1, 4] {
+        let out = dir.path().join(format!("out{workers}.bin"));
+        let prefs = Prefs::builder().nb_workers(workers).build();
+        let stats = decompress_filename(lz4.to_str().unwrap(), out.to_str().unwrap(), &prefs)
+            .expect("decompress should succeed");
+        assert_eq!(stats.decompressed_bytes, original.len() as u64);
+        assert_eq!(fs::read(&out).unwrap(), original, "{workers} workers");
+    }
+
+    // A flipped payload byte is caught by a worker's block checksum.
+    let mut corrupt = fs::read(&lz4).unwrap();
+    let mid = corrupt.len() / 2;
+    corrupt[mid] ^= 0x01;
+    fs::write(&lz4, &corrupt).unwrap();
+    let prefs = Prefs::builder().nb_workers(4).build();
+    let out = dir.path().join("bad.bin");
+    assert!(decompress_filename(lz4.to_str().unwrap(), out.to_str().unwrap(), &prefs).is_err());
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Test 12: Pseudo-files that stat as empty are compressed as streams
+// Validates: /proc inputs are read to EOF; no bogus content size is declared
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_compress_proc_pseudo_file() {
+    let dir = TempDir::new().unwrap();
+    let lz4 = dir.path().join("maps.lz4");
+    let out = dir.path().join("maps");
+    assert_eq!(fs::metadata("/proc/self/maps").unwrap().len(), 0);
+
+    set_notification_level(0);
+    let prefs = Prefs::builder().block_size_id(4).content_size(true).build();
+    let stats = compress_filename("/proc/self/maps", lz4.to_str().unwrap(), 1, &prefs)
+        .expect("compressing a /proc file should succeed");
+    assert!(stats.bytes_in > 0, "the pseudo-file must be read to EOF");
+
+    let d_stats = decompress_filename(lz4.to_str().unwrap(), out.to_str().unwrap(), &prefs)
+        .expect("decompress should succeed");
+    assert_eq!(d_stats.decompressed_bytes, stats.bytes_in);
+    let text = fs::read_to_string(&out).unwrap();
+    assert!(
+        text.contains("[stack]"),
+        "got {:?}",
+        &text[..text.len().min(200)]
+    );
+}
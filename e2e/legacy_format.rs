@@ -4,6 +4,8 @@
 //! and magic-number auto-detection in `decompress_filename`.
 //! Legacy format uses magic number `0x184C2102`.
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 use lz4::io::prefs::{set_notification_level, Prefs};
 use lz4::io::{
     compress_filename, compress_filename_legacy, compress_multiple_filenames_legacy,
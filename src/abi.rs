@@ -5,6 +5,14 @@
 //!
 //! The produced `target/release/liblz4.a` can replace `liblz4.o + liblz4hc.o`
 //! in the lzbench link step via the `Makefile.rust` override.
+//!
+//! With `verify`, the reference C library is linked too and owns the C names,
+//! so the shims are exported with an `lz4r_` prefix instead
+//! (`lz4r_LZ4_compress_default`, …).
+
+// The Rust names stay the C names, and each shim follows its C function's
+// pointer contract; these lints are silent under `no_mangle` only.
+#![allow(non_snake_case, clippy::missing_safety_doc)]
 
 use std::os::raw::{c_char, c_int};
 use std::slice;
@@ -33,7 +41,8 @@ fn ok_or_zero(n: i32) -> c_int {
 //
 // Returns number of bytes written to dst, or 0 on failure.
 // ─────────────────────────────────────────────────────────────────────────────
-#[no_mangle]
+#[cfg_attr(not(feature = "verify"), no_mangle)]
+#[cfg_attr(feature = "verify", export_name = "lz4r_LZ4_compress_default")]
 pub unsafe extern "C" fn LZ4_compress_default(
     src: *const c_char,
     dst: *mut c_char,
@@ -59,7 +68,8 @@ pub unsafe extern "C" fn LZ4_compress_default(
 //
 // Returns number of bytes written to dst, or 0 on failure.
 // ─────────────────────────────────────────────────────────────────────────────
-#[no_mangle]
+#[cfg_attr(not(feature = "verify"), no_mangle)]
+#[cfg_attr(feature = "verify", export_name = "lz4r_LZ4_compress_fast")]
 pub unsafe extern "C" fn LZ4_compress_fast(
     src: *const c_char,
     dst: *mut c_char,
@@ -88,7 +98,8 @@ pub unsafe extern "C" fn LZ4_compress_fast(
 //
 // Returns number of bytes written to dst, or a negative value on error.
 // ─────────────────────────────────────────────────────────────────────────────
-#[no_mangle]
+#[cfg_attr(not(feature = "verify"), no_mangle)]
+#[cfg_attr(feature = "verify", export_name = "lz4r_LZ4_decompress_safe")]
 pub unsafe extern "C" fn LZ4_decompress_safe(
     src: *const c_char,
     dst: *mut c_char,
@@ -115,7 +126,8 @@ pub unsafe extern "C" fn LZ4_decompress_safe(
 //
 // Returns number of bytes written to dst, or 0 on failure.
 // ─────────────────────────────────────────────────────────────────────────────
-#[no_mangle]
+#[cfg_attr(not(feature = "verify"), no_mangle)]
+#[cfg_attr(feature = "verify", export_name = "lz4r_LZ4_compress_HC")]
 pub unsafe extern "C" fn LZ4_compress_HC(
     src: *const c_char,
     dst: *mut c_char,
@@ -24,6 +24,7 @@ use super::config::{
     BenchConfig, ACTIVEPERIOD_NANOSEC, COOLPERIOD_SEC, DECOMP_MULT, MB, TIMELOOP_NANOSEC,
 };
 use super::decompress_binding::{decompress_frame_block, FrameDecompressor};
+use super::energy::{EnergyMeter, PhaseEnergy};
 
 use crate::block::{compress_bound, decompress_safe_using_dict};
 
@@ -80,6 +81,13 @@ pub struct BenchResult {
     pub decompress_speed_mb_s: f64,
     /// Compression level used.
     pub c_level: i32,
+    /// CPU package energy per GB of input compressed, from RAPL counters.
+    /// `None` unless built with the `energy` feature on Linux and the
+    /// counters are readable (see [`crate::bench::energy`]).
+    pub compress_joules_per_gb: Option<f64>,
+    /// CPU package energy per GB of output decompressed; see
+    /// `compress_joules_per_gb`.
+    pub decompress_joules_per_gb: Option<f64>,
 }
 
 // ── bench_mem ─────────────────────────────────────────────────────────────────
@@ -96,15 +104,16 @@ pub struct BenchResult {
 /// - `display_name`   — label printed in the progress line (≤17 chars displayed).
 /// - `config`         — runtime benchmark parameters.
 /// - `c_level`        — compression level (for display only; `strategy` was
-///                      already constructed from this level by the caller).
+///   already constructed from this level by the caller).
 /// - `strategy`       — mutable compression strategy (owns any stream state).
 /// - `decompressor`   — frame decompressor used in `decode_only` mode.
 /// - `dict`           — optional dictionary bytes; empty slice means no dict.
 /// - `file_sizes`     — per-file byte counts within `src`.  An empty slice
-///                      means treat the entire `src` as a single file.
+///   means treat the entire `src` as a single file.
 ///
 /// # Returns
 /// `Ok(BenchResult)` on success, or `Err` if compression or checksum verification fails.
+#[allow(clippy::too_many_arguments)]
 pub fn bench_mem(
     src: &[u8],
     display_name: &str,
@@ -278,6 +287,11 @@ pub fn bench_mem(
 
     let mut bench_error = false;
 
+    // RAPL counters are sampled around each timed loop when available.
+    let energy_meter = EnergyMeter::open();
+    let mut c_energy = PhaseEnergy::default();
+    let mut d_energy = PhaseEnergy::default();
+
     while !c_completed || !d_completed {
         // ── overheat protection ────────────────────────────────────────────────
         // If the active measurement period exceeds the threshold, sleep briefly
@@ -314,6 +328,8 @@ pub fn bench_mem(
         std::thread::sleep(Duration::from_millis(1));
 
         if !c_completed {
+            let loops = nb_compression_loops;
+            let energy_span = energy_meter.as_ref().and_then(EnergyMeter::start);
             let time_start = Instant::now();
 
             'compress_outer: for _ in 0..nb_compression_loops {
@@ -341,6 +357,9 @@ pub fn bench_mem(
             }
 
             let duration_ns = time_start.elapsed().as_nanos() as u64;
+            if let Some(joules) = energy_span.and_then(|span| span.finish()) {
+                c_energy.add(joules, src_size as u64 * loops as u64);
+            }
             if duration_ns > 0 {
                 let per_loop = duration_ns / nb_compression_loops as u64;
                 if per_loop < fastest_c_ns {
@@ -394,6 +413,8 @@ pub fn bench_mem(
         std::thread::sleep(Duration::from_millis(1));
 
         if !d_completed {
+            let loops = nb_decode_loops;
+            let energy_span = energy_meter.as_ref().and_then(EnergyMeter::start);
             let time_start = Instant::now();
 
             'decode_outer: for _ in 0..nb_decode_loops {
@@ -463,6 +484,10 @@ pub fn bench_mem(
             }
 
             let duration_ns = time_start.elapsed().as_nanos() as u64;
+            if let Some(joules) = energy_span.and_then(|span| span.finish()) {
+                let decoded: usize = block_table.iter().map(|b| b.res_size).sum();
+                d_energy.add(joules, decoded as u64 * loops as u64);
+            }
             if duration_ns > 0 {
                 let per_loop = duration_ns / nb_decode_loops as u64;
                 if per_loop < fastest_d_ns {
@@ -558,8 +583,22 @@ pub fn bench_mem(
         0.0
     };
 
+    let compress_joules_per_gb = c_energy.joules_per_gb();
+    let decompress_joules_per_gb = d_energy.joules_per_gb();
+    let energy_note = match (compress_joules_per_gb, decompress_joules_per_gb) {
+        (None, None) => None,
+        (c, d) => Some(format!(
+            "{} J/GB, {} J/GB",
+            c.map_or("-".to_owned(), |j| format!("{j:.1}")),
+            d.map_or("-".to_owned(), |j| format!("{j:.1}")),
+        )),
+    };
+
     if config.display_level >= 2 {
         eprintln!("{:2}#", c_level);
+        if let Some(note) = &energy_note {
+            eprintln!("    energy : {note}");
+        }
     }
 
     // Quiet mode: print a single summary line without a progress spinner.
@@ -571,6 +610,9 @@ pub fn bench_mem(
         if config.additional_param != 0 {
             print!(" (param={})", config.additional_param);
         }
+        if let Some(note) = &energy_note {
+            print!("  ({note})");
+        }
         println!();
     }
 
@@ -587,6 +629,8 @@ pub fn bench_mem(
         compress_speed_mb_s,
         decompress_speed_mb_s,
         c_level,
+        compress_joules_per_gb,
+        decompress_joules_per_gb,
     })
 }
 
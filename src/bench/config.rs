@@ -34,8 +34,11 @@ pub const KB: usize = 1 << 10;
 pub const MB: usize = 1 << 20;
 pub const GB: usize = 1 << 30;
 
+/// Size of the generated buffer benchmarked when no file is given (10 MB).
+pub const SYNTHETIC_SIZE: usize = 10_000_000;
+
 /// Maximum dictionary size accepted by LZ4 (64 KiB).
-pub const LZ4_MAX_DICT_SIZE: usize = 64 * KB;
+pub const LZ4_MAX_DICT_SIZE: usize = crate::dict::MAX_DICT_SIZE;
 
 /// Maximum memory the benchmark will attempt to allocate.
 ///
@@ -82,6 +85,27 @@ pub struct BenchConfig {
     /// When `true`, skip checksum verification during decode-only benchmarking
     /// to isolate pure decompression throughput. Default: `false`.
     pub skip_checksums: bool,
+
+    /// Dictionary sizes, in bytes, to compare against the full dictionary.
+    /// For each size the corpus is benchmarked again with only that many
+    /// trailing bytes of the dictionary, and the ratio change per level is
+    /// reported. Requires a dictionary; empty disables the sweep (default).
+    pub dict_size_sweep: Vec<usize>,
+
+    /// When `true`, the corpus is benchmarked once without and once with the
+    /// dictionary, and the ratios of both runs are reported side by side per
+    /// level. Requires a dictionary. Default: `false`.
+    pub dict_report: bool,
+
+    /// Entropy of the generated data benchmarked when no file is given, from
+    /// 0.0 (highly compressible) to 1.0 (incompressible); see
+    /// [`corpus::compressible`](crate::corpus::compressible). `None` uses
+    /// lorem ipsum text (default).
+    pub synthetic_entropy: Option<f64>,
+
+    /// Size in bytes of the generated data benchmarked when no file is given.
+    /// Default: [`SYNTHETIC_SIZE`].
+    pub synthetic_size: usize,
 }
 
 impl Default for BenchConfig {
@@ -93,6 +117,10 @@ impl Default for BenchConfig {
     /// - `bench_separately` = false
     /// - `decode_only`   = false
     /// - `skip_checksums` = false
+    /// - `dict_size_sweep` = empty (no sweep)
+    /// - `dict_report`   = false
+    /// - `synthetic_entropy` = `None` (lorem ipsum)
+    /// - `synthetic_size` = 10 MB ([`SYNTHETIC_SIZE`])
     fn default() -> Self {
         BenchConfig {
             display_level: 2,
@@ -102,6 +130,10 @@ impl Default for BenchConfig {
             bench_separately: false,
             decode_only: false,
             skip_checksums: false,
+            dict_size_sweep: Vec::new(),
+            dict_report: false,
+            synthetic_entropy: None,
+            synthetic_size: SYNTHETIC_SIZE,
         }
     }
 }
@@ -167,6 +199,36 @@ impl BenchConfig {
         self.skip_checksums = skip;
         self
     }
+
+    /// Set the dictionary sizes to sweep (see [`BenchConfig::dict_size_sweep`]).
+    ///
+    /// Sizes above the loaded dictionary are clamped to its length; `0`
+    /// benchmarks without a dictionary.
+    pub fn set_dict_size_sweep(&mut self, sizes: Vec<usize>) -> &mut Self {
+        self.dict_size_sweep = sizes;
+        self
+    }
+
+    /// Set whether to report the dictionary's benefit (see
+    /// [`BenchConfig::dict_report`]).
+    pub fn set_dict_report(&mut self, enabled: bool) -> &mut Self {
+        self.dict_report = enabled;
+        self
+    }
+
+    /// Benchmark generated data of the given entropy instead of lorem ipsum
+    /// when no file is given (see [`BenchConfig::synthetic_entropy`]).
+    /// Values outside `0.0..=1.0` are clamped.
+    pub fn set_synthetic_entropy(&mut self, entropy: Option<f64>) -> &mut Self {
+        self.synthetic_entropy = entropy.map(|e| e.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Set the size of the generated benchmark data.
+    pub fn set_synthetic_size(&mut self, size: usize) -> &mut Self {
+        self.synthetic_size = size;
+        self
+    }
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
@@ -208,7 +270,7 @@ mod tests {
     fn setter_chain() {
         let mut cfg = BenchConfig::default();
         cfg.set_nb_seconds(5)
-            .set_block_size(1 * MB)
+            .set_block_size(MB)
             .set_decode_only(true)
             .set_skip_checksums(true);
         assert_eq!(cfg.nb_seconds, 5);
@@ -224,6 +286,34 @@ mod tests {
         assert!(!cfg.bench_separately);
     }
 
+    #[test]
+    fn setter_dict_size_sweep() {
+        let mut cfg = BenchConfig::default();
+        assert!(cfg.dict_size_sweep.is_empty());
+        cfg.set_dict_size_sweep(vec![4 * KB, 16 * KB, 64 * KB]);
+        assert_eq!(cfg.dict_size_sweep, [4096, 16384, 65536]);
+    }
+
+    #[test]
+    fn setter_dict_report() {
+        let mut cfg = BenchConfig::default();
+        assert!(!cfg.dict_report);
+        cfg.set_dict_report(true);
+        assert!(cfg.dict_report);
+    }
+
+    #[test]
+    fn setter_synthetic() {
+        let mut cfg = BenchConfig::default();
+        assert_eq!(cfg.synthetic_entropy, None);
+        assert_eq!(cfg.synthetic_size, SYNTHETIC_SIZE);
+        cfg.set_synthetic_entropy(Some(0.25)).set_synthetic_size(MB);
+        assert_eq!(cfg.synthetic_entropy, Some(0.25));
+        assert_eq!(cfg.synthetic_size, MB);
+        cfg.set_synthetic_entropy(Some(7.0));
+        assert_eq!(cfg.synthetic_entropy, Some(1.0));
+    }
+
     #[test]
     fn constants_sanity() {
         assert_eq!(KB, 1024);
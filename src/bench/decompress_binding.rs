@@ -91,6 +91,7 @@ pub fn decompress_frame_block(
     let opts = DecompressOptions {
         stable_dst: true,
         skip_checksums,
+        ..Default::default()
     };
 
     // Temporary output chunk buffer — 64 KiB keeps stack usage reasonable while
@@ -0,0 +1,236 @@
+//! Package energy measurement from Intel/AMD RAPL counters (`energy` feature).
+//!
+//! Battery- and power-constrained deployments pick a compression level by
+//! energy spent per byte, not by throughput alone.  With the `energy` feature
+//! on Linux, [`bench_mem`](crate::bench::bench_mem::bench_mem) samples the
+//! RAPL package counters exposed by the kernel's powercap interface
+//! (`/sys/class/powercap/intel-rapl:N/energy_uj`; AMD CPUs use the same names)
+//! around each timed loop and reports joules per GB for compression and
+//! decompression.
+//!
+//! The counters cover the whole CPU package, so other load on the machine is
+//! charged to the benchmark; run it on an otherwise idle system.  Recent
+//! kernels make `energy_uj` readable by root only.  When no package counter
+//! can be read, or the feature is off, [`EnergyMeter::open`] returns `None`
+//! and the benchmark runs without energy figures.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Root of the Linux powercap sysfs tree.
+pub const POWERCAP_ROOT: &str = "/sys/class/powercap";
+
+/// One RAPL package domain.
+#[derive(Debug, Clone)]
+struct Domain {
+    energy_path: PathBuf,
+    /// Counter value at which `energy_uj` wraps to zero.
+    max_range_uj: u64,
+}
+
+/// Reads the RAPL package counters of every CPU socket.
+#[derive(Debug, Clone)]
+pub struct EnergyMeter {
+    domains: Vec<Domain>,
+}
+
+impl EnergyMeter {
+    /// Opens the system's RAPL package counters.
+    ///
+    /// Returns `None` without the `energy` feature, on non-Linux targets, and
+    /// when no package counter is readable.
+    pub fn open() -> Option<Self> {
+        #[cfg(all(feature = "energy", target_os = "linux"))]
+        {
+            Self::open_at(Path::new(POWERCAP_ROOT))
+        }
+        #[cfg(not(all(feature = "energy", target_os = "linux")))]
+        {
+            None
+        }
+    }
+
+    /// Opens the package domains found under a powercap tree rooted at `root`.
+    ///
+    /// Only top-level zones named `package-N` are used: sub-zones (`core`,
+    /// `dram`) and the `psys` platform zone overlap with them.
+    pub fn open_at(root: &Path) -> Option<Self> {
+        let mut zones: Vec<PathBuf> = fs::read_dir(root)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_prefix("intel-rapl:"))
+                    .is_some_and(|rest| !rest.contains(':'))
+            })
+            .collect();
+        zones.sort();
+
+        let domains: Vec<Domain> = zones
+            .into_iter()
+            .filter(|zone| {
+                fs::read_to_string(zone.join("name"))
+                    .is_ok_and(|name| name.trim().starts_with("package"))
+            })
+            .filter_map(|zone| {
+                let energy_path = zone.join("energy_uj");
+                read_u64(&energy_path)?;
+                let max_range_uj = read_u64(&zone.join("max_energy_range_uj")).unwrap_or(0);
+                Some(Domain {
+                    energy_path,
+                    max_range_uj,
+                })
+            })
+            .collect();
+        (!domains.is_empty()).then_some(EnergyMeter { domains })
+    }
+
+    /// Number of package domains being summed.
+    pub fn domains(&self) -> usize {
+        self.domains.len()
+    }
+
+    /// Starts measuring; `None` if a counter could not be read.
+    pub fn start(&self) -> Option<EnergySpan<'_>> {
+        Some(EnergySpan {
+            meter: self,
+            start: self.read()?,
+        })
+    }
+
+    fn read(&self) -> Option<Vec<u64>> {
+        self.domains
+            .iter()
+            .map(|d| read_u64(&d.energy_path))
+            .collect()
+    }
+}
+
+/// An energy measurement in progress, begun by [`EnergyMeter::start`].
+pub struct EnergySpan<'a> {
+    meter: &'a EnergyMeter,
+    start: Vec<u64>,
+}
+
+impl EnergySpan<'_> {
+    /// Joules consumed by all package domains since the span started.
+    ///
+    /// A counter that wrapped once is accounted for; `None` if a counter could
+    /// not be read.
+    pub fn finish(self) -> Option<f64> {
+        let end = self.meter.read()?;
+        let micro_joules: u64 = self
+            .meter
+            .domains
+            .iter()
+            .zip(self.start.iter().zip(&end))
+            .map(|(d, (&s, &e))| {
+                if e >= s {
+                    e - s
+                } else {
+                    d.max_range_uj.saturating_sub(s) + e
+                }
+            })
+            .sum();
+        Some(micro_joules as f64 / 1e6)
+    }
+}
+
+/// Energy and work accumulated over the timed passes of one benchmark phase.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PhaseEnergy {
+    joules: f64,
+    bytes: u64,
+}
+
+impl PhaseEnergy {
+    /// Adds one pass that spent `joules` processing `bytes` of input.
+    pub fn add(&mut self, joules: f64, bytes: u64) {
+        self.joules += joules;
+        self.bytes += bytes;
+    }
+
+    /// Joules per 10⁹ bytes processed; `None` before any pass was recorded.
+    pub fn joules_per_gb(&self) -> Option<f64> {
+        (self.bytes > 0).then(|| self.joules * 1e9 / self.bytes as f64)
+    }
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn zone(root: &Path, dir: &str, name: &str, energy: u64, max: u64) -> PathBuf {
+        let zone = root.join(dir);
+        fs::create_dir_all(&zone).unwrap();
+        fs::write(zone.join("name"), format!("{name}\n")).unwrap();
+        fs::write(zone.join("energy_uj"), format!("{energy}\n")).unwrap();
+        fs::write(zone.join("max_energy_range_uj"), format!("{max}\n")).unwrap();
+        zone
+    }
+
+    #[test]
+    fn sums_package_zones_only() {
+        let root = TempDir::new().unwrap();
+        let p0 = zone(
+            root.path(),
+            "intel-rapl:0",
+            "package-0",
+            1_000_000,
+            u64::MAX,
+        );
+        let p1 = zone(
+            root.path(),
+            "intel-rapl:1",
+            "package-1",
+            5_000_000,
+            u64::MAX,
+        );
+        let core = zone(root.path(), "intel-rapl:0:0", "core", 0, u64::MAX);
+        let psys = zone(root.path(), "intel-rapl:2", "psys", 0, u64::MAX);
+
+        let meter = EnergyMeter::open_at(root.path()).unwrap();
+        assert_eq!(meter.domains(), 2);
+        let span = meter.start().unwrap();
+        fs::write(p0.join("energy_uj"), "3000000").unwrap();
+        fs::write(p1.join("energy_uj"), "6500000").unwrap();
+        fs::write(core.join("energy_uj"), "9000000").unwrap();
+        fs::write(psys.join("energy_uj"), "9000000").unwrap();
+        assert_eq!(span.finish(), Some(3.5));
+    }
+
+    #[test]
+    fn handles_counter_wrap() {
+        let root = TempDir::new().unwrap();
+        let p0 = zone(root.path(), "intel-rapl:0", "package-0", 999_000, 1_000_000);
+        let meter = EnergyMeter::open_at(root.path()).unwrap();
+        let span = meter.start().unwrap();
+        fs::write(p0.join("energy_uj"), "4000").unwrap();
+        assert_eq!(span.finish(), Some(0.005));
+    }
+
+    #[test]
+    fn no_readable_package_zone() {
+        let root = TempDir::new().unwrap();
+        assert!(EnergyMeter::open_at(root.path()).is_none());
+        zone(root.path(), "intel-rapl:0", "psys", 0, 0);
+        assert!(EnergyMeter::open_at(root.path()).is_none());
+        assert!(EnergyMeter::open_at(&root.path().join("missing")).is_none());
+    }
+
+    #[test]
+    fn phase_energy_per_gb() {
+        let mut phase = PhaseEnergy::default();
+        assert_eq!(phase.joules_per_gb(), None);
+        phase.add(2.0, 500_000_000);
+        phase.add(1.0, 500_000_000);
+        assert_eq!(phase.joules_per_gb(), Some(3.0));
+    }
+}
@@ -10,12 +10,18 @@
 //!   benchmarks them together as a single logical dataset.
 //!
 //! [`config::BenchConfig`] controls display verbosity, iteration count,
-//! decode-only mode, and other runtime knobs.
+//! decode-only mode, and other runtime knobs.  With
+//! [`BenchConfig::dict_size_sweep`] set, the corpus is re-run with shorter
+//! tails of the dictionary and the ratio change per size is reported; with
+//! [`BenchConfig::dict_report`] set, it is run without and with the
+//! dictionary and both ratios are reported per level.  With the `energy` feature on
+//! Linux, [`energy`] adds joules/GB per level from the RAPL counters.
 
 pub mod bench_mem;
 pub mod compress_strategy;
 pub mod config;
 pub mod decompress_binding;
+pub mod energy;
 pub mod runner;
 
 // Re-export public types so callers can use `bench::BenchConfig` directly.
@@ -24,34 +30,45 @@ pub use config::BenchConfig;
 use std::fs;
 use std::io::{self, Read, Seek, SeekFrom};
 
+use crate::dict::truncation_warning;
 use crate::hc::types::LZ4HC_CLEVEL_MAX;
+use bench_mem::BenchResult;
 use config::LZ4_MAX_DICT_SIZE;
 use runner::{bench_c_level, bench_file_table};
 
 // ── Synthetic test ────────────────────────────────────────────────────────────
 
-/// Run a benchmark using synthetically generated lorem-ipsum data.
+/// Run a benchmark using synthetically generated data.
 ///
-/// Allocates a 10 MiB buffer filled with lorem-ipsum text (seed 0), then calls
-/// [`bench_c_level`] for each compression level in `c_level..=c_level_last`.
-/// This exercises the compressor on realistic but reproducible incompressible-ish
-/// natural-language input without requiring an on-disk file.
+/// Allocates a [`BenchConfig::synthetic_size`] buffer (10 MB by default)
+/// filled with lorem-ipsum text (seed 0), then calls [`bench_c_level`] for
+/// each compression level in `c_level..=c_level_last`.  This exercises the
+/// compressor on realistic but reproducible natural-language input without
+/// requiring an on-disk file.  With [`BenchConfig::synthetic_entropy`] set,
+/// the buffer comes from [`crate::corpus::compressible`] instead, so ratio
+/// curves can be charted per entropy class.
 fn synthetic_test(
     c_level: i32,
     c_level_last: i32,
     dict: &[u8],
     config: &BenchConfig,
-) -> io::Result<()> {
-    const BENCHED_SIZE: usize = 10_000_000;
-    let src_buffer = crate::lorem::gen_buffer(BENCHED_SIZE, 0);
+) -> io::Result<Vec<BenchResult>> {
+    let size = config.synthetic_size;
+    let (src_buffer, name) = match config.synthetic_entropy {
+        Some(entropy) => (
+            crate::corpus::compressible(size, 0, entropy),
+            format!("Entropy {:.2}", entropy),
+        ),
+        None => (crate::lorem::gen_buffer(size, 0), "Lorem ipsum".to_owned()),
+    };
     bench_c_level(
         &src_buffer,
-        "Lorem ipsum",
+        &name,
         c_level,
         c_level_last,
         config,
         dict,
-        &[BENCHED_SIZE], // single "file" spanning the whole buffer
+        &[size], // single "file" spanning the whole buffer
     )
 }
 
@@ -68,23 +85,159 @@ fn bench_files_separately(
     c_level_last: i32,
     dict: &[u8],
     config: &BenchConfig,
-) -> io::Result<()> {
+) -> io::Result<Vec<BenchResult>> {
     // Clamp both levels to the HC ceiling, then ensure the range is non-empty.
     let c_level = c_level.min(LZ4HC_CLEVEL_MAX);
     let c_level_last = c_level_last.min(LZ4HC_CLEVEL_MAX).max(c_level);
 
     let mut bench_error = false;
+    let mut results = Vec::new();
     for file_name in file_names {
-        if let Err(e) = bench_file_table(&[file_name], c_level, c_level_last, dict, config) {
-            eprintln!("bench error for {}: {}", file_name, e);
-            bench_error = true;
+        match bench_file_table(&[file_name], c_level, c_level_last, dict, config) {
+            Ok(r) => results.extend(r),
+            Err(e) => {
+                eprintln!("bench error for {}: {}", file_name, e);
+                bench_error = true;
+            }
         }
     }
     if bench_error {
         Err(io::Error::other("benchmark reported errors"))
     } else {
-        Ok(())
+        Ok(results)
+    }
+}
+
+// ── Dictionary size sweep ─────────────────────────────────────────────────────
+
+/// Run the benchmark selected by `file_names` and `config` with `dict`.
+fn bench_corpus(
+    file_names: &[&str],
+    c_level: i32,
+    c_level_last: i32,
+    dict: &[u8],
+    config: &BenchConfig,
+) -> io::Result<Vec<BenchResult>> {
+    if file_names.is_empty() {
+        // No files provided — fall back to the built-in synthetic benchmark.
+        synthetic_test(c_level, c_level_last, dict, config)
+    } else if config.bench_separately {
+        bench_files_separately(file_names, c_level, c_level_last, dict, config)
+    } else {
+        bench_file_table(file_names, c_level, c_level_last, dict, config)
+    }
+}
+
+/// Aggregate compression ratio per level, in the order levels first appear.
+///
+/// Results for the same level (one per file when benchmarking separately) are
+/// combined as total source bytes over total compressed bytes.
+fn level_ratios(results: &[BenchResult]) -> Vec<(i32, f64)> {
+    let mut totals: Vec<(i32, usize, usize)> = Vec::new();
+    for r in results {
+        match totals.iter_mut().find(|t| t.0 == r.c_level) {
+            Some(t) => {
+                t.1 += r.src_size;
+                t.2 += r.compressed_size;
+            }
+            None => totals.push((r.c_level, r.src_size, r.compressed_size)),
+        }
+    }
+    totals
+        .into_iter()
+        .map(|(level, src, compressed)| (level, src as f64 / compressed.max(1) as f64))
+        .collect()
+}
+
+/// Benchmark with the full dictionary, then with the trailing bytes of it
+/// listed in [`BenchConfig::dict_size_sweep`], and report the ratio change of
+/// each size against the full dictionary.
+fn dict_size_sweep(
+    file_names: &[&str],
+    c_level: i32,
+    c_level_last: i32,
+    dict: &[u8],
+    config: &BenchConfig,
+) -> io::Result<()> {
+    if config.display_level >= 2 {
+        eprintln!("Dictionary : {} bytes (full)", dict.len());
+    }
+    let full = level_ratios(&bench_corpus(
+        file_names,
+        c_level,
+        c_level_last,
+        dict,
+        config,
+    )?);
+
+    let mut rows = Vec::with_capacity(config.dict_size_sweep.len());
+    for &size in &config.dict_size_sweep {
+        let size = size.min(dict.len());
+        if config.display_level >= 2 {
+            eprintln!("Dictionary : last {} bytes", size);
+        }
+        let tail = &dict[dict.len() - size..];
+        let results = bench_corpus(file_names, c_level, c_level_last, tail, config)?;
+        rows.push((size, level_ratios(&results)));
     }
+
+    if config.display_level >= 2 {
+        eprintln!(
+            "Dictionary size sweep, ratio vs full {} bytes :",
+            dict.len()
+        );
+        for (size, ratios) in &rows {
+            for (&(level, ratio), &(_, full_ratio)) in ratios.iter().zip(&full) {
+                eprintln!(
+                    "{:>6} bytes, level {:>2} : ratio {:7.3} ({:+6.2}%)",
+                    size,
+                    level,
+                    ratio,
+                    (ratio / full_ratio - 1.0) * 100.0
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+// ── Dictionary effectiveness ──────────────────────────────────────────────────
+
+/// Benchmark without and then with `dict`, and report the ratio of both runs
+/// side by side per level, with the compressed size saved by the dictionary.
+fn dict_report(
+    file_names: &[&str],
+    c_level: i32,
+    c_level_last: i32,
+    dict: &[u8],
+    config: &BenchConfig,
+) -> io::Result<()> {
+    if config.display_level >= 2 {
+        eprintln!("Dictionary : none");
+    }
+    let without = bench_corpus(file_names, c_level, c_level_last, &[], config)?;
+    if config.display_level >= 2 {
+        eprintln!("Dictionary : {} bytes", dict.len());
+    }
+    let with = bench_corpus(file_names, c_level, c_level_last, dict, config)?;
+
+    if config.display_level >= 2 {
+        eprintln!("Dictionary benefit, {} bytes :", dict.len());
+        for (&(level, plain), &(_, dicted)) in
+            level_ratios(&without).iter().zip(&level_ratios(&with))
+        {
+            // Equal source bytes in both runs, so the size change is the
+            // inverse of the ratio change.
+            eprintln!(
+                "level {:>2} : ratio {:7.3} without, {:7.3} with ({:+6.2}% size)",
+                level,
+                plain,
+                dicted,
+                (plain / dicted - 1.0) * 100.0
+            );
+        }
+    }
+    Ok(())
 }
 
 // ── Public API ────────────────────────────────────────────────────────────────
@@ -101,10 +254,14 @@ fn bench_files_separately(
 ///   [`LZ4_MAX_DICT_SIZE`] bytes of the file are loaded — LZ4 dictionaries
 ///   are always anchored at the tail.
 /// - `config`: runtime parameters (verbosity, iteration count, decode-only, …).
+///   A non-empty [`BenchConfig::dict_size_sweep`] repeats the run once per
+///   dictionary size and reports the ratio deltas; [`BenchConfig::dict_report`]
+///   runs without and with the dictionary and reports both ratios.
 ///
 /// # Errors
 /// Returns `Err` if a required file cannot be read, the dictionary cannot be
-/// loaded, or at least one benchmark pass reports a failure.
+/// loaded, a dictionary size sweep or report is requested without a dictionary, or at
+/// least one benchmark pass reports a failure.
 pub fn bench_files(
     file_names: &[&str],
     c_level: i32,
@@ -157,6 +314,11 @@ pub fn bench_files(
             )
         })?;
         let dict_file_size = meta.len() as usize;
+        if let Some(warning) = truncation_warning(meta.len()) {
+            if config.display_level >= 2 {
+                eprint!("{warning}");
+            }
+        }
         if dict_file_size == 0 {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -199,14 +361,25 @@ pub fn bench_files(
     };
 
     // ── Dispatch ──────────────────────────────────────────────────────────────
-    if file_names.is_empty() {
-        // No files provided — fall back to the built-in synthetic benchmark.
-        synthetic_test(c_level, c_level_last, &dict_buf, config)
-    } else if config.bench_separately {
-        bench_files_separately(file_names, c_level, c_level_last, &dict_buf, config)
-    } else {
-        bench_file_table(file_names, c_level, c_level_last, &dict_buf, config)
+    if !config.dict_size_sweep.is_empty() {
+        if dict_buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Error : dictionary size sweep requires a dictionary",
+            ));
+        }
+        return dict_size_sweep(file_names, c_level, c_level_last, &dict_buf, config);
     }
+    if config.dict_report {
+        if dict_buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Error : dictionary report requires a dictionary",
+            ));
+        }
+        return dict_report(file_names, c_level, c_level_last, &dict_buf, config);
+    }
+    bench_corpus(file_names, c_level, c_level_last, &dict_buf, config).map(|_| ())
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
@@ -299,4 +472,75 @@ mod tests {
         let result = bench_files(&[], 1, 1, Some("/nonexistent/dict.bin"), &config);
         assert!(result.is_err(), "missing dict file should return Err");
     }
+
+    #[test]
+    fn dict_size_sweep_requires_dict() {
+        let mut config = BenchConfig::default();
+        config.set_nb_seconds(0).set_notification_level(0);
+        config.set_dict_size_sweep(vec![4096]);
+        let err = bench_files(&[], 1, 1, None, &config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn bench_files_synthetic_entropy() {
+        let mut config = BenchConfig::default();
+        config.set_nb_seconds(0).set_notification_level(0);
+        config
+            .set_synthetic_entropy(Some(0.5))
+            .set_synthetic_size(256 * 1024);
+        let results = synthetic_test(1, 1, &[], &config).unwrap();
+        assert_eq!(results[0].src_size, 256 * 1024);
+        assert!(
+            (1.2..1.8).contains(&results[0].ratio),
+            "{}",
+            results[0].ratio
+        );
+    }
+
+    #[test]
+    fn dict_report_requires_dict() {
+        let mut config = BenchConfig::default();
+        config.set_nb_seconds(0).set_notification_level(0);
+        config.set_dict_report(true);
+        let err = bench_files(&[], 1, 1, None, &config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn dict_report_runs_both_variants() {
+        use std::io::Write;
+        let mut dict = tempfile::NamedTempFile::new().expect("dict");
+        let mut input = tempfile::NamedTempFile::new().expect("input");
+        dict.write_all(&crate::lorem::gen_buffer(8192, 1)).unwrap();
+        input.write_all(&crate::lorem::gen_buffer(4096, 2)).unwrap();
+        let dict_path = dict.path().to_str().unwrap().to_owned();
+        let input_path = input.path().to_str().unwrap().to_owned();
+
+        let mut config = BenchConfig::default();
+        config.set_nb_seconds(0).set_notification_level(0);
+        config.set_dict_report(true);
+        let result = bench_files(&[&input_path], 1, 2, Some(&dict_path), &config);
+        assert!(
+            result.is_ok(),
+            "dictionary report failed: {:?}",
+            result.err()
+        );
+    }
+
+    #[test]
+    fn level_ratios_combines_files_per_level() {
+        let result = |c_level, src_size, compressed_size| BenchResult {
+            src_size,
+            compressed_size,
+            ratio: 0.0,
+            compress_speed_mb_s: 0.0,
+            decompress_speed_mb_s: 0.0,
+            c_level,
+            compress_joules_per_gb: None,
+            decompress_joules_per_gb: None,
+        };
+        let ratios = level_ratios(&[result(1, 100, 50), result(2, 100, 40), result(1, 300, 50)]);
+        assert_eq!(ratios, [(1, 4.0), (2, 2.5)]);
+    }
 }
@@ -14,7 +14,7 @@
 use std::fs;
 use std::io::{self, Read};
 
-use super::bench_mem::bench_mem;
+use super::bench_mem::{bench_mem, BenchResult};
 use super::compress_strategy::build_compression_parameters;
 use super::config::{BenchConfig, MAX_MEMORY};
 use super::decompress_binding::FrameDecompressor;
@@ -76,6 +76,8 @@ fn find_max_mem(required_mem: u64) -> usize {
 /// `file_sizes` holds per-file byte counts within `src`; an empty slice causes
 /// `src` to be treated as a single logical file.
 ///
+/// Returns the [`BenchResult`] of every level, in level order.
+///
 /// When the `realtime-priority` Cargo feature is enabled, the function
 /// attempts to raise the process scheduling priority via `setpriority(2)` to
 /// reduce OS-induced jitter in measurements.
@@ -87,7 +89,7 @@ pub fn bench_c_level(
     config: &BenchConfig,
     dict: &[u8],
     file_sizes: &[usize],
-) -> io::Result<()> {
+) -> io::Result<Vec<BenchResult>> {
     // Strip path prefix: check '\\' first (Windows paths), then '/' (POSIX).
     // Using the last separator ensures deeply nested paths show only the filename.
     let display_name = if let Some(pos) = display_name.rfind('\\') {
@@ -127,10 +129,11 @@ pub fn bench_c_level(
     let c_level_last = c_level_last.max(c_level);
 
     let mut bench_error = false;
+    let mut results = Vec::new();
     for l in c_level..=c_level_last {
         let mut strategy = build_compression_parameters(l, src.len(), src.len());
         let mut decompressor = FrameDecompressor::new();
-        if let Err(e) = bench_mem(
+        match bench_mem(
             src,
             display_name,
             config,
@@ -140,15 +143,18 @@ pub fn bench_c_level(
             dict,
             file_sizes,
         ) {
-            eprintln!("bench error at level {}: {}", l, e);
-            bench_error = true;
+            Ok(result) => results.push(result),
+            Err(e) => {
+                eprintln!("bench error at level {}: {}", l, e);
+                bench_error = true;
+            }
         }
     }
 
     if bench_error {
         Err(io::Error::other("benchmark reported errors"))
     } else {
-        Ok(())
+        Ok(results)
     }
 }
 
@@ -243,14 +249,14 @@ pub fn load_files(
 /// Computes the total corpus size, derives a safe buffer limit with
 /// [`find_max_mem`] (also capping at [`LZ4_MAX_INPUT_SIZE`]), loads the files
 /// into that buffer with [`load_files`], then delegates to [`bench_c_level`]
-/// for every level in `c_level..=c_level_last`.
+/// for every level in `c_level..=c_level_last` and returns its results.
 pub fn bench_file_table(
     file_names: &[&str],
     c_level: i32,
     c_level_last: i32,
     dict: &[u8],
     config: &BenchConfig,
-) -> io::Result<()> {
+) -> io::Result<Vec<BenchResult>> {
     // Sum the sizes of all non-directory paths to determine how much data to load.
     let total_size_to_load: u64 = file_names
         .iter()
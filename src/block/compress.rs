@@ -10,8 +10,12 @@
 //! | [`compress_fast_ext_state`]          | `LZ4_compress_fast_extState`          |
 //! | [`compress_fast`]                    | `LZ4_compress_fast`                   |
 //! | [`compress_default`]                 | `LZ4_compress_default`                |
+//! | [`compress_fast_seeded`]             | —  (keyed match-finder hash)          |
 //! | [`compress_dest_size`]               | `LZ4_compress_destSize`               |
 //!
+//! [`Compressor`](super::Compressor) combines these options in one configurable
+//! entry point.
+//!
 //! The encoder uses a hash table to find back-references (matches) within a
 //! sliding window of up to [`LZ4_DISTANCE_MAX`] bytes.  Each compressed
 //! sequence consists of a literal run followed by a match (offset + length);
@@ -29,12 +33,11 @@
 use core::ptr;
 
 use super::types::{
-    clear_hash, count, get_index_on_hash, get_position, get_position_on_hash, hash_position,
-    prepare_table, put_index_on_hash, put_position, put_position_on_hash, read32, wild_copy8,
-    write32, write_le16, DictDirective, DictIssueDirective, LimitedOutputDirective,
-    StreamStateInternal, TableType, LASTLITERALS, LZ4_64KLIMIT, LZ4_DISTANCE_ABSOLUTE_MAX,
-    LZ4_DISTANCE_MAX, LZ4_MIN_LENGTH, LZ4_SKIP_TRIGGER, MFLIMIT, MINMATCH, ML_BITS, ML_MASK,
-    RUN_MASK,
+    clear_hash, count, get_index_on_hash, get_position_on_hash, hash_position_keyed, prepare_table,
+    put_index_on_hash, put_position_on_hash, read32, wild_copy8, write32, write_le16,
+    DictDirective, DictIssueDirective, HashKey, LimitedOutputDirective, StreamStateInternal,
+    TableType, LASTLITERALS, LZ4_64KLIMIT, LZ4_DISTANCE_ABSOLUTE_MAX, LZ4_DISTANCE_MAX,
+    LZ4_MIN_LENGTH, LZ4_SKIP_TRIGGER, MFLIMIT, MINMATCH, ML_BITS, ML_MASK, RUN_MASK,
 };
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -62,6 +65,8 @@ pub enum Lz4Error {
     OutputTooSmall,
     /// The input exceeds `LZ4_MAX_INPUT_SIZE`.
     InputTooLarge,
+    /// A match-distance limit outside `1..=LZ4_DISTANCE_MAX` was requested.
+    InvalidMaxDistance,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -98,6 +103,7 @@ pub fn compress_bound(input_size: i32) -> i32 {
 /// be exclusively accessed for the duration of the call.
 #[inline(always)]
 #[allow(unused_assignments)] // dead-store inits mirror C variable declarations; vars are set before first read
+#[allow(clippy::too_many_arguments)]
 pub unsafe fn compress_generic_validated(
     cctx: *mut StreamStateInternal,
     source: *const u8,
@@ -117,10 +123,13 @@ pub unsafe fn compress_generic_validated(
     let mut ip: *const u8 = source;
 
     let start_index: u32 = cctx_ref.current_offset;
+    let hash_key = HashKey::from_seed(cctx_ref.hash_seed);
+    let max_distance: u32 = cctx_ref.max_distance;
+    debug_assert!((1..=LZ4_DISTANCE_MAX).contains(&max_distance));
     // `base` maps an absolute offset back to a source pointer:  source == base + startIndex
     let base: *const u8 = source.wrapping_sub(start_index as usize);
 
-    let dict_ctx = cctx_ref.dict_ctx as *const StreamStateInternal;
+    let dict_ctx = cctx_ref.dict_ctx;
     let dictionary: *const u8 =
         if dict_directive == DictDirective::UsingDictCtx && !dict_ctx.is_null() {
             (*dict_ctx).dictionary
@@ -216,7 +225,7 @@ pub unsafe fn compress_generic_validated(
 
         // ── First byte ───────────────────────────────────────────────────────
         {
-            let h = hash_position(ip, table_type);
+            let h = hash_position_keyed(ip, table_type, hash_key);
             if table_type == TableType::ByPtr {
                 put_position_on_hash(
                     ip,
@@ -229,7 +238,7 @@ pub unsafe fn compress_generic_validated(
             }
         }
         ip = ip.add(1);
-        forward_h = hash_position(ip, table_type);
+        forward_h = hash_position_keyed(ip, table_type, hash_key);
 
         // ── Main find-match / encode loop ────────────────────────────────────
         #[allow(unused_labels)]
@@ -261,7 +270,7 @@ pub unsafe fn compress_generic_validated(
                         cctx_ref.hash_table.as_ptr() as *const *const u8,
                         table_type,
                     );
-                    forward_h = hash_position(forward_ip, table_type);
+                    forward_h = hash_position_keyed(forward_ip, table_type, hash_key);
                     put_position_on_hash(
                         ip,
                         h,
@@ -270,8 +279,7 @@ pub unsafe fn compress_generic_validated(
                     );
 
                     // Reject if too far or 4-byte prefix doesn't match
-                    if match_ptr.add(LZ4_DISTANCE_MAX as usize) < ip
-                        || read32(match_ptr) != read32(ip)
+                    if match_ptr.add(max_distance as usize) < ip || read32(match_ptr) != read32(ip)
                     {
                         continue;
                     }
@@ -331,7 +339,7 @@ pub unsafe fn compress_generic_validated(
                         match_ptr = base.add(match_index as usize);
                     }
 
-                    forward_h = hash_position(forward_ip, table_type);
+                    forward_h = hash_position_keyed(forward_ip, table_type, hash_key);
                     put_index_on_hash(current, h, cctx_ref.hash_table.as_mut_ptr(), table_type);
 
                     // Reject: match outside dictSmall valid range
@@ -342,9 +350,8 @@ pub unsafe fn compress_generic_validated(
 
                     // Reject: match too far back (only checked for byU32; byU16 offsets
                     // always fit in u16 so the distance is always ≤ 65535)
-                    if (table_type != TableType::ByU16
-                        || LZ4_DISTANCE_MAX < LZ4_DISTANCE_ABSOLUTE_MAX)
-                        && match_index.wrapping_add(LZ4_DISTANCE_MAX) < current
+                    if (table_type != TableType::ByU16 || max_distance < LZ4_DISTANCE_ABSOLUTE_MAX)
+                        && match_index.wrapping_add(max_distance) < current
                     {
                         continue; // too far
                     }
@@ -479,7 +486,7 @@ pub unsafe fn compress_generic_validated(
                             if (ip as usize) <= (filled_ip as usize) {
                                 let mut ptr = ip;
                                 while (ptr as usize) <= (filled_ip as usize) {
-                                    let h = hash_position(ptr, table_type);
+                                    let h = hash_position_keyed(ptr, table_type, hash_key);
                                     clear_hash(h, cctx_ref.hash_table.as_mut_ptr(), table_type);
                                     ptr = ptr.add(1);
                                 }
@@ -518,7 +525,7 @@ pub unsafe fn compress_generic_validated(
 
                 // ── Fill hash table (ip-2) ────────────────────────────────────
                 {
-                    let h = hash_position(ip.sub(2), table_type);
+                    let h = hash_position_keyed(ip.sub(2), table_type, hash_key);
                     if table_type == TableType::ByPtr {
                         put_position_on_hash(
                             ip.sub(2),
@@ -534,17 +541,19 @@ pub unsafe fn compress_generic_validated(
 
                 // ── Test next position: try immediate re-match ────────────────
                 if table_type == TableType::ByPtr {
-                    let m = get_position(
-                        ip,
+                    let h = hash_position_keyed(ip, table_type, hash_key);
+                    let m = get_position_on_hash(
+                        h,
                         cctx_ref.hash_table.as_ptr() as *const *const u8,
                         table_type,
                     );
-                    put_position(
+                    put_position_on_hash(
                         ip,
+                        h,
                         cctx_ref.hash_table.as_mut_ptr() as *mut *const u8,
                         table_type,
                     );
-                    if m.add(LZ4_DISTANCE_MAX as usize) >= ip && read32(m) == read32(ip) {
+                    if m.add(max_distance as usize) >= ip && read32(m) == read32(ip) {
                         // Immediate match at ip: emit a 0-literal sequence
                         token = op;
                         *op = 0;
@@ -554,7 +563,7 @@ pub unsafe fn compress_generic_validated(
                     }
                 } else {
                     // ByU32 / ByU16
-                    let h = hash_position(ip, table_type);
+                    let h = hash_position_keyed(ip, table_type, hash_key);
                     let current = (ip as usize - base as usize) as u32;
                     let mut m_index =
                         get_index_on_hash(h, cctx_ref.hash_table.as_ptr(), table_type);
@@ -600,11 +609,11 @@ pub unsafe fn compress_generic_validated(
                     };
                     // Validate: distance check (byU16 with max-distance == absolute-max always passes)
                     let dist_ok = if table_type == TableType::ByU16
-                        && LZ4_DISTANCE_MAX == LZ4_DISTANCE_ABSOLUTE_MAX
+                        && max_distance == LZ4_DISTANCE_ABSOLUTE_MAX
                     {
                         true
                     } else {
-                        m_index.wrapping_add(LZ4_DISTANCE_MAX) >= current
+                        m_index.wrapping_add(max_distance) >= current
                     };
 
                     if dict_ok && dist_ok && read32(match_ptr) == read32(ip) {
@@ -620,7 +629,7 @@ pub unsafe fn compress_generic_validated(
                 }
 
                 // No immediate match: restart find-match from advanced position.
-                forward_h = hash_position(ip.add(1), table_type);
+                forward_h = hash_position_keyed(ip.add(1), table_type, hash_key);
                 ip = ip.add(1);
                 break 'next_match; // back to 'main (find-match)
             } // end 'next_match
@@ -688,6 +697,7 @@ pub unsafe fn compress_generic_validated(
 /// # Safety
 /// See `compress_generic_validated`.
 #[inline(always)]
+#[allow(clippy::too_many_arguments)]
 pub unsafe fn compress_generic(
     cctx: *mut StreamStateInternal,
     src: *const u8,
@@ -759,9 +769,7 @@ pub unsafe fn compress_fast_ext_state(
     // Re-initialize state (equivalent to LZ4_initStream)
     *state = StreamStateInternal::new();
 
-    let acceleration = acceleration
-        .max(LZ4_ACCELERATION_DEFAULT)
-        .min(LZ4_ACCELERATION_MAX);
+    let acceleration = acceleration.clamp(LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX);
 
     if dst_capacity >= compress_bound(src_len) {
         // Unlimited output: select table type based on input size
@@ -847,9 +855,7 @@ pub unsafe fn compress_fast_ext_state_fast_reset(
     dst_capacity: i32,
     acceleration: i32,
 ) -> Result<usize, Lz4Error> {
-    let acceleration = acceleration
-        .max(LZ4_ACCELERATION_DEFAULT)
-        .min(LZ4_ACCELERATION_MAX);
+    let acceleration = acceleration.clamp(LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX);
 
     if dst_capacity >= compress_bound(src_len) {
         if (src_len as usize) < LZ4_64KLIMIT {
@@ -932,7 +938,10 @@ pub unsafe fn compress_fast_ext_state_fast_reset(
 
 /// Compress `src` into `dst` with a user-supplied `acceleration` factor.
 ///
-/// Allocates temporary compression state on the stack.
+/// Allocates temporary compression state on the stack.  `acceleration` is
+/// clamped to `[1, LZ4_ACCELERATION_MAX]`, so `0` and negative values behave
+/// like the default.  For combinations with a dictionary, a smaller match
+/// window or fill-destination mode, use [`Compressor`](super::Compressor).
 ///
 /// Equivalent to `LZ4_compress_fast`.
 ///
@@ -966,6 +975,39 @@ pub fn compress_default(src: &[u8], dst: &mut [u8]) -> Result<usize, Lz4Error> {
     compress_fast(src, dst, 1)
 }
 
+/// Like [`compress_fast`], but the match finder hashes with the keyed
+/// multiplier derived from `hash_seed` (see [`HashKey::from_seed`]).
+///
+/// Intended for services that compress untrusted input: data crafted to
+/// collide in the reference hash degrades both ratio and throughput, and a
+/// secret per-process seed makes such collisions impractical to construct.
+/// The output is an ordinary LZ4 block that any decoder accepts; a seed of
+/// `0` reproduces [`compress_fast`] exactly.
+pub fn compress_fast_seeded(
+    src: &[u8],
+    dst: &mut [u8],
+    acceleration: i32,
+    hash_seed: u64,
+) -> Result<usize, Lz4Error> {
+    let src_len = src.len();
+    if src_len > LZ4_MAX_INPUT_SIZE as usize {
+        return Err(Lz4Error::InputTooLarge);
+    }
+    let mut ctx = StreamStateInternal::with_hash_seed(hash_seed);
+    // SAFETY: `ctx` is freshly initialised, so the fast-reset path only has
+    // to prepare an already-cleared table; slices bound the pointer ranges.
+    unsafe {
+        compress_fast_ext_state_fast_reset(
+            &mut ctx,
+            src.as_ptr(),
+            src_len as i32,
+            dst.as_mut_ptr(),
+            dst.len() as i32,
+            acceleration,
+        )
+    }
+}
+
 /// Compress as much of `src` as fits in exactly `dst_capacity` bytes.
 ///
 /// On success returns the number of bytes consumed from `src` (via
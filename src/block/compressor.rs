@@ -0,0 +1,205 @@
+//! Configurable one-shot block compressor.
+//!
+//! [`Compressor`] gathers the orthogonal knobs of the fast block encoder —
+//! acceleration, an external dictionary, the match distance limit, the
+//! keyed hash seed and "fill the destination" mode — behind a single
+//! [`Compressor::compress`] call, so each new option no longer needs its own
+//! family of free functions.  The free functions in [`compress`](super::compress)
+//! remain and are equivalent to particular configurations:
+//!
+//! | Free function                      | `Compressor` configuration                      |
+//! |------------------------------------|-------------------------------------------------|
+//! | [`compress_default`]               | `Compressor::new()`                             |
+//! | [`compress_fast`]                  | `.acceleration(a)`                              |
+//! | [`compress_fast_seeded`]           | `.acceleration(a).hash_seed(s)`                 |
+//! | [`compress_dest_size`]             | `.fill_dest(true)`                              |
+//!
+//! [`compress_default`]: super::compress_default
+//! [`compress_fast`]: super::compress_fast
+//! [`compress_fast_seeded`]: super::compress_fast_seeded
+//! [`compress_dest_size`]: super::compress_dest_size
+
+use core::ptr;
+
+use super::compress::{
+    compress_bound, compress_generic, Lz4Error, LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX,
+    LZ4_MAX_INPUT_SIZE,
+};
+use super::stream::Lz4Stream;
+use super::types::{
+    DictDirective, DictIssueDirective, LimitedOutputDirective, StreamStateInternal, TableType,
+    LZ4_64KLIMIT, LZ4_DISTANCE_MAX,
+};
+
+const KB64: u32 = 64 * 1024;
+
+/// Block compressor configuration.
+///
+/// Built with [`Compressor::new`] and the chained setters below; a configured
+/// value is cheap to copy and can be reused for any number of
+/// [`compress`](Self::compress) calls, from several threads at once.  Each
+/// call uses its own fresh match-finder state, so blocks are independent.
+///
+/// ```
+/// use lz4::block::decompress_core::decompress_safe_using_dict;
+/// use lz4::block::{compress_bound, Compressor};
+///
+/// let dict = b"GET /index.html HTTP/1.1\r\nHost: ";
+/// let src = b"GET /index.html HTTP/1.1\r\nHost: example.com\r\n\r\n";
+/// let mut dst = vec![0u8; compress_bound(src.len() as i32) as usize];
+/// let (_, n) = Compressor::new().dictionary(dict).compress(src, &mut dst).unwrap();
+///
+/// let mut out = vec![0u8; src.len()];
+/// let m = decompress_safe_using_dict(&dst[..n], &mut out, dict).unwrap();
+/// assert_eq!(&out[..m], src);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Compressor<'d> {
+    acceleration: i32,
+    dictionary: Option<&'d [u8]>,
+    max_distance: u32,
+    hash_seed: u64,
+    fill_dest: bool,
+}
+
+impl Default for Compressor<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'d> Compressor<'d> {
+    /// The configuration of [`compress_default`](super::compress_default):
+    /// acceleration 1, no dictionary, full 64 KB window, reference hash, and
+    /// an error when `dst` is too small.
+    pub const fn new() -> Self {
+        Compressor {
+            acceleration: LZ4_ACCELERATION_DEFAULT,
+            dictionary: None,
+            max_distance: LZ4_DISTANCE_MAX,
+            hash_seed: 0,
+            fill_dest: false,
+        }
+    }
+
+    /// Acceleration factor, clamped to `[1, LZ4_ACCELERATION_MAX]` exactly as
+    /// [`compress_fast`](super::compress_fast) does.  Larger values trade
+    /// ratio for speed.
+    pub fn acceleration(mut self, acceleration: i32) -> Self {
+        self.acceleration = acceleration.clamp(LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX);
+        self
+    }
+
+    /// Compress against `dictionary` (only its last 64 KB is used).  The
+    /// block must then be decoded with the same dictionary, e.g. by
+    /// [`decompress_safe_using_dict`](super::decompress_core::decompress_safe_using_dict).
+    pub fn dictionary(mut self, dictionary: &'d [u8]) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    /// Largest match offset the encoder may emit, clamped to
+    /// `[1, LZ4_DISTANCE_MAX]`.  A smaller window lets a decoder keep less
+    /// history, at some cost in ratio; the output is still a standard block.
+    pub fn max_distance(mut self, distance: u32) -> Self {
+        self.max_distance = distance.clamp(1, LZ4_DISTANCE_MAX);
+        self
+    }
+
+    /// Keyed match-finder hash, as in
+    /// [`compress_fast_seeded`](super::compress_fast_seeded).  `0` (the
+    /// default) is the reference hash.
+    pub fn hash_seed(mut self, seed: u64) -> Self {
+        self.hash_seed = seed;
+        self
+    }
+
+    /// When `true`, compress as much of `src` as fits in `dst` instead of
+    /// failing, as [`compress_dest_size`](super::compress_dest_size) does.
+    pub fn fill_dest(mut self, yes: bool) -> Self {
+        self.fill_dest = yes;
+        self
+    }
+
+    /// Compress `src` into `dst` as one raw LZ4 block.
+    ///
+    /// Returns `(consumed, written)`: the number of source bytes encoded and
+    /// the compressed length.  `consumed` is always `src.len()` unless
+    /// [`fill_dest`](Self::fill_dest) is set and `dst` is smaller than
+    /// [`compress_bound`].  Without `fill_dest`, a `dst` that cannot hold the
+    /// block gives [`Lz4Error::OutputTooSmall`].
+    pub fn compress(&self, src: &[u8], dst: &mut [u8]) -> Result<(usize, usize), Lz4Error> {
+        if src.len() > LZ4_MAX_INPUT_SIZE as usize {
+            return Err(Lz4Error::InputTooLarge);
+        }
+        let src_len = src.len() as i32;
+        let dst_len = dst.len().min(i32::MAX as usize) as i32;
+        let output = if dst_len >= compress_bound(src_len) {
+            LimitedOutputDirective::NotLimited
+        } else if self.fill_dest {
+            LimitedOutputDirective::FillOutput
+        } else {
+            LimitedOutputDirective::LimitedOutput
+        };
+
+        // Only FillOutput writes through `consumed`; the other modes encode
+        // the whole input or fail.
+        let mut consumed = src_len;
+        let consumed_ptr = if output == LimitedOutputDirective::FillOutput {
+            &mut consumed as *mut i32
+        } else {
+            ptr::null_mut()
+        };
+
+        let mut stream = Lz4Stream::default();
+        stream.internal.hash_seed = self.hash_seed;
+        let (table_type, dict_directive, dict_issue) = match self.dictionary {
+            Some(dict) => {
+                stream.load_dict(dict);
+                // Mirrors the external-dictionary branch of
+                // `compress_fast_continue` on a freshly loaded stream.
+                let st = &stream.internal;
+                let issue = if st.dict_size < KB64 && st.dict_size < st.current_offset {
+                    DictIssueDirective::DictSmall
+                } else {
+                    DictIssueDirective::NoDictIssue
+                };
+                (TableType::ByU32, DictDirective::UsingExtDict, issue)
+            }
+            None if src.len() < LZ4_64KLIMIT => (
+                TableType::ByU16,
+                DictDirective::NoDict,
+                DictIssueDirective::NoDictIssue,
+            ),
+            None => (
+                TableType::ByU32,
+                DictDirective::NoDict,
+                DictIssueDirective::NoDictIssue,
+            ),
+        };
+        stream.internal.max_distance = self.max_distance;
+
+        // SAFETY: `stream` is freshly initialised and exclusively owned; the
+        // dictionary it points at is borrowed for `'d`, which outlives this
+        // call; `src` and `dst` bound the pointer ranges passed in.
+        let written = unsafe {
+            compress_generic(
+                &mut stream.internal as *mut StreamStateInternal,
+                src.as_ptr(),
+                dst.as_mut_ptr(),
+                src_len,
+                consumed_ptr,
+                dst_len,
+                output,
+                table_type,
+                dict_directive,
+                dict_issue,
+                self.acceleration,
+            )?
+        };
+        if written == 0 && dst_len == 0 {
+            return Err(Lz4Error::OutputTooSmall);
+        }
+        Ok((consumed as usize, written))
+    }
+}
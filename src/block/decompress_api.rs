@@ -3,14 +3,18 @@
 //! Implements the LZ4 block decompression functions from `lz4.c` v1.10.0
 //! (lines 2448–2760):
 //!
-//!   - One-shot decompression: `decompress_safe`, `decompress_safe_partial`
+//!   - One-shot decompression: `decompress_safe`, `decompress_safe_partial`,
+//!     `decompress_safe_uninit` (output into `MaybeUninit<u8>` memory)
 //!   - Dictionary decompression: `decompress_safe_using_dict`,
 //!     `decompress_safe_partial_using_dict`, `decompress_safe_force_ext_dict`,
 //!     `decompress_safe_partial_force_ext_dict`
+//!   - Split-dictionary decompression: `decompress_safe_using_split_dict`
+//!     (recent prefix in the output window + older external segment)
 //!   - Prefix-mode helpers: `decompress_safe_with_prefix64k`,
 //!     `decompress_safe_with_small_prefix`
 //!   - Streaming decode context: [`Lz4StreamDecode`]
 //!   - Streaming API: `decompress_safe_continue`
+//!   - Owned ring-buffer decoder: [`Lz4RingDecoder`]
 //!
 //! # Not implemented
 //!
@@ -24,6 +28,7 @@
 //! pointer addresses or track caller-managed ring-buffer positions are marked
 //! `unsafe`; their contracts are documented inline.
 
+use core::mem::MaybeUninit;
 use core::ptr;
 
 use super::decompress_core::{decompress_generic, DecompressError};
@@ -116,8 +121,39 @@ pub fn decompress_safe(src: &[u8], dst: &mut [u8]) -> Result<usize, DecompressEr
             dst.len(),
             false, // decode_full_block
             DictDirective::NoDict,
-            dst.as_ptr() as *const u8, // low_prefix = start of dst
-            ptr::null(),               // no external dictionary
+            dst.as_ptr(), // low_prefix = start of dst
+            ptr::null(),  // no external dictionary
+            0,
+        )
+    }
+}
+
+/// Decompress a full LZ4 block into uninitialised memory.
+///
+/// Behaves like [`decompress_safe`] but lets callers skip zero-filling large
+/// output buffers (e.g. the spare capacity of a `Vec`).  On `Ok(n)`, exactly
+/// `dst[..n]` has been initialised; bytes past `n` may also have been written
+/// but carry no meaningful value.  On error, the contents of `dst` are
+/// unspecified but no uninitialised byte has been read.
+pub fn decompress_safe_uninit(
+    src: &[u8],
+    dst: &mut [MaybeUninit<u8>],
+) -> Result<usize, DecompressError> {
+    // SAFETY: slices guarantee valid, non-overlapping memory regions, and
+    // `MaybeUninit<u8>` has the same layout as `u8`.  `decompress_generic`
+    // only reads output bytes it has already written during this call (see
+    // "Uninitialised output" in `decompress_core`), so no uninitialised
+    // memory is ever read.
+    unsafe {
+        decompress_generic(
+            src.as_ptr(),
+            dst.as_mut_ptr().cast::<u8>(),
+            src.len(),
+            dst.len(),
+            false, // decode_full_block
+            DictDirective::NoDict,
+            dst.as_ptr().cast::<u8>(), // low_prefix = start of dst
+            ptr::null(),
             0,
         )
     }
@@ -150,7 +186,7 @@ pub fn decompress_safe_partial(
             output_size,
             true, // partial_decode
             DictDirective::NoDict,
-            dst.as_ptr() as *const u8,
+            dst.as_ptr(),
             ptr::null(),
             0,
         )
@@ -503,6 +539,92 @@ pub unsafe fn decompress_safe_continue(
     Ok(result)
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Owned ring-buffer decoder
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Streaming block decoder that owns its ring buffer.
+///
+/// Wraps [`decompress_safe_continue`] with a buffer of
+/// [`decoder_ring_buffer_size`] bytes and handles the bookkeeping callers
+/// otherwise do by hand: each block is decoded at the current ring position,
+/// and the position wraps to the start whenever a maximum-size block would no
+/// longer fit.  The ring size guarantees that the previous 64 KB of output is
+/// still intact when blocks that reference it are decoded, so the blocks may
+/// come from any [`Lz4Stream`](super::stream::Lz4Stream) session that kept
+/// each decompressed block at or below `max_block_size` bytes.
+pub struct Lz4RingDecoder {
+    ring: Vec<u8>,
+    pos: usize,
+    max_block_size: usize,
+    ctx: Lz4StreamDecode,
+}
+
+// SAFETY: the context's raw pointers only ever point into `ring`, which is
+// owned by the same value and moves with it.
+unsafe impl Send for Lz4RingDecoder {}
+
+impl Lz4RingDecoder {
+    /// Create a decoder for blocks that decompress to at most
+    /// `max_block_size` bytes.
+    ///
+    /// Returns `None` if `max_block_size` exceeds [`LZ4_MAX_INPUT_SIZE`].
+    pub fn new(max_block_size: usize) -> Option<Self> {
+        let size = decoder_ring_buffer_size(max_block_size)?;
+        Some(Self {
+            ring: vec![0u8; size],
+            pos: 0,
+            max_block_size,
+            ctx: Lz4StreamDecode::new(),
+        })
+    }
+
+    /// Decode the next compressed block of the stream.
+    ///
+    /// Returns the decoded bytes, which stay valid until the next call.
+    /// A block that would decode to more than `max_block_size` bytes fails
+    /// with [`DecompressError::MalformedInput`].  After any error the
+    /// history is unreliable; call [`reset`](Self::reset) before decoding a
+    /// new stream.
+    pub fn decode_next(&mut self, src: &[u8]) -> Result<&[u8], DecompressError> {
+        if self.pos + self.max_block_size > self.ring.len() {
+            self.pos = 0;
+        }
+        let start = self.pos;
+        // SAFETY: `start + max_block_size <= ring.len()`, and every pointer
+        // held by `ctx` refers to earlier output inside `ring`.  The ring is
+        // sized so the bytes overwritten at the start after a wrap are never
+        // within the 64 KB window the block can reference.
+        let n = unsafe {
+            decompress_safe_continue(
+                &mut self.ctx,
+                src.as_ptr(),
+                self.ring.as_mut_ptr().add(start),
+                src.len(),
+                self.max_block_size,
+            )?
+        };
+        self.pos = start + n;
+        Ok(&self.ring[start..self.pos])
+    }
+
+    /// Forget all history so the decoder can start a new, independent stream.
+    pub fn reset(&mut self) {
+        self.pos = 0;
+        self.ctx = Lz4StreamDecode::new();
+    }
+
+    /// The largest decompressed block this decoder accepts.
+    pub fn max_block_size(&self) -> usize {
+        self.max_block_size
+    }
+
+    /// Size of the owned ring buffer in bytes.
+    pub fn capacity(&self) -> usize {
+        self.ring.len()
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Stateless dictionary API (lines 2719-2747)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -599,3 +721,65 @@ pub unsafe fn decompress_safe_partial_using_dict(
         dict_size,
     )
 }
+
+/// Decompress an LZ4 block whose dictionary is split into two segments.
+///
+/// Ring-buffer pipelines naturally hold their history in two pieces: the
+/// most recent bytes (the *prefix*) sit directly in front of the write
+/// position, while older bytes (the *external dictionary*) live elsewhere —
+/// typically the tail of the ring before it wrapped.  This is the same layout
+/// [`decompress_safe_continue`] tracks internally, and it lets callers feed
+/// both pieces without first concatenating them into a contiguous 64 KiB
+/// buffer.
+///
+/// `window[..prefix_len]` is the prefix; decoded bytes are written starting
+/// at `window[prefix_len]`.  `ext_dict` logically precedes the prefix, so
+/// the full dictionary seen by the compressor is `ext_dict ++ prefix`.
+///
+/// Returns the number of bytes written after the prefix, or
+/// `Err(DecompressError::MalformedInput)` on invalid input or when
+/// `prefix_len > window.len()`.
+///
+/// Equivalent to C `LZ4_decompress_safe_doubleDict`, exposed with a slice
+/// interface.
+pub fn decompress_safe_using_split_dict(
+    src: &[u8],
+    window: &mut [u8],
+    prefix_len: usize,
+    ext_dict: &[u8],
+) -> Result<usize, DecompressError> {
+    if prefix_len > window.len() {
+        return Err(DecompressError::MalformedInput);
+    }
+    let max_output = window.len() - prefix_len;
+    // SAFETY:
+    //   - `dst_ptr + max_output` is the end of `window`.
+    //   - The `prefix_len` bytes before `dst_ptr` are inside `window`.
+    //   - `ext_dict` is a shared borrow, so it cannot alias `window`.
+    unsafe {
+        let dst_ptr = window.as_mut_ptr().add(prefix_len);
+        if prefix_len >= KB64_MINUS1 {
+            // The prefix alone covers the maximum match distance; the
+            // external segment is unreachable.
+            decompress_safe_with_prefix64k(src.as_ptr(), dst_ptr, src.len(), max_output)
+        } else if ext_dict.is_empty() {
+            decompress_safe_with_small_prefix(
+                src.as_ptr(),
+                dst_ptr,
+                src.len(),
+                max_output,
+                prefix_len,
+            )
+        } else {
+            decompress_safe_double_dict(
+                src.as_ptr(),
+                dst_ptr,
+                src.len(),
+                max_output,
+                prefix_len,
+                ext_dict.as_ptr(),
+                ext_dict.len(),
+            )
+        }
+    }
+}
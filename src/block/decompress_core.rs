@@ -13,12 +13,24 @@
 //! undefined behaviour.
 //!
 //! All `unsafe` blocks carry an explicit `// SAFETY:` comment.
+//!
+//! # Uninitialised output
+//!
+//! `decompress_generic` never reads an output byte in `[dst, oend)` that it
+//! has not written earlier in the same call.  Match copies read from
+//! `op - offset`, which the offset check confines to the prefix/dictionary
+//! window or to bytes already produced; the 8- and 16-byte chunked copies
+//! only run ahead of `op` by at most the offset, so every chunk they read was
+//! written by a previous chunk; and the `offset < 8` path zeroes its first
+//! four bytes before reading them.  `decompress_safe_uninit` relies on this
+//! invariant — keep it when changing the copy routines.
 
 use core::ptr;
 
 use super::types::{
     read_le16, wild_copy8, write32, DictDirective, DEC64TABLE, INC32TABLE, LASTLITERALS,
-    MATCH_SAFEGUARD_DISTANCE, MFLIMIT, MINMATCH, ML_BITS, ML_MASK, RUN_MASK, WILDCOPYLENGTH,
+    LZ4_DISTANCE_MAX, MATCH_SAFEGUARD_DISTANCE, MFLIMIT, MINMATCH, ML_BITS, ML_MASK, RUN_MASK,
+    WILDCOPYLENGTH,
 };
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -158,6 +170,7 @@ unsafe fn read_variable_length(
 ///   of backing storage to absorb wildcard-copy overruns (callers outside this
 ///   crate are responsible for reserving sufficient buffer space).
 #[allow(clippy::too_many_arguments)]
+#[inline(always)]
 pub unsafe fn decompress_generic(
     src: *const u8,
     dst: *mut u8,
@@ -168,6 +181,41 @@ pub unsafe fn decompress_generic(
     low_prefix: *const u8,
     dict_start: *const u8, // only meaningful when dict == UsingExtDict
     dict_size: usize,
+) -> Result<usize, DecompressError> {
+    decompress_generic_windowed(
+        src,
+        dst,
+        src_size,
+        output_size,
+        partial_decoding,
+        dict,
+        low_prefix,
+        dict_start,
+        dict_size,
+        LZ4_DISTANCE_MAX as usize,
+    )
+}
+
+/// [`decompress_generic`] with a declared window: any match offset greater
+/// than `max_distance` is rejected as malformed, even when the referenced
+/// bytes are available.  With `max_distance == LZ4_DISTANCE_MAX` this is
+/// exactly [`decompress_generic`].
+///
+/// # Safety
+/// Same as [`decompress_generic`].
+#[allow(clippy::too_many_arguments)]
+#[inline(always)]
+pub unsafe fn decompress_generic_windowed(
+    src: *const u8,
+    dst: *mut u8,
+    src_size: usize,
+    output_size: usize,
+    partial_decoding: bool,
+    dict: DictDirective,
+    low_prefix: *const u8,
+    dict_start: *const u8, // only meaningful when dict == UsingExtDict
+    dict_size: usize,
+    max_distance: usize,
 ) -> Result<usize, DecompressError> {
     // ── Validate top-level arguments ─────────────────────────────────────────
     if src.is_null() || (output_size as isize) < 0 {
@@ -286,6 +334,7 @@ pub unsafe fn decompress_generic(
 
             if ml != ML_MASK as usize
                 && off16 >= 8
+                && off16 <= max_distance
                 && (dict == DictDirective::WithPrefix64k || mp >= low_prefix)
             {
                 // Fast 18-byte match copy — no overlap possible (offset >= 8).
@@ -441,9 +490,13 @@ pub unsafe fn decompress_generic(
         if check_offset && (match_ptr as usize).wrapping_add(dict_size) < low_prefix as usize {
             return output_error();
         }
+        // A declared window narrower than 64 KB rejects offsets beyond it.
+        if offset > max_distance {
+            return output_error();
+        }
 
         // ── External-dictionary match (C: lines 2358-2384) ────────────────────
-        if dict == DictDirective::UsingExtDict && (match_ptr as *const u8) < low_prefix {
+        if dict == DictDirective::UsingExtDict && match_ptr < low_prefix {
             // The reference is before the current output prefix → it lives in the
             // external dictionary.
             debug_assert!(!dict_end.is_null());
@@ -727,3 +780,49 @@ pub fn decompress_safe_using_dict(
         )
     }
 }
+
+/// Decompress an LZ4 block against an optional external dictionary, rejecting
+/// any match whose offset exceeds `max_distance`.
+///
+/// For decoders that advertise a window smaller than 64 KB and keep only that
+/// much history: a block produced with a matching encoder limit (see
+/// [`Lz4Stream::set_max_distance`](super::Lz4Stream::set_max_distance))
+/// decodes as usual, while a block reaching further back fails with
+/// `Err(DecompressError::MalformedInput)` instead of reading stale bytes.
+/// `max_distance` is clamped to `[1, LZ4_DISTANCE_MAX]`.
+pub fn decompress_safe_using_dict_windowed(
+    src: &[u8],
+    dst: &mut [u8],
+    dict: &[u8],
+    max_distance: u32,
+) -> Result<usize, DecompressError> {
+    if dst.is_empty() {
+        if src.len() == 1 && src[0] == 0 {
+            return Ok(0);
+        }
+        return output_error();
+    }
+    let window = max_distance.clamp(1, LZ4_DISTANCE_MAX) as usize;
+    let mode = if dict.is_empty() {
+        DictDirective::NoDict
+    } else {
+        DictDirective::UsingExtDict
+    };
+
+    // SAFETY: as for `decompress_safe_using_dict`; with an empty dictionary
+    // the mode is NoDict and the dictionary pointer is never read.
+    unsafe {
+        decompress_generic_windowed(
+            src.as_ptr(),
+            dst.as_mut_ptr(),
+            src.len(),
+            dst.len(),
+            false,
+            mode,
+            dst.as_ptr(),
+            dict.as_ptr(),
+            dict.len(),
+            window,
+        )
+    }
+}
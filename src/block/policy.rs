@@ -0,0 +1,182 @@
+//! Decode policies: limits on the shape of accepted blocks.
+//!
+//! [`decompress_safe`](super::decompress_safe) accepts every block the format
+//! allows.  A sandboxed decoder may want less: no back-references further
+//! than a small window, no huge stored literal runs, or a bound on the
+//! number of sequences (and so on decode time per block).
+//! [`decompress_safe_with_policy`] checks a [`DecodePolicy`] before decoding
+//! and reports a breach as [`PolicyError::Violation`], separately from
+//! [`PolicyError::Malformed`] for blocks that are not valid LZ4 at all.
+//!
+//! The limits are checked by walking the block's sequence headers before
+//! any output is written, so a rejected block leaves `dst` untouched and the
+//! decode loop itself runs at full speed.  Problems are reported in stream
+//! order: a block that breaks the policy before it becomes malformed yields
+//! a violation.
+
+use core::fmt;
+
+use super::safe::{for_each_sequence, DecompressError};
+
+/// Shape limits enforced by [`decompress_safe_with_policy`].
+///
+/// The default policy accepts every valid block; tighten individual fields
+/// with struct-update syntax:
+///
+/// ```
+/// use lz4::block::DecodePolicy;
+///
+/// let policy = DecodePolicy { max_offset: 4096, ..DecodePolicy::default() };
+/// assert_eq!(policy.max_sequences, usize::MAX);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodePolicy {
+    /// Largest accepted match offset, in bytes.  The format maximum is
+    /// 65 535.
+    pub max_offset: usize,
+    /// Largest accepted literal run in one sequence, the final one included.
+    /// Incompressible input is stored as a single run as long as the block,
+    /// so this also caps how much raw data a block may carry.
+    pub max_literal_run: usize,
+    /// Largest accepted number of sequences (tokens) in one block.
+    pub max_sequences: usize,
+}
+
+impl DecodePolicy {
+    /// A policy that accepts every valid block.
+    pub const UNRESTRICTED: DecodePolicy = DecodePolicy {
+        max_offset: 65_535,
+        max_literal_run: usize::MAX,
+        max_sequences: usize::MAX,
+    };
+}
+
+impl Default for DecodePolicy {
+    fn default() -> Self {
+        DecodePolicy::UNRESTRICTED
+    }
+}
+
+/// The [`DecodePolicy`] limit a block broke.
+///
+/// `sequence` is the zero-based index of the offending sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// A match reaches further back than [`DecodePolicy::max_offset`].
+    Offset { sequence: usize, offset: usize },
+    /// A literal run is longer than [`DecodePolicy::max_literal_run`].
+    LiteralRun { sequence: usize, len: usize },
+    /// The block has more than [`DecodePolicy::max_sequences`] sequences.
+    TooManySequences { limit: usize },
+}
+
+/// Errors returned by [`decompress_safe_with_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyError {
+    /// The block is valid LZ4 but breaks the policy.
+    Violation(PolicyViolation),
+    /// The block is malformed or truncated, or `dst` is too small.
+    Malformed,
+}
+
+impl From<DecompressError> for PolicyError {
+    fn from(_: DecompressError) -> Self {
+        PolicyError::Malformed
+    }
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::Offset { sequence, offset } => {
+                write!(
+                    f,
+                    "sequence {sequence}: match offset {offset} exceeds policy"
+                )
+            }
+            PolicyViolation::LiteralRun { sequence, len } => {
+                write!(
+                    f,
+                    "sequence {sequence}: literal run of {len} bytes exceeds policy"
+                )
+            }
+            PolicyViolation::TooManySequences { limit } => {
+                write!(f, "block has more than {limit} sequences")
+            }
+        }
+    }
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyError::Violation(v) => write!(f, "decode policy violation: {v}"),
+            PolicyError::Malformed => f.write_str("malformed LZ4 block"),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+/// Checks the block `src` against `policy` without decoding it.
+pub fn check_policy(src: &[u8], policy: &DecodePolicy) -> Result<(), PolicyError> {
+    let mut sequence = 0usize;
+    for_each_sequence(src, |seq| {
+        if sequence >= policy.max_sequences {
+            return Err(PolicyError::Violation(PolicyViolation::TooManySequences {
+                limit: policy.max_sequences,
+            }));
+        }
+        if seq.lit_len > policy.max_literal_run {
+            return Err(PolicyError::Violation(PolicyViolation::LiteralRun {
+                sequence,
+                len: seq.lit_len,
+            }));
+        }
+        if seq.offset > policy.max_offset {
+            return Err(PolicyError::Violation(PolicyViolation::Offset {
+                sequence,
+                offset: seq.offset,
+            }));
+        }
+        sequence += 1;
+        Ok(())
+    })
+}
+
+/// Decompresses the block `src` into `dst` if it satisfies `policy`.
+///
+/// Behaves like [`decompress_safe`](super::decompress_safe) for blocks within
+/// the policy.  A block that breaks it returns
+/// `Err(PolicyError::Violation(_))` with `dst` unmodified.
+pub fn decompress_safe_with_policy(
+    src: &[u8],
+    dst: &mut [u8],
+    policy: &DecodePolicy,
+) -> Result<usize, PolicyError> {
+    check_policy(src, policy)?;
+    Ok(super::decompress_safe(src, dst)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unrestricted_accepts_literal_only_block() {
+        // Token 0x50: five literals, no match.
+        let src = [0x50, b'h', b'e', b'l', b'l', b'o'];
+        let mut dst = [0u8; 5];
+        let n = decompress_safe_with_policy(&src, &mut dst, &DecodePolicy::default());
+        assert_eq!(n, Ok(5));
+        assert_eq!(&dst, b"hello");
+    }
+
+    #[test]
+    fn empty_block_is_malformed() {
+        assert_eq!(
+            check_policy(&[], &DecodePolicy::default()),
+            Err(PolicyError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,574 @@
+//! Safe-Rust LZ4 block codec.
+//!
+//! A slice-indexed implementation of the LZ4 block format with no `unsafe`
+//! code, used as the block engine when the crate is built with the
+//! `forbid-unsafe` feature.  It is always compiled so that its output can be
+//! cross-checked against the pointer-based port in [`compress`](super::compress)
+//! and [`decompress_core`](super::decompress_core).
+//!
+//! The compressor is the single-pass greedy parser of `LZ4_compress_fast`
+//! (4-byte hash, 4096-entry table, acceleration-driven skipping); its output is
+//! a valid LZ4 block but is not guaranteed to be byte-identical to the
+//! reference encoder.  The decompressor enforces the same end-of-block rules as
+//! `LZ4_decompress_safe`.
+//!
+//! Every byte access is bounds-checked, so throughput is lower than the
+//! default build (see the `forbid-unsafe` section of the README).
+
+#[cfg(not(feature = "forbid-unsafe"))]
+pub use super::compress::Lz4Error;
+#[cfg(not(feature = "forbid-unsafe"))]
+pub use super::decompress_core::DecompressError;
+
+/// Errors returned by LZ4 block compression functions.
+#[cfg(feature = "forbid-unsafe")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lz4Error {
+    /// The output buffer is too small to hold the compressed data.
+    OutputTooSmall,
+    /// The input exceeds `LZ4_MAX_INPUT_SIZE`.
+    InputTooLarge,
+}
+
+/// Errors returned by LZ4 block decompression.
+#[cfg(feature = "forbid-unsafe")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecompressError {
+    /// The compressed data is malformed, truncated, or the dimensions supplied
+    /// by the caller are inconsistent.
+    MalformedInput,
+}
+
+/// Maximum allowed input size (2 113 929 216 bytes).
+pub const LZ4_MAX_INPUT_SIZE: u32 = 0x7E00_0000;
+/// Default acceleration factor (equals 1 — check every position).
+pub const LZ4_ACCELERATION_DEFAULT: i32 = 1;
+/// Maximum allowed acceleration factor.
+pub const LZ4_ACCELERATION_MAX: i32 = 65_537;
+
+const MINMATCH: usize = 4;
+const LASTLITERALS: usize = 5;
+const MFLIMIT: usize = 12;
+const MAX_DISTANCE: usize = 65_535;
+const ML_MASK: usize = 15;
+const RUN_MASK: usize = 15;
+const HASH_LOG: u32 = 12;
+const SKIP_TRIGGER: u32 = 6;
+/// Width of the fixed-size copies used on the decoder fast path.
+const WILD: usize = 16;
+
+/// Worst-case compressed size for a given input size.
+///
+/// Returns 0 if `input_size` exceeds `LZ4_MAX_INPUT_SIZE`.
+/// Equivalent to `LZ4_compressBound`.
+#[inline]
+pub fn compress_bound(input_size: i32) -> i32 {
+    if input_size < 0 || (input_size as u32) > LZ4_MAX_INPUT_SIZE {
+        0
+    } else {
+        input_size + (input_size / 255) + 16
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Compression
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[inline(always)]
+fn read32(buf: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([buf[pos], buf[pos + 1], buf[pos + 2], buf[pos + 3]])
+}
+
+#[inline(always)]
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_LOG)) as usize
+}
+
+/// Appends `len` as a run of 255-valued bytes plus remainder (the LZ4
+/// length-extension encoding) at `dst[op..]`.
+#[inline(always)]
+fn write_length(dst: &mut [u8], mut op: usize, mut len: usize) -> usize {
+    while len >= 255 {
+        dst[op] = 255;
+        op += 1;
+        len -= 255;
+    }
+    dst[op] = len as u8;
+    op + 1
+}
+
+/// Writes the final literal-only sequence `input[anchor..]` at `dst[op..]`.
+fn write_last_literals(
+    input: &[u8],
+    anchor: usize,
+    dst: &mut [u8],
+    mut op: usize,
+) -> Result<usize, Lz4Error> {
+    let lit_len = input.len() - anchor;
+    if op + 1 + lit_len + (lit_len + 255 - RUN_MASK) / 255 > dst.len() {
+        return Err(Lz4Error::OutputTooSmall);
+    }
+    if lit_len >= RUN_MASK {
+        dst[op] = (RUN_MASK << 4) as u8;
+        op = write_length(dst, op + 1, lit_len - RUN_MASK);
+    } else {
+        dst[op] = (lit_len << 4) as u8;
+        op += 1;
+    }
+    dst[op..op + lit_len].copy_from_slice(&input[anchor..]);
+    Ok(op + lit_len)
+}
+
+/// Compresses `input[start..]` into `dst`, allowing matches to reach back at
+/// most `max_distance` bytes, into the history in `input[..start]` as well.
+///
+/// Returns the compressed size.  This is the engine behind [`compress_fast`]
+/// and the linked-block path of the safe frame encoder.
+pub(crate) fn compress_with_history(
+    input: &[u8],
+    start: usize,
+    dst: &mut [u8],
+    acceleration: i32,
+    max_distance: usize,
+) -> Result<usize, Lz4Error> {
+    if input.len() - start > LZ4_MAX_INPUT_SIZE as usize {
+        return Err(Lz4Error::InputTooLarge);
+    }
+    let acceleration = acceleration.clamp(1, LZ4_ACCELERATION_MAX) as u32;
+    let end = input.len();
+    let mut op = 0usize;
+    let mut anchor = start;
+
+    if end - start < MFLIMIT + 1 {
+        return write_last_literals(input, anchor, dst, op);
+    }
+
+    let mflimit = end - MFLIMIT;
+    let match_limit = end - LASTLITERALS;
+    let low = start.saturating_sub(max_distance);
+    let mut table = vec![0u32; 1 << HASH_LOG];
+    // Positions are stored relative to `low`, so 0 doubles as "empty"; a
+    // stale candidate is harmless because every match is verified.
+    let mut p = low;
+    while p + MINMATCH <= start {
+        table[hash(read32(input, p))] = (p - low) as u32;
+        p += 3;
+    }
+
+    let mut ip = start;
+    'sequences: loop {
+        // ── Find a match ───────────────────────────────────────────────────
+        let mut search = acceleration << SKIP_TRIGGER;
+        let candidate = loop {
+            if ip >= mflimit {
+                break 'sequences;
+            }
+            let seq = read32(input, ip);
+            let h = hash(seq);
+            let cand = low + table[h] as usize;
+            table[h] = (ip - low) as u32;
+            if cand < ip && ip - cand <= max_distance && read32(input, cand) == seq {
+                break cand;
+            }
+            ip += (search >> SKIP_TRIGGER) as usize;
+            search += 1;
+        };
+
+        // ── Extend backwards, then forwards ────────────────────────────────
+        let mut m = candidate;
+        while ip > anchor && m > low && input[ip - 1] == input[m - 1] {
+            ip -= 1;
+            m -= 1;
+        }
+        let mut len = MINMATCH;
+        while ip + len < match_limit && input[m + len] == input[ip + len] {
+            len += 1;
+        }
+
+        // ── Emit the sequence ──────────────────────────────────────────────
+        let lit_len = ip - anchor;
+        let needed = 1 + lit_len + lit_len / 255 + 1 + 2 + (len - MINMATCH) / 255 + 1;
+        if op + needed > dst.len() {
+            return Err(Lz4Error::OutputTooSmall);
+        }
+        let token_pos = op;
+        op += 1;
+        let lit_code = if lit_len >= RUN_MASK {
+            op = write_length(dst, op, lit_len - RUN_MASK);
+            RUN_MASK
+        } else {
+            lit_len
+        };
+        dst[op..op + lit_len].copy_from_slice(&input[anchor..ip]);
+        op += lit_len;
+        dst[op..op + 2].copy_from_slice(&((ip - m) as u16).to_le_bytes());
+        op += 2;
+        let ml = len - MINMATCH;
+        let ml_code = if ml >= ML_MASK {
+            op = write_length(dst, op, ml - ML_MASK);
+            ML_MASK
+        } else {
+            ml
+        };
+        dst[token_pos] = ((lit_code << 4) | ml_code) as u8;
+
+        ip += len;
+        anchor = ip;
+        if ip >= mflimit {
+            break;
+        }
+        table[hash(read32(input, ip - 2))] = (ip - 2 - low) as u32;
+    }
+
+    write_last_literals(input, anchor, dst, op)
+}
+
+/// Compress `src` into `dst` with a user-supplied `acceleration` factor.
+///
+/// Equivalent to `LZ4_compress_fast`.
+///
+/// Returns the number of bytes written to `dst`, or `Err(Lz4Error::OutputTooSmall)`.
+pub fn compress_fast(src: &[u8], dst: &mut [u8], acceleration: i32) -> Result<usize, Lz4Error> {
+    compress_with_history(src, 0, dst, acceleration, MAX_DISTANCE)
+}
+
+/// Compress `src` into `dst` with the default acceleration factor (1).
+///
+/// Equivalent to `LZ4_compress_default`.
+pub fn compress_default(src: &[u8], dst: &mut [u8]) -> Result<usize, Lz4Error> {
+    compress_fast(src, dst, LZ4_ACCELERATION_DEFAULT)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Decompression
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Reads an LZ4 length extension starting at `src[*ip]`.
+#[inline(always)]
+fn read_length(src: &[u8], ip: &mut usize) -> Result<usize, DecompressError> {
+    let mut len = 0usize;
+    loop {
+        let b = *src.get(*ip).ok_or(DecompressError::MalformedInput)?;
+        *ip += 1;
+        len = len
+            .checked_add(b as usize)
+            .ok_or(DecompressError::MalformedInput)?;
+        if b != 255 {
+            return Ok(len);
+        }
+    }
+}
+
+/// Decodes the block `src` into `dst[out_start..]`.
+///
+/// Matches may reach back into `dst[..out_start]` (prefix history) and then
+/// into `ext_dict` (bytes logically preceding `dst`).  With `partial`, decoding
+/// stops once `limit` output bytes exist; otherwise `limit` is the hard output
+/// capacity and the block must end exactly on its final literal run.
+///
+/// Returns the number of bytes written after `out_start`.
+pub(crate) fn decode_block(
+    src: &[u8],
+    dst: &mut [u8],
+    out_start: usize,
+    ext_dict: &[u8],
+    limit: usize,
+    partial: bool,
+) -> Result<usize, DecompressError> {
+    let oend = (out_start + limit).min(dst.len());
+    let mut ip = 0usize;
+    let mut op = out_start;
+
+    if src.is_empty() {
+        return Err(DecompressError::MalformedInput);
+    }
+
+    loop {
+        let token = src[ip] as usize;
+        ip += 1;
+
+        // ── Literals ───────────────────────────────────────────────────────
+        let mut lit_len = token >> 4;
+        if lit_len == RUN_MASK {
+            lit_len += read_length(src, &mut ip)?;
+        }
+        let lit_end = ip
+            .checked_add(lit_len)
+            .ok_or(DecompressError::MalformedInput)?;
+        if partial && op + lit_len >= oend {
+            let n = (oend - op).min(src.len().saturating_sub(ip));
+            dst[op..op + n].copy_from_slice(&src[ip..ip + n]);
+            return Ok(op + n - out_start);
+        }
+        if lit_end > src.len() || op + lit_len > oend {
+            return Err(DecompressError::MalformedInput);
+        }
+        if lit_len <= WILD && ip + WILD <= src.len() && op + WILD <= oend {
+            // Fixed-width copy: compiles to two moves instead of a memcpy call.
+            // Bytes past `op + lit_len` are overwritten by later sequences.
+            dst[op..op + WILD].copy_from_slice(&src[ip..ip + WILD]);
+        } else {
+            dst[op..op + lit_len].copy_from_slice(&src[ip..lit_end]);
+        }
+        op += lit_len;
+        ip = lit_end;
+
+        if ip == src.len() {
+            // The last sequence is literals only.
+            return Ok(op - out_start);
+        }
+        if partial && ip + 2 > src.len() {
+            return Ok(op - out_start);
+        }
+
+        // ── Match ──────────────────────────────────────────────────────────
+        if ip + 2 > src.len() {
+            return Err(DecompressError::MalformedInput);
+        }
+        let offset = u16::from_le_bytes([src[ip], src[ip + 1]]) as usize;
+        ip += 2;
+        let mut match_len = token & ML_MASK;
+        if match_len == ML_MASK {
+            match_len += read_length(src, &mut ip)?;
+        }
+        match_len += MINMATCH;
+        if offset == 0 || offset > op + ext_dict.len() {
+            return Err(DecompressError::MalformedInput);
+        }
+        if op + match_len > oend {
+            if !partial {
+                return Err(DecompressError::MalformedInput);
+            }
+            match_len = oend - op;
+        }
+        // A full block never ends on a match: at least LASTLITERALS must follow.
+        if !partial && ip >= src.len() {
+            return Err(DecompressError::MalformedInput);
+        }
+
+        let mut remaining = match_len;
+        if offset > op {
+            // Starts inside the external dictionary.
+            let back = offset - op;
+            let from = ext_dict.len() - back;
+            let n = back.min(remaining);
+            dst[op..op + n].copy_from_slice(&ext_dict[from..from + n]);
+            op += n;
+            remaining -= n;
+            if remaining > 0 {
+                copy_overlapping(dst, 0, op, remaining);
+                op += remaining;
+            }
+        } else if offset >= WILD && op + remaining + WILD <= oend {
+            let mut from = op - offset;
+            let end = op + remaining;
+            while op < end {
+                dst.copy_within(from..from + WILD, op);
+                from += WILD;
+                op += WILD;
+            }
+            op = end;
+        } else {
+            copy_overlapping(dst, op - offset, op, remaining);
+            op += remaining;
+        }
+
+        if partial && op >= oend {
+            return Ok(op - out_start);
+        }
+    }
+}
+
+/// Copies `len` bytes from `dst[from..]` to `dst[to..]` where `from < to`,
+/// replicating the pattern when the ranges overlap.
+#[inline(always)]
+fn copy_overlapping(dst: &mut [u8], from: usize, to: usize, len: usize) {
+    if to - from >= len {
+        dst.copy_within(from..from + len, to);
+        return;
+    }
+    // `dst[from..to]` is one period of the pattern; doubling the copied span
+    // each round keeps it a whole number of periods.
+    let mut done = 0;
+    while done < len {
+        let n = (to + done - from).min(len - done);
+        dst.copy_within(from..from + n, to + done);
+        done += n;
+    }
+}
+
+/// Decompress a full LZ4 block with no dictionary.
+///
+/// Equivalent to `LZ4_decompress_safe`.
+///
+/// Returns the number of bytes written into `dst`, or
+/// `Err(DecompressError::MalformedInput)` for invalid input.
+pub fn decompress_safe(src: &[u8], dst: &mut [u8]) -> Result<usize, DecompressError> {
+    let cap = dst.len();
+    decode_block(src, dst, 0, &[], cap, false)
+}
+
+/// Decompress up to `target_output_size` bytes from an LZ4 block.
+///
+/// Equivalent to `LZ4_decompress_safe_partial`.
+pub fn decompress_safe_partial(
+    src: &[u8],
+    dst: &mut [u8],
+    target_output_size: usize,
+) -> Result<usize, DecompressError> {
+    let limit = target_output_size.min(dst.len());
+    decode_block(src, dst, 0, &[], limit, true)
+}
+
+/// Decompress an LZ4 block whose matches may reference `dict`, the bytes
+/// that logically precede `dst`.
+///
+/// Equivalent to `LZ4_decompress_safe_usingDict` with a slice dictionary.
+pub fn decompress_safe_with_dict(
+    src: &[u8],
+    dst: &mut [u8],
+    dict: &[u8],
+) -> Result<usize, DecompressError> {
+    let cap = dst.len();
+    let dict = &dict[dict.len().saturating_sub(MAX_DISTANCE)..];
+    decode_block(src, dst, 0, dict, cap, false)
+}
+
+/// Exact decompressed size of the block `src`, found by walking its
+/// sequences without writing any output.
+///
+/// Lets callers that receive raw blocks without a stored size allocate the
+/// output buffer exactly.  The block structure is checked with the same
+/// end-of-block rules as [`decompress_safe`], but match offsets are not
+/// checked against the history, so the size is also right for blocks that
+/// reference a dictionary.  Decoding can therefore still fail on a block
+/// this function accepts.
+///
+/// Returns `Err(DecompressError::MalformedInput)` for a truncated or
+/// malformed block.
+pub fn decompressed_size(src: &[u8]) -> Result<usize, DecompressError> {
+    let mut size = 0usize;
+    for_each_sequence(src, |seq| {
+        size = size
+            .checked_add(seq.lit_len)
+            .and_then(|s| s.checked_add(seq.match_len))
+            .ok_or(DecompressError::MalformedInput)?;
+        Ok(())
+    })?;
+    Ok(size)
+}
+
+/// One decoded sequence header: a literal run followed by an optional match.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Sequence {
+    /// Number of literal bytes.
+    pub lit_len: usize,
+    /// Match offset; `0` for the final, literals-only sequence.
+    pub offset: usize,
+    /// Match length including `MINMATCH`; `0` for the final sequence.
+    pub match_len: usize,
+}
+
+/// Walks the sequences of the block `src` in order, calling `visit` for each
+/// without touching any output.
+///
+/// Structural errors (truncation, zero offset, a block ending on a match) are
+/// reported as `DecompressError::MalformedInput`; an error returned by
+/// `visit` stops the walk and is passed through.
+pub(crate) fn for_each_sequence<E: From<DecompressError>>(
+    src: &[u8],
+    mut visit: impl FnMut(Sequence) -> Result<(), E>,
+) -> Result<(), E> {
+    if src.is_empty() {
+        return Err(DecompressError::MalformedInput.into());
+    }
+    let mut ip = 0usize;
+    loop {
+        let token = src[ip] as usize;
+        ip += 1;
+
+        let mut lit_len = token >> 4;
+        if lit_len == RUN_MASK {
+            lit_len += read_length(src, &mut ip)?;
+        }
+        ip = ip
+            .checked_add(lit_len)
+            .filter(|&end| end <= src.len())
+            .ok_or(DecompressError::MalformedInput)?;
+        if ip == src.len() {
+            // The last sequence is literals only.
+            return visit(Sequence {
+                lit_len,
+                offset: 0,
+                match_len: 0,
+            });
+        }
+
+        if ip + 2 > src.len() {
+            return Err(DecompressError::MalformedInput.into());
+        }
+        let offset = u16::from_le_bytes([src[ip], src[ip + 1]]) as usize;
+        ip += 2;
+        let mut match_len = token & ML_MASK;
+        if match_len == ML_MASK {
+            match_len += read_length(src, &mut ip)?;
+        }
+        // A block never ends on a match: at least LASTLITERALS must follow.
+        if offset == 0 || ip >= src.len() {
+            return Err(DecompressError::MalformedInput.into());
+        }
+        visit(Sequence {
+            lit_len,
+            offset,
+            match_len: match_len
+                .checked_add(MINMATCH)
+                .ok_or(DecompressError::MalformedInput)?,
+        })?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(data: &[u8]) {
+        let mut c = vec![0u8; compress_bound(data.len() as i32) as usize];
+        let n = compress_default(data, &mut c).unwrap();
+        let mut d = vec![0u8; data.len()];
+        assert_eq!(decompress_safe(&c[..n], &mut d), Ok(data.len()));
+        assert_eq!(d, data);
+    }
+
+    #[test]
+    fn roundtrip_shapes() {
+        roundtrip(b"");
+        roundtrip(b"a");
+        roundtrip(&[7u8; 1000]);
+        roundtrip(&b"abcabcabcabcabcabcabcabc-overlap".repeat(50));
+        let noise: Vec<u8> = (0..5000u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+        roundtrip(&noise);
+    }
+
+    #[test]
+    fn output_too_small() {
+        let data = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        let mut c = [0u8; 4];
+        assert_eq!(
+            compress_default(&data, &mut c),
+            Err(Lz4Error::OutputTooSmall)
+        );
+    }
+
+    #[test]
+    fn rejects_zero_offset() {
+        // token: 0 literals, match len 4; offset 0.
+        let src = [0x00u8, 0x00, 0x00, 0x10, b'x'];
+        let mut dst = [0u8; 32];
+        assert_eq!(
+            decompress_safe(&src, &mut dst),
+            Err(DecompressError::MalformedInput)
+        );
+    }
+}
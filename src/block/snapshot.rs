@@ -0,0 +1,321 @@
+//! Portable snapshots of fast-compressor stream state.
+//!
+//! [`Lz4Stream::export`] serialises a stream into a self-contained byte
+//! string, and [`Lz4Stream::import`] rebuilds it, possibly in another process
+//! or on another machine.  A service can hand a live streaming session to its
+//! replacement this way: blocks compressed after the import reference the
+//! same history, and produce the same bytes, as they would have in the
+//! original process.
+//!
+//! The snapshot holds no pointers.  The history window the stream references
+//! (at most 64 KB) is copied in, and every integer is little-endian:
+//!
+//! | Offset  | Size    | Field |
+//! |---------|---------|-------|
+//! | 0       | 4       | magic `"LZ4S"` |
+//! | 4       | 1       | format version ([`STATE_VERSION`]) |
+//! | 5       | 1       | table type (0 = cleared, 2 = 32-bit indices) |
+//! | 6       | 2       | reserved, zero |
+//! | 8       | 4       | `current_offset` |
+//! | 12      | 4       | `dict_size` |
+//! | 16      | 8       | `hash_seed` |
+//! | 24      | 16 384  | hash table, 4096 × `u32` |
+//! | 16 408  | `dict_size` | history bytes |
+//! | end − 4 | 4       | XXH32 (seed 0) of everything before it |
+//!
+//! Importing checks the checksum and that every table entry lies inside the
+//! stream's history, so a damaged snapshot cannot steer the match finder
+//! outside its buffers.  The table is only as useful as the hash it was built
+//! with: a snapshot taken on a target that hashes differently (32-bit or
+//! big-endian, see [`HashKey`](super::HashKey)) imports safely, but its
+//! entries rarely produce matches.
+
+use core::fmt;
+
+use super::stream::Lz4Stream;
+use super::types::{TableType, KB, LZ4_HASH_SIZE_U32};
+use crate::xxhash::xxh32_oneshot;
+
+/// Magic bytes opening a stream-state snapshot.
+pub const STATE_MAGIC: [u8; 4] = *b"LZ4S";
+
+/// Snapshot format version written by [`Lz4Stream::export`].
+pub const STATE_VERSION: u8 = 1;
+
+const HEADER_SIZE: usize = 24;
+const TABLE_BYTES: usize = LZ4_HASH_SIZE_U32 * 4;
+const CHECKSUM_SIZE: usize = 4;
+const MAX_DICT_SIZE: u32 = 64 * KB as u32;
+
+/// Reasons a stream cannot be exported or a snapshot cannot be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    /// The stream has a dictionary stream attached with
+    /// [`Lz4Stream::attach_dictionary`]; it lives outside the stream and is
+    /// not captured.  Export after the next block, once the stream has
+    /// absorbed it.
+    DictionaryAttached,
+    /// The hash table is not in the 32-bit index layout the streaming API
+    /// uses (it was last used by a one-shot 16-bit or pointer-table call).
+    UnsupportedTable,
+    /// The snapshot is shorter than its header or declared history.
+    Truncated,
+    /// The snapshot does not start with [`STATE_MAGIC`].
+    BadMagic,
+    /// The snapshot was written by an unknown format version.
+    UnsupportedVersion(u8),
+    /// The trailing checksum does not match the snapshot contents.
+    ChecksumMismatch,
+    /// The fields are inconsistent, e.g. a table entry past the stream
+    /// position or a history longer than 64 KB.
+    Corrupt,
+    /// `dict_buffer` is smaller than the snapshot's history.
+    BufferTooSmall,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::DictionaryAttached => f.write_str("stream has an attached dictionary"),
+            StateError::UnsupportedTable => f.write_str("hash table is not in streaming layout"),
+            StateError::Truncated => f.write_str("stream state is truncated"),
+            StateError::BadMagic => f.write_str("not an LZ4 stream state"),
+            StateError::UnsupportedVersion(v) => {
+                write!(f, "unsupported stream state version {v}")
+            }
+            StateError::ChecksumMismatch => f.write_str("stream state checksum mismatch"),
+            StateError::Corrupt => f.write_str("stream state is corrupt"),
+            StateError::BufferTooSmall => f.write_str("dictionary buffer too small"),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl Lz4Stream {
+    /// Serialise the stream state, history included, into a portable snapshot.
+    ///
+    /// The snapshot is at most about 80 KB and is independent of addresses,
+    /// pointer width and byte order.  The stream is not modified.
+    ///
+    /// Fails if a dictionary stream is attached or the hash table is not in
+    /// the streaming layout; see [`StateError`].
+    pub fn export(&self) -> Result<Vec<u8>, StateError> {
+        let st = &self.internal;
+        if !st.dict_ctx.is_null() {
+            return Err(StateError::DictionaryAttached);
+        }
+        let table_type = TableType::from(st.table_type);
+        if !matches!(table_type, TableType::ClearedTable | TableType::ByU32) {
+            return Err(StateError::UnsupportedTable);
+        }
+
+        let dict_size = st.dict_size as usize;
+        let mut out = Vec::with_capacity(HEADER_SIZE + TABLE_BYTES + dict_size + CHECKSUM_SIZE);
+        out.extend_from_slice(&STATE_MAGIC);
+        out.extend_from_slice(&[STATE_VERSION, table_type as u8, 0, 0]);
+        out.extend_from_slice(&st.current_offset.to_le_bytes());
+        out.extend_from_slice(&st.dict_size.to_le_bytes());
+        out.extend_from_slice(&st.hash_seed.to_le_bytes());
+        for entry in &st.hash_table {
+            out.extend_from_slice(&entry.to_le_bytes());
+        }
+        if dict_size > 0 {
+            // SAFETY: a stream's `dictionary` covers `dict_size` readable bytes
+            // for as long as the stream may compress against it.
+            let history = unsafe { core::slice::from_raw_parts(st.dictionary, dict_size) };
+            out.extend_from_slice(history);
+        }
+        let checksum = xxh32_oneshot(&out, 0);
+        out.extend_from_slice(&checksum.to_le_bytes());
+        Ok(out)
+    }
+
+    /// Restore a snapshot written by [`export`](Self::export) into this stream.
+    ///
+    /// The history is copied to the start of `dict_buffer`, which must hold
+    /// at least that many bytes (64 KB always suffices).  As with
+    /// [`save_dict`](Self::save_dict), the stream then references
+    /// `dict_buffer`, which must stay alive and unmodified until the next
+    /// block has been compressed.  Any attached dictionary is detached.  The
+    /// limit set with [`set_max_distance`](Self::set_max_distance) is
+    /// configuration, not state: snapshots do not carry it, and this stream
+    /// keeps its own.
+    ///
+    /// On error the stream is left unchanged.
+    pub fn import(&mut self, state: &[u8], dict_buffer: &mut [u8]) -> Result<(), StateError> {
+        if state.len() < HEADER_SIZE + TABLE_BYTES + CHECKSUM_SIZE {
+            return Err(StateError::Truncated);
+        }
+        if state[..4] != STATE_MAGIC {
+            return Err(StateError::BadMagic);
+        }
+        if state[4] != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(state[4]));
+        }
+        let u32_at = |pos: usize| u32::from_le_bytes(state[pos..pos + 4].try_into().unwrap());
+        let current_offset = u32_at(8);
+        let dict_size = u32_at(12);
+        if dict_size > MAX_DICT_SIZE || dict_size > current_offset {
+            return Err(StateError::Corrupt);
+        }
+        let body_len = HEADER_SIZE + TABLE_BYTES + dict_size as usize;
+        if state.len() != body_len + CHECKSUM_SIZE {
+            return Err(if state.len() < body_len + CHECKSUM_SIZE {
+                StateError::Truncated
+            } else {
+                StateError::Corrupt
+            });
+        }
+        if xxh32_oneshot(&state[..body_len], 0) != u32_at(body_len) {
+            return Err(StateError::ChecksumMismatch);
+        }
+
+        let table_type = match state[5] {
+            0 => TableType::ClearedTable,
+            2 => TableType::ByU32,
+            _ => return Err(StateError::Corrupt),
+        };
+        if state[6..8] != [0, 0] {
+            return Err(StateError::Corrupt);
+        }
+        let mut hash_table = [0u32; LZ4_HASH_SIZE_U32];
+        for (i, slot) in hash_table.iter_mut().enumerate() {
+            *slot = u32_at(HEADER_SIZE + 4 * i);
+            // An index past the stream position would point past the data
+            // the next block can see.
+            if *slot > current_offset {
+                return Err(StateError::Corrupt);
+            }
+        }
+        let history = &state[HEADER_SIZE + TABLE_BYTES..body_len];
+        let dict_buffer = dict_buffer
+            .get_mut(..history.len())
+            .ok_or(StateError::BufferTooSmall)?;
+        dict_buffer.copy_from_slice(history);
+
+        let st = &mut self.internal;
+        st.hash_table = hash_table;
+        st.dictionary = dict_buffer.as_ptr();
+        st.dict_ctx = core::ptr::null();
+        st.current_offset = current_offset;
+        st.table_type = table_type as u32;
+        st.dict_size = dict_size;
+        st.hash_seed = u64::from_le_bytes(state[16..24].try_into().unwrap());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(seed: u32, len: usize) -> Vec<u8> {
+        (0..len as u32)
+            .flat_map(|i| format!("{}:{} ", (i * seed) % 251, i % 17).into_bytes())
+            .take(len)
+            .collect()
+    }
+
+    /// A stream with 32 KB of history at a non-zero offset.
+    fn primed_stream(history: &[u8]) -> Box<Lz4Stream> {
+        let mut s = Lz4Stream::new();
+        let mut dst = vec![0u8; 64 * KB];
+        assert!(s.compress_fast_continue(history, &mut dst, 1) > 0);
+        s
+    }
+
+    #[test]
+    fn round_trip_continues_identically() {
+        let history = text(7, 32 * KB);
+        let next = text(11, 20 * KB);
+        let mut original = primed_stream(&history);
+        let snapshot = original.export().unwrap();
+
+        let mut dict = vec![0u8; 64 * KB];
+        let mut restored = Lz4Stream::new();
+        restored.import(&snapshot, &mut dict).unwrap();
+
+        let mut a = vec![0u8; 64 * KB];
+        let mut b = vec![0u8; 64 * KB];
+        let na = original.compress_fast_continue(&next, &mut a, 1);
+        let nb = restored.compress_fast_continue(&next, &mut b, 1);
+        assert!(na > 0);
+        assert_eq!(a[..na as usize], b[..nb as usize]);
+    }
+
+    #[test]
+    fn rejects_damaged_snapshots() {
+        let history = text(3, 8 * KB);
+        let s = primed_stream(&history);
+        let snapshot = s.export().unwrap();
+        let mut dict = vec![0u8; 64 * KB];
+        let mut target = Lz4Stream::new();
+
+        assert_eq!(
+            target.import(&snapshot[..100], &mut dict),
+            Err(StateError::Truncated)
+        );
+        assert_eq!(
+            target.import(&snapshot[..snapshot.len() - 1], &mut dict),
+            Err(StateError::Truncated)
+        );
+        let mut bad = snapshot.clone();
+        bad[0] = b'X';
+        assert_eq!(target.import(&bad, &mut dict), Err(StateError::BadMagic));
+        let mut bad = snapshot.clone();
+        bad[4] = 9;
+        assert_eq!(
+            target.import(&bad, &mut dict),
+            Err(StateError::UnsupportedVersion(9))
+        );
+        let mut bad = snapshot.clone();
+        bad[HEADER_SIZE + 40] ^= 1;
+        assert_eq!(
+            target.import(&bad, &mut dict),
+            Err(StateError::ChecksumMismatch)
+        );
+        assert_eq!(
+            target.import(&snapshot, &mut dict[..100]),
+            Err(StateError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn rejects_index_past_position_even_with_valid_checksum() {
+        let s = primed_stream(&text(5, 4 * KB));
+        let mut snapshot = s.export().unwrap();
+        let body_len = snapshot.len() - CHECKSUM_SIZE;
+        snapshot[HEADER_SIZE..HEADER_SIZE + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let checksum = xxh32_oneshot(&snapshot[..body_len], 0);
+        snapshot[body_len..].copy_from_slice(&checksum.to_le_bytes());
+
+        let mut dict = vec![0u8; 64 * KB];
+        let mut target = Lz4Stream::new();
+        assert_eq!(
+            target.import(&snapshot, &mut dict),
+            Err(StateError::Corrupt)
+        );
+    }
+
+    #[test]
+    fn refuses_attached_dictionary() {
+        let dict_data = text(13, 16 * KB);
+        let mut dict_stream = Lz4Stream::new();
+        dict_stream.load_dict(&dict_data);
+        let mut working = Lz4Stream::new();
+        // SAFETY: `dict_stream` outlives every use of `working` below.
+        unsafe { working.attach_dictionary(Some(&*dict_stream as *const Lz4Stream)) };
+        assert_eq!(working.export(), Err(StateError::DictionaryAttached));
+    }
+
+    #[test]
+    fn fresh_stream_round_trips() {
+        let snapshot = Lz4Stream::new().export().unwrap();
+        assert_eq!(snapshot.len(), HEADER_SIZE + TABLE_BYTES + CHECKSUM_SIZE);
+        let mut target = primed_stream(&text(2, 4 * KB));
+        target.import(&snapshot, &mut []).unwrap();
+        assert_eq!(target.internal.current_offset, 0);
+        assert_eq!(target.internal.dict_size, 0);
+    }
+}
@@ -9,8 +9,9 @@
 //!   (`LZ4_resetStream`, `LZ4_resetStream_fast`)
 //! - [`Lz4Stream::load_dict`] / [`Lz4Stream::load_dict_slow`]
 //!   (`LZ4_loadDict`, `LZ4_loadDictSlow`) via shared internal helper
-//! - [`Lz4Stream::attach_dictionary`] (`LZ4_attach_dictionary`)
-//!   — stores a raw pointer to an external dict stream; see Safety note below
+//! - [`Lz4Stream::attach_dictionary`] / [`attach_dictionary`]
+//!   (`LZ4_attach_dictionary`) — stores a raw pointer to an external dict
+//!   stream; see Safety note below
 //! - [`Lz4Stream::renorm_dict`] (`LZ4_renormDictT`)
 //! - [`Lz4Stream::compress_fast_continue`] (`LZ4_compress_fast_continue`)
 //! - [`Lz4Stream::compress_force_ext_dict`] (`LZ4_compress_forceExtDict`)
@@ -25,10 +26,11 @@
 
 use core::ptr;
 
-use super::compress::{compress_generic, LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX};
+use super::compress::{compress_generic, Lz4Error, LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX};
 use super::types::{
-    get_index_on_hash, hash_position, prepare_table, put_index_on_hash, DictDirective,
-    DictIssueDirective, LimitedOutputDirective, StreamStateInternal, TableType, KB,
+    get_index_on_hash, hash_position_keyed, prepare_table, put_index_on_hash, DictDirective,
+    DictIssueDirective, HashKey, LimitedOutputDirective, StreamStateInternal, TableType, KB,
+    LZ4_DISTANCE_MAX,
 };
 
 // `HASH_UNIT` = `sizeof(reg_t)` in C.  On 64-bit targets `reg_t` is `u64`
@@ -90,9 +92,57 @@ impl Lz4Stream {
 
     /// Fully reset the stream to its zero-initialised state.
     ///
+    /// The hash seed chosen with [`set_hash_seed`](Self::set_hash_seed) and
+    /// the limit chosen with [`set_max_distance`](Self::set_max_distance) are
+    /// kept.
+    ///
     /// Equivalent to `LZ4_resetStream` / `LZ4_initStream`.
     pub fn reset(&mut self) {
-        self.internal = StreamStateInternal::new();
+        let max_distance = self.internal.max_distance;
+        self.internal = StreamStateInternal::with_hash_seed(self.internal.hash_seed);
+        self.internal.max_distance = max_distance;
+    }
+
+    /// Select the match-finder hash used by this stream.
+    ///
+    /// `0` (the default) is the reference LZ4 hash; any other value derives a
+    /// keyed multiplier via [`HashKey::from_seed`], which keeps adversarial
+    /// inputs from forcing hash collisions.  The stream is reset because
+    /// table entries written under one key are meaningless under another.
+    /// Call this before [`load_dict`](Self::load_dict); a dictionary stream
+    /// passed to [`attach_dictionary`](Self::attach_dictionary) should use
+    /// the same seed or its entries will rarely produce matches.
+    pub fn set_hash_seed(&mut self, seed: u64) {
+        let max_distance = self.internal.max_distance;
+        self.internal = StreamStateInternal::with_hash_seed(seed);
+        self.internal.max_distance = max_distance;
+    }
+
+    /// The hash seed selected with [`set_hash_seed`](Self::set_hash_seed).
+    pub fn hash_seed(&self) -> u64 {
+        self.internal.hash_seed
+    }
+
+    /// Limit match offsets to `distance` bytes, so a decoder only needs to
+    /// keep that much history.
+    ///
+    /// `distance` must be in `1..=LZ4_DISTANCE_MAX` (65 535, the default);
+    /// anything else gives [`Lz4Error::InvalidMaxDistance`] and leaves the
+    /// stream unchanged.  The limit survives [`reset`](Self::reset) and
+    /// applies to the next block, including matches into a loaded or attached
+    /// dictionary.  The output is a standard block; a smaller window only
+    /// costs ratio.
+    pub fn set_max_distance(&mut self, distance: u32) -> Result<(), Lz4Error> {
+        if !(1..=LZ4_DISTANCE_MAX).contains(&distance) {
+            return Err(Lz4Error::InvalidMaxDistance);
+        }
+        self.internal.max_distance = distance;
+        Ok(())
+    }
+
+    /// The limit selected with [`set_max_distance`](Self::set_max_distance).
+    pub fn max_distance(&self) -> u32 {
+        self.internal.max_distance
     }
 
     /// Fast reset — prepare the hash table for a new stream while avoiding
@@ -146,6 +196,7 @@ impl Lz4Stream {
         self.internal.dict_size = (dict_size.min(64 * KB)) as u32;
         self.internal.table_type = TableType::ByU32 as u32;
 
+        let key = HashKey::from_seed(self.internal.hash_seed);
         let mut p = p_start;
         let mut idx32 = self.internal.current_offset - self.internal.dict_size;
 
@@ -155,7 +206,7 @@ impl Lz4Stream {
             // SAFETY: p starts within the dict slice; loop guard prevents
             // reading past dict_end (we check p + HASH_UNIT <= dict_end).
             while p.add(HASH_UNIT) <= dict_end {
-                let h = hash_position(p, TableType::ByU32);
+                let h = hash_position_keyed(p, TableType::ByU32, key);
                 put_index_on_hash(
                     idx32,
                     h,
@@ -177,7 +228,7 @@ impl Lz4Stream {
                 let limit = self.internal.current_offset.wrapping_sub(64 * KB as u32);
 
                 while p.add(HASH_UNIT) <= dict_end {
-                    let h = hash_position(p, TableType::ByU32);
+                    let h = hash_position_keyed(p, TableType::ByU32, key);
                     if get_index_on_hash(h, self.internal.hash_table.as_ptr(), TableType::ByU32)
                         <= limit
                     {
@@ -348,9 +399,7 @@ impl Lz4Stream {
         // Prevent currentOffset from overflowing the 31-bit ptrdiff_t boundary.
         self.renorm_dict(input_size);
 
-        let acceleration = acceleration
-            .max(LZ4_ACCELERATION_DEFAULT)
-            .min(LZ4_ACCELERATION_MAX);
+        let acceleration = acceleration.clamp(LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX);
 
         // Invalidate tiny dictionaries (< 4 bytes) that are not in prefix mode
         // and not in dictCtx mode.  Doing so allows the faster prefix path to
@@ -631,6 +680,37 @@ impl Lz4Stream {
     }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// LZ4_attach_dictionary  (lz4.c:1624–1654)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Attach a pre-loaded dictionary stream to a working stream (no-copy).
+///
+/// Free-function counterpart of [`Lz4Stream::attach_dictionary`], mirroring
+/// [`attach_hc_dictionary`](crate::hc::attach_hc_dictionary) for the fast
+/// codec.  One dictionary stream prepared with [`Lz4Stream::load_dict`] can be
+/// attached to any number of working streams; each one reads the dictionary's
+/// hash table instead of rebuilding it per message.
+///
+/// Pass `None` to detach any existing dictionary.  The dictionary serves the
+/// next block compressed on `working_stream`; later blocks reference that
+/// block as history, exactly as after `load_dict`.
+///
+/// # Safety
+///
+/// If `dictionary_stream` is `Some(dict)`:
+/// - `dict` and the buffer it was loaded from must remain alive, at the same
+///   address, and **unmodified** until `working_stream` has compressed its
+///   next block, is reset, or has the dictionary detached.
+///
+/// Equivalent to `LZ4_attach_dictionary`.
+pub unsafe fn attach_dictionary(
+    working_stream: &mut Lz4Stream,
+    dictionary_stream: Option<&Lz4Stream>,
+) {
+    working_stream.attach_dictionary(dictionary_stream.map(|d| d as *const Lz4Stream));
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Unit tests (require pub(crate) field access)
 // ─────────────────────────────────────────────────────────────────────────────
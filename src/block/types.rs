@@ -171,6 +171,11 @@ pub enum DictIssueDirective {
 /// The `hash_table` field stores `u32` values in `ByU32` mode, `u16` pairs
 /// packed into `u32` slots in `ByU16` mode, and — on 32-bit targets only —
 /// raw `*const u8` pointer values in `ByPtr` mode.
+///
+/// `hash_seed` is a Rust-only extension: `0` selects the reference LZ4 hash,
+/// any other value derives a keyed multiplier via [`HashKey::from_seed`].
+/// `max_distance` (also Rust-only) caps match offsets; it defaults to
+/// [`LZ4_DISTANCE_MAX`] and must not exceed it.
 #[repr(C)]
 pub struct StreamStateInternal {
     pub hash_table: [u32; LZ4_HASH_SIZE_U32],
@@ -179,6 +184,8 @@ pub struct StreamStateInternal {
     pub current_offset: u32,
     pub table_type: u32,
     pub dict_size: u32,
+    pub hash_seed: u64,
+    pub max_distance: u32,
 }
 
 // SAFETY: Compression is driven by the caller under single-threaded
@@ -196,8 +203,18 @@ impl StreamStateInternal {
             current_offset: 0,
             table_type: TableType::ClearedTable as u32,
             dict_size: 0,
+            hash_seed: 0,
+            max_distance: LZ4_DISTANCE_MAX,
         }
     }
+
+    /// Create a zero-initialized state whose match finder uses the keyed hash
+    /// selected by `seed` (see [`HashKey::from_seed`]).
+    pub const fn with_hash_seed(seed: u64) -> Self {
+        let mut state = Self::new();
+        state.hash_seed = seed;
+        state
+    }
 }
 
 impl Default for StreamStateInternal {
@@ -557,6 +574,68 @@ pub unsafe fn count(mut p_in: *const u8, mut p_match: *const u8, p_in_limit: *co
 // Hash functions (lz4.c:777-806)
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Multipliers used by the fast compressor's match-finder hash.
+///
+/// [`HashKey::DEFAULT`] holds the reference LZ4 primes and reproduces the C
+/// library's output byte for byte.  Services that compress untrusted data can
+/// select a keyed multiplier with [`HashKey::from_seed`] so that inputs
+/// crafted to collide in the well-known hash no longer funnel every position
+/// into a handful of table slots.  Matches are always verified against the
+/// source bytes, so any key produces a valid block; only the match choices
+/// (and therefore the exact output bytes) differ.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HashKey {
+    /// Multiplier for the 4-byte hash (`hash4`).  Always odd.
+    pub prime4: u32,
+    /// Multiplier for the 5-byte hash (`hash5`).  Always odd.
+    pub prime5: u64,
+}
+
+impl HashKey {
+    /// The reference LZ4 hash (`LZ4_hash4` / `LZ4_hash5`).
+    pub const DEFAULT: Self = Self {
+        prime4: 2_654_435_761,
+        #[cfg(target_endian = "little")]
+        prime5: 889_523_592_379,
+        #[cfg(not(target_endian = "little"))]
+        prime5: 11_400_714_785_074_694_791,
+    };
+
+    /// Derive a keyed hash from `seed`.  A seed of `0` yields [`HashKey::DEFAULT`].
+    ///
+    /// The seed is scrambled with the SplitMix64 finalizer so that nearby
+    /// seeds give unrelated multipliers; both multipliers are forced odd so
+    /// the multiplication stays a bijection on the hashed bits.
+    pub const fn from_seed(seed: u64) -> Self {
+        if seed == 0 {
+            return Self::DEFAULT;
+        }
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Self {
+            prime4: (z >> 32) as u32 | 1,
+            prime5: z | 1,
+        }
+    }
+}
+
+impl Default for HashKey {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[inline(always)]
+fn hash_log_for(table_type: TableType) -> u32 {
+    if table_type == TableType::ByU16 {
+        LZ4_HASHLOG + 1
+    } else {
+        LZ4_HASHLOG
+    }
+}
+
 /// 4-byte Knuth-multiplicative hash for a match candidate.
 ///
 /// `table_type` selects the hash-log width:
@@ -566,13 +645,14 @@ pub unsafe fn count(mut p_in: *const u8, mut p_match: *const u8, p_in_limit: *co
 /// Equivalent to `LZ4_hash4`.
 #[inline(always)]
 pub fn hash4(sequence: u32, table_type: TableType) -> u32 {
-    let hash_log = if table_type == TableType::ByU16 {
-        LZ4_HASHLOG + 1
-    } else {
-        LZ4_HASHLOG
-    };
+    hash4_keyed(sequence, table_type, HashKey::DEFAULT)
+}
+
+/// [`hash4`] with the multiplier taken from `key`.
+#[inline(always)]
+pub fn hash4_keyed(sequence: u32, table_type: TableType, key: HashKey) -> u32 {
     // MINMATCH * 8 == 32; shift removes the low (32 - hash_log) bits.
-    sequence.wrapping_mul(2_654_435_761u32) >> (32 - hash_log)
+    sequence.wrapping_mul(key.prime4) >> (32 - hash_log_for(table_type))
 }
 
 /// 5-byte hash for a match candidate (preferred on 64-bit targets).
@@ -583,20 +663,20 @@ pub fn hash4(sequence: u32, table_type: TableType) -> u32 {
 /// Equivalent to `LZ4_hash5`.
 #[inline(always)]
 pub fn hash5(sequence: u64, table_type: TableType) -> u32 {
-    let hash_log = if table_type == TableType::ByU16 {
-        LZ4_HASHLOG + 1
-    } else {
-        LZ4_HASHLOG
-    };
+    hash5_keyed(sequence, table_type, HashKey::DEFAULT)
+}
+
+/// [`hash5`] with the multiplier taken from `key`.
+#[inline(always)]
+pub fn hash5_keyed(sequence: u64, table_type: TableType, key: HashKey) -> u32 {
+    let hash_log = hash_log_for(table_type);
     #[cfg(target_endian = "little")]
     {
-        const PRIME5: u64 = 889_523_592_379;
-        (((sequence << 24).wrapping_mul(PRIME5)) >> (64 - hash_log)) as u32
+        (((sequence << 24).wrapping_mul(key.prime5)) >> (64 - hash_log)) as u32
     }
     #[cfg(not(target_endian = "little"))]
     {
-        const PRIME8: u64 = 11_400_714_785_074_694_791;
-        (((sequence >> 24).wrapping_mul(PRIME8)) >> (64 - hash_log)) as u32
+        (((sequence >> 24).wrapping_mul(key.prime5)) >> (64 - hash_log)) as u32
     }
 }
 
@@ -611,11 +691,20 @@ pub fn hash5(sequence: u64, table_type: TableType) -> u32 {
 /// `p` must be valid for reads of at least `size_of::<usize>()` bytes.
 #[inline(always)]
 pub unsafe fn hash_position(p: *const u8, table_type: TableType) -> u32 {
+    hash_position_keyed(p, table_type, HashKey::DEFAULT)
+}
+
+/// [`hash_position`] with the multipliers taken from `key`.
+///
+/// # Safety
+/// Same as [`hash_position`].
+#[inline(always)]
+pub unsafe fn hash_position_keyed(p: *const u8, table_type: TableType, key: HashKey) -> u32 {
     #[cfg(target_pointer_width = "64")]
     if table_type != TableType::ByU16 {
-        return hash5(read_arch(p) as u64, table_type);
+        return hash5_keyed(read_arch(p) as u64, table_type, key);
     }
-    hash4(read32(p), table_type)
+    hash4_keyed(read32(p), table_type, key)
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -38,12 +38,10 @@ pub fn exe_name_match(exe_path: &str, name: &str) -> bool {
 /// leading digits, or `Some((value, remainder))` where `remainder` is the
 /// unconsumed tail of `s`.
 ///
-/// Recognised suffixes (case-sensitive):
-///   `K` / `KB` / `KiB`  → multiply by 1 024
-///   `M` / `MB` / `MiB`  → multiply by 1 048 576
-///   `G` / `GB` / `GiB`  → multiply by 1 073 741 824
+/// Recognised suffixes are those of [`read_size_from_str`].
 ///
 /// Arithmetic wraps silently on overflow, consistent with [`u32::wrapping_mul`].
+/// Options that take a byte count should use [`parse_size`] instead.
 pub fn read_u32_from_str(s: &str) -> Option<(u32, &str)> {
     let bytes = s.as_bytes();
     let mut i = 0usize;
@@ -61,43 +59,62 @@ pub fn read_u32_from_str(s: &str) -> Option<(u32, &str)> {
         i += 1;
     }
 
-    if i < bytes.len() {
-        match bytes[i] {
-            b'K' => {
-                result <<= 10;
-                i += 1;
-                if i < bytes.len() && bytes[i] == b'i' {
-                    i += 1;
-                }
-                if i < bytes.len() && bytes[i] == b'B' {
-                    i += 1;
-                }
-            }
-            b'M' => {
-                result <<= 20;
-                i += 1;
-                if i < bytes.len() && bytes[i] == b'i' {
-                    i += 1;
-                }
-                if i < bytes.len() && bytes[i] == b'B' {
-                    i += 1;
-                }
-            }
-            b'G' => {
-                result <<= 30;
-                i += 1;
-                if i < bytes.len() && bytes[i] == b'i' {
-                    i += 1;
-                }
-                if i < bytes.len() && bytes[i] == b'B' {
-                    i += 1;
-                }
-            }
-            _ => {}
-        }
+    let (shift, len) = size_suffix(&bytes[i..]);
+    Some((result.wrapping_shl(shift), &s[i + len..]))
+}
+
+/// Parses a byte count from the start of `s`: decimal digits, then an
+/// optional multiplier.  Returns `None` if `s` has no leading digits or the
+/// value does not fit in a `u64`, or `Some((bytes, remainder))`.
+///
+/// Recognised suffixes (case-sensitive, all powers of 1 024 as in the C CLI):
+///   `K` / `Ki` / `KB` / `KiB`  → multiply by 1 024
+///   `M` / `Mi` / `MB` / `MiB`  → multiply by 1 048 576
+///   `G` / `Gi` / `GB` / `GiB`  → multiply by 1 073 741 824
+pub fn read_size_from_str(s: &str) -> Option<(u64, &str)> {
+    let bytes = s.as_bytes();
+    let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    let mut value: u64 = 0;
+    for &b in &bytes[..digits] {
+        value = value.checked_mul(10)?.checked_add((b - b'0') as u64)?;
     }
+    let (shift, len) = size_suffix(&bytes[digits..]);
+    if value.leading_zeros() < shift {
+        return None;
+    }
+    Some((value << shift, &s[digits + len..]))
+}
 
-    Some((result, &s[i..]))
+/// Parses all of `s` as a byte count, such as `4096`, `64K`, `4Mi` or
+/// `1GiB` (see [`read_size_from_str`]).  Returns `None` on anything else,
+/// including trailing text and overflow.
+pub fn parse_size(s: &str) -> Option<u64> {
+    match read_size_from_str(s)? {
+        (value, "") => Some(value),
+        _ => None,
+    }
+}
+
+/// Shift and length of the size suffix at the start of `bytes`, or `(0, 0)`
+/// when there is none.
+fn size_suffix(bytes: &[u8]) -> (u32, usize) {
+    let shift = match bytes.first() {
+        Some(b'K') => 10,
+        Some(b'M') => 20,
+        Some(b'G') => 30,
+        _ => return (0, 0),
+    };
+    let mut len = 1;
+    if bytes.get(len) == Some(&b'i') {
+        len += 1;
+    }
+    if bytes.get(len) == Some(&b'B') {
+        len += 1;
+    }
+    (shift, len)
 }
 
 /// Returns the value portion of a long CLI option if `arg` begins with
@@ -237,6 +254,56 @@ mod tests {
         assert_eq!(rest, "xyz");
     }
 
+    // --- read_size_from_str / parse_size ---
+
+    #[test]
+    fn test_parse_size_plain() {
+        assert_eq!(parse_size("0"), Some(0));
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("007"), Some(7));
+    }
+
+    #[test]
+    fn test_parse_size_every_suffix_form() {
+        for (unit, mult) in [("K", 1u64 << 10), ("M", 1 << 20), ("G", 1 << 30)] {
+            for tail in ["", "i", "B", "iB"] {
+                let s = format!("3{unit}{tail}");
+                assert_eq!(parse_size(&s), Some(3 * mult), "{s}");
+            }
+        }
+        assert_eq!(parse_size("64K"), Some(65536));
+        assert_eq!(parse_size("4Mi"), Some(4 << 20));
+        assert_eq!(parse_size("1G"), Some(1 << 30));
+    }
+
+    #[test]
+    fn test_parse_size_rejects_malformed() {
+        for s in [
+            "", "K", "-1", "+1", " 1", "1 ", "1k", "1m", "1KK", "1BK", "1iB", "1KiBx", "1.5M",
+            "1T", "0x10",
+        ] {
+            assert_eq!(parse_size(s), None, "{s:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_size_overflow() {
+        assert_eq!(parse_size("18446744073709551615"), Some(u64::MAX));
+        assert_eq!(parse_size("18446744073709551616"), None);
+        assert_eq!(parse_size("17179869183G"), Some(17179869183 << 30));
+        assert_eq!(parse_size("17179869184G"), None);
+        // Beyond u32 is fine here, unlike read_u32_from_str.
+        assert_eq!(parse_size("4G"), Some(4 << 30));
+        assert_eq!(read_u32_from_str("4G"), Some((0, "")));
+    }
+
+    #[test]
+    fn test_read_size_remainder() {
+        assert_eq!(read_size_from_str("64KD"), Some((65536, "D")));
+        assert_eq!(read_size_from_str("7X"), Some((7, "X")));
+        assert_eq!(read_size_from_str("x"), None);
+    }
+
     // --- long_command_w_arg ---
 
     #[test]
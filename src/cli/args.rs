@@ -13,18 +13,29 @@
 //! Bad or unrecognised options return an `Err` with a human-readable message
 //! that begins with `"bad usage: "`.
 
+use std::sync::Arc;
+
 use anyhow::anyhow;
 
 use crate::bench::BenchConfig;
-use crate::cli::arg_utils::{long_command_w_arg, read_u32_from_str};
+use crate::cli::arg_utils::{
+    long_command_w_arg, parse_size, read_size_from_str, read_u32_from_str,
+};
 use crate::cli::constants::{display_level, set_display_level, AUTHOR, COMPRESSOR_NAME, IO_MT};
 use crate::cli::help::{print_long_help, print_usage_advanced};
 use crate::cli::init::CliInit;
 use crate::cli::op_mode::OpMode;
 use crate::displaylevel;
+use crate::displaywarning;
+use crate::frame::types::{
+    BlockChecksum, BlockMode as FrameBlockMode, BlockSizeId, ContentChecksum, Preferences,
+};
+use crate::frame::ChecksumPolicy;
 use crate::hc::types::LZ4HC_CLEVEL_MAX;
+use crate::io::diag::{set_log_format, LogFormat};
 use crate::io::file_io::{NULL_OUTPUT, NUL_MARK, STDIN_MARK, STDOUT_MARK};
 use crate::io::prefs::{BlockMode, Prefs};
+use crate::io::progress::StderrProgress;
 
 // ── Constants ─────────────────────────────────────────────────────────────────
 
@@ -65,8 +76,16 @@ pub struct ParsedArgs {
     pub output_filename: Option<String>,
     /// Dictionary file path.
     pub dictionary_filename: Option<String>,
+    /// Archive written by `--join`: all inputs go into this one file.
+    pub join_filename: Option<String>,
     /// Input filenames collected in multiple-input mode.
     pub in_file_names: Vec<String>,
+    /// File naming further inputs, one per line or NUL-terminated
+    /// (`--files-from`); [`STDIN_MARK`] reads the list from stdin.
+    pub files_from: Option<String>,
+    /// Directory receiving the outputs of a multiple-input compression
+    /// (`--output-dir`); with `-r` the source tree is recreated under it.
+    pub output_dir: Option<String>,
     /// Traverse directories recursively (requires `recursive` Cargo feature).
     #[cfg(feature = "recursive")]
     pub recursive: bool,
@@ -112,8 +131,25 @@ pub fn parse_args_from(
         force_stdout: init_force_stdout,
         output_filename: init_output_filename,
         display_level_override: _,
+        config_file,
     } = init;
 
+    // ── Config file defaults ────────────────────────────────────────────────
+    // Options from the config file are parsed first, so explicit arguments
+    // override them.
+    let argv = &with_config_defaults(argv, config_file.as_deref())?;
+
+    // ── Behaviour profile ───────────────────────────────────────────────────
+    // `--gzip-compat` swaps the defaults rather than acting as one more flag,
+    // so it is applied before the loop and `-k`/`-f` win wherever they appear.
+    if argv
+        .iter()
+        .take_while(|a| a.as_str() != "--")
+        .any(|a| a == "--gzip-compat")
+    {
+        prefs.set_gzip_compat(true);
+    }
+
     // --- Mutable parsing state ---
     let mut op_mode = init_op_mode;
     let mut c_level: i32 = init_c_level;
@@ -128,7 +164,10 @@ pub fn parse_args_from(
     let mut input_filename: Option<String> = None;
     let mut output_filename: Option<String> = init_output_filename;
     let mut dictionary_filename: Option<String> = None;
+    let mut join_filename: Option<String> = None;
     let mut in_file_names: Vec<String> = Vec::new();
+    let mut files_from: Option<String> = None;
+    let mut output_dir: Option<String> = None;
     #[cfg(feature = "recursive")]
     let mut recursive = false;
     let mut block_size: usize = prefs.block_size; // initialised from default prefs
@@ -167,7 +206,7 @@ pub fn parse_args_from(
             } else {
                 // 3rd or later non-option argument with neither -m nor -f:
                 if force_overwrite {
-                    displaylevel!(
+                    displaywarning!(
                         1,
                         "Warning: {} won't be used ! Do you want multiple input files (-m) ? \n",
                         argument
@@ -217,15 +256,23 @@ pub fn parse_args_from(
                 multiple_inputs = true;
             } else if argument == "--test" {
                 op_mode = OpMode::Test;
+                test_all_inputs(
+                    &mut multiple_inputs,
+                    &mut input_filename,
+                    &mut in_file_names,
+                );
             } else if argument == "--force" {
-                prefs.set_overwrite(true);
+                force_overwrite = true;
+                prefs.set_force(true);
             } else if argument == "--no-force" {
-                prefs.set_overwrite(false);
+                force_overwrite = false;
+                prefs.set_force(false);
             } else if argument == "--stdout" || argument == "--to-stdout" {
                 force_stdout = true;
                 output_filename = Some(STDOUT_MARK.to_owned());
             } else if argument == "--frame-crc" {
                 prefs.set_stream_checksum_mode(true);
+                prefs.set_checksum_policy(ChecksumPolicy::VERIFY);
                 bench_config.set_skip_checksums(false);
             } else if argument == "--no-frame-crc" {
                 prefs.set_stream_checksum_mode(false);
@@ -233,21 +280,158 @@ pub fn parse_args_from(
             } else if argument == "--no-crc" {
                 prefs.set_stream_checksum_mode(false);
                 prefs.set_block_checksum_mode(false);
+                prefs.set_checksum_policy(ChecksumPolicy::SKIP);
                 bench_config.set_skip_checksums(true);
+            } else if argument == "--recover" {
+                prefs.set_recover(true);
             } else if argument == "--content-size" {
                 prefs.set_content_size(true);
             } else if argument == "--no-content-size" {
                 prefs.set_content_size(false);
+            } else if let Some(rest) = long_command_w_arg(argument, "--preset") {
+                // Accepts `--preset=NAME` or `--preset NAME`.
+                let name = if let Some(name) = rest.strip_prefix('=') {
+                    name.to_owned()
+                } else if rest.is_empty() {
+                    arg_idx += 1;
+                    argv.get(arg_idx)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("bad usage: --preset requires a name"))?
+                } else {
+                    return Err(anyhow!("bad usage: unknown option: {}", argument));
+                };
+                match name.as_str() {
+                    "archive" => {
+                        apply_frame_preset(&mut prefs, &Preferences::archival());
+                        prefs.set_content_size(true);
+                    }
+                    _ => return Err(anyhow!("bad usage: --preset: expected archive")),
+                }
             } else if argument == "--list" {
                 op_mode = OpMode::List;
                 multiple_inputs = true;
+            } else if argument == "--info" {
+                op_mode = OpMode::Info;
+                multiple_inputs = true;
+            } else if let Some(rest) = long_command_w_arg(argument, "--join") {
+                // Accepts `--join=FILE` or `--join FILE`; implies multiple-input mode.
+                let path = if let Some(path) = rest.strip_prefix('=') {
+                    path.to_owned()
+                } else if rest.is_empty() {
+                    arg_idx += 1;
+                    argv.get(arg_idx)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("bad usage: --join requires an output file"))?
+                } else {
+                    return Err(anyhow!("bad usage: unknown option: {}", argument));
+                };
+                join_filename = Some(path);
+                op_mode = OpMode::Compress;
+                multiple_inputs = true;
+            } else if let Some(rest) = long_command_w_arg(argument, "--files-from") {
+                // Accepts `--files-from=FILE` or `--files-from FILE`; `-` is stdin.
+                // Implies multiple-input mode.
+                let path = if let Some(path) = rest.strip_prefix('=') {
+                    path.to_owned()
+                } else if rest.is_empty() {
+                    arg_idx += 1;
+                    argv.get(arg_idx)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("bad usage: --files-from requires a file"))?
+                } else {
+                    return Err(anyhow!("bad usage: unknown option: {}", argument));
+                };
+                if path.is_empty() {
+                    return Err(anyhow!("bad usage: --files-from requires a file"));
+                }
+                files_from = Some(if path == "-" {
+                    STDIN_MARK.to_owned()
+                } else {
+                    path
+                });
+                multiple_inputs = true;
+            } else if let Some(rest) = long_command_w_arg(argument, "--output-dir") {
+                // Accepts `--output-dir=DIR` or `--output-dir DIR`.
+                // Implies multiple-input mode.
+                let path = if let Some(path) = rest.strip_prefix('=') {
+                    path.to_owned()
+                } else if rest.is_empty() {
+                    arg_idx += 1;
+                    argv.get(arg_idx)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("bad usage: --output-dir requires a directory"))?
+                } else {
+                    return Err(anyhow!("bad usage: unknown option: {}", argument));
+                };
+                if path.is_empty() {
+                    return Err(anyhow!("bad usage: --output-dir requires a directory"));
+                }
+                output_dir = Some(path);
+                multiple_inputs = true;
+            } else if argument == "--extract" {
+                op_mode = OpMode::Extract;
+            } else if argument == "--self-test" {
+                op_mode = OpMode::SelfTest;
             } else if argument == "--sparse" {
                 // 2 = forced sparse; 0 = off; 1 = auto (default).
-                prefs.sparse_file_support = 2;
+                prefs.set_sparse_file(true);
             } else if argument == "--no-sparse" {
-                prefs.sparse_file_support = 0;
+                prefs.set_sparse_file(false);
+            } else if let Some(rest) = long_command_w_arg(argument, "--flush-every") {
+                // Accepts `--flush-every=BYTES` or `--flush-every BYTES` (K/M/G suffixes).
+                let bytes = parse_next_size(rest, argv, &mut arg_idx, "--flush-every")?;
+                if bytes == 0 {
+                    return Err(anyhow!(
+                        "bad usage: --flush-every: expected a byte count > 0"
+                    ));
+                }
+                prefs.set_flush_every(bytes);
+            } else if let Some(rest) = long_command_w_arg(argument, "--synthetic") {
+                // Accepts `--synthetic=ENTROPY[,SIZE]` or `--synthetic ENTROPY[,SIZE]`.
+                let spec = if let Some(spec) = rest.strip_prefix('=') {
+                    spec.to_owned()
+                } else if rest.is_empty() {
+                    arg_idx += 1;
+                    argv.get(arg_idx).cloned().unwrap_or_default()
+                } else {
+                    return Err(anyhow!("bad usage: unknown option: {}", argument));
+                };
+                let (entropy, size) = parse_synthetic(&spec).ok_or_else(|| {
+                    anyhow!(
+                        "bad usage: --synthetic: expected ENTROPY[,SIZE], ENTROPY from 0.0 to 1.0"
+                    )
+                })?;
+                bench_config.set_synthetic_entropy(Some(entropy));
+                if let Some(size) = size {
+                    bench_config.set_synthetic_size(size);
+                }
+            } else if argument == "--dict-report" {
+                bench_config.set_dict_report(true);
+            } else if argument == "--no-config" {
+                // Handled by with_config_defaults before the loop.
+            } else if let Some(rest) = long_command_w_arg(argument, "--config") {
+                // Handled by with_config_defaults; skip the file name.
+                if rest.is_empty() {
+                    arg_idx += 1;
+                } else if !rest.starts_with('=') {
+                    return Err(anyhow!("bad usage: unknown option: {}", argument));
+                }
             } else if argument == "--favor-decSpeed" {
                 prefs.favor_dec_speed(true);
+            } else if argument == "--progress" {
+                // Render progress on stderr even when it is not a TTY or -q is set.
+                prefs.set_progress_sink(Some(Arc::new(StderrProgress::new())));
+                prefs.set_quiet_progress(false);
+            } else if argument == "--quiet-progress" {
+                // No progress lines, but keep the per-file summaries.
+                prefs.set_progress_sink(None);
+                prefs.set_quiet_progress(true);
+            } else if let Some(rest) = long_command_w_arg(argument, "--log-format=") {
+                // Takes effect immediately so later parse errors use it too.
+                let format = LogFormat::parse(rest).ok_or_else(|| {
+                    anyhow!("bad usage: --log-format: expected plain, color, auto or json")
+                })?;
+                set_log_format(format);
             } else if argument == "--verbose" {
                 let lvl = display_level().saturating_add(1);
                 set_display_level(lvl);
@@ -266,8 +450,30 @@ pub fn parse_args_from(
                 break;
             } else if argument == "--keep" {
                 prefs.set_remove_src_file(false);
-            } else if argument == "--rm" {
+            } else if argument == "--rm" || argument == "--no-keep" {
                 prefs.set_remove_src_file(true);
+            } else if argument == "--gzip-compat" {
+                // Applied before the loop.
+            } else if argument == "--no-restore-times"
+                || argument == "--touch"
+                || argument == "--no-timestamp"
+            {
+                prefs.set_restore_times(false);
+            } else if argument == "--same-owner" {
+                prefs.set_restore_owner(true);
+            } else if argument == "--no-same-owner" {
+                prefs.set_restore_owner(false);
+            } else if argument == "--no-atomic" {
+                prefs.set_atomic_output(false);
+            } else if let Some(rest) = long_command_w_arg(argument, "--mtime-window") {
+                // Accepts `--mtime-window=SECS` or `--mtime-window SECS`.
+                let (secs, rest_pos) = parse_next_uint32(rest, argv, &mut arg_idx, exe_name)?;
+                if !rest_pos.is_empty() {
+                    return Err(anyhow!(
+                        "bad usage: --mtime-window: expected a number of seconds"
+                    ));
+                }
+                prefs.set_mtime_window(Some(secs as u64));
             } else if let Some(rest) = long_command_w_arg(argument, "--threads") {
                 // Accepts `--threads=N` or `--threads N` syntax.
                 let (val, rest_pos) = parse_next_uint32(rest, argv, &mut arg_idx, exe_name)?;
@@ -463,11 +669,17 @@ pub fn parse_args_from(
                 b't' => {
                     // Verify integrity of compressed input; no output is written.
                     op_mode = OpMode::Test;
+                    test_all_inputs(
+                        &mut multiple_inputs,
+                        &mut input_filename,
+                        &mut in_file_names,
+                    );
                 }
                 b'f' => {
-                    // Overwrite existing destination files without prompting.
+                    // Overwrite existing destination files without prompting,
+                    // and accept sources that are not regular files.
                     force_overwrite = true;
-                    prefs.set_overwrite(true);
+                    prefs.set_force(true);
                 }
                 b'v' => {
                     // Increase verbosity level.
@@ -509,7 +721,10 @@ pub fn parse_args_from(
                             }
                             c if c.is_ascii_digit() => {
                                 // Numeric suffix: 4–7 selects a preset block-size ID; ≥32 is a raw byte count.
-                                let (b_val, remainder) = read_u32_from_str(&argument[j..]).unwrap();
+                                let (b_val, remainder) = read_size_from_str(&argument[j..])
+                                    .ok_or_else(|| anyhow!("bad usage: block size too large"))?;
+                                // Anything past u32 is clamped to the 4 MB maximum anyway.
+                                let b_val = u32::try_from(b_val).unwrap_or(u32::MAX);
                                 let consumed = argument[j..].len() - remainder.len();
                                 // j advances by consumed chars; inner loop does not auto-advance.
                                 j += consumed;
@@ -629,7 +844,10 @@ pub fn parse_args_from(
         input_filename,
         output_filename,
         dictionary_filename,
+        join_filename,
         in_file_names,
+        files_from,
+        output_dir,
         #[cfg(feature = "recursive")]
         recursive,
         block_size,
@@ -655,6 +873,176 @@ fn print_welcome_message(exe_name: &str) {
     let _ = exe_name; // unused; kept for symmetry with other help functions
 }
 
+/// `-t` tests every file named, so it implies `-m`.  A file named before the
+/// flag is kept as the first input; `-` (stdin) stays the lone input.
+fn test_all_inputs(
+    multiple_inputs: &mut bool,
+    input_filename: &mut Option<String>,
+    in_file_names: &mut Vec<String>,
+) {
+    if !*multiple_inputs && input_filename.as_deref() != Some(STDIN_MARK) {
+        in_file_names.extend(input_filename.take());
+    }
+    *multiple_inputs = true;
+}
+
+/// Copy the frame settings of a library preset onto the CLI preferences.
+///
+/// A non-zero `content_size` turns the content-size field on; the real value is
+/// filled in from the input file at compression time.  Presets leave it at 0
+/// for the caller to set, so callers wanting the field turn it on themselves.
+fn apply_frame_preset(prefs: &mut Prefs, preset: &Preferences) {
+    let fi = &preset.frame_info;
+    let bsid = match fi.block_size_id {
+        BlockSizeId::Default => BlockSizeId::Max64Kb as u32,
+        id => id as u32,
+    };
+    prefs.set_block_size_id(bsid);
+    prefs.set_block_mode(match fi.block_mode {
+        FrameBlockMode::Linked => BlockMode::Linked,
+        FrameBlockMode::Independent => BlockMode::Independent,
+    });
+    prefs.set_stream_checksum_mode(fi.content_checksum_flag == ContentChecksum::Enabled);
+    prefs.set_block_checksum_mode(fi.block_checksum_flag == BlockChecksum::Enabled);
+    prefs.set_content_size(fi.content_size != 0);
+}
+
+/// Prepends the options of the config file to `argv`.
+///
+/// `--config FILE` (or `--config=FILE`) names the file, which must exist;
+/// otherwise `default` is used when it exists.  `--no-config` disables both.
+/// Only arguments before a bare `--` are examined.
+///
+/// The file holds options separated by whitespace or newlines, with `#`
+/// starting a comment to the end of the line:
+///
+/// ```text
+/// # ~/.lz4rc
+/// -9          # high compression by default
+/// --rm        # remove sources after success
+/// --sparse
+/// ```
+///
+/// Every entry must be an option, so values are attached (`-T4`,
+/// `--threads=4`); file names, `-`, `--` and `--config` itself are rejected.
+fn with_config_defaults(
+    argv: &[String],
+    default: Option<&std::path::Path>,
+) -> anyhow::Result<Vec<String>> {
+    let mut explicit: Option<String> = None;
+    let mut disabled = false;
+    let mut i = 0;
+    while i < argv.len() && argv[i] != "--" {
+        if argv[i] == "--no-config" {
+            disabled = true;
+        } else if let Some(rest) = long_command_w_arg(&argv[i], "--config") {
+            if let Some(path) = rest.strip_prefix('=') {
+                explicit = Some(path.to_owned());
+            } else if rest.is_empty() {
+                i += 1;
+                explicit = Some(argv.get(i).cloned().unwrap_or_default());
+            }
+            if explicit.as_deref() == Some("") {
+                return Err(anyhow!("bad usage: --config requires a file"));
+            }
+        }
+        i += 1;
+    }
+    if disabled {
+        return Ok(argv.to_vec());
+    }
+    let path = match (&explicit, default) {
+        (Some(path), _) => std::path::PathBuf::from(path),
+        (None, Some(path)) if path.is_file() => path.to_owned(),
+        _ => return Ok(argv.to_vec()),
+    };
+    let text = std::fs::read_to_string(&path).map_err(|e| {
+        anyhow!(
+            "bad usage: cannot read config file {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+
+    let mut args = Vec::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        for token in line.split_whitespace() {
+            if !token.starts_with('-') || token == "-" || token == "--" {
+                return Err(anyhow!(
+                    "bad usage: config file {}: {} is not an option",
+                    path.display(),
+                    token
+                ));
+            }
+            if token == "--no-config" || long_command_w_arg(token, "--config").is_some() {
+                return Err(anyhow!(
+                    "bad usage: config file {}: {} is not allowed",
+                    path.display(),
+                    token
+                ));
+            }
+            args.push(token.to_owned());
+        }
+    }
+    displaylevel!(
+        4,
+        "Using options from {} : {}\n",
+        path.display(),
+        args.join(" ")
+    );
+    args.extend_from_slice(argv);
+    Ok(args)
+}
+
+/// Parses `ENTROPY[,SIZE]` for `--synthetic`: an entropy in `0.0..=1.0` and
+/// an optional non-zero size with K/M/G suffixes.
+fn parse_synthetic(spec: &str) -> Option<(f64, Option<usize>)> {
+    let (entropy, size) = match spec.split_once(',') {
+        Some((entropy, size)) => (entropy, Some(size)),
+        None => (spec, None),
+    };
+    let entropy: f64 = entropy.parse().ok()?;
+    if !(0.0..=1.0).contains(&entropy) {
+        return None;
+    }
+    let size = match size {
+        Some(size) => match usize::try_from(parse_size(size)?) {
+            Ok(n) if n > 0 => Some(n),
+            _ => return None,
+        },
+        None => None,
+    };
+    Some((entropy, size))
+}
+
+/// Reads the byte-count argument of `option`, given as `option=SIZE` or as
+/// the next argument, through [`parse_size`].
+fn parse_next_size(
+    rest: &str,
+    argv: &[String],
+    arg_idx: &mut usize,
+    option: &str,
+) -> anyhow::Result<usize> {
+    let value = if let Some(value) = rest.strip_prefix('=') {
+        value
+    } else if rest.is_empty() {
+        *arg_idx += 1;
+        argv.get(*arg_idx)
+            .ok_or_else(|| anyhow!("bad usage: {}: missing command argument", option))?
+    } else {
+        return Err(anyhow!("bad usage: unknown option: {}{}", option, rest));
+    };
+    parse_size(value)
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or_else(|| {
+            anyhow!(
+                "bad usage: {}: expected a byte count such as 4096, 64K or 4MiB",
+                option
+            )
+        })
+}
+
 /// Read a `u32` from either `=VALUE` within the current argument or from the next
 /// element of `argv` (advancing `arg_idx`), supporting both `--option=N` and
 /// `--option N` syntax.
@@ -714,14 +1102,20 @@ mod tests {
         args.iter().map(|s| s.to_string()).collect()
     }
 
+    /// Alias defaults without the user's own `~/.lz4rc`.
+    fn init() -> CliInit {
+        CliInit {
+            config_file: None,
+            ..detect_alias("lz4")
+        }
+    }
+
     fn parse(args: &[&str]) -> ParsedArgs {
-        let init = detect_alias("lz4");
-        parse_args_from(init, "lz4", &make_args(args)).expect("parse failed")
+        parse_args_from(init(), "lz4", &make_args(args)).expect("parse failed")
     }
 
     fn parse_err(args: &[&str]) -> anyhow::Error {
-        let init = detect_alias("lz4");
-        parse_args_from(init, "lz4", &make_args(args)).expect_err("expected error")
+        parse_args_from(init(), "lz4", &make_args(args)).expect_err("expected error")
     }
 
     // ── Compression level ────────────────────────────────────────────────────
@@ -807,6 +1201,39 @@ mod tests {
         assert!(p.multiple_inputs);
     }
 
+    #[test]
+    fn info_mode() {
+        let p = parse(&["--info", "a.lz4", "b.lz4"]);
+        assert_eq!(p.op_mode, OpMode::Info);
+        assert!(p.multiple_inputs);
+    }
+
+    #[test]
+    fn join_takes_output_in_both_forms() {
+        let p = parse(&["--join", "out.lz4", "a", "b"]);
+        assert_eq!(p.join_filename.as_deref(), Some("out.lz4"));
+        assert!(p.multiple_inputs);
+        assert_eq!(p.in_file_names, ["a", "b"]);
+        let p = parse(&["--join=out.lz4", "a"]);
+        assert_eq!(p.join_filename.as_deref(), Some("out.lz4"));
+        parse_err(&["--join"]);
+    }
+
+    #[test]
+    fn self_test_mode() {
+        let p = parse(&["--self-test", "vectors"]);
+        assert_eq!(p.op_mode, OpMode::SelfTest);
+        assert_eq!(p.input_filename.as_deref(), Some("vectors"));
+    }
+
+    #[test]
+    fn extract_mode() {
+        let p = parse(&["--extract", "arch.lz4", "outdir"]);
+        assert_eq!(p.op_mode, OpMode::Extract);
+        assert_eq!(p.input_filename.as_deref(), Some("arch.lz4"));
+        assert_eq!(p.output_filename.as_deref(), Some("outdir"));
+    }
+
     #[test]
     fn bench_mode() {
         let p = parse(&["-b"]);
@@ -861,6 +1288,21 @@ mod tests {
         assert!(p.prefs.block_checksum);
     }
 
+    #[test]
+    fn block_size_takes_size_suffixes() {
+        assert_eq!(parse(&["-B64K"]).prefs.block_size, 65536);
+        assert_eq!(parse(&["-B4Mi"]).prefs.block_size, 4 << 20);
+        assert_eq!(parse(&["-B256KiB"]).prefs.block_size, 256 << 10);
+        assert_eq!(parse(&["-B4096"]).prefs.block_size, 4096);
+        // Flags may follow the size in the same cluster.
+        let p = parse(&["-B64KD"]);
+        assert_eq!(p.prefs.block_size, 65536);
+        assert!(!p.prefs.block_independence);
+        // Oversized values clamp to 4 MB rather than wrapping to zero.
+        assert_eq!(parse(&["-B4G"]).prefs.block_size, 4 << 20);
+        parse_err(&["-B99999999999999999999"]);
+    }
+
     // ── Threads ──────────────────────────────────────────────────────────────
 
     #[test]
@@ -945,12 +1387,74 @@ mod tests {
         assert!(p.prefs.overwrite);
     }
 
+    #[test]
+    fn force_flags_cover_overwrite_and_non_regular_sources() {
+        let p = parse(&["in.txt"]);
+        assert!(!p.prefs.force && !p.prefs.overwrite && !p.force_overwrite);
+        for flag in ["-f", "--force"] {
+            let p = parse(&[flag]);
+            assert!(
+                p.prefs.force && p.prefs.overwrite && p.force_overwrite,
+                "{flag}"
+            );
+        }
+        let p = parse(&["-f", "--no-force"]);
+        assert!(!p.prefs.force && !p.prefs.overwrite && !p.force_overwrite);
+    }
+
     #[test]
     fn keep_flag() {
         let p = parse(&["--keep"]);
         assert!(!p.prefs.remove_src_file);
     }
 
+    #[test]
+    fn no_keep_mirrors_keep() {
+        assert!(parse(&["--no-keep"]).prefs.remove_src_file);
+        assert!(!parse(&["--no-keep", "--keep"]).prefs.remove_src_file);
+        assert!(parse(&["-k", "--no-keep"]).prefs.remove_src_file);
+    }
+
+    #[test]
+    fn gzip_compat_profile_yields_to_explicit_flags() {
+        let p = parse(&["--gzip-compat"]);
+        assert!(p.prefs.gzip_compat && p.prefs.remove_src_file);
+        assert!(!p.prefs.overwrite);
+        // The profile is applied first, wherever it appears.
+        let p = parse(&["-k", "-f", "--gzip-compat"]);
+        assert!(p.prefs.gzip_compat);
+        assert!(!p.prefs.remove_src_file && p.prefs.overwrite);
+        // After `--` it is a file name.
+        let p = parse(&["--", "--gzip-compat"]);
+        assert!(!p.prefs.gzip_compat);
+        assert_eq!(p.input_filename.as_deref(), Some("--gzip-compat"));
+    }
+
+    #[test]
+    fn no_restore_times_and_touch() {
+        assert!(parse(&[]).prefs.restore_times);
+        assert!(!parse(&["--no-restore-times"]).prefs.restore_times);
+        assert!(!parse(&["--touch"]).prefs.restore_times);
+        assert!(!parse(&["--no-timestamp"]).prefs.restore_times);
+    }
+
+    #[test]
+    fn same_owner_flags() {
+        assert!(!parse(&[]).prefs.restore_owner);
+        assert!(parse(&["--same-owner"]).prefs.restore_owner);
+        assert!(
+            !parse(&["--same-owner", "--no-same-owner"])
+                .prefs
+                .restore_owner
+        );
+    }
+
+    #[test]
+    fn no_atomic_flag() {
+        assert!(parse(&[]).prefs.atomic_output);
+        assert!(!parse(&["--no-atomic"]).prefs.atomic_output);
+    }
+
     #[test]
     fn no_frame_crc() {
         let p = parse(&["--no-frame-crc"]);
@@ -958,12 +1462,225 @@ mod tests {
         assert!(p.bench_config.skip_checksums);
     }
 
+    #[test]
+    fn no_crc_skips_verification() {
+        let p = parse(&["--no-crc"]);
+        assert!(!p.prefs.stream_checksum);
+        assert!(!p.prefs.block_checksum);
+        assert_eq!(p.prefs.checksum_policy, ChecksumPolicy::SKIP);
+        assert!(p.bench_config.skip_checksums);
+        assert_eq!(
+            parse(&["--no-crc", "--frame-crc"]).prefs.checksum_policy,
+            ChecksumPolicy::VERIFY
+        );
+    }
+
+    #[test]
+    fn recover() {
+        assert!(parse(&["--recover"]).prefs.recover);
+        assert!(!parse(&[]).prefs.recover);
+    }
+
     #[test]
     fn content_size() {
         let p = parse(&["--content-size"]);
         assert!(p.prefs.content_size_flag);
     }
 
+    #[test]
+    fn files_from_takes_path_and_sets_multiple() {
+        let p = parse(&["--files-from", "list.txt", "extra"]);
+        assert_eq!(p.files_from.as_deref(), Some("list.txt"));
+        assert!(p.multiple_inputs);
+        assert_eq!(p.in_file_names, ["extra"]);
+        let p = parse(&["-d", "--files-from=-"]);
+        assert_eq!(p.files_from.as_deref(), Some(STDIN_MARK));
+        assert!(parse(&["a"]).files_from.is_none());
+        parse_err(&["--files-from"]);
+        parse_err(&["--files-from="]);
+    }
+
+    #[test]
+    fn output_dir_takes_path_and_sets_multiple() {
+        let p = parse(&["--output-dir", "out", "a", "b"]);
+        assert_eq!(p.output_dir.as_deref(), Some("out"));
+        assert!(p.multiple_inputs);
+        assert_eq!(p.in_file_names, ["a", "b"]);
+        assert_eq!(
+            parse(&["--output-dir=out"]).output_dir.as_deref(),
+            Some("out")
+        );
+        assert!(parse(&["a"]).output_dir.is_none());
+        parse_err(&["--output-dir"]);
+        parse_err(&["--output-dir="]);
+    }
+
+    fn write_config(text: &str) -> tempfile::NamedTempFile {
+        use std::io::Write;
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn config_file_options_are_overridden_by_arguments() {
+        let rc = write_config("# defaults\n-9 --rm   # comment\n\n--sparse -T4\n");
+        let default = Some(rc.path().to_path_buf());
+        let with_default = |args: &[&str]| {
+            let init = CliInit {
+                config_file: default.clone(),
+                ..detect_alias("lz4")
+            };
+            parse_args_from(init, "lz4", &make_args(args)).expect("parse failed")
+        };
+
+        let p = with_default(&["in"]);
+        assert_eq!(p.c_level, 9);
+        assert!(p.prefs.remove_src_file);
+        assert_eq!(p.nb_workers, 4);
+        assert_eq!(p.input_filename.as_deref(), Some("in"));
+        assert_eq!(with_default(&["-3", "in"]).c_level, 3);
+        assert_eq!(with_default(&["--no-config", "in"]).c_level, 1);
+
+        // A missing default file is ignored.
+        let init = CliInit {
+            config_file: Some(rc.path().with_extension("missing")),
+            ..detect_alias("lz4")
+        };
+        assert_eq!(
+            parse_args_from(init, "lz4", &make_args(&["in"]))
+                .unwrap()
+                .c_level,
+            1
+        );
+    }
+
+    #[test]
+    fn config_flag_names_the_file() {
+        let rc = write_config("-5\n");
+        let path = rc.path().to_str().unwrap();
+        let p = parse(&["--config", path, "in"]);
+        assert_eq!((p.c_level, p.input_filename.as_deref()), (5, Some("in")));
+        assert_eq!(parse(&[&format!("--config={path}"), "-2"]).c_level, 2);
+        // `--no-config` wins over `--config`.
+        assert_eq!(parse(&["--config", path, "--no-config"]).c_level, 1);
+        // After `--`, `--config` is a file name.
+        let p = parse(&["-m", "--", "--config"]);
+        assert_eq!(p.c_level, 1);
+        assert_eq!(p.in_file_names, ["--config"]);
+
+        parse_err(&["--config"]);
+        parse_err(&["--config=/nonexistent/lz4rc"]);
+        for bad in ["-9 file.txt", "--", "--config=x", "--no-config"] {
+            let rc = write_config(bad);
+            parse_err(&["--config", rc.path().to_str().unwrap()]);
+        }
+    }
+
+    #[test]
+    fn synthetic_sets_entropy_and_size() {
+        let p = parse(&["-b", "--synthetic=0.25"]);
+        assert_eq!(p.bench_config.synthetic_entropy, Some(0.25));
+        assert_eq!(
+            p.bench_config.synthetic_size,
+            crate::bench::config::SYNTHETIC_SIZE
+        );
+        let p = parse(&["-b", "--synthetic", "1,64K"]);
+        assert_eq!(p.bench_config.synthetic_entropy, Some(1.0));
+        assert_eq!(p.bench_config.synthetic_size, 65536);
+        assert_eq!(parse(&["-b"]).bench_config.synthetic_entropy, None);
+        parse_err(&["--synthetic"]);
+        parse_err(&["--synthetic=1.5"]);
+        parse_err(&["--synthetic=x"]);
+        parse_err(&["--synthetic=0.5,0"]);
+        parse_err(&["--synthetic=0.5,12x"]);
+        let p = parse(&["-b", "--synthetic=0.5,2Mi"]);
+        assert_eq!(p.bench_config.synthetic_size, 2 << 20);
+    }
+
+    #[test]
+    fn dict_report_sets_bench_config() {
+        assert!(
+            parse(&["-b", "-D", "dict", "--dict-report"])
+                .bench_config
+                .dict_report
+        );
+        assert!(!parse(&["-b", "-D", "dict"]).bench_config.dict_report);
+    }
+
+    #[test]
+    fn mtime_window_takes_seconds_in_both_forms() {
+        let p = parse(&["-m", "--mtime-window", "2", "a", "b"]);
+        assert_eq!(p.prefs.mtime_window, Some(2));
+        // Stale outputs follow the usual overwrite rules.
+        assert_eq!(p.prefs.overwrite, parse(&["-m", "a", "b"]).prefs.overwrite);
+        let p = parse(&["-m", "--mtime-window=0", "a"]);
+        assert_eq!(p.prefs.mtime_window, Some(0));
+        assert_eq!(parse(&["-m", "a"]).prefs.mtime_window, None);
+        parse_err(&["--mtime-window=2s"]);
+        parse_err(&["--mtime-window"]);
+    }
+
+    #[test]
+    fn flush_every_takes_size_in_both_forms() {
+        let p = parse(&["-d", "--flush-every", "4096", "in.lz4"]);
+        assert_eq!(p.prefs.flush_every, Some(4096));
+        let p = parse(&["-d", "--flush-every=64K", "in.lz4"]);
+        assert_eq!(p.prefs.flush_every, Some(64 * 1024));
+        assert_eq!(parse(&["-d", "in.lz4"]).prefs.flush_every, None);
+        parse_err(&["--flush-every=0"]);
+        parse_err(&["--flush-every=12x"]);
+        parse_err(&["--flush-every"]);
+        let p = parse(&["-d", "--flush-every", "1MiB", "in.lz4"]);
+        assert_eq!(p.prefs.flush_every, Some(1 << 20));
+        // Trailing text is rejected in the separate-argument form too.
+        parse_err(&["--flush-every", "12x"]);
+        parse_err(&["--flush-every", "-1"]);
+    }
+
+    #[test]
+    fn preset_archive() {
+        for argv in [&["--preset", "archive"][..], &["--preset=archive"][..]] {
+            let p = parse(argv);
+            assert_eq!(p.prefs.block_size_id, 5);
+            assert!(p.prefs.block_independence);
+            assert!(p.prefs.block_checksum);
+            assert!(p.prefs.stream_checksum);
+            assert!(p.prefs.content_size_flag);
+        }
+        let err = parse_err(&["--preset=fastest"]);
+        assert!(err.to_string().contains("--preset"));
+    }
+
+    #[test]
+    fn progress_flag() {
+        assert!(parse(&[]).prefs.progress_sink.is_none());
+        let p = parse(&["--progress"]);
+        assert!(p.prefs.progress_sink.is_some());
+    }
+
+    #[test]
+    fn quiet_progress_flag() {
+        assert!(!parse(&[]).prefs.quiet_progress);
+        let p = parse(&["--quiet-progress"]);
+        assert!(p.prefs.quiet_progress);
+        // The later of --progress and --quiet-progress wins.
+        let p = parse(&["--progress", "--quiet-progress"]);
+        assert!(p.prefs.quiet_progress && p.prefs.progress_sink.is_none());
+        let p = parse(&["--quiet-progress", "--progress"]);
+        assert!(!p.prefs.quiet_progress && p.prefs.progress_sink.is_some());
+    }
+
+    #[test]
+    fn log_format_flag() {
+        use crate::io::diag::{log_format, set_log_format, LogFormat};
+        parse(&["--log-format=json"]);
+        assert_eq!(log_format(), LogFormat::Json);
+        parse(&["--log-format=plain"]);
+        assert_eq!(log_format(), LogFormat::Plain);
+        set_log_format(LogFormat::Plain);
+    }
+
     // ── Sparse ───────────────────────────────────────────────────────────────
 
     #[test]
@@ -7,8 +7,9 @@
 //! - Binary size multipliers (`KB`, `MB`, `GB`)
 //! - The verbosity level used by [`displaylevel!`] and friends
 //! - The legacy-command flag that activates `lz4c`-style short options
-//! - The [`displayout!`], [`display!`], [`displaylevel!`], [`debugoutput!`],
-//!   and [`end_process!`] output macros used throughout the CLI
+//! - The [`displayout!`], [`display!`], [`displaylevel!`], [`displayerror!`],
+//!   [`displaywarning!`], [`debugoutput!`], and [`end_process!`] output macros
+//!   used throughout the CLI
 
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
@@ -25,6 +26,10 @@ pub const LZ4CAT: &str = "lz4cat";
 pub const UNLZ4: &str = "unlz4";
 /// Name of the legacy `lz4c` binary whose short-option dialect this library supports.
 pub const LZ4_LEGACY: &str = "lz4c";
+/// Name of the binary alias that starts with gzip-style defaults (`--gzip-compat`).
+pub const LZ4GZ: &str = "lz4gz";
+/// Name of the per-user config file of default options, looked up in the home directory.
+pub const LZ4RC: &str = ".lz4rc";
 
 /// Format string for the startup welcome banner.
 ///
@@ -103,6 +108,9 @@ pub fn set_lz4c_legacy_commands(enabled: bool) {
 //   displayout!  — informational output that belongs on stdout (e.g. decompressed data)
 //   display!     — diagnostic output that always goes to stderr
 //   displaylevel! — conditional stderr output gated on the current verbosity level
+//
+// Warnings and errors go through `displaywarning!` / `displayerror!` instead,
+// so they honour `--log-format`.
 
 /// Write a formatted message to **stdout**.
 ///
@@ -139,6 +147,29 @@ macro_rules! displaylevel {
     };
 }
 
+/// Write a formatted **error** to stderr, in the current
+/// [`LogFormat`](crate::io::diag::LogFormat), if the verbosity level is ≥ 1.
+#[macro_export]
+macro_rules! displayerror {
+    ($($arg:tt)*) => {
+        if $crate::cli::constants::display_level() >= 1 {
+            $crate::io::diag::emit($crate::io::diag::Severity::Error, &format!($($arg)*));
+        }
+    };
+}
+
+/// Write a formatted **warning** to stderr, in the current
+/// [`LogFormat`](crate::io::diag::LogFormat), if the verbosity level is at
+/// least `level`.
+#[macro_export]
+macro_rules! displaywarning {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::cli::constants::display_level() >= $level {
+            $crate::io::diag::emit($crate::io::diag::Severity::Warning, &format!($($arg)*));
+        }
+    };
+}
+
 // ── Debug and fatal-error macros ─────────────────────────────────────────────
 //
 // `debugoutput!` — emits to stderr in debug builds only; a no-op in release.
@@ -169,14 +200,13 @@ macro_rules! debugoutput {
 #[macro_export]
 macro_rules! end_process {
     ($error:expr, $($arg:tt)*) => {{
+        let msg = format!("Error {} : {}", $error, format!($($arg)*));
         // In debug builds, include the source location for easier triage.
         #[cfg(debug_assertions)]
-        eprint!("Error in {}, line {} : \n", file!(), line!());
+        let msg = format!("Error in {}, line {} : \n{}", file!(), line!(), msg);
         // Respect a verbosity of 0 (fully silent mode).
         if $crate::cli::constants::display_level() >= 1 {
-            eprint!("Error {} : ", $error);
-            eprint!($($arg)*);
-            eprint!("\n");
+            $crate::io::diag::emit($crate::io::diag::Severity::Error, &msg);
         }
         std::process::exit($error as i32);
     }};
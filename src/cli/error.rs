@@ -0,0 +1,228 @@
+//! Error taxonomy and process exit codes for the `lz4` binary.
+//!
+//! Every failure that reaches `main` is a [`CliError`].  Its category decides
+//! the exit status, so scripts can tell a missing file from a damaged archive:
+//!
+//! | Variant                  | Exit code | Meaning |
+//! |--------------------------|-----------|---------|
+//! | [`CliError::Usage`]      | 1         | Bad arguments or an operation that cannot run as requested. |
+//! | [`CliError::Io`]         | 2         | A file could not be opened, read or written. |
+//! | [`CliError::Corrupt`]    | 3         | Input is not valid LZ4 data (bad header, truncated, malformed block). |
+//! | [`CliError::Checksum`]   | 4         | Input decoded, but a header, block or content checksum did not match. |
+//! | [`CliError::Damaged`]    | 5         | `--recover` wrote the output, but skipped damaged blocks. |
+//!
+//! I/O-layer functions report failures as [`io::Error`]; the `From` conversion
+//! classifies them by looking for a [`DamagedOutput`] or an [`Lz4FError`] in
+//! the error's source chain and otherwise falling back on the [`io::ErrorKind`].
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+
+use crate::frame::Lz4FError;
+use crate::io::DamagedOutput;
+
+/// Exit code for a successful run.
+pub const EXIT_OK: i32 = 0;
+/// Exit code for [`CliError::Usage`].
+pub const EXIT_USAGE: i32 = 1;
+/// Exit code for [`CliError::Io`].
+pub const EXIT_IO: i32 = 2;
+/// Exit code for [`CliError::Corrupt`].
+pub const EXIT_CORRUPT: i32 = 3;
+/// Exit code for [`CliError::Checksum`].
+pub const EXIT_CHECKSUM: i32 = 4;
+/// Exit code for [`CliError::Damaged`].
+pub const EXIT_DAMAGED: i32 = 5;
+
+/// A failure of the `lz4` command, categorised by exit code.
+#[derive(Debug)]
+pub enum CliError {
+    /// Invalid command line or an operation that cannot proceed (e.g. refusing
+    /// to write compressed data to a terminal).
+    Usage(String),
+    /// Opening, reading or writing a file failed.
+    Io(io::Error),
+    /// The input is not a well-formed LZ4 stream.
+    Corrupt(io::Error),
+    /// A checksum stored in the input did not match the decoded data.
+    Checksum(io::Error),
+    /// `--recover` skipped damaged blocks; the output was written without them.
+    Damaged(io::Error),
+}
+
+impl CliError {
+    /// Process exit status for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::Io(_) => EXIT_IO,
+            CliError::Corrupt(_) => EXIT_CORRUPT,
+            CliError::Checksum(_) => EXIT_CHECKSUM,
+            CliError::Damaged(_) => EXIT_DAMAGED,
+        }
+    }
+
+    /// Build a [`CliError::Usage`] from a message.
+    pub fn usage(msg: impl Into<String>) -> Self {
+        CliError::Usage(msg.into())
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(msg) => f.write_str(msg),
+            CliError::Io(e)
+            | CliError::Corrupt(e)
+            | CliError::Checksum(e)
+            | CliError::Damaged(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl StdError for CliError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            CliError::Usage(_) => None,
+            CliError::Io(e)
+            | CliError::Corrupt(e)
+            | CliError::Checksum(e)
+            | CliError::Damaged(e) => Some(e),
+        }
+    }
+}
+
+/// Whether `e`'s payload or its source chain holds a [`DamagedOutput`].
+fn is_damaged_output(e: &io::Error) -> bool {
+    let mut cur: Option<&(dyn StdError + 'static)> = e.get_ref().map(|inner| inner as _);
+    while let Some(err) = cur {
+        if err.is::<DamagedOutput>() {
+            return true;
+        }
+        cur = err.source();
+    }
+    false
+}
+
+/// Find the first [`Lz4FError`] in `e`'s payload or its source chain.
+fn find_lz4f_error(e: &io::Error) -> Option<Lz4FError> {
+    let mut cur: Option<&(dyn StdError + 'static)> = e.get_ref().map(|inner| inner as _);
+    while let Some(err) = cur {
+        if let Some(&lz4f) = err.downcast_ref::<Lz4FError>() {
+            return Some(lz4f);
+        }
+        if let Some(inner) = err.downcast_ref::<io::Error>() {
+            if let Some(found) = find_lz4f_error(inner) {
+                return Some(found);
+            }
+        }
+        cur = err.source();
+    }
+    None
+}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        if is_damaged_output(&e) {
+            return CliError::Damaged(e);
+        }
+        match find_lz4f_error(&e) {
+            Some(
+                Lz4FError::HeaderChecksumInvalid
+                | Lz4FError::BlockChecksumInvalid
+                | Lz4FError::ContentChecksumInvalid,
+            ) => CliError::Checksum(e),
+            Some(_) => CliError::Corrupt(e),
+            None => match e.kind() {
+                io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => CliError::Corrupt(e),
+                _ => CliError::Io(e),
+            },
+        }
+    }
+}
+
+impl From<anyhow::Error> for CliError {
+    /// Argument-parsing failures are usage errors.
+    fn from(e: anyhow::Error) -> Self {
+        CliError::Usage(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::decompress_frame::lz4f_err_to_io;
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let codes = [
+            CliError::usage("x").exit_code(),
+            CliError::Io(io::Error::other("x")).exit_code(),
+            CliError::Corrupt(io::Error::other("x")).exit_code(),
+            CliError::Checksum(io::Error::other("x")).exit_code(),
+            CliError::Damaged(io::Error::other("x")).exit_code(),
+        ];
+        assert_eq!(
+            codes,
+            [
+                EXIT_USAGE,
+                EXIT_IO,
+                EXIT_CORRUPT,
+                EXIT_CHECKSUM,
+                EXIT_DAMAGED
+            ]
+        );
+    }
+
+    #[test]
+    fn classifies_checksum_failures() {
+        for e in [
+            Lz4FError::HeaderChecksumInvalid,
+            Lz4FError::BlockChecksumInvalid,
+            Lz4FError::ContentChecksumInvalid,
+        ] {
+            let err = CliError::from(lz4f_err_to_io(e));
+            assert_eq!(err.exit_code(), EXIT_CHECKSUM, "{e:?}");
+        }
+    }
+
+    #[test]
+    fn classifies_corrupt_data() {
+        let err = CliError::from(lz4f_err_to_io(Lz4FError::DecompressionFailed));
+        assert_eq!(err.exit_code(), EXIT_CORRUPT);
+        let err = CliError::from(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated"));
+        assert_eq!(err.exit_code(), EXIT_CORRUPT);
+    }
+
+    #[test]
+    fn classifies_wrapped_lz4f_error() {
+        let inner = lz4f_err_to_io(Lz4FError::ContentChecksumInvalid);
+        let outer = io::Error::new(inner.kind(), inner);
+        assert_eq!(CliError::from(outer).exit_code(), EXIT_CHECKSUM);
+    }
+
+    #[test]
+    fn classifies_damaged_output() {
+        let err = CliError::from(io::Error::new(
+            io::ErrorKind::InvalidData,
+            DamagedOutput { damaged_blocks: 2 },
+        ));
+        assert_eq!(err.exit_code(), EXIT_DAMAGED);
+    }
+
+    #[test]
+    fn classifies_io_failures() {
+        let err = CliError::from(io::Error::from(io::ErrorKind::NotFound));
+        assert_eq!(err.exit_code(), EXIT_IO);
+        let err = CliError::from(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert_eq!(err.exit_code(), EXIT_IO);
+    }
+
+    #[test]
+    fn parse_errors_are_usage() {
+        let err = CliError::from(anyhow::anyhow!("bad usage: nope"));
+        assert_eq!(err.exit_code(), EXIT_USAGE);
+        assert_eq!(err.to_string(), "bad usage: nope");
+    }
+}
@@ -1,12 +1,13 @@
 //! Help and usage text for the `lz4` CLI.
 //!
 //! Provides functions that write brief usage, advanced options, and long-form
-//! help to stderr, along with error-exit and interactive-pause helpers used by
-//! the argument parser.
+//! help to stderr, along with an interactive-pause helper used by the argument
+//! parser.  Failures are reported as [`CliError`](crate::cli::error::CliError)s,
+//! not by exiting here.
 
 use std::io::{self, Write};
 
-use crate::cli::constants::{display_level, lz4c_legacy_commands, LZ4_EXTENSION};
+use crate::cli::constants::{lz4c_legacy_commands, LZ4_EXTENSION};
 
 /// Maximum HC compression level (12), corresponding to the `--best` flag.
 const LZ4HC_CLEVEL_MAX: i32 = 12;
@@ -26,17 +27,6 @@ const STDOUTMARK: &str = "stdout";
 /// Sentinel string that discards all output (useful for integrity testing).
 const NULL_OUTPUT: &str = "null";
 
-/// Print `msg` to stderr and exit with code 1.
-///
-/// The message is suppressed when the global display level is below 1
-/// (i.e. when `-qq` has been passed).
-pub fn error_out(msg: &str) -> ! {
-    if display_level() >= 1 {
-        eprintln!("{} ", msg);
-    }
-    std::process::exit(1);
-}
-
 /// Print a brief usage summary to stderr.
 pub fn print_usage(program: &str) {
     eprintln!("Usage : ");
@@ -58,9 +48,9 @@ pub fn print_usage(program: &str) {
         " -d     : decompression (default for {} extension)",
         LZ4_EXTENSION
     );
-    eprintln!(" -f     : overwrite output without prompting ");
+    eprintln!(" -f     : overwrite output without prompting, also compress block devices ");
     eprintln!(" -k     : preserve source files(s)  (default) ");
-    eprintln!("--rm    : remove source file(s) after successful de/compression ");
+    eprintln!("--rm    : remove source file(s) after successful de/compression (also --no-keep) ");
     eprintln!(" -h/-H  : display help/long help and exit ");
 }
 
@@ -98,26 +88,59 @@ pub fn print_usage_advanced(program: &str) {
     eprintln!(" -v     : verbose mode ");
     eprintln!(" -q     : suppress warnings; specify twice to suppress errors too");
     eprintln!(" -c     : force write to standard output, even if it is the console");
-    eprintln!(" -t     : test compressed file integrity");
+    eprintln!(" -t     : test integrity of each compressed file (sets also -m) ");
     eprintln!(" -m     : multiple input files (implies automatic output filenames)");
     #[cfg(feature = "recursive")]
     eprintln!(" -r     : operate recursively on directories (sets also -m) ");
     eprintln!(" -l     : compress using Legacy format (Linux kernel compression)");
     eprintln!(" -z     : force compression ");
     eprintln!(" -D FILE: use FILE as dictionary (compression & decompression)");
-    eprintln!(" -B#    : cut file into blocks of size # bytes [32+] ");
+    eprintln!(" -B#    : cut file into blocks of size # bytes [32+], e.g. 4096, 64K, 1MiB ");
     eprintln!(
-        "                     or predefined block size [4-7] (default: {}) ",
+        "                     or predefined block size [4-7] (default: auto, up to {}) ",
         LZ4_BLOCKSIZEID_DEFAULT
     );
     eprintln!(" -BI    : Block Independence (default) ");
     eprintln!(" -BD    : Block dependency (improves compression ratio) ");
     eprintln!(" -BX    : enable block checksum (default:disabled) ");
     eprintln!("--no-frame-crc : disable stream checksum (default:enabled) ");
+    eprintln!("--no-crc : disable stream and block checksums; skip verifying them with -d and -b ");
+    eprintln!(
+        "--recover : with -d, skip independent blocks failing their checksum and keep going (exit status 5) "
+    );
     eprintln!("--content-size : compressed frame includes original size (default:not present)");
+    eprintln!(
+        "--preset archive : content size, frame and block checksums, independent 256 KB blocks "
+    );
     eprintln!("--list FILE : lists information about .lz4 files (useful for files compressed with --content-size flag)");
+    eprintln!(
+        "--info FILE : --list, plus a quick check decoding the first and last block of each frame "
+    );
+    eprintln!("-m --join OUT FILES : compress each FILE as its own frame into one archive OUT ");
+    eprintln!("--files-from FILE : also read input file names from FILE (- = stdin), one per line or NUL-terminated (sets also -m) ");
+    eprintln!("--output-dir DIR : write compressed outputs into DIR; with -r, recreate the source tree under it (sets also -m) ");
+    eprintln!("--extract ARCHIVE [DIR] : split an archive made with --join into DIR (default: .) ");
+    eprintln!(
+        "--self-test [DIR] : check the conformance vectors in DIR (default: generated in memory); with --output-dir, write them there first "
+    );
     eprintln!("--[no-]sparse  : sparse mode (default:enabled on file, disabled on stdout)");
+    eprintln!("--no-timestamp (or --touch) : give outputs the current time instead of the source's mtime ");
+    eprintln!(
+        "--[no-]same-owner : copy the source's owner and group to outputs (default:disabled) "
+    );
+    eprintln!("--gzip-compat : gzip-style defaults: remove sources (kept with -c), refuse to overwrite without -f (default when run as lz4gz) ");
+    eprintln!("--no-atomic : write outputs in place instead of through a temporary file renamed on success ");
+    eprintln!("--mtime-window SECS : skip sources whose output is at most SECS older and records the same size; rebuild the rest ");
+    eprintln!(
+        "--flush-every BYTES : when decompressing, flush output at least every BYTES (e.g. 64K) "
+    );
     eprintln!("--favor-decSpeed: compressed files decompress faster, but are less compressed ");
+    eprintln!("--progress : always display progress, even when stderr is not a terminal ");
+    eprintln!("--quiet-progress : no progress lines, but keep the final summaries ");
+    eprintln!("--log-format=FMT : warnings and errors as plain, color, auto or json (one object per line) ");
+    eprintln!("--config FILE : read default options from FILE instead of ~/.lz4rc; arguments override them ");
+    eprintln!("--no-config : ignore ~/.lz4rc ");
+    eprintln!("Environment variables LZ4_CLEVEL and LZ4_NBWORKERS set the default level and -T#; ~/.lz4rc and arguments override them ");
     eprintln!(
         "--fast[=#]: switch to ultra fast compression level (default: {})",
         1
@@ -127,6 +150,8 @@ pub fn print_usage_advanced(program: &str) {
     eprintln!(" -b#    : benchmark file(s), using # compression level (default : 1) ");
     eprintln!(" -e#    : test all compression levels from -bX to # (default : 1)");
     eprintln!(" -i#    : minimum evaluation time in seconds (default : 3s) ");
+    eprintln!("--synthetic=E[,SIZE] : without files, benchmark SIZE bytes (default : 10 MB) of data with entropy E from 0.0 (compressible) to 1.0 ");
+    eprintln!("--dict-report : with -D, benchmark without and with the dictionary and compare ratios per level ");
 
     // Legacy arguments are only shown when the binary is invoked as `lz4c`.
     if lz4c_legacy_commands() {
@@ -223,18 +248,6 @@ pub fn print_long_help(program: &str) {
     }
 }
 
-/// Print "Incorrect parameters" to stderr, show brief usage, and exit with code 1.
-///
-/// Both the message and the usage text are suppressed when the display level
-/// is below 1 (i.e. when `-qq` has been passed).
-pub fn print_bad_usage(program: &str) -> ! {
-    if display_level() >= 1 {
-        eprintln!("Incorrect parameters");
-        print_usage(program);
-    }
-    std::process::exit(1);
-}
-
 /// Print a prompt to stderr and block until the user presses Enter.
 ///
 /// Reads exactly one byte from stdin via `libc::getchar` so that only the
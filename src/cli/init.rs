@@ -1,6 +1,6 @@
 //! CLI initialization and binary-alias detection.
 //!
-//! When LZ4 is installed under multiple names (`lz4cat`, `unlz4`, `lz4c`)
+//! When LZ4 is installed under multiple names (`lz4cat`, `unlz4`, `lz4c`, `lz4gz`)
 //! via hard or symbolic links, the program detects which operation mode to
 //! enter from `argv[0]` before any flag parsing takes place.
 //!
@@ -10,11 +10,12 @@
 
 use crate::cli::arg_utils::{exe_name_match, last_name_from_path};
 use crate::cli::constants::{
-    set_display_level, set_lz4c_legacy_commands, LZ4CAT, LZ4_LEGACY, UNLZ4,
+    set_display_level, set_lz4c_legacy_commands, LZ4CAT, LZ4GZ, LZ4_LEGACY, UNLZ4,
 };
-use crate::cli::op_mode::{init_c_level, init_nb_workers, OpMode};
+use crate::cli::op_mode::{init_c_level, init_config_file, init_nb_workers, OpMode};
+use crate::frame::types::BlockSizePolicy;
 use crate::io::file_io::STDOUT_MARK;
-use crate::io::prefs::{Prefs, LZ4IO_BLOCKSIZEID_DEFAULT};
+use crate::io::prefs::Prefs;
 
 /// Initial CLI state derived from the binary name and environment.
 ///
@@ -41,6 +42,9 @@ pub struct CliInit {
     /// Display level override applied by the alias (e.g. `lz4cat` sets level 1).
     /// The caller should apply this via `set_display_level` after `detect_alias` returns.
     pub display_level_override: Option<u32>,
+    /// Config file of default options (`~/.lz4rc`), read if it exists unless
+    /// `--config` names another file or `--no-config` is given.
+    pub config_file: Option<std::path::PathBuf>,
 }
 
 /// Detect the operation mode and initial settings from `argv[0]`.
@@ -53,6 +57,7 @@ pub struct CliInit {
 /// | `lz4cat`    | Decompress + pass-through + force stdout + multiple inputs     |
 /// | `unlz4`     | Decompress only                                                |
 /// | `lz4c`      | Enable legacy option spellings                                 |
+/// | `lz4gz`     | gzip-style defaults (see [`Prefs::set_gzip_compat`])           |
 ///
 /// `argv0` may be a full path; the basename is extracted internally.
 ///
@@ -62,11 +67,14 @@ pub struct CliInit {
 pub fn detect_alias(argv0: &str) -> CliInit {
     let exe_name = last_name_from_path(argv0);
 
-    let mut prefs = Prefs::default();
-    // `Prefs::default()` sets overwrite to true; the normal invocation path starts
-    // with it disabled.  The lz4cat branch below re-enables it explicitly.
-    prefs.overwrite = false;
-    prefs.set_block_size_id(LZ4IO_BLOCKSIZEID_DEFAULT);
+    // `Prefs::default()` sets overwrite and force to true; the normal invocation
+    // path starts with both disabled.  The lz4cat branch below re-enables
+    // overwrite explicitly.
+    let mut prefs = Prefs::builder()
+        .force(false)
+        .block_size_policy(BlockSizePolicy::Auto)
+        .atomic_output(true)
+        .build();
 
     let mut op_mode = OpMode::Auto;
     let mut lz4c_legacy = false;
@@ -103,6 +111,11 @@ pub fn detect_alias(argv0: &str) -> CliInit {
         set_lz4c_legacy_commands(true);
     }
 
+    // lz4gz: same as `--gzip-compat`; explicit flags still override it.
+    if exe_name_match(exe_name, LZ4GZ) {
+        prefs.set_gzip_compat(true);
+    }
+
     CliInit {
         prefs,
         op_mode,
@@ -113,6 +126,7 @@ pub fn detect_alias(argv0: &str) -> CliInit {
         force_stdout,
         output_filename,
         display_level_override,
+        config_file: init_config_file(),
     }
 }
 
@@ -272,6 +286,18 @@ mod tests {
         assert!(!init.prefs.overwrite);
     }
 
+    // ── lz4gz alias ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn lz4gz_applies_gzip_compat_profile() {
+        reset_globals();
+        let init = detect_alias("/usr/bin/lz4gz");
+        assert!(init.prefs.gzip_compat);
+        assert!(init.prefs.remove_src_file);
+        assert!(!init.prefs.overwrite);
+        assert_eq!(init.op_mode, OpMode::Auto);
+    }
+
     // ── unrecognised binary name ─────────────────────────────────────────────
 
     #[test]
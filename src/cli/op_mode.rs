@@ -7,8 +7,8 @@
 //! - [`LZ4_CLEVEL_DEFAULT`] / [`LZ4_NBWORKERS_DEFAULT`] — fallback constants used when no
 //!   environment override is present.
 
-use crate::cli::arg_utils::read_u32_from_str;
-use crate::cli::constants::{display_level, LZ4_EXTENSION};
+use crate::cli::arg_utils::read_size_from_str;
+use crate::cli::constants::LZ4_EXTENSION;
 
 /// Default compression level (1 — fast, lossless). Used when `LZ4_CLEVEL` is unset or invalid.
 pub const LZ4_CLEVEL_DEFAULT: i32 = 1;
@@ -30,6 +30,12 @@ pub enum OpMode {
     Bench,
     /// Print metadata about LZ4 archives.
     List,
+    /// Print `--list` metadata, then spot-check the first and last block of each frame.
+    Info,
+    /// Split an archive written by `--join` back into its member files.
+    Extract,
+    /// Generate or check frame-format conformance vectors.
+    SelfTest,
 }
 
 /// Infer the operation mode from `filename`'s extension.
@@ -46,8 +52,9 @@ pub fn determine_op_mode(filename: &str) -> OpMode {
 
 /// Read the number of worker threads from the `LZ4_NBWORKERS` environment variable.
 ///
-/// If the variable is set and starts with a decimal digit, it is parsed as an
-/// unsigned integer. Otherwise [`LZ4_NBWORKERS_DEFAULT`] (`0` — auto) is returned.
+/// If the variable is set to an unsigned integer that fits in a `u32`, that
+/// value is used; anything else, trailing text included, is ignored with a
+/// warning. Otherwise [`LZ4_NBWORKERS_DEFAULT`] (`0` — auto) is returned.
 pub fn init_nb_workers() -> usize {
     init_nb_workers_from(std::env::var("LZ4_NBWORKERS").ok().as_deref())
 }
@@ -58,27 +65,67 @@ pub fn init_nb_workers() -> usize {
 /// unset. Separating env-var I/O from parsing keeps the conversion logic
 /// unit-testable without touching the process environment.
 pub fn init_nb_workers_from(env_val: Option<&str>) -> usize {
-    const ENV_NBTHREADS: &str = "LZ4_NBWORKERS";
-    if let Some(env) = env_val {
-        if let Some((val, _rest)) = read_u32_from_str(env) {
-            return val as usize;
+    env_val
+        .and_then(|env| read_env_u32("LZ4_NBWORKERS", env))
+        .map_or(LZ4_NBWORKERS_DEFAULT, |val| val as usize)
+}
+
+/// Parses the whole of an environment variable's value as an unsigned
+/// number, with the suffixes [`read_size_from_str`] accepts, like the C CLI's
+/// `readU32FromCharChecked`.  Warns at level 2 and returns `None` when the
+/// value has trailing text or does not fit in a `u32`.
+fn read_env_u32(name: &str, env: &str) -> Option<u32> {
+    let too_large = match read_size_from_str(env) {
+        Some((val, "")) => match u32::try_from(val) {
+            Ok(val) => return Some(val),
+            Err(_) => true,
+        },
+        Some(_) => false,
+        // Digits alone only fail to parse when they overflow a u64.
+        None => !env.is_empty() && env.bytes().all(|b| b.is_ascii_digit()),
+    };
+    crate::displaywarning!(
+        2,
+        "Ignore environment variable setting {}={}: {} ",
+        name,
+        env,
+        if too_large {
+            "numeric value too large"
+        } else {
+            "not a valid unsigned value"
         }
-        // Non-numeric value — warn and fall through to default.
-        if display_level() >= 2 {
-            eprintln!(
-                "Ignore environment variable setting {}={}: not a valid unsigned value ",
-                ENV_NBTHREADS, env
-            );
+    );
+    None
+}
+
+/// Locate the per-user config file, `~/.lz4rc`.
+///
+/// The home directory comes from `HOME`, or `USERPROFILE` on Windows.  The
+/// path is returned whether or not the file exists; a missing default file is
+/// not an error.
+pub fn init_config_file() -> Option<std::path::PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| {
+        if cfg!(windows) {
+            std::env::var_os("USERPROFILE")
+        } else {
+            None
         }
-    }
-    LZ4_NBWORKERS_DEFAULT
+    });
+    init_config_file_from(home.as_deref())
+}
+
+/// Testable core of [`init_config_file`]: the config file under `home`.
+pub fn init_config_file_from(home: Option<&std::ffi::OsStr>) -> Option<std::path::PathBuf> {
+    home.filter(|h| !h.is_empty())
+        .map(|h| std::path::Path::new(h).join(crate::cli::constants::LZ4RC))
 }
 
 /// Read the default compression level from the `LZ4_CLEVEL` environment variable.
 ///
-/// If the variable is set and starts with a decimal digit, it is parsed as an
-/// unsigned integer and widened to `i32`. Otherwise [`LZ4_CLEVEL_DEFAULT`] (1)
-/// is returned.
+/// If the variable is set to an unsigned integer that fits in a `u32`, it is
+/// widened to `i32`; anything else, trailing text included, is ignored with a
+/// warning. Otherwise [`LZ4_CLEVEL_DEFAULT`] (1) is returned.  `-#` and
+/// `~/.lz4rc` both override it.
 pub fn init_c_level() -> i32 {
     init_c_level_from(std::env::var("LZ4_CLEVEL").ok().as_deref())
 }
@@ -89,20 +136,9 @@ pub fn init_c_level() -> i32 {
 /// unset. Separating env-var I/O from parsing keeps the conversion logic
 /// unit-testable without touching the process environment.
 pub fn init_c_level_from(env_val: Option<&str>) -> i32 {
-    const ENV_CLEVEL: &str = "LZ4_CLEVEL";
-    if let Some(env) = env_val {
-        if let Some((val, _rest)) = read_u32_from_str(env) {
-            return val as i32;
-        }
-        // Non-numeric value — warn and fall through to default.
-        if display_level() >= 2 {
-            eprintln!(
-                "Ignore environment variable setting {}={}: not a valid unsigned value ",
-                ENV_CLEVEL, env
-            );
-        }
-    }
-    LZ4_CLEVEL_DEFAULT
+    env_val
+        .and_then(|env| read_env_u32("LZ4_CLEVEL", env))
+        .map_or(LZ4_CLEVEL_DEFAULT, |val| val as i32)
 }
 
 #[cfg(test)]
@@ -112,8 +148,8 @@ mod tests {
     // ── OpMode enum ──────────────────────────────────────────────────────────
 
     #[test]
-    fn op_mode_has_six_variants() {
-        // Ensure all six variants exist and are distinct.
+    fn op_mode_has_nine_variants() {
+        // Ensure all nine variants exist and are distinct.
         let variants = [
             OpMode::Auto,
             OpMode::Compress,
@@ -121,8 +157,11 @@ mod tests {
             OpMode::Test,
             OpMode::Bench,
             OpMode::List,
+            OpMode::Info,
+            OpMode::Extract,
+            OpMode::SelfTest,
         ];
-        assert_eq!(variants.len(), 6);
+        assert_eq!(variants.len(), 9);
     }
 
     // ── determine_op_mode ───────────────────────────────────────────────────
@@ -196,4 +235,12 @@ mod tests {
         std::env::remove_var("LZ4_CLEVEL");
         assert_eq!(result, LZ4_CLEVEL_DEFAULT);
     }
+
+    #[test]
+    fn init_config_file_from_home() {
+        let path = init_config_file_from(Some(std::ffi::OsStr::new("/home/u"))).unwrap();
+        assert_eq!(path, std::path::Path::new("/home/u/.lz4rc"));
+        assert_eq!(init_config_file_from(Some(std::ffi::OsStr::new(""))), None);
+        assert_eq!(init_config_file_from(None), None);
+    }
 }
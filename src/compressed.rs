@@ -0,0 +1,181 @@
+//! `serde` integration: values stored as LZ4-framed blobs.
+//!
+//! [`Lz4Compressed<T, F>`] wraps a value that, when serialized, is first
+//! encoded with the inner format `F`, then compressed into a standard LZ4
+//! frame and handed to the outer serializer as a byte string.  Deserializing
+//! reverses the steps, so the wrapper can sit in any field of a config record
+//! or message envelope and keep large payloads small on the wire.
+//!
+//! The inner format is any serde data format that can write a value to bytes
+//! and read it back; plugging one in takes a few lines through
+//! [`BlobFormat`]:
+//!
+//! ```ignore
+//! struct Json;
+//! impl lz4::compressed::BlobFormat for Json {
+//!     type Error = serde_json::Error;
+//!     fn to_vec<T: serde::Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Self::Error> {
+//!         serde_json::to_vec(value)
+//!     }
+//!     fn from_slice<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, Self::Error> {
+//!         serde_json::from_slice(bytes)
+//!     }
+//! }
+//! type CompressedDoc = lz4::compressed::Lz4Compressed<Vec<String>, Json>;
+//! ```
+//!
+//! The blob is an ordinary LZ4 frame, so `lz4 -d` can unpack it once the
+//! outer format's byte-string encoding has been stripped.
+//!
+//! Deserialized blobs come from untrusted input, so decoding stops at
+//! [`BlobFormat::MAX_DECOMPRESSED_SIZE`] bytes ([`DEFAULT_MAX_DECOMPRESSED_SIZE`]
+//! unless the format overrides it): a small frame cannot claim gigabytes.
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use serde::de::{self, DeserializeOwned, SeqAccess, Visitor};
+use serde::ser;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::frame::{compress_frame_to_vec, decompress_frame_to_vec_with_limit};
+
+/// Default for [`BlobFormat::MAX_DECOMPRESSED_SIZE`]: 64 MiB.
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: u64 = 64 << 20;
+
+/// A serde data format used to encode the value inside an [`Lz4Compressed`].
+pub trait BlobFormat {
+    /// Error reported by the format's encoder and decoder.
+    type Error: fmt::Display;
+
+    /// Largest decoded blob accepted when deserializing.  A frame that
+    /// decodes to more is a deserialization error.  Override it in the
+    /// format's impl to accept larger values.
+    const MAX_DECOMPRESSED_SIZE: u64 = DEFAULT_MAX_DECOMPRESSED_SIZE;
+
+    /// Encode `value` to bytes.
+    fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Self::Error>;
+
+    /// Decode a value previously written by [`to_vec`](Self::to_vec).
+    fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Self::Error>;
+}
+
+/// A value serialized as an LZ4 frame of its `F`-encoded bytes.
+///
+/// Dereferences to the wrapped value.
+pub struct Lz4Compressed<T, F> {
+    value: T,
+    format: PhantomData<fn() -> F>,
+}
+
+impl<T, F> Lz4Compressed<T, F> {
+    /// Wrap `value`.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            format: PhantomData,
+        }
+    }
+
+    /// Unwrap the inner value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, F> From<T> for Lz4Compressed<T, F> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T, F> Deref for Lz4Compressed<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, F> DerefMut for Lz4Compressed<T, F> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: Clone, F> Clone for Lz4Compressed<T, F> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for Lz4Compressed<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Lz4Compressed").field(&self.value).finish()
+    }
+}
+
+impl<T: PartialEq, F> PartialEq for Lz4Compressed<T, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, F> Eq for Lz4Compressed<T, F> {}
+
+impl<T: Default, F> Default for Lz4Compressed<T, F> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Serialize, F: BlobFormat> Serialize for Lz4Compressed<T, F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let raw = F::to_vec(&self.value).map_err(ser::Error::custom)?;
+        serializer.serialize_bytes(&compress_frame_to_vec(&raw))
+    }
+}
+
+impl<'de, T: DeserializeOwned, F: BlobFormat> Deserialize<'de> for Lz4Compressed<T, F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let framed = deserializer.deserialize_byte_buf(FrameBytesVisitor)?;
+        let raw = decompress_frame_to_vec_with_limit(&framed, F::MAX_DECOMPRESSED_SIZE)
+            .map_err(de::Error::custom)?;
+        F::from_slice(&raw)
+            .map(Self::new)
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Largest capacity reserved up front from a sequence's length hint.  The
+/// hint comes from the input, so a larger claim only grows as bytes arrive.
+const MAX_SEQ_PREALLOC: usize = 1 << 20;
+
+/// Accepts a byte string however the outer format represents it: native
+/// bytes, or a sequence of integers (JSON and other text formats).
+struct FrameBytesVisitor;
+
+impl<'de> Visitor<'de> for FrameBytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an LZ4 frame as a byte string")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut out = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(MAX_SEQ_PREALLOC));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            out.push(byte);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,444 @@
+//! Frame-format conformance vectors.
+//!
+//! [`generate`] builds a canonical corpus of frames, one for every
+//! combination of the header options a decoder has to handle: the four block
+//! sizes, linked and independent blocks, block checksums, content checksums,
+//! a declared content size, a dictionary ID, and a leading skippable frame.
+//! [`write_vectors`] stores the corpus in a directory and [`verify_dir`]
+//! checks one, so the vectors can be exchanged with other implementations in
+//! both directions: decode ours with their decoder, or check the vectors
+//! their encoder wrote with `lz4 --self-test DIR`.
+//!
+//! A vector directory holds:
+//!
+//! | File             | Contents |
+//! |------------------|----------|
+//! | `content.bin`    | the data every vector decodes to |
+//! | `dictionary.bin` | the dictionary of the `dict1` vectors |
+//! | `<name>.lz4`     | one vector, named by [`VectorSpec::name`] |
+//!
+//! Checking a vector decodes it, compares the output with `content.bin`, and
+//! checks that the frame header carries exactly the options its name claims.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::frame::skippable::{is_skippable_magic, skippable_frame_bound};
+use crate::frame::types::LZ4F_VERSION;
+use crate::frame::{
+    lz4f_compress_begin, lz4f_compress_begin_using_dict, lz4f_compress_end,
+    lz4f_compress_frame_bound, lz4f_compress_update, lz4f_decompress_using_dict,
+    lz4f_get_frame_info, read_skippable_frame, write_skippable_frame, BlockChecksum, BlockMode,
+    BlockSizeId, ContentChecksum, FrameInfo, Lz4FCCtx, Lz4FDCtx, Lz4FError, Preferences,
+};
+
+/// Dictionary ID written into the header of every `dict1` vector.
+pub const DICT_ID: u32 = 0x4C5A_3444;
+
+/// Payload of the skippable frame leading every `skip1` vector.
+pub const SKIPPABLE_PAYLOAD: &[u8] = b"lz4 conformance vector";
+
+/// Name of the content file in a vector directory.
+pub const CONTENT_FILE: &str = "content.bin";
+
+/// Name of the dictionary file in a vector directory.
+pub const DICTIONARY_FILE: &str = "dictionary.bin";
+
+/// Size of [`content`]: more than one 64 KB block, so that linked and
+/// independent blocks differ.
+const CONTENT_SIZE: usize = 100_000;
+const DICTIONARY_SIZE: usize = 16 * 1024;
+
+const BLOCK_SIZES: [BlockSizeId; 4] = [
+    BlockSizeId::Max64Kb,
+    BlockSizeId::Max256Kb,
+    BlockSizeId::Max1Mb,
+    BlockSizeId::Max4Mb,
+];
+
+/// The frame options of one conformance vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorSpec {
+    /// Maximum block size written in the header.
+    pub block_size_id: BlockSizeId,
+    /// Linked or independent blocks.
+    pub block_mode: BlockMode,
+    /// A checksum follows every block.
+    pub block_checksum: bool,
+    /// A content checksum follows the end mark.
+    pub content_checksum: bool,
+    /// The header declares the content size.
+    pub content_size: bool,
+    /// Compressed with the dictionary; the header carries [`DICT_ID`].
+    pub dict: bool,
+    /// A skippable frame carrying [`SKIPPABLE_PAYLOAD`] precedes the frame.
+    pub skippable: bool,
+}
+
+impl VectorSpec {
+    /// Every combination of options, 256 in all, in [`name`](Self::name)
+    /// order.
+    pub fn all() -> Vec<VectorSpec> {
+        let mut specs = Vec::with_capacity(256);
+        for block_size_id in BLOCK_SIZES {
+            for block_mode in [BlockMode::Linked, BlockMode::Independent] {
+                for bits in 0..32u32 {
+                    let bit = |n: u32| bits & (1 << n) != 0;
+                    specs.push(VectorSpec {
+                        block_size_id,
+                        block_mode,
+                        block_checksum: bit(4),
+                        content_checksum: bit(3),
+                        content_size: bit(2),
+                        dict: bit(1),
+                        skippable: bit(0),
+                    });
+                }
+            }
+        }
+        specs
+    }
+
+    /// File stem of the vector, e.g. `b4-linked-bc1-cc0-sz1-dict0-skip0`:
+    /// the block size ID, the block mode, then one digit per option.
+    pub fn name(&self) -> String {
+        format!(
+            "b{}-{}-bc{}-cc{}-sz{}-dict{}-skip{}",
+            self.block_size_id as u32,
+            match self.block_mode {
+                BlockMode::Linked => "linked",
+                BlockMode::Independent => "indep",
+            },
+            self.block_checksum as u8,
+            self.content_checksum as u8,
+            self.content_size as u8,
+            self.dict as u8,
+            self.skippable as u8,
+        )
+    }
+
+    /// Parses a name written by [`name`](Self::name); `None` if it is not one.
+    pub fn parse(name: &str) -> Option<VectorSpec> {
+        let mut parts = name.split('-');
+        let block_size_id = match parts.next()? {
+            "b4" => BlockSizeId::Max64Kb,
+            "b5" => BlockSizeId::Max256Kb,
+            "b6" => BlockSizeId::Max1Mb,
+            "b7" => BlockSizeId::Max4Mb,
+            _ => return None,
+        };
+        let block_mode = match parts.next()? {
+            "linked" => BlockMode::Linked,
+            "indep" => BlockMode::Independent,
+            _ => return None,
+        };
+        let mut flag = |prefix: &str| match parts.next()?.strip_prefix(prefix)? {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => None,
+        };
+        let spec = VectorSpec {
+            block_size_id,
+            block_mode,
+            block_checksum: flag("bc")?,
+            content_checksum: flag("cc")?,
+            content_size: flag("sz")?,
+            dict: flag("dict")?,
+            skippable: flag("skip")?,
+        };
+        parts.next().is_none().then_some(spec)
+    }
+
+    /// The frame header this spec produces for `content_len` bytes of
+    /// content.
+    pub fn frame_info(&self, content_len: usize) -> FrameInfo {
+        FrameInfo {
+            block_size_id: self.block_size_id,
+            block_mode: self.block_mode,
+            content_checksum_flag: if self.content_checksum {
+                ContentChecksum::Enabled
+            } else {
+                ContentChecksum::Disabled
+            },
+            content_size: if self.content_size {
+                content_len as u64
+            } else {
+                0
+            },
+            dict_id: if self.dict { DICT_ID } else { 0 },
+            block_checksum_flag: if self.block_checksum {
+                BlockChecksum::Enabled
+            } else {
+                BlockChecksum::Disabled
+            },
+            ..FrameInfo::default()
+        }
+    }
+}
+
+/// The canonical content every generated vector decodes to.
+pub fn content() -> Vec<u8> {
+    crate::lorem::gen_buffer(CONTENT_SIZE, 0x4C5A)
+}
+
+/// The canonical dictionary of the `dict1` vectors.
+pub fn dictionary() -> Vec<u8> {
+    crate::lorem::gen_buffer(DICTIONARY_SIZE, 0x4443)
+}
+
+/// Encodes `content` as the vector described by `spec`.
+///
+/// Uses the streaming encoder, which keeps the requested block size even
+/// when the content would fit a smaller one.
+pub fn encode(spec: &VectorSpec, content: &[u8], dict: &[u8]) -> Result<Vec<u8>, Lz4FError> {
+    let prefs = Preferences {
+        frame_info: spec.frame_info(content.len()),
+        ..Preferences::default()
+    };
+    let skip_len = if spec.skippable {
+        skippable_frame_bound(SKIPPABLE_PAYLOAD.len())
+    } else {
+        0
+    };
+    let mut out = vec![0u8; skip_len + lz4f_compress_frame_bound(content.len(), Some(&prefs))];
+    let mut pos = 0;
+    if spec.skippable {
+        pos += write_skippable_frame(&mut out, 0, SKIPPABLE_PAYLOAD)?;
+    }
+
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    pos += if spec.dict {
+        lz4f_compress_begin_using_dict(&mut cctx, &mut out[pos..], dict, Some(&prefs))?
+    } else {
+        lz4f_compress_begin(&mut cctx, &mut out[pos..], Some(&prefs))?
+    };
+    pos += lz4f_compress_update(&mut cctx, &mut out[pos..], content, None)?;
+    pos += lz4f_compress_end(&mut cctx, &mut out[pos..], None)?;
+    out.truncate(pos);
+    Ok(out)
+}
+
+/// Every vector, encoded from [`content`] and [`dictionary`].
+pub fn generate() -> Vec<(VectorSpec, Vec<u8>)> {
+    let content = content();
+    let dict = dictionary();
+    VectorSpec::all()
+        .into_iter()
+        .map(|spec| {
+            let frame = encode(&spec, &content, &dict)
+                .unwrap_or_else(|e| panic!("encoding {} failed: {e}", spec.name()));
+            (spec, frame)
+        })
+        .collect()
+}
+
+/// Writes the content, the dictionary and every vector into `dir`, creating
+/// it if needed.  Returns the number of vectors written.
+pub fn write_vectors(dir: &Path) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(CONTENT_FILE), content())?;
+    fs::write(dir.join(DICTIONARY_FILE), dictionary())?;
+    let vectors = generate();
+    for (spec, frame) in &vectors {
+        fs::write(dir.join(format!("{}.lz4", spec.name())), frame)?;
+    }
+    Ok(vectors.len())
+}
+
+/// Why a vector failed its check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VectorError {
+    /// The leading skippable frame is missing, unexpected, or carries the
+    /// wrong payload.
+    Skippable,
+    /// The header field `field` does not match the vector's name.
+    Header { field: &'static str },
+    /// The frame did not decode.
+    Decode(Lz4FError),
+    /// The frame ended before its end mark.
+    Truncated,
+    /// Bytes follow the frame.
+    TrailingData,
+    /// The frame decoded, but not to the expected content.
+    Content,
+}
+
+impl fmt::Display for VectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VectorError::Skippable => f.write_str("skippable frame missing or wrong"),
+            VectorError::Header { field } => write!(f, "header field {field} does not match"),
+            VectorError::Decode(e) => write!(f, "decoding failed: {e}"),
+            VectorError::Truncated => f.write_str("frame is truncated"),
+            VectorError::TrailingData => f.write_str("data after the frame"),
+            VectorError::Content => f.write_str("decoded content differs"),
+        }
+    }
+}
+
+impl std::error::Error for VectorError {}
+
+/// Checks one vector: its header must match `spec` and it must decode to
+/// `content`, using `dict` when `spec.dict` is set.
+pub fn check_vector(
+    spec: &VectorSpec,
+    vector: &[u8],
+    content: &[u8],
+    dict: &[u8],
+) -> Result<(), VectorError> {
+    let leading_skippable = vector.len() >= 4
+        && is_skippable_magic(u32::from_le_bytes([
+            vector[0], vector[1], vector[2], vector[3],
+        ]));
+    let mut frame = vector;
+    if spec.skippable {
+        let skip = read_skippable_frame(vector).map_err(|_| VectorError::Skippable)?;
+        if skip.payload != SKIPPABLE_PAYLOAD {
+            return Err(VectorError::Skippable);
+        }
+        frame = &vector[skip.frame_size()..];
+    } else if leading_skippable {
+        return Err(VectorError::Skippable);
+    }
+
+    let mut dctx = Lz4FDCtx::new(LZ4F_VERSION);
+    let (info, _, _) = lz4f_get_frame_info(&mut dctx, frame).map_err(VectorError::Decode)?;
+    let want = spec.frame_info(content.len());
+    let fields = [
+        ("block size", info.block_size_id == want.block_size_id),
+        ("block mode", info.block_mode == want.block_mode),
+        (
+            "block checksum",
+            info.block_checksum_flag == want.block_checksum_flag,
+        ),
+        (
+            "content checksum",
+            info.content_checksum_flag == want.content_checksum_flag,
+        ),
+        ("content size", info.content_size == want.content_size),
+        ("dictionary ID", info.dict_id == want.dict_id),
+    ];
+    if let Some(&(field, _)) = fields.iter().find(|(_, ok)| !ok) {
+        return Err(VectorError::Header { field });
+    }
+
+    // Decode from scratch: the header parse above advanced the context.
+    let mut dctx = Lz4FDCtx::new(LZ4F_VERSION);
+    let dict = if spec.dict { dict } else { &[] };
+    let mut out = Vec::with_capacity(content.len());
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut pos = 0;
+    loop {
+        let (consumed, written, hint) =
+            lz4f_decompress_using_dict(&mut dctx, Some(&mut buf), &frame[pos..], dict, None)
+                .map_err(VectorError::Decode)?;
+        out.extend_from_slice(&buf[..written]);
+        pos += consumed;
+        if hint == 0 {
+            break;
+        }
+        if consumed == 0 && written == 0 {
+            return Err(VectorError::Truncated);
+        }
+    }
+    if pos != frame.len() {
+        return Err(VectorError::TrailingData);
+    }
+    if out != content {
+        return Err(VectorError::Content);
+    }
+    Ok(())
+}
+
+/// Result of checking a set of vectors.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Number of vectors that passed.
+    pub passed: usize,
+    /// Vectors that failed, by name, with the reason.
+    pub failed: Vec<(String, VectorError)>,
+    /// Names from [`VectorSpec::all`] with no vector in the directory.
+    pub missing: Vec<String>,
+}
+
+impl VerifyReport {
+    /// `true` if every vector was present and passed.
+    pub fn is_ok(&self) -> bool {
+        self.failed.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Checks the vectors [`generate`] produces, without touching the disk.
+pub fn self_test() -> VerifyReport {
+    let content = content();
+    let dict = dictionary();
+    let mut report = VerifyReport::default();
+    for (spec, frame) in generate() {
+        match check_vector(&spec, &frame, &content, &dict) {
+            Ok(()) => report.passed += 1,
+            Err(e) => report.failed.push((spec.name(), e)),
+        }
+    }
+    report
+}
+
+/// Checks every vector in `dir` against the directory's `content.bin` and
+/// `dictionary.bin`.
+///
+/// Every name in [`VectorSpec::all`] is looked for; absent ones are listed in
+/// [`VerifyReport::missing`].  Other files are ignored.  Fails only when the
+/// content or dictionary file cannot be read.
+pub fn verify_dir(dir: &Path) -> io::Result<VerifyReport> {
+    let read = |name: &str| {
+        let path = dir.join(name);
+        fs::read(&path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+    };
+    let content = read(CONTENT_FILE)?;
+    let dict = read(DICTIONARY_FILE)?;
+    let mut report = VerifyReport::default();
+    for spec in VectorSpec::all() {
+        let name = spec.name();
+        match fs::read(dir.join(format!("{name}.lz4"))) {
+            Ok(vector) => match check_vector(&spec, &vector, &content, &dict) {
+                Ok(()) => report.passed += 1,
+                Err(e) => report.failed.push((name, e)),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => report.missing.push(name),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_unique_and_parse_back() {
+        let specs = VectorSpec::all();
+        assert_eq!(specs.len(), 256);
+        let mut names: Vec<String> = specs.iter().map(VectorSpec::name).collect();
+        for (spec, name) in specs.iter().zip(&names) {
+            assert_eq!(VectorSpec::parse(name), Some(*spec));
+        }
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), 256);
+    }
+
+    #[test]
+    fn parse_rejects_other_names() {
+        for name in [
+            "",
+            "b8-linked-bc0-cc0-sz0-dict0-skip0",
+            "b4-linked-bc2-cc0-sz0-dict0-skip0",
+            "b4-linked-bc0-cc0-sz0-dict0",
+            "b4-linked-bc0-cc0-sz0-dict0-skip0-x",
+        ] {
+            assert_eq!(VectorSpec::parse(name), None, "{name}");
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Deterministic synthetic corpora for benchmarks and tests.
+//!
+//! Every generator takes an output size and a `u32` seed and returns exactly
+//! `size` bytes; the same seed always produces the same bytes on every
+//! platform.  The generators cover a range of compressibility:
+//!
+//! | Generator        | Content                                              |
+//! |------------------|------------------------------------------------------|
+//! | [`lorem`]        | Latin prose (the built-in benchmark corpus)          |
+//! | [`log_lines`]    | Timestamped server log lines with repeated fields    |
+//! | [`json_records`] | One JSON object per line with a fixed set of keys    |
+//! | [`binary`]       | Random bytes with a chosen entropy per byte          |
+//! | [`compressible`] | Random literals and back-references in a set mix     |
+//!
+//! [`Corpus`] names a generator so callers can pick one at run time.
+
+// ---------------------------------------------------------------------------
+// PRNG
+// ---------------------------------------------------------------------------
+
+/// SplitMix64: small, fast and stable across platforms.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u32) -> Self {
+        Rng(u64::from(seed) ^ 0x5DEE_CE66_D1CE_4E5B)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `[0, range)`; `range` must be non-zero.
+    fn below(&mut self, range: u32) -> u32 {
+        (((self.next_u64() >> 32) * u64::from(range)) >> 32) as u32
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u32) as usize]
+    }
+}
+
+/// Appends lines from `line` until `size` bytes are written, cutting the last.
+fn fill_lines(size: usize, seed: u32, mut line: impl FnMut(&mut Rng, u64, &mut String)) -> Vec<u8> {
+    let mut rng = Rng::new(seed);
+    let mut out = Vec::with_capacity(size);
+    let mut buf = String::new();
+    let mut n = 0u64;
+    while out.len() < size {
+        buf.clear();
+        line(&mut rng, n, &mut buf);
+        let take = buf.len().min(size - out.len());
+        out.extend_from_slice(&buf.as_bytes()[..take]);
+        n += 1;
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Generators
+// ---------------------------------------------------------------------------
+
+/// Lorem ipsum text; identical to [`crate::lorem::gen_buffer`].
+pub fn lorem(size: usize, seed: u32) -> Vec<u8> {
+    crate::lorem::gen_buffer(size, seed)
+}
+
+static LEVELS: &[&str] = &["INFO ", "INFO ", "INFO ", "DEBUG", "WARN ", "ERROR"];
+static METHODS: &[&str] = &["GET", "GET", "GET", "POST", "PUT", "DELETE"];
+static PATHS: &[&str] = &[
+    "/api/v1/users",
+    "/api/v1/orders",
+    "/api/v1/items",
+    "/api/v2/search",
+    "/healthz",
+    "/static/app.js",
+    "/login",
+];
+static STATUSES: &[&str] = &["200", "200", "200", "201", "204", "304", "404", "500"];
+
+/// Server log lines such as
+/// `2024-03-01T12:00:07.123Z INFO  [worker-3] GET /api/v1/users status=200 latency_ms=14 req=5f3a…`.
+///
+/// Timestamps increase monotonically; request ids are random hex.
+pub fn log_lines(size: usize, seed: u32) -> Vec<u8> {
+    use std::fmt::Write;
+
+    let mut millis = 0u64;
+    fill_lines(size, seed, |rng, _, line| {
+        millis += u64::from(rng.below(900)) + 1;
+        let secs = millis / 1000;
+        let _ = writeln!(
+            line,
+            "2024-03-01T{:02}:{:02}:{:02}.{:03}Z {} [worker-{}] {} {} status={} latency_ms={} req={:016x}",
+            12 + secs / 3600 % 12,
+            secs / 60 % 60,
+            secs % 60,
+            millis % 1000,
+            rng.pick(LEVELS),
+            rng.below(8),
+            rng.pick(METHODS),
+            rng.pick(PATHS),
+            rng.pick(STATUSES),
+            rng.below(250),
+            rng.next_u64(),
+        );
+    })
+}
+
+static NAMES: &[&str] = &[
+    "alice", "bob", "carol", "dave", "erin", "frank", "grace", "heidi", "ivan", "judy",
+];
+static TAGS: &[&str] = &["new", "vip", "trial", "legacy", "beta", "eu", "us", "apac"];
+
+/// Newline-delimited JSON objects with the same keys in the same order, e.g.
+/// `{"id":17,"name":"carol","active":true,"score":73.25,"tags":["vip","eu"]}`.
+pub fn json_records(size: usize, seed: u32) -> Vec<u8> {
+    use std::fmt::Write;
+
+    fill_lines(size, seed, |rng, n, line| {
+        let _ = write!(
+            line,
+            "{{\"id\":{},\"name\":\"{}\",\"active\":{},\"score\":{}.{:02},\"tags\":[",
+            n,
+            rng.pick(NAMES),
+            rng.below(4) != 0,
+            rng.below(100),
+            rng.below(4) * 25,
+        );
+        for i in 0..rng.below(4) {
+            if i > 0 {
+                line.push(',');
+            }
+            let _ = write!(line, "\"{}\"", rng.pick(TAGS));
+        }
+        line.push_str("]}\n");
+    })
+}
+
+/// Random bytes carrying about `bits_per_byte` bits of entropy each.
+///
+/// Each byte is drawn uniformly from an alphabet of `2^bits_per_byte`
+/// symbols, so 0 gives a run of one value and 8 (the cap) is incompressible.
+pub fn binary(size: usize, seed: u32, bits_per_byte: u32) -> Vec<u8> {
+    let bits = bits_per_byte.min(8);
+    let mut rng = Rng::new(seed);
+    // Spread the alphabet over the byte range so symbols are not all small.
+    let base = (rng.next_u64() & 0xFF) as u8;
+    let mut out = Vec::with_capacity(size);
+    while out.len() < size {
+        let word = rng.next_u64();
+        for i in 0..8 {
+            if out.len() == size {
+                break;
+            }
+            let symbol = if bits == 0 {
+                0
+            } else {
+                (word >> (i * 8)) as u8 >> (8 - bits)
+            };
+            out.push(base.wrapping_add(symbol.wrapping_mul(0x9D)));
+        }
+    }
+    out
+}
+
+/// Random literal runs interleaved with copies of earlier output.
+///
+/// `entropy` (clamped to `0.0..=1.0`) is the expected fraction of bytes that
+/// are fresh random literals; the rest repeat a span from the previous
+/// 64 KiB, as an LZ compressor would find it.  The first 4 KiB are always
+/// literals, so the history is varied.  1.0 is incompressible, and
+/// the ratio rises steadily as it falls towards 0.0, which gives a smooth
+/// curve to chart level against ratio per entropy class.
+pub fn compressible(size: usize, seed: u32, entropy: f64) -> Vec<u8> {
+    // Mean lengths of a literal run (1..=16) and of a match (4..=260).
+    const LITERAL_MEAN: f64 = 8.5;
+    const MATCH_MEAN: f64 = 132.0;
+    let entropy = entropy.clamp(0.0, 1.0);
+    // Odds of a literal run per segment such that literals make up
+    // `entropy` of the bytes.
+    let literal_odds =
+        entropy * MATCH_MEAN / (entropy * MATCH_MEAN + (1.0 - entropy) * LITERAL_MEAN);
+    let threshold = (literal_odds * f64::from(u32::MAX)) as u32;
+    let mut rng = Rng::new(seed);
+    let mut out = Vec::with_capacity(size);
+    while out.len() < size {
+        let roll = (rng.next_u64() >> 32) as u32;
+        // A random 4 KiB prefix gives the matches varied history to copy.
+        if out.len() < 4096 || (threshold > 0 && roll <= threshold) {
+            let len = (1 + rng.below(16) as usize).min(size - out.len());
+            for _ in 0..len {
+                out.push(rng.next_u64() as u8);
+            }
+        } else {
+            let len = (4 + rng.below(257) as usize).min(size - out.len());
+            let window = out.len().min(65_535) as u32;
+            let start = out.len() - 1 - rng.below(window) as usize;
+            // Byte by byte: the copy may overlap what it produces.
+            for i in 0..len {
+                out.push(out[start + i]);
+            }
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Corpus selector
+// ---------------------------------------------------------------------------
+
+/// A named generator from this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corpus {
+    /// [`lorem`]
+    Lorem,
+    /// [`log_lines`]
+    Logs,
+    /// [`json_records`]
+    Json,
+    /// [`binary`] with the given bits of entropy per byte.
+    Binary { bits_per_byte: u32 },
+}
+
+impl Corpus {
+    /// Generate `size` bytes of this corpus from `seed`.
+    pub fn generate(self, size: usize, seed: u32) -> Vec<u8> {
+        match self {
+            Corpus::Lorem => lorem(size, seed),
+            Corpus::Logs => log_lines(size, seed),
+            Corpus::Json => json_records(size, seed),
+            Corpus::Binary { bits_per_byte } => binary(size, seed, bits_per_byte),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Dictionary size rules shared by every dictionary entry point.
+//!
+//! LZ4 matches reach at most 64 KiB back, so only the last
+//! [`MAX_DICT_SIZE`] bytes of a dictionary can ever be referenced.  Every
+//! API that accepts a dictionary — [`Lz4FCDict`](crate::frame::Lz4FCDict),
+//! the HC dictionary loader, the frame decoder and the CLI's `-D` option —
+//! keeps that tail and ignores the rest.  [`effective_dict`] is the single
+//! place that rule lives, so callers can see in advance which bytes will be
+//! used.
+//!
+//! [`DictResolver`] maps the dictionary IDs stored in frame headers to
+//! dictionaries, for decoders that serve frames made with many of them.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+/// Largest dictionary LZ4 can reference (64 KiB).
+pub const MAX_DICT_SIZE: usize = 64 * 1024;
+
+/// The part of `dict` that is actually used: its last [`MAX_DICT_SIZE`]
+/// bytes, or all of it when shorter.
+///
+/// ```
+/// let dict = vec![7u8; 1 << 20];
+/// assert_eq!(lz4::dict::effective_dict(&dict).len(), lz4::dict::MAX_DICT_SIZE);
+/// assert_eq!(lz4::dict::effective_dict(b"short"), b"short");
+/// ```
+#[inline]
+pub fn effective_dict(dict: &[u8]) -> &[u8] {
+    &dict[dict.len().saturating_sub(MAX_DICT_SIZE)..]
+}
+
+/// Warning for a dictionary of `dict_len` bytes that will be truncated, or
+/// `None` when it fits.  The CLI prints it for `-D` files above 64 KiB.
+pub fn truncation_warning(dict_len: u64) -> Option<String> {
+    (dict_len > MAX_DICT_SIZE as u64).then(|| {
+        format!(
+            "Warning : dictionary is {} bytes; only its last {} bytes are used \n",
+            dict_len, MAX_DICT_SIZE
+        )
+    })
+}
+
+/// A registry the frame decoder consults for the dictionary a frame names.
+///
+/// Installed with
+/// [`Lz4FDCtx::set_dict_resolver`](crate::frame::Lz4FDCtx::set_dict_resolver),
+/// it is asked for the dictionary whenever a frame header carries a nonzero
+/// dictionary ID and the caller did not pass one explicitly.  The result is
+/// copied into the context (only its [`effective_dict`] tail), so a borrowed
+/// slice need only live for the call.
+///
+/// Implemented for `HashMap<u32, Vec<u8>>` and for closures returning an
+/// owned dictionary:
+///
+/// ```
+/// use lz4::dict::DictResolver;
+///
+/// let registry = std::collections::HashMap::from([(7u32, b"tenant dictionary".to_vec())]);
+/// assert_eq!(registry.resolve(7).as_deref(), Some(&b"tenant dictionary"[..]));
+/// assert!(registry.resolve(8).is_none());
+///
+/// let fetch = |id: u32| (id == 7).then(|| b"from storage".to_vec());
+/// assert!(fetch.resolve(7).is_some());
+/// ```
+pub trait DictResolver: Send + Sync {
+    /// The dictionary with ID `dict_id`, or `None` when it is unknown.
+    fn resolve(&self, dict_id: u32) -> Option<Cow<'_, [u8]>>;
+}
+
+impl<S: BuildHasher + Send + Sync> DictResolver for HashMap<u32, Vec<u8>, S> {
+    fn resolve(&self, dict_id: u32) -> Option<Cow<'_, [u8]>> {
+        self.get(&dict_id).map(|d| Cow::Borrowed(&d[..]))
+    }
+}
+
+impl<F> DictResolver for F
+where
+    F: Fn(u32) -> Option<Vec<u8>> + Send + Sync,
+{
+    fn resolve(&self, dict_id: u32) -> Option<Cow<'_, [u8]>> {
+        self(dict_id).map(Cow::Owned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_dict_keeps_the_tail() {
+        let dict: Vec<u8> = (0..MAX_DICT_SIZE as u32 + 10).map(|i| i as u8).collect();
+        assert_eq!(effective_dict(&dict), &dict[10..]);
+        assert_eq!(
+            effective_dict(&dict[..MAX_DICT_SIZE]),
+            &dict[..MAX_DICT_SIZE]
+        );
+        assert!(effective_dict(&[]).is_empty());
+    }
+
+    #[test]
+    fn warning_only_above_the_limit() {
+        assert_eq!(truncation_warning(MAX_DICT_SIZE as u64), None);
+        let msg = truncation_warning(1 << 20).unwrap();
+        assert!(msg.contains("1048576 bytes"), "{msg}");
+        assert!(msg.contains("last 65536 bytes"), "{msg}");
+    }
+}
@@ -0,0 +1,55 @@
+//! Kernel read-ahead hints for large, memory-mapped inputs.
+//!
+//! When [`lz4f_compress_frame_to_writer`](super::lz4f_compress_frame_to_writer)
+//! is handed a slice backed by a read-only file mapping, the first touch of
+//! every page is a major fault.  On spinning disks and network filesystems
+//! the default read-ahead window is far smaller than an LZ4 block, so the
+//! compressor stalls on I/O once per few pages.  These helpers forward `madvise(2)`
+//! hints so the kernel reads the whole input sequentially and starts
+//! fetching the next block window while the current one is compressed.
+//!
+//! Hints are advisory: failures (e.g. `ENOMEM` for heap memory that is not
+//! file-backed) are ignored.  Without the `mmap` feature, or on non-Unix
+//! targets, every function is a no-op.
+
+/// Hints that `region` will be read once, front to back.
+#[inline]
+pub(crate) fn sequential(region: &[u8]) {
+    #[cfg(all(feature = "mmap", unix))]
+    advise(region, libc::MADV_SEQUENTIAL);
+    #[cfg(not(all(feature = "mmap", unix)))]
+    let _ = region;
+}
+
+/// Hints that `region` will be needed soon, so the kernel can prefetch it.
+#[inline]
+pub(crate) fn will_need(region: &[u8]) {
+    #[cfg(all(feature = "mmap", unix))]
+    advise(region, libc::MADV_WILLNEED);
+    #[cfg(not(all(feature = "mmap", unix)))]
+    let _ = region;
+}
+
+/// Issues `madvise(advice)` over the pages spanned by `region`.
+///
+/// `madvise` requires a page-aligned start address, so the range is widened
+/// down to the enclosing page boundary.  The widened range stays inside the
+/// same mapping because mappings are themselves page-aligned.
+#[cfg(all(feature = "mmap", unix))]
+fn advise(region: &[u8], advice: libc::c_int) {
+    if region.is_empty() {
+        return;
+    }
+    // SAFETY: sysconf has no preconditions.
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    let page = if page > 0 { page as usize } else { 4096 };
+    let start = region.as_ptr() as usize;
+    let aligned = start & !(page - 1);
+    let len = region.len() + (start - aligned);
+    // SAFETY: madvise only changes kernel paging behaviour; it never reads
+    // or writes the memory, and the range covers pages already mapped for
+    // `region`.
+    unsafe {
+        libc::madvise(aligned as *mut libc::c_void, len, advice);
+    }
+}
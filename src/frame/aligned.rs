@@ -0,0 +1,216 @@
+//! Aligned staging buffers for the frame contexts.
+//!
+//! [`Lz4FCCtx::tmp_buf`](crate::frame::Lz4FCCtx::tmp_buf) and the
+//! decompression context's `tmp_in` / `tmp_out_buffer` are [`AlignedBuf`]s
+//! whose first byte sits on a [`BUFFER_ALIGN`] boundary.  The alignment is
+//! fixed when the crate is built, from the `LZ4R_BUFFER_ALIGN` environment
+//! variable (a power of two, default 1):
+//!
+//! ```text
+//! LZ4R_BUFFER_ALIGN=4096 cargo build --release
+//! ```
+//!
+//! With page alignment the staging buffers can be handed to DMA engines or
+//! `O_DIRECT` writes without a bounce copy.  The buffer over-allocates by
+//! up to `BUFFER_ALIGN - 1` bytes to find the boundary, so the default of 1
+//! costs nothing.  No unsafe code is involved, and the type is available
+//! under `forbid-unsafe`.
+
+use core::ops::{Deref, DerefMut};
+
+use crate::frame::pool::BufferPool;
+
+/// Alignment in bytes of the frame contexts' staging buffers, chosen at build
+/// time through `LZ4R_BUFFER_ALIGN`.  `build.rs` rejects values that are not
+/// a power of two.
+pub const BUFFER_ALIGN: usize = match option_env!("LZ4R_BUFFER_ALIGN") {
+    Some(value) => parse_align(value),
+    None => 1,
+};
+
+// Decimal parse usable in a const initializer.
+const fn parse_align(value: &str) -> usize {
+    let bytes = value.as_bytes();
+    let mut n = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        assert!(
+            bytes[i].is_ascii_digit(),
+            "LZ4R_BUFFER_ALIGN must be a number"
+        );
+        n = n * 10 + (bytes[i] - b'0') as usize;
+        i += 1;
+    }
+    assert!(
+        n.is_power_of_two(),
+        "LZ4R_BUFFER_ALIGN must be a power of two"
+    );
+    n
+}
+
+/// A zero-initialised byte buffer whose start is aligned to [`BUFFER_ALIGN`].
+///
+/// Dereferences to `[u8]` of length [`len`](AlignedBuf::len).  Like `Vec`,
+/// shrinking keeps the allocation, so a context reused for frames of the
+/// same geometry does not allocate again.
+#[derive(Debug, Default)]
+pub struct AlignedBuf {
+    storage: Vec<u8>,
+    offset: usize,
+    len: usize,
+}
+
+impl AlignedBuf {
+    /// An empty buffer; does not allocate.
+    pub const fn new() -> Self {
+        AlignedBuf {
+            storage: Vec::new(),
+            offset: 0,
+            len: 0,
+        }
+    }
+
+    /// A buffer of `len` zero bytes.
+    pub fn zeroed(len: usize) -> Self {
+        let mut buf = AlignedBuf::new();
+        buf.resize(len, 0);
+        buf
+    }
+
+    /// Resize to `len` bytes, filling new bytes with `value` and keeping the
+    /// existing contents, as `Vec::resize` does.
+    pub fn resize(&mut self, len: usize, value: u8) {
+        self.resize_aligned(len, value, BUFFER_ALIGN, None);
+    }
+
+    /// Like [`resize`](Self::resize), but a larger allocation is taken from
+    /// `pool` and the one it replaces is given back to it.
+    pub fn resize_in(&mut self, len: usize, value: u8, pool: Option<&dyn BufferPool>) {
+        self.resize_aligned(len, value, BUFFER_ALIGN, pool);
+    }
+
+    /// Give the allocation to `pool`, leaving the buffer empty.
+    pub fn release_to(&mut self, pool: &dyn BufferPool) {
+        self.offset = 0;
+        self.len = 0;
+        pool.put(core::mem::take(&mut self.storage));
+    }
+
+    fn resize_aligned(
+        &mut self,
+        len: usize,
+        value: u8,
+        align: usize,
+        pool: Option<&dyn BufferPool>,
+    ) {
+        if self.offset + len > self.storage.len() {
+            let needed = len + align - 1;
+            let mut storage = match pool {
+                // Pooled bytes are stale, but only the kept prefix and the
+                // filled tail below are ever visible.
+                Some(pool) => {
+                    let mut buf = pool.get(needed);
+                    buf.resize(needed.max(buf.len()), 0);
+                    buf
+                }
+                None => vec![0u8; needed],
+            };
+            let offset = storage.as_ptr().align_offset(align);
+            storage[offset..offset + self.len].copy_from_slice(self);
+            let old = core::mem::replace(&mut self.storage, storage);
+            self.offset = offset;
+            if let Some(pool) = pool {
+                pool.put(old);
+            }
+        }
+        if len > self.len {
+            self.storage[self.offset + self.len..self.offset + len].fill(value);
+        }
+        self.len = len;
+    }
+
+    /// Number of bytes in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// `true` when the buffer holds no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bytes the buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.storage.len() - self.offset
+    }
+}
+
+// A derived `Clone` would copy `offset` into an allocation with a different
+// address; re-align instead.
+impl Clone for AlignedBuf {
+    fn clone(&self) -> Self {
+        let mut buf = AlignedBuf::new();
+        buf.resize(self.len, 0);
+        buf.copy_from_slice(self);
+        buf
+    }
+}
+
+impl Deref for AlignedBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.storage[self.offset..self.offset + self.len]
+    }
+}
+
+impl DerefMut for AlignedBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.storage[self.offset..self.offset + self.len]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resize_aligns_and_keeps_contents() {
+        let mut buf = AlignedBuf::new();
+        buf.resize_aligned(100, 7, 4096, None);
+        assert_eq!(buf.as_ptr() as usize % 4096, 0);
+        assert!(buf.iter().all(|&b| b == 7));
+        buf[..3].copy_from_slice(b"abc");
+
+        buf.resize_aligned(70_000, 0, 4096, None);
+        assert_eq!(buf.as_ptr() as usize % 4096, 0);
+        assert_eq!(&buf[..3], b"abc");
+        assert!(buf[100..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn shrinking_keeps_the_allocation() {
+        let mut buf = AlignedBuf::new();
+        buf.resize_aligned(5000, 0, 64, None);
+        let ptr = buf.as_ptr();
+        buf.resize_aligned(10, 0, 64, None);
+        buf.resize_aligned(5000, 1, 64, None);
+        assert_eq!(buf.as_ptr(), ptr);
+        assert!(buf[10..].iter().all(|&b| b == 1));
+        assert!(buf.capacity() >= 5000);
+    }
+
+    #[test]
+    fn clone_is_aligned() {
+        let buf = AlignedBuf::zeroed(3000);
+        let copy = buf.clone();
+        assert!((copy.as_ptr() as usize).is_multiple_of(BUFFER_ALIGN));
+        assert_eq!(*copy, *buf);
+    }
+
+    #[test]
+    fn parse_align_reads_decimal() {
+        assert_eq!(parse_align("1"), 1);
+        assert_eq!(parse_align("4096"), 4096);
+    }
+}
@@ -0,0 +1,285 @@
+//! Appending frames to existing `.lz4` files.
+//!
+//! Concatenated LZ4 frames form a valid `.lz4` stream, so a log rotator can
+//! add new data to a compressed file by writing another frame after the last
+//! one.  That only works if the file really ends where a frame ends: after a
+//! crash mid-write, or on a file that is not LZ4 at all, the appended frame
+//! would be glued onto a partial block and the decoder would stop at the
+//! damage.  [`append_frame`] walks the existing frames first and refuses to
+//! write unless the file ends exactly at a frame boundary.
+//!
+//! The walk reads only headers and block sizes and seeks over block data, so
+//! it costs a few reads per block, not a decompression.  Standard, skippable
+//! and legacy frames are recognised.
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use super::compress::lz4f_compress_frame_to_writer;
+use super::header::{block_size_from_id, frame_header_size, parse_frame_header, LZ4F_MAGIC_NUMBER};
+use super::skippable::is_skippable_magic;
+use super::types::{
+    BlockChecksum, ContentChecksum, FrameInfo, Lz4FError, Preferences, MAX_FH_SIZE,
+};
+
+/// Magic number of the legacy frame format (`lz4 -l`).
+const LEGACY_MAGIC_NUMBER: u32 = 0x184C_2102;
+/// Legacy frames cut their input into blocks of this size.
+const LEGACY_BLOCK_SIZE: u64 = 8 << 20;
+
+/// Compresses `data` into one frame and appends it to `file`.
+///
+/// `file` is first checked with [`check_frame_boundary`]; if it does not end
+/// at a frame boundary, nothing is written and an
+/// [`io::ErrorKind::InvalidData`] error names the offset of the damage.  An
+/// empty file is a valid boundary.  On success the file position is at the
+/// new end of file and the compressed size of the new frame is returned.
+///
+/// `prefs` defaults to [`Preferences::default`] when `None`.
+///
+/// ```
+/// use lz4::frame::{append_frame, check_frame_boundary};
+/// use std::io::Cursor;
+///
+/// let mut file = Cursor::new(Vec::new());
+/// append_frame(&mut file, b"first line\n", None).unwrap();
+/// append_frame(&mut file, b"second line\n", None).unwrap();
+/// let len = file.get_ref().len() as u64;
+/// assert_eq!(check_frame_boundary(&mut file).unwrap(), len);
+///
+/// file.get_mut().pop(); // damage the last frame
+/// assert!(append_frame(&mut file, b"third line\n", None).is_err());
+/// assert_eq!(file.get_ref().len() as u64, len - 1);
+/// ```
+pub fn append_frame<F: Read + Write + Seek>(
+    file: &mut F,
+    data: &[u8],
+    prefs: Option<&Preferences>,
+) -> io::Result<u64> {
+    let end = check_frame_boundary(file)?;
+    file.seek(SeekFrom::Start(end))?;
+    let written = lz4f_compress_frame_to_writer(&mut *file, data, prefs)?;
+    file.flush()?;
+    Ok(written)
+}
+
+/// Checks that `reader` holds a sequence of complete frames and returns its
+/// length.
+///
+/// Frame headers (magic, flags and header checksum) and block sizes are
+/// validated; block contents and checksums are not.  A truncated frame,
+/// trailing bytes that are not a frame, or an unknown magic number is an
+/// [`io::ErrorKind::InvalidData`] error.  The reader's position afterwards is
+/// unspecified.
+pub fn check_frame_boundary<R: Read + Seek>(reader: &mut R) -> io::Result<u64> {
+    let not_a_boundary = |e: io::Error| match e.kind() {
+        io::ErrorKind::InvalidData => io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("not at a frame boundary: {e}"),
+        ),
+        _ => e,
+    };
+    let walked = walk_frames(reader, |_, _| Ok(())).map_err(not_a_boundary)?;
+    match walked {
+        FrameWalk::Complete(len) => Ok(len),
+        FrameWalk::TrailingData(offset) => Err(not_a_boundary(frame_error(
+            offset,
+            "unknown frame magic number",
+        ))),
+    }
+}
+
+/// Position and size field of a data block, as read from its block header.
+///
+/// Only `lz4 -t` reads the block positions, so without `cli` they go unused.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "cli"), allow(dead_code))]
+pub(crate) struct BlockRef {
+    /// Offset of the block payload (just past the 4-byte header).
+    pub(crate) offset: u64,
+    /// Raw size field, including the uncompressed flag of LZ4 frames.
+    pub(crate) size_field: u32,
+}
+
+impl BlockRef {
+    /// Payload size in bytes, block checksum excluded.
+    pub(crate) fn size(self) -> usize {
+        (self.size_field & 0x7FFF_FFFF) as usize
+    }
+
+    /// Whether the block is stored uncompressed (LZ4 frames only).
+    #[cfg_attr(not(feature = "cli"), allow(dead_code))]
+    pub(crate) fn is_stored(self) -> bool {
+        self.size_field & 0x8000_0000 != 0
+    }
+}
+
+/// One frame found by [`walk_frames`].
+#[cfg_attr(not(feature = "cli"), allow(dead_code))]
+pub(crate) enum FrameLayout {
+    /// A standard LZ4 frame: its header and its first and last data blocks.
+    Lz4 {
+        info: FrameInfo,
+        first: Option<BlockRef>,
+        last: Option<BlockRef>,
+    },
+    /// A skippable frame.
+    Skippable,
+    /// A legacy frame and its first and last blocks.
+    Legacy {
+        first: Option<BlockRef>,
+        last: Option<BlockRef>,
+    },
+}
+
+/// How a [`walk_frames`] walk ended.
+pub(crate) enum FrameWalk {
+    /// The stream, of this length, ends exactly at a frame boundary.
+    Complete(u64),
+    /// Bytes with an unknown magic number start at this offset.
+    TrailingData(u64),
+}
+
+/// Walks the frames of `reader` from its start, reading only headers and
+/// block sizes, and calls `on_frame` with the reader and the layout of each
+/// complete frame.  `on_frame` may move the reader; the walk seeks back.
+///
+/// A truncated frame, a bad header or an oversized block is an
+/// [`io::ErrorKind::InvalidData`] error naming its offset.
+pub(crate) fn walk_frames<R: Read + Seek>(
+    reader: &mut R,
+    mut on_frame: impl FnMut(&mut R, &FrameLayout) -> io::Result<()>,
+) -> io::Result<FrameWalk> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    let mut walker = Walker {
+        reader,
+        pos: 0,
+        len,
+    };
+    walker.reader.seek(SeekFrom::Start(0))?;
+    while walker.pos < len {
+        let start = walker.pos;
+        let magic = walker.read_u32(start, "frame magic number")?;
+        let layout = if magic == LZ4F_MAGIC_NUMBER {
+            walker.walk_lz4_frame(start)?
+        } else if is_skippable_magic(magic) {
+            let size = walker.read_u32(start, "skippable frame size")?;
+            walker.skip(u64::from(size), start, "skippable frame")?;
+            FrameLayout::Skippable
+        } else if magic == LEGACY_MAGIC_NUMBER {
+            walker.walk_legacy_frame(start)?
+        } else {
+            return Ok(FrameWalk::TrailingData(start));
+        };
+        on_frame(walker.reader, &layout)?;
+        walker.reader.seek(SeekFrom::Start(walker.pos))?;
+    }
+    Ok(FrameWalk::Complete(len))
+}
+
+fn frame_error(offset: u64, what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{what} at offset {offset}"),
+    )
+}
+
+/// Forward-only cursor over the frames of a seekable stream.
+struct Walker<'a, R> {
+    reader: &'a mut R,
+    pos: u64,
+    len: u64,
+}
+
+impl<R: Read + Seek> Walker<'_, R> {
+    fn read_exact(&mut self, buf: &mut [u8], frame: u64, what: &str) -> io::Result<()> {
+        if self.len - self.pos < buf.len() as u64 {
+            return Err(frame_error(frame, &format!("truncated {what}")));
+        }
+        self.reader.read_exact(buf)?;
+        self.pos += buf.len() as u64;
+        Ok(())
+    }
+
+    fn read_u32(&mut self, frame: u64, what: &str) -> io::Result<u32> {
+        let mut b = [0u8; 4];
+        self.read_exact(&mut b, frame, what)?;
+        Ok(u32::from_le_bytes(b))
+    }
+
+    fn skip(&mut self, n: u64, frame: u64, what: &str) -> io::Result<()> {
+        if self.len - self.pos < n {
+            return Err(frame_error(frame, &format!("truncated {what}")));
+        }
+        self.pos += n;
+        self.reader.seek(SeekFrom::Start(self.pos))?;
+        Ok(())
+    }
+
+    fn walk_lz4_frame(&mut self, frame: u64) -> io::Result<FrameLayout> {
+        let mut header = [0u8; MAX_FH_SIZE];
+        header[..4].copy_from_slice(&LZ4F_MAGIC_NUMBER.to_le_bytes());
+        self.read_exact(&mut header[4..5], frame, "frame header")?;
+        let header_len = frame_header_size(header[4], false)
+            .map_err(|_| frame_error(frame, "unsupported frame version"))?;
+        self.read_exact(&mut header[5..header_len], frame, "frame header")?;
+        let (info, _) = parse_frame_header(&header[..header_len], false).map_err(|e| {
+            let what = match e {
+                Lz4FError::HeaderChecksumInvalid => "bad frame header checksum",
+                _ => "invalid block size",
+            };
+            frame_error(frame, what)
+        })?;
+        let block_max = block_size_from_id(info.block_size_id) as u64;
+        let block_checksum = info.block_checksum_flag == BlockChecksum::Enabled;
+
+        let (mut first, mut last) = (None, None);
+        loop {
+            let size_field = self.read_u32(frame, "block header")?;
+            if size_field == 0 {
+                let trailer = match info.content_checksum_flag {
+                    ContentChecksum::Enabled => 4,
+                    ContentChecksum::Disabled => 0,
+                };
+                self.skip(trailer, frame, "content checksum")?;
+                return Ok(FrameLayout::Lz4 { info, first, last });
+            }
+            let block = BlockRef {
+                offset: self.pos,
+                size_field,
+            };
+            if block.size() as u64 > block_max {
+                return Err(frame_error(
+                    frame,
+                    "block larger than the frame's block size",
+                ));
+            }
+            first.get_or_insert(block);
+            last = Some(block);
+            let checksum = if block_checksum { 4 } else { 0 };
+            self.skip(block.size() as u64 + checksum, frame, "block")?;
+        }
+    }
+
+    fn walk_legacy_frame(&mut self, frame: u64) -> io::Result<FrameLayout> {
+        // LZ4_COMPRESSBOUND(LEGACY_BLOCK_SIZE): anything larger is the magic
+        // number of the next frame.
+        let max_block = LEGACY_BLOCK_SIZE + LEGACY_BLOCK_SIZE / 255 + 16;
+        let (mut first, mut last) = (None, None);
+        while self.pos < self.len {
+            let size = self.read_u32(frame, "legacy block header")?;
+            if u64::from(size) > max_block {
+                self.pos -= 4;
+                self.reader.seek(SeekFrom::Start(self.pos))?;
+                break;
+            }
+            let block = BlockRef {
+                offset: self.pos,
+                size_field: size,
+            };
+            first.get_or_insert(block);
+            last = Some(block);
+            self.skip(u64::from(size), frame, "legacy block")?;
+        }
+        Ok(FrameLayout::Legacy { first, last })
+    }
+}
@@ -14,6 +14,12 @@
 //!   - `fastCtx`     — a `LZ4_stream_t` pre-loaded with the dictionary
 //!   - `HCCtx`       — a `LZ4_streamHC_t` pre-loaded at `LZ4HC_CLEVEL_DEFAULT`
 //!
+//! [`Lz4FCDict::create_with_level`] extends this with a preferred compression
+//! level: the HC tables are digested at that level, and one-shot compression
+//! without explicit preferences uses it.  Frame compression attaches the HC
+//! tables only when their layout matches the frame's level; otherwise the
+//! dictionary is re-digested into the working stream.
+//!
 //! In Rust these are modelled as:
 //!   - `dict_content: Vec<u8>` — owns the trimmed dictionary bytes
 //!   - `fast_ctx: Box<block::stream::Lz4Stream>` — owns the fast stream state
@@ -26,13 +32,9 @@
 //! no explicit free function is exposed.
 
 use crate::block::stream::Lz4Stream;
+use crate::dict::{effective_dict, MAX_DICT_SIZE};
 use crate::hc::api::{init_stream_hc, load_dict_hc, set_compression_level, Lz4StreamHc};
-use crate::hc::types::LZ4HC_CLEVEL_DEFAULT;
-
-// Maximum dictionary size the frame format retains (64 KB).
-// Dictionaries longer than this are trimmed to their last 64 KB before use;
-// see the trim logic in `LZ4F_createCDict_advanced` (lz4frame.c:546-549).
-const MAX_DICT_SIZE: usize = 64 * 1024;
+use crate::hc::types::{LZ4HC_CLEVEL_DEFAULT, LZ4HC_CLEVEL_MAX, LZ4HC_CLEVEL_MIN};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Lz4FCDict — pre-digested compression dictionary
@@ -52,16 +54,19 @@ const MAX_DICT_SIZE: usize = 64 * 1024;
 pub struct Lz4FCDict {
     /// Trimmed copy of the user-supplied dictionary (at most 64 KB).
     /// Equivalent to `cdict->dictContent` in C.
-    #[allow(dead_code)]
     pub(crate) dict_content: Vec<u8>,
 
     /// Fast LZ4 stream pre-loaded with the dictionary data.
     /// Equivalent to `cdict->fastCtx` in C.
     pub(crate) fast_ctx: Box<Lz4Stream>,
 
-    /// HC LZ4 stream pre-loaded with the dictionary data at `LZ4HC_CLEVEL_DEFAULT`.
+    /// HC LZ4 stream pre-loaded with the dictionary data at the HC level
+    /// derived from `level` (`LZ4HC_CLEVEL_DEFAULT` for fast levels).
     /// Equivalent to `cdict->HCCtx` in C.
     pub(crate) hc_ctx: Box<Lz4StreamHc>,
+
+    /// Compression level requested at creation; 0 for [`Lz4FCDict::create`].
+    pub(crate) level: i32,
 }
 
 // SAFETY: All shared mutable state lives inside `Lz4StreamHc` and `Lz4Stream`.
@@ -89,26 +94,53 @@ impl Lz4FCDict {
     /// let cdict = Lz4FCDict::create(dict_bytes).expect("allocation failed");
     /// ```
     pub fn create(dict: &[u8]) -> Option<Box<Self>> {
+        Self::create_with_level(dict, 0)
+    }
+
+    /// Create a pre-digested dictionary for compression at `level`.
+    ///
+    /// Both tables are built up front: the fast table, used for levels below
+    /// `LZ4HC_CLEVEL_MIN`, and the HC tables digested at `level` (clamped to
+    /// `LZ4HC_CLEVEL_MAX`; fast levels digest them at `LZ4HC_CLEVEL_DEFAULT`).
+    /// [`lz4f_compress_frame_using_cdict`] compresses at `level` when no
+    /// preferences are given, and an explicit level in the preferences picks
+    /// whichever table suits it.
+    ///
+    /// `create(dict)` is `create_with_level(dict, 0)`.
+    ///
+    /// [`lz4f_compress_frame_using_cdict`]: crate::frame::lz4f_compress_frame_using_cdict
+    ///
+    /// # Example
+    /// ```
+    /// # use lz4::frame::cdict::Lz4FCDict;
+    /// let cdict = Lz4FCDict::create_with_level(b"example dictionary content", 9)
+    ///     .expect("allocation failed");
+    /// assert_eq!(cdict.level(), 9);
+    /// ```
+    pub fn create_with_level(dict: &[u8], level: i32) -> Option<Box<Self>> {
         // Trim to last 64 KB (lz4frame.c:546-549).
-        let trimmed = if dict.len() > MAX_DICT_SIZE {
-            &dict[dict.len() - MAX_DICT_SIZE..]
-        } else {
-            dict
-        };
+        let trimmed = effective_dict(dict);
 
         // Copy the trimmed dictionary bytes into owned storage (lz4frame.c:558).
         let dict_content: Vec<u8> = trimmed.to_vec();
 
         // Initialise fast stream and load dictionary (lz4frame.c:559-560).
-        // Lz4Stream::new() already returns Box<Lz4Stream>.
+        // Lz4Stream::new() already returns Box<Lz4Stream>.  The stream keeps
+        // a pointer to the bytes it loads, so load the owned copy rather than
+        // the caller's slice, which may not outlive the CDict.
         let mut fast_ctx = Lz4Stream::new();
-        fast_ctx.load_dict_slow(trimmed);
+        fast_ctx.load_dict_slow(&dict_content);
 
-        // Initialise HC stream, set default compression level, load dictionary
+        // Initialise HC stream, set its compression level, load dictionary
         // (lz4frame.c:561-565).
+        let hc_level = if level < LZ4HC_CLEVEL_MIN {
+            LZ4HC_CLEVEL_DEFAULT
+        } else {
+            level.min(LZ4HC_CLEVEL_MAX)
+        };
         let mut hc_ctx = Lz4StreamHc::create()?; // returns None on alloc failure
         init_stream_hc(&mut hc_ctx);
-        set_compression_level(&mut hc_ctx, LZ4HC_CLEVEL_DEFAULT);
+        set_compression_level(&mut hc_ctx, hc_level);
         // SAFETY: `dict_content` is valid for `dict_content.len()` bytes and will
         // outlive `hc_ctx` (both are owned by the same `Lz4FCDict` value).
         unsafe {
@@ -123,8 +155,96 @@ impl Lz4FCDict {
             dict_content,
             fast_ctx,
             hc_ctx,
+            level,
         }))
     }
+
+    /// Create a dictionary from several samples, most recent last.
+    ///
+    /// Only the last 64 KB of a dictionary is ever referenced, and the bytes
+    /// nearest its end are reachable from the most positions, so samples are
+    /// packed tail-first: walking from the newest sample backwards, each is
+    /// placed in front of those already chosen until `max_size` (capped at
+    /// 64 KB) is filled; the sample that overflows contributes its tail.
+    /// Identical samples are stored once, and a sample seen more often sorts
+    /// as more recent, so frequent content lands closest to the data.
+    /// Empty samples are ignored.
+    ///
+    /// Decoders need the same bytes; [`content`](Self::content) returns them.
+    ///
+    /// # Example
+    /// ```
+    /// # use lz4::frame::cdict::Lz4FCDict;
+    /// let samples: [&[u8]; 3] = [b"old record", b"newer record", b"newest record"];
+    /// let cdict = Lz4FCDict::create_from_samples(&samples, 20).unwrap();
+    /// assert_eq!(cdict.content(), b" recordnewest record");
+    /// ```
+    pub fn create_from_samples(samples: &[&[u8]], max_size: usize) -> Option<Box<Self>> {
+        Self::create(&pack_samples(samples, max_size))
+    }
+
+    /// Compression level this dictionary was created for.
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// The dictionary bytes retained after trimming.
+    ///
+    /// Frames compressed with this dictionary decode with these bytes.
+    pub fn content(&self) -> &[u8] {
+        &self.dict_content
+    }
+
+    /// The pre-digested HC state, if its table layout suits `level`.
+    ///
+    /// The mid-strategy levels hash into a different table layout than the
+    /// hash-chain and optimal levels, so HC tables digested for one cannot
+    /// be attached to a stream of the other.
+    pub(crate) fn hc_ctx_for(&self, stream: &Lz4StreamHc) -> Option<&Lz4StreamHc> {
+        stream
+            .ctx
+            .is_compatible(&self.hc_ctx.ctx)
+            .then_some(&*self.hc_ctx)
+    }
+}
+
+/// Packs `samples` tail-first into at most `min(max_size, 64 KB)` bytes.
+///
+/// Duplicates collapse into one entry ranked by (occurrences, last index);
+/// entries are then taken from the highest rank down and laid out so the
+/// highest rank ends the dictionary.
+fn pack_samples(samples: &[&[u8]], max_size: usize) -> Vec<u8> {
+    let budget = max_size.min(MAX_DICT_SIZE);
+
+    // (sample, occurrences, last index), in first-seen order.
+    let mut unique: Vec<(&[u8], usize, usize)> = Vec::new();
+    for (i, &sample) in samples.iter().enumerate() {
+        if sample.is_empty() {
+            continue;
+        }
+        match unique.iter_mut().find(|(s, _, _)| *s == sample) {
+            Some(entry) => {
+                entry.1 += 1;
+                entry.2 = i;
+            }
+            None => unique.push((sample, 1, i)),
+        }
+    }
+    unique.sort_by_key(|&(_, count, last)| (count, last));
+
+    // Collect pieces newest-first, then reverse into dictionary order.
+    let mut pieces: Vec<&[u8]> = Vec::new();
+    let mut remaining = budget;
+    for &(sample, _, _) in unique.iter().rev() {
+        if remaining == 0 {
+            break;
+        }
+        let take = sample.len().min(remaining);
+        pieces.push(&sample[sample.len() - take..]);
+        remaining -= take;
+    }
+    pieces.reverse();
+    pieces.concat()
 }
 
 // No explicit `Drop` impl is needed: the compiler inserts implicit drops for
@@ -190,5 +310,28 @@ mod tests {
             cdict.hc_ctx.ctx.compression_level as i32,
             LZ4HC_CLEVEL_DEFAULT
         );
+        assert_eq!(cdict.level(), 0);
+    }
+
+    /// Duplicates are stored once and the most frequent sample ends the
+    /// dictionary; the overflowing sample contributes only its tail.
+    #[test]
+    fn pack_samples_dedups_and_weights() {
+        let samples: [&[u8]; 5] = [b"aaaa", b"bbbb", b"", b"cccc", b"bbbb"];
+        assert_eq!(pack_samples(&samples, 100), b"aaaaccccbbbb");
+        assert_eq!(pack_samples(&samples, 10), b"aaccccbbbb");
+        assert!(pack_samples(&samples, 0).is_empty());
+        assert!(pack_samples(&[], 100).is_empty());
+    }
+
+    /// HC tables follow the requested level; fast levels keep the default.
+    #[test]
+    fn create_with_level_sets_hc_level() {
+        let dict = b"level-aware dictionary".repeat(64);
+        for (level, hc_level) in [(1, LZ4HC_CLEVEL_DEFAULT), (4, 4), (12, 12), (99, 12)] {
+            let cdict = Lz4FCDict::create_with_level(&dict, level).unwrap();
+            assert_eq!(cdict.level(), level);
+            assert_eq!(cdict.hc_ctx.ctx.compression_level as i32, hc_level);
+        }
     }
 }
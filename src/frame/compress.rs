@@ -14,28 +14,48 @@
 //! - Streaming update: [`lz4f_compress_update_impl`], [`lz4f_compress_update`],
 //!   [`lz4f_uncompressed_update`], [`lz4f_flush`], [`lz4f_compress_end`]
 //! - One-shot: [`lz4f_compress_frame_using_cdict`], [`lz4f_compress_frame`]
+//! - One-shot into a writer: [`lz4f_compress_frame_to_writer`] (no
+//!   frame-bound-sized destination buffer)
 //!
 //! # Resource management
 //! Compression contexts ([`Lz4FCCtx`]) implement [`Drop`], which frees the
 //! inner LZ4 or HC stream.  The one-shot [`lz4f_compress_frame`] allocates a
 //! temporary context via `Box`, ensuring cleanup on both success and error
 //! without explicit cleanup code.  Error propagation uses `?` throughout.
+//!
+//! # Misuse detection
+//! Each context carries a frame sequence tag.  Debug builds panic, naming the
+//! frame number, on `compress_update`, `flush` or `compress_end` after
+//! `compress_end` finished the frame, and on a `compress_begin` that would
+//! abandon an open frame which already took input.  Release builds keep the
+//! reference behaviour.
+
+use std::io::{self, Write};
+use std::sync::Arc;
 
-use crate::block::compress::compress_fast_ext_state_fast_reset;
+use crate::block::compress::{compress_dest_size, compress_fast_ext_state_fast_reset};
 use crate::block::stream::Lz4Stream;
+use crate::block::types::LZ4_DISTANCE_MAX;
+use crate::dict::effective_dict;
+use crate::frame::advise;
+use crate::frame::aligned::AlignedBuf;
 use crate::frame::cdict::Lz4FCDict;
 use crate::frame::header::{
     lz4f_compress_bound_internal, lz4f_compress_frame_bound, lz4f_get_block_size,
     lz4f_header_checksum, lz4f_optimal_bsid, write_le32, write_le64,
 };
+use crate::frame::pool::BufferPool;
+use crate::frame::skippable::{LZ4F_MAGIC_SKIPPABLE_START, SKIPPABLE_HEADER_SIZE};
 use crate::frame::types::{
-    BlockChecksum, BlockCompressMode, BlockMode, BlockSizeId, ContentChecksum, CtxType, Lz4FCCtx,
-    Lz4FError, Preferences, BF_SIZE, BH_SIZE, MAX_FH_SIZE,
+    BlockChecksum, BlockCompressMode, BlockDecision, BlockMode, BlockSizeId, ContentChecksum,
+    CtxType, FrameInfo, FrameSeq, FrameState, Lz4FCCtx, Lz4FError, Preferences, BF_SIZE, BH_SIZE,
+    LZ4F_BLOCKUNCOMPRESSED_FLAG, MAX_FH_SIZE,
 };
 use crate::hc::api::{
-    attach_hc_dictionary, compress_hc_continue, compress_hc_ext_state_fast_reset,
-    favor_decompression_speed, init_stream_hc, load_dict_hc, reset_stream_hc_fast, save_dict_hc,
-    set_compression_level as hc_set_compression_level, Lz4StreamHc,
+    attach_hc_dictionary, compress_hc_continue, compress_hc_dest_size_initialised,
+    compress_hc_ext_state_fast_reset, favor_decompression_speed, init_stream_hc, load_dict_hc,
+    reset_stream_hc_fast, save_dict_hc, set_compression_level as hc_set_compression_level,
+    Lz4StreamHc,
 };
 use crate::hc::types::LZ4HC_CLEVEL_MIN;
 use crate::xxhash::{xxh32_oneshot, Xxh32State};
@@ -44,8 +64,7 @@ use crate::xxhash::{xxh32_oneshot, Xxh32State};
 // Constants
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// LZ4 frame magic number (lz4frame.h:280).
-pub const LZ4F_MAGIC_NUMBER: u32 = 0x184D_2204;
+pub use crate::frame::header::LZ4F_MAGIC_NUMBER;
 
 /// API version guarded by `LZ4F_createCompressionContext`.
 pub const LZ4F_VERSION: u32 = 100;
@@ -71,6 +90,56 @@ pub struct CompressOptions {
     ///
     /// Equivalent to the `stableSrc` field (lz4frame.h:204).
     pub stable_src: bool,
+    /// When greater than 1, [`lz4f_compress_end`] appends a skippable frame
+    /// of zero padding so the whole frame, header included, is a multiple of
+    /// this many bytes.  Ignored by every other call.  See
+    /// [`CompressOptions::pad_to`].
+    pub pad_alignment: usize,
+}
+
+impl CompressOptions {
+    /// Pad the finished frame to a multiple of `alignment` bytes (e.g. 4 KiB
+    /// for block devices, 1 MiB for object-store parts).
+    ///
+    /// The padding is a skippable frame tagged [`LZ4F_PADDING_NIBBLE`], which
+    /// every decoder ignores.  Because a skippable frame is at least 8 bytes,
+    /// up to `alignment + 7` extra bytes may be written, so `dst` passed to
+    /// [`lz4f_compress_end`] must have that much room beyond the usual bound;
+    /// [`lz4f_compress_bound_padded`] includes it.
+    pub fn pad_to(mut self, alignment: usize) -> Self {
+        self.pad_alignment = alignment;
+        self
+    }
+}
+
+/// User nibble of the skippable frame written by [`CompressOptions::pad_to`].
+pub const LZ4F_PADDING_NIBBLE: u8 = 0x0F;
+
+/// Bytes of padding needed after a frame of `frame_len` bytes to reach the
+/// next multiple of `alignment`, never fewer than a skippable-frame header.
+/// Returns 0 when no padding is needed or `alignment <= 1`.
+pub fn lz4f_padding_size(frame_len: u64, alignment: usize) -> usize {
+    if alignment <= 1 {
+        return 0;
+    }
+    let rem = (frame_len % alignment as u64) as usize;
+    if rem == 0 {
+        return 0;
+    }
+    let mut pad = alignment - rem;
+    while pad < SKIPPABLE_HEADER_SIZE {
+        pad += alignment;
+    }
+    pad
+}
+
+/// Most padding [`lz4f_padding_size`] can return for `alignment`.
+fn lz4f_max_padding_size(alignment: usize) -> usize {
+    if alignment <= 1 {
+        0
+    } else {
+        alignment + SKIPPABLE_HEADER_SIZE - 1
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -213,6 +282,8 @@ unsafe fn cdict_ref(cctx: &Lz4FCCtx) -> *const Lz4FCDict {
 /// API performs its own reset, so no action is needed here.
 ///
 /// For HC streams: always fast-resets and optionally attaches the HC dict.
+/// When the CDict's HC tables were digested for an incompatible table layout,
+/// the dictionary is loaded into the stream at `level` instead.
 ///
 /// Mirrors `LZ4F_initStream` (lz4frame.c:648–674).
 ///
@@ -240,8 +311,17 @@ unsafe fn lz4f_init_stream(
         let stream = &mut *(ctx_ptr as *mut Lz4StreamHc);
         reset_stream_hc_fast(stream, level);
         if !cdict.is_null() {
-            let hc_dict: *const Lz4StreamHc = &*(*cdict).hc_ctx;
-            attach_hc_dictionary(stream, Some(hc_dict));
+            let cdict = &*cdict;
+            match cdict.hc_ctx_for(stream) {
+                Some(hc_dict) => attach_hc_dictionary(stream, Some(hc_dict)),
+                None => {
+                    load_dict_hc(
+                        stream,
+                        cdict.dict_content.as_ptr(),
+                        cdict.dict_content.len() as i32,
+                    );
+                }
+            }
         }
     }
 }
@@ -280,7 +360,8 @@ unsafe fn local_save_dict(cctx: &mut Lz4FCCtx) -> i32 {
 ///
 /// `dst` must have capacity ≥ `BH_SIZE + src.len() + (if crc { BF_SIZE } else { 0 })`.
 ///
-/// Returns the number of bytes written to `dst`.
+/// Returns the number of bytes written to `dst`; 0 when the
+/// [block filter](Lz4FCCtx::set_block_filter) skips the block.
 ///
 /// Mirrors `LZ4F_makeBlock` (lz4frame.c:879–908) with the `compressFunc_t`
 /// replaced by [`CompressMode`].
@@ -316,9 +397,13 @@ unsafe fn lz4f_make_block(
         CompressMode::FastIndependent => {
             // init_stream first (resets + attaches dict if present),
             // then create the &mut reference — avoids two simultaneous &mut to same memory.
-            lz4f_init_stream(ctx_ptr, cdict, level, BlockMode::Independent);
+            // A raw dictionary, loaded at frame start, serves the first block only.
+            let use_dict = cctx.dict_pending;
+            if !use_dict {
+                lz4f_init_stream(ctx_ptr, cdict, level, BlockMode::Independent);
+            }
             let stream = &mut *(ctx_ptr as *mut Lz4Stream);
-            let result: i32 = if !cdict.is_null() {
+            let result: i32 = if !cdict.is_null() || use_dict {
                 // Dict attached: use continue API (stream was reset+attached above).
                 let dst_slice =
                     core::slice::from_raw_parts_mut(compress_dst, src_size.saturating_sub(1));
@@ -352,9 +437,12 @@ unsafe fn lz4f_make_block(
 
         CompressMode::HcIndependent => {
             // HC independent: init per-block then compress.
-            lz4f_init_stream(ctx_ptr, cdict, level, BlockMode::Independent);
+            let use_dict = cctx.dict_pending;
+            if !use_dict {
+                lz4f_init_stream(ctx_ptr, cdict, level, BlockMode::Independent);
+            }
             let stream = &mut *(ctx_ptr as *mut Lz4StreamHc);
-            let result: i32 = if !cdict.is_null() {
+            let result: i32 = if !cdict.is_null() || use_dict {
                 compress_hc_continue(
                     stream,
                     src.as_ptr(),
@@ -389,9 +477,30 @@ unsafe fn lz4f_make_block(
         }
     };
 
+    cctx.dict_pending = false;
+
     // Decide: compressed or uncompressed block?
+    let compressible = c_size != 0 && c_size < src_size;
+    let decision = match cctx.block_filter.as_mut() {
+        Some(filter) => {
+            let candidate = compressible.then(|| &dst[BH_SIZE..BH_SIZE + c_size]);
+            filter(src, candidate)
+        }
+        None => BlockDecision::Auto,
+    };
+    if decision == BlockDecision::Skip {
+        let fi = &cctx.prefs.frame_info;
+        // Dropping a block is only safe when no later block, checksum or
+        // size field accounts for its bytes.
+        if fi.block_mode == BlockMode::Independent
+            && fi.content_checksum_flag == ContentChecksum::Disabled
+            && fi.content_size == 0
+        {
+            return 0;
+        }
+    }
     let final_c_size: usize;
-    if c_size == 0 || c_size >= src_size {
+    if !compressible || decision != BlockDecision::Auto {
         // Not compressible — store raw
         final_c_size = src_size;
         write_le32(
@@ -441,25 +550,95 @@ impl Lz4FCCtx {
             c_stage: 0,
             max_block_size: 0,
             max_buffer_size: 0,
-            tmp_buf: Vec::new(),
+            tmp_buf: AlignedBuf::new(),
             tmp_in_offset: 0,
             tmp_in_size: 0,
             total_in_size: 0,
+            total_out_size: 0,
             xxh: Xxh32State::new(0),
             lz4_ctx: None,
             lz4_ctx_alloc: 0,
             lz4_ctx_type: CtxType::None,
             block_compress_mode: BlockCompressMode::Compressed,
             cdict_ptr: 0,
+            dict_buf: Vec::new(),
+            dict_pending: false,
+            pool: None,
+            block_filter: None,
+            seq: FrameSeq::default(),
         })
     }
+
+    /// Create a compression context whose staging buffer comes from `pool`
+    /// and returns to it when replaced or dropped.
+    pub fn with_pool(version: u32, pool: Arc<dyn BufferPool>) -> Box<Self> {
+        let mut cctx = Self::new(version);
+        cctx.pool = Some(pool);
+        cctx
+    }
+
+    /// Install `filter` to decide, block by block, whether the compressed
+    /// candidate is kept, the input is stored uncompressed, or the block is
+    /// skipped (see [`BlockDecision`]).  Generalises the built-in fallback to
+    /// storage for incompressible blocks.  Stays set across frames.
+    pub fn set_block_filter(
+        &mut self,
+        filter: impl FnMut(&[u8], Option<&[u8]>) -> BlockDecision + Send + 'static,
+    ) {
+        self.block_filter = Some(Box::new(filter));
+    }
+
+    /// Remove the filter installed by [`set_block_filter`](Self::set_block_filter).
+    pub fn clear_block_filter(&mut self) {
+        self.block_filter = None;
+    }
+
+    /// Abandon any frame in progress and get ready for one compressed with
+    /// `prefs`, keeping every allocation.
+    ///
+    /// The staging buffer and the inner LZ4/HC state are grown, if needed, to
+    /// what a frame with `prefs` uses, so [`lz4f_compress_begin`] with the same
+    /// `prefs` — and every block of the frame after it — allocates nothing.
+    /// Nothing is ever shrunk or freed: a context that once staged 4 MB
+    /// blocks keeps that capacity until it is dropped, and a pooled context
+    /// keeps its buffer checked out.  The next frame must still be started
+    /// with `lz4f_compress_begin`; until then updates fail with
+    /// [`Lz4FError::CompressionStateUninitialized`].  The pool and the block
+    /// filter stay installed.  Fails only if the HC state cannot be
+    /// allocated.
+    pub fn reset_for_reuse(&mut self, prefs: &Preferences) -> Result<(), Lz4FError> {
+        self.c_stage = 0;
+        self.tmp_in_offset = 0;
+        self.tmp_in_size = 0;
+        self.total_in_size = 0;
+        self.total_out_size = 0;
+        self.xxh = Xxh32State::new(0);
+        self.cdict_ptr = 0;
+        self.dict_buf.clear();
+        self.dict_pending = false;
+        self.prefs = *prefs;
+        self.seq.state = FrameState::Idle;
+        ensure_inner_ctx(self, ctx_type_for(prefs.compression_level))?;
+        ensure_tmp_buf(self);
+        Ok(())
+    }
+
+    /// Bytes of heap memory the context holds for staging input and for a
+    /// raw dictionary, not counting the fixed-size inner LZ4/HC state.
+    /// [`reset_for_reuse`](Self::reset_for_reuse) never lowers it.
+    pub fn buffer_capacity(&self) -> usize {
+        self.tmp_buf.capacity() + self.dict_buf.capacity()
+    }
 }
 
 impl Drop for Lz4FCCtx {
-    /// Free the inner LZ4/HC context.
+    /// Free the inner LZ4/HC context and hand `tmp_buf` back to the pool.
     ///
     /// Mirrors `LZ4F_freeCompressionContext` (lz4frame.c:629–637).
     fn drop(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            self.tmp_buf.release_to(&*pool);
+        }
         // SAFETY: We own the context and this is called at most once.
         unsafe { free_inner_ctx(self) }
     }
@@ -491,38 +670,22 @@ pub fn lz4f_free_compression_context(_cctx: Box<Lz4FCCtx>) {
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
-// LZ4F_compressBegin_internal (lz4frame.c:690–813)
+// Context sizing, shared by compress_begin and reset_for_reuse
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Write the LZ4 frame header and initialise a compression session.
-///
-/// Only one of `dict_buffer` or `cdict` should be non-null/non-zero (the C
-/// assert is `assert(cdict == NULL || dictBuffer == NULL)`).
-///
-/// Returns the number of bytes written to `dst`.
-///
-/// Mirrors `LZ4F_compressBegin_internal` (lz4frame.c:690–813).
-pub fn lz4f_compress_begin_internal(
-    cctx: &mut Lz4FCCtx,
-    dst: &mut [u8],
-    dict_buffer: Option<&[u8]>,
-    cdict: Option<*const Lz4FCDict>,
-    prefs: Option<&Preferences>,
-) -> Result<usize, Lz4FError> {
-    if dst.len() < MAX_FH_SIZE {
-        return Err(Lz4FError::DstMaxSizeTooSmall);
-    }
-
-    let prefs_val = prefs.copied().unwrap_or_default();
-    cctx.prefs = prefs_val;
-
-    // ── Inner context management ──────────────────────────────────────────────
-    let ctx_type_id: u16 = if cctx.prefs.compression_level < LZ4HC_CLEVEL_MIN {
+/// Inner context kind for `level`: 1 = fast, 2 = HC.
+fn ctx_type_for(level: i32) -> u16 {
+    if level < LZ4HC_CLEVEL_MIN {
         1
     } else {
         2
-    };
+    }
+}
 
+/// Allocate the inner LZ4/HC context for kind `ctx_type_id` if the one held
+/// is too small, or re-initialise it as that kind.  HC levels take
+/// `cctx.prefs.compression_level`.
+fn ensure_inner_ctx(cctx: &mut Lz4FCCtx, ctx_type_id: u16) -> Result<(), Lz4FError> {
     // Determine whether we need to (re-)allocate the inner context.
     // C: `if (allocatedSize < requiredSize)` — in C, HC > Fast in bytes.
     // In Rust we track by type-ID; 2 (HC) > 1 (Fast) > 0 (None).
@@ -550,8 +713,10 @@ pub fn lz4f_compress_begin_internal(
         if ctx_type_id == 1 {
             // SAFETY: lz4_ctx_alloc >= 1, ptr points to valid Lz4Stream bytes.
             unsafe {
+                // The memory last held an HC stream, so `reset` would keep a
+                // stray hash seed; reset to the reference hash instead.
                 let stream = &mut *(ptr as *mut Lz4Stream);
-                stream.reset();
+                stream.set_hash_seed(0);
             }
         } else {
             // SAFETY: lz4_ctx_alloc >= 2, ptr points to valid Lz4StreamHc.
@@ -567,14 +732,18 @@ pub fn lz4f_compress_begin_internal(
             CtxType::Hc
         };
     }
+    Ok(())
+}
 
-    // ── Buffer management ─────────────────────────────────────────────────────
+/// Size `tmp_buf` for a frame under `cctx.prefs`, only ever growing it, and
+/// set `max_block_size`.  A `Default` block size is resolved to 64 KB.
+fn ensure_tmp_buf(cctx: &mut Lz4FCCtx) {
     if cctx.prefs.frame_info.block_size_id == BlockSizeId::Default {
         cctx.prefs.frame_info.block_size_id = BlockSizeId::Max64Kb;
     }
     cctx.max_block_size = lz4f_get_block_size(cctx.prefs.frame_info.block_size_id).unwrap_or(KB64);
 
-    let required_buff_size: usize = if prefs_val.auto_flush {
+    let required_buff_size: usize = if cctx.prefs.auto_flush {
         if cctx.prefs.frame_info.block_mode == BlockMode::Linked {
             KB64
         } else {
@@ -590,10 +759,100 @@ pub fn lz4f_compress_begin_internal(
     };
 
     if cctx.max_buffer_size < required_buff_size {
-        cctx.tmp_buf = vec![0u8; required_buff_size];
+        // Emptied first so that growing copies nothing.
+        let pool = cctx.pool.as_deref();
+        cctx.tmp_buf.resize_in(0, 0, pool);
+        cctx.tmp_buf.resize_in(required_buff_size, 0, pool);
         cctx.max_buffer_size = required_buff_size;
     }
-    cctx.tmp_in_offset = 0;
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// LZ4F_compressBegin_internal (lz4frame.c:690–813)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Write the LZ4 frame header and initialise a compression session.
+///
+/// Only one of `dict_buffer` or `cdict` should be non-null/non-zero (the C
+/// assert is `assert(cdict == NULL || dictBuffer == NULL)`).
+///
+/// Returns the number of bytes written to `dst`.
+///
+/// Mirrors `LZ4F_compressBegin_internal` (lz4frame.c:690–813).
+///
+/// # Panics
+/// In debug builds, if the context's previous frame took input and was
+/// neither ended nor failed; see [`Lz4FCCtx::reset_for_reuse`].
+pub fn lz4f_compress_begin_internal(
+    cctx: &mut Lz4FCCtx,
+    dst: &mut [u8],
+    dict_buffer: Option<&[u8]>,
+    cdict: Option<*const Lz4FCDict>,
+    prefs: Option<&Preferences>,
+) -> Result<usize, Lz4FError> {
+    cctx.seq.begin();
+    let result = compress_begin_unchecked(cctx, dst, dict_buffer, cdict, prefs);
+    cctx.seq.note(&result);
+    result
+}
+
+fn compress_begin_unchecked(
+    cctx: &mut Lz4FCCtx,
+    dst: &mut [u8],
+    dict_buffer: Option<&[u8]>,
+    cdict: Option<*const Lz4FCDict>,
+    prefs: Option<&Preferences>,
+) -> Result<usize, Lz4FError> {
+    if dst.len() < MAX_FH_SIZE {
+        return Err(Lz4FError::DstMaxSizeTooSmall);
+    }
+
+    let prefs_val = prefs
+        .copied()
+        .unwrap_or_else(|| cdict_default_prefs(cdict.unwrap_or(core::ptr::null())));
+    let window = match prefs_val.max_distance {
+        0 => LZ4_DISTANCE_MAX,
+        d if d > LZ4_DISTANCE_MAX => return Err(Lz4FError::ParameterInvalid),
+        // The HC match finders have no configurable window.
+        d if d < LZ4_DISTANCE_MAX && prefs_val.compression_level >= LZ4HC_CLEVEL_MIN => {
+            return Err(Lz4FError::ParameterInvalid)
+        }
+        d => d,
+    };
+    cctx.prefs = prefs_val;
+    if cctx.prefs.deterministic {
+        // Fixed block segmentation: blocks are cut by size only.
+        cctx.prefs.auto_flush = false;
+    }
+
+    // ── Inner context management ──────────────────────────────────────────────
+    let ctx_type_id = ctx_type_for(cctx.prefs.compression_level);
+    ensure_inner_ctx(cctx, ctx_type_id)?;
+
+    // A full reset drops whatever the previous frame left in the tables, which
+    // the fast reset used below would keep.
+    if cctx.prefs.deterministic {
+        let ptr = read_inner_ptr(cctx);
+        // SAFETY: the inner context was allocated above for `ctx_type_id`.
+        unsafe {
+            if ctx_type_id == 1 {
+                (*(ptr as *mut Lz4Stream)).set_hash_seed(0);
+            } else {
+                let stream = &mut *(ptr as *mut Lz4StreamHc);
+                init_stream_hc(stream);
+                set_hc_level(stream, cctx.prefs.compression_level);
+            }
+        }
+    }
+
+    // ── Buffer management ─────────────────────────────────────────────────────
+    ensure_tmp_buf(cctx);
+    cctx.tmp_in_offset =
+        if cctx.prefs.deterministic && cctx.prefs.frame_info.block_mode == BlockMode::Linked {
+            DETACHED_STAGE_OFFSET
+        } else {
+            0
+        };
     cctx.tmp_in_size = 0;
     cctx.xxh = Xxh32State::new(0);
 
@@ -617,15 +876,28 @@ pub fn lz4f_compress_begin_internal(
         unsafe {
             let stream = &mut *(ctx_ptr as *mut Lz4StreamHc);
             favor_decompression_speed(stream, prefs_val.favor_dec_speed);
+            stream.set_search_depth(prefs_val.compression_attempts);
+        }
+    } else {
+        // Every later reset of the fast stream keeps the limit.
+        // SAFETY: the inner context is an `Lz4Stream` for fast levels.
+        unsafe {
+            (*(ctx_ptr as *mut Lz4Stream)).internal.max_distance = window;
         }
     }
 
-    // Load raw dict buffer (only when no CDict is provided).
+    // Load raw dict buffer (only when no CDict is provided).  The inner
+    // context keeps pointing at it, so it is copied into the cctx, which
+    // outlives the frame, rather than borrowed from the caller.
+    cctx.dict_pending = false;
     if let Some(dict) = dict_buffer {
         if !dict.is_empty() {
             if dict.len() > i32::MAX as usize {
                 return Err(Lz4FError::ParameterInvalid);
             }
+            cctx.dict_buf.clear();
+            cctx.dict_buf.extend_from_slice(effective_dict(dict));
+            let dict = &cctx.dict_buf;
             unsafe {
                 if ctx_type_id == 1 {
                     let stream = &mut *(ctx_ptr as *mut Lz4Stream);
@@ -635,10 +907,24 @@ pub fn lz4f_compress_begin_internal(
                     load_dict_hc(stream, dict.as_ptr(), dict.len() as i32);
                 }
             }
+            cctx.dict_pending = cctx.prefs.frame_info.block_mode == BlockMode::Independent;
         }
     }
 
     // ── Write frame header ────────────────────────────────────────────────────
+    if cctx.prefs.frame_info.content_size > 0 {
+        cctx.total_in_size = 0;
+    }
+    let pos = write_frame_header(dst, &cctx.prefs.frame_info);
+
+    cctx.c_stage = 1; // header written; ready to accept blocks
+    cctx.total_out_size = pos as u64;
+    Ok(pos)
+}
+
+/// Write the frame header described by `fi` (magic number through header
+/// checksum) to the start of `dst` and return its length.
+fn write_frame_header(dst: &mut [u8], fi: &FrameInfo) -> usize {
     let mut pos: usize = 0;
 
     // Magic number (4 bytes)
@@ -648,7 +934,6 @@ pub fn lz4f_compress_begin_internal(
     let header_start = pos;
 
     // FLG byte
-    let fi = &cctx.prefs.frame_info;
     let flg: u8 = (1u8 << 6) // Version = 01
         | ((fi.block_mode as u8 & 1) << 5)
         | ((fi.block_checksum_flag as u8 & 1) << 4)
@@ -667,7 +952,6 @@ pub fn lz4f_compress_begin_internal(
     if fi.content_size > 0 {
         write_le64(dst, pos, fi.content_size);
         pos += 8;
-        cctx.total_in_size = 0;
     }
 
     // Optional dictionary ID (4 bytes LE)
@@ -679,10 +963,7 @@ pub fn lz4f_compress_begin_internal(
     // Header checksum byte (XXH32 of FLG..dictID, byte [1])
     let hc = lz4f_header_checksum(&dst[header_start..pos]);
     dst[pos] = hc;
-    pos += 1;
-
-    cctx.c_stage = 1; // header written; ready to accept blocks
-    Ok(pos)
+    pos + 1
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -700,11 +981,35 @@ pub fn lz4f_compress_begin(
     lz4f_compress_begin_internal(cctx, dst, None, None, prefs)
 }
 
+/// Begin a new frame that pledges exactly `pledged_src_size` bytes of input.
+///
+/// The pledge is written to the header as the frame's content size,
+/// overriding `prefs.frame_info.content_size`.  `lz4f_compress_update` then
+/// rejects any call that would exceed it, and `lz4f_compress_end` rejects a
+/// frame that falls short, both with [`Lz4FError::FrameSizeWrong`], so a
+/// header can never advertise a size the payload does not match.  A pledge
+/// of `0` means "unknown" and disables the check, as in the header format.
+pub fn lz4f_compress_begin_with_pledged_size(
+    cctx: &mut Lz4FCCtx,
+    dst: &mut [u8],
+    prefs: Option<&Preferences>,
+    pledged_src_size: u64,
+) -> Result<usize, Lz4FError> {
+    let mut p = prefs.copied().unwrap_or_default();
+    p.frame_info.content_size = pledged_src_size;
+    lz4f_compress_begin_internal(cctx, dst, None, None, Some(&p))
+}
+
 /// Begin using a raw dictionary buffer (applied once, not per block).
 ///
-/// Note: like the C implementation, this applies the dictionary once rather
-/// than per block in independent-block mode.  For per-block dict reuse,
-/// prefer [`lz4f_compress_begin_using_cdict`].
+/// Only the last 64 KB of `dict` is used, and the context keeps its own copy
+/// of it, so `dict` need not outlive the call.  With linked blocks the
+/// dictionary is the history of the first block, and so of the whole frame.
+/// With independent blocks, like the C implementation, only the first block
+/// uses it; for per-block dict reuse, prefer
+/// [`lz4f_compress_begin_using_cdict`].  Decode with
+/// [`lz4f_decompress_using_dict`](crate::frame::lz4f_decompress_using_dict)
+/// and the same dictionary.
 ///
 /// Mirrors `LZ4F_compressBegin_usingDictOnce` / `LZ4F_compressBegin_usingDict`
 /// (lz4frame.c:828–849).
@@ -719,6 +1024,9 @@ pub fn lz4f_compress_begin_using_dict(
 
 /// Begin using a pre-digested [`Lz4FCDict`].
 ///
+/// Without `prefs`, the session compresses at the CDict's
+/// [`level`](Lz4FCDict::level).
+///
 /// Mirrors `LZ4F_compressBegin_usingCDict` (lz4frame.c:851–859).
 ///
 /// # Safety
@@ -740,10 +1048,16 @@ pub unsafe fn lz4f_compress_begin_using_cdict(
 
 /// Worst-case output buffer size for a streaming `compress_update` call.
 ///
+/// `auto_flush` does not count for [`Preferences::deterministic`] frames,
+/// which always buffer partial blocks.
+///
 /// Mirrors `LZ4F_compressBound` (lz4frame.c:862–873).
 pub fn lz4f_compress_bound(src_size: usize, prefs: Option<&Preferences>) -> usize {
-    let default_prefs = Preferences::default();
-    let prefs = prefs.unwrap_or(&default_prefs);
+    let mut prefs = prefs.copied().unwrap_or_default();
+    if prefs.deterministic {
+        prefs.auto_flush = false;
+    }
+    let prefs = &prefs;
     let already_buffered = if prefs.auto_flush {
         0
     } else {
@@ -752,6 +1066,16 @@ pub fn lz4f_compress_bound(src_size: usize, prefs: Option<&Preferences>) -> usiz
     lz4f_compress_bound_internal(src_size, prefs, already_buffered)
 }
 
+/// [`lz4f_compress_bound`] plus the worst-case padding that
+/// [`lz4f_compress_end`] appends under [`CompressOptions::pad_to`]`(alignment)`.
+pub fn lz4f_compress_bound_padded(
+    src_size: usize,
+    prefs: Option<&Preferences>,
+    alignment: usize,
+) -> usize {
+    lz4f_compress_bound(src_size, prefs) + lz4f_max_padding_size(alignment)
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // LZ4F_compressUpdateImpl (lz4frame.c:989–1105)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -762,16 +1086,44 @@ pub fn lz4f_compress_bound(src_size: usize, prefs: Option<&Preferences>) -> usiz
 /// [`lz4f_uncompressed_update`] (verbatim blocks).
 ///
 /// Mirrors `LZ4F_compressUpdateImpl` (lz4frame.c:989–1105).
+///
+/// # Panics
+/// In debug builds, if `lz4f_compress_end` already finished the frame.
 pub fn lz4f_compress_update_impl(
     cctx: &mut Lz4FCCtx,
     dst: &mut [u8],
     src: &[u8],
     opts: Option<&CompressOptions>,
     block_compression: BlockCompressMode,
+) -> Result<usize, Lz4FError> {
+    cctx.seq.check_not_ended(match block_compression {
+        BlockCompressMode::Compressed => "lz4f_compress_update",
+        BlockCompressMode::Uncompressed => "lz4f_uncompressed_update",
+    });
+    let result = compress_update_unchecked(cctx, dst, src, opts, block_compression);
+    cctx.seq.note(&result);
+    if result.is_ok() {
+        cctx.seq.frame_in += src.len() as u64;
+    }
+    result
+}
+
+fn compress_update_unchecked(
+    cctx: &mut Lz4FCCtx,
+    dst: &mut [u8],
+    src: &[u8],
+    opts: Option<&CompressOptions>,
+    block_compression: BlockCompressMode,
 ) -> Result<usize, Lz4FError> {
     if cctx.c_stage != 1 {
         return Err(Lz4FError::CompressionStateUninitialized);
     }
+    // A declared content size is a pledge: refuse input that would overrun it
+    // before any of it reaches `dst`.
+    let pledged = cctx.prefs.frame_info.content_size;
+    if pledged != 0 && cctx.total_in_size + src.len() as u64 > pledged {
+        return Err(Lz4FError::FrameSizeWrong);
+    }
     // Capacity checks
     if dst.len() < lz4f_compress_bound_internal(src.len(), &cctx.prefs, cctx.tmp_in_size) {
         return Err(Lz4FError::DstMaxSizeTooSmall);
@@ -799,6 +1151,13 @@ pub fn lz4f_compress_update_impl(
     let crc_flag = cctx.prefs.frame_info.block_checksum_flag == BlockChecksum::Enabled;
     let block_mode = cctx.prefs.frame_info.block_mode;
 
+    if cctx.prefs.deterministic && block_mode == BlockMode::Linked {
+        dst_pos += unsafe {
+            compress_update_detached(cctx, &mut dst[dst_pos..], src, compress_mode, crc_flag)
+        };
+        return Ok(finish_update(cctx, src, dst_pos));
+    }
+
     let mut src_pos: usize = 0;
     let mut last_block_status = 0u8; // 0=notDone, 1=fromTmpBuffer, 2=fromSrcBuffer
 
@@ -895,13 +1254,70 @@ pub fn lz4f_compress_update_impl(
         cctx.tmp_in_size += rem.len();
     }
 
-    // ── Content checksum update ───────────────────────────────────────────────
+    Ok(finish_update(cctx, src, dst_pos))
+}
+
+/// Account for `src` having been consumed and `written` bytes produced.
+fn finish_update(cctx: &mut Lz4FCCtx, src: &[u8], written: usize) -> usize {
     if cctx.prefs.frame_info.content_checksum_flag == ContentChecksum::Enabled {
         cctx.xxh.update(src);
     }
-
     cctx.total_in_size += src.len() as u64;
-    Ok(dst_pos)
+    cctx.total_out_size += written as u64;
+    written
+}
+
+/// Offset of the staging area in `tmp_buf` for deterministic linked frames,
+/// past the 64 KB of history saved at the start.
+const DETACHED_STAGE_OFFSET: usize = 2 * KB64;
+
+/// Linked-block update for [`Preferences::deterministic`] frames.
+///
+/// Every block is compressed with its history detached, in the saved
+/// dictionary at the start of `tmp_buf`, never contiguous with the block
+/// itself.  Full blocks come straight from `src`; partial ones are staged at
+/// [`DETACHED_STAGE_OFFSET`].  Either way the match finder sees the same
+/// layout, so the output does not depend on how the input was split.  This
+/// is the layout the reference `lz4` CLI produces, which feeds whole blocks
+/// from a reused read buffer.
+///
+/// # Safety
+/// `cctx.lz4_ctx` must hold a valid inner context appropriate for `mode`.
+unsafe fn compress_update_detached(
+    cctx: &mut Lz4FCCtx,
+    dst: &mut [u8],
+    src: &[u8],
+    mode: CompressMode,
+    crc_flag: bool,
+) -> usize {
+    let block_size = cctx.max_block_size;
+    let mut src_pos = 0;
+    let mut dst_pos = 0;
+    while src_pos < src.len() {
+        let written = if cctx.tmp_in_size == 0 && src.len() - src_pos >= block_size {
+            let block = &src[src_pos..src_pos + block_size];
+            src_pos += block_size;
+            lz4f_make_block(&mut dst[dst_pos..], block, mode, cctx, crc_flag)
+        } else {
+            let take = (block_size - cctx.tmp_in_size).min(src.len() - src_pos);
+            let off = DETACHED_STAGE_OFFSET + cctx.tmp_in_size;
+            cctx.tmp_buf[off..off + take].copy_from_slice(&src[src_pos..src_pos + take]);
+            cctx.tmp_in_size += take;
+            src_pos += take;
+            if cctx.tmp_in_size < block_size {
+                break;
+            }
+            cctx.tmp_in_size = 0;
+            let block = core::slice::from_raw_parts(
+                cctx.tmp_buf.as_ptr().add(DETACHED_STAGE_OFFSET),
+                block_size,
+            );
+            lz4f_make_block(&mut dst[dst_pos..], block, mode, cctx, crc_flag)
+        };
+        dst_pos += written;
+        local_save_dict(cctx);
+    }
+    dst_pos
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -979,8 +1395,12 @@ fn lz4f_flush_impl(
     }
     cctx.tmp_in_size = 0;
 
-    // Keep tmpIn within bounds (linked mode only).
-    if cctx.tmp_in_offset + cctx.max_block_size > cctx.max_buffer_size {
+    if cctx.prefs.deterministic && cctx.prefs.frame_info.block_mode == BlockMode::Linked {
+        // Detach the history again, as after every other block.
+        unsafe { local_save_dict(cctx) };
+        cctx.tmp_in_offset = DETACHED_STAGE_OFFSET;
+    } else if cctx.tmp_in_offset + cctx.max_block_size > cctx.max_buffer_size {
+        // Keep tmpIn within bounds (linked mode only).
         let real_dict_size = unsafe { local_save_dict(cctx) };
         cctx.tmp_in_offset = real_dict_size as usize;
     }
@@ -998,7 +1418,12 @@ pub fn lz4f_flush(
     dst: &mut [u8],
     opts: Option<&CompressOptions>,
 ) -> Result<usize, Lz4FError> {
-    lz4f_flush_impl(cctx, dst, opts)
+    cctx.seq.check_not_ended("lz4f_flush");
+    let result = lz4f_flush_impl(cctx, dst, opts);
+    cctx.seq.note(&result);
+    let written = result?;
+    cctx.total_out_size += written as u64;
+    Ok(written)
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -1013,40 +1438,88 @@ pub fn lz4f_flush(
 /// Returns the number of bytes written (at minimum 4 for the end-mark).
 ///
 /// Mirrors `LZ4F_compressEnd` (lz4frame.c:1206–1241).
+///
+/// # Panics
+/// In debug builds, if called again after the frame was ended.
 pub fn lz4f_compress_end(
     cctx: &mut Lz4FCCtx,
     dst: &mut [u8],
     opts: Option<&CompressOptions>,
 ) -> Result<usize, Lz4FError> {
-    let flush_size = lz4f_flush_impl(cctx, dst, opts)?;
-    let mut pos = flush_size;
+    cctx.seq.check_not_ended("lz4f_compress_end");
+    let result = compress_end_unchecked(cctx, dst, opts);
+    cctx.seq.note(&result);
+    if result.is_ok() && cctx.seq.state == FrameState::Open {
+        cctx.seq.state = FrameState::Ended;
+    }
+    result
+}
 
-    if dst.len() - pos < 4 {
+fn compress_end_unchecked(
+    cctx: &mut Lz4FCCtx,
+    dst: &mut [u8],
+    opts: Option<&CompressOptions>,
+) -> Result<usize, Lz4FError> {
+    // Verify the declared content size before the end mark is written, so a
+    // short frame is never completed under a header that overstates it.
+    // Like the reference, the context is left ready for a new frame.
+    if cctx.c_stage == 1
+        && cctx.prefs.frame_info.content_size != 0
+        && cctx.prefs.frame_info.content_size != cctx.total_in_size
+    {
+        cctx.c_stage = 0;
+        return Err(Lz4FError::FrameSizeWrong);
+    }
+
+    // Check room for the whole tail before flushing, so a short `dst` fails
+    // with the buffered block still in place and the call can be retried.
+    // The flushed block's size is unknown until it is compressed, so the
+    // padding after it is bounded by its worst case.
+    let content_crc = cctx.prefs.frame_info.content_checksum_flag == ContentChecksum::Enabled;
+    let end_size = 4 + if content_crc { 4 } else { 0 };
+    let alignment = opts.map_or(0, |o| o.pad_alignment);
+    let (flush_bound, pad_bound) = if cctx.tmp_in_size == 0 {
+        let frame_len = cctx.total_out_size + end_size as u64;
+        (0, lz4f_padding_size(frame_len, alignment))
+    } else {
+        let block_crc = cctx.prefs.frame_info.block_checksum_flag == BlockChecksum::Enabled;
+        let flush_bound = cctx.tmp_in_size + BH_SIZE + if block_crc { BF_SIZE } else { 0 };
+        (flush_bound, lz4f_max_padding_size(alignment))
+    };
+    if dst.len() < flush_bound + end_size + pad_bound {
         return Err(Lz4FError::DstMaxSizeTooSmall);
     }
+
+    let flush_size = lz4f_flush_impl(cctx, dst, opts)?;
+    let mut pos = flush_size;
+
     // End-mark: 4 zero bytes
     write_le32(dst, pos, 0);
     pos += 4;
 
     // Optional content checksum
-    if cctx.prefs.frame_info.content_checksum_flag == ContentChecksum::Enabled {
-        if dst.len() - pos < 4 {
-            return Err(Lz4FError::DstMaxSizeTooSmall);
-        }
+    if content_crc {
         let xxh = cctx.xxh.digest();
         write_le32(dst, pos, xxh);
         pos += 4;
     }
 
-    cctx.c_stage = 0; // context is re-usable
-
-    // Verify content size if it was declared in the frame header.
-    if cctx.prefs.frame_info.content_size != 0
-        && cctx.prefs.frame_info.content_size != cctx.total_in_size
-    {
-        return Err(Lz4FError::FrameSizeWrong);
+    // Optional alignment padding, as a skippable frame of zeros.
+    let pad = lz4f_padding_size(cctx.total_out_size + pos as u64, alignment);
+    if pad > 0 {
+        let payload_len =
+            u32::try_from(pad - SKIPPABLE_HEADER_SIZE).map_err(|_| Lz4FError::ParameterInvalid)?;
+        write_le32(
+            dst,
+            pos,
+            LZ4F_MAGIC_SKIPPABLE_START | LZ4F_PADDING_NIBBLE as u32,
+        );
+        write_le32(dst, pos + 4, payload_len);
+        dst[pos + SKIPPABLE_HEADER_SIZE..pos + pad].fill(0);
+        pos += pad;
     }
 
+    cctx.c_stage = 0; // context is re-usable
     Ok(pos)
 }
 
@@ -1054,10 +1527,22 @@ pub fn lz4f_compress_end(
 // One-shot compression (lz4frame.c:419–524)
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Default preferences for a session using `cdict`: compress at the level
+/// the dictionary was created for.
+fn cdict_default_prefs(cdict: *const Lz4FCDict) -> Preferences {
+    let mut prefs = Preferences::default();
+    if !cdict.is_null() {
+        // SAFETY: callers pass a null or valid CDict pointer.
+        prefs.compression_level = unsafe { (*cdict).level() };
+    }
+    prefs
+}
+
 /// Compress `src` into a complete LZ4 frame in one call, using a CDict.
 ///
 /// `dst` must be large enough per `lz4f_compress_frame_bound`.
-/// `cdict` may be null (no dictionary).
+/// `cdict` may be null (no dictionary).  Without `prefs`, the frame is
+/// compressed at the CDict's [`level`](Lz4FCDict::level).
 ///
 /// Mirrors `LZ4F_compressFrame_usingCDict` (lz4frame.c:428–474).
 pub fn lz4f_compress_frame_using_cdict(
@@ -1067,7 +1552,7 @@ pub fn lz4f_compress_frame_using_cdict(
     cdict: *const Lz4FCDict,
     prefs: Option<&Preferences>,
 ) -> Result<usize, Lz4FError> {
-    let mut local_prefs = prefs.copied().unwrap_or_default();
+    let mut local_prefs = prefs.copied().unwrap_or_else(|| cdict_default_prefs(cdict));
 
     // Auto-correct content size if the caller declared non-zero.
     if local_prefs.frame_info.content_size != 0 {
@@ -1091,7 +1576,10 @@ pub fn lz4f_compress_frame_using_cdict(
         return Err(Lz4FError::DstMaxSizeTooSmall);
     }
 
-    let opts = CompressOptions { stable_src: true };
+    let opts = CompressOptions {
+        stable_src: true,
+        ..Default::default()
+    };
 
     // Write header
     let cdict_opt = if cdict.is_null() { None } else { Some(cdict) };
@@ -1125,6 +1613,187 @@ pub fn lz4f_compress_frame(
     // `cctx` is dropped here → inner LZ4 ctx freed via Drop impl
 }
 
+/// Compress `src` into a complete LZ4 frame, writing it to `writer` block by
+/// block.  Returns the number of compressed bytes written.
+///
+/// Produces the same frame as [`lz4f_compress_frame`] (content size fix-up,
+/// optimal block size, auto-flush) but only allocates one block-sized staging
+/// buffer instead of a [`lz4f_compress_frame_bound`]-sized destination, so
+/// multi-GB inputs can be compressed without a multi-GB allocation.  With the
+/// `mmap` feature, `madvise` read-ahead hints are issued per block window.
+///
+/// Codec errors are reported as [`io::Error`]s wrapping the [`Lz4FError`].
+pub fn lz4f_compress_frame_to_writer(
+    mut writer: impl Write,
+    src: &[u8],
+    prefs: Option<&Preferences>,
+) -> io::Result<u64> {
+    let mut local_prefs = prefs.copied().unwrap_or_default();
+    if local_prefs.frame_info.content_size != 0 {
+        local_prefs.frame_info.content_size = src.len() as u64;
+    }
+    local_prefs.frame_info.block_size_id =
+        lz4f_optimal_bsid(local_prefs.frame_info.block_size_id, src.len());
+    local_prefs.auto_flush = true;
+    let block_size = lz4f_get_block_size(local_prefs.frame_info.block_size_id).unwrap_or(KB64);
+    if src.len() <= block_size {
+        local_prefs.frame_info.block_mode = BlockMode::Independent;
+    }
+
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut staging = vec![0u8; lz4f_compress_bound(block_size, Some(&local_prefs))];
+    // The whole of `src` stays put for the duration of the call, so linked
+    // blocks can reference earlier windows in place.
+    let opts = CompressOptions {
+        stable_src: true,
+        ..Default::default()
+    };
+
+    advise::sequential(src);
+
+    let header_size =
+        lz4f_compress_begin_internal(&mut cctx, &mut staging, None, None, Some(&local_prefs))
+            .map_err(io::Error::other)?;
+    writer.write_all(&staging[..header_size])?;
+    let mut total = header_size as u64;
+
+    for (i, window) in src.chunks(block_size).enumerate() {
+        // Prefetch the following window while this one is compressed.
+        let next = (i + 1) * block_size;
+        if next < src.len() {
+            advise::will_need(&src[next..(next + block_size).min(src.len())]);
+        }
+        let c_size = lz4f_compress_update(&mut cctx, &mut staging, window, Some(&opts))
+            .map_err(io::Error::other)?;
+        writer.write_all(&staging[..c_size])?;
+        total += c_size as u64;
+    }
+
+    let tail_size =
+        lz4f_compress_end(&mut cctx, &mut staging, Some(&opts)).map_err(io::Error::other)?;
+    writer.write_all(&staging[..tail_size])?;
+    Ok(total + tail_size as u64)
+}
+
+/// Compress as much of `src` as fits into one complete frame of at most
+/// `dst.len()` bytes.  Returns `(consumed, written)`: the frame decodes to
+/// `src[..consumed]` and occupies `dst[..written]`.
+///
+/// The frame-level analogue of [`compress_dest_size`], for fixed-size packet
+/// payloads.  Every block is compressed on its own with the fast or HC
+/// destination-size encoder, or stored when that packs more input, so the
+/// header always declares independent blocks.  The block size, checksums,
+/// dictionary ID and compression level come from `prefs`; a requested content
+/// size is replaced by `consumed`.
+///
+/// Fails with [`Lz4FError::DstMaxSizeTooSmall`] when `dst` cannot hold an
+/// empty frame, and with [`Lz4FError::ParameterInvalid`] for a
+/// `max_distance` below 64 KB, which the destination-size encoders cannot
+/// honour.
+pub fn compress_frame_dest_size(
+    dst: &mut [u8],
+    src: &[u8],
+    prefs: Option<&Preferences>,
+) -> Result<(usize, usize), Lz4FError> {
+    let mut p = prefs.copied().unwrap_or_default();
+    if p.max_distance != 0 && p.max_distance < LZ4_DISTANCE_MAX {
+        return Err(Lz4FError::ParameterInvalid);
+    }
+    let info = &mut p.frame_info;
+    let block_size = lz4f_get_block_size(info.block_size_id).unwrap_or(KB64);
+    let block_crc = info.block_checksum_flag == BlockChecksum::Enabled;
+    let content_crc = info.content_checksum_flag == ContentChecksum::Enabled;
+    let header_size = 7 + 8 * (info.content_size != 0) as usize + 4 * (info.dict_id != 0) as usize;
+    let block_overhead = BH_SIZE + BF_SIZE * block_crc as usize;
+    let tail_size = BH_SIZE + BF_SIZE * content_crc as usize;
+    if dst.len() < header_size + tail_size {
+        return Err(Lz4FError::DstMaxSizeTooSmall);
+    }
+    let limit = dst.len() - tail_size;
+
+    let mut hc = if p.compression_level >= LZ4HC_CLEVEL_MIN {
+        Some(Lz4StreamHc::create().ok_or(Lz4FError::AllocationFailed)?)
+    } else {
+        None
+    };
+    let mut pos = header_size;
+    let mut consumed = 0;
+    while consumed < src.len() && pos + block_overhead < limit {
+        let avail = (limit - pos - block_overhead).min(block_size);
+        let chunk = &src[consumed..(consumed + block_size).min(src.len())];
+        let body = pos + BH_SIZE;
+        let (packed, written) = match hc.as_deref_mut() {
+            Some(state) => {
+                // `LZ4_compress_HC_destSize` resets the state, parser
+                // settings included; the frame's are applied after the reset.
+                init_stream_hc(state);
+                favor_decompression_speed(state, p.favor_dec_speed);
+                state.set_search_depth(p.compression_attempts);
+                let mut read = chunk.len() as i32;
+                // SAFETY: `chunk` is readable for `read` bytes and `dst` is
+                // writable for `avail` bytes from `body`.
+                let n = unsafe {
+                    compress_hc_dest_size_initialised(
+                        state,
+                        chunk.as_ptr(),
+                        dst[body..].as_mut_ptr(),
+                        &mut read,
+                        avail as i32,
+                        p.compression_level,
+                    )
+                };
+                if n > 0 {
+                    (read as usize, n as usize)
+                } else {
+                    (0, 0)
+                }
+            }
+            // A target too small to encode into falls back to a stored block.
+            None => compress_dest_size(chunk, &mut dst[body..body + avail]).unwrap_or((0, 0)),
+        };
+        let stored = chunk.len().min(avail);
+        let len = if packed > stored || (packed == stored && written < stored) {
+            write_le32(dst, pos, written as u32);
+            consumed += packed;
+            written
+        } else if stored > 0 {
+            dst[body..body + stored].copy_from_slice(&chunk[..stored]);
+            write_le32(dst, pos, stored as u32 | LZ4F_BLOCKUNCOMPRESSED_FLAG);
+            consumed += stored;
+            stored
+        } else {
+            break;
+        };
+        if block_crc {
+            write_le32(dst, body + len, xxh32_oneshot(&dst[body..body + len], 0));
+        }
+        pos = body + len + BF_SIZE * block_crc as usize;
+    }
+
+    write_le32(dst, pos, 0);
+    pos += BH_SIZE;
+    if content_crc {
+        write_le32(dst, pos, xxh32_oneshot(&src[..consumed], 0));
+        pos += BF_SIZE;
+    }
+
+    // The header goes in last, once the content size is known.  Nothing
+    // consumed means no content size field, so the rest moves up.
+    info.block_mode = BlockMode::Independent;
+    if info.block_size_id == BlockSizeId::Default {
+        info.block_size_id = BlockSizeId::Max64Kb;
+    }
+    info.block_size_id = lz4f_optimal_bsid(info.block_size_id, consumed);
+    if info.content_size != 0 {
+        info.content_size = consumed as u64;
+    }
+    let mut header = [0u8; MAX_FH_SIZE];
+    let n = write_frame_header(&mut header, info);
+    dst.copy_within(header_size..pos, n);
+    dst[..n].copy_from_slice(&header[..n]);
+    Ok((consumed, pos - (header_size - n)))
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Private helpers
 // ─────────────────────────────────────────────────────────────────────────────
@@ -1247,7 +1916,10 @@ mod tests {
 
         // Feed in 1 KB chunks
         for chunk in src.chunks(1024) {
-            let opts = CompressOptions { stable_src: false };
+            let opts = CompressOptions {
+                stable_src: false,
+                ..Default::default()
+            };
             pos += lz4f_compress_update(&mut cctx, &mut out[pos..], chunk, Some(&opts))
                 .expect("update");
         }
@@ -1293,7 +1965,10 @@ mod tests {
         let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
         let mut streaming = vec![0u8; frame_bound];
         let mut pos = 0;
-        let opts = CompressOptions { stable_src: true };
+        let opts = CompressOptions {
+            stable_src: true,
+            ..Default::default()
+        };
         pos += lz4f_compress_begin(&mut cctx, &mut streaming[pos..], Some(&prefs)).expect("begin");
         pos += lz4f_compress_update(&mut cctx, &mut streaming[pos..], &src, Some(&opts))
             .expect("update");
@@ -10,17 +10,26 @@
 //! - Skippable frames (magic range `0x184D2A50`–`0x184D2A5F`)
 //! - Linked (streaming) and independent block modes
 //! - Per-block and whole-frame XXH32 checksum verification
-//! - Predefined-dictionary decompression via [`lz4f_decompress_using_dict`]
+//! - Predefined-dictionary decompression via [`lz4f_decompress_using_dict`],
+//!   or by dictionary ID through a [`DictResolver`]
 //!
 //! The implementation follows the LZ4 frame format spec and mirrors the
 //! `lz4frame.c` reference implementation (v1.10.0), but is pure Rust with no
 //! unsafe code outside of the deliberate raw-pointer zero-copy paths.
 
+use std::sync::Arc;
+
 use crate::block::decompress_api::decompress_safe_using_dict;
-use crate::frame::header::{lz4f_get_block_size, lz4f_header_checksum, read_le32, read_le64};
+use crate::block::decompress_core::decompress_safe_using_dict_windowed;
+use crate::dict::{effective_dict, DictResolver, MAX_DICT_SIZE};
+use crate::frame::aligned::AlignedBuf;
+use crate::frame::header::{
+    frame_header_size, lz4f_get_block_size, parse_frame_header, read_le32, LZ4F_MAGIC_NUMBER,
+};
+use crate::frame::pool::BufferPool;
 use crate::frame::types::{
-    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, CustomMem, DecompressStage, FrameInfo,
-    FrameType, Lz4FError, BF_SIZE, BH_SIZE, LZ4F_VERSION, MAX_FH_SIZE, MIN_FH_SIZE,
+    BlockChecksum, BlockMode, ContentChecksum, DecompressStage, FrameInfo, FrameType, Lz4FError,
+    BF_SIZE, BH_SIZE, LZ4F_VERSION, MAX_FH_SIZE, MIN_FH_SIZE,
 };
 use crate::xxhash::{xxh32_oneshot, Xxh32State};
 
@@ -28,10 +37,72 @@ use crate::xxhash::{xxh32_oneshot, Xxh32State};
 // Constants
 // ─────────────────────────────────────────────────────────────────────────────
 
-const LZ4F_MAGICNUMBER: u32 = 0x184D_2204;
 const LZ4F_MAGIC_SKIPPABLE_START: u32 = 0x184D_2A50;
 const LZ4F_MIN_SIZE_TO_KNOW_HEADER_LENGTH: usize = 5;
-const MAX_DICT_SIZE: usize = 64 * 1024;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ChecksumPolicy
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// How the decoder treats one kind of checksum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ChecksumMode {
+    /// Reject frames whose header does not enable the checksum; verify it
+    /// otherwise.
+    Require,
+    /// Verify the checksum when the frame carries one.
+    #[default]
+    Verify,
+    /// Neither compute nor verify the checksum.
+    Skip,
+}
+
+/// Checksum handling for content (end-of-frame) and block checksums.
+///
+/// The default verifies whatever checksums a frame carries, as the reference
+/// decoder does.  `Require` additionally rejects frames that do not carry
+/// them, with [`Lz4FError::ContentChecksumMissing`] or
+/// [`Lz4FError::BlockChecksumMissing`] as soon as the header is decoded.
+///
+/// ```
+/// use lz4::frame::{ChecksumMode, ChecksumPolicy};
+///
+/// let strict = ChecksumPolicy { content: ChecksumMode::Require, ..Default::default() };
+/// assert_eq!(strict.block, ChecksumMode::Verify);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ChecksumPolicy {
+    /// The XXH32 of the whole decoded content, stored after the end mark.
+    pub content: ChecksumMode,
+    /// The XXH32 stored after each block.
+    pub block: ChecksumMode,
+}
+
+impl ChecksumPolicy {
+    /// Verify the checksums present (the default).
+    pub const VERIFY: Self = ChecksumPolicy {
+        content: ChecksumMode::Verify,
+        block: ChecksumMode::Verify,
+    };
+    /// Skip all checksums; what `DecompressOptions::skip_checksums` selects.
+    pub const SKIP: Self = ChecksumPolicy {
+        content: ChecksumMode::Skip,
+        block: ChecksumMode::Skip,
+    };
+    /// Require and verify both checksums.
+    pub const REQUIRE: Self = ChecksumPolicy {
+        content: ChecksumMode::Require,
+        block: ChecksumMode::Require,
+    };
+
+    fn checks_content(&self) -> bool {
+        self.content != ChecksumMode::Skip
+    }
+
+    fn checks_block(&self) -> bool {
+        self.block != ChecksumMode::Skip
+    }
+}
 
 // ─────────────────────────────────────────────────────────────────────────────
 // DecompressOptions
@@ -43,8 +114,153 @@ const MAX_DICT_SIZE: usize = 64 * 1024;
 pub struct DecompressOptions {
     /// Hint that destination buffer is stable between calls (not used in this impl).
     pub stable_dst: bool,
-    /// Disable all checksum verification. Sticky once set for the frame lifetime.
+    /// Disable all checksum verification for the current frame. Sticky once
+    /// set until the frame ends; overrides `checksum_policy` meanwhile.
     pub skip_checksums: bool,
+    /// Checksum handling for this and later frames.
+    ///
+    /// Supplied before a frame's header is decoded, the policy applies to that
+    /// frame; supplied mid-frame, it applies from the next one, since skipped
+    /// bytes cannot be hashed after the fact.  It stays with the context until
+    /// a later call supplies a different one.  `None` keeps the current policy.
+    pub checksum_policy: Option<ChecksumPolicy>,
+    /// Upper bound on the decoded size of each frame.
+    ///
+    /// A frame whose header declares a larger content size is rejected before
+    /// any block is decoded; a frame without one (or one that lies) fails as
+    /// soon as its output passes the limit.  Either way the call returns
+    /// [`Lz4FError::OutputLimitExceeded`] and at most one byte past the limit
+    /// is written to `dst`.  Once set, the limit stays with the context until
+    /// a later call supplies a different one.
+    pub max_output_size: Option<u64>,
+    /// Accept frame headers that set reserved FLG/BD bits.
+    ///
+    /// The default (`false`) rejects them with [`Lz4FError::ReservedFlagSet`],
+    /// as the reference decoder does.  When `true`, the reserved bits are
+    /// ignored as long as the header checksum, computed over the header as
+    /// this version lays it out, still matches; a future flag that added a
+    /// header field would fail that check.  The version field and block-size
+    /// ID are always validated.  Like `max_output_size`, the setting stays
+    /// with the context until a later call supplies options again.
+    pub allow_unknown_future_flags: bool,
+    /// Window size the caller is prepared to serve, in bytes (`1..=65535`).
+    ///
+    /// Blocks are decoded against at most this much history, and a match
+    /// reaching further back fails with [`Lz4FError::DecompressionFailed`],
+    /// so frames from an encoder configured with a matching
+    /// [`Preferences::max_distance`](crate::frame::types::Preferences::max_distance)
+    /// decode while others are refused rather than silently relying on
+    /// history the caller did not budget for.  Values outside the range give
+    /// [`Lz4FError::ParameterInvalid`].  Like `max_output_size`, the setting
+    /// stays with the context until a later call supplies a different one.
+    pub max_distance: Option<u32>,
+    /// Skip damaged blocks instead of failing, for recovering what is left
+    /// of a corrupted archive.
+    ///
+    /// In a frame with independent blocks, a block whose checksum does not
+    /// match is dropped (its header, data and checksum are consumed, nothing
+    /// is written) and recorded in the context's [`DamageReport`]; decoding
+    /// resumes with the next block.  A damaged frame's content checksum and
+    /// declared content size are then not enforced.  Linked frames still fail
+    /// with [`Lz4FError::BlockChecksumInvalid`], since every later block may
+    /// refer to the lost one.  Only blocks carrying checksums can be detected.
+    /// Like `allow_unknown_future_flags`, the setting stays with the context
+    /// until a later call supplies options again.
+    pub best_effort: bool,
+    /// Hash the decoded output of every frame with XXH32, even frames
+    /// without a content checksum or with checksums skipped, so that
+    /// [`Lz4FDCtx::content_hash_so_far`] can serve as an integrity digest
+    /// without a second pass over the output.  Takes effect from the next
+    /// frame that starts; like `best_effort`, the setting stays with the
+    /// context until a later call supplies options again.
+    pub hash_content: bool,
+}
+
+/// A block skipped by a [`DecompressOptions::best_effort`] decode.
+///
+/// Offsets count from the first byte given to the context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DamagedBlock {
+    /// Position of the block header in the input.
+    pub input_offset: u64,
+    /// Position in the output where the block's data belongs.
+    pub output_offset: u64,
+    /// Input bytes the block occupies: header, data and checksum.
+    pub input_len: usize,
+    /// `true` for an uncompressed block, whose bytes had already been written
+    /// when its checksum arrived; they are kept but may be wrong.  `false`
+    /// when the block was dropped.
+    pub passed_through: bool,
+}
+
+/// Damage found by [`DecompressOptions::best_effort`] decoding.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DamageReport {
+    /// Skipped blocks, in input order.
+    pub blocks: Vec<DamagedBlock>,
+    /// Decoded bytes missing from damaged frames that declare a content
+    /// size.  Losses in frames without one are not counted.
+    pub lost_bytes: u64,
+}
+
+impl DamageReport {
+    /// `true` when nothing was skipped.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+/// Counters for the frame being decoded; see [`Lz4FDCtx::frame_stats`].
+///
+/// Blocks and checksums are counted as their headers are read, so a
+/// finished frame's counters describe the whole frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Data blocks, not counting the end mark.
+    pub blocks: u64,
+    /// Of `blocks`, those stored uncompressed.
+    pub uncompressed_blocks: u64,
+    /// Frame bytes: header, block headers, block data, end mark and
+    /// checksums.
+    pub compressed_bytes: u64,
+    /// Decoded bytes written to the caller.
+    pub uncompressed_bytes: u64,
+    /// Block checksums plus the content checksum.
+    pub checksums: u64,
+}
+
+impl FrameStats {
+    /// `compressed_bytes / uncompressed_bytes`, or `None` before any output.
+    pub fn ratio(&self) -> Option<f64> {
+        (self.uncompressed_bytes != 0)
+            .then(|| self.compressed_bytes as f64 / self.uncompressed_bytes as f64)
+    }
+}
+
+/// Where a decompression context is in its input; see
+/// [`Lz4FDCtx::decode_stage`].
+///
+/// Coarser than the context's internal state machine, and kept stable when
+/// that changes.  New variants may be added, but existing ones keep their
+/// meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeStage {
+    /// At a frame boundary: the next input byte starts a frame.  A new or
+    /// reset context, and one that has just finished a frame, is here.
+    AwaitingFrame,
+    /// Part of a frame header has been consumed.
+    FrameHeader,
+    /// Expecting a block header or the end mark.
+    BlockHeader,
+    /// Inside a block: its data or its checksum.
+    Block,
+    /// Decoded data is waiting for room in the caller's output buffer.
+    FlushingOutput,
+    /// Reading the content checksum that follows the end mark.
+    ContentChecksum,
+    /// Inside a skippable frame: its size field or its payload.
+    SkippableFrame,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -54,57 +270,281 @@ pub struct DecompressOptions {
 /// LZ4 Frame decompression context.
 /// Corresponds to `LZ4F_dctx_s` in lz4frame.c:1260.
 pub struct Lz4FDCtx {
-    pub cmem: CustomMem,
-    pub frame_info: FrameInfo,
-    pub version: u32,
-    pub stage: DecompressStage,
-    pub frame_remaining_size: u64,
-    pub max_block_size: usize,
-    pub max_buffer_size: usize,
-    pub tmp_in: Vec<u8>,
-    pub tmp_in_size: usize,
-    pub tmp_in_target: usize,
-    pub tmp_out_buffer: Vec<u8>,
+    pub(crate) frame_info: FrameInfo,
+    pub(crate) version: u32,
+    pub(crate) stage: DecompressStage,
+    pub(crate) frame_remaining_size: u64,
+    pub(crate) max_block_size: usize,
+    pub(crate) max_buffer_size: usize,
+    /// Staging for split block input; aligned to
+    /// [`BUFFER_ALIGN`](crate::frame::BUFFER_ALIGN).
+    pub(crate) tmp_in: AlignedBuf,
+    pub(crate) tmp_in_size: usize,
+    pub(crate) tmp_in_target: usize,
+    /// Staging for decoded output that does not fit `dst`; aligned like
+    /// `tmp_in`.
+    pub(crate) tmp_out_buffer: AlignedBuf,
     /// Offset of `tmpOut` within `tmp_out_buffer` (C: `tmpOut - tmpOutBuffer`).
-    pub tmp_out_offset: usize,
-    pub tmp_out_size: usize,
-    pub tmp_out_start: usize,
+    pub(crate) tmp_out_offset: usize,
+    pub(crate) tmp_out_size: usize,
+    pub(crate) tmp_out_start: usize,
     /// Rolling 64 KiB decompression dictionary. Replaces C\'s raw `dict` + `dictSize`.
-    pub dict_bytes: Vec<u8>,
-    pub xxh: Xxh32State,
-    pub block_checksum: Xxh32State,
-    /// Sticky: once `true`, checksums are skipped for the rest of the frame.
-    pub skip_checksum: bool,
+    pub(crate) dict_bytes: Vec<u8>,
+    pub(crate) xxh: Xxh32State,
+    /// `xxh` covers the current frame's output; fixed when the frame's first
+    /// block is reached.
+    pub(crate) frame_hashing: bool,
+    /// Set through [`DecompressOptions::hash_content`].
+    pub(crate) hash_content: bool,
+    pub(crate) block_checksum: Xxh32State,
+    /// Policy set through [`DecompressOptions::checksum_policy`].
+    pub(crate) checksum_policy: ChecksumPolicy,
+    /// Policy in force for the current frame: `checksum_policy` as of the
+    /// frame's start, or [`ChecksumPolicy::SKIP`] once `skip_checksums` is seen.
+    pub(crate) frame_checksum_policy: ChecksumPolicy,
+    /// Limit set through [`DecompressOptions::max_output_size`].
+    pub(crate) max_output_size: Option<u64>,
+    /// Bytes produced so far for the current frame.
+    pub(crate) frame_output_size: u64,
+    /// Set through [`DecompressOptions::allow_unknown_future_flags`].
+    pub(crate) allow_unknown_future_flags: bool,
+    /// Window set through [`DecompressOptions::max_distance`].
+    pub(crate) max_distance: Option<u32>,
+    /// Set through [`DecompressOptions::best_effort`].
+    pub(crate) best_effort: bool,
+    /// Blocks skipped so far; see [`Lz4FDCtx::take_damage_report`].
+    pub(crate) damage: DamageReport,
+    /// The current frame lost a block to best-effort decoding.
+    pub(crate) frame_damaged: bool,
+    /// Input bytes consumed by earlier calls.
+    pub(crate) total_in: u64,
+    /// Output bytes produced by earlier calls.
+    pub(crate) total_out: u64,
+    /// Input length of the current block: header, data and checksum.
+    pub(crate) block_len: usize,
+    /// Counters for the current frame; see [`Lz4FDCtx::frame_stats`].
+    pub(crate) stats: FrameStats,
     /// Staging area for frame header bytes and per-block checksum bytes.
-    pub header: [u8; MAX_FH_SIZE],
+    pub(crate) header: [u8; MAX_FH_SIZE],
+    /// Source of `tmp_in` / `tmp_out_buffer` allocations; `None` uses the
+    /// global allocator.  Set by [`Lz4FDCtx::with_pool`].
+    pub(crate) pool: Option<Arc<dyn BufferPool>>,
+    /// Consulted for frames naming a dictionary the caller did not pass.
+    /// Set by [`Lz4FDCtx::set_dict_resolver`].
+    pub(crate) dict_resolver: Option<Arc<dyn DictResolver>>,
+}
+
+impl Drop for Lz4FDCtx {
+    /// Hand the staging buffers back to the pool, if any.
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            self.tmp_in.release_to(&*pool);
+            self.tmp_out_buffer.release_to(&*pool);
+        }
+    }
 }
 
 impl Lz4FDCtx {
     /// Create a zeroed decompression context.
     pub fn new(version: u32) -> Box<Self> {
         Box::new(Lz4FDCtx {
-            cmem: CustomMem::default(),
             frame_info: FrameInfo::default(),
             version,
             stage: DecompressStage::GetFrameHeader,
             frame_remaining_size: 0,
             max_block_size: 0,
             max_buffer_size: 0,
-            tmp_in: Vec::new(),
+            tmp_in: AlignedBuf::new(),
             tmp_in_size: 0,
             tmp_in_target: 0,
-            tmp_out_buffer: Vec::new(),
+            tmp_out_buffer: AlignedBuf::new(),
             tmp_out_offset: 0,
             tmp_out_size: 0,
             tmp_out_start: 0,
             dict_bytes: Vec::new(),
             xxh: Xxh32State::new(0),
+            frame_hashing: false,
+            hash_content: false,
             block_checksum: Xxh32State::new(0),
-            skip_checksum: false,
+            checksum_policy: ChecksumPolicy::default(),
+            frame_checksum_policy: ChecksumPolicy::default(),
+            max_output_size: None,
+            frame_output_size: 0,
+            allow_unknown_future_flags: false,
+            max_distance: None,
+            best_effort: false,
+            damage: DamageReport::default(),
+            frame_damaged: false,
+            total_in: 0,
+            total_out: 0,
+            block_len: 0,
+            stats: FrameStats::default(),
             header: [0u8; MAX_FH_SIZE],
+            pool: None,
+            dict_resolver: None,
         })
     }
 
+    /// Create a decompression context whose staging buffers come from `pool`
+    /// and return to it when replaced or dropped.
+    pub fn with_pool(version: u32, pool: Arc<dyn BufferPool>) -> Box<Self> {
+        let mut dctx = Self::new(version);
+        dctx.pool = Some(pool);
+        dctx
+    }
+
+    /// Look up dictionaries through `resolver` from now on.
+    ///
+    /// When a frame header carries a nonzero dictionary ID and no dictionary
+    /// was passed to [`lz4f_decompress_using_dict`], the decoder loads the
+    /// one `resolver` returns for that ID, so frames made with different
+    /// dictionaries decode through the same calls.  An ID the resolver does
+    /// not know fails the header with [`Lz4FError::DictionaryUnavailable`].
+    /// A dictionary passed explicitly still takes precedence.  The resolver
+    /// stays installed across frames and resets; an `Arc` lets many contexts
+    /// share one registry.
+    pub fn set_dict_resolver(&mut self, resolver: Arc<dyn DictResolver>) {
+        self.dict_resolver = Some(resolver);
+    }
+
+    /// Return to the start of a frame without freeing anything.
+    ///
+    /// Any frame in progress is abandoned, so this also recovers a context
+    /// after an error.  `tmp_in`, `tmp_out_buffer` and the history keep
+    /// their capacity, and a pooled context keeps its buffers checked out.
+    /// The reset itself never allocates, and the next frame allocates only
+    /// if it needs larger blocks than any before it.  Options set by earlier
+    /// calls, such as limits and checksum policies, stay in force, and so
+    /// does the damage record.
+    pub fn reset_keep_buffers(&mut self) {
+        self.stage = DecompressStage::GetFrameHeader;
+        self.dict_bytes.clear();
+        self.frame_checksum_policy = self.checksum_policy;
+        self.frame_output_size = 0;
+        self.frame_remaining_size = 0;
+        self.frame_damaged = false;
+        self.frame_info = FrameInfo::default();
+        self.tmp_in_size = 0;
+        self.tmp_in_target = 0;
+        self.tmp_out_offset = 0;
+        self.tmp_out_size = 0;
+        self.tmp_out_start = 0;
+    }
+
+    /// Blocks skipped by [`DecompressOptions::best_effort`] decoding since
+    /// the last call, which clears the record.
+    pub fn take_damage_report(&mut self) -> DamageReport {
+        core::mem::take(&mut self.damage)
+    }
+
+    /// XXH32 (seed 0) of the current frame's output so far, or `None` when
+    /// the frame is not being hashed.
+    ///
+    /// A frame is hashed when it carries a content checksum that is being
+    /// verified, or when [`DecompressOptions::hash_content`] was set before
+    /// it started.  Once the frame ends the value is the digest of its whole
+    /// output, which for a checksummed frame equals the stored checksum; it
+    /// stays available until the next frame header is decoded.
+    pub fn content_hash_so_far(&self) -> Option<u32> {
+        self.frame_hashing.then(|| self.xxh.digest())
+    }
+
+    /// The API version the context was created with.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Where decoding stands: between frames, in a header, in a block, and
+    /// so on.  See [`DecodeStage`].
+    pub fn decode_stage(&self) -> DecodeStage {
+        match self.stage {
+            DecompressStage::GetFrameHeader => DecodeStage::AwaitingFrame,
+            DecompressStage::StoreFrameHeader => DecodeStage::FrameHeader,
+            DecompressStage::Init
+            | DecompressStage::GetBlockHeader
+            | DecompressStage::StoreBlockHeader => DecodeStage::BlockHeader,
+            DecompressStage::CopyDirect
+            | DecompressStage::GetBlockChecksum
+            | DecompressStage::GetCBlock
+            | DecompressStage::StoreCBlock => DecodeStage::Block,
+            DecompressStage::FlushOut => DecodeStage::FlushingOutput,
+            DecompressStage::GetSuffix | DecompressStage::StoreSuffix => {
+                DecodeStage::ContentChecksum
+            }
+            DecompressStage::GetSFrameSize
+            | DecompressStage::StoreSFrameSize
+            | DecompressStage::SkipSkippable => DecodeStage::SkippableFrame,
+        }
+    }
+
+    /// The staging buffers for split block input and for decoded output
+    /// that did not fit `dst`, in that order.  Empty until a frame needs
+    /// them.
+    pub fn staging_buffers(&self) -> (&AlignedBuf, &AlignedBuf) {
+        (&self.tmp_in, &self.tmp_out_buffer)
+    }
+
+    /// Bytes of heap memory the context holds for staging and for the
+    /// linked-block history.  [`reset_keep_buffers`](Self::reset_keep_buffers)
+    /// leaves it unchanged.
+    pub fn buffer_capacity(&self) -> usize {
+        self.tmp_in.capacity() + self.tmp_out_buffer.capacity() + self.dict_bytes.capacity()
+    }
+
+    /// Counters for the current frame, accumulated by [`lz4f_decompress`].
+    ///
+    /// They restart when a frame header is decoded and, like
+    /// [`content_hash_so_far`](Self::content_hash_so_far), describe the
+    /// finished frame until the next one begins.  Skippable frames leave them
+    /// untouched.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    /// React to a block checksum mismatch detected with the block's last
+    /// byte at `src_end` (this call's input) and `dst_pos` bytes written.
+    ///
+    /// Fails unless best effort applies; otherwise records the block and
+    /// moves on to the next block header.
+    fn block_checksum_failed(
+        &mut self,
+        src_end: usize,
+        dst_pos: usize,
+        passed_through: bool,
+    ) -> Result<(), Lz4FError> {
+        if !self.best_effort || self.frame_info.block_mode != BlockMode::Independent {
+            return Err(Lz4FError::BlockChecksumInvalid);
+        }
+        let mut output_offset = self.total_out + dst_pos as u64;
+        if passed_through {
+            output_offset -= (self.block_len - BH_SIZE - BF_SIZE) as u64;
+        }
+        self.damage.blocks.push(DamagedBlock {
+            input_offset: self.total_in + src_end as u64 - self.block_len as u64,
+            output_offset,
+            input_len: self.block_len,
+            passed_through,
+        });
+        self.frame_damaged = true;
+        self.stage = DecompressStage::GetBlockHeader;
+        Ok(())
+    }
+
+    /// Count `n` newly produced bytes against [`DecompressOptions::max_output_size`].
+    fn account_output(&mut self, n: usize) -> Result<(), Lz4FError> {
+        self.frame_output_size += n as u64;
+        self.stats.uncompressed_bytes += n as u64;
+        match self.max_output_size {
+            Some(limit) if self.frame_output_size > limit => Err(Lz4FError::OutputLimitExceeded),
+            _ => Ok(()),
+        }
+    }
+
+    /// History kept for linked blocks: 64 KiB, or the declared window.
+    fn history_size(&self) -> usize {
+        self.max_distance.map_or(MAX_DICT_SIZE, |d| d as usize)
+    }
+
     /// Append `new_bytes` to the rolling 64 KiB history dictionary.
     /// Equivalent to the copy-based part of `LZ4F_updateDict` (lz4frame.c:1527).
     fn update_dict(&mut self, new_bytes: &[u8]) {
@@ -112,14 +552,15 @@ impl Lz4FDCtx {
         if n == 0 {
             return;
         }
-        if n >= MAX_DICT_SIZE {
-            let src_start = n - MAX_DICT_SIZE;
-            self.dict_bytes.resize(MAX_DICT_SIZE, 0);
+        let history = self.history_size();
+        if n >= history {
+            let src_start = n - history;
+            self.dict_bytes.resize(history, 0);
             self.dict_bytes.copy_from_slice(&new_bytes[src_start..]);
         } else {
             let total = self.dict_bytes.len() + n;
-            if total > MAX_DICT_SIZE {
-                let drop = total - MAX_DICT_SIZE;
+            if total > history {
+                let drop = total - history;
                 self.dict_bytes.drain(..drop);
             }
             self.dict_bytes.extend_from_slice(new_bytes);
@@ -144,14 +585,11 @@ pub fn lz4f_create_decompression_context(version: u32) -> Result<Box<Lz4FDCtx>,
 /// Equivalent to `LZ4F_freeDecompressionContext` (lz4frame.c:1313).
 pub fn lz4f_free_decompression_context(_dctx: Box<Lz4FDCtx>) {}
 
-/// Reset context to initial state for a new frame.
+/// Reset context to initial state for a new frame, keeping its buffers (see
+/// [`Lz4FDCtx::reset_keep_buffers`]).
 /// Equivalent to `LZ4F_resetDecompressionContext` (lz4frame.c:1327).
 pub fn lz4f_reset_decompression_context(dctx: &mut Lz4FDCtx) {
-    dctx.stage = DecompressStage::GetFrameHeader;
-    dctx.dict_bytes.clear();
-    dctx.skip_checksum = false;
-    dctx.frame_remaining_size = 0;
-    dctx.frame_info = FrameInfo::default();
+    dctx.reset_keep_buffers();
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -174,6 +612,7 @@ fn decode_header(
         return Err(Lz4FError::FrameHeaderIncomplete);
     }
     dctx.frame_info = FrameInfo::default();
+    dctx.frame_hashing = false;
 
     let magic = read_le32(src, 0);
     if (magic & 0xFFFF_FFF0) == LZ4F_MAGIC_SKIPPABLE_START {
@@ -189,29 +628,13 @@ fn decode_header(
         }
     }
 
-    if magic != LZ4F_MAGICNUMBER {
+    if magic != LZ4F_MAGIC_NUMBER {
         return Err(Lz4FError::FrameTypeUnknown);
     }
     dctx.frame_info.frame_type = FrameType::Frame;
 
-    let flg = src[4] as u32;
-    let version = (flg >> 6) & 0x3;
-    let block_mode = (flg >> 5) & 0x1;
-    let block_checksum_flag = (flg >> 4) & 0x1;
-    let content_size_flag = (flg >> 3) & 0x1;
-    let content_checksum_flag = (flg >> 2) & 0x1;
-    let dict_id_flag = flg & 0x1;
-    if ((flg >> 1) & 0x1) != 0 {
-        return Err(Lz4FError::ReservedFlagSet);
-    }
-    if version != 1 {
-        return Err(Lz4FError::HeaderVersionWrong);
-    }
-
-    let fh_size = MIN_FH_SIZE
-        + if content_size_flag != 0 { 8 } else { 0 }
-        + if dict_id_flag != 0 { 4 } else { 0 };
-
+    let strict = !dctx.allow_unknown_future_flags;
+    let fh_size = frame_header_size(src[4], strict)?;
     if src.len() < fh_size {
         if !from_header_buf {
             dctx.header[..src.len()].copy_from_slice(src);
@@ -222,58 +645,39 @@ fn decode_header(
         return Ok(src.len());
     }
 
-    let bd = src[5] as u32;
-    let bsid_raw = (bd >> 4) & 0x7;
-    if ((bd >> 7) & 0x1) != 0 {
-        return Err(Lz4FError::ReservedFlagSet);
+    let (info, fh_size) = parse_frame_header(src, strict)?;
+    let policy = dctx.frame_checksum_policy;
+    if policy.content == ChecksumMode::Require
+        && info.content_checksum_flag == ContentChecksum::Disabled
+    {
+        return Err(Lz4FError::ContentChecksumMissing);
     }
-    if bsid_raw < 4 {
-        return Err(Lz4FError::MaxBlockSizeInvalid);
+    if policy.block == ChecksumMode::Require && info.block_checksum_flag == BlockChecksum::Disabled
+    {
+        return Err(Lz4FError::BlockChecksumMissing);
     }
-    if (bd & 0x0F) != 0 {
-        return Err(Lz4FError::ReservedFlagSet);
+    if dctx
+        .max_output_size
+        .is_some_and(|limit| info.content_size > limit)
+    {
+        return Err(Lz4FError::OutputLimitExceeded);
     }
-
-    let hc = lz4f_header_checksum(&src[4..fh_size - 1]);
-    if hc != src[fh_size - 1] {
-        return Err(Lz4FError::HeaderChecksumInvalid);
+    if info.dict_id != 0 && dctx.dict_bytes.is_empty() {
+        if let Some(resolver) = dctx.dict_resolver.as_deref() {
+            let dict = resolver
+                .resolve(info.dict_id)
+                .ok_or(Lz4FError::DictionaryUnavailable)?;
+            dctx.dict_bytes.extend_from_slice(effective_dict(&dict));
+        }
     }
+    dctx.frame_info = info;
+    dctx.max_block_size = lz4f_get_block_size(info.block_size_id).unwrap_or(MAX_DICT_SIZE);
+    dctx.frame_remaining_size = info.content_size;
 
-    let block_size_id = match bsid_raw {
-        4 => BlockSizeId::Max64Kb,
-        5 => BlockSizeId::Max256Kb,
-        6 => BlockSizeId::Max1Mb,
-        7 => BlockSizeId::Max4Mb,
-        _ => return Err(Lz4FError::MaxBlockSizeInvalid),
-    };
-
-    dctx.frame_info.block_mode = if block_mode != 0 {
-        BlockMode::Independent
-    } else {
-        BlockMode::Linked
-    };
-    dctx.frame_info.block_checksum_flag = if block_checksum_flag != 0 {
-        BlockChecksum::Enabled
-    } else {
-        BlockChecksum::Disabled
-    };
-    dctx.frame_info.content_checksum_flag = if content_checksum_flag != 0 {
-        ContentChecksum::Enabled
-    } else {
-        ContentChecksum::Disabled
+    dctx.stats = FrameStats {
+        compressed_bytes: fh_size as u64,
+        ..FrameStats::default()
     };
-    dctx.frame_info.block_size_id = block_size_id;
-    dctx.max_block_size = lz4f_get_block_size(block_size_id).unwrap_or(MAX_DICT_SIZE);
-
-    if content_size_flag != 0 {
-        let cs = read_le64(src, 6);
-        dctx.frame_info.content_size = cs;
-        dctx.frame_remaining_size = cs;
-    }
-    if dict_id_flag != 0 {
-        dctx.frame_info.dict_id = read_le32(src, fh_size - 5);
-    }
-
     dctx.stage = DecompressStage::Init;
     Ok(fh_size)
 }
@@ -292,7 +696,7 @@ pub fn lz4f_header_size(src: &[u8]) -> Result<usize, Lz4FError> {
     if (magic & 0xFFFF_FFF0) == LZ4F_MAGIC_SKIPPABLE_START {
         return Ok(8);
     }
-    if magic != LZ4F_MAGICNUMBER {
+    if magic != LZ4F_MAGIC_NUMBER {
         return Err(Lz4FError::FrameTypeUnknown);
     }
     let flg = src[4] as u32;
@@ -342,13 +746,59 @@ pub fn lz4f_decompress(
     dst: Option<&mut [u8]>,
     src: &[u8],
     opts: Option<&DecompressOptions>,
+) -> Result<(usize, usize, usize), Lz4FError> {
+    let result = decompress_step(dctx, dst, src, opts);
+    if let Ok((consumed, written, _)) = result {
+        dctx.total_in += consumed as u64;
+        dctx.total_out += written as u64;
+    }
+    result
+}
+
+/// Body of [`lz4f_decompress`]; the wrapper keeps the running totals that
+/// [`DamagedBlock`] offsets are based on.
+fn decompress_step(
+    dctx: &mut Lz4FDCtx,
+    dst: Option<&mut [u8]>,
+    src: &[u8],
+    opts: Option<&DecompressOptions>,
 ) -> Result<(usize, usize, usize), Lz4FError> {
     if let Some(o) = opts {
-        dctx.skip_checksum |= o.skip_checksums;
+        if let Some(policy) = o.checksum_policy {
+            dctx.checksum_policy = policy;
+            if dctx.stage <= DecompressStage::StoreFrameHeader {
+                dctx.frame_checksum_policy = policy;
+            }
+        }
+        if o.skip_checksums {
+            dctx.frame_checksum_policy = ChecksumPolicy::SKIP;
+            // Hashing only to verify the checksum stops with it.
+            dctx.frame_hashing &= dctx.hash_content;
+        }
+        if o.max_output_size.is_some() {
+            dctx.max_output_size = o.max_output_size;
+        }
+        dctx.allow_unknown_future_flags = o.allow_unknown_future_flags;
+        dctx.best_effort = o.best_effort;
+        dctx.hash_content = o.hash_content;
+        if let Some(window) = o.max_distance {
+            if !(1..=MAX_DICT_SIZE as u32 - 1).contains(&window) {
+                return Err(Lz4FError::ParameterInvalid);
+            }
+            dctx.max_distance = Some(window);
+        }
     }
 
     let src_len = src.len();
-    let dst_len = dst.as_ref().map_or(0, |d| d.len());
+    let mut dst_len = dst.as_ref().map_or(0, |d| d.len());
+    if let Some(limit) = dctx.max_output_size {
+        // Leave room for exactly one byte past the limit, so an oversized
+        // frame is detected without ever stalling for want of output space.
+        let allowance = limit
+            .saturating_sub(dctx.frame_output_size)
+            .saturating_add(1);
+        dst_len = dst_len.min(usize::try_from(allowance).unwrap_or(usize::MAX));
+    }
 
     // Raw pointer to dst so we can re-borrow after mutable operations on dctx.
     // SAFETY: `dst_raw` is valid for `dst_len` bytes for the lifetime of `dst`.
@@ -392,8 +842,8 @@ pub fn lz4f_decompress(
                         continue 'sm;
                     }
                     let tgt = dctx.tmp_in_target;
-                    let hdr: Vec<u8> = dctx.header[..tgt].to_vec();
-                    decode_header(dctx, &hdr, true)?;
+                    let hdr = dctx.header;
+                    decode_header(dctx, &hdr[..tgt], true)?;
                 }
             }
 
@@ -409,14 +859,17 @@ pub fn lz4f_decompress(
                     do_another = false;
                 } else {
                     let tgt = dctx.tmp_in_target;
-                    let hdr: Vec<u8> = dctx.header[..tgt].to_vec();
-                    decode_header(dctx, &hdr, true)?;
+                    let hdr = dctx.header;
+                    decode_header(dctx, &hdr[..tgt], true)?;
                 }
             }
 
             // ── Init ─────────────────────────────────────────────────────────
             DecompressStage::Init => {
-                if dctx.frame_info.content_checksum_flag == ContentChecksum::Enabled {
+                dctx.frame_hashing = dctx.hash_content
+                    || (dctx.frame_checksum_policy.checks_content()
+                        && dctx.frame_info.content_checksum_flag == ContentChecksum::Enabled);
+                if dctx.frame_hashing {
                     dctx.xxh = Xxh32State::new(0);
                 }
                 let buf_needed = dctx.max_block_size
@@ -425,12 +878,21 @@ pub fn lz4f_decompress(
                     } else {
                         0
                     };
+                // Every staging buffer is sized for the largest block here, at
+                // header time, so no block of this frame allocates.
                 if buf_needed > dctx.max_buffer_size {
                     dctx.max_buffer_size = 0;
-                    dctx.tmp_in.resize(dctx.max_block_size + BF_SIZE, 0);
-                    dctx.tmp_out_buffer.resize(buf_needed, 0);
+                    let pool = dctx.pool.as_deref();
+                    dctx.tmp_in
+                        .resize_in(dctx.max_block_size + BF_SIZE, 0, pool);
+                    dctx.tmp_out_buffer.resize_in(buf_needed, 0, pool);
                     dctx.max_buffer_size = buf_needed;
                 }
+                if dctx.frame_info.block_mode == BlockMode::Linked {
+                    let len = dctx.dict_bytes.len();
+                    let history = dctx.history_size();
+                    dctx.dict_bytes.reserve(history.saturating_sub(len));
+                }
                 dctx.tmp_in_size = 0;
                 dctx.tmp_in_target = 0;
                 dctx.tmp_out_offset = 0;
@@ -532,14 +994,15 @@ pub fn lz4f_decompress(
                             size_to_copy,
                         );
                     }
-                    if !dctx.skip_checksum {
-                        if dctx.frame_info.block_checksum_flag == BlockChecksum::Enabled {
-                            dctx.block_checksum
-                                .update(&src[src_pos..src_pos + size_to_copy]);
-                        }
-                        if dctx.frame_info.content_checksum_flag == ContentChecksum::Enabled {
-                            dctx.xxh.update(&src[src_pos..src_pos + size_to_copy]);
-                        }
+                    let policy = dctx.frame_checksum_policy;
+                    if policy.checks_block()
+                        && dctx.frame_info.block_checksum_flag == BlockChecksum::Enabled
+                    {
+                        dctx.block_checksum
+                            .update(&src[src_pos..src_pos + size_to_copy]);
+                    }
+                    if dctx.frame_hashing {
+                        dctx.xxh.update(&src[src_pos..src_pos + size_to_copy]);
                     }
                     if dctx.frame_info.content_size != 0 {
                         dctx.frame_remaining_size -= size_to_copy as u64;
@@ -549,6 +1012,7 @@ pub fn lz4f_decompress(
                     }
                     src_pos += size_to_copy;
                     dst_pos += size_to_copy;
+                    dctx.account_output(size_to_copy)?;
                 }
 
                 if size_to_copy == dctx.tmp_in_target {
@@ -600,14 +1064,14 @@ pub fn lz4f_decompress(
                         dctx.header[3],
                     ];
                 }
-                if !dctx.skip_checksum {
+                dctx.stage = DecompressStage::GetBlockHeader;
+                if dctx.frame_checksum_policy.checks_block() {
                     let read_crc = u32::from_le_bytes(crc4);
                     let calc_crc = dctx.block_checksum.digest();
                     if read_crc != calc_crc {
-                        return Err(Lz4FError::BlockChecksumInvalid);
+                        dctx.block_checksum_failed(src_pos, dst_pos, true)?;
                     }
                 }
-                dctx.stage = DecompressStage::GetBlockHeader;
             }
 
             // ── GetCBlock ────────────────────────────────────────────────────
@@ -643,14 +1107,14 @@ pub fn lz4f_decompress(
                             dctx.tmp_in[c_size + 3],
                         ]);
                         let calc_crc = xxh32_oneshot(&dctx.tmp_in[..c_size], 0);
-                        if !dctx.skip_checksum && read_crc != calc_crc {
-                            return Err(Lz4FError::BlockChecksumInvalid);
+                        if dctx.frame_checksum_policy.checks_block() && read_crc != calc_crc {
+                            dctx.block_checksum_failed(src_pos, dst_pos, false)?;
+                            continue 'sm;
                         }
                     }
-                    let src_data: Vec<u8> = dctx.tmp_in[..c_size].to_vec();
-                    decompress_and_dispatch(
+                    decompress_buffered_block(
                         dctx,
-                        &src_data,
+                        c_size,
                         &mut dst_pos,
                         dst_len,
                         dst_raw,
@@ -672,14 +1136,14 @@ pub fn lz4f_decompress(
                             src[crc_off + 3],
                         ]);
                         let calc_crc = xxh32_oneshot(&src[block_start..block_start + c_size], 0);
-                        if !dctx.skip_checksum && read_crc != calc_crc {
-                            return Err(Lz4FError::BlockChecksumInvalid);
+                        if dctx.frame_checksum_policy.checks_block() && read_crc != calc_crc {
+                            dctx.block_checksum_failed(src_pos, dst_pos, false)?;
+                            continue 'sm;
                         }
                     }
-                    let src_data: Vec<u8> = src[block_start..block_start + c_size].to_vec();
                     decompress_and_dispatch(
                         dctx,
-                        &src_data,
+                        &src[block_start..block_start + c_size],
                         &mut dst_pos,
                         dst_len,
                         dst_raw,
@@ -718,14 +1182,14 @@ pub fn lz4f_decompress(
                         dctx.tmp_in[c_size + 3],
                     ]);
                     let calc_crc = xxh32_oneshot(&dctx.tmp_in[..c_size], 0);
-                    if !dctx.skip_checksum && read_crc != calc_crc {
-                        return Err(Lz4FError::BlockChecksumInvalid);
+                    if dctx.frame_checksum_policy.checks_block() && read_crc != calc_crc {
+                        dctx.block_checksum_failed(src_pos, dst_pos, false)?;
+                        continue 'sm;
                     }
                 }
-                let src_data: Vec<u8> = dctx.tmp_in[..c_size].to_vec();
-                decompress_and_dispatch(
+                decompress_buffered_block(
                     dctx,
-                    &src_data,
+                    c_size,
                     &mut dst_pos,
                     dst_len,
                     dst_raw,
@@ -749,15 +1213,21 @@ pub fn lz4f_decompress(
                             copy,
                         );
                     }
+                    if dctx.frame_hashing {
+                        dctx.xxh
+                            .update(&dctx.tmp_out_buffer[src_off..src_off + copy]);
+                    }
                     // Mirror C's LZ4F_updateDict(withinTmp=1) in lz4frame.c:1969: update the
                     // rolling history window with the bytes just flushed from tmp_out_buffer.
-                    // The clone releases the tmp_out_buffer borrow before the mutable call.
+                    // The buffer is moved out for the call rather than copied.
                     if dctx.frame_info.block_mode == BlockMode::Linked && copy > 0 {
-                        let flushed = dctx.tmp_out_buffer[src_off..src_off + copy].to_vec();
-                        dctx.update_dict(&flushed);
+                        let tmp_out = core::mem::take(&mut dctx.tmp_out_buffer);
+                        dctx.update_dict(&tmp_out[src_off..src_off + copy]);
+                        dctx.tmp_out_buffer = tmp_out;
                     }
                     dctx.tmp_out_start += copy;
                     dst_pos += copy;
+                    dctx.account_output(copy)?;
                 }
                 if dctx.tmp_out_start == dctx.tmp_out_size {
                     dctx.stage = DecompressStage::GetBlockHeader;
@@ -770,7 +1240,11 @@ pub fn lz4f_decompress(
             // ── GetSuffix ────────────────────────────────────────────────────
             DecompressStage::GetSuffix => {
                 if dctx.frame_remaining_size != 0 {
-                    return Err(Lz4FError::FrameSizeWrong);
+                    if !dctx.frame_damaged {
+                        return Err(Lz4FError::FrameSizeWrong);
+                    }
+                    dctx.damage.lost_bytes += dctx.frame_remaining_size;
+                    dctx.frame_remaining_size = 0;
                 }
                 if dctx.frame_info.content_checksum_flag == ContentChecksum::Disabled {
                     next_hint = 0;
@@ -943,6 +1417,7 @@ pub fn lz4f_decompress(
 /// `LZ4F_BLOCKUNCOMPRESSED_FLAG`).  `src_pos`, `next_hint`, and `do_another`
 /// are updated in place to control the outer state-machine loop in
 /// [`lz4f_decompress`].
+#[allow(clippy::too_many_arguments)]
 fn process_block_header(
     dctx: &mut Lz4FDCtx,
     bh: [u8; BH_SIZE],
@@ -960,16 +1435,24 @@ fn process_block_header(
     } else {
         0
     };
+    dctx.block_len = BH_SIZE + next_c_block_size + crc_size;
 
     if block_header == 0 {
+        dctx.stats.compressed_bytes += BH_SIZE as u64;
         dctx.stage = DecompressStage::GetSuffix;
         return Ok(());
     }
     if next_c_block_size > dctx.max_block_size {
         return Err(Lz4FError::MaxBlockSizeInvalid);
     }
+    dctx.stats.blocks += 1;
+    dctx.stats.compressed_bytes += dctx.block_len as u64;
+    if crc_size != 0 {
+        dctx.stats.checksums += 1;
+    }
 
     if (block_header & crate::frame::types::LZ4F_BLOCKUNCOMPRESSED_FLAG) != 0 {
+        dctx.stats.uncompressed_blocks += 1;
         dctx.tmp_in_target = next_c_block_size;
         if dctx.frame_info.block_checksum_flag == BlockChecksum::Enabled {
             dctx.block_checksum = Xxh32State::new(0);
@@ -991,6 +1474,69 @@ fn process_block_header(
 // Block decompression and dispatch
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Decodes the first `c_size` bytes of `tmp_in` (a block buffered across
+/// calls).  `tmp_in` is moved out for the call rather than copied, so a
+/// block costs no allocation.
+fn decompress_buffered_block(
+    dctx: &mut Lz4FDCtx,
+    c_size: usize,
+    dst_pos: &mut usize,
+    dst_len: usize,
+    dst_raw: *mut u8,
+    next_hint: &mut usize,
+    do_another: &mut bool,
+) -> Result<(), Lz4FError> {
+    let tmp_in = core::mem::take(&mut dctx.tmp_in);
+    let result = decompress_and_dispatch(
+        dctx,
+        &tmp_in[..c_size],
+        dst_pos,
+        dst_len,
+        dst_raw,
+        next_hint,
+        do_another,
+    );
+    dctx.tmp_in = tmp_in;
+    result
+}
+
+/// Decode one block into `cap` bytes at `out` against `dict_len` bytes of
+/// history, honouring the declared window when there is one.
+///
+/// # Safety
+/// `out` must be valid for writes of `cap` bytes, `dict_ptr` valid for reads
+/// of `dict_len` bytes (or null when `dict_len == 0`), and the two must not
+/// overlap.
+unsafe fn decode_block(
+    compressed: &[u8],
+    out: *mut u8,
+    cap: usize,
+    dict_ptr: *const u8,
+    dict_len: usize,
+    max_distance: Option<u32>,
+) -> Result<usize, Lz4FError> {
+    let decoded = match max_distance {
+        None => decompress_safe_using_dict(
+            compressed.as_ptr(),
+            out,
+            compressed.len(),
+            cap,
+            dict_ptr,
+            dict_len,
+        ),
+        Some(window) => {
+            let dict = if dict_len == 0 {
+                &[][..]
+            } else {
+                core::slice::from_raw_parts(dict_ptr, dict_len)
+            };
+            let dst = core::slice::from_raw_parts_mut(out, cap);
+            decompress_safe_using_dict_windowed(compressed, dst, dict, window)
+        }
+    };
+    decoded.map_err(|_| Lz4FError::DecompressionFailed)
+}
+
 /// Decompress a validated LZ4 block and route the output to either the
 /// caller's destination buffer or the internal `tmp_out_buffer`.
 ///
@@ -1018,21 +1564,19 @@ fn decompress_and_dispatch(
         // Decode directly into caller\'s destination buffer.
         // SAFETY: dst_raw is valid for dst_avail bytes; dict_ptr valid for dict_len bytes.
         let decoded = unsafe {
-            decompress_safe_using_dict(
-                compressed.as_ptr(),
+            decode_block(
+                compressed,
                 dst_raw.add(*dst_pos),
-                compressed.len(),
                 dst_avail,
                 dict_ptr,
                 dict_len,
-            )
-            .map_err(|_| Lz4FError::DecompressionFailed)?
+                dctx.max_distance,
+            )?
         };
 
         // Post-decode: update checksum and dict by reading back the decoded bytes.
         // SAFETY: we just wrote `decoded` bytes at dst_raw+*dst_pos; they are valid.
-        if !dctx.skip_checksum && dctx.frame_info.content_checksum_flag == ContentChecksum::Enabled
-        {
+        if dctx.frame_hashing {
             let decoded_slice =
                 unsafe { core::slice::from_raw_parts(dst_raw.add(*dst_pos) as *const u8, decoded) };
             dctx.xxh.update(decoded_slice);
@@ -1046,6 +1590,7 @@ fn decompress_and_dispatch(
             dctx.update_dict(decoded_slice);
         }
         *dst_pos += decoded;
+        dctx.account_output(decoded)?;
         dctx.stage = DecompressStage::GetBlockHeader;
     } else {
         // Decode into tmp_out_buffer then flush to dst later.
@@ -1054,24 +1599,21 @@ fn decompress_and_dispatch(
         let tmp_ptr = dctx.tmp_out_buffer.as_mut_ptr();
         // SAFETY: tmp_ptr valid for cap bytes; dict_ptr valid for dict_len bytes.
         let decoded = unsafe {
-            decompress_safe_using_dict(
-                compressed.as_ptr(),
+            decode_block(
+                compressed,
                 tmp_ptr,
-                compressed.len(),
                 cap,
                 dict_ptr,
                 dict_len,
-            )
-            .map_err(|_| Lz4FError::DecompressionFailed)?
+                dctx.max_distance,
+            )?
         };
 
-        if !dctx.skip_checksum && dctx.frame_info.content_checksum_flag == ContentChecksum::Enabled
-        {
-            dctx.xxh.update(&dctx.tmp_out_buffer[..decoded]);
-        }
         if dctx.frame_info.content_size != 0 {
             dctx.frame_remaining_size -= decoded as u64;
         }
+        // The content hash is updated in FlushOut, so that it only ever covers
+        // bytes the caller has received.
         // Do NOT update dict here for the tmpOut path: the dictionary is updated
         // incrementally in FlushOut as data is flushed to the caller's buffer,
         // mirroring C's LZ4F_updateDict(withinTmp=1) semantics (lz4frame.c:1969).
@@ -1089,9 +1631,12 @@ fn decompress_and_dispatch(
 /// Verify the four-byte little-endian XXH32 content checksum stored at the
 /// end of a standard LZ4 frame.  Returns `Lz4FError::ContentChecksumInvalid`
 /// if the stored value does not match the running `dctx.xxh` digest.  A no-op
-/// when `dctx.skip_checksum` is `true`.
+/// when the frame's policy skips content checksums, or when best-effort
+/// decoding dropped part of the frame.
 fn verify_content_checksum(dctx: &mut Lz4FDCtx, crc4: [u8; 4]) -> Result<(), Lz4FError> {
-    if !dctx.skip_checksum {
+    dctx.stats.compressed_bytes += 4;
+    dctx.stats.checksums += 1;
+    if dctx.frame_checksum_policy.checks_content() && !dctx.frame_damaged {
         let read_crc = u32::from_le_bytes(crc4);
         let result_crc = dctx.xxh.digest();
         if read_crc != result_crc {
@@ -1117,11 +1662,7 @@ pub fn lz4f_decompress_using_dict(
 ) -> Result<(usize, usize, usize), Lz4FError> {
     if dctx.stage <= DecompressStage::Init {
         dctx.dict_bytes.clear();
-        dctx.dict_bytes.extend_from_slice(dict);
-        if dctx.dict_bytes.len() > MAX_DICT_SIZE {
-            let excess = dctx.dict_bytes.len() - MAX_DICT_SIZE;
-            dctx.dict_bytes.drain(..excess);
-        }
+        dctx.dict_bytes.extend_from_slice(effective_dict(dict));
     }
     lz4f_decompress(dctx, dst, src, opts)
 }
@@ -1133,6 +1674,7 @@ pub fn lz4f_decompress_using_dict(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::frame::header::lz4f_header_checksum;
 
     #[test]
     fn create_and_free_context() {
@@ -1150,16 +1692,32 @@ mod tests {
     #[test]
     fn reset_clears_state() {
         let mut dctx = Lz4FDCtx::new(LZ4F_VERSION);
-        dctx.skip_checksum = true;
+        dctx.frame_checksum_policy = ChecksumPolicy::SKIP;
         dctx.frame_remaining_size = 42;
         dctx.dict_bytes.extend_from_slice(b"hello");
         lz4f_reset_decompression_context(&mut dctx);
         assert_eq!(dctx.stage, DecompressStage::GetFrameHeader);
-        assert!(!dctx.skip_checksum);
+        assert_eq!(dctx.frame_checksum_policy, ChecksumPolicy::VERIFY);
         assert_eq!(dctx.frame_remaining_size, 0);
         assert!(dctx.dict_bytes.is_empty());
     }
 
+    /// Parity: an external dictionary larger than 64 KiB is cut to its
+    /// last 64 KiB, as C's pointer arithmetic does.
+    #[test]
+    fn large_external_dict_truncated_in_ctx() {
+        let large_dict = vec![0xAAu8; 128 * 1024];
+        let frame = crate::frame::compress_frame_to_vec(b"hello dict");
+        let mut dctx = Lz4FDCtx::new(LZ4F_VERSION);
+        let mut dst = vec![0u8; 64];
+        let _ = lz4f_decompress_using_dict(&mut dctx, Some(&mut dst), &frame, &large_dict, None);
+        assert!(
+            dctx.dict_bytes.len() <= MAX_DICT_SIZE,
+            "dict_bytes exceeded 64 KiB max: {}",
+            dctx.dict_bytes.len()
+        );
+    }
+
     #[test]
     fn header_size_skippable() {
         let mut buf = [0u8; 8];
@@ -11,13 +11,20 @@
 //! - Little-endian read/write helpers: [`read_le32`], [`write_le32`], [`read_le64`], [`write_le64`].
 //! - [`lz4f_compression_level_max`] — the highest valid HC compression level.
 //! - [`lz4f_get_block_size`] — byte capacity for a [`BlockSizeId`](crate::frame::types::BlockSizeId).
+//! - [`block_size_from_id`], [`max_block_size_of`], [`lz4f_decompress_bound`] — per-block
+//!   output buffer sizes for a block size ID, a [`FrameInfo`], or a raw frame header.
 //! - [`lz4f_optimal_bsid`] — smallest block size that fits a given source length.
+//! - [`lz4f_block_size_downgrade`] — whether that choice departs from the requested size.
 //! - [`lz4f_header_checksum`] — single-byte frame header integrity check.
+//! - [`HeaderParser`] — incremental frame header decoding without a decompression context.
 //! - [`lz4f_compress_bound_internal`] — worst-case compressed size for streaming callers.
 //! - [`lz4f_compress_frame_bound`] — worst-case compressed frame size for one-shot callers.
 
+use crate::block::estimate::estimate_block_size;
+use crate::frame::skippable::{is_skippable_magic, SKIPPABLE_HEADER_SIZE};
 use crate::frame::types::{
-    BlockChecksum, BlockSizeId, ContentChecksum, Preferences, BF_SIZE, BH_SIZE, MAX_FH_SIZE,
+    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, FrameInfo, FrameType, Lz4FError,
+    Preferences, BF_SIZE, BH_SIZE, MAX_FH_SIZE, MIN_FH_SIZE,
 };
 use crate::xxhash::xxh32_oneshot;
 
@@ -25,6 +32,9 @@ use crate::xxhash::xxh32_oneshot;
 // Maximum HC compression level (lz4hc.h: LZ4HC_CLEVEL_MAX)
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// LZ4 frame magic number (lz4frame.h:280).
+pub const LZ4F_MAGIC_NUMBER: u32 = 0x184D_2204;
+
 /// Maximum allowed LZ4 HC compression level.
 /// Equivalent to `LZ4HC_CLEVEL_MAX` = 12.
 pub const LZ4HC_CLEVEL_MAX: i32 = 12;
@@ -126,6 +136,41 @@ pub fn lz4f_get_block_size(block_size_id: BlockSizeId) -> Option<usize> {
     Some(BLOCK_SIZES[idx])
 }
 
+/// Returns the block byte-size for `block_size_id`, treating
+/// `BlockSizeId::Default` as `Max64Kb`.
+///
+/// Infallible form of [`lz4f_get_block_size`] for sizing buffers.
+pub fn block_size_from_id(block_size_id: BlockSizeId) -> usize {
+    lz4f_get_block_size(block_size_id).unwrap_or(64 * 1024)
+}
+
+/// Returns the largest number of bytes one block of a frame described by
+/// `info` can decode to.
+pub fn max_block_size_of(info: &FrameInfo) -> usize {
+    block_size_from_id(info.block_size_id)
+}
+
+/// Returns the output buffer size that holds any single decoded block of the
+/// frame starting with `frame_header`.
+///
+/// `frame_header` must contain the complete header (7–19 bytes; more is
+/// ignored).  A `dst` of this size lets every `lz4f_decompress` call emit a
+/// whole block directly, without staging it in the context.  Skippable frames
+/// decode to nothing and return `Ok(0)`.
+///
+/// # Errors
+/// * [`Lz4FError::FrameHeaderIncomplete`] if the header is cut short.
+/// * [`Lz4FError::FrameTypeUnknown`] on an unknown magic number.
+/// * [`Lz4FError::HeaderVersionWrong`], [`Lz4FError::MaxBlockSizeInvalid`] or
+///   [`Lz4FError::HeaderChecksumInvalid`] on a malformed header.
+pub fn lz4f_decompress_bound(frame_header: &[u8]) -> Result<usize, Lz4FError> {
+    if frame_header.len() >= 4 && is_skippable_magic(read_le32(frame_header, 0)) {
+        return Ok(0);
+    }
+    let (info, _) = parse_frame_header(frame_header, false)?;
+    Ok(block_size_from_id(info.block_size_id))
+}
+
 /// Selects the smallest `BlockSizeId` sufficient to hold `src_size` bytes,
 /// capped at `requested_bsid`.
 ///
@@ -152,6 +197,37 @@ pub fn lz4f_optimal_bsid(requested_bsid: BlockSizeId, src_size: usize) -> BlockS
     requested_bsid
 }
 
+/// A requested block size that the one-shot compressors shrank to fit the
+/// input, as reported by [`lz4f_block_size_downgrade`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSizeDowngrade {
+    /// Block size ID the preferences asked for.
+    pub requested: BlockSizeId,
+    /// Block size ID written to the frame header.
+    pub used: BlockSizeId,
+}
+
+/// Reports whether a one-shot frame of `src_size` bytes will declare a
+/// smaller block size than `requested_bsid`.
+///
+/// [`lz4f_compress_frame`](crate::frame::compress::lz4f_compress_frame) and
+/// the other one-shot paths apply [`lz4f_optimal_bsid`], so a 10 KiB input
+/// compressed with `Max4Mb` gets a header declaring 64 KiB blocks.  Tools
+/// that depend on the declared geometry (seek tables, fixed-stride readers)
+/// can call this with the same arguments to learn about it.  Returns `None`
+/// when the requested size is kept; `BlockSizeId::Default` is never
+/// downgraded.
+pub fn lz4f_block_size_downgrade(
+    requested_bsid: BlockSizeId,
+    src_size: usize,
+) -> Option<BlockSizeDowngrade> {
+    let used = lz4f_optimal_bsid(requested_bsid, src_size);
+    (used != requested_bsid).then_some(BlockSizeDowngrade {
+        requested: requested_bsid,
+        used,
+    })
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Header checksum (lz4frame.c:349–353)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -166,6 +242,230 @@ pub fn lz4f_header_checksum(header: &[u8]) -> u8 {
     ((xxh >> 8) & 0xFF) as u8
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Frame header parsing (lz4frame.c:1296–1395, LZ4F_decodeHeader)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Checks the FLG byte of an LZ4 frame header and returns the header's total
+/// length, magic number and checksum byte included.
+///
+/// With `strict`, the reserved bit must be clear, as the decoder requires by
+/// default.
+pub(crate) fn frame_header_size(flg: u8, strict: bool) -> Result<usize, Lz4FError> {
+    if strict && flg & 0x02 != 0 {
+        return Err(Lz4FError::ReservedFlagSet);
+    }
+    if flg >> 6 != 1 {
+        return Err(Lz4FError::HeaderVersionWrong);
+    }
+    Ok(MIN_FH_SIZE + if flg & 0x08 != 0 { 8 } else { 0 } + if flg & 0x01 != 0 { 4 } else { 0 })
+}
+
+/// Decodes the complete LZ4 frame header at the start of `src`, which must
+/// already hold all of it, into a [`FrameInfo`].  Returns the info and the
+/// header length.
+///
+/// Mirrors the validation of `LZ4F_decodeHeader`; skippable frames are the
+/// caller's business.
+pub(crate) fn parse_frame_header(
+    src: &[u8],
+    strict: bool,
+) -> Result<(FrameInfo, usize), Lz4FError> {
+    if src.len() < MIN_FH_SIZE {
+        return Err(Lz4FError::FrameHeaderIncomplete);
+    }
+    if read_le32(src, 0) != LZ4F_MAGIC_NUMBER {
+        return Err(Lz4FError::FrameTypeUnknown);
+    }
+    let flg = src[4];
+    let fh_size = frame_header_size(flg, strict)?;
+    if src.len() < fh_size {
+        return Err(Lz4FError::FrameHeaderIncomplete);
+    }
+
+    let bd = src[5];
+    if strict && bd & 0x80 != 0 {
+        return Err(Lz4FError::ReservedFlagSet);
+    }
+    let bsid_raw = (bd >> 4) & 0x7;
+    if bsid_raw < 4 {
+        return Err(Lz4FError::MaxBlockSizeInvalid);
+    }
+    if strict && bd & 0x0F != 0 {
+        return Err(Lz4FError::ReservedFlagSet);
+    }
+    if lz4f_header_checksum(&src[4..fh_size - 1]) != src[fh_size - 1] {
+        return Err(Lz4FError::HeaderChecksumInvalid);
+    }
+
+    let info = FrameInfo {
+        block_size_id: match bsid_raw {
+            4 => BlockSizeId::Max64Kb,
+            5 => BlockSizeId::Max256Kb,
+            6 => BlockSizeId::Max1Mb,
+            _ => BlockSizeId::Max4Mb,
+        },
+        block_mode: if flg & 0x20 != 0 {
+            BlockMode::Independent
+        } else {
+            BlockMode::Linked
+        },
+        block_checksum_flag: if flg & 0x10 != 0 {
+            BlockChecksum::Enabled
+        } else {
+            BlockChecksum::Disabled
+        },
+        content_checksum_flag: if flg & 0x04 != 0 {
+            ContentChecksum::Enabled
+        } else {
+            ContentChecksum::Disabled
+        },
+        content_size: if flg & 0x08 != 0 {
+            read_le64(src, 6)
+        } else {
+            0
+        },
+        dict_id: if flg & 0x01 != 0 {
+            read_le32(src, fh_size - 5)
+        } else {
+            0
+        },
+        frame_type: FrameType::Frame,
+    };
+    Ok((info, fh_size))
+}
+
+/// Progress reported by [`HeaderParser::feed`].
+#[derive(Debug, Clone, Copy)]
+pub enum HeaderStatus {
+    /// The header is incomplete; at least this many more bytes are needed.
+    NeedMoreBytes(usize),
+    /// The header is complete.  Carries the decoded [`FrameInfo`] and how
+    /// many bytes of the last `feed` input belonged to the header; the
+    /// frame's first block (or a skippable frame's payload) starts right
+    /// after them.
+    Done(FrameInfo, usize),
+}
+
+/// Incremental LZ4 frame header parser.
+///
+/// Feed it a stream's first bytes in pieces of any size; it buffers at most
+/// [`MAX_FH_SIZE`] bytes and asks for exactly what it still needs, so a
+/// protocol parser can classify an LZ4 stream without setting up a
+/// decompression context.  It validates the header as
+/// [`lz4f_decompress`](crate::frame::lz4f_decompress) does (magic, version,
+/// reserved bits, block size, header checksum).
+///
+/// A skippable frame completes after its 8-byte header with
+/// [`FrameType::SkippableFrame`] and, as in the reference library, the
+/// payload length in [`FrameInfo::content_size`].
+///
+/// ```
+/// use lz4::frame::{compress_frame_to_vec, HeaderParser, HeaderStatus};
+///
+/// let frame = compress_frame_to_vec(b"hello hello hello");
+/// let mut parser = HeaderParser::new();
+/// assert!(matches!(parser.feed(&frame[..3]), Ok(HeaderStatus::NeedMoreBytes(1))));
+/// match parser.feed(&frame[3..]).unwrap() {
+///     HeaderStatus::Done(_info, used) => assert_eq!(3 + used, 7),
+///     HeaderStatus::NeedMoreBytes(_) => unreachable!(),
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeaderParser {
+    buf: [u8; MAX_FH_SIZE],
+    len: usize,
+    strict: bool,
+    info: Option<FrameInfo>,
+}
+
+impl Default for HeaderParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeaderParser {
+    /// Creates a parser that rejects headers with reserved bits set.
+    pub fn new() -> Self {
+        HeaderParser {
+            buf: [0; MAX_FH_SIZE],
+            len: 0,
+            strict: true,
+            info: None,
+        }
+    }
+
+    /// Accepts headers with reserved bits set, like
+    /// [`DecompressOptions::allow_unknown_future_flags`](crate::frame::DecompressOptions::allow_unknown_future_flags).
+    pub fn allow_unknown_future_flags(mut self, yes: bool) -> Self {
+        self.strict = !yes;
+        self
+    }
+
+    /// Forgets any buffered bytes, ready for a new header.
+    pub fn reset(&mut self) {
+        self.len = 0;
+        self.info = None;
+    }
+
+    /// Consumes header bytes from `src`.
+    ///
+    /// Takes only what the header needs: once it returns
+    /// [`HeaderStatus::Done`], bytes of `src` past the reported count are
+    /// untouched, and further calls return `Done` again with a count of 0.
+    /// An error is returned as soon as the bytes seen so far rule out a valid
+    /// header, and again on later calls until [`reset`](Self::reset).
+    pub fn feed(&mut self, src: &[u8]) -> Result<HeaderStatus, Lz4FError> {
+        if let Some(info) = self.info {
+            return Ok(HeaderStatus::Done(info, 0));
+        }
+        let mut used = 0;
+        loop {
+            let target = self.target()?;
+            if self.len < target {
+                let take = (target - self.len).min(src.len() - used);
+                self.buf[self.len..self.len + take].copy_from_slice(&src[used..used + take]);
+                self.len += take;
+                used += take;
+                if self.len < target {
+                    return Ok(HeaderStatus::NeedMoreBytes(target - self.len));
+                }
+                continue;
+            }
+            let info = if is_skippable_magic(read_le32(&self.buf, 0)) {
+                FrameInfo {
+                    frame_type: FrameType::SkippableFrame,
+                    content_size: read_le32(&self.buf, 4) as u64,
+                    ..FrameInfo::default()
+                }
+            } else {
+                parse_frame_header(&self.buf[..self.len], self.strict)?.0
+            };
+            self.info = Some(info);
+            return Ok(HeaderStatus::Done(info, used));
+        }
+    }
+
+    /// How many bytes the header spans, as far as the buffered prefix tells.
+    fn target(&self) -> Result<usize, Lz4FError> {
+        if self.len < 4 {
+            return Ok(4);
+        }
+        let magic = read_le32(&self.buf, 0);
+        if is_skippable_magic(magic) {
+            return Ok(SKIPPABLE_HEADER_SIZE);
+        }
+        if magic != LZ4F_MAGIC_NUMBER {
+            return Err(Lz4FError::FrameTypeUnknown);
+        }
+        if self.len < 5 {
+            return Ok(5);
+        }
+        frame_header_size(self.buf[4], self.strict)
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Compress-bound functions (lz4frame.c:379–416)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -189,7 +489,7 @@ pub fn lz4f_compress_bound_internal(
         BlockSizeId::Default => BlockSizeId::Max64Kb,
         id => id,
     };
-    let block_size = lz4f_get_block_size(block_id).unwrap_or(64 * 1024);
+    let block_size = block_size_from_id(block_id);
 
     let max_buffered = block_size - 1;
     let buffered_size = already_buffered.min(max_buffered); // clamp to the usable buffer headroom
@@ -239,6 +539,50 @@ pub fn lz4f_compress_frame_bound(src_size: usize, prefs: Option<&Preferences>) -
     MAX_FH_SIZE + lz4f_compress_bound_internal(src_size, &local_prefs, 0)
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Size estimation (no C equivalent)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Estimates the size of the frame that one-shot compression of `src` with
+/// `prefs` would produce, without compressing anything.
+///
+/// Each block is sized with [`estimate_block_size`] and capped at its raw
+/// length, because incompressible blocks are stored uncompressed.  Header,
+/// block headers, checksums, and the end mark are counted exactly.  Blocks
+/// are estimated independently and with the fast encoder.  Linked blocks and
+/// HC levels therefore usually compress somewhat better than estimated.
+///
+/// Use it as a pre-flight check: if the estimate is not comfortably below
+/// `src.len()`, store the data raw instead.
+pub fn estimate_frame_size(src: &[u8], prefs: Option<&Preferences>) -> usize {
+    let mut p = prefs.copied().unwrap_or_default();
+    p.frame_info.block_size_id = lz4f_optimal_bsid(p.frame_info.block_size_id, src.len());
+    let block_id = match p.frame_info.block_size_id {
+        BlockSizeId::Default => BlockSizeId::Max64Kb,
+        id => id,
+    };
+    let block_size = lz4f_get_block_size(block_id).unwrap_or(64 * 1024);
+    let info = &p.frame_info;
+
+    let header =
+        7 + if info.content_size != 0 { 8 } else { 0 } + if info.dict_id != 0 { 4 } else { 0 };
+    let block_crc = if info.block_checksum_flag == BlockChecksum::Enabled {
+        BF_SIZE
+    } else {
+        0
+    };
+    let blocks: usize = src
+        .chunks(block_size)
+        .map(|block| BH_SIZE + estimate_block_size(block).min(block.len()) + block_crc)
+        .sum();
+    let content_crc = if info.content_checksum_flag == ContentChecksum::Enabled {
+        BF_SIZE
+    } else {
+        0
+    };
+    header + blocks + BH_SIZE + content_crc
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,6 +661,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn block_size_downgrade_reports_shrunk_requests() {
+        assert_eq!(
+            lz4f_block_size_downgrade(BlockSizeId::Max4Mb, 100_000),
+            Some(BlockSizeDowngrade {
+                requested: BlockSizeId::Max4Mb,
+                used: BlockSizeId::Max256Kb,
+            })
+        );
+        assert_eq!(
+            lz4f_block_size_downgrade(BlockSizeId::Max4Mb, 5 << 20),
+            None
+        );
+        assert_eq!(lz4f_block_size_downgrade(BlockSizeId::Max64Kb, 10), None);
+        assert_eq!(lz4f_block_size_downgrade(BlockSizeId::Default, 10), None);
+    }
+
     #[test]
     fn optimal_bsid_requested_limits_result() {
         // Even though src needs 256 KB, requested cap is Max64Kb → return Max64Kb
@@ -433,6 +794,23 @@ mod tests {
         let _ = internal; // suppress unused warning
     }
 
+    // ── frame_header_size ────────────────────────────────────────────────────
+
+    #[test]
+    fn frame_header_size_from_flg() {
+        assert_eq!(frame_header_size(0x40, true), Ok(MIN_FH_SIZE));
+        assert_eq!(frame_header_size(0x49, true), Ok(MAX_FH_SIZE));
+        assert_eq!(
+            frame_header_size(0x42, true),
+            Err(Lz4FError::ReservedFlagSet)
+        );
+        assert_eq!(frame_header_size(0x42, false), Ok(MIN_FH_SIZE));
+        assert_eq!(
+            frame_header_size(0x80, false),
+            Err(Lz4FError::HeaderVersionWrong)
+        );
+    }
+
     // ── lz4f_compression_level_max ───────────────────────────────────────────
 
     #[test]
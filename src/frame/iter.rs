@@ -0,0 +1,82 @@
+//! Frame-by-frame iteration over a multi-frame stream.
+//!
+//! Log shippers and rotated `.lz4` files often hold many concatenated frames.
+//! [`FrameIter`] decodes them one at a time from any [`Read`] source, so
+//! memory is bounded by the largest single frame rather than the whole
+//! stream.  Reads are sized by the decoder's input hint (see
+//! [`read_frame`](super::read_frame)), so the reader is never advanced past
+//! the frame just returned.
+
+use std::io::{self, Read};
+
+use super::types::FrameType;
+
+/// Iterator yielding the decoded contents of each frame in `reader`.
+///
+/// Skippable frames are consumed and not yielded; an empty LZ4 frame yields
+/// an empty `Vec`.  Iteration ends at end of input.  After an error (a
+/// truncated or corrupt frame, or a read failure) the iterator yields the
+/// error once and then ends.
+///
+/// ```
+/// use lz4::frame::{compress_frame_to_vec, FrameIter};
+///
+/// let mut stream = compress_frame_to_vec(b"line 1\n");
+/// stream.extend(compress_frame_to_vec(b"line 2\n"));
+/// let frames: Vec<Vec<u8>> = FrameIter::new(&stream[..])
+///     .collect::<std::io::Result<_>>()
+///     .unwrap();
+/// assert_eq!(frames, [b"line 1\n".to_vec(), b"line 2\n".to_vec()]);
+/// ```
+pub struct FrameIter<R: Read> {
+    reader: R,
+    done: bool,
+    frames: u64,
+}
+
+impl<R: Read> FrameIter<R> {
+    /// Iterates over the frames of `reader`, starting at its current position.
+    pub fn new(reader: R) -> Self {
+        FrameIter {
+            reader,
+            done: false,
+            frames: 0,
+        }
+    }
+
+    /// Number of frames yielded so far (skippable frames are not counted).
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Returns the underlying reader, positioned just after the last frame
+    /// consumed.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for FrameIter<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let mut out = Vec::new();
+            match super::decode_next_frame(&mut self.reader, &mut out) {
+                Ok(Some((FrameType::SkippableFrame, _))) => continue,
+                Ok(Some((FrameType::Frame, _))) => {
+                    self.frames += 1;
+                    return Some(Ok(out));
+                }
+                Ok(None) => self.done = true,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R: Read> std::iter::FusedIterator for FrameIter<R> {}
@@ -13,40 +13,103 @@
 //! # Submodules
 //!
 //! * [`types`]   — shared data types: [`Preferences`], [`FrameInfo`], error codes, etc.
-//! * [`header`]  — frame-header encoding/decoding and bound calculation.
+//! * [`header`]  — frame-header encoding/decoding, bound calculation, and size estimation.
 //! * [`compress`] — compression context lifecycle and streaming compress API.
 //! * [`decompress`] — decompression context lifecycle and streaming decompress API.
 //! * [`cdict`]   — compression dictionary support ([`Lz4FCDict`]).
+//! * [`safe`]    — `unsafe`-free one-shot frame codec (the `forbid-unsafe` surface).
+//! * [`skippable`] — writing and parsing skippable (user-data) frames.
+//! * [`iter`]    — [`FrameIter`], frame-by-frame decoding of a multi-frame stream.
+//! * [`append`]  — appending frames to existing `.lz4` files at a checked frame boundary.
+//! * [`wire`]    — size-prefixed raw blocks, a smaller container for RPC messages.
+//!
+//! # Without the `hc` feature
+//!
+//! The streaming encoder (`compress`, `cdict`, `append`) is built on the HC
+//! engine and is compiled out with it.  Decoding is unchanged, and
+//! [`lz4f_compress_frame`] and [`compress_frame_to_vec`] are served by the
+//! fast-only [`safe`] encoder, which maps HC levels to the fast encoder.
 //!
 //! # One-shot helpers
 //!
 //! [`compress_frame_to_vec`] and [`decompress_frame_to_vec`] are thin,
 //! allocation-owning wrappers for callers that don't need streaming control.
+//! [`decompress_frame_into`] decodes into a caller-sized slice instead and
+//! allocates nothing.
+//! [`lz4f_compress_frame_to_writer`] (or [`compress_to_writer`], which takes
+//! the writer by reference) compresses one large contiguous slice, typically
+//! a memory-mapped file, block by block into any [`std::io::Write`].
+//! [`read_frame`] goes the other way, decoding one frame from any
+//! [`std::io::Read`] with reads sized by the decoder's input hint.
+//! Inside [`with_pooled_buffers`], the decode helpers take their staging
+//! buffers from a [`BufferPool`] instead of allocating per call.
 //!
 //! [LZ4 Frame Format Specification]: https://github.com/lz4/lz4/blob/dev/doc/lz4_Frame_format.md
 
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
+pub(crate) mod advise;
+pub mod aligned;
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
+pub mod append;
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
 pub mod cdict;
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
 pub mod compress;
+#[cfg(not(feature = "forbid-unsafe"))]
 pub mod decompress;
 pub mod header;
+#[cfg(not(feature = "forbid-unsafe"))]
+pub mod iter;
+pub mod pool;
+pub mod safe;
+pub mod skippable;
 pub mod types;
+pub mod wire;
 
+pub use aligned::{AlignedBuf, BUFFER_ALIGN};
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
+pub use append::{append_frame, check_frame_boundary};
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
 pub use cdict::Lz4FCDict;
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
 pub use compress::{
-    lz4f_compress_begin, lz4f_compress_bound, lz4f_compress_end, lz4f_compress_frame,
+    compress_frame_dest_size, lz4f_compress_begin, lz4f_compress_begin_using_dict,
+    lz4f_compress_begin_with_pledged_size, lz4f_compress_bound, lz4f_compress_bound_padded,
+    lz4f_compress_end, lz4f_compress_frame, lz4f_compress_frame_to_writer,
     lz4f_compress_frame_using_cdict, lz4f_compress_update, lz4f_create_compression_context,
-    lz4f_flush, lz4f_free_compression_context, lz4f_uncompressed_update, CompressOptions,
+    lz4f_flush, lz4f_free_compression_context, lz4f_padding_size, lz4f_uncompressed_update,
+    CompressOptions, LZ4F_PADDING_NIBBLE,
 };
+#[cfg(not(feature = "forbid-unsafe"))]
 pub use decompress::{
     lz4f_create_decompression_context, lz4f_decompress, lz4f_decompress_using_dict,
     lz4f_free_decompression_context, lz4f_get_frame_info, lz4f_header_size,
-    lz4f_reset_decompression_context, DecompressOptions, Lz4FDCtx,
+    lz4f_reset_decompression_context, ChecksumMode, ChecksumPolicy, DamageReport, DamagedBlock,
+    DecodeStage, DecompressOptions, FrameStats, Lz4FDCtx,
+};
+pub use header::{
+    block_size_from_id, estimate_frame_size, lz4f_block_size_downgrade, lz4f_compress_frame_bound,
+    lz4f_decompress_bound, max_block_size_of, BlockSizeDowngrade, HeaderParser, HeaderStatus,
 };
-pub use header::lz4f_compress_frame_bound;
+#[cfg(not(feature = "forbid-unsafe"))]
+pub use iter::FrameIter;
+#[cfg(not(feature = "forbid-unsafe"))]
+pub use pool::with_pooled_buffers;
+pub use pool::{BufferPool, SizeClassPool};
+pub use safe::decompress_frame_into;
+#[cfg(feature = "forbid-unsafe")]
+pub use safe::{
+    compress_frame_to_vec, decompress_frame_to_vec, decompress_frame_to_vec_with_limit,
+    lz4f_compress_frame,
+};
+#[cfg(not(any(feature = "hc", feature = "forbid-unsafe")))]
+pub use safe::{compress_frame_to_vec, lz4f_compress_frame};
+pub use skippable::{read_skippable_frame, write_skippable_frame, SkippableFrame};
 pub use types::{
-    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, FrameInfo, FrameType, Lz4FCCtx,
-    Lz4FError, Preferences,
+    BlockChecksum, BlockDecision, BlockFilter, BlockMode, BlockSizeId, BlockSizePolicy,
+    ContentChecksum, FrameInfo, FrameType, Lz4FCCtx, Lz4FError, Preferences,
 };
+pub use wire::{decode_prefixed_block, encode_prefixed_block};
 
 // ---------------------------------------------------------------------------
 // One-shot convenience helpers
@@ -61,6 +124,7 @@ pub use types::{
 ///
 /// Returns an empty `Vec` if the underlying codec returns an error, which
 /// should not occur for valid inputs under default settings.
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
 pub fn compress_frame_to_vec(data: &[u8]) -> Vec<u8> {
     let prefs = types::Preferences::default();
     let bound = header::lz4f_compress_frame_bound(data.len(), Some(&prefs));
@@ -74,6 +138,17 @@ pub fn compress_frame_to_vec(data: &[u8]) -> Vec<u8> {
     }
 }
 
+/// [`lz4f_compress_frame_to_writer`] taking the writer by reference.
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
+#[inline]
+pub fn compress_to_writer<W: std::io::Write>(
+    writer: &mut W,
+    src: &[u8],
+    prefs: Option<&Preferences>,
+) -> std::io::Result<u64> {
+    compress::lz4f_compress_frame_to_writer(writer, src, prefs)
+}
+
 /// Decompress a complete LZ4 frame from `compressed` into a freshly-allocated
 /// `Vec<u8>`.
 ///
@@ -83,20 +158,48 @@ pub fn compress_frame_to_vec(data: &[u8]) -> Vec<u8> {
 ///
 /// For streaming or incremental decompression, use the lower-level
 /// [`lz4f_decompress`] API directly.
+#[cfg(not(feature = "forbid-unsafe"))]
 pub fn decompress_frame_to_vec(compressed: &[u8]) -> std::io::Result<Vec<u8>> {
-    let mut dctx = decompress::lz4f_create_decompression_context(types::LZ4F_VERSION)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{e:?}")))?;
+    decode_frame_to_vec(compressed, None)
+}
+
+/// Like [`decompress_frame_to_vec`], but fails once the decoded frame would
+/// exceed `max_output_size` bytes.
+///
+/// Guards against decompression bombs: a frame whose header declares a larger
+/// content size is rejected up front, and a frame that omits or understates
+/// its size is stopped as soon as its output passes the limit, so no more
+/// than `max_output_size` bytes (plus one output chunk) are ever allocated.
+/// The error carries [`Lz4FError::OutputLimitExceeded`] in its message.
+#[cfg(not(feature = "forbid-unsafe"))]
+pub fn decompress_frame_to_vec_with_limit(
+    compressed: &[u8],
+    max_output_size: u64,
+) -> std::io::Result<Vec<u8>> {
+    let opts = DecompressOptions {
+        max_output_size: Some(max_output_size),
+        ..Default::default()
+    };
+    decode_frame_to_vec(compressed, Some(&opts))
+}
+
+#[cfg(not(feature = "forbid-unsafe"))]
+fn decode_frame_to_vec(
+    compressed: &[u8],
+    opts: Option<&DecompressOptions>,
+) -> std::io::Result<Vec<u8>> {
+    let mut dctx = pooled_dctx();
     let mut out = Vec::new();
     let mut pos = 0usize;
     // 64 KiB output buffer — large enough to amortise Vec growth cost for
     // most real-world block sizes (LZ4 block size ID 4 = 64 KiB max).
-    let mut dst_buf = vec![0u8; 65536];
+    let mut dst_buf = pool::Scratch::new(65536);
     loop {
         if pos >= compressed.len() {
             break;
         }
         let (consumed, written, hint) =
-            decompress::lz4f_decompress(&mut dctx, Some(&mut dst_buf), &compressed[pos..], None)
+            decompress::lz4f_decompress(&mut dctx, Some(&mut dst_buf), &compressed[pos..], opts)
                 .map_err(|e| {
                     std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{e:?}"))
                 })?;
@@ -118,3 +221,138 @@ pub fn decompress_frame_to_vec(compressed: &[u8]) -> std::io::Result<Vec<u8>> {
     }
     Ok(out)
 }
+
+/// A decompression context drawing from the pool of [`with_pooled_buffers`],
+/// if one is installed on this thread.
+#[cfg(not(feature = "forbid-unsafe"))]
+fn pooled_dctx() -> Box<Lz4FDCtx> {
+    match pool::scoped_pool() {
+        Some(pool) => Lz4FDCtx::with_pool(types::LZ4F_VERSION, pool),
+        None => Lz4FDCtx::new(types::LZ4F_VERSION),
+    }
+}
+
+/// Decode one frame from `reader` into `sink`, reading exactly as many bytes
+/// as the decoder asks for, and return the number of decoded bytes.
+///
+/// Each read is sized by the `next_src_hint` of the previous
+/// [`lz4f_decompress`] call: the frame header, then one block (plus the next
+/// block header) at a time, then the end mark and checksum.  Blocks are
+/// decoded straight into a buffer of [`max_block_size_of`] the frame, so each
+/// block costs one read and one write.  Because no byte past the frame is
+/// read, `reader` is left at the start of whatever follows: call again to
+/// decode the next concatenated frame.  A skippable frame is consumed and
+/// decodes to nothing.
+///
+/// A reader that ends mid-frame gives [`std::io::ErrorKind::UnexpectedEof`];
+/// a corrupt frame gives [`std::io::ErrorKind::InvalidData`].
+///
+/// ```
+/// use lz4::frame::{compress_frame_to_vec, read_frame};
+///
+/// let mut stream = compress_frame_to_vec(b"first");
+/// stream.extend(compress_frame_to_vec(b"second"));
+/// let mut reader = &stream[..];
+/// let mut out = Vec::new();
+/// assert_eq!(read_frame(&mut reader, &mut out).unwrap(), 5);
+/// assert_eq!(read_frame(&mut reader, &mut out).unwrap(), 6);
+/// assert!(reader.is_empty());
+/// assert_eq!(out, b"firstsecond");
+/// ```
+#[cfg(not(feature = "forbid-unsafe"))]
+pub fn read_frame<R: std::io::Read, W: std::io::Write>(reader: R, sink: W) -> std::io::Result<u64> {
+    match decode_next_frame(reader, sink)? {
+        Some((_, total)) => Ok(total),
+        None => Err(truncated_frame()),
+    }
+}
+
+#[cfg(not(feature = "forbid-unsafe"))]
+fn truncated_frame() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "truncated LZ4 frame")
+}
+
+/// Decodes one frame as [`read_frame`] does and returns its type and decoded
+/// size, or `None` when `reader` is already at end of input.
+#[cfg(not(feature = "forbid-unsafe"))]
+pub(crate) fn decode_next_frame<R: std::io::Read, W: std::io::Write>(
+    mut reader: R,
+    mut sink: W,
+) -> std::io::Result<Option<(FrameType, u64)>> {
+    use std::io::{Error, ErrorKind};
+
+    let invalid = |e: Lz4FError| Error::new(ErrorKind::InvalidData, format!("{e:?}"));
+    let mut dctx = pooled_dctx();
+    let mut src = pool::Scratch::new(types::MAX_FH_SIZE);
+    let mut dst = pool::Scratch::new(block_size_from_id(BlockSizeId::Max64Kb));
+    let mut total: u64 = 0;
+    let mut hint = types::MIN_FH_SIZE;
+    let mut frame_type = None;
+
+    while hint != 0 {
+        // A block plus its checksum and the next block header fits in one
+        // read; larger hints (skippable payloads) are taken in pieces.
+        let want = hint.min(dst.len() + 2 * types::BH_SIZE + types::BF_SIZE);
+        if src.len() < want {
+            src.resize(want, 0);
+        }
+        let got = read_full(&mut reader, &mut src[..want])?;
+        if got < want {
+            return if frame_type.is_none() && got == 0 {
+                Ok(None)
+            } else {
+                Err(truncated_frame())
+            };
+        }
+        // The context forgets a skippable frame once it ends, so the type
+        // comes from the magic number of the first read.
+        frame_type.get_or_insert_with(|| {
+            let magic = u32::from_le_bytes([src[0], src[1], src[2], src[3]]);
+            if skippable::is_skippable_magic(magic) {
+                FrameType::SkippableFrame
+            } else {
+                FrameType::Frame
+            }
+        });
+
+        let mut pos = 0;
+        loop {
+            let (consumed, written, next) =
+                decompress::lz4f_decompress(&mut dctx, Some(&mut dst), &src[pos..want], None)
+                    .map_err(invalid)?;
+            sink.write_all(&dst[..written])?;
+            total += written as u64;
+            pos += consumed;
+            hint = next;
+            // Keep calling while input remains or the output buffer filled up
+            // (the context may still hold decoded bytes).
+            if hint == 0 || (pos == want && written < dst.len()) {
+                break;
+            }
+            if consumed == 0 && written == 0 {
+                return Err(invalid(Lz4FError::Generic));
+            }
+        }
+
+        let block_max = max_block_size_of(&dctx.frame_info);
+        if dst.len() < block_max {
+            dst.resize(block_max, 0);
+        }
+    }
+    Ok(frame_type.map(|t| (t, total)))
+}
+
+/// Reads until `buf` is full or the reader is exhausted; returns the count.
+#[cfg(not(feature = "forbid-unsafe"))]
+fn read_full<R: std::io::Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
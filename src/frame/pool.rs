@@ -0,0 +1,214 @@
+//! Recycling of the frame contexts' staging buffers.
+//!
+//! A compression or decompression context allocates block-sized staging
+//! buffers — up to 4 MiB plus history — when a frame starts, and frees them
+//! when it is dropped.  A service creating contexts per request can instead
+//! hand them a shared [`BufferPool`] through
+//! [`Lz4FCCtx::with_pool`](crate::frame::Lz4FCCtx::with_pool) or
+//! [`Lz4FDCtx::with_pool`](crate::frame::Lz4FDCtx::with_pool): buffers are
+//! then taken from the pool when a context needs a larger one and given back
+//! when it is replaced or the context is dropped.
+//!
+//! [`SizeClassPool`] is a ready-made pool keyed by capacity, in classes an
+//! eighth of a power of two apart.
+//!
+//! The one-shot decode helpers create their contexts internally; wrap calls
+//! in [`with_pooled_buffers`] to have them draw both the context buffers and
+//! their own output scratch from a pool.  The `forbid-unsafe` decoder does
+//! not use staging buffers, so that build has no `with_pooled_buffers`.
+
+#[cfg(not(feature = "forbid-unsafe"))]
+use core::ops::{Deref, DerefMut};
+#[cfg(not(feature = "forbid-unsafe"))]
+use std::cell::RefCell;
+#[cfg(not(feature = "forbid-unsafe"))]
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/// A source of reusable byte buffers for frame contexts.
+///
+/// Buffers move as plain `Vec<u8>`s.  A context only relies on capacity:
+/// it resizes what [`get`](Self::get) returns to the length it needs, so a
+/// pool that returns a `Vec` with `capacity() >= size` saves an allocation,
+/// and one that returns anything smaller costs one.  Contents are never
+/// read before being overwritten.
+pub trait BufferPool: Send + Sync {
+    /// A buffer to hold `size` bytes.
+    fn get(&self, size: usize) -> Vec<u8>;
+
+    /// Take back a buffer a context no longer uses.
+    fn put(&self, buf: Vec<u8>);
+}
+
+/// A [`BufferPool`] that keeps idle buffers in size classes.
+///
+/// Each power-of-two range is split into eight classes, so a class capacity
+/// is at most 12.5% above the request: a 4 MiB block plus history gets a
+/// 4.5 MiB buffer, not 8 MiB.  A request is served from its own class or,
+/// failing that, from a larger idle buffer up to twice its size; a miss
+/// allocates the class capacity so that the buffer can serve the same
+/// request later.  At most `max_per_class` idle buffers are kept per class;
+/// extra ones are freed.
+#[derive(Debug)]
+pub struct SizeClassPool {
+    classes: Mutex<Vec<Vec<Vec<u8>>>>,
+    max_per_class: usize,
+}
+
+/// Classes per power of two, as a shift.
+const SUBCLASS_BITS: u32 = 3;
+
+/// `size` rounded up to a class capacity.
+fn class_capacity(size: usize) -> usize {
+    let size = size.max(1);
+    let step = 1usize << size.ilog2().saturating_sub(SUBCLASS_BITS);
+    size.div_ceil(step) * step
+}
+
+/// The class index of `capacity` rounded down to a class capacity.
+fn class_index(capacity: usize) -> usize {
+    let log = capacity.ilog2();
+    let shift = log.saturating_sub(SUBCLASS_BITS);
+    let sub = (capacity >> shift) - (1 << (log - shift));
+    ((log as usize) << SUBCLASS_BITS) + sub
+}
+
+impl SizeClassPool {
+    /// An empty pool keeping up to `max_per_class` idle buffers per class.
+    pub fn new(max_per_class: usize) -> Self {
+        SizeClassPool {
+            classes: Mutex::new(vec![Vec::new(); (usize::BITS as usize) << SUBCLASS_BITS]),
+            max_per_class,
+        }
+    }
+
+    /// Number of idle buffers held.
+    pub fn idle(&self) -> usize {
+        self.lock().iter().map(Vec::len).sum()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Vec<Vec<u8>>>> {
+        // A panic while holding the lock leaves the lists intact.
+        self.classes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl BufferPool for SizeClassPool {
+    fn get(&self, size: usize) -> Vec<u8> {
+        let capacity = class_capacity(size);
+        let class = class_index(capacity);
+        // The classes up to twice the request: one power of two's worth.
+        let mut classes = self.lock();
+        let last = (class + (1 << SUBCLASS_BITS)).min(classes.len() - 1);
+        (class..=last)
+            .find_map(|c| classes[c].pop())
+            .unwrap_or_else(|| Vec::with_capacity(capacity))
+    }
+
+    fn put(&self, mut buf: Vec<u8>) {
+        if buf.capacity() == 0 {
+            return;
+        }
+        // Filed under the largest class it can fully serve.
+        let class = class_index(buf.capacity());
+        let mut classes = self.lock();
+        if classes[class].len() < self.max_per_class {
+            buf.clear();
+            classes[class].push(buf);
+        }
+    }
+}
+
+#[cfg(not(feature = "forbid-unsafe"))]
+thread_local! {
+    static SCOPED: RefCell<Option<Arc<dyn BufferPool>>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with `pool` serving the staging buffers of the one-shot decode
+/// helpers ([`decompress_frame_to_vec`](crate::frame::decompress_frame_to_vec),
+/// [`decompress_frame_to_vec_with_limit`](crate::frame::decompress_frame_to_vec_with_limit),
+/// [`read_frame`](crate::frame::read_frame) and [`FrameIter`](crate::frame::FrameIter))
+/// called on this thread.
+///
+/// The pool applies to the current thread only, and calls nest: the previous
+/// pool, if any, is restored when `f` returns or unwinds.  Each server worker
+/// can wrap its request handling with a shared pool, so concurrent decodes
+/// reuse 64 KiB+ buffers instead of allocating them per call.
+///
+/// ```
+/// use std::sync::Arc;
+/// use lz4::frame::{compress_frame_to_vec, decompress_frame_to_vec, with_pooled_buffers};
+/// use lz4::frame::SizeClassPool;
+///
+/// let pool = Arc::new(SizeClassPool::new(8));
+/// let frame = compress_frame_to_vec(b"request body");
+/// let body = with_pooled_buffers(pool.clone(), || decompress_frame_to_vec(&frame)).unwrap();
+/// assert_eq!(body, b"request body");
+/// assert!(pool.idle() > 0);
+/// ```
+#[cfg(not(feature = "forbid-unsafe"))]
+pub fn with_pooled_buffers<R>(pool: Arc<dyn BufferPool>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Arc<dyn BufferPool>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            SCOPED.with(|scoped| *scoped.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(SCOPED.with(|scoped| scoped.borrow_mut().replace(pool)));
+    f()
+}
+
+/// The pool installed by [`with_pooled_buffers`] on this thread, if any.
+#[cfg(not(feature = "forbid-unsafe"))]
+pub(crate) fn scoped_pool() -> Option<Arc<dyn BufferPool>> {
+    SCOPED.with(|scoped| scoped.borrow().clone())
+}
+
+/// A zeroed scratch `Vec` taken from the [scoped pool](scoped_pool), when one
+/// is installed, and given back to it on drop.
+#[cfg(not(feature = "forbid-unsafe"))]
+pub(crate) struct Scratch {
+    buf: Vec<u8>,
+    pool: Option<Arc<dyn BufferPool>>,
+}
+
+#[cfg(not(feature = "forbid-unsafe"))]
+impl Scratch {
+    pub(crate) fn new(len: usize) -> Self {
+        let pool = scoped_pool();
+        let mut buf = match &pool {
+            Some(pool) => pool.get(len),
+            None => Vec::new(),
+        };
+        buf.clear();
+        buf.resize(len, 0);
+        Scratch { buf, pool }
+    }
+}
+
+#[cfg(not(feature = "forbid-unsafe"))]
+impl Deref for Scratch {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.buf
+    }
+}
+
+#[cfg(not(feature = "forbid-unsafe"))]
+impl DerefMut for Scratch {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.buf
+    }
+}
+
+#[cfg(not(feature = "forbid-unsafe"))]
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        if let Some(pool) = &self.pool {
+            pool.put(core::mem::take(&mut self.buf));
+        }
+    }
+}
@@ -0,0 +1,492 @@
+//! Safe-Rust one-shot LZ4 frame encoder and decoder.
+//!
+//! Builds on the [`block::safe`](crate::block::safe) codec to provide the
+//! one-shot frame surface — [`lz4f_compress_frame`],
+//! [`compress_frame_to_vec`] and [`decompress_frame_to_vec`] — without any
+//! `unsafe` code.  When the crate is built with `forbid-unsafe`, these are the
+//! functions exported from [`crate::frame`]; otherwise they are available here
+//! for cross-checking.  [`decompress_frame_into`], which decodes into a
+//! caller-owned slice, is exported from [`crate::frame`] in both builds.
+//!
+//! Differences from the default build:
+//!
+//! * Compression levels ≥ 3 (HC) are not available; they fall back to the fast
+//!   encoder at acceleration 1.  Negative levels map to acceleration `-level`.
+//! * Frames that declare a dictionary ID can only be decoded if they never
+//!   reference the dictionary.
+
+use std::io;
+
+use super::header::{
+    lz4f_compress_frame_bound, lz4f_get_block_size, lz4f_header_checksum, lz4f_optimal_bsid,
+    LZ4F_MAGIC_NUMBER,
+};
+use super::skippable::is_skippable_magic;
+use super::types::{
+    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, Lz4FError, Preferences, BF_SIZE,
+    BH_SIZE, LZ4F_BLOCKUNCOMPRESSED_FLAG,
+};
+use crate::block::safe::{compress_bound, compress_with_history, decode_block};
+use crate::xxhash::{xxh32_oneshot, Xxh32State};
+
+const KB64: usize = 64 * 1024;
+
+fn put_le32(dst: &mut [u8], pos: usize, v: u32) {
+    dst[pos..pos + 4].copy_from_slice(&v.to_le_bytes());
+}
+
+fn get_le32(src: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([src[pos], src[pos + 1], src[pos + 2], src[pos + 3]])
+}
+
+/// Compress `src` into a complete LZ4 frame in one call.
+///
+/// Applies the same preference fix-ups as the default build (content size,
+/// optimal block size, independent blocks for single-block inputs) and
+/// requires `dst` to hold [`lz4f_compress_frame_bound`] bytes.
+pub fn lz4f_compress_frame(
+    dst: &mut [u8],
+    src: &[u8],
+    prefs: Option<&Preferences>,
+) -> Result<usize, Lz4FError> {
+    let mut p = prefs.copied().unwrap_or_default();
+    if p.frame_info.content_size != 0 {
+        p.frame_info.content_size = src.len() as u64;
+    }
+    p.frame_info.block_size_id = lz4f_optimal_bsid(p.frame_info.block_size_id, src.len());
+    p.auto_flush = true;
+    let bsid = match p.frame_info.block_size_id {
+        BlockSizeId::Default => BlockSizeId::Max64Kb,
+        id => id,
+    };
+    let block_size = lz4f_get_block_size(bsid).unwrap_or(KB64);
+    if src.len() <= block_size {
+        p.frame_info.block_mode = BlockMode::Independent;
+    }
+    if dst.len() < lz4f_compress_frame_bound(src.len(), Some(&p)) {
+        return Err(Lz4FError::DstMaxSizeTooSmall);
+    }
+    let acceleration = if p.compression_level < 0 {
+        -p.compression_level
+    } else {
+        1
+    };
+    let window = match p.max_distance {
+        0 => KB64 - 1,
+        d if d as usize >= KB64 => return Err(Lz4FError::ParameterInvalid),
+        d => d as usize,
+    };
+    let info = &p.frame_info;
+
+    // ── Header ────────────────────────────────────────────────────────────
+    put_le32(dst, 0, LZ4F_MAGIC_NUMBER);
+    let flg = (1u8 << 6)
+        | (((info.block_mode == BlockMode::Independent) as u8) << 5)
+        | (((info.block_checksum_flag == BlockChecksum::Enabled) as u8) << 4)
+        | (((info.content_size != 0) as u8) << 3)
+        | (((info.content_checksum_flag == ContentChecksum::Enabled) as u8) << 2)
+        | ((info.dict_id != 0) as u8);
+    dst[4] = flg;
+    dst[5] = ((bsid as u8) & 0x07) << 4;
+    let mut pos = 6;
+    if info.content_size != 0 {
+        dst[pos..pos + 8].copy_from_slice(&info.content_size.to_le_bytes());
+        pos += 8;
+    }
+    if info.dict_id != 0 {
+        put_le32(dst, pos, info.dict_id);
+        pos += 4;
+    }
+    dst[pos] = lz4f_header_checksum(&dst[4..pos]);
+    pos += 1;
+
+    // ── Blocks ────────────────────────────────────────────────────────────
+    let linked = info.block_mode == BlockMode::Linked;
+    let block_crc = info.block_checksum_flag == BlockChecksum::Enabled;
+    let mut scratch = vec![0u8; compress_bound(block_size as i32) as usize];
+    let mut start = 0;
+    while start < src.len() {
+        let end = (start + block_size).min(src.len());
+        let hist = if linked {
+            start.saturating_sub(window)
+        } else {
+            start
+        };
+        let c = compress_with_history(
+            &src[hist..end],
+            start - hist,
+            &mut scratch,
+            acceleration,
+            window,
+        )
+        .map_err(|_| Lz4FError::Generic)?;
+        let raw = &src[start..end];
+        let (body, header) = if c < raw.len() {
+            (&scratch[..c], c as u32)
+        } else {
+            (raw, raw.len() as u32 | LZ4F_BLOCKUNCOMPRESSED_FLAG)
+        };
+        put_le32(dst, pos, header);
+        pos += BH_SIZE;
+        dst[pos..pos + body.len()].copy_from_slice(body);
+        pos += body.len();
+        if block_crc {
+            put_le32(dst, pos, xxh32_oneshot(body, 0));
+            pos += BF_SIZE;
+        }
+        start = end;
+    }
+
+    // ── End mark and content checksum ─────────────────────────────────────
+    put_le32(dst, pos, 0);
+    pos += BH_SIZE;
+    if info.content_checksum_flag == ContentChecksum::Enabled {
+        put_le32(dst, pos, xxh32_oneshot(src, 0));
+        pos += BF_SIZE;
+    }
+    Ok(pos)
+}
+
+/// Compress `data` as a single, complete LZ4 frame with default
+/// [`Preferences`] and return it as a freshly-allocated `Vec<u8>`.
+pub fn compress_frame_to_vec(data: &[u8]) -> Vec<u8> {
+    let prefs = Preferences::default();
+    let mut out = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(&prefs))];
+    match lz4f_compress_frame(&mut out, data, Some(&prefs)) {
+        Ok(n) => {
+            out.truncate(n);
+            out
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+fn invalid(e: Lz4FError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{e:?}"))
+}
+
+/// Decompress the first frame in `compressed` into a freshly-allocated `Vec<u8>`.
+///
+/// Mirrors the default build: a leading skippable frame yields an empty
+/// result, bytes after the first frame are ignored, and a truncated frame
+/// yields the output of its complete blocks.
+pub fn decompress_frame_to_vec(compressed: &[u8]) -> io::Result<Vec<u8>> {
+    decode_frame(compressed, None)
+}
+
+/// Like [`decompress_frame_to_vec`], but fails with
+/// [`Lz4FError::OutputLimitExceeded`] once the frame would decode to more than
+/// `max_output_size` bytes.
+pub fn decompress_frame_to_vec_with_limit(
+    compressed: &[u8],
+    max_output_size: u64,
+) -> io::Result<Vec<u8>> {
+    decode_frame(compressed, Some(max_output_size))
+}
+
+/// Decompresses the first frame in `src` into `dst` and returns the decoded
+/// size, without allocating.
+///
+/// Blocks are decoded straight into `dst`, and linked blocks find their
+/// history there, so nothing is staged or copied.  A frame whose header
+/// declares more than `dst.len()` bytes is refused before any block is
+/// decoded; a frame without a content size is stopped at the block that
+/// would overflow `dst`.  As with [`decompress_frame_to_vec`], a leading
+/// skippable frame decodes to nothing and bytes after the first frame are
+/// ignored, but a truncated frame is an error here.
+///
+/// # Errors
+///
+/// * [`Lz4FError::SrcSizeTooLarge`] if the frame decodes to more than
+///   `dst.len()` bytes.
+/// * [`Lz4FError::FrameHeaderIncomplete`] if `src` ends inside the header.
+/// * [`Lz4FError::DecompressionFailed`] if `src` ends inside a block or the
+///   trailer, or a block is malformed.
+/// * The header and checksum errors of [`decompress_frame_to_vec`].
+///
+/// ```
+/// use lz4::frame::{compress_frame_to_vec, decompress_frame_into};
+///
+/// let frame = compress_frame_to_vec(b"reused buffer, reused buffer");
+/// let mut buf = [0u8; 64];
+/// let n = decompress_frame_into(&mut buf, &frame).unwrap();
+/// assert_eq!(&buf[..n], b"reused buffer, reused buffer");
+/// ```
+pub fn decompress_frame_into(dst: &mut [u8], src: &[u8]) -> Result<usize, Lz4FError> {
+    if src.len() < 4 {
+        return Err(Lz4FError::FrameHeaderIncomplete);
+    }
+    let magic = get_le32(src, 0);
+    if is_skippable_magic(magic) {
+        return Ok(0);
+    }
+    if magic != LZ4F_MAGIC_NUMBER {
+        return Err(Lz4FError::FrameTypeUnknown);
+    }
+    let h = parse_header(src)?.ok_or(Lz4FError::FrameHeaderIncomplete)?;
+    if h.content_size.is_some_and(|size| size > dst.len() as u64) {
+        return Err(Lz4FError::SrcSizeTooLarge);
+    }
+
+    let truncated = Lz4FError::DecompressionFailed;
+    let mut op = 0usize;
+    let mut xxh = Xxh32State::new(0);
+    let mut pos = h.len;
+    loop {
+        if pos + BH_SIZE > src.len() {
+            return Err(truncated);
+        }
+        let header = get_le32(src, pos);
+        pos += BH_SIZE;
+        if header == 0 {
+            break;
+        }
+        let len = (header & !LZ4F_BLOCKUNCOMPRESSED_FLAG) as usize;
+        if len > h.block_max {
+            return Err(Lz4FError::MaxBlockSizeInvalid);
+        }
+        let crc_len = if h.block_crc { BF_SIZE } else { 0 };
+        if pos + len + crc_len > src.len() {
+            return Err(truncated);
+        }
+        let body = &src[pos..pos + len];
+        pos += len;
+        if h.block_crc {
+            if xxh32_oneshot(body, 0) != get_le32(src, pos) {
+                return Err(Lz4FError::BlockChecksumInvalid);
+            }
+            pos += BF_SIZE;
+        }
+        let room = dst.len() - op;
+        let n = if header & LZ4F_BLOCKUNCOMPRESSED_FLAG != 0 {
+            if len > room {
+                return Err(Lz4FError::SrcSizeTooLarge);
+            }
+            dst[op..op + len].copy_from_slice(body);
+            len
+        } else {
+            let hist = if h.independent {
+                op
+            } else {
+                op.saturating_sub(KB64)
+            };
+            let limit = h.block_max.min(room);
+            match decode_block(body, &mut dst[hist..], op - hist, &[], limit, false) {
+                Ok(n) => n,
+                // Short of a full block of room, a block that fills what is
+                // left overflowed `dst` rather than being malformed.
+                Err(_)
+                    if room < h.block_max
+                        && decode_block(body, &mut dst[hist..], op - hist, &[], room, true)
+                            == Ok(room) =>
+                {
+                    return Err(Lz4FError::SrcSizeTooLarge)
+                }
+                Err(_) => return Err(Lz4FError::DecompressionFailed),
+            }
+        };
+        if h.content_crc {
+            xxh.update(&dst[op..op + n]);
+        }
+        op += n;
+    }
+
+    if h.content_size.is_some_and(|size| size != op as u64) {
+        return Err(Lz4FError::FrameSizeWrong);
+    }
+    if h.content_crc {
+        if pos + BF_SIZE > src.len() {
+            return Err(truncated);
+        }
+        if xxh.digest() != get_le32(src, pos) {
+            return Err(Lz4FError::ContentChecksumInvalid);
+        }
+    }
+    Ok(op)
+}
+
+/// Frame descriptor fields needed to walk the blocks.
+struct Header {
+    independent: bool,
+    block_crc: bool,
+    content_crc: bool,
+    content_size: Option<u64>,
+    block_max: usize,
+    /// Header length, magic number included.
+    len: usize,
+}
+
+/// Parses the header of the LZ4 frame at the start of `src`, whose magic
+/// number has been checked.  Returns `Ok(None)` if `src` ends inside it.
+fn parse_header(src: &[u8]) -> Result<Option<Header>, Lz4FError> {
+    if src.len() < 7 {
+        return Ok(None);
+    }
+    let flg = src[4];
+    let bd = src[5];
+    if flg >> 6 != 1 {
+        return Err(Lz4FError::HeaderVersionWrong);
+    }
+    if flg & 0x02 != 0 || bd & 0x8F != 0 {
+        return Err(Lz4FError::ReservedFlagSet);
+    }
+    let has_size = flg & 0x08 != 0;
+    let has_dict_id = flg & 0x01 != 0;
+    let len = 7 + if has_size { 8 } else { 0 } + if has_dict_id { 4 } else { 0 };
+    if src.len() < len {
+        return Ok(None);
+    }
+    let block_max = match bd >> 4 {
+        4 => lz4f_get_block_size(BlockSizeId::Max64Kb),
+        5 => lz4f_get_block_size(BlockSizeId::Max256Kb),
+        6 => lz4f_get_block_size(BlockSizeId::Max1Mb),
+        7 => lz4f_get_block_size(BlockSizeId::Max4Mb),
+        _ => None,
+    }
+    .ok_or(Lz4FError::MaxBlockSizeInvalid)?;
+    if lz4f_header_checksum(&src[4..len - 1]) != src[len - 1] {
+        return Err(Lz4FError::HeaderChecksumInvalid);
+    }
+    Ok(Some(Header {
+        independent: flg & 0x20 != 0,
+        block_crc: flg & 0x10 != 0,
+        content_crc: flg & 0x04 != 0,
+        content_size: has_size.then(|| u64::from_le_bytes(src[6..14].try_into().unwrap())),
+        block_max,
+        len,
+    }))
+}
+
+fn decode_frame(compressed: &[u8], limit: Option<u64>) -> io::Result<Vec<u8>> {
+    let src = compressed;
+    if src.len() < 4 {
+        return Ok(Vec::new());
+    }
+    let magic = get_le32(src, 0);
+    if is_skippable_magic(magic) {
+        return Ok(Vec::new());
+    }
+    if magic != LZ4F_MAGIC_NUMBER {
+        return Err(invalid(Lz4FError::FrameTypeUnknown));
+    }
+    let Some(Header {
+        independent,
+        block_crc,
+        content_crc,
+        content_size,
+        block_max,
+        len: header_len,
+    }) = parse_header(src).map_err(invalid)?
+    else {
+        return Ok(Vec::new());
+    };
+    if let (Some(size), Some(limit)) = (content_size, limit) {
+        if size > limit {
+            return Err(invalid(Lz4FError::OutputLimitExceeded));
+        }
+    }
+
+    // ── Blocks ────────────────────────────────────────────────────────────
+    let mut out: Vec<u8> = Vec::new();
+    let mut xxh = Xxh32State::new(0);
+    let mut pos = header_len;
+    loop {
+        if pos + BH_SIZE > src.len() {
+            return Ok(out);
+        }
+        let header = get_le32(src, pos);
+        pos += BH_SIZE;
+        if header == 0 {
+            break;
+        }
+        let len = (header & !LZ4F_BLOCKUNCOMPRESSED_FLAG) as usize;
+        if len > block_max {
+            return Err(invalid(Lz4FError::MaxBlockSizeInvalid));
+        }
+        let crc_len = if block_crc { BF_SIZE } else { 0 };
+        if pos + len + crc_len > src.len() {
+            return Ok(out);
+        }
+        let body = &src[pos..pos + len];
+        pos += len;
+        if block_crc {
+            if xxh32_oneshot(body, 0) != get_le32(src, pos) {
+                return Err(invalid(Lz4FError::BlockChecksumInvalid));
+            }
+            pos += BF_SIZE;
+        }
+        let block_start = out.len();
+        if header & LZ4F_BLOCKUNCOMPRESSED_FLAG != 0 {
+            out.extend_from_slice(body);
+        } else {
+            out.resize(block_start + block_max, 0);
+            let n = if independent {
+                decode_block(body, &mut out[block_start..], 0, &[], block_max, false)
+            } else {
+                let hist = block_start.saturating_sub(KB64);
+                decode_block(
+                    body,
+                    &mut out[hist..],
+                    block_start - hist,
+                    &[],
+                    block_max,
+                    false,
+                )
+            }
+            .map_err(|_| invalid(Lz4FError::DecompressionFailed))?;
+            out.truncate(block_start + n);
+        }
+        if limit.is_some_and(|limit| out.len() as u64 > limit) {
+            return Err(invalid(Lz4FError::OutputLimitExceeded));
+        }
+        if content_crc {
+            xxh.update(&out[block_start..]);
+        }
+    }
+
+    // ── Trailer ───────────────────────────────────────────────────────────
+    if let Some(size) = content_size {
+        if size != out.len() as u64 {
+            return Err(invalid(Lz4FError::FrameSizeWrong));
+        }
+    }
+    if content_crc {
+        if pos + BF_SIZE > src.len() {
+            return Ok(out);
+        }
+        if xxh.digest() != get_le32(src, pos) {
+            return Err(invalid(Lz4FError::ContentChecksumInvalid));
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_multi_block_linked() {
+        let data: Vec<u8> = (0..300_000u32)
+            .map(|i| (i % 251) as u8 ^ (i >> 12) as u8)
+            .collect();
+        let mut prefs = Preferences::default();
+        prefs.frame_info.block_size_id = BlockSizeId::Max64Kb;
+        prefs.frame_info.content_checksum_flag = ContentChecksum::Enabled;
+        prefs.frame_info.block_checksum_flag = BlockChecksum::Enabled;
+        let mut dst = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(&prefs))];
+        let n = lz4f_compress_frame(&mut dst, &data, Some(&prefs)).unwrap();
+        assert_eq!(decompress_frame_to_vec(&dst[..n]).unwrap(), data);
+    }
+
+    #[test]
+    fn corrupt_content_checksum_detected() {
+        let mut prefs = Preferences::default();
+        prefs.frame_info.content_checksum_flag = ContentChecksum::Enabled;
+        let data = b"checksummed payload ".repeat(10);
+        let mut dst = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(&prefs))];
+        let n = lz4f_compress_frame(&mut dst, &data, Some(&prefs)).unwrap();
+        dst[n - 1] ^= 0xFF;
+        assert!(decompress_frame_to_vec(&dst[..n]).is_err());
+    }
+}
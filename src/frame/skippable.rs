@@ -0,0 +1,133 @@
+//! Skippable frames — authoring and parsing.
+//!
+//! A skippable frame is a standard-compliant container for application data
+//! (indexes, signatures, metadata) that LZ4 decoders ignore.  Its layout is:
+//!
+//! ```text
+//! | magic (4, LE) | payload size (4, LE) | payload (size bytes) |
+//! ```
+//!
+//! where the magic is `0x184D2A50 | user_magic_nibble` (`0x184D2A50`–`0x184D2A5F`).
+//! Skippable frames can be concatenated before, between, or after LZ4 frames;
+//! both [`lz4f_decompress`](super::lz4f_decompress) and `lz4 -d` skip them,
+//! and `lz4 --list` reports them.
+
+use super::types::Lz4FError;
+
+/// First magic number of the skippable range (`user_magic_nibble == 0`).
+pub const LZ4F_MAGIC_SKIPPABLE_START: u32 = 0x184D_2A50;
+
+/// Size of the skippable-frame header: magic number plus payload size.
+pub const SKIPPABLE_HEADER_SIZE: usize = 8;
+
+/// A skippable frame borrowed from an input buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SkippableFrame<'a> {
+    /// Low nibble of the magic number (0–15), free for applications to use as a tag.
+    pub user_magic_nibble: u8,
+    /// The frame's payload.
+    pub payload: &'a [u8],
+}
+
+impl SkippableFrame<'_> {
+    /// Total encoded size of this frame, header included.
+    pub fn frame_size(&self) -> usize {
+        SKIPPABLE_HEADER_SIZE + self.payload.len()
+    }
+}
+
+/// Returns `true` if `magic` is in the skippable range `0x184D2A50`–`0x184D2A5F`.
+#[inline]
+pub fn is_skippable_magic(magic: u32) -> bool {
+    (magic & 0xFFFF_FFF0) == LZ4F_MAGIC_SKIPPABLE_START
+}
+
+/// Number of bytes [`write_skippable_frame`] needs for a payload of `payload_len` bytes.
+#[inline]
+pub fn skippable_frame_bound(payload_len: usize) -> usize {
+    SKIPPABLE_HEADER_SIZE + payload_len
+}
+
+/// Writes a skippable frame carrying `payload` into `dst` and returns the
+/// number of bytes written.
+///
+/// # Errors
+///
+/// * [`Lz4FError::ParameterInvalid`] if `user_magic_nibble > 15`.
+/// * [`Lz4FError::SrcSizeTooLarge`] if `payload` is longer than `u32::MAX` bytes.
+/// * [`Lz4FError::DstMaxSizeTooSmall`] if `dst` is shorter than
+///   [`skippable_frame_bound`]`(payload.len())`.
+pub fn write_skippable_frame(
+    dst: &mut [u8],
+    user_magic_nibble: u8,
+    payload: &[u8],
+) -> Result<usize, Lz4FError> {
+    if user_magic_nibble > 0x0F {
+        return Err(Lz4FError::ParameterInvalid);
+    }
+    let size = u32::try_from(payload.len()).map_err(|_| Lz4FError::SrcSizeTooLarge)?;
+    let total = skippable_frame_bound(payload.len());
+    if dst.len() < total {
+        return Err(Lz4FError::DstMaxSizeTooSmall);
+    }
+    let magic = LZ4F_MAGIC_SKIPPABLE_START | user_magic_nibble as u32;
+    dst[0..4].copy_from_slice(&magic.to_le_bytes());
+    dst[4..8].copy_from_slice(&size.to_le_bytes());
+    dst[SKIPPABLE_HEADER_SIZE..total].copy_from_slice(payload);
+    Ok(total)
+}
+
+/// Parses the skippable frame at the start of `src`.
+///
+/// Bytes after the frame are ignored; use [`SkippableFrame::frame_size`] to
+/// advance to the next frame.
+///
+/// # Errors
+///
+/// * [`Lz4FError::FrameHeaderIncomplete`] if `src` is shorter than 8 bytes.
+/// * [`Lz4FError::FrameTypeUnknown`] if the magic number is not in the skippable range.
+/// * [`Lz4FError::FrameSizeWrong`] if the declared payload extends past the end of `src`.
+pub fn read_skippable_frame(src: &[u8]) -> Result<SkippableFrame<'_>, Lz4FError> {
+    if src.len() < SKIPPABLE_HEADER_SIZE {
+        return Err(Lz4FError::FrameHeaderIncomplete);
+    }
+    let magic = u32::from_le_bytes([src[0], src[1], src[2], src[3]]);
+    if !is_skippable_magic(magic) {
+        return Err(Lz4FError::FrameTypeUnknown);
+    }
+    let size = u32::from_le_bytes([src[4], src[5], src[6], src[7]]) as usize;
+    let end = SKIPPABLE_HEADER_SIZE
+        .checked_add(size)
+        .ok_or(Lz4FError::FrameSizeWrong)?;
+    let payload = src
+        .get(SKIPPABLE_HEADER_SIZE..end)
+        .ok_or(Lz4FError::FrameSizeWrong)?;
+    Ok(SkippableFrame {
+        user_magic_nibble: (magic & 0x0F) as u8,
+        payload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_layout() {
+        let mut buf = [0u8; 11];
+        let n = write_skippable_frame(&mut buf, 0x0A, b"abc").unwrap();
+        assert_eq!(n, 11);
+        assert_eq!(&buf[..4], &0x184D_2A5Au32.to_le_bytes());
+        assert_eq!(&buf[4..8], &3u32.to_le_bytes());
+        assert_eq!(&buf[8..], b"abc");
+    }
+
+    #[test]
+    fn rejects_bad_nibble() {
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            write_skippable_frame(&mut buf, 16, b""),
+            Err(Lz4FError::ParameterInvalid)
+        );
+    }
+}
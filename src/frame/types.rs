@@ -8,14 +8,20 @@
 //! Covers:
 //! - Frame format constants ([`LZ4F_BLOCKUNCOMPRESSED_FLAG`], [`BH_SIZE`], [`BF_SIZE`], …)
 //! - Frame parameter enums: [`BlockSizeId`], [`BlockMode`], [`ContentChecksum`], …
+//! - [`BlockSizePolicy`] — fixed or automatic block size selection
 //! - [`FrameInfo`] / [`Preferences`] — user-facing frame header configuration
 //! - Internal enums: [`BlockCompressMode`], [`CtxType`]
+//! - [`BlockDecision`] / [`BlockFilter`] — per-block hook on compression
 //! - [`Lz4FCCtx`] — streaming compression context state
-//! - [`DecompressStage`] — decompression state-machine stages
+//! - `DecompressStage` — the decoder's internal state-machine stages
 //! - [`Lz4FError`] — error code enum with `Display` and `Error` impls
 
+use crate::frame::aligned::AlignedBuf;
+use crate::frame::header::lz4f_optimal_bsid;
+use crate::frame::pool::BufferPool;
 use crate::xxhash::Xxh32State;
 use core::fmt;
+use std::sync::Arc;
 
 // ─────────────────────────────────────────────────────────────────────────────
 // API version (lz4frame.h:256)
@@ -109,6 +115,45 @@ pub enum FrameType {
     SkippableFrame = 1,
 }
 
+/// How the block size of a new frame is chosen.
+///
+/// `Fixed` always uses the given ID.  `Auto` picks, per frame, the smallest
+/// block size that holds the whole input when its size is known, up to 4 MB;
+/// when the input size is unknown it uses 4 MB.  This is the block size the
+/// reference `lz4` CLI chooses, so `Auto` output matches it at every level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockSizePolicy {
+    /// Choose from the input size.
+    #[default]
+    Auto,
+    /// Always use this block size ID.
+    Fixed(BlockSizeId),
+}
+
+impl BlockSizePolicy {
+    /// Resolves the policy for one frame of `src_size` bytes (`None` when
+    /// unknown).
+    ///
+    /// ```
+    /// use lz4::frame::{BlockSizeId, BlockSizePolicy};
+    ///
+    /// assert_eq!(BlockSizePolicy::Auto.resolve(Some(100_000)), BlockSizeId::Max256Kb);
+    /// assert_eq!(BlockSizePolicy::Auto.resolve(None), BlockSizeId::Max4Mb);
+    /// ```
+    pub fn resolve(self, src_size: Option<u64>) -> BlockSizeId {
+        match self {
+            BlockSizePolicy::Fixed(id) => id,
+            BlockSizePolicy::Auto => match src_size {
+                Some(size) => lz4f_optimal_bsid(
+                    BlockSizeId::Max4Mb,
+                    usize::try_from(size).unwrap_or(usize::MAX),
+                ),
+                None => BlockSizeId::Max4Mb,
+            },
+        }
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // FrameInfo and Preferences structs (lz4frame.h:175-198)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -143,8 +188,70 @@ pub struct Preferences {
     pub compression_level: i32,
     /// When `true`, flush after every `compress_update` call (reduces buffering).
     pub auto_flush: bool,
-    /// When `true`, HC parser favors decompression speed over ratio (`>= OPT_MIN` only).
+    /// When `true`, the HC optimal parser (levels 10–12) favors decompression
+    /// speed over ratio, as `LZ4F_preferences_t.favorDecSpeed`; lower levels
+    /// ignore it.
     pub favor_dec_speed: bool,
+    /// HC match-search attempts per position (levels 3–12); 0 = the level's default.
+    /// See [`Lz4StreamHc::set_search_depth`](crate::hc::Lz4StreamHc::set_search_depth).
+    pub compression_attempts: u32,
+    /// When `true`, the frame's bytes depend only on the input and these
+    /// preferences, never on how the input was split across update calls,
+    /// `stable_src`, or what the context compressed before.
+    ///
+    /// `auto_flush` is ignored, so every block but the last is full-sized; the
+    /// context is fully reset at `compress_begin`; and each linked block sees
+    /// its history as a detached dictionary, as when the reference `lz4` CLI
+    /// compresses a file, at the cost of a 64 KB copy per block.  Explicit
+    /// `lz4f_flush` calls still end a block early.  Output is identical on
+    /// every 64-bit little-endian target.  32-bit and big-endian targets hash
+    /// differently, as in the reference library, and produce other, equally
+    /// repeatable bytes.
+    pub deterministic: bool,
+    /// Largest match offset the encoder may emit, in bytes (`1..=65535`);
+    /// 0 = the format maximum of 65535.
+    ///
+    /// A smaller window lets the decoder keep less history (see
+    /// [`DecompressOptions::max_distance`](crate::frame::DecompressOptions::max_distance))
+    /// at some cost in ratio; the frame itself is unchanged in format.  Only
+    /// the fast levels honour it: an HC level with a limit below 65535, or a
+    /// limit above 65535, gives [`Lz4FError::ParameterInvalid`] at
+    /// `compress_begin`.
+    pub max_distance: u32,
+}
+
+impl Preferences {
+    /// Preset for long-term storage: every integrity and random-access feature on.
+    ///
+    /// Enables the content-size field, the content checksum and per-block
+    /// checksums, and uses independent 256 KiB blocks so any block can be
+    /// decoded (and verified) on its own.
+    ///
+    /// `frame_info.content_size` is left at 0, since the preset cannot know
+    /// the input: use [`archival_for`](Self::archival_for) when the length is
+    /// known, or stream with
+    /// [`lz4f_compress_begin_with_pledged_size`](crate::frame::lz4f_compress_begin_with_pledged_size).
+    /// Left at 0, the frame simply carries no content size.
+    pub fn archival() -> Self {
+        Preferences {
+            frame_info: FrameInfo {
+                block_size_id: BlockSizeId::Max256Kb,
+                block_mode: BlockMode::Independent,
+                content_checksum_flag: ContentChecksum::Enabled,
+                block_checksum_flag: BlockChecksum::Enabled,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// [`archival`](Self::archival) for an input of `content_size` bytes, so
+    /// the frame records its length as well.
+    pub fn archival_for(content_size: u64) -> Self {
+        let mut prefs = Self::archival();
+        prefs.frame_info.content_size = content_size;
+        prefs
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -171,7 +278,9 @@ pub struct CustomMem {
 // lifetime of any compression or decompression context that holds this value.
 // Rust cannot verify those constraints statically, so `Send + Sync` are asserted
 // here under that contract — consistent with how the C API treats this as plain data.
+#[cfg(not(feature = "forbid-unsafe"))]
 unsafe impl Send for CustomMem {}
+#[cfg(not(feature = "forbid-unsafe"))]
 unsafe impl Sync for CustomMem {}
 
 impl fmt::Debug for CustomMem {
@@ -197,6 +306,30 @@ pub enum BlockCompressMode {
     Uncompressed = 1,
 }
 
+/// What to do with a block, as returned by a [`BlockFilter`].
+///
+/// No C equivalent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockDecision {
+    /// Keep the built-in choice: the compressed candidate when it is smaller
+    /// than the input, otherwise the input stored uncompressed.
+    #[default]
+    Auto,
+    /// Store the input uncompressed, even when a candidate was produced.
+    Store,
+    /// Emit nothing for this block.  Only honoured for independent blocks in
+    /// frames without a content checksum or content size, where the frame
+    /// stays decodable; otherwise the block is stored instead.
+    Skip,
+}
+
+/// Per-block hook set with [`Lz4FCCtx::set_block_filter`].
+///
+/// Called once per block with the uncompressed input and the compressed
+/// candidate, or `None` when the block does not compress (or the frame
+/// stores blocks uncompressed).  Block checksums cover whatever is emitted.
+pub type BlockFilter = Box<dyn FnMut(&[u8], Option<&[u8]>) -> BlockDecision + Send>;
+
 /// Which internal LZ4 context type is currently allocated in a `Lz4FCCtx`.
 /// Corresponds to `LZ4F_CtxType_e` (`ctxNone` / `ctxFast` / `ctxHC`) in lz4frame.c:263.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -208,6 +341,73 @@ pub enum CtxType {
     Hc = 2,
 }
 
+/// Where a [`Lz4FCCtx`] is in its frame sequence (no C equivalent).
+// Only the streaming encoder uses it; it is compiled under the same cfg.
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum FrameState {
+    /// Fresh, or reset by `reset_for_reuse`.
+    #[default]
+    Idle,
+    /// Between `compress_begin` and `compress_end`.
+    Open,
+    /// `compress_end` finished the frame.
+    Ended,
+    /// A call on the open frame failed; its output is unusable.
+    Failed,
+}
+
+/// Frame sequence tag of a [`Lz4FCCtx`], used to catch misuse in debug builds.
+///
+/// Always tracked (it costs a few stores per call); only debug builds check
+/// it, panicking where release builds fall back to the C error codes.
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct FrameSeq {
+    /// Frames begun on this context; the current one is frame #`frames`.
+    pub(crate) frames: u64,
+    pub(crate) state: FrameState,
+    /// Input bytes accepted by the current frame.
+    pub(crate) frame_in: u64,
+}
+
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
+impl FrameSeq {
+    /// Stamps the start of a new frame, first rejecting one begun while the
+    /// previous frame still holds input.
+    pub(crate) fn begin(&mut self) {
+        if cfg!(debug_assertions) && self.state == FrameState::Open && self.frame_in > 0 {
+            panic!(
+                "lz4f_compress_begin called while frame #{} on this context is still open \
+                 ({} bytes in); frames cannot be interleaved on one Lz4FCCtx. Finish it with \
+                 lz4f_compress_end, or abandon it with Lz4FCCtx::reset_for_reuse",
+                self.frames, self.frame_in
+            );
+        }
+        self.frames += 1;
+        self.state = FrameState::Open;
+        self.frame_in = 0;
+    }
+
+    /// Rejects `op` on a context whose frame `compress_end` already finished.
+    pub(crate) fn check_not_ended(&self, op: &str) {
+        if cfg!(debug_assertions) && self.state == FrameState::Ended {
+            panic!(
+                "{op} called after lz4f_compress_end finished frame #{} on this context; \
+                 start the next frame with lz4f_compress_begin",
+                self.frames
+            );
+        }
+    }
+
+    /// Records the outcome of a call on the open frame.
+    pub(crate) fn note<T, E>(&mut self, result: &Result<T, E>) {
+        if result.is_err() && self.state == FrameState::Open {
+            self.state = FrameState::Failed;
+        }
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // LZ4F_cctx_s → Lz4FCCtx (lz4frame.c:265-283)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -234,13 +434,17 @@ pub struct Lz4FCCtx {
     /// Allocated size of `tmp_buf` in bytes (C: `maxBufferSize`).
     pub max_buffer_size: usize,
     /// Internal staging buffer: holds up to `blockSize` of input + compressed output area (C: `tmpBuff`/`tmpIn`).
-    pub tmp_buf: Vec<u8>,
+    /// Aligned to [`BUFFER_ALIGN`](crate::frame::BUFFER_ALIGN).
+    pub tmp_buf: AlignedBuf,
     /// Byte offset within `tmp_buf` where the current accumulation window starts (C: `tmpIn` pointer offset).
     pub tmp_in_offset: usize,
     /// Number of bytes buffered in the current accumulation window (C: `tmpInSize`).
     pub tmp_in_size: usize,
     /// Total uncompressed bytes consumed across all `compress_update` calls (C: `totalInSize`).
     pub total_in_size: u64,
+    /// Bytes emitted for the current frame so far, header included; used to
+    /// pad the frame to [`CompressOptions::pad_alignment`](crate::frame::CompressOptions::pad_alignment).
+    pub total_out_size: u64,
     /// Running XXH32 state for the optional content checksum (C: `xxh`).
     pub xxh: Xxh32State,
     /// The inner LZ4 or LZ4-HC context, stored as a raw byte buffer (C: `lz4CtxPtr`).
@@ -257,6 +461,22 @@ pub struct Lz4FCCtx {
     /// The CDict must outlive the active compression session.
     /// Equivalent to `cdict` in `LZ4F_cctx_s` (lz4frame.c:275).
     pub cdict_ptr: usize,
+    /// Copy of the dictionary given to `compress_begin_using_dict` (its last
+    /// 64 KB), which the inner context refers to for the whole frame.
+    pub dict_buf: Vec<u8>,
+    /// The next independent block is compressed against `dict_buf`; only the
+    /// first block of a frame is.
+    pub dict_pending: bool,
+    /// Source of `tmp_buf` allocations; `None` uses the global allocator.
+    /// Set by [`Lz4FCCtx::with_pool`].
+    pub pool: Option<Arc<dyn BufferPool>>,
+    /// Per-block hook deciding whether each block is kept, stored or
+    /// skipped; `None` keeps the built-in choice.
+    pub block_filter: Option<BlockFilter>,
+    /// Frame sequence tag checked in debug builds to catch misuse such as
+    /// `compress_update` after `compress_end`.
+    #[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
+    pub(crate) seq: FrameSeq,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -270,7 +490,9 @@ pub struct Lz4FCCtx {
 /// remain valid when translated to Rust.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 #[repr(u32)]
-pub enum DecompressStage {
+// Only the streaming decoder, which `forbid-unsafe` compiles out, uses it.
+#[cfg_attr(feature = "forbid-unsafe", allow(dead_code))]
+pub(crate) enum DecompressStage {
     /// Try to decode the frame header from available input (C: `dstage_getFrameHeader` = 0).
     #[default]
     GetFrameHeader = 0,
@@ -366,6 +588,24 @@ pub enum Lz4FError {
     IoWrite,
     /// An I/O read operation on the underlying source failed.
     IoRead,
+    /// Decoded output grew past the caller's `max_output_size`.
+    ///
+    /// Rust-only: it has no `LZ4F_errorStrings[]` index, so
+    /// [`from_index`](Self::from_index) never returns it.
+    OutputLimitExceeded,
+    /// The frame has no content checksum, but the checksum policy requires one.
+    ///
+    /// Rust-only, like [`OutputLimitExceeded`](Self::OutputLimitExceeded).
+    ContentChecksumMissing,
+    /// The frame has no block checksums, but the checksum policy requires them.
+    ///
+    /// Rust-only, like [`OutputLimitExceeded`](Self::OutputLimitExceeded).
+    BlockChecksumMissing,
+    /// The frame names a dictionary that the context's
+    /// [`DictResolver`](crate::dict::DictResolver) does not know.
+    ///
+    /// Rust-only, like [`OutputLimitExceeded`](Self::OutputLimitExceeded).
+    DictionaryUnavailable,
 }
 
 impl Lz4FError {
@@ -399,6 +639,10 @@ impl Lz4FError {
             Lz4FError::ParameterNull => "ERROR_parameter_null",
             Lz4FError::IoWrite => "ERROR_io_write",
             Lz4FError::IoRead => "ERROR_io_read",
+            Lz4FError::OutputLimitExceeded => "ERROR_outputLimit_exceeded",
+            Lz4FError::ContentChecksumMissing => "ERROR_contentChecksum_missing",
+            Lz4FError::BlockChecksumMissing => "ERROR_blockChecksum_missing",
+            Lz4FError::DictionaryUnavailable => "ERROR_dictionary_unavailable",
         }
     }
 
@@ -638,4 +882,57 @@ mod tests {
         assert_eq!(DecompressStage::StoreSFrameSize as u32, 13);
         assert_eq!(DecompressStage::SkipSkippable as u32, 14);
     }
+
+    #[test]
+    fn decompress_stage_default_is_get_frame_header() {
+        let d: DecompressStage = Default::default();
+        assert_eq!(d, DecompressStage::GetFrameHeader);
+    }
+
+    #[test]
+    fn decompress_stage_ordering_le_init() {
+        // C code uses comparisons like `dStage <= dstage_init` (== Init == 2)
+        // All stages 0-2 must compare ≤ Init; stages 3+ must compare > Init.
+        assert!(DecompressStage::GetFrameHeader <= DecompressStage::Init);
+        assert!(DecompressStage::StoreFrameHeader <= DecompressStage::Init);
+        assert!(DecompressStage::Init <= DecompressStage::Init);
+        assert!(DecompressStage::GetBlockHeader > DecompressStage::Init);
+        assert!(DecompressStage::SkipSkippable > DecompressStage::Init);
+    }
+
+    #[test]
+    fn decompress_stage_ordering_monotone() {
+        // Each stage must be strictly less than the next
+        let stages = [
+            DecompressStage::GetFrameHeader,
+            DecompressStage::StoreFrameHeader,
+            DecompressStage::Init,
+            DecompressStage::GetBlockHeader,
+            DecompressStage::StoreBlockHeader,
+            DecompressStage::CopyDirect,
+            DecompressStage::GetBlockChecksum,
+            DecompressStage::GetCBlock,
+            DecompressStage::StoreCBlock,
+            DecompressStage::FlushOut,
+            DecompressStage::GetSuffix,
+            DecompressStage::StoreSuffix,
+            DecompressStage::GetSFrameSize,
+            DecompressStage::StoreSFrameSize,
+            DecompressStage::SkipSkippable,
+        ];
+        for w in stages.windows(2) {
+            assert!(w[0] < w[1], "{:?} should be < {:?}", w[0], w[1]);
+        }
+    }
+
+    #[test]
+    fn block_size_policy_auto_tracks_size() {
+        let auto = BlockSizePolicy::Auto;
+        assert_eq!(auto.resolve(Some(1000)), BlockSizeId::Max64Kb);
+        assert_eq!(auto.resolve(Some(300 * 1024)), BlockSizeId::Max1Mb);
+        assert_eq!(auto.resolve(Some(64 << 20)), BlockSizeId::Max4Mb);
+        assert_eq!(auto.resolve(None), BlockSizeId::Max4Mb);
+        let fixed = BlockSizePolicy::Fixed(BlockSizeId::Max256Kb);
+        assert_eq!(fixed.resolve(Some(1000)), BlockSizeId::Max256Kb);
+    }
 }
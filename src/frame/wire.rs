@@ -0,0 +1,113 @@
+//! Size-prefixed raw blocks — a minimal container for RPC payloads.
+//!
+//! A full LZ4 frame costs at least 15 bytes of header, end mark and block
+//! header, and its streaming machinery is more than a single small message
+//! needs.  This module defines a smaller layout:
+//!
+//! ```text
+//! | decoded size (4, LE) | raw LZ4 block |
+//! ```
+//!
+//! The block runs to the end of the buffer, so the transport must delimit
+//! messages itself.  There is no magic number and no checksum; use frames
+//! when data is stored or crosses a boundary that may corrupt it.  The layout
+//! matches `lz4.block.compress(data, store_size=True)` in python-lz4.
+//!
+//! Decoding goes through [`crate::block::decompress_safe`], and the declared
+//! size is checked against a caller-supplied limit before anything is
+//! allocated.
+
+use super::types::Lz4FError;
+use crate::block::{compress_bound, compress_default, decompress_safe, LZ4_MAX_INPUT_SIZE};
+
+/// Size of the decoded-size prefix.
+pub const PREFIX_SIZE: usize = 4;
+
+/// Largest encoding of `src_len` bytes: the prefix plus the block bound, or
+/// `0` when `src_len` exceeds [`LZ4_MAX_INPUT_SIZE`].
+pub fn prefixed_block_bound(src_len: usize) -> usize {
+    if src_len > LZ4_MAX_INPUT_SIZE as usize {
+        return 0;
+    }
+    PREFIX_SIZE + compress_bound(src_len as i32) as usize
+}
+
+/// Compresses `src` into a size-prefixed block.
+///
+/// # Errors
+///
+/// * [`Lz4FError::SrcSizeTooLarge`] if `src` is longer than
+///   [`LZ4_MAX_INPUT_SIZE`] bytes.
+///
+/// ```
+/// use lz4::frame::wire::{decode_prefixed_block, encode_prefixed_block};
+///
+/// let msg = encode_prefixed_block(b"ping ping ping ping").unwrap();
+/// assert_eq!(decode_prefixed_block(&msg, 1024).unwrap(), b"ping ping ping ping");
+/// ```
+pub fn encode_prefixed_block(src: &[u8]) -> Result<Vec<u8>, Lz4FError> {
+    let bound = prefixed_block_bound(src.len());
+    if bound == 0 {
+        return Err(Lz4FError::SrcSizeTooLarge);
+    }
+    let mut dst = vec![0u8; bound];
+    dst[..PREFIX_SIZE].copy_from_slice(&(src.len() as u32).to_le_bytes());
+    let n = compress_default(src, &mut dst[PREFIX_SIZE..]).map_err(|_| Lz4FError::Generic)?;
+    dst.truncate(PREFIX_SIZE + n);
+    Ok(dst)
+}
+
+/// Decoded size declared by the size-prefixed block `src`, without decoding
+/// it.
+///
+/// # Errors
+///
+/// * [`Lz4FError::FrameHeaderIncomplete`] if `src` is shorter than the prefix.
+pub fn prefixed_block_size(src: &[u8]) -> Result<usize, Lz4FError> {
+    let prefix = src
+        .get(..PREFIX_SIZE)
+        .ok_or(Lz4FError::FrameHeaderIncomplete)?;
+    Ok(u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize)
+}
+
+/// Decodes a size-prefixed block, refusing ones that declare more than
+/// `max_size` decoded bytes.
+///
+/// # Errors
+///
+/// * [`Lz4FError::FrameHeaderIncomplete`] if `src` is shorter than the prefix.
+/// * [`Lz4FError::OutputLimitExceeded`] if the declared size is above
+///   `max_size`.
+/// * [`Lz4FError::DecompressionFailed`] if the block is malformed or does not
+///   decode to exactly the declared size.
+pub fn decode_prefixed_block(src: &[u8], max_size: usize) -> Result<Vec<u8>, Lz4FError> {
+    let size = prefixed_block_size(src)?;
+    if size > max_size {
+        return Err(Lz4FError::OutputLimitExceeded);
+    }
+    let mut dst = vec![0u8; size];
+    match decompress_safe(&src[PREFIX_SIZE..], &mut dst) {
+        Ok(n) if n == size => Ok(dst),
+        _ => Err(Lz4FError::DecompressionFailed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_layout() {
+        let msg = encode_prefixed_block(b"abcabcabcabcabcabcabc").unwrap();
+        assert_eq!(&msg[..4], &21u32.to_le_bytes());
+        assert_eq!(prefixed_block_size(&msg), Ok(21));
+        assert!(msg.len() <= prefixed_block_bound(21));
+    }
+
+    #[test]
+    fn empty_input_is_prefix_and_empty_block() {
+        let msg = encode_prefixed_block(b"").unwrap();
+        assert_eq!(&msg[..4], &[0, 0, 0, 0]);
+        assert_eq!(decode_prefixed_block(&msg, 0).unwrap(), b"");
+    }
+}
@@ -14,6 +14,7 @@
 //! | `LZ4_createStreamHC`                    | [`Lz4StreamHc::create`]               |
 //! | `LZ4_freeStreamHC`                      | (via `Drop` on `Box<Lz4StreamHc>`)    |
 //! | `LZ4_initStreamHC`                      | [`init_stream_hc`]                    |
+//! | `LZ4_initStreamHC` (caller's buffer)    | [`init_stream_hc_in`], [`init_stream_hc_in_buffer`] |
 //! | `LZ4_resetStreamHC`                     | [`reset_stream_hc`]                   |
 //! | `LZ4_resetStreamHC_fast`                | [`reset_stream_hc_fast`]              |
 //! | `LZ4_setCompressionLevel`               | [`set_compression_level`]             |
@@ -21,7 +22,7 @@
 //! | `LZ4_loadDictHC`                        | [`load_dict_hc`]                      |
 //! | `LZ4_attach_HC_dictionary`              | [`attach_hc_dictionary`]              |
 //! | `LZ4_compress_HC_continue`              | [`compress_hc_continue`]              |
-//! | `LZ4_compress_HC_continue_destSize`     | [`compress_hc_continue_dest_size`]    |
+//! | `LZ4_compress_HC_continue_destSize`     | [`compress_hc_continue_dest_size`], [`compress_continue_fitting`] |
 //! | `LZ4_saveDictHC`                        | [`save_dict_hc`]                      |
 //!
 //! ## Notes on `attach_hc_dictionary`
@@ -35,19 +36,31 @@
 //!   dictionary buffer must likewise remain accessible.
 //! - If `None`, any existing dictionary association is detached.
 //!
+//! ## Caller-provided state memory
+//!
+//! The hash and chain tables live inline in [`Lz4StreamHc`] (about 384 KB),
+//! so wherever the state lives, the tables live.  [`Lz4StreamHc::create`]
+//! puts it on the heap.  [`init_stream_hc_in`] and
+//! [`init_stream_hc_in_buffer`] instead initialise it in memory the caller
+//! reserved up front — a `static`, a slot in a pre-allocated pool or an
+//! arena — so soft-realtime code can set up its compressors before entering
+//! the time-critical phase and allocate nothing afterwards.  The one-shot
+//! and streaming entry points only ever borrow the state.
+//!
 //! Deprecated LZ4 HC functions are not exposed by this module.
 
-use core::mem;
+use core::mem::{self, MaybeUninit};
 
 use super::dispatch::{compress_generic, set_external_dict};
 use super::lz4mid::fill_htable;
 use super::search::insert;
 use super::types::{
-    clear_tables, get_clevel_params, init_internal, HcCCtxInternal, HcStrategy,
+    clear_tables, init_internal, CompressionStrategy, HcCCtxInternal, HcStrategy,
     LZ4HC_CLEVEL_DEFAULT, LZ4HC_CLEVEL_MAX, LZ4HC_HASHSIZE,
 };
 use crate::block::compress::compress_bound;
 use crate::block::types::LimitedOutputDirective;
+use crate::dict::effective_dict;
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Lz4StreamHc — streaming HC state (equivalent to LZ4_streamHC_t)
@@ -82,6 +95,143 @@ impl Lz4StreamHc {
         set_compression_level(&mut stream, LZ4HC_CLEVEL_DEFAULT);
         Some(stream)
     }
+
+    /// Override the number of match-search attempts per position.
+    ///
+    /// Levels 3–12 take this budget (`nbSearches` in `lz4hc.c`) from the
+    /// level table — e.g. 256 at level 9 and 16384 at level 12.  A non-zero
+    /// `depth` replaces it for the hash-chain and optimal parsers, trading
+    /// compression time against ratio without changing strategy or target
+    /// length; `0` restores the level default.  Levels 1–2 ignore it.
+    ///
+    /// The override survives [`reset_stream_hc_fast`] (and so
+    /// [`compress_hc_ext_state_fast_reset`]) and [`set_compression_level`];
+    /// [`init_stream_hc`] and [`compress_hc_ext_state`] clear it.
+    pub fn set_search_depth(&mut self, depth: u32) {
+        self.ctx.search_depth = depth;
+    }
+
+    /// Current search-depth override; `0` means the level default is used.
+    pub fn search_depth(&self) -> u32 {
+        self.ctx.search_depth
+    }
+
+    /// Pin the compression engine regardless of level; `None` restores
+    /// level-based selection.
+    ///
+    /// When the level already selects `strategy` its parameters are used
+    /// unchanged; otherwise the strategy runs with the parameters of a
+    /// representative level (see [`CompressionStrategy::params_at`]).  The
+    /// [`search_depth`](Self::search_depth) override still applies.
+    /// [`CompressionStrategy::Fast`] compresses each block on its own with the
+    /// fast block compressor.
+    ///
+    /// Meant for benchmarks and tests that isolate one engine.  Like
+    /// [`set_compression_level`], switch between `Mid` and the other
+    /// strategies only at the start of a stream: they lay out the match tables
+    /// differently.  The override survives [`reset_stream_hc_fast`] and
+    /// [`set_compression_level`]; [`init_stream_hc`] and
+    /// [`compress_hc_ext_state`] clear it.
+    pub fn force_strategy(&mut self, strategy: Option<CompressionStrategy>) {
+        self.ctx.forced_strategy = strategy;
+    }
+
+    /// Currently forced strategy; `None` means the level chooses.
+    pub fn forced_strategy(&self) -> Option<CompressionStrategy> {
+        self.ctx.forced_strategy
+    }
+
+    /// Compress each of `inputs` as an independent HC block at `level`.
+    ///
+    /// The state is prepared once and fast-reset between items (as
+    /// [`reset_stream_hc_fast`]), so the per-item cost is one table slide
+    /// rather than a full 256 KB clear.  This suits callers compressing many
+    /// small buffers, such as columnar engines with thousands of chunks.
+    ///
+    /// If a dictionary was attached with [`attach_hc_dictionary`] before the
+    /// call, it is re-attached for every item, and each block can be
+    /// decompressed with that dictionary (e.g. via
+    /// [`decompress_safe_using_dict`](crate::block::decompress_safe_using_dict)).
+    /// The dictionary stream must remain valid and unmodified until this
+    /// method returns.
+    ///
+    /// Each output is a raw LZ4 block.  An input larger than
+    /// [`LZ4_MAX_INPUT_SIZE`](crate::block::LZ4_MAX_INPUT_SIZE) produces an
+    /// empty `Vec`.  The [`search_depth`](Self::search_depth) override applies
+    /// to every item.
+    pub fn compress_batch(&mut self, inputs: &[&[u8]], level: i32) -> Vec<Vec<u8>> {
+        let dict = self.ctx.dict_ctx;
+        inputs
+            .iter()
+            .map(|src| {
+                reset_stream_hc_fast(self, level);
+                self.ctx.dict_ctx = dict;
+                let bound = compress_bound(i32::try_from(src.len()).unwrap_or(i32::MAX));
+                if bound <= 0 {
+                    return Vec::new();
+                }
+                let mut out = vec![0u8; bound as usize];
+                // SAFETY: `src` and `out` are live slices of the stated lengths;
+                // `out` is sized to `compress_bound`, so output is never limited.
+                let n = unsafe {
+                    compress_hc_continue(
+                        self,
+                        src.as_ptr(),
+                        out.as_mut_ptr(),
+                        src.len() as i32,
+                        bound,
+                    )
+                };
+                out.truncate(n.max(0) as usize);
+                out
+            })
+            .collect()
+    }
+
+    /// Compress `src` as the block that follows `history` in a stream.
+    ///
+    /// The last 64 KB of `history` serve as the external dictionary, passed
+    /// explicitly instead of being found through pointer contiguity, so
+    /// consecutive blocks may live in rotated or pooled buffers.  The result
+    /// is a raw LZ4 block that decodes with
+    /// [`decompress_safe_using_dict`](crate::block::decompress_safe_using_dict)
+    /// given the same `history`.
+    ///
+    /// Uses the stream's compression level, search depth and strategy.  Each
+    /// call indexes `history` afresh (a full table reset, as
+    /// [`load_dict_hc`]), replaces any attached dictionary, and leaves the
+    /// stream holding no references to `src` or `history`.  Returns the
+    /// number of bytes written to `dst`, or 0 if they did not fit.
+    pub fn compress_block_with_history(
+        &mut self,
+        src: &[u8],
+        history: &[u8],
+        dst: &mut [u8],
+    ) -> usize {
+        let Ok(src_size) = i32::try_from(src.len()) else {
+            return 0;
+        };
+        let level = self.ctx.compression_level as i32;
+        let search_depth = self.ctx.search_depth;
+        let favor_dec_speed = self.ctx.favor_dec_speed;
+        let history = effective_dict(history);
+        // SAFETY: both slices outlive the two calls below, and the fast reset
+        // afterwards drops every pointer into them.
+        let written = unsafe {
+            load_dict_hc(self, history.as_ptr(), history.len() as i32);
+            self.ctx.search_depth = search_depth;
+            self.ctx.favor_dec_speed = favor_dec_speed;
+            compress_hc_continue(
+                self,
+                src.as_ptr(),
+                dst.as_mut_ptr(),
+                src_size,
+                dst.len().min(i32::MAX as usize) as i32,
+            )
+        };
+        reset_stream_hc_fast(self, level);
+        written.max(0) as usize
+    }
 }
 
 // Dropping a `Box<Lz4StreamHc>` frees the allocation; no explicit free
@@ -125,11 +275,53 @@ pub fn init_stream_hc(state: &mut Lz4StreamHc) {
     ctx.compression_level = 0;
     ctx.favor_dec_speed = 0;
     ctx.dirty = 0;
+    ctx.search_depth = 0;
+    ctx.forced_strategy = None;
     ctx.dict_ctx = core::ptr::null();
     // Set default compression level after clearing
     set_compression_level(state, LZ4HC_CLEVEL_DEFAULT);
 }
 
+/// Initialise an HC state in caller-provided, possibly uninitialised memory.
+///
+/// The returned reference is the same state [`init_stream_hc`] would produce
+/// and borrows `slot`.  Nothing is allocated, so `slot` can sit in a `static`,
+/// an arena or a pool reserved ahead of time.  The state needs no drop.
+///
+/// Equivalent to `LZ4_initStreamHC(buffer, size)` with a typed buffer.
+pub fn init_stream_hc_in(slot: &mut MaybeUninit<Lz4StreamHc>) -> &mut Lz4StreamHc {
+    let p = slot.as_mut_ptr();
+    // SAFETY: zero bytes are valid for every field but `forced_strategy`,
+    // which is written before the state is assumed initialised; `p` is valid
+    // for writes of one `Lz4StreamHc`.
+    let state = unsafe {
+        core::ptr::write_bytes(p, 0, 1);
+        core::ptr::addr_of_mut!((*p).ctx.forced_strategy).write(None);
+        slot.assume_init_mut()
+    };
+    init_stream_hc(state);
+    state
+}
+
+/// Initialise an HC state at the start of a raw byte buffer.
+///
+/// Returns `None` when `buffer` is shorter than [`sizeof_state_hc`] or its
+/// start is not aligned to `align_of::<Lz4StreamHc>()`, as
+/// `LZ4_initStreamHC` returns `NULL`.  Otherwise behaves like
+/// [`init_stream_hc_in`].
+pub fn init_stream_hc_in_buffer(buffer: &mut [u8]) -> Option<&mut Lz4StreamHc> {
+    let p = buffer.as_mut_ptr();
+    if buffer.len() < mem::size_of::<Lz4StreamHc>()
+        || p.align_offset(mem::align_of::<Lz4StreamHc>()) != 0
+    {
+        return None;
+    }
+    // SAFETY: size and alignment were checked, and the returned reference
+    // keeps `buffer` mutably borrowed.
+    let slot = unsafe { &mut *(p as *mut MaybeUninit<Lz4StreamHc>) };
+    Some(init_stream_hc_in(slot))
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // LZ4_compress_HC_extStateHC_fastReset  (lz4hc.c:1500–1510)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -243,6 +435,46 @@ pub unsafe fn compress_hc(
     // state dropped here — equivalent to `FREEMEM(statePtr)` in C
 }
 
+/// Worst-case size of an HC-compressed block of `input_size` bytes.
+///
+/// HC emits the same block format as the fast encoder and never expands
+/// input further, so this equals
+/// [`compress_bound`](crate::block::compress_bound).  A destination of this
+/// size never limits [`compress_hc`] at any level.  Returns 0 if
+/// `input_size` exceeds [`LZ4_MAX_INPUT_SIZE`](crate::block::LZ4_MAX_INPUT_SIZE).
+#[inline]
+pub fn compress_hc_bound(input_size: usize) -> usize {
+    i32::try_from(input_size).map_or(0, |n| compress_bound(n).max(0) as usize)
+}
+
+/// HC-compress `src` at `level` into a new `Vec<u8>` (raw LZ4 block, no
+/// size prefix).
+///
+/// Allocates [`compress_hc_bound`] bytes and truncates to the compressed
+/// length.  An input larger than
+/// [`LZ4_MAX_INPUT_SIZE`](crate::block::LZ4_MAX_INPUT_SIZE), or a failure to
+/// allocate the HC state, produces an empty `Vec`.
+pub fn compress_hc_to_vec(src: &[u8], level: i32) -> Vec<u8> {
+    let bound = compress_hc_bound(src.len());
+    if bound == 0 {
+        return Vec::new();
+    }
+    let mut out = vec![0u8; bound];
+    // SAFETY: `src` and `out` are live slices of the stated lengths; `out` is
+    // sized to the bound, so output is never limited.
+    let n = unsafe {
+        compress_hc(
+            src.as_ptr(),
+            out.as_mut_ptr(),
+            src.len() as i32,
+            bound as i32,
+            level,
+        )
+    };
+    out.truncate(n.max(0) as usize);
+    out
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // LZ4_compress_HC_destSize  (lz4hc.c:1538–1545)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -271,6 +503,23 @@ pub unsafe fn compress_hc_dest_size(
     // Full initialisation resets the compression level to its default;
     // we then apply the caller-supplied level before compressing.
     init_stream_hc(state);
+    compress_hc_dest_size_initialised(state, src, dst, src_size_ptr, target_dst_size, c_level)
+}
+
+/// The part of [`compress_hc_dest_size`] after [`init_stream_hc`]: settings
+/// made on `state` since the reset, such as the search depth and
+/// [`favor_decompression_speed`], are kept.
+///
+/// # Safety
+/// As for [`compress_hc_dest_size`].
+pub(crate) unsafe fn compress_hc_dest_size_initialised(
+    state: &mut Lz4StreamHc,
+    src: *const u8,
+    dst: *mut u8,
+    src_size_ptr: &mut i32,
+    target_dst_size: i32,
+    c_level: i32,
+) -> i32 {
     init_internal(&mut state.ctx, src);
     set_compression_level(state, c_level);
     compress_generic(
@@ -310,8 +559,14 @@ pub fn reset_stream_hc(state: &mut Lz4StreamHc, compression_level: i32) {
 /// Equivalent to `LZ4_resetStreamHC_fast`.
 pub fn reset_stream_hc_fast(state: &mut Lz4StreamHc, compression_level: i32) {
     if state.ctx.dirty != 0 {
-        // Stream is in an unknown state — must do a full reset.
+        // Stream is in an unknown state — must do a full reset.  The search
+        // depth and forced strategy are caller settings, not stream state, so
+        // they are kept.
+        let search_depth = state.ctx.search_depth;
+        let forced_strategy = state.ctx.forced_strategy;
         init_stream_hc(state);
+        state.ctx.search_depth = search_depth;
+        state.ctx.forced_strategy = forced_strategy;
     } else {
         // Fast path: slide the prefix window forward.
         let ctx = &mut state.ctx;
@@ -384,21 +639,24 @@ pub unsafe fn load_dict_hc(state: &mut Lz4StreamHc, dictionary: *const u8, dict_
     debug_assert!(dict_size >= 0);
 
     // Trim to last 64 KB.
-    let (dict, dict_size) = if dict_size as usize > 64 * 1024 {
-        let trim = dict_size as usize - 64 * 1024;
-        (dictionary.add(trim), 64_i32 * 1024)
+    let (dict, dict_size) = if dict_size > 0 {
+        // SAFETY: the caller guarantees `dictionary` is readable for
+        // `dict_size` bytes.
+        let used = effective_dict(core::slice::from_raw_parts(dictionary, dict_size as usize));
+        (used.as_ptr(), used.len() as i32)
     } else {
         (dictionary, dict_size)
     };
 
-    // Save compression level; full init resets it to default.
+    // Save compression level and forced strategy; full init resets them.
     let c_level = state.ctx.compression_level as i32;
+    let forced_strategy = state.ctx.forced_strategy;
 
     // Need a full initialisation (fast-reset has bad side-effects here).
     init_stream_hc(state);
     set_compression_level(state, c_level);
+    state.ctx.forced_strategy = forced_strategy;
 
-    let cp = get_clevel_params(c_level);
     let ctx = &mut state.ctx;
 
     // Position context at the start of the dictionary.
@@ -406,7 +664,7 @@ pub unsafe fn load_dict_hc(state: &mut Lz4StreamHc, dictionary: *const u8, dict_
     ctx.end = dict.add(dict_size as usize);
 
     // Build hash tables over the dictionary content.
-    if cp.strat == HcStrategy::Lz4Mid {
+    if ctx.table_strategy() == HcStrategy::Lz4Mid {
         fill_htable(ctx, dict, dict_size as usize);
     } else if dict_size as usize >= LZ4HC_HASHSIZE {
         // Insert everything up to end-3 so the last few bytes are searchable.
@@ -631,6 +889,56 @@ pub unsafe fn compress_hc_continue_dest_size(
     )
 }
 
+/// Safe, slice-based form of [`compress_hc_continue_dest_size`].
+///
+/// Compresses as much of `src` as fits in `dst`, using the stream's history,
+/// and returns `(consumed, written)`.  `(0, 0)` means nothing could be
+/// stored; the stream is then dirty and must be reset before further use.
+///
+/// When only part of `src` is consumed, the stream's history is cut back to
+/// end at the last consumed byte, so the next call may continue with
+/// `&src[consumed..]`, a copy of it, or any other block, and the output still
+/// decodes as one chained stream.  The raw function leaves the unconsumed
+/// tail in the history, which is only safe if the next block overlaps it.
+///
+/// Like [`compress_hc_continue`], earlier blocks must stay readable and
+/// unmodified while the stream refers to them; use [`save_dict_hc`] to move
+/// the history into a buffer of your own.
+pub fn compress_continue_fitting(
+    state: &mut Lz4StreamHc,
+    src: &[u8],
+    dst: &mut [u8],
+) -> (usize, usize) {
+    let mut src_size = src.len().min(i32::MAX as usize) as i32;
+    let target = dst.len().min(i32::MAX as usize) as i32;
+    // SAFETY: the slices bound both pointer ranges; keeping earlier blocks
+    // alive is the documented caller contract, as for the raw function.
+    let written = unsafe {
+        compress_hc_continue_dest_size(state, src.as_ptr(), dst.as_mut_ptr(), &mut src_size, target)
+    };
+    if written <= 0 {
+        return (0, 0);
+    }
+    let consumed = src_size as usize;
+    if consumed < src.len() {
+        // The encoder indexed (and counted in `end`) input it never emitted.
+        // Rebuild the tables over the last 64 KB the decoder will have seen;
+        // the prefix is contiguous and ends at the last consumed byte.
+        let search_depth = state.ctx.search_depth;
+        let favor_dec_speed = state.ctx.favor_dec_speed;
+        // SAFETY: `src` lies inside the prefix, which the caller keeps alive.
+        unsafe {
+            let history_end = src.as_ptr().add(consumed);
+            let prefix = history_end.offset_from(state.ctx.prefix_start) as usize;
+            let keep = prefix.min(64 * 1024);
+            load_dict_hc(state, history_end.sub(keep), keep as i32);
+        }
+        state.ctx.search_depth = search_depth;
+        state.ctx.favor_dec_speed = favor_dec_speed;
+    }
+    (consumed, written as usize)
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // LZ4_saveDictHC  (lz4hc.c:1742–1764)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -99,8 +99,10 @@ pub unsafe fn compress_hash_chain(
     let mut ip: *const u8 = source;
     let mut anchor: *const u8 = ip;
     let iend: *const u8 = ip.add(input_size as usize);
-    let mflimit: *const u8 = iend.sub(MFLIMIT);
-    let matchlimit: *const u8 = iend.sub(LASTLITERALS);
+    // Inputs shorter than MFLIMIT put these before `source`; `wrapping_sub`
+    // keeps that defined, and such inputs never reach the loops using them.
+    let mflimit: *const u8 = iend.wrapping_sub(MFLIMIT);
+    let matchlimit: *const u8 = iend.wrapping_sub(LASTLITERALS);
 
     let mut optr: *mut u8 = dest;
     let mut op: *mut u8 = dest;
@@ -132,6 +134,7 @@ pub unsafe fn compress_hash_chain(
         // the end; shorten the effective output limit so encode_sequence
         // never writes into that reserved region.  The limit is restored
         // before writing the final literal run.
+        oend = oend.sub(LASTLITERALS);
     }
 
     // Short inputs carry no matches; jump straight to the final literal run.
@@ -620,11 +623,14 @@ pub unsafe fn compress_optimal(
     let opt_len = LZ4_OPT_NUM + TRAILING_LITERALS;
     let mut opt: Box<[Lz4HcOptimal]> = vec![Lz4HcOptimal::default(); opt_len].into_boxed_slice();
 
+    let input_size = *src_size_ptr;
     let mut ip: *const u8 = source;
     let mut anchor: *const u8 = ip;
-    let iend: *const u8 = ip.add(*src_size_ptr as usize);
-    let mflimit: *const u8 = iend.sub(MFLIMIT);
-    let matchlimit: *const u8 = iend.sub(LASTLITERALS);
+    let iend: *const u8 = ip.add(input_size as usize);
+    // Inputs shorter than MFLIMIT put these before `source`; `wrapping_sub`
+    // keeps that defined, and such inputs never reach the loops using them.
+    let mflimit: *const u8 = iend.wrapping_sub(MFLIMIT);
+    let matchlimit: *const u8 = iend.wrapping_sub(LASTLITERALS);
 
     let mut op: *mut u8 = dst;
     let mut op_saved: *mut u8 = dst;
@@ -649,7 +655,10 @@ pub unsafe fn compress_optimal(
     }
 
     // ── Main Loop ─────────────────────────────────────────────────────────────
-    'compress_loop: while ip <= mflimit {
+    // Short inputs carry no matches.  The length test also keeps the pointer
+    // comparison meaningful when `mflimit` falls before `source` (for an
+    // empty slice's dangling pointer it wraps around).
+    'compress_loop: while input_size >= LZ4_MIN_LENGTH as i32 && ip <= mflimit {
         let llen = ip.offset_from(anchor) as i32;
         let mut last_match_pos: usize = 0;
 
@@ -18,6 +18,9 @@
 //!   [`HcCCtxInternal`] as a dictionary, automatically promoting it to ext-dict
 //!   mode when the position is zero and the source exceeds 4 KB.
 //!
+//! [`Lz4StreamHc::force_strategy`](crate::hc::Lz4StreamHc::force_strategy) pins
+//! one of these (or the fast block compressor) regardless of level.
+//!
 //! The public entry point for most callers is [`compress_generic`], which routes
 //! to the no-dict or dict-ctx path based on `ctx.dict_ctx`.
 //!
@@ -30,8 +33,8 @@ use super::search::{insert, HcFavor};
 use super::types::{
     get_clevel_params, DictCtxDirective, HcCCtxInternal, HcStrategy, LZ4HC_CLEVEL_MAX,
 };
-use crate::block::compress::LZ4_MAX_INPUT_SIZE;
-use crate::block::types::LimitedOutputDirective;
+use crate::block::compress::{compress_dest_size_raw, compress_fast_ext_state, LZ4_MAX_INPUT_SIZE};
+use crate::block::types::{LimitedOutputDirective, StreamStateInternal};
 
 /// Maximum back-reference distance at which a dict-ctx is still usable.
 /// Beyond 64 KB the LZ4 format cannot encode the offset, so the dict is discarded.
@@ -50,10 +53,8 @@ impl HcCCtxInternal {
     /// logically equivalent to `isMid1 == isMid2`.
     #[inline]
     pub fn is_compatible(&self, other: &Self) -> bool {
-        let is_mid_self =
-            get_clevel_params(self.compression_level as i32).strat == HcStrategy::Lz4Mid;
-        let is_mid_other =
-            get_clevel_params(other.compression_level as i32).strat == HcStrategy::Lz4Mid;
+        let is_mid_self = self.table_strategy() == HcStrategy::Lz4Mid;
+        let is_mid_other = other.table_strategy() == HcStrategy::Lz4Mid;
         is_mid_self == is_mid_other
     }
 }
@@ -80,9 +81,7 @@ impl HcCCtxInternal {
 pub unsafe fn set_external_dict(ctx: &mut HcCCtxInternal, new_block: *const u8) {
     // If the prefix has at least 4 bytes and we are not in lz4mid mode, insert
     // the last 3 prefix positions so they stay reachable after the window slides.
-    if ctx.end >= ctx.prefix_start.add(4)
-        && get_clevel_params(ctx.compression_level as i32).strat != HcStrategy::Lz4Mid
-    {
+    if ctx.end >= ctx.prefix_start.add(4) && ctx.table_strategy() != HcStrategy::Lz4Mid {
         insert(ctx, ctx.end.sub(3));
     }
 
@@ -123,6 +122,7 @@ pub unsafe fn set_external_dict(ctx: &mut HcCCtxInternal, new_block: *const u8)
 /// - `dst` must be writable for `dst_capacity` bytes.
 /// - `ctx` must be a valid, exclusively-accessible context.
 #[inline(always)]
+#[allow(clippy::too_many_arguments)]
 pub unsafe fn compress_generic_internal(
     ctx: &mut HcCCtxInternal,
     src: *const u8,
@@ -138,12 +138,6 @@ pub unsafe fn compress_generic_internal(
     if limit == LimitedOutputDirective::FillOutput && dst_capacity < 1 {
         return 0;
     }
-    if limit != LimitedOutputDirective::NotLimited && dst_capacity < 5 {
-        // Not enough room even for a single token + minimal literal run.
-        *src_size_ptr = 0;
-        ctx.dirty = 1;
-        return 0;
-    }
     // Reject oversized or negative inputs.
     // Mirrors C: `(U32)*srcSizePtr > (U32)LZ4_MAX_INPUT_SIZE`.
     if *src_size_ptr as u32 > LZ4_MAX_INPUT_SIZE {
@@ -153,7 +147,32 @@ pub unsafe fn compress_generic_internal(
     // Advance ctx.end by srcSize so match offsets are relative to the new end.
     ctx.end = ctx.end.add(*src_size_ptr as usize);
 
-    let c_param = get_clevel_params(c_level);
+    // Checked only after `end` has moved: a streaming caller that stores the
+    // block raw on failure (as the frame encoder does) still expects it in
+    // the history, and the next contiguous block must not look detached.
+    if limit != LimitedOutputDirective::NotLimited && dst_capacity < 5 {
+        // Not enough room even for a single token + minimal literal run.
+        *src_size_ptr = 0;
+        ctx.dirty = 1;
+        return 0;
+    }
+
+    let mut c_param = match ctx.forced_strategy {
+        None => get_clevel_params(c_level),
+        Some(forced) => match forced.params_at(c_level) {
+            Some(params) => params,
+            None => {
+                let result = compress_block_fast(src, dst, src_size_ptr, dst_capacity, limit);
+                if result <= 0 {
+                    ctx.dirty = 1;
+                }
+                return result;
+            }
+        },
+    };
+    if ctx.search_depth != 0 {
+        c_param.nb_searches = ctx.search_depth.min(i32::MAX as u32);
+    }
     let favor = if ctx.favor_dec_speed != 0 {
         HcFavor::DecompressionSpeed
     } else {
@@ -195,6 +214,31 @@ pub unsafe fn compress_generic_internal(
     result
 }
 
+/// Compress one block with the fast block compressor, for
+/// [`CompressionStrategy::Fast`](super::types::CompressionStrategy::Fast).
+///
+/// Uses a fresh block state, so the block does not reference earlier input;
+/// the HC tables are left alone and pick the block up lazily on the next HC
+/// search.  Honours `limit` like the HC compressors, including `FillOutput`.
+///
+/// # Safety
+/// Same as [`compress_generic_internal`].
+unsafe fn compress_block_fast(
+    src: *const u8,
+    dst: *mut u8,
+    src_size_ptr: &mut i32,
+    dst_capacity: i32,
+    limit: LimitedOutputDirective,
+) -> i32 {
+    let result = if limit == LimitedOutputDirective::FillOutput {
+        compress_dest_size_raw(src, dst, src_size_ptr, dst_capacity)
+    } else {
+        let mut state = StreamStateInternal::new();
+        compress_fast_ext_state(&mut state, src, *src_size_ptr, dst, dst_capacity, 1)
+    };
+    result.map_or(0, |n| n as i32)
+}
+
 /// Compress without a dictionary context.
 ///
 /// Asserts (in debug builds) that `ctx.dict_ctx` is null, then delegates to
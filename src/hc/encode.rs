@@ -57,8 +57,8 @@ pub enum Lz4HcError {
 /// # Errors
 ///
 /// Returns `Err(Lz4HcError::OutputTooSmall)` when
-/// `limit == LimitedOutputDirective::LimitedOutput` and the output buffer
-/// does not have sufficient space for the encoded sequence.
+/// `limit` is `LimitedOutput` or `FillOutput` and the output buffer does not
+/// have sufficient space for the encoded sequence.
 #[inline(always)]
 pub unsafe fn encode_sequence(
     ip: &mut *const u8,
@@ -81,7 +81,7 @@ pub unsafe fn encode_sequence(
     // Worst-case space needed before the match data:
     //   ceil(length/255) extension bytes + length literal bytes
     //   + 2-byte offset + 1 remaining token byte + LASTLITERALS reserved.
-    if limit == LimitedOutputDirective::LimitedOutput {
+    if limit != LimitedOutputDirective::NotLimited {
         let needed = literal_length / 255 + literal_length + (2 + 1 + LASTLITERALS);
         if (*op).add(needed) > oend {
             return Err(Lz4HcError::OutputTooSmall);
@@ -123,7 +123,7 @@ pub unsafe fn encode_sequence(
     let mut ml_remaining = (match_length as usize) - MINMATCH;
 
     // ── Output-limit check for match length ──────────────────────────────
-    if limit == LimitedOutputDirective::LimitedOutput {
+    if limit != LimitedOutputDirective::NotLimited {
         let needed = ml_remaining / 255 + (1 + LASTLITERALS);
         if (*op).add(needed) > oend {
             return Err(Lz4HcError::OutputTooSmall);
@@ -36,8 +36,8 @@
 
 use super::encode::{encode_sequence, Lz4HcError};
 use super::types::{
-    count_back, get_clevel_params, mid_hash4_ptr, mid_hash8_ptr, DictCtxDirective, HcCCtxInternal,
-    HcStrategy, LZ4MID_HASHSIZE, LZ4MID_HASHTABLESIZE,
+    count_back, mid_hash4_ptr, mid_hash8_ptr, DictCtxDirective, HcCCtxInternal, HcStrategy,
+    LZ4MID_HASHSIZE, LZ4MID_HASHTABLESIZE,
 };
 use crate::block::types::{
     self as bt, LimitedOutputDirective, LASTLITERALS, LZ4_DISTANCE_MAX, MFLIMIT, MINMATCH, ML_BITS,
@@ -96,6 +96,7 @@ pub enum DictSearchMode {
 ///
 /// # Safety
 /// All pointers must be valid for their respective read accesses.
+#[allow(clippy::too_many_arguments)]
 pub unsafe fn hc_search_ext_dict(
     ip: *const u8,
     ip_index: u32,
@@ -378,7 +379,7 @@ pub unsafe fn select_dict_search_mode(dict_ctx: *const HcCCtxInternal) -> Option
         return None;
     }
     let ctx = &*dict_ctx;
-    if get_clevel_params(ctx.compression_level as i32).strat == HcStrategy::Lz4Mid {
+    if ctx.table_strategy() == HcStrategy::Lz4Mid {
         Some(DictSearchMode::Ext)
     } else {
         Some(DictSearchMode::Hc)
@@ -461,9 +462,11 @@ pub unsafe fn lz4mid_compress(
     let mut ip: *const u8 = src;
     let mut anchor: *const u8 = ip;
     let iend: *const u8 = ip.add(*src_size_ptr as usize);
-    let mflimit: *const u8 = iend.sub(MFLIMIT);
-    let matchlimit: *const u8 = iend.sub(LASTLITERALS);
-    let ilimit: *const u8 = iend.sub(LZ4MID_HASHSIZE);
+    // Inputs shorter than MFLIMIT put these before `src`; `wrapping_sub`
+    // keeps that defined, and such inputs skip the loops using them.
+    let mflimit: *const u8 = iend.wrapping_sub(MFLIMIT);
+    let matchlimit: *const u8 = iend.wrapping_sub(LASTLITERALS);
+    let ilimit: *const u8 = iend.wrapping_sub(LZ4MID_HASHSIZE);
     let mut op: *mut u8 = dst;
     let mut oend: *mut u8 = op.add(max_output_size as usize);
 
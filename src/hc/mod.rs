@@ -39,9 +39,12 @@ pub mod search;
 pub mod types;
 
 pub use api::{
-    attach_hc_dictionary, compress_hc, compress_hc_continue, compress_hc_dest_size,
-    compress_hc_ext_state, favor_decompression_speed, init_stream_hc, load_dict_hc,
-    reset_stream_hc, reset_stream_hc_fast, save_dict_hc, set_compression_level, sizeof_state_hc,
-    Lz4StreamHc,
+    attach_hc_dictionary, compress_hc, compress_hc_bound, compress_hc_continue,
+    compress_hc_dest_size, compress_hc_ext_state, compress_hc_to_vec, favor_decompression_speed,
+    init_stream_hc, init_stream_hc_in, init_stream_hc_in_buffer, load_dict_hc, reset_stream_hc,
+    reset_stream_hc_fast, save_dict_hc, set_compression_level, sizeof_state_hc, Lz4StreamHc,
+};
+pub use types::{
+    CompressionStrategy, LZ4HC_CLEVEL_DEFAULT, LZ4HC_CLEVEL_MAX, LZ4HC_CLEVEL_MIN,
+    LZ4HC_CLEVEL_OPT_MIN,
 };
-pub use types::{LZ4HC_CLEVEL_DEFAULT, LZ4HC_CLEVEL_MAX, LZ4HC_CLEVEL_MIN, LZ4HC_CLEVEL_OPT_MIN};
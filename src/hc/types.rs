@@ -97,6 +97,47 @@ pub enum HcStrategy {
     Lz4Opt = 2,
 }
 
+/// Compression engine pinned with
+/// [`Lz4StreamHc::force_strategy`](crate::hc::Lz4StreamHc::force_strategy),
+/// overriding the one the compression level would select.
+///
+/// Intended for benchmarks and tests that need to exercise a single code path.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompressionStrategy {
+    /// The fast (non-HC) block compressor at acceleration 1.  Each block is
+    /// compressed on its own, without referencing earlier blocks or a dictionary.
+    Fast,
+    /// The lz4mid dual-hash compressor (levels 1–2).
+    Mid,
+    /// The hash-chain compressor (levels 3–9).
+    Hc,
+    /// The optimal parser (levels 10–12).
+    Optimal,
+}
+
+impl CompressionStrategy {
+    /// Parameters used when this strategy is forced at `c_level`.
+    ///
+    /// The level's own parameters are kept when it already selects this
+    /// strategy; otherwise those of a representative level are used (2 for
+    /// `Mid`, [`LZ4HC_CLEVEL_DEFAULT`] for `Hc`, [`LZ4HC_CLEVEL_OPT_MIN`] for
+    /// `Optimal`).  Returns `None` for `Fast`, which takes no HC parameters.
+    pub fn params_at(self, c_level: i32) -> Option<CParams> {
+        let (strat, fallback_level) = match self {
+            CompressionStrategy::Fast => return None,
+            CompressionStrategy::Mid => (HcStrategy::Lz4Mid, 2),
+            CompressionStrategy::Hc => (HcStrategy::Lz4Hc, LZ4HC_CLEVEL_DEFAULT),
+            CompressionStrategy::Optimal => (HcStrategy::Lz4Opt, LZ4HC_CLEVEL_OPT_MIN),
+        };
+        let params = get_clevel_params(c_level);
+        Some(if params.strat == strat {
+            params
+        } else {
+            get_clevel_params(fallback_level)
+        })
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Compression parameters (lz4hc.c:87–91)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -428,6 +469,10 @@ pub struct HcCCtxInternal {
     pub favor_dec_speed: i8,
     /// Non-zero → stream must be fully reset before next use.
     pub dirty: i8,
+    /// Match-search attempts per position; 0 = use the level's `nb_searches`.
+    pub search_depth: u32,
+    /// Strategy pinned regardless of level; `None` = chosen by the level.
+    pub forced_strategy: Option<CompressionStrategy>,
     /// Attached dictionary context (may be null).
     pub dict_ctx: *const HcCCtxInternal,
 }
@@ -454,11 +499,29 @@ impl HcCCtxInternal {
             compression_level: 0,
             favor_dec_speed: 0,
             dirty: 0,
+            search_depth: 0,
+            forced_strategy: None,
             dict_ctx: core::ptr::null(),
         }
     }
 }
 
+impl HcCCtxInternal {
+    /// Strategy whose table layout this context uses: the forced strategy if
+    /// any, else the one selected by `compression_level`.
+    ///
+    /// `Fast` does not touch the tables and reports [`HcStrategy::Lz4Hc`], so
+    /// later hash-chain blocks can still index its input.
+    pub fn table_strategy(&self) -> HcStrategy {
+        match self.forced_strategy {
+            None => get_clevel_params(self.compression_level as i32).strat,
+            Some(CompressionStrategy::Fast) | Some(CompressionStrategy::Hc) => HcStrategy::Lz4Hc,
+            Some(CompressionStrategy::Mid) => HcStrategy::Lz4Mid,
+            Some(CompressionStrategy::Optimal) => HcStrategy::Lz4Opt,
+        }
+    }
+}
+
 impl Default for HcCCtxInternal {
     fn default() -> Self {
         Self::new()
@@ -4,25 +4,40 @@
 //! consumed by the CLI and library users.  The organisation mirrors `lz4io.h`
 //! from the LZ4 reference implementation.
 
+pub mod archive;
 pub mod compress_frame;
 pub mod compress_legacy;
 pub mod compress_mt;
 pub mod decompress_dispatch;
 pub mod decompress_frame;
+pub mod decompress_frame_mt;
 pub mod decompress_legacy;
 pub mod decompress_resources;
+pub mod diag;
 pub mod file_info;
 pub mod file_io;
+pub mod flush;
+pub mod incremental;
+#[cfg(all(feature = "mmap", unix))]
+pub mod mmap;
 pub mod prefs;
+pub mod progress;
+pub mod read_ahead;
 pub mod sparse;
 
 // ── Core type re-exports (lz4io.h public surface) ────────────────────────────
+pub use diag::LogFormat;
 pub use file_info::CompressedFileInfo;
-pub use prefs::Prefs;
+pub use flush::FlushEvery;
+pub use prefs::{BlockMode, IoPrefsBuilder, Prefs};
+pub use progress::{Progress, ProgressSink, StderrProgress};
 
 // ── Special I/O sentinels (mirrors lz4io.h #defines) ─────────────────────────
 pub use file_io::{NULL_OUTPUT, NUL_MARK, STDIN_MARK, STDOUT_MARK};
 
+// ── Broken-pipe detection for the binary's exit status ───────────────────────
+pub use file_io::stdout_closed;
+
 // ── Magic number constants ────────────────────────────────────────────────────
 pub use prefs::{LEGACY_MAGICNUMBER, LZ4IO_MAGICNUMBER, LZ4IO_SKIPPABLE0, LZ4IO_SKIPPABLEMASK};
 
@@ -38,9 +53,19 @@ pub use prefs::default_nb_workers;
 /// Compress a single file. Mirrors `LZ4IO_compressFilename`.
 pub use compress_frame::compress_filename;
 
+/// Compress between caller-owned descriptors (Unix) or handles (Windows).
+#[cfg(unix)]
+pub use compress_frame::compress_fd;
+#[cfg(windows)]
+pub use compress_frame::compress_handle;
+
 /// Compress multiple files with a given suffix. Mirrors `LZ4IO_compressMultipleFilenames`.
 pub use compress_frame::compress_multiple_filenames;
 
+/// Compress multiple files into an output directory, flat or mirroring the
+/// source tree (`--output-dir`).
+pub use compress_frame::compress_multiple_filenames_to_dir;
+
 // ── Legacy LZ4 frame format compression ──────────────────────────────────────────
 /// Compress a single file using the legacy LZ4 frame format.
 pub use compress_legacy::compress_filename_legacy;
@@ -52,9 +77,21 @@ pub use compress_legacy::compress_multiple_filenames_legacy;
 /// Decompress a single file. Mirrors `LZ4IO_decompressFilename`.
 pub use decompress_dispatch::decompress_filename;
 
+/// Decompress between caller-owned descriptors (Unix) or handles (Windows).
+#[cfg(unix)]
+pub use decompress_dispatch::decompress_fd;
+#[cfg(windows)]
+pub use decompress_dispatch::decompress_handle;
+
 /// Decompress multiple files. Mirrors `LZ4IO_decompressMultipleFilenames`.
 pub use decompress_dispatch::decompress_multiple_filenames;
+/// `-t`: check the integrity of each file, reporting OK or FAILED per file.
+pub use decompress_dispatch::test_multiple_filenames;
+/// Error for an output written with blocks skipped by `--recover`.
+pub use decompress_dispatch::DamagedOutput;
 
 // ── File info / --list (mirrors lz4io.h) ─────────────────────────────────────
 /// Print `--list` metadata for compressed files. Mirrors `LZ4IO_displayCompressedFilesInfo`.
 pub use file_info::display_compressed_files_info;
+/// `--info`: the `--list` table plus a first/last-block spot check per file.
+pub use file_info::{display_compressed_files_info_checked, quick_check_file, QuickCheckReport};
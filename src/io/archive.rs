@@ -0,0 +1,379 @@
+//! Multi-file archives made of concatenated LZ4 frames.
+//!
+//! [`join_files`] compresses each input as its own LZ4 frame and writes the
+//! frames back to back into a single output file, followed by a skippable
+//! frame holding a manifest of every member's name, original size, and frame
+//! location.  [`extract_archive`] reads the manifest and splits the archive
+//! back into individual files.
+//!
+//! Because the manifest is an ordinary skippable frame, any LZ4 decoder can
+//! still read a joined archive: it simply decodes to the concatenation of all
+//! members.
+//!
+//! # Manifest layout
+//!
+//! The manifest is the last frame in the file, tagged with
+//! [`ARCHIVE_MANIFEST_NIBBLE`].  All integers are little-endian:
+//!
+//! ```text
+//! entry_count : u32
+//! entry_count × { name_len: u16, name: [u8; name_len],
+//!                 original_size: u64, frame_offset: u64, frame_size: u64 }
+//! payload_len : u32            (length of the whole payload, trailer included)
+//! tag         : b"LZ4J"
+//! ```
+//!
+//! The fixed 8-byte trailer lets a reader locate the manifest from the end of
+//! the file without scanning the frames before it.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::frame::skippable::{
+    skippable_frame_bound, write_skippable_frame, LZ4F_MAGIC_SKIPPABLE_START, SKIPPABLE_HEADER_SIZE,
+};
+use crate::io::compress_frame::{
+    apply_block_size_policy, compress_stream, effective_block_size, CompressResources,
+};
+use crate::io::decompress_frame::decompress_lz4f;
+use crate::io::decompress_resources::DecompressResources;
+use crate::io::file_io::{open_dst_file, src_size_hint, STDIN_MARK};
+use crate::io::prefs::{display_level, Prefs, LZ4IO_MAGICNUMBER};
+
+/// User nibble of the skippable frame that carries an archive manifest.
+pub const ARCHIVE_MANIFEST_NIBBLE: u8 = 0x0A;
+
+/// Tag closing every manifest payload.
+const MANIFEST_TAG: &[u8; 4] = b"LZ4J";
+/// `payload_len` + tag.
+const TRAILER_SIZE: usize = 8;
+
+/// One member of a joined archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// File name (base name only) the member is extracted to.
+    pub name: String,
+    /// Uncompressed size in bytes.
+    pub original_size: u64,
+    /// Byte offset of the member's LZ4 frame from the start of the archive.
+    pub frame_offset: u64,
+    /// Length of the member's LZ4 frame in bytes.
+    pub frame_size: u64,
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Serialises `entries` into a manifest payload (trailer included).
+fn encode_manifest(entries: &[ArchiveEntry]) -> io::Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for e in entries {
+        let name_len = u16::try_from(e.name.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file name too long"))?;
+        payload.extend_from_slice(&name_len.to_le_bytes());
+        payload.extend_from_slice(e.name.as_bytes());
+        payload.extend_from_slice(&e.original_size.to_le_bytes());
+        payload.extend_from_slice(&e.frame_offset.to_le_bytes());
+        payload.extend_from_slice(&e.frame_size.to_le_bytes());
+    }
+    let payload_len = u32::try_from(payload.len() + TRAILER_SIZE)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "manifest too large"))?;
+    payload.extend_from_slice(&payload_len.to_le_bytes());
+    payload.extend_from_slice(MANIFEST_TAG);
+    Ok(payload)
+}
+
+/// Parses a manifest payload produced by [`encode_manifest`].
+fn decode_manifest(payload: &[u8]) -> io::Result<Vec<ArchiveEntry>> {
+    let body = payload
+        .len()
+        .checked_sub(TRAILER_SIZE)
+        .map(|n| &payload[..n])
+        .ok_or_else(|| invalid_data("archive manifest truncated"))?;
+    let mut pos = 0usize;
+    let mut take = |n: usize| -> io::Result<&[u8]> {
+        let s = body
+            .get(pos..pos + n)
+            .ok_or_else(|| invalid_data("archive manifest truncated"))?;
+        pos += n;
+        Ok(s)
+    };
+    let read_u64 = |s: &[u8]| u64::from_le_bytes(s.try_into().unwrap());
+
+    let count = u32::from_le_bytes(take(4)?.try_into().unwrap()) as usize;
+    let mut entries = Vec::with_capacity(count.min(body.len() / 26));
+    for _ in 0..count {
+        let name_len = u16::from_le_bytes(take(2)?.try_into().unwrap()) as usize;
+        let name = String::from_utf8(take(name_len)?.to_vec())
+            .map_err(|_| invalid_data("archive manifest: member name is not UTF-8"))?;
+        entries.push(ArchiveEntry {
+            name,
+            original_size: read_u64(take(8)?),
+            frame_offset: read_u64(take(8)?),
+            frame_size: read_u64(take(8)?),
+        });
+    }
+    if pos != body.len() {
+        return Err(invalid_data("archive manifest has trailing bytes"));
+    }
+    Ok(entries)
+}
+
+/// A member name is safe to extract if it cannot escape the output directory.
+fn is_safe_member_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', '\0'])
+}
+
+/// Compresses every file in `srcs` into one archive at `dst`.
+///
+/// Each input becomes an independent LZ4 frame at `compression_level`,
+/// honouring the block size, block mode, checksum, content-size and
+/// dictionary settings in `prefs`.  Members are named after the base name of
+/// their source path, so two inputs with the same base name are rejected
+/// before anything is written.  Stdin cannot be a member.
+///
+/// Returns the manifest that was appended to the archive.
+pub fn join_files(
+    srcs: &[&str],
+    dst: &str,
+    compression_level: i32,
+    prefs: &Prefs,
+) -> io::Result<Vec<ArchiveEntry>> {
+    let mut seen = HashSet::new();
+    let mut names = Vec::with_capacity(srcs.len());
+    for &src in srcs {
+        if src == STDIN_MARK {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot join stdin into an archive",
+            ));
+        }
+        let name = Path::new(src)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .filter(|n| is_safe_member_name(n))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{}: not a file name", src),
+                )
+            })?;
+        if !seen.insert(name.clone()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("duplicate member name in archive: {}", name),
+            ));
+        }
+        names.push(name);
+    }
+
+    let mut ress = CompressResources::new(prefs)?;
+    let block_size = effective_block_size(prefs);
+    let mut dst_writer = open_dst_file(dst, prefs)?;
+    let pending = dst_writer.pending.take();
+    let mut entries = Vec::with_capacity(srcs.len());
+    let mut offset = 0u64;
+
+    for (&src, name) in srcs.iter().zip(names) {
+        let mut src_reader =
+            File::open(src).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", src, e)))?;
+        let mut frame_prefs = ress.prepared_prefs;
+        frame_prefs.compression_level = compression_level;
+        let size_hint = src_size_hint(src);
+        apply_block_size_policy(prefs, &mut frame_prefs, size_hint);
+        if prefs.content_size_flag {
+            frame_prefs.frame_info.content_size = size_hint.unwrap_or(0);
+        }
+        let (read, written) = compress_stream(
+            &mut ress,
+            &mut src_reader,
+            &mut dst_writer,
+            &frame_prefs,
+            block_size,
+            false,
+        )?;
+        display_level(
+            3,
+            &format!("{:20} : {} ==> {} bytes \n", name, read, written),
+        );
+        entries.push(ArchiveEntry {
+            name,
+            original_size: read,
+            frame_offset: offset,
+            frame_size: written,
+        });
+        offset += written;
+    }
+
+    let payload = encode_manifest(&entries)?;
+    let mut frame = vec![0u8; skippable_frame_bound(payload.len())];
+    let n = write_skippable_frame(&mut frame, ARCHIVE_MANIFEST_NIBBLE, &payload)
+        .map_err(|e| io::Error::other(format!("Manifest generation failed: {}", e)))?;
+    dst_writer.write_all(&frame[..n])?;
+    dst_writer.flush()?;
+    drop(dst_writer);
+    if let Some(pending) = pending {
+        pending.commit()?;
+    }
+
+    display_level(
+        2,
+        &format!(
+            "Joined {} files into {} ({} bytes) \n",
+            entries.len(),
+            dst,
+            offset + n as u64
+        ),
+    );
+    Ok(entries)
+}
+
+/// Reads the manifest of the archive at `src`.
+///
+/// # Errors
+///
+/// Returns [`io::ErrorKind::InvalidData`] if the file does not end with an
+/// archive manifest written by [`join_files`], or if any member's frame lies
+/// outside the archive.
+pub fn read_manifest(src: &str) -> io::Result<Vec<ArchiveEntry>> {
+    let mut file =
+        File::open(src).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", src, e)))?;
+    let file_len = file.metadata()?.len();
+    if file_len < (SKIPPABLE_HEADER_SIZE + TRAILER_SIZE) as u64 {
+        return Err(invalid_data(format!("{}: not an lz4 archive", src)));
+    }
+
+    let mut trailer = [0u8; TRAILER_SIZE];
+    file.seek(SeekFrom::Start(file_len - TRAILER_SIZE as u64))?;
+    file.read_exact(&mut trailer)?;
+    if &trailer[4..] != MANIFEST_TAG {
+        return Err(invalid_data(format!("{}: not an lz4 archive", src)));
+    }
+    let payload_len = u32::from_le_bytes(trailer[..4].try_into().unwrap()) as u64;
+    let frame_start = file_len
+        .checked_sub(SKIPPABLE_HEADER_SIZE as u64 + payload_len)
+        .ok_or_else(|| invalid_data(format!("{}: archive manifest truncated", src)))?;
+
+    let mut header = [0u8; SKIPPABLE_HEADER_SIZE];
+    file.seek(SeekFrom::Start(frame_start))?;
+    file.read_exact(&mut header)?;
+    let magic = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let size = u32::from_le_bytes(header[4..].try_into().unwrap()) as u64;
+    if magic != LZ4F_MAGIC_SKIPPABLE_START | ARCHIVE_MANIFEST_NIBBLE as u32 || size != payload_len {
+        return Err(invalid_data(format!(
+            "{}: archive manifest header corrupted",
+            src
+        )));
+    }
+    let mut payload = vec![0u8; payload_len as usize];
+    file.read_exact(&mut payload)?;
+
+    let entries = decode_manifest(&payload)?;
+    for e in &entries {
+        let end = e.frame_offset.checked_add(e.frame_size);
+        if end.is_none_or(|end| end > frame_start) {
+            return Err(invalid_data(format!(
+                "{}: member {} lies outside the archive",
+                src, e.name
+            )));
+        }
+    }
+    Ok(entries)
+}
+
+/// Splits the archive at `src` into one file per member inside `out_dir`.
+///
+/// The directory is created if needed.  Existing files are handled according
+/// to `prefs.overwrite`, exactly as for single-file decompression.  Member
+/// names containing path separators or `..` are refused, so an archive can
+/// never write outside `out_dir`.
+///
+/// Returns the archive's manifest.
+pub fn extract_archive(src: &str, out_dir: &str, prefs: &Prefs) -> io::Result<Vec<ArchiveEntry>> {
+    let entries = read_manifest(src)?;
+    if let Some(bad) = entries.iter().find(|e| !is_safe_member_name(&e.name)) {
+        return Err(invalid_data(format!(
+            "{}: refusing to extract unsafe member name {:?}",
+            src, bad.name
+        )));
+    }
+    fs::create_dir_all(out_dir)?;
+
+    let mut resources = DecompressResources::from_prefs(prefs)?;
+    let mut file = File::open(src)?;
+    for e in &entries {
+        file.seek(SeekFrom::Start(e.frame_offset))?;
+        let mut frame = (&mut file).take(e.frame_size);
+        let mut magic = [0u8; 4];
+        frame.read_exact(&mut magic)?;
+        if u32::from_le_bytes(magic) != LZ4IO_MAGICNUMBER {
+            return Err(invalid_data(format!(
+                "{}: member {} is not an LZ4 frame",
+                src, e.name
+            )));
+        }
+
+        let dst_path = Path::new(out_dir).join(&e.name);
+        let dst_name = dst_path.to_string_lossy();
+        let mut dst = open_dst_file(&dst_name, prefs)?;
+        let pending = dst.pending.take();
+        let decoded = decompress_lz4f(&mut frame, &mut dst, prefs, &mut resources)?;
+        dst.flush()?;
+        drop(dst);
+        if decoded != e.original_size {
+            return Err(invalid_data(format!(
+                "{}: member {} decoded to {} bytes, manifest says {}",
+                src, e.name, decoded, e.original_size
+            )));
+        }
+        if let Some(pending) = pending {
+            pending.commit()?;
+        }
+        display_level(3, &format!("{:20} : {} bytes \n", e.name, decoded));
+    }
+
+    display_level(
+        2,
+        &format!("Extracted {} files from {} \n", entries.len(), src),
+    );
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_round_trips() {
+        let entries = vec![
+            ArchiveEntry {
+                name: "a.txt".into(),
+                original_size: 10,
+                frame_offset: 0,
+                frame_size: 29,
+            },
+            ArchiveEntry {
+                name: "b.bin".into(),
+                original_size: 1 << 40,
+                frame_offset: 29,
+                frame_size: 7,
+            },
+        ];
+        let payload = encode_manifest(&entries).unwrap();
+        assert_eq!(&payload[payload.len() - 4..], MANIFEST_TAG);
+        assert_eq!(decode_manifest(&payload).unwrap(), entries);
+        assert!(decode_manifest(&payload[..payload.len() - 9]).is_err());
+    }
+
+    #[test]
+    fn member_names_cannot_escape() {
+        assert!(is_safe_member_name("file.txt"));
+        for bad in ["", ".", "..", "../x", "a/b", "a\\b"] {
+            assert!(!is_safe_member_name(bad), "{bad:?}");
+        }
+    }
+}
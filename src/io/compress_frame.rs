@@ -12,7 +12,11 @@
 //!   [`compress_frame_chunk`], the per-chunk primitive also consumed by the
 //!   multi-threaded path in `io::compress_mt`.
 //! - [`compress_filename`] — end-to-end single-file compression.
+//! - `compress_fd` / `compress_handle` — the same between caller-owned
+//!   descriptors (Unix) or handles (Windows), without pathnames.
 //! - [`compress_multiple_filenames`] — batch compression with a shared suffix.
+//! - [`compress_multiple_filenames_to_dir`] — batch compression into an
+//!   output directory, flat or mirroring the source tree (`--output-dir`).
 //!
 //! # Single-threaded vs multi-threaded
 //!
@@ -20,6 +24,15 @@
 //! `multithread` feature is enabled and `nb_workers > 1`, callers should
 //! dispatch to `io::compress_mt::compress_filename_mt` instead.
 //!
+//! # Memory-mapped input
+//!
+//! With the `mmap` feature on Unix, [`compress_filename`] maps regular files of
+//! at least `io::mmap::MMAP_MIN_SIZE` (4 MiB) and compresses blocks straight
+//! out of the mapping.  Stdin, pipes and smaller
+//! files are read through a buffer as before; the output is identical.  A
+//! mapped file that another process truncates mid-run raises `SIGBUS`.
+//! The declared content size is the mapping's length, not a second `stat`.
+//!
 //! # Dictionary support
 //!
 //! A dictionary file is read once via `load_dict_file`, which uses a circular
@@ -28,27 +41,44 @@
 //! bytes are then digested into an [`Lz4FCDict`] for efficient reuse across
 //! all compressed files.
 
+use std::collections::HashMap;
 use std::fs;
 use std::io::{self, Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::ptr;
-use std::time::SystemTime;
+use std::sync::Arc;
 
+use crate::dict::truncation_warning;
 use crate::frame::compress::{
     lz4f_compress_begin_using_cdict, lz4f_compress_begin_using_dict, LZ4F_VERSION,
 };
-use crate::frame::header::lz4f_compress_frame_bound;
+use crate::frame::header::{
+    lz4f_block_size_downgrade, lz4f_compress_frame_bound, lz4f_get_block_size, BlockSizeDowngrade,
+};
 use crate::frame::types::{
-    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, FrameInfo, FrameType, Preferences,
+    BlockChecksum, BlockMode, BlockSizePolicy, ContentChecksum, FrameInfo, FrameType, Preferences,
 };
 use crate::frame::{
     lz4f_compress_end, lz4f_compress_frame_using_cdict, lz4f_compress_update,
     lz4f_create_compression_context, Lz4FCCtx, Lz4FCDict,
 };
-use crate::io::file_io::{open_dst_file, open_src_file, NUL_MARK, STDIN_MARK, STDOUT_MARK};
-use crate::io::prefs::{display_level, final_time_display, Prefs, KB, LZ4_MAX_DICT_SIZE, MB};
+use crate::io::diag;
+#[cfg(unix)]
+use crate::io::file_io::file_from_fd;
+#[cfg(windows)]
+use crate::io::file_io::file_from_handle;
+use crate::io::file_io::{
+    check_regular_src, copy_file_stat, open_dst_file, open_src_file_buffered, remaining_size_hint,
+    src_size_hint, stdout_closed, NUL_MARK, STDIN_MARK, STDOUT_MARK,
+};
+use crate::io::incremental::skip_up_to_date;
+use crate::io::prefs::{
+    display_level, final_time_display, frame_block_size_id, Prefs, KB, LZ4_MAX_DICT_SIZE, MB,
+};
+use crate::io::progress;
+use crate::io::read_ahead::ReadAhead;
 use crate::timefn::get_time;
-use crate::util::set_file_stat;
+use crate::util::is_reg_file;
 
 extern "C" {
     fn clock() -> libc::clock_t;
@@ -73,6 +103,9 @@ pub struct CompressStats {
     pub bytes_in: u64,
     /// Total compressed bytes written to the destination.
     pub bytes_out: u64,
+    /// Set when a block size fixed with `-B` was lowered because the input
+    /// fits in one smaller block; see [`block_size_notice`].
+    pub block_size_downgrade: Option<BlockSizeDowngrade>,
 }
 
 // ---------------------------------------------------------------------------
@@ -114,6 +147,8 @@ pub struct CompressResources {
     /// Pre-digested dictionary, or `None` when no dictionary is active.
     /// Equivalent to `cdict` (an `LZ4F_CDict*`).
     pub cdict: Option<Box<Lz4FCDict>>,
+    /// [`block_size_notice`] for the last file compressed with these resources.
+    pub block_size_downgrade: Option<BlockSizeDowngrade>,
 }
 
 // SAFETY: The raw *const Lz4FCDict pointer derived from `cdict` is only used
@@ -125,12 +160,7 @@ unsafe impl Send for CompressResources {}
 // ---------------------------------------------------------------------------
 
 fn build_preferences(io_prefs: &Prefs) -> Preferences {
-    let block_size_id = match io_prefs.block_size_id {
-        4 => BlockSizeId::Max64Kb,
-        5 => BlockSizeId::Max256Kb,
-        6 => BlockSizeId::Max1Mb,
-        _ => BlockSizeId::Max4Mb, // 7 (default) → 4 MB
-    };
+    let block_size_id = frame_block_size_id(io_prefs.block_size_id);
     let block_mode = if io_prefs.block_independence {
         BlockMode::Independent
     } else {
@@ -157,6 +187,9 @@ fn build_preferences(io_prefs: &Prefs) -> Preferences {
         compression_level: 0, // overridden per-call
         auto_flush: true,     // mirrors ress.preparedPrefs.autoFlush = 1
         favor_dec_speed: io_prefs.favor_dec_speed,
+        compression_attempts: 0,
+        deterministic: false,
+        max_distance: 0,
     }
 }
 
@@ -167,7 +200,7 @@ fn build_preferences(io_prefs: &Prefs) -> Preferences {
 /// Returns the actual block size in bytes, deriving it from block_size_id when
 /// block_size is 0. Equivalent to `io_prefs->blockSize` in the C code after
 /// `LZ4IO_createCResources` has been called (which fills in the default).
-fn effective_block_size(io_prefs: &Prefs) -> usize {
+pub(crate) fn effective_block_size(io_prefs: &Prefs) -> usize {
     if io_prefs.block_size > 0 {
         io_prefs.block_size
     } else {
@@ -180,6 +213,53 @@ fn effective_block_size(io_prefs: &Prefs) -> usize {
     }
 }
 
+/// Applies `io_prefs.block_size_policy` to one input of `src_size` bytes
+/// (`None` when unknown) by setting the block size ID in `prefs`.
+///
+/// Input is still read in [`effective_block_size`] pieces, and an automatic
+/// pick never exceeds it.
+pub(crate) fn apply_block_size_policy(
+    io_prefs: &Prefs,
+    prefs: &mut Preferences,
+    src_size: Option<u64>,
+) {
+    if io_prefs.block_size_policy != BlockSizePolicy::Auto {
+        return;
+    }
+    let id = BlockSizePolicy::Auto.resolve(src_size);
+    if lz4f_get_block_size(id).is_some_and(|size| size <= effective_block_size(io_prefs)) {
+        prefs.frame_info.block_size_id = id;
+    }
+}
+
+/// Reports a block size fixed with `-B` that the one-shot frame path lowers
+/// for an input of `src_size` bytes, since it fits in one smaller block.
+///
+/// Only a [`BlockSizePolicy::Fixed`] size is reported; an automatic pick is
+/// expected to follow the input.  An empty input holds no block, so there is
+/// nothing to report.  The notice is shown at display level 2.
+pub(crate) fn block_size_notice(
+    io_prefs: &Prefs,
+    prefs: &Preferences,
+    src_size: u64,
+) -> Option<BlockSizeDowngrade> {
+    if src_size == 0 || !matches!(io_prefs.block_size_policy, BlockSizePolicy::Fixed(_)) {
+        return None;
+    }
+    let src_size = usize::try_from(src_size).unwrap_or(usize::MAX);
+    let downgrade = lz4f_block_size_downgrade(prefs.frame_info.block_size_id, src_size)?;
+    let kb = |id| lz4f_get_block_size(id).unwrap_or(64 * KB) / KB;
+    display_level(
+        2,
+        &format!(
+            "Note : block size lowered from {} KB to {} KB : input fits in one block \n",
+            kb(downgrade.requested),
+            kb(downgrade.used),
+        ),
+    );
+    Some(downgrade)
+}
+
 // ---------------------------------------------------------------------------
 // load_dict_file — LZ4IO_createDict (lz4io.c lines 1005-1062)
 // Reads at most LZ4_MAX_DICT_SIZE (64 KB) bytes from the end of a file.
@@ -191,6 +271,7 @@ fn load_dict_file(dict_filename: &str) -> io::Result<Vec<u8>> {
     let mut circular_buf = vec![0u8; circ_size];
     let mut dict_end: usize = 0;
     let mut dict_len: usize = 0;
+    let mut file_len: Option<u64> = None;
 
     // Open the dict file (stdin sentinel or a real file).
     let mut reader: Box<dyn Read> = if dict_filename == STDIN_MARK {
@@ -202,6 +283,7 @@ fn load_dict_file(dict_filename: &str) -> io::Result<Vec<u8>> {
                 format!("Dictionary error: could not open {}: {}", dict_filename, e),
             )
         })?;
+        file_len = f.metadata().ok().map(|m| m.len());
         // Opportunistically seek to the last 64 KB (lz4io.c:1027-1029).
         // If this fails (e.g. pipes), we just read from the current position.
         {
@@ -221,6 +303,10 @@ fn load_dict_file(dict_filename: &str) -> io::Result<Vec<u8>> {
         dict_len += n;
     }
 
+    if let Some(warning) = truncation_warning(file_len.unwrap_or(dict_len as u64)) {
+        display_level(2, &warning);
+    }
+
     // Clamp to 64 KB (lz4io.c:1037-1039).
     if dict_len > LZ4_MAX_DICT_SIZE {
         dict_len = LZ4_MAX_DICT_SIZE;
@@ -299,6 +385,7 @@ impl CompressResources {
             ctx,
             prepared_prefs,
             cdict,
+            block_size_downgrade: None,
         })
     }
 
@@ -329,25 +416,6 @@ fn read_to_capacity(reader: &mut dyn Read, buf: &mut [u8]) -> io::Result<usize>
     Ok(total)
 }
 
-// ---------------------------------------------------------------------------
-// copy_file_stat helper — UTIL_setFileStat (lz4io.c lines 1467-1473)
-// ---------------------------------------------------------------------------
-
-fn copy_file_stat(src: &str, dst: &str) -> io::Result<()> {
-    let m = fs::metadata(src)?;
-    let mtime = m.modified().unwrap_or(SystemTime::UNIX_EPOCH);
-
-    #[cfg(unix)]
-    let (uid, gid, mode) = {
-        use std::os::unix::fs::MetadataExt;
-        (m.uid(), m.gid(), m.mode())
-    };
-    #[cfg(not(unix))]
-    let (uid, gid, mode) = (0u32, 0u32, 0o644u32);
-
-    set_file_stat(Path::new(dst), mtime, uid, gid, mode)
-}
-
 // ---------------------------------------------------------------------------
 // compress_frame_chunk — LZ4IO_compressFrameChunk (lz4io.c lines 1120-1150)
 // ---------------------------------------------------------------------------
@@ -419,76 +487,162 @@ pub fn compress_frame_chunk(
 }
 
 // ---------------------------------------------------------------------------
-// compress_filename_st — LZ4IO_compressFilename_extRess_ST (lz4io.c 1366-1488)
+// compress_stream — the frame-writing core of compress_filename_st
 // ---------------------------------------------------------------------------
 
-/// Single-threaded frame-format compression of one file.
+/// Compresses everything `src_reader` yields as one LZ4 frame into `dst_writer`.
 ///
-/// Returns the number of uncompressed source bytes processed via `in_stream_size`.
-/// Equivalent to `LZ4IO_compressFilename_extRess_ST`.
-fn compress_filename_st(
-    in_stream_size: &mut u64,
+/// `prefs` must already carry the compression level and content size.  Inputs
+/// shorter than one block use the one-shot frame path; longer inputs are
+/// streamed block by block.  With `show_progress` the built-in `Read : …`
+/// line is updated at notification level 2.
+///
+/// Returns `(bytes_read, bytes_written)`.
+pub(crate) fn compress_stream(
     ress: &mut CompressResources,
-    src_filename: &str,
-    dst_filename: &str,
-    compression_level: i32,
-    io_prefs: &Prefs,
-) -> io::Result<()> {
-    let block_size = effective_block_size(io_prefs);
+    src_reader: &mut dyn Read,
+    dst_writer: &mut dyn Write,
+    prefs: &Preferences,
+    block_size: usize,
+    show_progress: bool,
+) -> io::Result<(u64, u64)> {
+    compress_blocks(
+        ress,
+        &mut SrcBlocks::Reader(Box::new(src_reader)),
+        dst_writer,
+        prefs,
+        block_size,
+        show_progress,
+    )
+}
 
-    // Open source (lz4io.c:1384-1385).
-    let mut src_reader = open_src_file(src_filename)?;
+/// Where [`compress_blocks`] takes its input from.
+enum SrcBlocks<'a> {
+    /// Blocks are read into the caller's buffer.
+    Reader(Box<dyn Read + 'a>),
+    /// Blocks are slices of a memory-mapped file; nothing is copied.
+    #[cfg(all(feature = "mmap", unix))]
+    Mapped {
+        map: crate::io::mmap::MappedFile,
+        pos: usize,
+        tracker: Option<Arc<progress::ProgressTracker>>,
+    },
+}
 
-    // Build per-call preferences (lz4io.c:1391-1398).
-    let mut prefs = ress.prepared_prefs;
-    prefs.compression_level = compression_level;
-    if io_prefs.content_size_flag {
-        // UTIL_getOpenFileSize equivalent: stat before reading.
-        let file_size = if src_filename != STDIN_MARK {
-            fs::metadata(src_filename).map(|m| m.len()).unwrap_or(0)
-        } else {
-            0
-        };
-        prefs.frame_info.content_size = file_size;
-        if file_size == 0 {
-            display_level(3, "Warning : cannot determine input content size \n");
+impl SrcBlocks<'_> {
+    /// Opens `src_filename`, mapping it when the `mmap` feature is on and the
+    /// file is large and regular; otherwise reads it through `tracker`.
+    fn open(
+        src_filename: &str,
+        buffer_size: usize,
+        tracker: Option<&Arc<progress::ProgressTracker>>,
+    ) -> io::Result<Self> {
+        #[cfg(all(feature = "mmap", unix))]
+        if let Ok(Some(map)) = crate::io::mmap::MappedFile::open(src_filename) {
+            return Ok(SrcBlocks::Mapped {
+                map,
+                pos: 0,
+                tracker: tracker.cloned(),
+            });
         }
+        Ok(SrcBlocks::Reader(progress::wrap_reader(
+            open_src_file_buffered(src_filename, buffer_size)?,
+            tracker,
+        )))
     }
 
-    // Open destination (lz4io.c:1386-1387).
-    let dst_file = open_dst_file(dst_filename, io_prefs)?;
-    let dst_is_stdout = dst_file.is_stdout;
-    let mut dst_writer: Box<dyn Write> = Box::new(dst_file);
+    /// The input size: the length mapped for a mapped file, otherwise a stat
+    /// of `src_filename`.
+    fn size_hint(&self, src_filename: &str) -> Option<u64> {
+        match self {
+            SrcBlocks::Reader(_) => src_size_hint(src_filename),
+            #[cfg(all(feature = "mmap", unix))]
+            SrcBlocks::Mapped { map, .. } => Some(map.len() as u64),
+        }
+    }
 
+    /// Returns the next block of at most `buf.len()` bytes; shorter only at
+    /// end of input, empty once exhausted.  `buf` is scratch space for reads.
+    fn next_block<'s>(&'s mut self, buf: &'s mut [u8]) -> io::Result<&'s [u8]> {
+        match self {
+            SrcBlocks::Reader(reader) => {
+                let n = read_to_capacity(&mut **reader, buf)?;
+                Ok(&buf[..n])
+            }
+            #[cfg(all(feature = "mmap", unix))]
+            SrcBlocks::Mapped { map, pos, tracker } => {
+                let data = map.as_slice();
+                let start = *pos;
+                let end = (start + buf.len()).min(data.len());
+                *pos = end;
+                progress::record_read(tracker.as_ref(), end - start);
+                // Let the kernel fetch the following block while this one
+                // is compressed.
+                let ahead = (end + buf.len()).min(data.len());
+                crate::frame::advise::will_need(&data[end..ahead]);
+                Ok(&data[start..end])
+            }
+        }
+    }
+}
+
+/// The body of [`compress_stream`], generic over where blocks come from.
+fn compress_blocks(
+    ress: &mut CompressResources,
+    src: &mut SrcBlocks<'_>,
+    dst_writer: &mut dyn Write,
+    prefs: &Preferences,
+    block_size: usize,
+    show_progress: bool,
+) -> io::Result<(u64, u64)> {
+    let result = compress_frame_blocks(ress, src, dst_writer, prefs, block_size, show_progress);
+    if result.is_err() {
+        // A read or write error leaves the frame open; abandon it so the
+        // next file can begin a frame on the same context.
+        let _ = ress.ctx.reset_for_reuse(prefs);
+    }
+    result
+}
+
+fn compress_frame_blocks(
+    ress: &mut CompressResources,
+    src: &mut SrcBlocks<'_>,
+    dst_writer: &mut dyn Write,
+    prefs: &Preferences,
+    block_size: usize,
+    show_progress: bool,
+) -> io::Result<(u64, u64)> {
     let cdict_ptr = ress.cdict_ptr();
 
     let mut filesize: u64 = 0;
     let mut compressedfilesize: u64 = 0;
 
     // Read first block (lz4io.c:1401-1403).
-    let mut read_size = read_to_capacity(&mut *src_reader, &mut ress.src_buffer[..block_size])?;
-    filesize += read_size as u64;
+    let mut block = src.next_block(&mut ress.src_buffer[..block_size])?;
+    filesize += block.len() as u64;
 
-    if read_size < block_size {
+    if block.len() < block_size {
         // Single-block file: one-shot frame compression (lz4io.c:1406-1418).
         let c_size = lz4f_compress_frame_using_cdict(
             &mut ress.ctx,
             &mut ress.dst_buffer,
-            &ress.src_buffer[..read_size],
+            block,
             cdict_ptr,
-            Some(&prefs),
+            Some(prefs),
         )
         .map_err(|e| io::Error::other(format!("Compression failed: {}", e)))?;
         compressedfilesize = c_size as u64;
 
-        display_level(
-            2,
-            &format!(
-                "\rRead : {} MiB   ==> {:.2}%   ",
-                filesize >> 20,
-                compressedfilesize as f64 / (filesize.max(1)) as f64 * 100.0,
-            ),
-        );
+        if show_progress {
+            display_level(
+                2,
+                &format!(
+                    "\rRead : {} MiB   ==> {:.2}%   ",
+                    filesize >> 20,
+                    compressedfilesize as f64 / (filesize.max(1)) as f64 * 100.0,
+                ),
+            );
+        }
 
         dst_writer
             .write_all(&ress.dst_buffer[..c_size])
@@ -501,6 +655,16 @@ fn compress_filename_st(
     } else {
         // Multi-block file: streaming frame compression (lz4io.c:1423-1460).
 
+        // A stat size smaller than what was already read is stale or made up
+        // (sysfs reports 4096 for every attribute); declaring it would make
+        // the frame end with a size mismatch, so drop it.
+        let mut prefs = *prefs;
+        if prefs.frame_info.content_size != 0 && prefs.frame_info.content_size < filesize {
+            diag::warning(3, "Warning : input larger than its reported size \n");
+            prefs.frame_info.content_size = 0;
+        }
+        let prefs = &prefs;
+
         // Write frame header (lz4io.c:1425-1430).
         // SAFETY: cdict_ptr is valid for the lifetime of ress.
         let header_size = unsafe {
@@ -508,7 +672,7 @@ fn compress_filename_st(
                 &mut ress.ctx,
                 &mut ress.dst_buffer,
                 cdict_ptr,
-                Some(&prefs),
+                Some(prefs),
             )
         }
         .map_err(|e| io::Error::other(format!("File header generation failed: {}", e)))?;
@@ -521,24 +685,21 @@ fn compress_filename_st(
         compressedfilesize += header_size as u64;
 
         // Main loop — one block at a time (lz4io.c:1433-1449).
-        while read_size > 0 {
-            let out_size = lz4f_compress_update(
-                &mut ress.ctx,
-                &mut ress.dst_buffer,
-                &ress.src_buffer[..read_size],
-                None,
-            )
-            .map_err(|e| io::Error::other(format!("Compression failed: {}", e)))?;
+        while !block.is_empty() {
+            let out_size = lz4f_compress_update(&mut ress.ctx, &mut ress.dst_buffer, block, None)
+                .map_err(|e| io::Error::other(format!("Compression failed: {}", e)))?;
             compressedfilesize += out_size as u64;
 
-            display_level(
-                2,
-                &format!(
-                    "\rRead : {} MiB   ==> {:.2}%   ",
-                    filesize >> 20,
-                    compressedfilesize as f64 / filesize as f64 * 100.0,
-                ),
-            );
+            if show_progress {
+                display_level(
+                    2,
+                    &format!(
+                        "\rRead : {} MiB   ==> {:.2}%   ",
+                        filesize >> 20,
+                        compressedfilesize as f64 / filesize as f64 * 100.0,
+                    ),
+                );
+            }
 
             dst_writer
                 .write_all(&ress.dst_buffer[..out_size])
@@ -550,8 +711,8 @@ fn compress_filename_st(
                 })?;
 
             // Read next block (lz4io.c:1447-1448).
-            read_size = read_to_capacity(&mut *src_reader, &mut ress.src_buffer[..block_size])?;
-            filesize += read_size as u64;
+            block = src.next_block(&mut ress.src_buffer[..block_size])?;
+            filesize += block.len() as u64;
         }
 
         // End-of-frame mark (lz4io.c:1452-1459).
@@ -568,24 +729,109 @@ fn compress_filename_st(
         compressedfilesize += end_size as u64;
     }
 
+    Ok((filesize, compressedfilesize))
+}
+
+// ---------------------------------------------------------------------------
+// compress_filename_st — LZ4IO_compressFilename_extRess_ST (lz4io.c 1366-1488)
+// ---------------------------------------------------------------------------
+
+/// Single-threaded frame-format compression of one file.
+///
+/// Returns the number of uncompressed source bytes processed via `in_stream_size`.
+/// Equivalent to `LZ4IO_compressFilename_extRess_ST`.
+fn compress_filename_st(
+    in_stream_size: &mut u64,
+    ress: &mut CompressResources,
+    src_filename: &str,
+    dst_filename: &str,
+    compression_level: i32,
+    io_prefs: &Prefs,
+) -> io::Result<()> {
+    let block_size = effective_block_size(io_prefs);
+
+    if skip_up_to_date(src_filename, dst_filename, io_prefs) {
+        *in_stream_size = 0;
+        return Ok(());
+    }
+
+    // Open source (lz4io.c:1384-1385).  With the `mmap` feature, large
+    // regular files are mapped rather than read.
+    check_regular_src(src_filename, io_prefs)?;
+    // FIFOs and devices have no size, and their metadata describes the node
+    // rather than the data read from it: it is neither copied nor removed.
+    let src_is_file = is_reg_file(Path::new(src_filename));
+    let tracker = progress::start(io_prefs, src_filename);
+    let mut src = SrcBlocks::open(src_filename, io_prefs.io_buffer_size, tracker.as_ref())?;
+
+    // Build per-call preferences (lz4io.c:1391-1398).
+    let mut prefs = ress.prepared_prefs;
+    prefs.compression_level = compression_level;
+    // UTIL_getOpenFileSize equivalent: stat before reading.
+    let size_hint = src.size_hint(src_filename);
+    apply_block_size_policy(io_prefs, &mut prefs, size_hint);
+    // Incremental runs compare the recorded size on the next pass.
+    if io_prefs.content_size_flag || io_prefs.mtime_window.is_some() {
+        let file_size = size_hint.unwrap_or(0);
+        prefs.frame_info.content_size = file_size;
+        if file_size == 0 {
+            diag::warning(3, "Warning : cannot determine input content size \n");
+        }
+    }
+
+    // Open destination (lz4io.c:1386-1387).
+    let mut dst_file = open_dst_file(dst_filename, io_prefs)?;
+    let dst_is_stdout = dst_file.is_stdout;
+    let pending = dst_file.pending.take();
+    let mut dst_writer = progress::wrap_writer(Box::new(dst_file), tracker.as_ref());
+
+    let (filesize, compressedfilesize) = compress_blocks(
+        ress,
+        &mut src,
+        &mut *dst_writer,
+        &prefs,
+        block_size,
+        io_prefs.builtin_progress(),
+    )?;
+
     // Release file handles (lz4io.c:1463-1464):
     // dst_writer is dropped here; for stdout the DstFile wrapper does not close it.
+    drop(src);
     drop(dst_writer);
+    if let Some(pending) = pending {
+        pending.commit()?;
+    }
+    progress::finish(tracker.as_ref());
 
     // Copy owner/permissions/mtime from src to dst (lz4io.c:1467-1473).
-    if src_filename != STDIN_MARK && !dst_is_stdout && dst_filename != NUL_MARK {
-        let _ = copy_file_stat(src_filename, dst_filename);
+    if src_is_file && src_filename != STDIN_MARK && !dst_is_stdout && dst_filename != NUL_MARK {
+        if let Ok(meta) = fs::metadata(src_filename) {
+            copy_file_stat(&meta, dst_filename, io_prefs);
+        }
     }
 
     // Remove source file if requested (lz4io.c:1475-1478).
     if io_prefs.remove_src_file && src_filename != STDIN_MARK {
-        fs::remove_file(src_filename).map_err(|e| {
-            io::Error::new(e.kind(), format!("Remove error: {}: {}", src_filename, e))
-        })?;
+        if src_is_file {
+            fs::remove_file(src_filename).map_err(|e| {
+                io::Error::new(e.kind(), format!("Remove error: {}: {}", src_filename, e))
+            })?;
+        } else {
+            display_level(
+                2,
+                &format!("lz4: {}: not a regular file, not removed \n", src_filename),
+            );
+        }
     }
 
     // Final status display (lz4io.c:1481-1484).
     display_level(2, &format!("\r{:79}\r", ""));
+    // Inputs shorter than one block took the one-shot path.
+    ress.block_size_downgrade = if filesize < block_size as u64 {
+        block_size_notice(io_prefs, &prefs, filesize)
+    } else {
+        None
+    };
     display_level(
         2,
         &format!(
@@ -667,6 +913,115 @@ pub fn compress_filename(
     Ok(CompressStats {
         bytes_in: processed,
         bytes_out: 0,
+        block_size_downgrade: ress.block_size_downgrade,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Public: compress_fd / compress_handle — no C equivalent
+// ---------------------------------------------------------------------------
+
+/// Compress everything readable from the descriptor `input` into `output` as
+/// one LZ4 frame.
+///
+/// For daemons handed inherited descriptors or socketpairs, which would
+/// otherwise need a pathname or the `stdin`/`stdout` sentinels.  Both
+/// descriptors are duplicated, so the caller keeps them open and owned;
+/// reads and writes start at, and advance, their current offsets.
+/// Compression follows `prefs` as [`compress_filename`] does, including its
+/// dictionary and block settings.  A regular-file `input` supplies the
+/// content size when `prefs.content_size_flag` is set; other inputs are read
+/// to end of stream.  Path-only steps — overwrite checks, temporary outputs,
+/// metadata copying, `--rm` — do not apply.
+///
+/// ```no_run
+/// use std::fs::File;
+/// use lz4::io::{compress_fd, Prefs};
+///
+/// // Any `AsFd` works: an inherited `OwnedFd`, a `UnixStream`, stdout.
+/// let input = File::open("access.log")?;
+/// let stats = compress_fd(&input, std::io::stdout(), 1, &Prefs::default())?;
+/// eprintln!("{} -> {} bytes", stats.bytes_in, stats.bytes_out);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(unix)]
+pub fn compress_fd(
+    input: impl std::os::fd::AsFd,
+    output: impl std::os::fd::AsFd,
+    compression_level: i32,
+    prefs: &Prefs,
+) -> io::Result<CompressStats> {
+    compress_open_file(
+        file_from_fd(input.as_fd())?,
+        file_from_fd(output.as_fd())?,
+        compression_level,
+        prefs,
+    )
+}
+
+/// Windows counterpart of `compress_fd`: compresses from one handle into
+/// another, which stay open and owned by the caller.
+#[cfg(windows)]
+pub fn compress_handle(
+    input: impl std::os::windows::io::AsHandle,
+    output: impl std::os::windows::io::AsHandle,
+    compression_level: i32,
+    prefs: &Prefs,
+) -> io::Result<CompressStats> {
+    compress_open_file(
+        file_from_handle(input.as_handle())?,
+        file_from_handle(output.as_handle())?,
+        compression_level,
+        prefs,
+    )
+}
+
+/// The body of [`compress_fd`] and `compress_handle`, once both ends are
+/// `File`s.
+#[cfg(any(unix, windows))]
+fn compress_open_file(
+    mut src: fs::File,
+    mut dst: fs::File,
+    compression_level: i32,
+    io_prefs: &Prefs,
+) -> io::Result<CompressStats> {
+    let mut ress = CompressResources::new(io_prefs)?;
+    let block_size = effective_block_size(io_prefs);
+
+    let mut prefs = ress.prepared_prefs;
+    prefs.compression_level = compression_level;
+    let size_hint = remaining_size_hint(&mut src);
+    apply_block_size_policy(io_prefs, &mut prefs, size_hint);
+    if io_prefs.content_size_flag {
+        prefs.frame_info.content_size = size_hint.unwrap_or(0);
+        if size_hint.is_none() {
+            diag::warning(3, "Warning : cannot determine input content size \n");
+        }
+    }
+
+    let mut reader = ReadAhead::with_capacity(src, io_prefs.io_buffer_size);
+    let (bytes_in, bytes_out) =
+        compress_stream(&mut ress, &mut reader, &mut dst, &prefs, block_size, false)?;
+    dst.flush()?;
+
+    let block_size_downgrade = if bytes_in < block_size as u64 {
+        block_size_notice(io_prefs, &prefs, bytes_in)
+    } else {
+        None
+    };
+    display_level(
+        2,
+        &format!(
+            "Compressed {} bytes into {} bytes ==> {:.2}%\n",
+            bytes_in,
+            bytes_out,
+            bytes_out as f64 / bytes_in.max(1) as f64 * 100.0,
+        ),
+    );
+    Ok(CompressStats {
+        bytes_in,
+        bytes_out,
+        block_size_downgrade,
     })
 }
 
@@ -714,6 +1069,11 @@ pub fn compress_multiple_filenames(
         .is_err()
         {
             missed_files += 1;
+            // Nobody is reading stdout any more; the remaining files would
+            // only fail the same way.
+            if stdout_closed() {
+                break;
+            }
         }
 
         total_processed += processed;
@@ -725,11 +1085,113 @@ pub fn compress_multiple_filenames(
     Ok(missed_files)
 }
 
+// ---------------------------------------------------------------------------
+// Public: compress_multiple_filenames_to_dir — `--output-dir`
+// ---------------------------------------------------------------------------
+
+/// Compress multiple files into `out_dir`, naming each output after its
+/// source plus `suffix`.
+///
+/// With `mirror_tree` (`-r --output-dir`) each source keeps its directory
+/// structure under `out_dir` (see [`crate::util::mirror_path`]) and missing
+/// intermediate directories are created; otherwise every output lands
+/// directly in `out_dir` under the source's file name.
+///
+/// Two sources that map to the same output in one run (`a/x` and `b/x`
+/// flattened, or `x` and `../x` mirrored) are not allowed to overwrite each
+/// other: the first one wins and each later one is reported and counted as
+/// missed.  Outputs that already existed before the run follow the usual
+/// overwrite rules.
+///
+/// Returns the number of files that could not be compressed, like
+/// [`compress_multiple_filenames`].
+pub fn compress_multiple_filenames_to_dir(
+    srcs: &[&str],
+    out_dir: &str,
+    mirror_tree: bool,
+    suffix: &str,
+    compression_level: i32,
+    prefs: &Prefs,
+) -> io::Result<usize> {
+    let time_start = get_time();
+    let cpu_start = unsafe { clock() };
+    let mut ress = CompressResources::new(prefs)?;
+    let mut total_processed: u64 = 0;
+    let mut missed_files: usize = 0;
+    let mut claimed: HashMap<PathBuf, &str> = HashMap::new();
+
+    fs::create_dir_all(out_dir)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", out_dir, e)))?;
+
+    for &src_name in srcs {
+        let src_path = Path::new(src_name);
+        let mut dst_path = if mirror_tree {
+            crate::util::mirror_path(src_path, Path::new(out_dir))
+        } else {
+            match src_path.file_name() {
+                Some(name) => Path::new(out_dir).join(name),
+                None => {
+                    display_level(
+                        1,
+                        &format!("lz4: {}: cannot name an output for this path \n", src_name),
+                    );
+                    missed_files += 1;
+                    continue;
+                }
+            }
+        };
+        dst_path.as_mut_os_string().push(suffix);
+
+        if let Some(first) = claimed.get(&dst_path) {
+            display_level(
+                1,
+                &format!(
+                    "lz4: {}: {} is already the output of {}; skipped \n",
+                    src_name,
+                    dst_path.display(),
+                    first
+                ),
+            );
+            missed_files += 1;
+            continue;
+        }
+        claimed.insert(dst_path.clone(), src_name);
+
+        if let Some(parent) = dst_path.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                display_level(1, &format!("lz4: {}: {} \n", parent.display(), e));
+                missed_files += 1;
+                continue;
+            }
+        }
+
+        let mut processed: u64 = 0;
+        if compress_filename_ext(
+            &mut processed,
+            &mut ress,
+            src_name,
+            &dst_path.to_string_lossy(),
+            compression_level,
+            prefs,
+        )
+        .is_err()
+        {
+            missed_files += 1;
+        }
+        total_processed += processed;
+    }
+
+    final_time_display(time_start, cpu_start, total_processed);
+
+    Ok(missed_files)
+}
+
 // ---------------------------------------------------------------------------
 // Unit tests
 // ---------------------------------------------------------------------------
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
     use crate::io::prefs::Prefs;
@@ -760,6 +1222,31 @@ mod tests {
         assert!(ress.cdict.is_some());
     }
 
+    // ── compress_stream ───────────────────────────────────────────────────────
+
+    #[test]
+    fn compress_stream_drops_understated_content_size() {
+        let prefs = Prefs::default();
+        let mut ress = CompressResources::new(&prefs).unwrap();
+        let mut frame_prefs = ress.prepared_prefs;
+        frame_prefs.frame_info.content_size = 4096;
+        let src = crate::lorem::gen_buffer(200 * KB, 3);
+        let mut dst = Vec::new();
+
+        let (read, written) = compress_stream(
+            &mut ress,
+            &mut &src[..],
+            &mut dst,
+            &frame_prefs,
+            64 * KB,
+            false,
+        )
+        .expect("a stale stat size must not fail compression");
+        assert_eq!(read, src.len() as u64);
+        assert_eq!(written, dst.len() as u64);
+        assert_eq!(crate::frame::decompress_frame_to_vec(&dst).unwrap(), src);
+    }
+
     // ── load_dict_file ────────────────────────────────────────────────────────
 
     #[test]
@@ -806,6 +1293,36 @@ mod tests {
         assert_eq!(effective_block_size(&p), 4 * MB);
     }
 
+    // ── block_size_notice ─────────────────────────────────────────────────────
+
+    #[test]
+    fn compress_filename_reports_fixed_block_size_downgrade() {
+        use crate::frame::types::BlockSizeId;
+
+        let dir = TempDir::new().unwrap();
+        let src_path = dir.path().join("small.bin");
+        let dst_path = dir.path().join("small.lz4");
+        std::fs::write(&src_path, vec![7u8; 100_000]).unwrap();
+        let (src, dst) = (src_path.to_str().unwrap(), dst_path.to_str().unwrap());
+
+        let mut prefs = Prefs::default();
+        prefs.overwrite = true;
+        let auto = compress_filename(src, dst, 1, &prefs).unwrap();
+        assert_eq!(auto.block_size_downgrade, None);
+
+        prefs.set_block_size_id(7);
+        let fixed = compress_filename(src, dst, 1, &prefs).unwrap();
+        assert_eq!(
+            fixed.block_size_downgrade,
+            Some(BlockSizeDowngrade {
+                requested: BlockSizeId::Max4Mb,
+                used: BlockSizeId::Max256Kb,
+            })
+        );
+        // The header declares the block size actually used (BD byte, bits 4-6).
+        assert_eq!(std::fs::read(&dst_path).unwrap()[5] >> 4, 5);
+    }
+
     // ── compress_filename round-trip ──────────────────────────────────────────
 
     #[test]
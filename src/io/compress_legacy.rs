@@ -23,10 +23,11 @@
 use std::io::{self, Read, Write};
 
 use crate::block::compress::{compress_bound, compress_fast};
-use crate::io::file_io::{open_dst_file, open_src_file, STDOUT_MARK};
+use crate::io::file_io::{check_regular_src, open_dst_file, open_src_file_buffered, STDOUT_MARK};
 use crate::io::prefs::{
     final_time_display, Prefs, LEGACY_BLOCKSIZE, LEGACY_MAGICNUMBER, MAGICNUMBER_SIZE,
 };
+use crate::io::progress;
 use crate::timefn::get_time;
 
 extern "C" {
@@ -124,8 +125,15 @@ fn compress_legacy_internal(
     compressionlevel: i32,
     prefs: &Prefs,
 ) -> io::Result<LegacyResult> {
-    let mut src_reader = open_src_file(input_filename)?;
-    let mut dst_file = open_dst_file(output_filename, prefs)?;
+    check_regular_src(input_filename, prefs)?;
+    let tracker = progress::start(prefs, input_filename);
+    let mut src_reader = progress::wrap_reader(
+        open_src_file_buffered(input_filename, prefs.io_buffer_size)?,
+        tracker.as_ref(),
+    );
+    let mut dst = open_dst_file(output_filename, prefs)?;
+    let pending = dst.pending.take();
+    let mut dst_file = progress::wrap_writer(Box::new(dst), tracker.as_ref());
 
     // Write the 4-byte little-endian legacy magic number that opens the archive.
     let magic_bytes = LEGACY_MAGICNUMBER.to_le_bytes();
@@ -174,6 +182,11 @@ fn compress_legacy_internal(
     }
 
     dst_file.flush()?;
+    drop(dst_file);
+    if let Some(pending) = pending {
+        pending.commit()?;
+    }
+    progress::finish(tracker.as_ref());
 
     // Report the compression ratio to the user.
     let ratio = if bytes_read == 0 {
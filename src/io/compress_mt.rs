@@ -24,12 +24,12 @@
 //! Files smaller than `CHUNK_SIZE` take a fast single-block path and skip
 //! the batch machinery entirely.
 
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::fs;
 use std::io::{self, Read, Write};
 use std::path::Path;
 use std::sync::Mutex;
-use std::time::SystemTime;
 
 use rayon::prelude::*;
 
@@ -37,10 +37,19 @@ use crate::frame::compress::lz4f_compress_begin;
 use crate::frame::header::lz4f_compress_frame_bound;
 use crate::frame::types::{BlockMode, ContentChecksum};
 use crate::frame::{lz4f_compress_frame_using_cdict, Lz4FCDict};
-use crate::io::compress_frame::{compress_frame_chunk, CfcParameters, CompressResources};
-use crate::io::file_io::{open_dst_file, open_src_file, NUL_MARK, STDIN_MARK};
+use crate::io::compress_frame::{
+    apply_block_size_policy, block_size_notice, compress_frame_chunk, CfcParameters,
+    CompressResources,
+};
+use crate::io::diag;
+use crate::io::file_io::{
+    check_regular_src, copy_file_stat, open_dst_file, open_src_file_buffered, src_size_hint,
+    NUL_MARK, STDIN_MARK,
+};
+use crate::io::incremental::skip_up_to_date;
 use crate::io::prefs::{display_level, Prefs, KB, MB};
-use crate::util::set_file_stat;
+use crate::io::progress;
+use crate::util::is_reg_file;
 use crate::xxhash::Xxh32State;
 
 // ---------------------------------------------------------------------------
@@ -109,6 +118,8 @@ struct WriteRegister {
     total_csize: u64,
     /// Block size used for the progress display denominator.
     block_size: usize,
+    /// Print the built-in progress line; off when a progress sink is installed.
+    display_progress: bool,
 }
 
 impl WriteRegister {
@@ -119,6 +130,7 @@ impl WriteRegister {
             pending: Mutex::new(BTreeMap::new()),
             total_csize: 0,
             block_size,
+            display_progress: true,
         }
     }
 
@@ -151,7 +163,7 @@ impl WriteRegister {
             self.total_csize += data.len() as u64;
             drop(pending); // Release lock while writing.
             write_fn(&data)?;
-            {
+            if self.display_progress {
                 let processed = self.expected_rank * self.block_size as u64;
                 let ratio = if processed > 0 {
                     self.total_csize as f64 / processed as f64 * 100.0
@@ -191,36 +203,63 @@ fn read_to_capacity(reader: &mut dyn Read, buf: &mut [u8]) -> io::Result<usize>
     Ok(total)
 }
 
-// ---------------------------------------------------------------------------
-// copy_file_stat — copies mtime and, on Unix, uid/gid/mode from src to dst.
-// ---------------------------------------------------------------------------
-
-fn copy_file_stat(src: &str, dst: &str) -> io::Result<()> {
-    let m = fs::metadata(src)?;
-    let mtime = m.modified().unwrap_or(SystemTime::UNIX_EPOCH);
-
-    #[cfg(unix)]
-    let (uid, gid, mode) = {
-        use std::os::unix::fs::MetadataExt;
-        (m.uid(), m.gid(), m.mode())
-    };
-    #[cfg(not(unix))]
-    let (uid, gid, mode) = (0u32, 0u32, 0o644u32);
-
-    set_file_stat(Path::new(dst), mtime, uid, gid, mode)
-}
-
 // ---------------------------------------------------------------------------
 // Chunk — internal data unit for the MT pipeline
 // ---------------------------------------------------------------------------
 
 /// A single read chunk with metadata needed for parallel compression.
-struct Chunk {
+///
+/// Chunks of a memory-mapped input borrow the mapping; chunks read from a
+/// stream own their bytes.
+struct Chunk<'a> {
     /// Raw (uncompressed) chunk data.
-    data: Vec<u8>,
+    data: Cow<'a, [u8]>,
     /// Last PREFIX_SIZE bytes of the previous chunk, used as dict in linked mode.
     /// `None` for the first chunk or when blockMode == Independent.
-    prefix: Option<Vec<u8>>,
+    prefix: Option<Cow<'a, [u8]>>,
+}
+
+/// Yields the input in `CHUNK_SIZE` pieces.
+enum ChunkSource<'a> {
+    /// Each chunk is read into a fresh buffer.
+    Reader(Box<dyn Read + 'a>),
+    /// Each chunk is a zero-copy slice of the mapped input.
+    #[cfg(all(feature = "mmap", unix))]
+    Mapped {
+        data: &'a [u8],
+        tracker: Option<&'a std::sync::Arc<progress::ProgressTracker>>,
+    },
+}
+
+impl<'a> ChunkSource<'a> {
+    /// Next chunk of at most `CHUNK_SIZE` bytes; empty at end of input.
+    fn next_chunk(&mut self) -> io::Result<Cow<'a, [u8]>> {
+        match self {
+            ChunkSource::Reader(reader) => {
+                let mut buf = vec![0u8; CHUNK_SIZE];
+                let n = read_to_capacity(&mut **reader, &mut buf)?;
+                buf.truncate(n);
+                Ok(Cow::Owned(buf))
+            }
+            #[cfg(all(feature = "mmap", unix))]
+            ChunkSource::Mapped { data, tracker } => {
+                let (chunk, rest) = data.split_at(CHUNK_SIZE.min(data.len()));
+                *data = rest;
+                progress::record_read(*tracker, chunk.len());
+                // Prefetch the next chunk while this batch compresses.
+                crate::frame::advise::will_need(&rest[..CHUNK_SIZE.min(rest.len())]);
+                Ok(Cow::Borrowed(chunk))
+            }
+        }
+    }
+}
+
+/// The last `PREFIX_SIZE` bytes of `chunk`, borrowed when `chunk` is.
+fn chunk_suffix<'a>(chunk: &Cow<'a, [u8]>) -> Cow<'a, [u8]> {
+    match chunk {
+        Cow::Borrowed(b) => Cow::Borrowed(&b[b.len() - PREFIX_SIZE..]),
+        Cow::Owned(v) => Cow::Owned(v[v.len() - PREFIX_SIZE..].to_vec()),
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -241,32 +280,68 @@ pub fn compress_filename_mt(
     compression_level: i32,
     io_prefs: &Prefs,
 ) -> io::Result<()> {
-    let mut src_reader = open_src_file(src_filename)?;
-    let dst_file = open_dst_file(dst_filename, io_prefs)?;
+    if skip_up_to_date(src_filename, dst_filename, io_prefs) {
+        *in_stream_size = 0;
+        return Ok(());
+    }
+    check_regular_src(src_filename, io_prefs)?;
+    // FIFOs and devices have no size, and their metadata describes the node
+    // rather than the data read from it: it is neither copied nor removed.
+    let src_is_file = is_reg_file(Path::new(src_filename));
+    let tracker = progress::start(io_prefs, src_filename);
+    // With the `mmap` feature, large regular files are mapped and their chunks
+    // handed to the workers as slices; anything else is read.
+    #[cfg(all(feature = "mmap", unix))]
+    let mapped = crate::io::mmap::MappedFile::open(src_filename)
+        .ok()
+        .flatten();
+    #[cfg(all(feature = "mmap", unix))]
+    let mut src = match &mapped {
+        Some(map) => ChunkSource::Mapped {
+            data: map.as_slice(),
+            tracker: tracker.as_ref(),
+        },
+        None => ChunkSource::Reader(progress::wrap_reader(
+            open_src_file_buffered(src_filename, io_prefs.io_buffer_size)?,
+            tracker.as_ref(),
+        )),
+    };
+    #[cfg(not(all(feature = "mmap", unix)))]
+    let mut src = ChunkSource::Reader(progress::wrap_reader(
+        open_src_file_buffered(src_filename, io_prefs.io_buffer_size)?,
+        tracker.as_ref(),
+    ));
+    let mut dst_file = open_dst_file(dst_filename, io_prefs)?;
     let dst_is_stdout = dst_file.is_stdout;
-    let mut dst_writer: Box<dyn Write> = Box::new(dst_file);
+    let pending = dst_file.pending.take();
+    let mut dst_writer = progress::wrap_writer(Box::new(dst_file), tracker.as_ref());
 
     // Build per-call preferences: inherit global settings, then apply call-site overrides.
     let mut prefs = ress.prepared_prefs;
     prefs.compression_level = compression_level;
-    if io_prefs.content_size_flag {
-        let file_size = if src_filename != STDIN_MARK {
-            fs::metadata(src_filename).map(|m| m.len()).unwrap_or(0)
-        } else {
-            0
-        };
+    // A mapped input's size is the length mapped, not a second stat.
+    #[cfg(all(feature = "mmap", unix))]
+    let size_hint = match &mapped {
+        Some(map) => Some(map.len() as u64),
+        None => src_size_hint(src_filename),
+    };
+    #[cfg(not(all(feature = "mmap", unix)))]
+    let size_hint = src_size_hint(src_filename);
+    apply_block_size_policy(io_prefs, &mut prefs, size_hint);
+    // Incremental runs compare the recorded size on the next pass.
+    if io_prefs.content_size_flag || io_prefs.mtime_window.is_some() {
+        let file_size = size_hint.unwrap_or(0);
         prefs.frame_info.content_size = file_size;
         if file_size == 0 {
-            display_level(3, "Warning : cannot determine input content size \n");
+            diag::warning(3, "Warning : cannot determine input content size \n");
         }
     }
 
     let cdict_ptr = ress.cdict_ptr();
 
     // Read the first chunk to decide whether the single-block or multi-block path applies.
-    let mut first_buf = vec![0u8; CHUNK_SIZE];
-    let read_size = read_to_capacity(&mut *src_reader, &mut first_buf)?;
-    first_buf.truncate(read_size);
+    let first_buf = src.next_chunk()?;
+    let read_size = first_buf.len();
 
     let mut filesize: u64 = read_size as u64;
     let mut compressedfilesize: u64 = 0;
@@ -286,14 +361,16 @@ pub fn compress_filename_mt(
         .map_err(|e| io::Error::other(format!("Compression failed: {}", e)))?;
         compressedfilesize = c_size as u64;
 
-        display_level(
-            2,
-            &format!(
-                "\rRead : {} MiB   ==> {:.2}%   ",
-                filesize >> 20,
-                compressedfilesize as f64 / filesize.max(1) as f64 * 100.0,
-            ),
-        );
+        if io_prefs.builtin_progress() {
+            display_level(
+                2,
+                &format!(
+                    "\rRead : {} MiB   ==> {:.2}%   ",
+                    filesize >> 20,
+                    compressedfilesize as f64 / filesize.max(1) as f64 * 100.0,
+                ),
+            );
+        }
 
         dst_writer.write_all(&dst_buf[..c_size]).map_err(|_| {
             io::Error::new(
@@ -304,6 +381,13 @@ pub fn compress_filename_mt(
     } else {
         // Multi-block path: read, compress in parallel, and write in bounded batches.
 
+        // Never declare a stat size that the first chunk already exceeds
+        // (pseudo-files such as sysfs attributes report a fixed 4096).
+        if prefs.frame_info.content_size != 0 && prefs.frame_info.content_size < filesize {
+            diag::warning(3, "Warning : input larger than its reported size \n");
+            prefs.frame_info.content_size = 0;
+        }
+
         let linked_blocks = prefs.frame_info.block_mode == BlockMode::Linked;
         let use_checksum = prefs.frame_info.content_checksum_flag == ContentChecksum::Enabled;
 
@@ -340,6 +424,7 @@ pub fn compress_filename_mt(
         // Wrap cdict_ptr in a Sync+Send newtype so rayon closures can capture it.
         let sync_cdict = SyncCDictPtr(cdict_ptr);
         let mut write_register = WriteRegister::new(CHUNK_SIZE);
+        write_register.display_progress = io_prefs.builtin_progress();
 
         // xxh32 accumulates content checksum over raw input bytes.
         let mut xxh32 = if use_checksum {
@@ -353,8 +438,8 @@ pub fn compress_filename_mt(
         // last_suffix: the final PREFIX_SIZE bytes of the most-recently-read chunk.
         // In linked-block mode this slice is given to the *next* chunk as its
         // prefix dictionary, so each block can reference data from its predecessor.
-        let mut last_suffix: Option<Vec<u8>> = if linked_blocks && read_size >= PREFIX_SIZE {
-            Some(first_buf[read_size - PREFIX_SIZE..].to_vec())
+        let mut last_suffix: Option<Cow<[u8]>> = if linked_blocks && read_size >= PREFIX_SIZE {
+            Some(chunk_suffix(&first_buf))
         } else {
             None
         };
@@ -382,13 +467,12 @@ pub fn compress_filename_mt(
 
             // Read additional chunks to fill the batch.
             while !eof && batch.len() < batch_size {
-                let mut buf = vec![0u8; CHUNK_SIZE];
-                let n = read_to_capacity(&mut *src_reader, &mut buf)?;
+                let buf = src.next_chunk()?;
+                let n = buf.len();
                 if n == 0 {
                     eof = true;
                     break;
                 }
-                buf.truncate(n);
                 filesize += n as u64;
 
                 if let Some(ref mut h) = xxh32 {
@@ -398,7 +482,7 @@ pub fn compress_filename_mt(
                 // Prefix for this chunk = suffix of the previous chunk.
                 let prefix = last_suffix.take();
                 if linked_blocks && n >= PREFIX_SIZE {
-                    last_suffix = Some(buf[n - PREFIX_SIZE..].to_vec());
+                    last_suffix = Some(chunk_suffix(&buf));
                 }
 
                 let short = n < CHUNK_SIZE;
@@ -485,21 +569,39 @@ pub fn compress_filename_mt(
 
     // Flush and close the destination file before touching its metadata.
     drop(dst_writer);
+    if let Some(pending) = pending {
+        pending.commit()?;
+    }
+    progress::finish(tracker.as_ref());
 
     // Propagate mtime and, on Unix, uid/gid/mode from source to destination.
-    if src_filename != STDIN_MARK && !dst_is_stdout && dst_filename != NUL_MARK {
-        let _ = copy_file_stat(src_filename, dst_filename);
+    if src_is_file && src_filename != STDIN_MARK && !dst_is_stdout && dst_filename != NUL_MARK {
+        if let Ok(meta) = fs::metadata(src_filename) {
+            copy_file_stat(&meta, dst_filename, io_prefs);
+        }
     }
 
     // Remove the source file when `--rm` is active.
     if io_prefs.remove_src_file && src_filename != STDIN_MARK {
-        fs::remove_file(src_filename).map_err(|e| {
-            io::Error::new(e.kind(), format!("Remove error : {}: {}", src_filename, e))
-        })?;
+        if src_is_file {
+            fs::remove_file(src_filename).map_err(|e| {
+                io::Error::new(e.kind(), format!("Remove error : {}: {}", src_filename, e))
+            })?;
+        } else {
+            display_level(
+                2,
+                &format!("lz4: {}: not a regular file, not removed \n", src_filename),
+            );
+        }
     }
 
     // Print the final compression-ratio summary line.
     display_level(2, &format!("\r{:79}\r", ""));
+    ress.block_size_downgrade = if filesize < CHUNK_SIZE as u64 {
+        block_size_notice(io_prefs, &prefs, filesize)
+    } else {
+        None
+    };
     display_level(
         2,
         &format!(
@@ -519,6 +621,7 @@ pub fn compress_filename_mt(
 // ---------------------------------------------------------------------------
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
     use crate::io::compress_frame::CompressResources;
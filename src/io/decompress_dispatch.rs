@@ -9,10 +9,14 @@
 //! - [`pass_through`] for unrecognised headers when pass-through mode is active
 //! - Skippable frames (`0x184D2A50`–`0x184D2A5F`) are silently discarded
 //!
-//! The public API exposes two functions:
+//! The public API exposes these functions:
 //! - [`decompress_filename`] — decompresses a single source/destination pair
+//! - `decompress_fd` / `decompress_handle` — the same between caller-owned
+//!   descriptors (Unix) or handles (Windows)
 //! - [`decompress_multiple_filenames`] — decompresses a list of source files,
 //!   deriving destination names by stripping a suffix (e.g., `.lz4`)
+//! - [`test_multiple_filenames`] — decodes a list of source files without
+//!   writing anything, reporting OK or FAILED for each (`lz4 -t`)
 //!
 //! Corresponds to `LZ4IO_passThrough`, `skipStream`, `selectDecoder`,
 //! `LZ4IO_decompressSrcFile`, `LZ4IO_decompressDstFile`,
@@ -54,9 +58,8 @@
 //!   All errors are returned as `io::Error` with descriptive messages so that
 //!   callers can handle or propagate them without terminating the process.
 
-use std::fs::{self, File, OpenOptions};
+use std::fs::{self, File};
 use std::io::{self, Read, Write};
-use std::path::Path;
 use std::sync::atomic::Ordering;
 
 // `libc::clock` is not exposed directly on macOS/Linux via the `libc` crate
@@ -69,13 +72,22 @@ extern "C" {
 use crate::io::decompress_frame::decompress_lz4f;
 use crate::io::decompress_legacy::decode_legacy_stream;
 use crate::io::decompress_resources::DecompressResources;
+use crate::io::diag;
+#[cfg(unix)]
+use crate::io::file_io::file_from_fd;
+#[cfg(windows)]
+use crate::io::file_io::file_from_handle;
 use crate::io::file_io::{
-    is_skippable_magic_number, open_src_file, NUL_MARK, STDIN_MARK, STDOUT_MARK,
+    copy_file_stat, is_skippable_magic_number, open_regular_dst, open_src_file_buffered,
+    stdout_closed, StdoutSink, NUL_MARK, STDIN_MARK, STDOUT_MARK,
 };
+use crate::io::flush::FlushEvery;
 use crate::io::prefs::{
     display_level, final_time_display, Prefs, DISPLAY_LEVEL, LEGACY_MAGICNUMBER, LZ4IO_MAGICNUMBER,
     LZ4IO_SKIPPABLE0, MAGICNUMBER_SIZE,
 };
+use crate::io::progress;
+use crate::io::read_ahead::ReadAhead;
 use crate::io::sparse::{fwrite_sparse, fwrite_sparse_end, SPARSE_SEGMENT_SIZE};
 use crate::timefn::get_time;
 
@@ -250,9 +262,8 @@ fn decompress_loop<R: Read, W: Write>(
             // Use a single-byte read to distinguish clean EOF from mid-read EOF
             // (mirrors `if (nbReadBytes==0) { nbFrames = 0; return ENDOFSTREAM; }`
             // at lz4io.c:2357).
-            match src.read(&mut mb[..1])? {
-                0 => break, // Clean EOF — end of stream.
-                _ => {}
+            if src.read(&mut mb[..1])? == 0 {
+                break; // Clean EOF — end of stream.
             }
             if let Err(e) = src.read_exact(&mut mb[1..]) {
                 return Err(io::Error::new(
@@ -354,75 +365,84 @@ fn decompress_src_file<W: Write>(
     prefs: &Prefs,
     resources: &mut DecompressResources,
 ) -> io::Result<u64> {
-    let mut src = open_src_file(src_path)?; // Box<dyn Read>: Read via impl<R: Read + ?Sized> Read for Box<R>
-    let filesize = decompress_loop(&mut src, dst, prefs, resources)?;
-
-    // `--rm`: remove source file after successful decompression (lz4io.c:2430–2432).
-    if prefs.remove_src_file {
-        fs::remove_file(src_path)
-            .map_err(|e| io::Error::new(e.kind(), format!("Remove error : {}: {}", src_path, e)))?;
-    }
+    let tracker = progress::start(prefs, src_path);
+    // Box<dyn Read>: Read via impl<R: Read + ?Sized> Read for Box<R>
+    let mut src = progress::wrap_reader(
+        open_src_file_buffered(src_path, prefs.io_buffer_size)?,
+        tracker.as_ref(),
+    );
+    let filesize = match tracker {
+        Some(ref t) => {
+            let mut counted = progress::wrap_writer(Box::new(&mut *dst), Some(t));
+            decompress_flushing(&mut src, &mut counted, prefs, resources)?
+        }
+        None => decompress_flushing(&mut src, dst, prefs, resources)?,
+    };
+    progress::finish(tracker.as_ref());
 
-    // Progress display (lz4io.c:2436–2437).
+    // Progress display (lz4io.c:2436–2437).  Test mode reports each file
+    // itself, in `test_multiple_filenames`.
     if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 2 {
         display_level(2, &format!("\r{:79}\r", ""));
-        display_level(
-            2,
-            &format!("{:<30.30} : decoded {} bytes \n", src_path, filesize),
-        );
+        if !prefs.test_mode {
+            display_level(
+                2,
+                &format!("{:<30.30} : decoded {} bytes \n", src_path, filesize),
+            );
+        }
     }
 
     Ok(filesize)
 }
 
-// ---------------------------------------------------------------------------
-// open_regular_dst — overwrite-checked file open for decompress_dst_file
-// ---------------------------------------------------------------------------
-
-/// Opens a regular destination file, honouring the `overwrite` preference.
+/// `--rm`: removes `src_path` once its output is complete (lz4io.c:2430–2432).
 ///
-/// Inlines the overwrite-prompt logic from `LZ4IO_openDstFile` (lz4io.c
-/// lines 2455, 419–435) but returns a raw `File` rather than a `FILE*`
-/// so that the caller can wrap it in `SparseWriter`.
-fn open_regular_dst(dst_path: &str, prefs: &Prefs) -> io::Result<File> {
-    if !prefs.overwrite && Path::new(dst_path).exists() {
-        let level = DISPLAY_LEVEL.load(Ordering::Relaxed);
-        if level <= 1 {
-            eprintln!("{} already exists; not overwritten  ", dst_path);
-            return Err(io::Error::new(
-                io::ErrorKind::AlreadyExists,
-                format!("{}: already exists; not overwritten", dst_path),
-            ));
-        }
-        // Interactive prompt (lz4io.c:422–436).
-        eprint!(
-            "{} already exists; do you want to overwrite (y/N) ? ",
-            dst_path
-        );
-        let _ = io::stderr().flush();
-        let mut line = String::new();
-        io::stdin().read_line(&mut line)?;
-        let first = line.trim_start().chars().next().unwrap_or('\0');
-        if first != 'y' && first != 'Y' {
-            eprintln!("    not overwritten  ");
-            return Err(io::Error::new(
-                io::ErrorKind::AlreadyExists,
-                format!("{}: not overwritten", dst_path),
-            ));
+/// Callers run this after the output has been committed, so the source is
+/// never removed while the only other copy is still a temporary file.  A file
+/// recovered with `--recover` lost data (`damaged`), so it is kept.
+fn remove_decoded_src(src_path: &str, prefs: &Prefs, damaged: bool) -> io::Result<()> {
+    if prefs.remove_src_file && !damaged {
+        fs::remove_file(src_path)
+            .map_err(|e| io::Error::new(e.kind(), format!("Remove error : {}: {}", src_path, e)))?;
+    }
+    Ok(())
+}
+
+/// Runs [`decompress_loop`], routing output through [`FlushEvery`] when
+/// `prefs.flush_every` is set.
+fn decompress_flushing<R: Read, W: Write>(
+    src: &mut R,
+    dst: &mut W,
+    prefs: &Prefs,
+    resources: &mut DecompressResources,
+) -> io::Result<u64> {
+    match prefs.flush_every {
+        Some(interval) => {
+            let mut flushing = FlushEvery::new(dst, interval);
+            let filesize = decompress_loop(src, &mut flushing, prefs, resources)?;
+            flushing.flush()?;
+            Ok(filesize)
         }
+        None => decompress_loop(src, dst, prefs, resources),
     }
+}
 
-    OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(dst_path)
-        .map_err(|e| {
-            if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 1 {
-                eprintln!("{}: {}", dst_path, e);
-            }
-            e
-        })
+/// Opens stdout as a decompression destination.
+///
+/// `std`'s `Stdout` is line-buffered and splits a write at its last newline.
+/// With `prefs.flush_every` set on Unix, output instead goes to a duplicate of
+/// descriptor 1, so each flushed unit reaches the pipe or `O_APPEND` file in
+/// one `write_all`.
+fn stdout_dst(prefs: &Prefs) -> io::Result<Box<dyn Write>> {
+    #[cfg(unix)]
+    if prefs.flush_every.is_some() {
+        use std::os::fd::AsFd;
+        let fd = io::stdout().as_fd().try_clone_to_owned()?;
+        return Ok(Box::new(StdoutSink(File::from(fd))));
+    }
+    #[cfg(not(unix))]
+    let _ = prefs;
+    Ok(Box::new(StdoutSink(io::stdout())))
 }
 
 // ---------------------------------------------------------------------------
@@ -430,7 +450,8 @@ fn open_regular_dst(dst_path: &str, prefs: &Prefs) -> io::Result<File> {
 // ---------------------------------------------------------------------------
 
 /// Opens `dst_path` for writing, calls `decompress_src_file`, and copies
-/// file metadata (mtime, permissions) from the source to the destination.
+/// file metadata (ownership, permissions, mtime) from the source to the
+/// destination.
 ///
 /// Returns the total decompressed byte count.
 ///
@@ -450,17 +471,20 @@ fn decompress_dst_file(
     };
 
     // ── Open destination and decompress ──────────────────────────────────────
+    let damaged_before = resources.damaged_blocks;
     let filesize = if dst_path == STDOUT_MARK {
         // Write to stdout (no sparse).
-        let mut dst = io::stdout();
+        let mut dst = stdout_dst(prefs)?;
         decompress_src_file(src_path, &mut dst, prefs, resources)?
     } else if dst_path == NUL_MARK {
         // Discard output (no sparse).
         let mut dst = io::sink();
         decompress_src_file(src_path, &mut dst, prefs, resources)?
     } else {
-        // Regular file: sparse-write-capable output.
-        let file = open_regular_dst(dst_path, prefs)?;
+        // Regular file: sparse-write-capable output.  Same overwrite check as
+        // `open_dst_file` (lz4io.c lines 2455, 419–435), but a raw `File` so
+        // that it can be wrapped in `SparseWriter`.
+        let (file, pending) = open_regular_dst(dst_path, prefs)?;
         // C: `sparseMode = (sparseFileSupport - (f==stdout)) > 0`
         // Since `f != stdout` here: `sparseMode = prefs->sparseFileSupport > 0`.
         let sparse_mode = prefs.sparse_file_support > 0;
@@ -471,6 +495,10 @@ fn decompress_dst_file(
         let finish_result = sparse_writer.finish();
         let sz = result?;
         finish_result?;
+        drop(sparse_writer);
+        if let Some(pending) = pending {
+            pending.commit()?;
+        }
         sz
     };
 
@@ -478,15 +506,10 @@ fn decompress_dst_file(
     let is_special_dst = dst_path == STDOUT_MARK || dst_path == NUL_MARK;
     if !is_special_dst {
         if let Some(meta) = &src_stat {
-            // Modification time via the `filetime` crate.
-            if let Ok(mtime) = meta.modified() {
-                let ft = filetime::FileTime::from_system_time(mtime);
-                let _ = filetime::set_file_mtime(dst_path, ft);
-            }
-            // Permissions via std::fs.
-            let _ = fs::set_permissions(dst_path, meta.permissions());
+            copy_file_stat(meta, dst_path, prefs);
         }
     }
+    remove_decoded_src(src_path, prefs, resources.damaged_blocks != damaged_before)?;
 
     Ok(filesize)
 }
@@ -518,9 +541,12 @@ pub fn decompress_filename(src: &str, dst: &str, prefs: &Prefs) -> io::Result<De
 
     // C lz4io.c:2491–2492: LZ4IO_finalTimeDisplay is called only on error.
     match result {
-        Ok(bytes) => Ok(DecompressStats {
-            decompressed_bytes: bytes,
-        }),
+        Ok(bytes) => {
+            check_damage(&resources)?;
+            Ok(DecompressStats {
+                decompressed_bytes: bytes,
+            })
+        }
         Err(e) => {
             final_time_display(time_start, cpu_start, 0);
             Err(e)
@@ -528,6 +554,57 @@ pub fn decompress_filename(src: &str, dst: &str, prefs: &Prefs) -> io::Result<De
     }
 }
 
+/// Decompresses everything readable from the descriptor `input` into
+/// `output`.
+///
+/// The descriptor counterpart of [`decompress_filename`], for daemons that
+/// receive inherited descriptors or socketpairs rather than paths.  Both
+/// descriptors are duplicated, so the caller keeps them open and owned;
+/// reads and writes start at, and advance, their current offsets.  Every
+/// format `decompress_filename` accepts is decoded, under the same `prefs`
+/// (pass-through, `--recover`, flush interval).  Output is written as is:
+/// sparse writes would seek, which a descriptor opened with `O_APPEND` or
+/// shared with another writer cannot absorb.
+#[cfg(unix)]
+pub fn decompress_fd(
+    input: impl std::os::fd::AsFd,
+    output: impl std::os::fd::AsFd,
+    prefs: &Prefs,
+) -> io::Result<DecompressStats> {
+    decompress_open_file(
+        file_from_fd(input.as_fd())?,
+        file_from_fd(output.as_fd())?,
+        prefs,
+    )
+}
+
+/// Windows counterpart of `decompress_fd`: decompresses from one handle into
+/// another, which stay open and owned by the caller.
+#[cfg(windows)]
+pub fn decompress_handle(
+    input: impl std::os::windows::io::AsHandle,
+    output: impl std::os::windows::io::AsHandle,
+    prefs: &Prefs,
+) -> io::Result<DecompressStats> {
+    decompress_open_file(
+        file_from_handle(input.as_handle())?,
+        file_from_handle(output.as_handle())?,
+        prefs,
+    )
+}
+
+/// The body of [`decompress_fd`] and `decompress_handle`, once both ends
+/// are `File`s.
+#[cfg(any(unix, windows))]
+fn decompress_open_file(src: File, mut dst: File, prefs: &Prefs) -> io::Result<DecompressStats> {
+    let mut resources = DecompressResources::from_prefs(prefs)?;
+    let mut src = ReadAhead::with_capacity(src, prefs.io_buffer_size);
+    let decompressed_bytes = decompress_flushing(&mut src, &mut dst, prefs, &mut resources)?;
+    dst.flush()?;
+    check_damage(&resources)?;
+    Ok(DecompressStats { decompressed_bytes })
+}
+
 /// Decompresses multiple source files, deriving each output filename by
 /// stripping `suffix` from the source name.
 ///
@@ -541,7 +618,8 @@ pub fn decompress_filename(src: &str, dst: &str, prefs: &Prefs) -> io::Result<De
 /// lz4io.c:2548).
 ///
 /// Returns `Ok(())` when all files succeed; `Err` summarising the counts of
-/// missing and skipped files otherwise.
+/// missing and skipped files otherwise, or a [`DamagedOutput`] when every
+/// file was written but `prefs.recover` skipped blocks.
 ///
 /// Equivalent to `LZ4IO_decompressMultipleFilenames` (lz4io.c lines 2498–2550).
 pub fn decompress_multiple_filenames(srcs: &[&str], suffix: &str, prefs: &Prefs) -> io::Result<()> {
@@ -558,6 +636,7 @@ pub fn decompress_multiple_filenames(srcs: &[&str], suffix: &str, prefs: &Prefs)
     let mut total_processed: u64 = 0;
     let mut missing_files: i32 = 0;
     let mut skipped_files: i32 = 0;
+    let mut first_failure: Option<io::Error> = None;
 
     let dst_is_special = suffix == STDOUT_MARK || suffix == NUL_MARK;
 
@@ -566,21 +645,33 @@ pub fn decompress_multiple_filenames(srcs: &[&str], suffix: &str, prefs: &Prefs)
             // Decompress directly to stdout / devnull (lz4io.c:2524–2527).
             // The `ress.dstFile` in C is already set to the special handle;
             // here we just write to the same special destination each iteration.
+            let damaged_before = resources.damaged_blocks;
             let result = if suffix == NUL_MARK {
                 let mut sink = io::sink();
                 decompress_src_file(src_path, &mut sink, prefs, &mut resources)
             } else {
-                let mut stdout = io::stdout();
+                let mut stdout = stdout_dst(prefs)?;
                 decompress_src_file(src_path, &mut stdout, prefs, &mut resources)
-            };
+            }
+            .and_then(|n| {
+                let damaged = resources.damaged_blocks != damaged_before;
+                remove_decoded_src(src_path, prefs, damaged).map(|()| n)
+            });
             match result {
                 Ok(n) => total_processed += n,
-                Err(_) => missing_files += 1,
+                Err(e) => {
+                    missing_files += 1;
+                    first_failure.get_or_insert(e);
+                    // The reader closed stdout; later files cannot be written.
+                    if stdout_closed() {
+                        break;
+                    }
+                }
             }
         } else {
             // Check that the source filename ends with `suffix` (lz4io.c:2535–2543).
             if src_path.len() <= suffix.len() || !src_path.ends_with(suffix) {
-                display_level(
+                diag::warning(
                     1,
                     &format!(
                         "File extension doesn't match expected LZ4_EXTENSION ({:4}); \
@@ -597,7 +688,10 @@ pub fn decompress_multiple_filenames(srcs: &[&str], suffix: &str, prefs: &Prefs)
 
             match decompress_dst_file(src_path, out_path, prefs, &mut resources) {
                 Ok(n) => total_processed += n,
-                Err(_) => missing_files += 1,
+                Err(e) => {
+                    missing_files += 1;
+                    first_failure.get_or_insert(e);
+                }
             }
         }
     }
@@ -607,12 +701,118 @@ pub fn decompress_multiple_filenames(srcs: &[&str], suffix: &str, prefs: &Prefs)
 
     let total_failures = missing_files + skipped_files;
     if total_failures > 0 {
-        Err(io::Error::other(format!(
+        let summary = format!(
             "{} file(s) could not be decompressed; {} file(s) skipped",
             missing_files, skipped_files
-        )))
+        );
+        Err(match first_failure {
+            // Keep the first file's error as the source so callers can still
+            // see why decoding failed (e.g. corrupt data vs. missing file).
+            Some(first) => io::Error::new(first.kind(), BatchError { summary, first }),
+            None => io::Error::other(summary),
+        })
     } else {
-        Ok(())
+        check_damage(&resources)
+    }
+}
+
+/// Checks the integrity of each file in `srcs` by decoding it with nothing
+/// written, as `lz4 -t` does.
+///
+/// Every file is tested, whatever happened to the ones before it.  Each gets
+/// a line on stderr: `OK` with its decoded size at display level 2, or
+/// `FAILED` with the reason (unrecognised header, checksum mismatch,
+/// truncated frame, unreadable file) at level 1.  The count summary
+/// follows at level 2, unless a file failed: the error then carries the
+/// summary, with the first failure as its source.  Sources are never
+/// removed, whatever `prefs.remove_src_file` says.
+///
+/// `prefs.test_mode` should be set, so that decoders skip their output
+/// work; if it is not, the output is discarded anyway.  `"stdin"` reads
+/// standard input.
+pub fn test_multiple_filenames(srcs: &[&str], prefs: &Prefs) -> io::Result<()> {
+    let mut resources = DecompressResources::from_prefs(prefs)?;
+    let mut failed: usize = 0;
+    let mut first_failure: Option<io::Error> = None;
+
+    for &src_path in srcs {
+        match decompress_src_file(src_path, &mut io::sink(), prefs, &mut resources) {
+            Ok(n) => display_level(2, &format!("{:<30} : OK ({} bytes) \n", src_path, n)),
+            Err(e) => {
+                diag::error(&format!("{:<30} : FAILED ({}) \n", src_path, e));
+                failed += 1;
+                first_failure.get_or_insert(e);
+            }
+        }
+    }
+
+    let summary = format!(
+        "{} file(s) tested: {} OK, {} FAILED",
+        srcs.len(),
+        srcs.len() - failed,
+        failed
+    );
+    match first_failure {
+        Some(first) => Err(io::Error::new(first.kind(), BatchError { summary, first })),
+        None => {
+            display_level(2, &format!("{} \n", summary));
+            Ok(())
+        }
+    }
+}
+
+/// Error returned by [`decompress_multiple_filenames`] and
+/// [`test_multiple_filenames`]: a count summary whose source is the first
+/// per-file failure.
+#[derive(Debug)]
+struct BatchError {
+    summary: String,
+    first: io::Error,
+}
+
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.summary)
+    }
+}
+
+impl std::error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.first)
+    }
+}
+
+/// Error returned when [`Prefs::recover`] skipped damaged blocks.
+///
+/// Decoding went through and the output was written, but it lacks the
+/// skipped blocks' data.  It is reported as an error so that callers, and
+/// the CLI's exit status, can tell a lossy restore from a clean one.
+#[derive(Debug)]
+pub struct DamagedOutput {
+    /// Number of blocks skipped.
+    pub damaged_blocks: u64,
+}
+
+impl std::fmt::Display for DamagedOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} damaged block(s) skipped; output is incomplete",
+            self.damaged_blocks
+        )
+    }
+}
+
+impl std::error::Error for DamagedOutput {}
+
+/// `Err(DamagedOutput)` when `resources` recorded skipped blocks.
+fn check_damage(resources: &DecompressResources) -> io::Result<()> {
+    match resources.damaged_blocks {
+        0 => Ok(()),
+        damaged_blocks => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            DamagedOutput { damaged_blocks },
+        )),
     }
 }
 
@@ -621,6 +821,7 @@ pub fn decompress_multiple_filenames(srcs: &[&str], suffix: &str, prefs: &Prefs)
 // ---------------------------------------------------------------------------
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
     use crate::io::prefs::Prefs;
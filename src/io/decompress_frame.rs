@@ -12,11 +12,12 @@
 //!   Each `src.read()` is sized to exactly that hint, minimising syscalls on
 //!   buffered sources and avoiding wasteful over-reads.
 //!
-//! * **Multi-worker path** — When `prefs.nb_workers > 1` the function uses the
-//!   same single-threaded algorithm as `nb_workers == 1`.  Output is
-//!   byte-for-byte identical.  True read→decompress→write pipelining would
-//!   require `dst` to be `Send`, which conflicts with the current
-//!   `&mut impl Write` signature.
+//! * **Multi-worker path** — When `prefs.nb_workers > 1` (and no dictionary
+//!   is loaded) the frame goes to [`crate::io::decompress_frame_mt`], which
+//!   decodes and checksums independent blocks in parallel.  Linked-block
+//!   frames fall back to the single-threaded loop.  Output is byte-for-byte
+//!   identical either way.  With `prefs.flush_every` set the single-threaded
+//!   loop is always used, so output leaves one block at a time.
 //!
 //! * **Dictionary decompression** — When `resources.dict_buffer` is `Some`,
 //!   [`decompress_lz4f_st_dict`] is used.  Each [`lz4f_decompress_using_dict`]
@@ -27,9 +28,17 @@
 //!   generic `impl Write`.  Callers that hold a concrete `File` handle can
 //!   invoke [`crate::io::sparse`] directly.
 //!
-//! * **Checksum validation** — Frame and block checksums are verified by
-//!   default.  Pass [`DecompressOptions`] with `skip_checksums = true` to opt
-//!   out (e.g. in latency-sensitive test paths).
+//! * **Checksum validation** — [`Prefs::checksum_policy`] is forwarded as
+//!   `checksum_policy` in [`DecompressOptions`]; by default the checksums a
+//!   frame carries are verified.  `--no-crc` sets
+//!   [`ChecksumPolicy::SKIP`](crate::frame::ChecksumPolicy::SKIP)
+//!   so trusted archives restore without hashing.
+//!
+//! * **Recovery** — [`Prefs::recover`] (`--recover`) forwards `best_effort`:
+//!   blocks failing their checksum in independent-block frames are skipped,
+//!   reported as warnings and counted in
+//!   [`DecompressResources::damaged_blocks`].  The multi-worker path does not
+//!   recover, so recovery always decodes on this thread.
 //!
 //! * **Errors** — All failure modes — I/O errors, invalid frames, checksum
 //!   mismatches, truncated input — are surfaced as [`io::Error`].
@@ -38,9 +47,12 @@ use std::io::{self, Read, Write};
 
 use crate::frame::types::LZ4F_VERSION;
 use crate::frame::{
-    lz4f_create_decompression_context, lz4f_decompress, lz4f_decompress_using_dict, Lz4FDCtx,
+    lz4f_create_decompression_context, lz4f_decompress, lz4f_decompress_using_dict,
+    DecompressOptions, Lz4FDCtx,
 };
+use crate::io::decompress_frame_mt::decompress_lz4f_mt;
 use crate::io::decompress_resources::DecompressResources;
+use crate::io::diag;
 use crate::io::prefs::{display_level, Prefs, DISPLAY_LEVEL, LZ4IO_MAGICNUMBER};
 
 // Read/write buffer capacity for the decompression loop (64 KiB).
@@ -51,8 +63,27 @@ const DECOMP_BUF_SIZE: usize = 64 * 1024;
 /// Converts an [`Lz4FError`](crate::frame::Lz4FError) into an [`io::Error`]
 /// with [`io::ErrorKind::InvalidData`], suitable for propagation from I/O
 /// functions that return `io::Result`.
-fn lz4f_err_to_io(e: crate::frame::Lz4FError) -> io::Error {
-    io::Error::new(io::ErrorKind::InvalidData, format!("LZ4F error: {e}"))
+///
+/// The original error stays reachable through [`std::error::Error::source`],
+/// which the CLI uses to tell checksum failures from other corruption.
+pub(crate) fn lz4f_err_to_io(e: crate::frame::Lz4FError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, Lz4FIoError(e))
+}
+
+/// `io::Error` payload wrapping a frame error as `LZ4F error: …`.
+#[derive(Debug)]
+struct Lz4FIoError(crate::frame::Lz4FError);
+
+impl std::fmt::Display for Lz4FIoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LZ4F error: {}", self.0)
+    }
+}
+
+impl std::error::Error for Lz4FIoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -90,17 +121,55 @@ pub fn decompress_lz4f(
     // When a dictionary is loaded, use the dict-aware decompression path.
     if let Some(dict) = &resources.dict_buffer {
         let dict = dict.clone(); // clone to avoid borrow conflict with &mut dst
-        return decompress_lz4f_st_dict(src, dst, prefs, &dict);
+        return decompress_lz4f_st_dict(src, dst, prefs, &dict, &mut resources.damaged_blocks);
     }
 
-    // Both branches invoke the same ST implementation. True pipelining for
-    // nb_workers > 1 is not implemented because `dst: &mut impl Write` is not
-    // `Send`. The output is byte-for-byte identical regardless of worker count.
-    if prefs.nb_workers > 1 {
-        decompress_lz4f_st(src, dst, prefs)
-    } else {
-        decompress_lz4f_st(src, dst, prefs)
+    // The MT path holds a whole batch of blocks before writing; interval
+    // flushing wants output block by block, and recovery needs the
+    // single-threaded decoder's damage report.
+    if prefs.nb_workers > 1 && prefs.flush_every.is_none() && !prefs.recover {
+        return decompress_lz4f_mt(src, dst, prefs);
     }
+    let mut dctx = lz4f_create_decompression_context(LZ4F_VERSION).map_err(lz4f_err_to_io)?;
+    let result = decode_frame_st(&mut dctx, src, dst, prefs, &LZ4IO_MAGICNUMBER.to_le_bytes());
+    resources.damaged_blocks += report_damage(&mut dctx);
+    result
+}
+
+// Decoder options for `prefs`.
+fn decode_options(prefs: &Prefs) -> DecompressOptions {
+    DecompressOptions {
+        checksum_policy: Some(prefs.checksum_policy),
+        best_effort: prefs.recover,
+        ..DecompressOptions::default()
+    }
+}
+
+// Warns about every block `dctx` skipped under `--recover` and returns how
+// many there were.  Offsets are relative to the start of the frame.
+fn report_damage(dctx: &mut Lz4FDCtx) -> u64 {
+    let report = dctx.take_damage_report();
+    for block in &report.blocks {
+        let action = if block.passed_through {
+            "kept unverified"
+        } else {
+            "skipped"
+        };
+        diag::warning(
+            1,
+            &format!(
+                "damaged block {} : frame offset {}, {} bytes (output offset {})",
+                action, block.input_offset, block.input_len, block.output_offset
+            ),
+        );
+    }
+    if report.lost_bytes > 0 {
+        diag::warning(
+            1,
+            &format!("{} decoded bytes lost to damaged blocks", report.lost_bytes),
+        );
+    }
+    report.blocks.len() as u64
 }
 
 // Feeds `input` to the frame decompressor in a loop until the entire slice
@@ -119,10 +188,12 @@ fn feed_to_decompressor(
 ) -> io::Result<usize> {
     let mut pos = 0usize;
     let mut next_hint: usize = 1; // non-zero default
+    let opts = decode_options(prefs);
 
     while pos < input.len() {
         let (src_consumed, dst_written, hint) =
-            lz4f_decompress(dctx, Some(dst_buf), &input[pos..], None).map_err(lz4f_err_to_io)?;
+            lz4f_decompress(dctx, Some(dst_buf), &input[pos..], Some(&opts))
+                .map_err(lz4f_err_to_io)?;
         pos += src_consumed;
         next_hint = hint;
 
@@ -132,7 +203,9 @@ fn feed_to_decompressor(
                 dst.write_all(&dst_buf[..dst_written])
                     .map_err(|e| io::Error::new(e.kind(), format!("Write error: {e}")))?;
             }
-            if DISPLAY_LEVEL.load(std::sync::atomic::Ordering::Relaxed) >= 2 {
+            if prefs.builtin_progress()
+                && DISPLAY_LEVEL.load(std::sync::atomic::Ordering::Relaxed) >= 2
+            {
                 display_level(2, &format!("\rDecompressed : {} MiB  ", *filesize >> 20));
             }
         }
@@ -166,10 +239,11 @@ fn feed_to_decompressor_dict(
 ) -> io::Result<usize> {
     let mut pos = 0usize;
     let mut next_hint: usize = 1;
+    let opts = decode_options(prefs);
 
     while pos < input.len() {
         let (src_consumed, dst_written, hint) =
-            lz4f_decompress_using_dict(dctx, Some(dst_buf), &input[pos..], dict, None)
+            lz4f_decompress_using_dict(dctx, Some(dst_buf), &input[pos..], dict, Some(&opts))
                 .map_err(lz4f_err_to_io)?;
         pos += src_consumed;
         next_hint = hint;
@@ -180,7 +254,9 @@ fn feed_to_decompressor_dict(
                 dst.write_all(&dst_buf[..dst_written])
                     .map_err(|e| io::Error::new(e.kind(), format!("Write error: {e}")))?;
             }
-            if DISPLAY_LEVEL.load(std::sync::atomic::Ordering::Relaxed) >= 2 {
+            if prefs.builtin_progress()
+                && DISPLAY_LEVEL.load(std::sync::atomic::Ordering::Relaxed) >= 2
+            {
                 display_level(2, &format!("\rDecompressed : {} MiB  ", *filesize >> 20));
             }
         }
@@ -201,27 +277,38 @@ fn feed_to_decompressor_dict(
 // ---------------------------------------------------------------------------
 
 /// Decompresses one LZ4 frame from `src` into `dst` using the
-/// `next_hint`-driven read loop.  Also serves as the implementation for
-/// `nb_workers > 1`; see the module-level note on the multi-worker path.
-fn decompress_lz4f_st(src: &mut impl Read, dst: &mut impl Write, prefs: &Prefs) -> io::Result<u64> {
+/// `next_hint`-driven read loop.
+///
+/// `prefix` holds the frame bytes already consumed from `src` — the magic
+/// number, plus any header bytes the MT path read before falling back.
+pub(crate) fn decompress_lz4f_st(
+    src: &mut impl Read,
+    dst: &mut impl Write,
+    prefs: &Prefs,
+    prefix: &[u8],
+) -> io::Result<u64> {
     let mut dctx = lz4f_create_decompression_context(LZ4F_VERSION).map_err(lz4f_err_to_io)?;
+    decode_frame_st(&mut dctx, src, dst, prefs, prefix)
+}
 
+// Body of `decompress_lz4f_st` on a caller-owned context, so the caller can
+// collect its damage report afterwards.
+fn decode_frame_st(
+    dctx: &mut Lz4FDCtx,
+    src: &mut impl Read,
+    dst: &mut impl Write,
+    prefs: &Prefs,
+    prefix: &[u8],
+) -> io::Result<u64> {
     let mut src_buf = vec![0u8; DECOMP_BUF_SIZE];
     let mut dst_buf = vec![0u8; DECOMP_BUF_SIZE];
     let mut filesize: u64 = 0;
 
-    // Re-inject the 4 magic bytes that the caller already consumed from `src`.
+    // Re-inject the bytes that the caller already consumed from `src`.
     // The frame decoder needs a complete, contiguous byte stream starting with
     // the magic number to parse the frame header correctly.
-    let magic_bytes = LZ4IO_MAGICNUMBER.to_le_bytes();
-    let mut next_hint = feed_to_decompressor(
-        &mut dctx,
-        &magic_bytes,
-        &mut dst_buf,
-        dst,
-        prefs,
-        &mut filesize,
-    )?;
+    let mut next_hint =
+        feed_to_decompressor(dctx, prefix, &mut dst_buf, dst, prefs, &mut filesize)?;
 
     // Drive the decoder with hint-sized reads until the frame is complete.
     while next_hint != 0 {
@@ -234,7 +321,7 @@ fn decompress_lz4f_st(src: &mut impl Read, dst: &mut impl Write, prefs: &Prefs)
         }
 
         next_hint = feed_to_decompressor(
-            &mut dctx,
+            dctx,
             &src_buf[..read_n],
             &mut dst_buf,
             dst,
@@ -263,15 +350,28 @@ fn decompress_lz4f_st(src: &mut impl Read, dst: &mut impl Write, prefs: &Prefs)
 /// [`decompress_lz4f_st`], but passes `dict` to every decompressor call
 /// so the decoder can resolve cross-dictionary backreferences.
 ///
-/// Called by [`decompress_lz4f`] when `resources.dict_buffer` is `Some`.
+/// Called by [`decompress_lz4f`] when `resources.dict_buffer` is `Some`;
+/// blocks skipped under `--recover` are added to `damaged_blocks`.
 fn decompress_lz4f_st_dict(
     src: &mut impl Read,
     dst: &mut impl Write,
     prefs: &Prefs,
     dict: &[u8],
+    damaged_blocks: &mut u64,
 ) -> io::Result<u64> {
     let mut dctx = lz4f_create_decompression_context(LZ4F_VERSION).map_err(lz4f_err_to_io)?;
+    let result = decode_frame_st_dict(&mut dctx, src, dst, prefs, dict);
+    *damaged_blocks += report_damage(&mut dctx);
+    result
+}
 
+fn decode_frame_st_dict(
+    dctx: &mut Lz4FDCtx,
+    src: &mut impl Read,
+    dst: &mut impl Write,
+    prefs: &Prefs,
+    dict: &[u8],
+) -> io::Result<u64> {
     let mut src_buf = vec![0u8; DECOMP_BUF_SIZE];
     let mut dst_buf = vec![0u8; DECOMP_BUF_SIZE];
     let mut filesize: u64 = 0;
@@ -279,7 +379,7 @@ fn decompress_lz4f_st_dict(
     // Re-inject the 4 magic bytes the caller already consumed from `src`.
     let magic_bytes = LZ4IO_MAGICNUMBER.to_le_bytes();
     let mut next_hint = feed_to_decompressor_dict(
-        &mut dctx,
+        dctx,
         &magic_bytes,
         dict,
         &mut dst_buf,
@@ -299,7 +399,7 @@ fn decompress_lz4f_st_dict(
         }
 
         next_hint = feed_to_decompressor_dict(
-            &mut dctx,
+            dctx,
             &src_buf[..read_n],
             dict,
             &mut dst_buf,
@@ -325,6 +425,7 @@ fn decompress_lz4f_st_dict(
 // ---------------------------------------------------------------------------
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
     use crate::io::decompress_resources::DecompressResources;
@@ -434,7 +535,7 @@ mod tests {
     fn corrupt_input_returns_error() {
         // 4 bytes of magic already consumed; feed garbage as the frame body.
         let garbage: &[u8] = b"\x00\x01\x02\x03\xFF\xFE\xFD";
-        let mut src = &garbage[..];
+        let mut src = garbage;
 
         let prefs = Prefs::default();
         let mut res = DecompressResources::new(&prefs).unwrap();
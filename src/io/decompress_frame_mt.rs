@@ -0,0 +1,427 @@
+//! Multi-threaded (MT) decompression of block-independent LZ4 frames.
+//!
+//! Frames written with `BlockMode::Independent` (`lz4 -BI`, and every frame
+//! produced by the MT compressor with independent blocks) can be decoded one
+//! block per worker.  [`decompress_lz4f_mt`] reads the frame header itself;
+//! if the frame uses linked blocks, or the header is malformed, the bytes read
+//! so far are handed to the single-threaded decoder, which produces the usual
+//! output and error reporting.
+//!
+//! The pipeline per batch is:
+//!
+//! 1. The calling thread reads a batch of raw blocks (header, payload, and
+//!    optional block checksum) from `src`.
+//! 2. A scoped thread fans the batch out over a [`rayon`] pool of
+//!    `prefs.nb_workers` threads, built for the frame.  Each worker
+//!    verifies its block checksum and decodes the block, so neither step is
+//!    serialised.
+//! 3. Meanwhile the calling thread feeds the *previous* batch's output, in
+//!    order, to the content-checksum state (a plain chunked XXH32 update), and
+//!    writes it to `dst`.
+//!
+//! The [checksum policy](crate::io::prefs::Prefs::checksum_policy) applies as
+//! on the single-threaded path: a skipped checksum is read but not computed,
+//! and a required one that the header does not enable fails the frame.
+//!
+//! `dst` never leaves the calling thread, so it does not need to be `Send`.
+//! Errors surface with the same `LZ4F error: …` messages as the
+//! single-threaded path.  All blocks before the failing one are written.
+
+use std::io::{self, Read, Write};
+
+use rayon::prelude::*;
+
+use crate::block::decompress_api::decompress_safe;
+use crate::frame::header::{lz4f_get_block_size, lz4f_header_checksum};
+use crate::frame::types::{BlockSizeId, Lz4FError, BF_SIZE, BH_SIZE, LZ4F_BLOCKUNCOMPRESSED_FLAG};
+use crate::frame::ChecksumMode;
+use crate::io::decompress_frame::{decompress_lz4f_st, lz4f_err_to_io};
+use crate::io::prefs::{display_level, Prefs, DISPLAY_LEVEL, LZ4IO_MAGICNUMBER};
+use crate::xxhash::{xxh32_oneshot, Xxh32State};
+
+/// Decompressed bytes handed to each worker per batch, at minimum.
+///
+/// With 4 MB blocks a batch holds one block per worker; with 64 KB blocks it
+/// holds 64, so per-batch scheduling overhead stays small.
+const BATCH_BYTES_PER_WORKER: usize = 4 * 1024 * 1024;
+
+/// A raw block read from the frame, not yet verified or decoded.
+struct RawBlock {
+    payload: Vec<u8>,
+    uncompressed: bool,
+    checksum: Option<u32>,
+}
+
+/// Frame-header fields the MT decoder needs.
+struct FrameHeader {
+    block_max: usize,
+    block_checksum: bool,
+    content_checksum: bool,
+    content_size: Option<u64>,
+}
+
+/// Fills `buf` as far as possible; returns the number of bytes read.
+fn read_full(src: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match src.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(k) => n += k,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(io::Error::new(e.kind(), format!("Read error: {e}"))),
+        }
+    }
+    Ok(n)
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated LZ4 frame")
+}
+
+/// Reads the frame header after the magic number.
+///
+/// Returns the raw header bytes (magic included) and, when the frame is
+/// well-formed and block-independent, its parsed fields.
+fn read_header(src: &mut impl Read) -> io::Result<(Vec<u8>, Option<FrameHeader>)> {
+    let mut raw = LZ4IO_MAGICNUMBER.to_le_bytes().to_vec();
+    let mut descriptor = [0u8; 2];
+    let n = read_full(src, &mut descriptor)?;
+    raw.extend_from_slice(&descriptor[..n]);
+    if n < 2 {
+        return Ok((raw, None));
+    }
+    let (flg, bd) = (descriptor[0], descriptor[1]);
+    let has_size = flg & 0x08 != 0;
+    let has_dict_id = flg & 0x01 != 0;
+    let rest_len = 1 + if has_size { 8 } else { 0 } + if has_dict_id { 4 } else { 0 };
+    let mut rest = vec![0u8; rest_len];
+    let n = read_full(src, &mut rest)?;
+    raw.extend_from_slice(&rest[..n]);
+    if n < rest_len {
+        return Ok((raw, None));
+    }
+
+    let well_formed = flg >> 6 == 1
+        && flg & 0x02 == 0
+        && bd & 0x8F == 0
+        && lz4f_header_checksum(&raw[4..raw.len() - 1]) == raw[raw.len() - 1];
+    let independent = flg & 0x20 != 0;
+    let block_max = match bd >> 4 {
+        4 => lz4f_get_block_size(BlockSizeId::Max64Kb),
+        5 => lz4f_get_block_size(BlockSizeId::Max256Kb),
+        6 => lz4f_get_block_size(BlockSizeId::Max1Mb),
+        7 => lz4f_get_block_size(BlockSizeId::Max4Mb),
+        _ => None,
+    };
+    let header = match block_max {
+        Some(block_max) if well_formed && independent => Some(FrameHeader {
+            block_max,
+            block_checksum: flg & 0x10 != 0,
+            content_checksum: flg & 0x04 != 0,
+            content_size: has_size.then(|| u64::from_le_bytes(rest[..8].try_into().unwrap())),
+        }),
+        _ => None,
+    };
+    Ok((raw, header))
+}
+
+/// Reads up to `max_blocks` blocks.  Returns `true` once the end mark is read.
+fn read_batch(
+    src: &mut impl Read,
+    header: &FrameHeader,
+    max_blocks: usize,
+    batch: &mut Vec<RawBlock>,
+) -> io::Result<bool> {
+    while batch.len() < max_blocks {
+        let mut bh = [0u8; BH_SIZE];
+        if read_full(src, &mut bh)? < BH_SIZE {
+            return Err(truncated());
+        }
+        let word = u32::from_le_bytes(bh);
+        if word == 0 {
+            return Ok(true);
+        }
+        let len = (word & !LZ4F_BLOCKUNCOMPRESSED_FLAG) as usize;
+        if len > header.block_max {
+            return Err(lz4f_err_to_io(Lz4FError::MaxBlockSizeInvalid));
+        }
+        let mut payload = vec![0u8; len];
+        if read_full(src, &mut payload)? < len {
+            return Err(truncated());
+        }
+        let checksum = if header.block_checksum {
+            let mut crc = [0u8; BF_SIZE];
+            if read_full(src, &mut crc)? < BF_SIZE {
+                return Err(truncated());
+            }
+            Some(u32::from_le_bytes(crc))
+        } else {
+            None
+        };
+        batch.push(RawBlock {
+            payload,
+            uncompressed: word & LZ4F_BLOCKUNCOMPRESSED_FLAG != 0,
+            checksum,
+        });
+    }
+    Ok(false)
+}
+
+/// Verifies (unless `skip_checksum`) and decodes one block on a worker thread.
+fn decode_block(
+    block: RawBlock,
+    block_max: usize,
+    skip_checksum: bool,
+) -> Result<Vec<u8>, Lz4FError> {
+    if let Some(expected) = block.checksum.filter(|_| !skip_checksum) {
+        if xxh32_oneshot(&block.payload, 0) != expected {
+            return Err(Lz4FError::BlockChecksumInvalid);
+        }
+    }
+    if block.uncompressed {
+        return Ok(block.payload);
+    }
+    let mut out = vec![0u8; block_max];
+    let n =
+        decompress_safe(&block.payload, &mut out).map_err(|_| Lz4FError::DecompressionFailed)?;
+    out.truncate(n);
+    Ok(out)
+}
+
+/// Hashes and writes one batch of decoded blocks, in order.
+fn emit_batch(
+    results: Vec<Result<Vec<u8>, Lz4FError>>,
+    dst: &mut impl Write,
+    prefs: &Prefs,
+    xxh: &mut Option<Xxh32State>,
+    filesize: &mut u64,
+) -> io::Result<()> {
+    for result in results {
+        let block = result.map_err(lz4f_err_to_io)?;
+        if let Some(state) = xxh.as_mut() {
+            state.update(&block);
+        }
+        *filesize += block.len() as u64;
+        if !prefs.test_mode {
+            dst.write_all(&block)
+                .map_err(|e| io::Error::new(e.kind(), format!("Write error: {e}")))?;
+        }
+        if prefs.builtin_progress() && DISPLAY_LEVEL.load(std::sync::atomic::Ordering::Relaxed) >= 2
+        {
+            display_level(2, &format!("\rDecompressed : {} MiB  ", *filesize >> 20));
+        }
+    }
+    Ok(())
+}
+
+/// Decompresses one LZ4 frame from `src` into `dst`, decoding independent
+/// blocks on a pool of `prefs.nb_workers` threads.
+///
+/// The caller must already have consumed the 4-byte magic number.  Frames
+/// with linked blocks, and frames whose header fails validation, fall back to
+/// the single-threaded decoder.
+///
+/// Returns the number of decompressed bytes.
+pub fn decompress_lz4f_mt(
+    src: &mut impl Read,
+    dst: &mut impl Write,
+    prefs: &Prefs,
+) -> io::Result<u64> {
+    let (raw_header, header) = read_header(src)?;
+    let header = match header {
+        Some(h) => h,
+        None => return decompress_lz4f_st(src, dst, prefs, &raw_header),
+    };
+
+    let workers = prefs.nb_workers.max(1) as usize;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .build()
+        .map_err(io::Error::other)?;
+    let max_blocks = workers * (BATCH_BYTES_PER_WORKER / header.block_max).max(1);
+    let block_max = header.block_max;
+    let policy = prefs.checksum_policy;
+    if policy.content == ChecksumMode::Require && !header.content_checksum {
+        return Err(lz4f_err_to_io(Lz4FError::ContentChecksumMissing));
+    }
+    if policy.block == ChecksumMode::Require && !header.block_checksum {
+        return Err(lz4f_err_to_io(Lz4FError::BlockChecksumMissing));
+    }
+    let skip_block_checksums = policy.block == ChecksumMode::Skip;
+    let mut xxh = (header.content_checksum && policy.content != ChecksumMode::Skip)
+        .then(|| Xxh32State::new(0));
+    let mut filesize: u64 = 0;
+    let mut pending: Vec<Result<Vec<u8>, Lz4FError>> = Vec::new();
+    let mut done = false;
+
+    while !done {
+        let mut batch = Vec::with_capacity(max_blocks);
+        done = match read_batch(src, &header, max_blocks, &mut batch) {
+            Ok(done) => done,
+            Err(e) => {
+                // Flush what was decoded before the bad or truncated block.
+                emit_batch(pending, dst, prefs, &mut xxh, &mut filesize)?;
+                return Err(e);
+            }
+        };
+        let previous = std::mem::take(&mut pending);
+        pending = std::thread::scope(|s| {
+            let pool = &pool;
+            let decoder = s.spawn(move || {
+                pool.install(|| {
+                    batch
+                        .into_par_iter()
+                        .map(|block| decode_block(block, block_max, skip_block_checksums))
+                        .collect::<Vec<_>>()
+                })
+            });
+            let emitted = emit_batch(previous, dst, prefs, &mut xxh, &mut filesize);
+            let decoded = decoder.join().expect("decode worker panicked");
+            emitted.map(|()| decoded)
+        })?;
+    }
+    emit_batch(pending, dst, prefs, &mut xxh, &mut filesize)?;
+
+    if let Some(size) = header.content_size {
+        if size != filesize {
+            return Err(lz4f_err_to_io(Lz4FError::FrameSizeWrong));
+        }
+    }
+    if header.content_checksum {
+        let mut crc = [0u8; BF_SIZE];
+        if read_full(src, &mut crc)? < BF_SIZE {
+            return Err(truncated());
+        }
+        if xxh.is_some_and(|state| state.digest() != u32::from_le_bytes(crc)) {
+            return Err(lz4f_err_to_io(Lz4FError::ContentChecksumInvalid));
+        }
+    }
+    Ok(filesize)
+}
+
+#[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
+mod tests {
+    use super::*;
+    use crate::frame::types::{BlockChecksum, BlockMode, ContentChecksum, Preferences};
+    use crate::frame::{lz4f_compress_frame, lz4f_compress_frame_bound};
+
+    fn frame(data: &[u8], mode: BlockMode, checks: bool) -> Vec<u8> {
+        let mut prefs = Preferences::default();
+        prefs.frame_info.block_mode = mode;
+        prefs.frame_info.block_size_id = BlockSizeId::Max64Kb;
+        if checks {
+            prefs.frame_info.block_checksum_flag = BlockChecksum::Enabled;
+            prefs.frame_info.content_checksum_flag = ContentChecksum::Enabled;
+            prefs.frame_info.content_size = data.len() as u64;
+        }
+        let mut out = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(&prefs))];
+        let n = lz4f_compress_frame(&mut out, data, Some(&prefs)).unwrap();
+        out.truncate(n);
+        out
+    }
+
+    fn mt_prefs() -> Prefs {
+        let mut prefs = Prefs::default();
+        prefs.nb_workers = 4;
+        prefs
+    }
+
+    #[test]
+    fn independent_and_linked_round_trip() {
+        let data = crate::lorem::gen_buffer(1_000_000, 3);
+        for mode in [BlockMode::Independent, BlockMode::Linked] {
+            for checks in [false, true] {
+                let f = frame(&data, mode, checks);
+                let mut out = Vec::new();
+                let n = decompress_lz4f_mt(&mut &f[4..], &mut out, &mt_prefs()).unwrap();
+                assert_eq!(n, data.len() as u64);
+                assert_eq!(out, data);
+            }
+        }
+    }
+
+    #[test]
+    fn corrupt_block_checksum_detected() {
+        let data = crate::lorem::gen_buffer(300_000, 5);
+        let mut f = frame(&data, BlockMode::Independent, true);
+        let mid = f.len() / 2;
+        f[mid] ^= 0x20;
+        let err = decompress_lz4f_mt(&mut &f[4..], &mut Vec::new(), &mt_prefs()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn skip_checksums_ignores_bad_checksums() {
+        let data = crate::lorem::gen_buffer(300_000, 6);
+        let mut f = frame(&data, BlockMode::Independent, true);
+        // Damage the first block's checksum and the content checksum only;
+        // the header (with content size) is 15 bytes.
+        let word = u32::from_le_bytes(f[15..19].try_into().unwrap());
+        let crc_at = 19 + (word & !LZ4F_BLOCKUNCOMPRESSED_FLAG) as usize;
+        f[crc_at] ^= 1;
+        let last = f.len() - 1;
+        f[last] ^= 1;
+
+        for workers in [1, 4] {
+            let mut prefs = mt_prefs();
+            prefs.nb_workers = workers;
+            let decode = |prefs: &Prefs, out: &mut Vec<u8>| {
+                if workers > 1 {
+                    decompress_lz4f_mt(&mut &f[4..], out, prefs)
+                } else {
+                    decompress_lz4f_st(&mut &f[4..], out, prefs, &f[..4])
+                }
+            };
+            let err = decode(&prefs, &mut Vec::new()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{workers} workers");
+
+            prefs.set_checksum_policy(crate::frame::ChecksumPolicy::SKIP);
+            let mut out = Vec::new();
+            assert_eq!(decode(&prefs, &mut out).unwrap(), data.len() as u64);
+            assert_eq!(out, data, "{workers} workers");
+        }
+    }
+
+    #[test]
+    fn checksum_policy_applies_on_both_paths() {
+        use crate::frame::ChecksumPolicy;
+
+        let data = crate::lorem::gen_buffer(300_000, 7);
+        let plain = frame(&data, BlockMode::Independent, false);
+        let mut checked = frame(&data, BlockMode::Independent, true);
+        let last = checked.len() - 1;
+        checked[last] ^= 1;
+
+        for workers in [1, 4] {
+            let mut prefs = mt_prefs();
+            prefs.nb_workers = workers;
+            let decode = |f: &[u8], prefs: &Prefs, out: &mut Vec<u8>| {
+                if workers > 1 {
+                    decompress_lz4f_mt(&mut &f[4..], out, prefs)
+                } else {
+                    decompress_lz4f_st(&mut &f[4..], out, prefs, &f[..4])
+                }
+            };
+
+            prefs.set_checksum_policy(ChecksumPolicy::REQUIRE);
+            let err = decode(&plain, &prefs, &mut Vec::new()).unwrap_err();
+            assert!(
+                err.to_string().contains("missing"),
+                "{workers} workers: {err}"
+            );
+
+            // Only the content checksum is damaged: skipping it alone is enough.
+            prefs.set_checksum_policy(ChecksumPolicy {
+                content: ChecksumMode::Skip,
+                ..ChecksumPolicy::VERIFY
+            });
+            let mut out = Vec::new();
+            assert_eq!(
+                decode(&checked, &prefs, &mut out).unwrap(),
+                data.len() as u64
+            );
+            assert_eq!(out, data, "{workers} workers");
+        }
+    }
+}
@@ -265,6 +265,7 @@ fn decode_legacy_mt<R: Read, W: Write>(
 // ---------------------------------------------------------------------------
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
     use crate::io::prefs::Prefs;
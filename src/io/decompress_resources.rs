@@ -20,7 +20,8 @@ use std::path::Path;
 
 use crossbeam_channel::{bounded, Receiver, Sender};
 
-use crate::io::prefs::{Prefs, LZ4_MAX_DICT_SIZE, MB};
+use crate::dict::truncation_warning;
+use crate::io::prefs::{display_level, Prefs, LZ4_MAX_DICT_SIZE, MB};
 
 // ---------------------------------------------------------------------------
 // Buffer-size constants
@@ -53,6 +54,13 @@ pub const LZ4IO_D_BUFFER_SIZE: usize = 64 * 1024;
 /// Returns `io::Error` on any I/O failure.
 pub fn load_dict_file(dict_path: &Path) -> io::Result<Vec<u8>> {
     let mut file = fs::File::open(dict_path)?;
+    if let Some(warning) = file
+        .metadata()
+        .ok()
+        .and_then(|m| truncation_warning(m.len()))
+    {
+        display_level(2, &warning);
+    }
 
     // Opportunistically seek to the tail of the file, ignoring errors
     // (the file might be stdin-like, in which case we just read it all).
@@ -111,6 +119,9 @@ pub struct DecompressResources {
 
     /// Pre-loaded dictionary bytes, if any.
     pub dict_buffer: Option<Vec<u8>>,
+
+    /// Blocks skipped by [`Prefs::recover`] so far.
+    pub damaged_blocks: u64,
 }
 
 impl DecompressResources {
@@ -120,6 +131,7 @@ impl DecompressResources {
             src_buffer: vec![0u8; LZ4IO_D_BUFFER_SIZE],
             dst_buffer: vec![0u8; LZ4IO_D_BUFFER_SIZE],
             dict_buffer: None,
+            damaged_blocks: 0,
         })
     }
 
@@ -133,6 +145,7 @@ impl DecompressResources {
             src_buffer: vec![0u8; LZ4IO_D_BUFFER_SIZE],
             dst_buffer: vec![0u8; LZ4IO_D_BUFFER_SIZE],
             dict_buffer: Some(dict),
+            damaged_blocks: 0,
         })
     }
 
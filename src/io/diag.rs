@@ -0,0 +1,206 @@
+//! User-facing warning and error formatting.
+//!
+//! Diagnostics from the `io` layer and the CLI are routed through [`emit`] so
+//! that a single process-wide [`LogFormat`] decides how they are rendered:
+//!
+//! - [`LogFormat::Plain`] — the message text unchanged (the default, matching
+//!   the reference `lz4` output byte for byte).
+//! - [`LogFormat::Color`] — a bold red `error:` / yellow `warning:` prefix,
+//!   intended for interactive terminals.
+//! - [`LogFormat::Json`] — one JSON object per line
+//!   (`{"level":"error","message":"…"}`) for orchestration systems.
+//!
+//! Verbosity gating stays with the caller: [`error`] and [`warning`] check the
+//! `io` notification level, while the CLI macros check the CLI display level.
+
+use std::io::Write;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::io::prefs::DISPLAY_LEVEL;
+
+/// Output style for warnings and errors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Message text as-is.
+    #[default]
+    Plain,
+    /// ANSI-coloured severity prefix.
+    Color,
+    /// JSON lines.
+    Json,
+}
+
+impl LogFormat {
+    /// Parses a `--log-format` value.
+    ///
+    /// Accepts `plain`, `color`, `json`, and `auto`; `auto` selects
+    /// [`LogFormat::Color`] when stderr is a terminal and [`LogFormat::Plain`]
+    /// otherwise.  Returns `None` for anything else.
+    pub fn parse(s: &str) -> Option<LogFormat> {
+        match s {
+            "plain" => Some(LogFormat::Plain),
+            "color" | "colour" => Some(LogFormat::Color),
+            "json" => Some(LogFormat::Json),
+            "auto" => {
+                use std::io::IsTerminal;
+                Some(if std::io::stderr().is_terminal() {
+                    LogFormat::Color
+                } else {
+                    LogFormat::Plain
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// Something was skipped or ignored; processing continues.
+    Warning,
+    /// An operation failed.
+    Error,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+static LOG_FORMAT: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the process-wide diagnostic format.
+pub fn set_log_format(format: LogFormat) {
+    let v = match format {
+        LogFormat::Plain => 0,
+        LogFormat::Color => 1,
+        LogFormat::Json => 2,
+    };
+    LOG_FORMAT.store(v, Ordering::Relaxed);
+}
+
+/// Returns the process-wide diagnostic format.
+pub fn log_format() -> LogFormat {
+    match LOG_FORMAT.load(Ordering::Relaxed) {
+        1 => LogFormat::Color,
+        2 => LogFormat::Json,
+        _ => LogFormat::Plain,
+    }
+}
+
+/// Renders `msg` in `format`, including the trailing newline.
+///
+/// Trailing whitespace (the reference messages often end in `" \n"`) is
+/// trimmed for the coloured and JSON forms.
+pub fn format_message(format: LogFormat, severity: Severity, msg: &str) -> String {
+    match format {
+        LogFormat::Plain => {
+            if msg.ends_with('\n') {
+                msg.to_owned()
+            } else {
+                format!("{}\n", msg)
+            }
+        }
+        LogFormat::Color => {
+            let color = match severity {
+                Severity::Warning => "\x1b[1;33m",
+                Severity::Error => "\x1b[1;31m",
+            };
+            format!(
+                "{}{}:\x1b[0m {}\n",
+                color,
+                severity.as_str(),
+                msg.trim_end()
+            )
+        }
+        LogFormat::Json => format!(
+            "{{\"level\":\"{}\",\"message\":\"{}\"}}\n",
+            severity.as_str(),
+            json_escape(msg.trim_end())
+        ),
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Writes `msg` to stderr in the current [`log_format`], without level gating.
+pub fn emit(severity: Severity, msg: &str) {
+    let line = format_message(log_format(), severity, msg);
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(line.as_bytes());
+    let _ = stderr.flush();
+}
+
+/// Emits an error when the `io` notification level is at least 1.
+pub fn error(msg: &str) {
+    if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 1 {
+        emit(Severity::Error, msg);
+    }
+}
+
+/// Emits a warning when the `io` notification level is at least `level`.
+pub fn warning(level: i32, msg: &str) {
+    if DISPLAY_LEVEL.load(Ordering::Relaxed) >= level {
+        emit(Severity::Warning, msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_is_unchanged() {
+        assert_eq!(
+            format_message(LogFormat::Plain, Severity::Error, "boom \n"),
+            "boom \n"
+        );
+        assert_eq!(
+            format_message(LogFormat::Plain, Severity::Warning, "careful"),
+            "careful\n"
+        );
+    }
+
+    #[test]
+    fn color_prefixes_severity() {
+        let s = format_message(LogFormat::Color, Severity::Error, "boom \n");
+        assert_eq!(s, "\x1b[1;31merror:\x1b[0m boom\n");
+    }
+
+    #[test]
+    fn json_escapes_message() {
+        let s = format_message(LogFormat::Json, Severity::Warning, "a \"b\"\\c\td\x01 \n");
+        assert_eq!(
+            s,
+            "{\"level\":\"warning\",\"message\":\"a \\\"b\\\"\\\\c\\td\\u0001\"}\n"
+        );
+    }
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(LogFormat::parse("plain"), Some(LogFormat::Plain));
+        assert_eq!(LogFormat::parse("color"), Some(LogFormat::Color));
+        assert_eq!(LogFormat::parse("json"), Some(LogFormat::Json));
+        assert!(LogFormat::parse("auto").is_some());
+        assert_eq!(LogFormat::parse("xml"), None);
+    }
+}
@@ -7,18 +7,28 @@
 //! All three frame families are recognised: standard LZ4 frames
 //! (`LZ4IO_MAGICNUMBER`), legacy frames, and skippable frames.
 //!
-//! Entry point: [`display_compressed_files_info`].
+//! Entry points: [`display_compressed_files_info`] for `--list`, and
+//! [`display_compressed_files_info_checked`] for `--info`, which adds a quick
+//! spot check that decodes only the first and last block of each frame.
 
 use std::fs;
 use std::io::{self, Read, Seek, SeekFrom};
 use std::sync::atomic::Ordering;
 
+use crate::block::decompress_safe;
+use crate::frame::append::{walk_frames, BlockRef, FrameLayout, FrameWalk};
+use crate::frame::header::lz4f_get_block_size;
 use crate::frame::types::{
     BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, FrameInfo as NativeFrameInfo,
-    FrameType as NativeFrameType,
+    FrameType as NativeFrameType, Lz4FError,
 };
-use crate::frame::{lz4f_create_decompression_context, lz4f_get_frame_info, lz4f_header_size};
+use crate::frame::{
+    lz4f_create_decompression_context, lz4f_decompress, lz4f_get_frame_info, lz4f_header_size,
+    FrameStats,
+};
+use crate::xxhash::{xxh32_of_reader, xxh32_oneshot};
 
+use crate::io::diag::{self, Severity};
 use crate::io::file_io::STDIN_MARK;
 use crate::io::prefs::{
     DISPLAY_LEVEL, LEGACY_MAGICNUMBER, LZ4IO_MAGICNUMBER, LZ4IO_SKIPPABLE0, LZ4IO_SKIPPABLEMASK,
@@ -127,6 +137,10 @@ pub struct CompressedFileInfo {
     pub eq_block_types: bool,
     /// `true` if every frame reported a content size.
     pub all_content_size: bool,
+    /// Number of skippable (user-data) frames among `frame_count`.
+    pub skippable_frames: u64,
+    /// Total size of those skippable frames, headers included.
+    pub skippable_bytes: u64,
 }
 
 impl CompressedFileInfo {
@@ -140,6 +154,8 @@ impl CompressedFileInfo {
             eq_frame_types: true,
             eq_block_types: true,
             all_content_size: true,
+            skippable_frames: 0,
+            skippable_bytes: 0,
         }
     }
 }
@@ -221,6 +237,43 @@ fn skip_blocks_data(file: &mut fs::File, block_checksum: bool, content_checksum:
     total
 }
 
+// ---------------------------------------------------------------------------
+// decode_frame_stats
+// ---------------------------------------------------------------------------
+
+/// Decodes the `len`-byte LZ4 frame starting at `start` and returns its
+/// [`FrameStats`], or `None` if it does not decode.  The decoded bytes are
+/// discarded.  The cursor is left at `start + len` either way.
+fn decode_frame_stats(file: &mut fs::File, start: u64, len: u64) -> Option<FrameStats> {
+    let stats = (|| {
+        file.seek(SeekFrom::Start(start)).ok()?;
+        let mut dctx = lz4f_create_decompression_context(LZ4F_VERSION).ok()?;
+        let mut src = vec![0u8; 64 * 1024];
+        let mut dst = vec![0u8; 64 * 1024];
+        let mut left = len;
+        let mut hint = 1;
+        while left > 0 {
+            let n = (src.len() as u64).min(left) as usize;
+            file.read_exact(&mut src[..n]).ok()?;
+            left -= n as u64;
+            let mut pos = 0;
+            // Drain decoded output even after the input is used up.
+            loop {
+                let (used, written, next) =
+                    lz4f_decompress(&mut dctx, Some(&mut dst), &src[pos..n], None).ok()?;
+                pos += used;
+                hint = next;
+                if hint == 0 || (pos == n && written < dst.len()) {
+                    break;
+                }
+            }
+        }
+        (hint == 0).then(|| dctx.frame_stats())
+    })();
+    file.seek(SeekFrom::Start(start + len)).ok()?;
+    stats
+}
+
 // ---------------------------------------------------------------------------
 // skip_legacy_blocks_data
 // ---------------------------------------------------------------------------
@@ -262,7 +315,7 @@ fn skip_legacy_blocks_data(file: &mut fs::File) -> u64 {
                 .seek(SeekFrom::Current(-(LEGACY_BLOCK_HEADER_SIZE as i64)))
                 .is_err()
             {
-                eprintln!("Error 37 : impossible to skip backward");
+                diag::emit(Severity::Error, "Error 37 : impossible to skip backward");
                 std::process::exit(37);
             }
             break;
@@ -270,7 +323,10 @@ fn skip_legacy_blocks_data(file: &mut fs::File) -> u64 {
 
         if next_cblock_size as usize > LEGACY_BLOCK_SIZE_MAX {
             if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 4 {
-                eprintln!("Error : block in legacy frame is too large");
+                diag::emit(
+                    Severity::Error,
+                    "Error : block in legacy frame is too large",
+                );
             }
             return LEGACY_FRAME_UNDECODABLE;
         }
@@ -382,9 +438,7 @@ fn get_compressed_file_info(
     let mut file = match fs::File::open(path) {
         Ok(f) => f,
         Err(e) => {
-            if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 1 {
-                eprintln!("{}: {}", path, e);
-            }
+            diag::error(&format!("{}: {}", path, e));
             return InfoResult::NotAFile;
         }
     };
@@ -407,11 +461,15 @@ fn get_compressed_file_info(
 
         if n != MAGICNUMBER_SIZE {
             // C: END_PROCESS(40, "Unrecognized header : Magic Number unreadable")
-            eprintln!("Error 40 : Unrecognized header : Magic Number unreadable");
+            diag::emit(
+                Severity::Error,
+                "Error 40 : Unrecognized header : Magic Number unreadable",
+            );
             std::process::exit(40);
         }
 
         let mut magic = read_le32(&buf[..4]);
+        let user_magic_nibble = magic & 0x0F;
         if is_skippable_magic_number(magic) {
             magic = LZ4IO_SKIPPABLE0; // fold all skippable magic numbers
         }
@@ -430,12 +488,18 @@ fn get_compressed_file_info(
                     let n2 = match file.read(&mut buf[MAGICNUMBER_SIZE..LZ4F_HEADER_SIZE_MIN]) {
                         Ok(n) => n,
                         Err(_) => {
-                            eprintln!("Error 71 : Error reading {}", path);
+                            diag::emit(
+                                Severity::Error,
+                                &format!("Error 71 : Error reading {}", path),
+                            );
                             std::process::exit(71);
                         }
                     };
                     if n2 == 0 {
-                        eprintln!("Error 71 : Error reading {}", path);
+                        diag::emit(
+                            Severity::Error,
+                            &format!("Error 71 : Error reading {}", path),
+                        );
                         std::process::exit(71);
                     }
                 }
@@ -455,12 +519,18 @@ fn get_compressed_file_info(
                     let n3 = match file.read(&mut buf[LZ4F_HEADER_SIZE_MIN..end]) {
                         Ok(n) => n,
                         Err(_) => {
-                            eprintln!("Error 72 : Error reading {}", path);
+                            diag::emit(
+                                Severity::Error,
+                                &format!("Error 72 : Error reading {}", path),
+                            );
                             std::process::exit(72);
                         }
                     };
                     if n3 == 0 {
-                        eprintln!("Error 72 : Error reading {}", path);
+                        diag::emit(
+                            Severity::Error,
+                            &format!("Error 72 : Error reading {}", path),
+                        );
                         std::process::exit(72);
                     }
                 }
@@ -501,6 +571,16 @@ fn get_compressed_file_info(
                 );
                 let total_blocks_size =
                     skip_blocks_data(&mut file, block_checksum, content_checksum);
+                // At -v each frame is decoded once more for its block counts.
+                let frame_stats = if display_now && total_blocks_size != 0 {
+                    let frame_len = total_blocks_size + h_size as u64;
+                    let start = file.stream_position().map(|end| end - frame_len);
+                    start
+                        .ok()
+                        .and_then(|start| decode_frame_stats(&mut file, start, frame_len))
+                } else {
+                    None
+                };
 
                 if total_blocks_size != 0 {
                     let b_type = block_type_id(
@@ -540,6 +620,16 @@ fn get_compressed_file_info(
                         }
                         cfinfo.all_content_size = false;
                     }
+                    if let Some(stats) = frame_stats {
+                        println!(
+                            "    {:>6} blocks: {} ({} stored), checksums: {}, decoded: {} bytes",
+                            "",
+                            stats.blocks,
+                            stats.uncompressed_blocks,
+                            stats.checksums,
+                            stats.uncompressed_bytes
+                        );
+                    }
                     result = InfoResult::Ok;
                 }
             }
@@ -557,9 +647,7 @@ fn get_compressed_file_info(
 
                 let total_blocks_size = skip_legacy_blocks_data(&mut file);
                 if total_blocks_size == LEGACY_FRAME_UNDECODABLE {
-                    if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 1 {
-                        eprintln!("Corrupted legacy frame");
-                    }
+                    diag::error("Corrupted legacy frame");
                     result = InfoResult::FormatNotKnown;
                     break 'frame_loop;
                 }
@@ -595,27 +683,40 @@ fn get_compressed_file_info(
                 let n = match file.read(&mut buf[..4]) {
                     Ok(n) => n,
                     Err(_) => {
-                        eprintln!("Error 42 : Stream error : skippable size unreadable");
+                        diag::emit(
+                            Severity::Error,
+                            "Error 42 : Stream error : skippable size unreadable",
+                        );
                         std::process::exit(42);
                     }
                 };
                 if n != 4 {
-                    eprintln!("Error 42 : Stream error : skippable size unreadable");
+                    diag::emit(
+                        Severity::Error,
+                        "Error 42 : Stream error : skippable size unreadable",
+                    );
                     std::process::exit(42);
                 }
 
                 let size = read_le32(&buf[..4]);
                 if file.seek(SeekFrom::Current(size as i64)).is_err() {
-                    eprintln!("Error 43 : Stream error : cannot skip skippable area");
+                    diag::emit(
+                        Severity::Error,
+                        "Error 43 : Stream error : cannot skip skippable area",
+                    );
                     std::process::exit(43);
                 }
 
+                cfinfo.skippable_frames += 1;
+                cfinfo.skippable_bytes += size as u64 + 8;
+
                 if display_now {
+                    // The Block column carries the user magic nibble (0x184D2A5?).
                     println!(
                         "    {:>6} {:>14} {:>5} {:>8} {:>20} {:>20} {:>9}",
                         cfinfo.frame_count + 1,
                         "SkippableFrame",
-                        "-",
+                        format!("m{:X}", user_magic_nibble),
                         "-",
                         size + 8, // payload + magic (4) + size field (4)
                         "-",
@@ -628,11 +729,11 @@ fn get_compressed_file_info(
             // ---------------------------------------------------------------
             _ => {
                 if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 3 {
-                    eprint!("Stream followed by undecodable data ");
+                    let mut msg = String::from("Stream followed by undecodable data ");
                     if let Ok(pos) = file.stream_position() {
-                        eprint!("at position {} ", pos);
+                        msg.push_str(&format!("at position {} ", pos));
                     }
-                    eprintln!();
+                    diag::emit(Severity::Warning, &msg);
                 }
                 result = InfoResult::FormatNotKnown;
                 break 'frame_loop;
@@ -684,9 +785,7 @@ pub fn display_compressed_files_info(paths: &[&str]) -> io::Result<()> {
                 .unwrap_or(false)
         };
         if !is_regular {
-            if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 1 {
-                eprintln!("lz4: {} is not a regular file", path);
-            }
+            diag::error(&format!("lz4: {} is not a regular file", path));
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 format!("{} is not a regular file", path),
@@ -703,9 +802,7 @@ pub fn display_compressed_files_info(paths: &[&str]) -> io::Result<()> {
 
         let op_result = get_compressed_file_info(&mut cfinfo, path, display_level >= 3);
         if op_result != InfoResult::Ok {
-            if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 1 {
-                eprintln!("lz4: {}: File format not recognized", path);
-            }
+            diag::error(&format!("lz4: {}: File format not recognized", path));
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("{}: File format not recognized", path),
@@ -754,8 +851,219 @@ pub fn display_compressed_files_info(paths: &[&str]) -> io::Result<()> {
             } else {
                 println!("{:>8}   {}", "-", cfinfo.file_name);
             }
+            if cfinfo.skippable_frames > 0 {
+                println!(
+                    "{:>10} skippable frame(s), {} of user data",
+                    cfinfo.skippable_frames,
+                    to_human(cfinfo.skippable_bytes as f64)
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Quick integrity check (--info)
+// ---------------------------------------------------------------------------
+
+/// Outcome of the `--info` spot check on one file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct QuickCheckReport {
+    /// Number of frames walked, skippable frames included.
+    pub frames: u64,
+    /// Data blocks that were read, checksum-verified, and decoded.
+    pub blocks_checked: u64,
+    /// Blocks that cannot be decoded in isolation: the last block of a
+    /// linked (`-BD`) frame, or any block of a frame that declares a
+    /// dictionary ID.
+    pub blocks_skipped: u64,
+}
+
+fn corrupt(offset: u64, msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} (at offset {})", msg, offset),
+    )
+}
+
+/// A block whose stored checksum does not match its payload.  Its source is
+/// [`Lz4FError::BlockChecksumInvalid`], so the CLI reports a checksum
+/// failure.
+#[derive(Debug)]
+struct BlockChecksumMismatch {
+    offset: u64,
+}
+
+impl std::fmt::Display for BlockChecksumMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "block checksum mismatch (at offset {})", self.offset)
+    }
+}
+
+impl std::error::Error for BlockChecksumMismatch {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&Lz4FError::BlockChecksumInvalid)
+    }
+}
+
+/// Reads and decodes one block, verifying its block checksum when present.
+fn check_block(
+    file: &mut fs::File,
+    block: BlockRef,
+    block_max: usize,
+    block_checksum: bool,
+) -> io::Result<()> {
+    let size = block.size();
+    let checksum_mismatch = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            BlockChecksumMismatch {
+                offset: block.offset,
+            },
+        )
+    };
+    file.seek(SeekFrom::Start(block.offset))?;
+    if block.is_stored() {
+        // Nothing to decode: hash the payload straight from the file.
+        if block_checksum {
+            let hash = xxh32_of_reader((&mut *file).take(size as u64))?;
+            let mut stored = [0u8; 4];
+            file.read_exact(&mut stored)
+                .map_err(|_| corrupt(block.offset, "block truncated"))?;
+            if hash != read_le32(&stored) {
+                return Err(checksum_mismatch());
+            }
         }
+        return Ok(());
+    }
+    let mut data = vec![0u8; size + if block_checksum { 4 } else { 0 }];
+    file.read_exact(&mut data)
+        .map_err(|_| corrupt(block.offset, "block truncated"))?;
+    let (payload, stored) = data.split_at(size);
+    if block_checksum && xxh32_oneshot(payload, 0) != read_le32(stored) {
+        return Err(checksum_mismatch());
     }
+    let mut out = vec![0u8; block_max];
+    decompress_safe(payload, &mut out)
+        .map_err(|_| corrupt(block.offset, "block does not decode"))?;
+    Ok(())
+}
 
+/// Decodes the first and last blocks of one frame found by [`walk_frames`].
+/// Blocks that need history the check does not have are only counted.
+fn quick_check_frame(
+    file: &mut fs::File,
+    layout: &FrameLayout,
+    report: &mut QuickCheckReport,
+) -> io::Result<()> {
+    let (first, last, block_max, block_checksum, linked, needs_dict) = match *layout {
+        FrameLayout::Lz4 {
+            ref info,
+            first,
+            last,
+        } => (
+            first,
+            last,
+            lz4f_get_block_size(info.block_size_id).unwrap_or(4 * MB),
+            info.block_checksum_flag == BlockChecksum::Enabled,
+            info.block_mode == BlockMode::Linked,
+            info.dict_id != 0,
+        ),
+        // Legacy blocks are always independent.
+        FrameLayout::Legacy { first, last } => {
+            (first, last, LEGACY_BLOCK_SIZE_MAX, false, false, false)
+        }
+        FrameLayout::Skippable => return Ok(()),
+    };
+    let mut candidates = Vec::with_capacity(2);
+    if let Some(f) = first {
+        candidates.push((f, needs_dict));
+    }
+    if let (Some(f), Some(l)) = (first, last) {
+        if l.offset != f.offset {
+            candidates.push((l, needs_dict || linked));
+        }
+    }
+    for (block, skip) in candidates {
+        if skip {
+            report.blocks_skipped += 1;
+        } else {
+            check_block(file, block, block_max, block_checksum)?;
+            report.blocks_checked += 1;
+        }
+    }
     Ok(())
 }
+
+/// Spot-checks the integrity of `path` without a full decode.
+///
+/// Every frame's block headers are walked (seeking over payloads) to confirm
+/// the frame is complete, then only its first and last blocks are read,
+/// checksum-verified when block checksums are enabled, and decoded.  On
+/// multi-terabyte files with independent blocks this gives a fast
+/// confidence check that the header, the block chain, and both ends of the
+/// payload are sound.  It is not a substitute for `-t`: middle blocks and
+/// content checksums are not verified.
+pub fn quick_check_file(path: &str) -> io::Result<QuickCheckReport> {
+    let mut file = fs::File::open(path)?;
+    let mut report = QuickCheckReport::default();
+    let walked = walk_frames(&mut file, |file, layout| {
+        quick_check_frame(file, layout, &mut report)?;
+        report.frames += 1;
+        Ok(())
+    })?;
+    if let FrameWalk::TrailingData(offset) = walked {
+        if report.frames == 0 {
+            return Err(corrupt(offset, "file format not recognized"));
+        }
+        diag::emit(
+            Severity::Warning,
+            &format!(
+                "Stream followed by undecodable data at position {} ",
+                offset
+            ),
+        );
+    }
+    if report.frames == 0 {
+        return Err(corrupt(0, "file format not recognized"));
+    }
+    Ok(report)
+}
+
+/// Prints the `--list` table for `paths`, followed by one quick-check line
+/// per file (see [`quick_check_file`]).
+///
+/// Every file is checked even after a failure; the first error is returned.
+pub fn display_compressed_files_info_checked(paths: &[&str]) -> io::Result<()> {
+    display_compressed_files_info(paths)?;
+    let mut first_error = None;
+    for &path in paths {
+        let result = if path == STDIN_MARK {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "quick check needs a seekable file name",
+            ))
+        } else {
+            quick_check_file(path)
+        };
+        match result {
+            Ok(report) => println!(
+                "{}: OK ({} frame(s), {} block(s) decoded, {} skipped)",
+                base_name(path),
+                report.frames,
+                report.blocks_checked,
+                report.blocks_skipped
+            ),
+            Err(e) => {
+                diag::error(&format!("lz4: {}: {}", path, e));
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
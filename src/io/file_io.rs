@@ -1,14 +1,19 @@
 //! File I/O primitives for the LZ4 streaming pipeline.
 //!
-//! This module provides two entry points used by the higher-level I/O
+//! This module provides the entry points used by the higher-level I/O
 //! orchestration layer:
 //!
 //! - [`open_src_file`] — resolves a path string to a `Box<dyn Read>`,
-//!   handling the `"stdin"` sentinel and rejecting directories.
+//!   handling the `"stdin"` sentinel and rejecting directories; reads go
+//!   through a [`ReadAhead`] buffer.
 //! - [`open_dst_file`] — resolves a path string to a [`DstFile`],
 //!   handling the `"stdout"` and `/dev/null` sentinels, enforcing the
 //!   overwrite policy from [`Prefs`], and tracking whether sparse writes are
-//!   appropriate for the resulting file descriptor.
+//!   appropriate for the resulting file descriptor.  With
+//!   [`Prefs::atomic_output`], regular files are written under a temporary
+//!   name and renamed into place by [`DstFile::finish`].
+//! - [`copy_file_stat`] — copies the source's metadata onto a finished
+//!   output, warning instead of failing when the filesystem refuses.
 //!
 //! Sentinel string constants ([`STDIN_MARK`], [`STDOUT_MARK`], [`NUL_MARK`],
 //! [`NULL_OUTPUT`]) are re-exported so callers can compare against them without
@@ -17,13 +22,18 @@
 //! Verbosity-gated diagnostics are emitted via stderr using the global
 //! [`DISPLAY_LEVEL`] atomic.
 
-use std::fs::{File, OpenOptions};
-use std::io::{self, BufReader, Read, Write};
-use std::path::Path;
-use std::sync::atomic::Ordering;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::SystemTime;
 
-use crate::io::prefs::{DISPLAY_LEVEL, LZ4IO_SKIPPABLE0, LZ4IO_SKIPPABLEMASK};
-use crate::util::is_directory;
+use crate::io::diag::{self, Severity};
+use crate::io::prefs::{
+    DISPLAY_LEVEL, IO_BUFFER_SIZE_DEFAULT, LZ4IO_SKIPPABLE0, LZ4IO_SKIPPABLEMASK,
+};
+use crate::io::read_ahead::ReadAhead;
+use crate::util::{file_kind, is_directory, set_file_attrs, FileKind};
 
 // ---------------------------------------------------------------------------
 // Sentinel strings
@@ -63,6 +73,45 @@ fn is_stdout(s: &str) -> bool {
     s == STDOUT_MARK
 }
 
+// ---------------------------------------------------------------------------
+// Standard output
+// ---------------------------------------------------------------------------
+
+/// Set by [`StdoutSink`] once a write fails because the reader went away.
+static STDOUT_CLOSED: AtomicBool = AtomicBool::new(false);
+
+/// `true` once a write to standard output failed with a broken pipe, e.g.
+/// `lz4 -dc big.lz4 | head`.
+///
+/// The error itself may be rewrapped by the time it reaches the caller, so
+/// the binary checks this flag to exit quietly with success, as Unix filters
+/// do when their reader stops early.
+pub fn stdout_closed() -> bool {
+    STDOUT_CLOSED.load(Ordering::Relaxed)
+}
+
+/// Every data path writing to standard output goes through this wrapper so
+/// a broken pipe is recorded in one place.
+pub(crate) struct StdoutSink<W: Write>(pub(crate) W);
+
+impl<W: Write> StdoutSink<W> {
+    fn note(e: io::Error) -> io::Error {
+        if e.kind() == io::ErrorKind::BrokenPipe {
+            STDOUT_CLOSED.store(true, Ordering::Relaxed);
+        }
+        e
+    }
+}
+
+impl<W: Write> Write for StdoutSink<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf).map_err(Self::note)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush().map_err(Self::note)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Skippable magic number
 // ---------------------------------------------------------------------------
@@ -85,11 +134,19 @@ pub fn is_skippable_magic_number(magic: u32) -> bool {
 ///
 /// - If `path` is the sentinel `"stdin"`, returns standard input.
 /// - If `path` is a directory, returns an [`io::ErrorKind::InvalidInput`] error.
-/// - Otherwise opens the file and wraps it in a [`BufReader`] for efficient
-///   sequential reads.
+/// - Otherwise opens the file.
 ///
-/// Diagnostics are printed to stderr when [`DISPLAY_LEVEL`] permits.
+/// Either way the source is read through a [`ReadAhead`] buffer of
+/// [`IO_BUFFER_SIZE_DEFAULT`] bytes; use [`open_src_file_buffered`] to pick
+/// the size.  Diagnostics are printed to stderr when [`DISPLAY_LEVEL`]
+/// permits.
 pub fn open_src_file(path: &str) -> io::Result<Box<dyn Read>> {
+    open_src_file_buffered(path, IO_BUFFER_SIZE_DEFAULT)
+}
+
+/// Like [`open_src_file`], with a read-ahead buffer of `buffer_size` bytes
+/// (normally [`Prefs::io_buffer_size`](crate::io::Prefs::io_buffer_size)).
+pub fn open_src_file_buffered(path: &str, buffer_size: usize) -> io::Result<Box<dyn Read>> {
     if is_stdin(path) {
         if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 4 {
             eprintln!("Using stdin for input");
@@ -99,13 +156,11 @@ pub fn open_src_file(path: &str) -> io::Result<Box<dyn Read>> {
         unsafe {
             libc::_setmode(0, libc::O_BINARY);
         }
-        return Ok(Box::new(io::stdin()));
+        return Ok(Box::new(ReadAhead::with_capacity(io::stdin(), buffer_size)));
     }
 
     if is_directory(Path::new(path)) {
-        if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 1 {
-            eprintln!("lz4: {} is a directory -- ignored", path);
-        }
+        diag::warning(1, &format!("lz4: {} is a directory -- ignored", path));
         return Err(io::Error::new(
             io::ErrorKind::InvalidInput,
             format!("{}: is a directory", path),
@@ -113,12 +168,59 @@ pub fn open_src_file(path: &str) -> io::Result<Box<dyn Read>> {
     }
 
     let f = File::open(path).map_err(|e| {
-        if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 1 {
-            eprintln!("{}: {}", path, e);
-        }
+        diag::error(&format!("{}: {}", path, e));
         e
     })?;
-    Ok(Box::new(BufReader::new(f)))
+    Ok(Box::new(ReadAhead::with_capacity(f, buffer_size)))
+}
+
+/// Size of the source file as reported by the filesystem, if it is usable.
+///
+/// Returns `None` for stdin, non-regular files, and regular files that report
+/// a length of zero.  Pseudo-files under `/proc` and some container overlay
+/// filesystems stat as empty yet yield data when read, so a zero length is
+/// treated as "unknown" and the file is read as a stream until EOF.
+pub(crate) fn src_size_hint(path: &str) -> Option<u64> {
+    if is_stdin(path) {
+        return None;
+    }
+    fs::metadata(path)
+        .ok()
+        .filter(|m| m.is_file() && m.len() > 0)
+        .map(|m| m.len())
+}
+
+// ---------------------------------------------------------------------------
+// Caller-owned descriptors and handles
+// ---------------------------------------------------------------------------
+
+/// A [`File`] on the open file description behind `fd`.
+///
+/// The descriptor is duplicated, so the caller keeps ownership of `fd` and
+/// it stays open after the `File` is dropped.  Both share one file offset:
+/// reading or writing through the `File` moves the caller's position too.
+#[cfg(unix)]
+pub(crate) fn file_from_fd(fd: std::os::fd::BorrowedFd<'_>) -> io::Result<File> {
+    Ok(File::from(fd.try_clone_to_owned()?))
+}
+
+/// Windows counterpart of [`file_from_fd`]: a `File` on a duplicate of
+/// `handle`, which stays open and owned by the caller.
+#[cfg(windows)]
+pub(crate) fn file_from_handle(
+    handle: std::os::windows::io::BorrowedHandle<'_>,
+) -> io::Result<File> {
+    Ok(File::from(handle.try_clone_to_owned()?))
+}
+
+/// Bytes left between the current offset of `file` and its end, when it is
+/// a regular file; `None` for pipes, sockets and devices.  Like
+/// [`src_size_hint`], a zero result is treated as unknown.
+pub(crate) fn remaining_size_hint(file: &mut File) -> Option<u64> {
+    use std::io::Seek;
+    let meta = file.metadata().ok().filter(|m| m.is_file())?;
+    let pos = file.stream_position().ok()?;
+    Some(meta.len().saturating_sub(pos)).filter(|&n| n > 0)
 }
 
 // ---------------------------------------------------------------------------
@@ -137,6 +239,128 @@ pub struct DstFile {
     /// `true` when the underlying file descriptor supports sparse writes
     /// (i.e. `prefs.sparse_file_support > 0` and the destination is not stdout).
     pub sparse_mode: bool,
+    /// Set when the output goes to a temporary file (see
+    /// [`Prefs::atomic_output`]).  The io pipeline takes it before handing
+    /// the `DstFile` on, and commits it once the output is complete and the
+    /// file is closed; other callers use [`finish`](Self::finish).
+    pub(crate) pending: Option<PendingOutput>,
+}
+
+impl DstFile {
+    /// Flushes and closes the destination, then renames a temporary file
+    /// into place.  Without it, an output opened with
+    /// [`Prefs::atomic_output`] is deleted when dropped; other outputs only
+    /// need the flush.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        let pending = self.pending.take();
+        drop(self);
+        match pending {
+            Some(pending) => pending.commit(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An output written under a temporary name in the destination's directory.
+///
+/// [`commit`](Self::commit) renames it to the destination, replacing any
+/// existing file in one step.  Dropping it uncommitted deletes the temporary
+/// file, so an interrupted or failed run never leaves a truncated output
+/// that looks valid.
+#[derive(Debug)]
+pub(crate) struct PendingOutput {
+    temp: PathBuf,
+    dst: PathBuf,
+    committed: bool,
+}
+
+impl PendingOutput {
+    /// Path of the temporary file.
+    #[cfg(test)]
+    pub(crate) fn temp_path(&self) -> &Path {
+        &self.temp
+    }
+
+    /// Renames the temporary file to the destination.  Close every handle
+    /// on it first: Windows cannot rename an open file.
+    pub(crate) fn commit(mut self) -> io::Result<()> {
+        fs::rename(&self.temp, &self.dst).map_err(|e| {
+            diag::error(&format!("{}: {}", self.dst.display(), e));
+            e
+        })?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for PendingOutput {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.temp);
+        }
+    }
+}
+
+/// Whether an output to `path` should go through a temporary file: the
+/// destination must be missing or a regular file.  Renaming over a device,
+/// FIFO or symbolic link would replace it rather than write through it.
+fn wants_atomic(path: &str, prefs: &crate::io::prefs::Prefs) -> bool {
+    prefs.atomic_output
+        && match fs::symlink_metadata(path) {
+            Ok(meta) => meta.is_file(),
+            Err(e) => e.kind() == io::ErrorKind::NotFound,
+        }
+}
+
+/// Creates `<path>.tmpXXXX`, a new file next to `path`.
+fn create_temp_beside(path: &str) -> io::Result<(File, PendingOutput)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let seed = std::process::id() as usize
+        ^ SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos() as usize);
+    let mut attempt = 0;
+    loop {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let suffix = seed.wrapping_add(n.wrapping_mul(0x9E37)) & 0xFFFF;
+        let temp = PathBuf::from(format!("{}.tmp{:04x}", path, suffix));
+        match OpenOptions::new().write(true).create_new(true).open(&temp) {
+            Ok(f) => {
+                let pending = PendingOutput {
+                    temp,
+                    dst: PathBuf::from(path),
+                    committed: false,
+                };
+                return Ok((f, pending));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 64 => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Opens a regular destination file after the overwrite check, through a
+/// temporary file when `prefs.atomic_output` applies to `path`.
+pub(crate) fn open_regular_dst(
+    path: &str,
+    prefs: &crate::io::prefs::Prefs,
+) -> io::Result<(File, Option<PendingOutput>)> {
+    confirm_overwrite(path, prefs)?;
+    let opened = if wants_atomic(path, prefs) {
+        create_temp_beside(path).map(|(f, pending)| (f, Some(pending)))
+    } else {
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .map(|f| (f, None))
+    };
+    opened.map_err(|e| {
+        diag::error(&format!("{}: {}", path, e));
+        e
+    })
 }
 
 impl Write for DstFile {
@@ -148,17 +372,80 @@ impl Write for DstFile {
     }
 }
 
+/// Overwrite guard shared by every destination opener: returns `Ok` when
+/// `path` may be created or truncated.
+///
+/// With `prefs.overwrite` set, or when `path` does not exist, there is
+/// nothing to check.  Otherwise the user is asked on stderr, but only when
+/// [`DISPLAY_LEVEL`] is above 1 and stdin is a terminal; a script, a pipe
+/// feeding the data itself, or `-q` gets an [`io::ErrorKind::AlreadyExists`]
+/// error instead of a prompt that would block or eat input.
+pub(crate) fn confirm_overwrite(path: &str, prefs: &crate::io::prefs::Prefs) -> io::Result<()> {
+    if prefs.overwrite || !Path::new(path).exists() {
+        return Ok(());
+    }
+    if DISPLAY_LEVEL.load(Ordering::Relaxed) <= 1 || !io::stdin().is_terminal() {
+        diag::emit(
+            Severity::Error,
+            &format!("{} already exists; not overwritten (use -f to force)", path),
+        );
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{}: already exists; not overwritten", path),
+        ));
+    }
+    eprint!("{} already exists; do you want to overwrite (y/N) ? ", path);
+    let _ = io::stderr().flush();
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    let first = line.trim_start().chars().next().unwrap_or('\0');
+    if first != 'y' && first != 'Y' {
+        eprintln!("    not overwritten  ");
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{}: not overwritten", path),
+        ));
+    }
+    Ok(())
+}
+
+/// Refuses to compress a block device, socket or other special entry unless
+/// `prefs.force` is set, as with `-f`.
+///
+/// FIFOs and character devices are accepted: they are read as a stream of
+/// unknown size, like stdin, which is what `lz4 <(cmd)` and `lz4 /dev/stdin`
+/// need.  A block device is refused because reading a whole disk is rarely
+/// what was meant.  Stdin and directories are left to [`open_src_file`],
+/// which accepts the former and rejects the latter.
+pub(crate) fn check_regular_src(path: &str, prefs: &crate::io::prefs::Prefs) -> io::Result<()> {
+    if prefs.force || is_stdin(path) {
+        return Ok(());
+    }
+    match file_kind(Path::new(path)) {
+        Some(FileKind::BlockDevice | FileKind::Socket | FileKind::Other) => {
+            // Reported here, like an open failure: the multi-file loops only
+            // count failed inputs.
+            let err = io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a regular file (use -f to force)",
+            );
+            diag::error(&format!("{}: {}", path, err));
+            Err(err)
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Opens a destination for writing, returning a [`DstFile`].
 ///
 /// Resolves special sentinels before touching the filesystem:
 /// - `"stdout"` → stdout (`is_stdout = true`, `sparse_mode = false`).
 /// - [`NUL_MARK`] → [`io::sink`] (all bytes discarded, no file created).
 ///
-/// For regular paths, enforces the overwrite policy from `prefs`:
-/// - When `prefs.overwrite == false` and the file already exists, the
-///   behaviour depends on [`DISPLAY_LEVEL`]: at level ≤ 1 the call returns
-///   an [`io::ErrorKind::AlreadyExists`] error without prompting; at higher
-///   levels an interactive yes/no prompt is shown on stderr.
+/// For regular paths, enforces the overwrite policy from `prefs` through
+/// [`confirm_overwrite`]: an existing file is refused, or the user is asked
+/// when stdin is a terminal.  With `prefs.atomic_output` the data goes to a
+/// temporary file, which only [`DstFile::finish`] renames to `path`.
 ///
 /// `sparse_mode` on the returned [`DstFile`] is `true` when
 /// `prefs.sparse_file_support > 0` and the destination is a regular file.
@@ -179,9 +466,10 @@ pub fn open_dst_file(path: &str, prefs: &crate::io::prefs::Prefs) -> io::Result<
             );
         }
         return Ok(DstFile {
-            inner: Box::new(io::stdout()),
+            inner: Box::new(StdoutSink(io::stdout())),
             is_stdout: true,
             sparse_mode: false,
+            pending: None,
         });
     }
 
@@ -190,46 +478,11 @@ pub fn open_dst_file(path: &str, prefs: &crate::io::prefs::Prefs) -> io::Result<
             inner: Box::new(io::sink()),
             is_stdout: false,
             sparse_mode: false,
+            pending: None,
         });
     }
 
-    // Overwrite guard: refuse or prompt before clobbering an existing file.
-    if !prefs.overwrite && Path::new(path).exists() {
-        let display_level = DISPLAY_LEVEL.load(Ordering::Relaxed);
-        if display_level <= 1 {
-            // No interaction possible — refuse silently.
-            eprintln!("{} already exists; not overwritten  ", path);
-            return Err(io::Error::new(
-                io::ErrorKind::AlreadyExists,
-                format!("{}: already exists; not overwritten", path),
-            ));
-        }
-        // Interactive prompt.
-        eprint!("{} already exists; do you want to overwrite (y/N) ? ", path);
-        let _ = io::stderr().flush();
-        let mut line = String::new();
-        io::stdin().read_line(&mut line)?;
-        let first = line.trim_start().chars().next().unwrap_or('\0');
-        if first != 'y' && first != 'Y' {
-            eprintln!("    not overwritten  ");
-            return Err(io::Error::new(
-                io::ErrorKind::AlreadyExists,
-                format!("{}: not overwritten", path),
-            ));
-        }
-    }
-
-    let f = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(path)
-        .map_err(|e| {
-            if DISPLAY_LEVEL.load(Ordering::Relaxed) >= 1 {
-                eprintln!("{}: {}", path, e);
-            }
-            e
-        })?;
+    let (f, pending) = open_regular_dst(path, prefs)?;
 
     // Sparse mode applies to regular files only, never to stdout.
     // Because we have already returned for the stdout sentinel above,
@@ -260,14 +513,73 @@ pub fn open_dst_file(path: &str, prefs: &crate::io::prefs::Prefs) -> io::Result<
         inner: Box::new(f),
         is_stdout: false,
         sparse_mode,
+        pending,
     })
 }
 
+// ---------------------------------------------------------------------------
+// copy_file_stat — UTIL_setFileStat (lz4io.c lines 1467-1473, 2467-2473)
+// ---------------------------------------------------------------------------
+
+/// Number of outputs whose metadata could not be fully restored.
+static STAT_WARNINGS: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns how many outputs [`copy_file_stat`] has warned about since the
+/// process started (or since [`reset_stat_warnings`]).
+pub fn stat_warnings() -> usize {
+    STAT_WARNINGS.load(Ordering::Relaxed)
+}
+
+/// Resets the [`stat_warnings`] tally to zero.
+pub fn reset_stat_warnings() {
+    STAT_WARNINGS.store(0, Ordering::Relaxed);
+}
+
+/// Copies permissions from the file at `src` to the file at `dst`, plus the
+/// modification time when `prefs.restore_times` is set and the owner and
+/// group when `prefs.restore_owner` is set.
+///
+/// Failures do not fail the operation: the output is already complete, so a
+/// filesystem that cannot store the metadata (FAT, some NFS mounts) only
+/// earns a warning at level 2 and a tick in [`stat_warnings`].
+pub fn copy_file_stat(src: &fs::Metadata, dst: &str, prefs: &crate::io::prefs::Prefs) {
+    let mtime = if prefs.restore_times {
+        Some(src.modified().unwrap_or(SystemTime::UNIX_EPOCH))
+    } else {
+        None
+    };
+
+    #[cfg(unix)]
+    let (owner, mode) = {
+        use std::os::unix::fs::MetadataExt;
+        let owner = prefs.restore_owner.then(|| (src.uid(), src.gid()));
+        (owner, src.mode())
+    };
+    #[cfg(not(unix))]
+    let (owner, mode) = (
+        None,
+        if src.permissions().readonly() {
+            0o444
+        } else {
+            0o644
+        },
+    );
+
+    if let Err(e) = set_file_attrs(Path::new(dst), mtime, owner, mode) {
+        STAT_WARNINGS.fetch_add(1, Ordering::Relaxed);
+        diag::warning(
+            2,
+            &format!("{}: cannot restore file attributes: {} \n", dst, e),
+        );
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Unit tests
 // ---------------------------------------------------------------------------
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
     use crate::io::prefs::Prefs;
@@ -376,6 +688,116 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn atomic_output_appears_only_on_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.lz4");
+        std::fs::write(&path, b"old contents").unwrap();
+        let name = path.to_str().unwrap();
+        let mut prefs = Prefs::default();
+        prefs.set_atomic_output(true);
+
+        // Committed: the new bytes replace the old file and no temp remains.
+        let mut dst = open_dst_file(name, &prefs).unwrap();
+        let temp = dst
+            .pending
+            .as_ref()
+            .expect("regular file goes through a temp")
+            .temp_path()
+            .to_path_buf();
+        assert!(temp.to_str().unwrap().starts_with(&format!("{name}.tmp")));
+        dst.write_all(b"new contents").unwrap();
+        dst.flush().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"old contents");
+        dst.finish().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new contents");
+        assert!(!temp.exists());
+
+        // Abandoned: the temp file is deleted and the output is untouched.
+        let mut dst = open_dst_file(name, &prefs).unwrap();
+        let temp = dst.pending.as_ref().unwrap().temp_path().to_path_buf();
+        dst.write_all(b"partial").unwrap();
+        drop(dst);
+        assert!(!temp.exists());
+        assert_eq!(std::fs::read(&path).unwrap(), b"new contents");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn non_atomic_output_writes_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.lz4");
+        let mut prefs = Prefs::default();
+        prefs.set_atomic_output(false);
+        let mut dst = open_dst_file(path.to_str().unwrap(), &prefs).unwrap();
+        assert!(dst.pending.is_none());
+        dst.write_all(b"direct").unwrap();
+        drop(dst);
+        assert_eq!(std::fs::read(&path).unwrap(), b"direct");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_output_skips_non_regular_destinations() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        std::fs::write(&target, b"").unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        let mut prefs = Prefs::default();
+        prefs.set_atomic_output(true);
+        let mut dst = open_dst_file(link.to_str().unwrap(), &prefs).unwrap();
+        assert!(dst.pending.is_none(), "a symlink is written through");
+        dst.write_all(b"via link").unwrap();
+        drop(dst);
+        assert_eq!(std::fs::read(&target).unwrap(), b"via link");
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn check_regular_src_streams_fifos_and_gates_other_specials() {
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("pipe");
+        let c_path = std::ffi::CString::new(fifo.to_str().unwrap()).unwrap();
+        // SAFETY: `c_path` is a valid NUL-terminated path.
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        let socket = dir.path().join("sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        let regular = dir.path().join("file");
+        std::fs::write(&regular, b"data").unwrap();
+
+        let mut prefs = Prefs::default();
+        prefs.set_force(false);
+        assert!(check_regular_src(fifo.to_str().unwrap(), &prefs).is_ok());
+        assert!(check_regular_src("/dev/null", &prefs).is_ok());
+        let err = check_regular_src(socket.to_str().unwrap(), &prefs).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("use -f to force"), "{err}");
+        assert!(check_regular_src(regular.to_str().unwrap(), &prefs).is_ok());
+        assert!(check_regular_src(STDIN_MARK, &prefs).is_ok());
+        prefs.set_force(true);
+        assert!(check_regular_src(socket.to_str().unwrap(), &prefs).is_ok());
+    }
+
+    #[test]
+    fn src_size_hint_ignores_unknown_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let full = dir.path().join("full.bin");
+        let empty = dir.path().join("empty.bin");
+        std::fs::write(&full, b"twelve bytes").unwrap();
+        std::fs::write(&empty, b"").unwrap();
+        assert_eq!(src_size_hint(full.to_str().unwrap()), Some(12));
+        assert_eq!(src_size_hint(empty.to_str().unwrap()), None);
+        assert_eq!(src_size_hint(dir.path().to_str().unwrap()), None);
+        assert_eq!(src_size_hint(STDIN_MARK), None);
+        #[cfg(target_os = "linux")]
+        assert_eq!(src_size_hint("/proc/self/status"), None);
+    }
+
     #[test]
     fn sentinel_constants() {
         assert_eq!(STDIN_MARK, "stdin");
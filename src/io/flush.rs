@@ -0,0 +1,161 @@
+//! Interval-flushing writer for streaming decompressed output.
+//!
+//! Decompressed data normally reaches its destination whenever the layers
+//! underneath choose to write it out: a line-buffered stdout holds a partial
+//! line, a `BufWriter` waits until it is full.  A consumer following the output
+//! as it grows (`lz4cat log.lz4 | tail -f`, a log shipper reading a pipe) wants
+//! a bound on how much decoded data can sit in memory instead.
+//!
+//! [`FlushEvery`] collects written bytes and hands them to the inner writer in
+//! units of at least `interval` bytes, each as a single `write_all` followed by
+//! `flush`.  Nothing is split below the interval, so when several processes
+//! append to the same pipe or `O_APPEND` file, their output interleaves only at
+//! flush boundaries.  The CLI turns this on with `--flush-every BYTES`; the
+//! `io` entry points honour [`Prefs::flush_every`](crate::io::Prefs::flush_every).
+
+use std::io::{self, Write};
+
+/// A writer that forwards data to `W` at least every `interval` bytes.
+///
+/// Bytes are held until `interval` of them have accumulated, then written and
+/// flushed together.  A single write of `interval` bytes or more goes through
+/// unbuffered.  [`flush`](Write::flush) emits whatever is pending; dropping the
+/// writer does the same but ignores errors, as [`io::BufWriter`] does.
+///
+/// ```
+/// use lz4::io::FlushEvery;
+/// use std::io::Write;
+///
+/// let mut out = Vec::new();
+/// let mut w = FlushEvery::new(&mut out, 4);
+/// w.write_all(b"ab").unwrap();
+/// assert!(w.get_ref().is_empty());
+/// w.write_all(b"cd").unwrap();
+/// assert_eq!(w.get_ref().as_slice(), b"abcd");
+/// ```
+pub struct FlushEvery<W: Write> {
+    inner: W,
+    interval: usize,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> FlushEvery<W> {
+    /// Wraps `inner`, flushing every `interval` bytes (at least 1).
+    pub fn new(inner: W, interval: usize) -> Self {
+        FlushEvery {
+            inner,
+            interval: interval.max(1),
+            pending: Vec::new(),
+        }
+    }
+
+    /// The flush interval in bytes.
+    pub fn interval(&self) -> usize {
+        self.interval
+    }
+
+    /// The wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// The wrapped writer, mutably.  Writing to it directly bypasses any
+    /// bytes still pending here.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    fn emit_pending(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            self.inner.write_all(&self.pending)?;
+            self.pending.clear();
+        }
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Write for FlushEvery<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.pending.is_empty() && buf.len() >= self.interval {
+            self.inner.write_all(buf)?;
+            self.inner.flush()?;
+        } else {
+            self.pending.extend_from_slice(buf);
+            if self.pending.len() >= self.interval {
+                self.emit_pending()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.emit_pending()
+    }
+}
+
+impl<W: Write> Drop for FlushEvery<W> {
+    fn drop(&mut self) {
+        let _ = self.emit_pending();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records each `write` call and counts flushes.
+    #[derive(Default)]
+    struct Recorder {
+        writes: Vec<Vec<u8>>,
+        flushes: usize,
+    }
+
+    impl Write for Recorder {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.writes.push(buf.to_vec());
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn coalesces_small_writes_up_to_interval() {
+        let mut w = FlushEvery::new(Recorder::default(), 10);
+        for _ in 0..4 {
+            w.write_all(b"abc").unwrap();
+        }
+        let rec = w.get_ref();
+        assert_eq!(rec.writes, vec![b"abcabcabcabc".to_vec()]);
+        assert_eq!(rec.flushes, 1);
+    }
+
+    #[test]
+    fn large_write_passes_through_whole() {
+        let mut w = FlushEvery::new(Recorder::default(), 4);
+        w.write_all(&[7u8; 100]).unwrap();
+        assert_eq!(w.get_ref().writes, vec![vec![7u8; 100]]);
+        assert_eq!(w.get_ref().flushes, 1);
+    }
+
+    #[test]
+    fn flush_and_drop_emit_pending_bytes() {
+        let mut out = Vec::new();
+        {
+            let mut w = FlushEvery::new(&mut out, 1024);
+            w.write_all(b"head").unwrap();
+            w.flush().unwrap();
+            assert_eq!(w.get_ref().as_slice(), b"head");
+            w.write_all(b"tail").unwrap();
+        }
+        assert_eq!(out, b"headtail");
+    }
+
+    #[test]
+    fn zero_interval_is_clamped() {
+        let w = FlushEvery::new(io::sink(), 0);
+        assert_eq!(w.interval(), 1);
+    }
+}
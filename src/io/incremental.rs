@@ -0,0 +1,149 @@
+//! Up-to-date checks for incremental compression (`--mtime-window`).
+//!
+//! Re-running `lz4 -r -m` over a large tree recompresses every file, even
+//! those whose `.lz4` is already current.  With
+//! [`Prefs::mtime_window`](crate::io::Prefs::mtime_window) set, the compress
+//! entry points first ask [`is_up_to_date`] and leave the output alone when it
+//! says yes.
+//!
+//! An output counts as current when it is a regular file whose modification
+//! time is no more than the window older than the source's, and whose frame
+//! header records the source's exact size.  The size comes from the header's
+//! content-size field, which the compressor always fills in while the window
+//! is set, so no sidecar file is needed; outputs written without it simply
+//! never match and are rebuilt once.  The window absorbs coarse or skewed
+//! timestamps (FAT keeps 2-second mtimes); `0` demands the output be at least
+//! as new as the source, which holds both when the source mtime was copied
+//! across and when the output was written later.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::frame::decompress::{lz4f_get_frame_info, Lz4FDCtx};
+use crate::frame::types::{LZ4F_VERSION, MAX_FH_SIZE};
+use crate::io::file_io::{NUL_MARK, STDIN_MARK, STDOUT_MARK};
+use crate::io::prefs::{display_level, Prefs};
+
+/// Returns the content size recorded in the frame header at the start of
+/// `path`, or `None` if the file cannot be read, does not start with an LZ4
+/// frame, or its header leaves the size out.
+pub fn recorded_content_size(path: &Path) -> Option<u64> {
+    let mut header = Vec::with_capacity(MAX_FH_SIZE);
+    File::open(path)
+        .ok()?
+        .take(MAX_FH_SIZE as u64)
+        .read_to_end(&mut header)
+        .ok()?;
+    let mut dctx = Lz4FDCtx::new(LZ4F_VERSION);
+    let (info, _, _) = lz4f_get_frame_info(&mut dctx, &header).ok()?;
+    (info.content_size != 0).then_some(info.content_size)
+}
+
+/// Returns `true` if `dst` already holds an up-to-date compressed copy of
+/// `src`: both are regular files, `dst` is at most `window_secs` seconds
+/// older than `src`, and `dst`'s frame header records `src`'s size.
+///
+/// Standard streams and the null device are never up to date, nor is an
+/// empty source (a zero content size means "unknown" in the frame format).
+pub fn is_up_to_date(src: &str, dst: &str, window_secs: u64) -> bool {
+    if [STDIN_MARK, STDOUT_MARK, NUL_MARK].contains(&src)
+        || [STDIN_MARK, STDOUT_MARK, NUL_MARK].contains(&dst)
+    {
+        return false;
+    }
+    let (Ok(src_meta), Ok(dst_meta)) = (fs::metadata(src), fs::metadata(dst)) else {
+        return false;
+    };
+    if !src_meta.is_file() || !dst_meta.is_file() || src_meta.len() == 0 {
+        return false;
+    }
+    let (Ok(src_mtime), Ok(dst_mtime)) = (src_meta.modified(), dst_meta.modified()) else {
+        return false;
+    };
+    let fresh = match src_mtime.duration_since(dst_mtime) {
+        Ok(lag) => lag <= Duration::from_secs(window_secs),
+        Err(_) => true, // dst is newer than src
+    };
+    fresh && recorded_content_size(Path::new(dst)) == Some(src_meta.len())
+}
+
+/// Returns `true`, after saying so at level 2, if `io_prefs` asks for
+/// incremental compression and `dst` is already up to date with `src`.
+pub(crate) fn skip_up_to_date(src: &str, dst: &str, io_prefs: &Prefs) -> bool {
+    match io_prefs.mtime_window {
+        Some(window) if is_up_to_date(src, dst, window) => {
+            display_level(
+                2,
+                &format!("lz4: {}: {} is up to date, skipped \n", src, dst),
+            );
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::compress::lz4f_compress_frame;
+    use crate::frame::types::{FrameInfo, Preferences};
+    use std::time::SystemTime;
+
+    fn write_frame(path: &Path, data: &[u8], record_size: bool) {
+        let prefs = Preferences {
+            frame_info: FrameInfo {
+                content_size: if record_size { data.len() as u64 } else { 0 },
+                ..FrameInfo::default()
+            },
+            ..Preferences::default()
+        };
+        let mut out = vec![0u8; 1024 + data.len()];
+        let n = lz4f_compress_frame(&mut out, data, Some(&prefs)).unwrap();
+        fs::write(path, &out[..n]).unwrap();
+    }
+
+    fn set_mtime(path: &Path, t: SystemTime) {
+        filetime::set_file_mtime(path, filetime::FileTime::from_system_time(t)).unwrap();
+    }
+
+    #[test]
+    fn recorded_content_size_reads_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let with = dir.path().join("with.lz4");
+        let without = dir.path().join("without.lz4");
+        write_frame(&with, b"hello hello hello", true);
+        write_frame(&without, b"hello hello hello", false);
+        assert_eq!(recorded_content_size(&with), Some(17));
+        assert_eq!(recorded_content_size(&without), None);
+        assert_eq!(recorded_content_size(&dir.path().join("missing")), None);
+    }
+
+    #[test]
+    fn up_to_date_needs_matching_size_and_fresh_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("a.txt");
+        let dst = dir.path().join("a.txt.lz4");
+        let (src_s, dst_s) = (src.to_str().unwrap(), dst.to_str().unwrap());
+        let now = SystemTime::now();
+        fs::write(&src, b"some source text").unwrap();
+        write_frame(&dst, b"some source text", true);
+        set_mtime(&src, now);
+        set_mtime(&dst, now);
+        assert!(is_up_to_date(src_s, dst_s, 0));
+
+        // Source touched after the output was written.
+        set_mtime(&src, now + Duration::from_secs(10));
+        assert!(!is_up_to_date(src_s, dst_s, 0));
+        assert!(is_up_to_date(src_s, dst_s, 10));
+
+        // Same mtime, different size.
+        set_mtime(&src, now);
+        fs::write(&src, b"longer source text").unwrap();
+        set_mtime(&src, now);
+        assert!(!is_up_to_date(src_s, dst_s, 0));
+
+        assert!(!is_up_to_date(src_s, STDOUT_MARK, 0));
+    }
+}
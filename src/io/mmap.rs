@@ -0,0 +1,156 @@
+//! Read-only memory mapping of large compression inputs (`mmap` feature, Unix).
+//!
+//! Compressing through a mapping skips the copy from the page cache into the
+//! `read()` buffer, and lets the multi-threaded path hand each worker a slice
+//! of the file instead of a freshly allocated chunk.  Mapping only pays off
+//! for large regular files: pipes, character devices and stdin cannot be
+//! mapped, and below [`MMAP_MIN_SIZE`] the saved copy is not worth the
+//! `mmap`/`munmap` round trip.  [`MappedFile::open`] returns `None` for all of
+//! those, and callers fall back to buffered reads.
+//!
+//! A mapping reflects later writes to the file.  If another process
+//! truncates the file while it is being compressed, touching the vanished
+//! pages raises `SIGBUS`, as it does for the reference `lz4` and other
+//! mmap-based tools.  Inputs that may shrink underneath the compressor should
+//! be compressed without the `mmap` feature.
+
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+
+use crate::io::file_io::STDIN_MARK;
+use crate::io::prefs::MB;
+
+/// Smallest regular file that is memory-mapped; smaller inputs are read.
+pub const MMAP_MIN_SIZE: u64 = 4 * MB as u64;
+
+/// A read-only, private mapping of a whole file, unmapped on drop.
+pub struct MappedFile {
+    ptr: *const u8,
+    len: usize,
+}
+
+// SAFETY: the mapping is read-only and owned exclusively by this value;
+// sharing `&[u8]` views across threads is as safe as sharing any slice.
+unsafe impl Send for MappedFile {}
+unsafe impl Sync for MappedFile {}
+
+impl MappedFile {
+    /// Maps `path` if it is a regular file of at least [`MMAP_MIN_SIZE`] bytes.
+    ///
+    /// Returns `Ok(None)` for stdin, non-regular files and small files, and
+    /// when the kernel refuses the mapping; the caller should read the file
+    /// instead.  Errors opening or inspecting the file are returned.
+    ///
+    /// The length is taken from the open descriptor, so it is the size of
+    /// the file actually mapped even if `path` is replaced meanwhile, and
+    /// callers should use [`len`](Self::len) rather than stat `path` again.
+    /// The mapping is only as stable as the file: if it is truncated while
+    /// mapped, reading the lost pages through [`as_slice`](Self::as_slice)
+    /// raises `SIGBUS` and kills the process.
+    pub fn open(path: &str) -> io::Result<Option<Self>> {
+        if path == STDIN_MARK {
+            return Ok(None);
+        }
+        // Skip special files before opening them; `O_NONBLOCK` also keeps a
+        // FIFO swapped in after this check from blocking the open.
+        if !fs::metadata(path)?.is_file() {
+            return Ok(None);
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)?;
+        let meta = file.metadata()?;
+        if !meta.is_file() || meta.len() < MMAP_MIN_SIZE {
+            return Ok(None);
+        }
+        let Ok(len) = usize::try_from(meta.len()) else {
+            return Ok(None);
+        };
+        // SAFETY: a fresh read-only private mapping of an open descriptor;
+        // the kernel picks the address.  The descriptor may be closed once
+        // the mapping exists.
+        let ptr = unsafe {
+            libc::mmap(
+                core::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Ok(None);
+        }
+        let map = MappedFile {
+            ptr: ptr as *const u8,
+            len,
+        };
+        crate::frame::advise::sequential(map.as_slice());
+        Ok(Some(map))
+    }
+
+    /// The mapped bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: `ptr` maps `len` readable bytes for the lifetime of `self`.
+        unsafe { core::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// Length of the mapping in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// `true` if the mapping is empty (never the case for a mapped file).
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        // SAFETY: `ptr`/`len` describe a mapping created by `open` and not
+        // yet unmapped; no slices borrowed from `self` outlive it.
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn maps_large_regular_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("big.bin");
+        let data: Vec<u8> = (0..MMAP_MIN_SIZE as usize).map(|i| i as u8).collect();
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(&data)
+            .unwrap();
+        let map = MappedFile::open(path.to_str().unwrap()).unwrap().unwrap();
+        assert_eq!(map.len(), data.len());
+        assert_eq!(map.as_slice(), &data[..]);
+    }
+
+    #[test]
+    fn skips_small_files_and_stdin() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("small.bin");
+        std::fs::write(&path, b"tiny").unwrap();
+        assert!(MappedFile::open(path.to_str().unwrap()).unwrap().is_none());
+        assert!(MappedFile::open(STDIN_MARK).unwrap().is_none());
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        assert!(MappedFile::open("/nonexistent/lz4r/mmap").is_err());
+    }
+}
@@ -4,6 +4,8 @@
 //!
 //! - [`Prefs`] — a plain value type holding all tunable compression and
 //!   decompression parameters (block size, checksum policy, worker count, etc.).
+//! - [`IoPrefsBuilder`] — the documented, chainable way to construct a `Prefs`.
+//!   A [`ProgressSink`] installed here receives progress updates.
 //! - [`DISPLAY_LEVEL`] / [`set_notification_level`] — an atomic global controlling
 //!   how much diagnostic output the library emits to stderr.
 //! - [`display_level`] — a conditional stderr printer keyed on that level.
@@ -12,6 +14,11 @@
 //! - Assorted numeric constants (magic numbers, buffer sizes, and SI units).
 
 use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+
+use crate::frame::types::{BlockSizeId, BlockSizePolicy};
+use crate::frame::ChecksumPolicy;
+use crate::io::progress::ProgressSink;
 
 use crate::timefn::{clock_span_ns, DurationNs, TimeT};
 
@@ -38,7 +45,11 @@ pub const CACHELINE: usize = 64;
 pub const LEGACY_BLOCKSIZE: usize = 8 * MB;
 pub const MIN_STREAM_BUFSIZE: usize = 192 * KB;
 pub const LZ4IO_BLOCKSIZEID_DEFAULT: u32 = 7;
-pub const LZ4_MAX_DICT_SIZE: usize = 64 * KB;
+pub const LZ4_MAX_DICT_SIZE: usize = crate::dict::MAX_DICT_SIZE;
+/// Default read-ahead buffer for source files (see [`Prefs::io_buffer_size`]).
+pub const IO_BUFFER_SIZE_DEFAULT: usize = 256 * KB;
+pub const IO_BUFFER_SIZE_MIN: usize = 4 * KB;
+pub const IO_BUFFER_SIZE_MAX: usize = 64 * MB;
 
 // ---------------------------------------------------------------------------
 // Display / notification globals
@@ -176,24 +187,44 @@ pub enum BlockMode {
 
 /// All tunable parameters for LZ4 compression and decompression.
 ///
-/// `Prefs` is a plain value type. Callers create one with [`Prefs::default`]
-/// and apply setters as needed before passing it to the I/O routines.
+/// `Prefs` is a plain value type. Callers create one with [`Prefs::builder`]
+/// (or [`Prefs::default`] plus setters) before passing it to the I/O routines.
 #[derive(Clone, Debug)]
 pub struct Prefs {
     /// Pass compressed data through without decompressing. Default: false.
     pub pass_through: bool,
     /// Overwrite existing destination files without prompting. Default: true.
     pub overwrite: bool,
+    /// Compress block devices and sockets instead of skipping them; FIFOs
+    /// and character devices are streamed either way. Set together with
+    /// `overwrite` by `-f`. Default: true.
+    pub force: bool,
     /// Test mode — decompress but discard output. Default: false.
     pub test_mode: bool,
     /// LZ4F block-size ID (4–7 corresponding to 64 KB – 4 MB). Default: 7.
     pub block_size_id: u32,
     /// Actual block size in bytes (0 = derive from block_size_id at use time). Default: 0.
     pub block_size: usize,
+    /// How each file's block size is chosen. Under [`BlockSizePolicy::Auto`]
+    /// (the default) `block_size_id` is an upper bound and every file gets the
+    /// size picked by [`BlockSizePolicy::resolve`]; the block size setters
+    /// switch to [`BlockSizePolicy::Fixed`].
+    pub block_size_policy: BlockSizePolicy,
     /// Append a per-block xxHash32 checksum. Default: false.
     pub block_checksum: bool,
     /// Append a whole-stream xxHash32 checksum. Default: true.
     pub stream_checksum: bool,
+    /// How block and content checksums are treated when decompressing:
+    /// require, verify or skip each kind. [`ChecksumPolicy::SKIP`] (`--no-crc`)
+    /// suits trusted local archives where restore speed matters more; header
+    /// checksums are still checked. Default: [`ChecksumPolicy::VERIFY`].
+    pub checksum_policy: ChecksumPolicy,
+    /// Skip blocks that fail their checksum instead of aborting when
+    /// decompressing frames with independent blocks (`--recover`). Each
+    /// skipped block is reported as a warning, and the decompress call
+    /// returns a [`DamagedOutput`](crate::io::DamagedOutput) error once the
+    /// output is written. Default: false.
+    pub recover: bool,
     /// Compress blocks independently (`true`) or linked (`false`). Default: true.
     pub block_independence: bool,
     /// Sparse-file write support: 0 = off, 1 = auto, 2 = forced. Default: 1.
@@ -208,8 +239,41 @@ pub struct Prefs {
     pub dictionary_filename: Option<String>,
     /// Remove source file after successful compression/decompression. Default: false.
     pub remove_src_file: bool,
+    /// Copy the source's modification time to the output. Default: true.
+    pub restore_times: bool,
+    /// Copy the source's owner and group to the output (POSIX only; needs
+    /// privileges to give files away). Default: false; the CLI turns it on
+    /// with `--same-owner` or `--gzip-compat`.
+    pub restore_owner: bool,
+    /// Write each output file under a temporary name in the same directory
+    /// and rename it into place once complete, so a failed or interrupted
+    /// run leaves no truncated output. Default: false; the CLI turns it on.
+    pub atomic_output: bool,
+    /// gzip-style defaults (`--gzip-compat`, or the `lz4gz` alias): sources
+    /// are removed after success, existing outputs are refused without `-f`,
+    /// and mode, mtime and owner are copied across. Set through
+    /// [`Prefs::set_gzip_compat`]. Default: false.
+    pub gzip_compat: bool,
     /// Number of worker threads for multi-threaded compression. Default: auto-detected.
     pub nb_workers: i32,
+    /// Receiver for progress updates; replaces the built-in progress lines. Default: None.
+    pub progress_sink: Option<Arc<dyn ProgressSink>>,
+    /// Suppress the built-in `Read : …` / `Decompressed : …` progress lines
+    /// while keeping the final per-file summaries. Default: false.
+    pub quiet_progress: bool,
+    /// Flush decompressed output at least every this many bytes (see
+    /// [`crate::io::FlushEvery`]); `None` leaves buffering to the destination.
+    /// Default: None.
+    pub flush_every: Option<usize>,
+    /// Size of the read-ahead buffer on source files, in bytes (see
+    /// [`crate::io::read_ahead::ReadAhead`]). Default: 256 KB.
+    pub io_buffer_size: usize,
+    /// Incremental compression: skip a source whose output is already up to
+    /// date, i.e. no more than this many seconds older than the source and
+    /// recording the source's size in its frame header (see
+    /// [`crate::io::incremental`]).  While set, outputs always record their
+    /// content size. Default: None.
+    pub mtime_window: Option<u64>,
 }
 
 // ---------------------------------------------------------------------------
@@ -238,6 +302,17 @@ pub fn default_nb_workers() -> i32 {
     }
 }
 
+/// Maps an io block-size ID (4–7) to the frame type; 7 and anything out of
+/// range map to 4 MB.
+pub(crate) fn frame_block_size_id(bsid: u32) -> BlockSizeId {
+    match bsid {
+        4 => BlockSizeId::Max64Kb,
+        5 => BlockSizeId::Max256Kb,
+        6 => BlockSizeId::Max1Mb,
+        _ => BlockSizeId::Max4Mb,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Default implementation
 // ---------------------------------------------------------------------------
@@ -247,11 +322,15 @@ impl Default for Prefs {
         Prefs {
             pass_through: false,
             overwrite: true,
+            force: true,
             test_mode: false,
             block_size_id: LZ4IO_BLOCKSIZEID_DEFAULT,
             block_size: 0,
+            block_size_policy: BlockSizePolicy::Auto,
             block_checksum: false,
             stream_checksum: true,
+            checksum_policy: ChecksumPolicy::VERIFY,
+            recover: false,
             block_independence: true,
             sparse_file_support: 1,
             content_size_flag: false,
@@ -259,7 +338,16 @@ impl Default for Prefs {
             favor_dec_speed: false,
             dictionary_filename: None,
             remove_src_file: false,
+            restore_times: true,
+            restore_owner: false,
+            atomic_output: false,
+            gzip_compat: false,
             nb_workers: default_nb_workers(),
+            progress_sink: None,
+            quiet_progress: false,
+            flush_every: None,
+            io_buffer_size: IO_BUFFER_SIZE_DEFAULT,
+            mtime_window: None,
         }
     }
 }
@@ -302,6 +390,14 @@ impl Prefs {
         yes
     }
 
+    /// `-f` / `--no-force`: sets both [`force`](Self::force) and
+    /// [`overwrite`](Self::overwrite). Returns the new value.
+    pub fn set_force(&mut self, yes: bool) -> bool {
+        self.force = yes;
+        self.overwrite = yes;
+        yes
+    }
+
     /// Enables or disables test mode (decompress and discard output). Returns the new value.
     pub fn set_test_mode(&mut self, yes: bool) -> bool {
         self.test_mode = yes;
@@ -321,6 +417,7 @@ impl Prefs {
         }
         self.block_size_id = bsid;
         self.block_size = BLOCK_SIZE_TABLE[(bsid - MIN_BSID) as usize];
+        self.block_size_policy = BlockSizePolicy::Fixed(frame_block_size_id(bsid));
         self.block_size
     }
 
@@ -330,7 +427,7 @@ impl Prefs {
     pub fn set_block_size(&mut self, block_size: usize) -> usize {
         const MIN_BLOCK_SIZE: usize = 32;
         const MAX_BLOCK_SIZE: usize = 4 * MB;
-        let block_size = block_size.max(MIN_BLOCK_SIZE).min(MAX_BLOCK_SIZE);
+        let block_size = block_size.clamp(MIN_BLOCK_SIZE, MAX_BLOCK_SIZE);
         self.block_size = block_size;
         // Count bit-pair positions to find the closest standard block-size ID.
         let mut bsid: u32 = 0;
@@ -345,9 +442,28 @@ impl Prefs {
             bsid = 7;
         }
         self.block_size_id = bsid - 3;
+        self.block_size_policy = BlockSizePolicy::Fixed(frame_block_size_id(self.block_size_id));
         block_size
     }
 
+    /// Sets the block size policy.  `Fixed(id)` behaves like
+    /// [`set_block_size_id`](Self::set_block_size_id); `Auto` restores the
+    /// default 4 MB upper bound.
+    pub fn set_block_size_policy(&mut self, policy: BlockSizePolicy) {
+        match policy {
+            BlockSizePolicy::Fixed(BlockSizeId::Default) => {
+                self.set_block_size_id(BlockSizeId::Max64Kb as u32);
+            }
+            BlockSizePolicy::Fixed(id) => {
+                self.set_block_size_id(id as u32);
+            }
+            BlockSizePolicy::Auto => {
+                self.set_block_size_id(LZ4IO_BLOCKSIZEID_DEFAULT);
+                self.block_size_policy = BlockSizePolicy::Auto;
+            }
+        }
+    }
+
     /// Sets block linking mode. Returns `true` if blocks are now independent.
     pub fn set_block_mode(&mut self, mode: BlockMode) -> bool {
         self.block_independence = mode == BlockMode::Independent;
@@ -366,6 +482,18 @@ impl Prefs {
         enable
     }
 
+    /// Sets how checksums are treated when decompressing.
+    pub fn set_checksum_policy(&mut self, policy: ChecksumPolicy) {
+        self.checksum_policy = policy;
+    }
+
+    /// Skips (`true`) or rejects (`false`) blocks with bad checksums when
+    /// decompressing. Returns the new value.
+    pub fn set_recover(&mut self, recover: bool) -> bool {
+        self.recover = recover;
+        recover
+    }
+
     /// Enables or disables forced sparse-file mode.
     /// Returns the internal sparse-file mode value: 0 = disabled, 2 = forced on.
     pub fn set_sparse_file(&mut self, enable: bool) -> i32 {
@@ -392,6 +520,287 @@ impl Prefs {
     pub fn set_remove_src_file(&mut self, flag: bool) {
         self.remove_src_file = flag;
     }
+
+    /// Enables or disables copying the source's modification time to the output.
+    pub fn set_restore_times(&mut self, flag: bool) {
+        self.restore_times = flag;
+    }
+
+    /// Enables or disables copying the source's owner and group to the output.
+    pub fn set_restore_owner(&mut self, flag: bool) {
+        self.restore_owner = flag;
+    }
+
+    /// Switches to gzip-style defaults: remove the source after success,
+    /// refuse to overwrite, and restore mtime and owner. Turning the profile
+    /// off clears the flag and leaves the other fields as they are.
+    pub fn set_gzip_compat(&mut self, yes: bool) {
+        self.gzip_compat = yes;
+        if yes {
+            self.remove_src_file = true;
+            self.set_force(false);
+            self.restore_times = true;
+            self.restore_owner = true;
+        }
+    }
+
+    /// Enables or disables writing outputs through a temporary file.
+    pub fn set_atomic_output(&mut self, flag: bool) {
+        self.atomic_output = flag;
+    }
+
+    /// Enables or disables the built-in progress lines (final summaries are
+    /// unaffected).
+    pub fn set_quiet_progress(&mut self, quiet: bool) {
+        self.quiet_progress = quiet;
+    }
+
+    /// Whether the built-in progress lines are shown: no sink is installed
+    /// and [`quiet_progress`](Self::quiet_progress) is off.  They still go
+    /// to stderr only, at notification level 2 and above.
+    pub(crate) fn builtin_progress(&self) -> bool {
+        self.progress_sink.is_none() && !self.quiet_progress
+    }
+
+    /// Installs (or clears) the progress sink. Returns whether one is installed.
+    pub fn set_progress_sink(&mut self, sink: Option<Arc<dyn ProgressSink>>) -> bool {
+        self.progress_sink = sink;
+        self.progress_sink.is_some()
+    }
+
+    /// Flushes decompressed output every `bytes` bytes; `0` turns interval
+    /// flushing off. Returns the interval now in effect.
+    pub fn set_flush_every(&mut self, bytes: usize) -> Option<usize> {
+        self.flush_every = (bytes > 0).then_some(bytes);
+        self.flush_every
+    }
+
+    /// Sets the source read-ahead buffer size, clamped to 4 KB – 64 MB and
+    /// rounded up to a multiple of 4 KB. Returns the size now in effect.
+    pub fn set_io_buffer_size(&mut self, bytes: usize) -> usize {
+        let clamped = bytes.clamp(IO_BUFFER_SIZE_MIN, IO_BUFFER_SIZE_MAX);
+        self.io_buffer_size = clamped.next_multiple_of(IO_BUFFER_SIZE_MIN);
+        self.io_buffer_size
+    }
+
+    /// Turns incremental compression on with a window of `secs` seconds, or
+    /// off with `None`.
+    pub fn set_mtime_window(&mut self, secs: Option<u64>) {
+        self.mtime_window = secs;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Preferences builder
+// ---------------------------------------------------------------------------
+
+/// Chainable builder for [`Prefs`] — the supported way for library users to
+/// configure the `io` entry points.
+///
+/// Every method forwards to the matching `Prefs` setter, so validation and
+/// clamping behave exactly as they do for the CLI. Options that are not set
+/// keep their [`Prefs::default`] values.
+///
+/// ```
+/// use lz4::io::{BlockMode, IoPrefsBuilder};
+///
+/// let prefs = IoPrefsBuilder::new()
+///     .block_size_id(5)
+///     .block_mode(BlockMode::Linked)
+///     .block_checksum(true)
+///     .overwrite(false)
+///     .build();
+/// assert_eq!(prefs.block_size, 256 * 1024);
+/// assert!(!prefs.block_independence);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct IoPrefsBuilder {
+    prefs: Prefs,
+}
+
+impl IoPrefsBuilder {
+    /// Creates a builder seeded with [`Prefs::default`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Selects a standard block-size ID (4–7 → 64 KB – 4 MB).
+    /// Out-of-range IDs leave the current block size unchanged.
+    pub fn block_size_id(mut self, bsid: u32) -> Self {
+        self.prefs.set_block_size_id(bsid);
+        self
+    }
+
+    /// Sets an explicit block size in bytes, clamped to `[32, 4 MB]`.
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        self.prefs.set_block_size(block_size);
+        self
+    }
+
+    /// Chooses between automatic and fixed block sizes.
+    pub fn block_size_policy(mut self, policy: BlockSizePolicy) -> Self {
+        self.prefs.set_block_size_policy(policy);
+        self
+    }
+
+    /// Selects linked or independent blocks.
+    pub fn block_mode(mut self, mode: BlockMode) -> Self {
+        self.prefs.set_block_mode(mode);
+        self
+    }
+
+    /// Enables or disables per-block checksums.
+    pub fn block_checksum(mut self, enable: bool) -> Self {
+        self.prefs.set_block_checksum_mode(enable);
+        self
+    }
+
+    /// Enables or disables the whole-stream content checksum.
+    pub fn stream_checksum(mut self, enable: bool) -> Self {
+        self.prefs.set_stream_checksum_mode(enable);
+        self
+    }
+
+    /// Requires, verifies or skips each kind of checksum when decompressing.
+    pub fn checksum_policy(mut self, policy: ChecksumPolicy) -> Self {
+        self.prefs.set_checksum_policy(policy);
+        self
+    }
+
+    /// Skips damaged blocks instead of failing when decompressing.
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.prefs.set_recover(recover);
+        self
+    }
+
+    /// Enables or disables embedding the content size in the frame header.
+    pub fn content_size(mut self, enable: bool) -> Self {
+        self.prefs.set_content_size(enable);
+        self
+    }
+
+    /// Forces sparse-file writes on (`true`) or off (`false`), replacing the
+    /// default automatic mode.
+    pub fn sparse(mut self, enable: bool) -> Self {
+        self.prefs.set_sparse_file(enable);
+        self
+    }
+
+    /// Allows (`true`) or refuses (`false`) overwriting existing destinations.
+    pub fn overwrite(mut self, yes: bool) -> Self {
+        self.prefs.set_overwrite(yes);
+        self
+    }
+
+    /// `-f` semantics: overwrite destinations and compress non-regular
+    /// sources (`true`), or do neither (`false`).
+    pub fn force(mut self, yes: bool) -> Self {
+        self.prefs.set_force(yes);
+        self
+    }
+
+    /// Sets the number of compression workers, clamped to `[1, NB_WORKERS_MAX]`.
+    pub fn nb_workers(mut self, nb_workers: i32) -> Self {
+        self.prefs.set_nb_workers(nb_workers);
+        self
+    }
+
+    /// Enables test mode: decompress and discard the output.
+    pub fn test_mode(mut self, yes: bool) -> Self {
+        self.prefs.set_test_mode(yes);
+        self
+    }
+
+    /// Copies non-LZ4 input through unchanged when decompressing.
+    pub fn pass_through(mut self, yes: bool) -> Self {
+        self.prefs.set_pass_through(yes);
+        self
+    }
+
+    /// Uses the given dictionary file; `None` clears it.
+    pub fn dictionary_filename(mut self, filename: Option<&str>) -> Self {
+        self.prefs.set_dictionary_filename(filename);
+        self
+    }
+
+    /// Removes the source file after a successful operation.
+    pub fn remove_src_file(mut self, yes: bool) -> Self {
+        self.prefs.set_remove_src_file(yes);
+        self
+    }
+
+    /// Copies the source's modification time to the output (on by default).
+    pub fn restore_times(mut self, yes: bool) -> Self {
+        self.prefs.set_restore_times(yes);
+        self
+    }
+
+    /// Copies the source's owner and group to the output (off by default).
+    pub fn restore_owner(mut self, yes: bool) -> Self {
+        self.prefs.set_restore_owner(yes);
+        self
+    }
+
+    /// Applies gzip-style defaults (see [`Prefs::set_gzip_compat`]).
+    pub fn gzip_compat(mut self, yes: bool) -> Self {
+        self.prefs.set_gzip_compat(yes);
+        self
+    }
+
+    /// Writes outputs through a temporary file renamed on success (off by
+    /// default; on in the CLI).
+    pub fn atomic_output(mut self, yes: bool) -> Self {
+        self.prefs.set_atomic_output(yes);
+        self
+    }
+
+    /// Biases HC levels toward decompression speed.
+    pub fn favor_dec_speed(mut self, yes: bool) -> Self {
+        self.prefs.favor_dec_speed(yes);
+        self
+    }
+
+    /// Reports progress to `sink` instead of the built-in progress lines.
+    pub fn progress_sink(mut self, sink: Arc<dyn ProgressSink>) -> Self {
+        self.prefs.set_progress_sink(Some(sink));
+        self
+    }
+
+    /// Hides the built-in progress lines but keeps the final summaries.
+    pub fn quiet_progress(mut self, quiet: bool) -> Self {
+        self.prefs.set_quiet_progress(quiet);
+        self
+    }
+
+    /// Flushes decompressed output every `bytes` bytes (`0` = off).
+    pub fn flush_every(mut self, bytes: usize) -> Self {
+        self.prefs.set_flush_every(bytes);
+        self
+    }
+
+    /// Reads source files through a buffer of `bytes` bytes (4 KB – 64 MB).
+    pub fn io_buffer_size(mut self, bytes: usize) -> Self {
+        self.prefs.set_io_buffer_size(bytes);
+        self
+    }
+
+    /// Skips sources whose output is up to date within `secs` seconds.
+    pub fn mtime_window(mut self, secs: u64) -> Self {
+        self.prefs.set_mtime_window(Some(secs));
+        self
+    }
+
+    /// Returns the configured [`Prefs`].
+    pub fn build(self) -> Prefs {
+        self.prefs
+    }
+}
+
+impl Prefs {
+    /// Returns an [`IoPrefsBuilder`] seeded with the default preferences.
+    pub fn builder() -> IoPrefsBuilder {
+        IoPrefsBuilder::new()
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -420,8 +829,10 @@ mod tests {
         assert!(!p.test_mode);
         assert_eq!(p.block_size_id, LZ4IO_BLOCKSIZEID_DEFAULT);
         assert_eq!(p.block_size, 0);
+        assert_eq!(p.block_size_policy, BlockSizePolicy::Auto);
         assert!(!p.block_checksum);
         assert!(p.stream_checksum);
+        assert_eq!(p.checksum_policy, ChecksumPolicy::VERIFY);
         assert!(p.block_independence);
         assert_eq!(p.sparse_file_support, 1);
         assert!(!p.content_size_flag);
@@ -429,7 +840,22 @@ mod tests {
         assert!(!p.favor_dec_speed);
         assert!(p.dictionary_filename.is_none());
         assert!(!p.remove_src_file);
+        assert!(p.restore_times);
+        assert!(!p.restore_owner);
+        assert!(!p.atomic_output);
+        assert!(!p.gzip_compat);
         assert!(p.nb_workers >= 1);
+        assert_eq!(p.io_buffer_size, IO_BUFFER_SIZE_DEFAULT);
+        assert_eq!(p.mtime_window, None);
+    }
+
+    #[test]
+    fn set_io_buffer_size_clamps_and_rounds() {
+        let mut p = Prefs::default();
+        assert_eq!(p.set_io_buffer_size(0), 4 * KB);
+        assert_eq!(p.set_io_buffer_size(5000), 8 * KB);
+        assert_eq!(p.set_io_buffer_size(MB), MB);
+        assert_eq!(p.set_io_buffer_size(GB), 64 * MB);
     }
 
     #[test]
@@ -465,6 +891,32 @@ mod tests {
         assert_eq!(s, 4 * MB);
     }
 
+    #[test]
+    fn block_size_setters_fix_the_policy() {
+        let mut p = Prefs::default();
+        p.set_block_size_id(5);
+        assert_eq!(
+            p.block_size_policy,
+            BlockSizePolicy::Fixed(BlockSizeId::Max256Kb)
+        );
+        p.set_block_size_policy(BlockSizePolicy::Auto);
+        assert_eq!(p.block_size_policy, BlockSizePolicy::Auto);
+        assert_eq!(p.block_size_id, LZ4IO_BLOCKSIZEID_DEFAULT);
+        assert_eq!(p.block_size, 4 * MB);
+        p.set_block_size(100 * KB);
+        assert_eq!(
+            p.block_size_policy,
+            BlockSizePolicy::Fixed(BlockSizeId::Max256Kb)
+        );
+        p.set_block_size_policy(BlockSizePolicy::Fixed(BlockSizeId::Max1Mb));
+        assert_eq!(p.block_size, MB);
+        assert_eq!(p.set_block_size_id(9), 0);
+        assert_eq!(
+            p.block_size_policy,
+            BlockSizePolicy::Fixed(BlockSizeId::Max1Mb)
+        );
+    }
+
     #[test]
     fn set_sparse_file_returns_two_when_enabled() {
         let mut p = Prefs::default();
@@ -495,4 +947,16 @@ mod tests {
         assert!(p.set_block_mode(BlockMode::Independent));
         assert!(!p.set_block_mode(BlockMode::Linked));
     }
+
+    #[test]
+    fn gzip_compat_applies_gzip_defaults() {
+        let p = Prefs::builder()
+            .restore_times(false)
+            .gzip_compat(true)
+            .build();
+        assert!(p.gzip_compat);
+        assert!(p.remove_src_file);
+        assert!(!p.overwrite && !p.force);
+        assert!(p.restore_times && p.restore_owner);
+    }
 }
@@ -0,0 +1,318 @@
+//! Progress reporting hooks for file-level compression and decompression.
+//!
+//! By default the `io` layer reports progress through [`display_level`]
+//! (`"\rRead : … MiB ==> …%"`), which is only visible at notification level 2
+//! and above.  Callers that want to render their own progress indicator — GUI
+//! wrappers, or the CLI's `--progress` flag — install a [`ProgressSink`] on
+//! [`Prefs`] instead.  While a sink is installed the built-in progress lines
+//! are suppressed and the sink receives [`Progress`] snapshots.
+//!
+//! Byte counters are taken at the file boundary: `bytes_read` counts bytes
+//! pulled from the source file and `bytes_written` counts bytes pushed to the
+//! destination.  In test mode (`-t`) decompressed data is discarded before it
+//! reaches the destination, so `bytes_written` stays at zero.
+//!
+//! [`display_level`]: crate::io::prefs::display_level
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::io::file_io::src_size_hint;
+use crate::io::prefs::{Prefs, REFRESH_RATE_NS};
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// A point-in-time snapshot of a single file operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Bytes read from the source so far.
+    pub bytes_read: u64,
+    /// Bytes written to the destination so far.
+    pub bytes_written: u64,
+    /// Size of the source, when it is a regular file.  `None` for stdin.
+    pub total_bytes: Option<u64>,
+    /// Wall-clock time since the operation started.
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Output size as a fraction of input size (`bytes_written / bytes_read`).
+    ///
+    /// Returns 0.0 before any input has been read.
+    pub fn ratio(&self) -> f64 {
+        if self.bytes_read == 0 {
+            0.0
+        } else {
+            self.bytes_written as f64 / self.bytes_read as f64
+        }
+    }
+
+    /// Fraction of the source consumed so far, in `[0.0, 1.0]`.
+    ///
+    /// Returns `None` when the source size is unknown.
+    pub fn fraction_done(&self) -> Option<f64> {
+        match self.total_bytes {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.bytes_read as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+
+    /// Estimated time remaining, extrapolated from the average throughput so far.
+    ///
+    /// Returns `None` when the source size is unknown or nothing has been read yet.
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total_bytes?;
+        if self.bytes_read == 0 {
+            return None;
+        }
+        let remaining = total.saturating_sub(self.bytes_read);
+        let per_byte = self.elapsed.as_secs_f64() / self.bytes_read as f64;
+        Some(Duration::from_secs_f64(remaining as f64 * per_byte))
+    }
+}
+
+/// Receiver for progress updates emitted by the `io` layer.
+///
+/// `update` is called at most once per refresh interval (200 ms), plus once
+/// immediately after the first bytes are transferred.  `finish` is called once
+/// when a file has been processed successfully.  Implementations must be cheap
+/// and must not panic; they run on the thread doing the I/O.
+pub trait ProgressSink: Send + Sync {
+    /// Called periodically while a file is being processed.
+    fn update(&self, progress: &Progress);
+
+    /// Called once when a file has been processed successfully.
+    fn finish(&self, _progress: &Progress) {}
+}
+
+impl fmt::Debug for dyn ProgressSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressSink")
+    }
+}
+
+/// A [`ProgressSink`] that renders a single status line on stderr.
+///
+/// Unlike the built-in progress display, this sink ignores the notification
+/// level and whether stderr is a terminal.  The CLI installs it for
+/// `--progress`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StderrProgress;
+
+impl StderrProgress {
+    /// Creates a new stderr progress renderer.
+    pub fn new() -> Self {
+        StderrProgress
+    }
+}
+
+impl ProgressSink for StderrProgress {
+    fn update(&self, progress: &Progress) {
+        let mut line = format!(
+            "\rRead : {} MiB   ==> {:.2}%   ",
+            progress.bytes_read >> 20,
+            progress.ratio() * 100.0
+        );
+        if let (Some(done), Some(eta)) = (progress.fraction_done(), progress.eta()) {
+            line.push_str(&format!(
+                "[{:3.0}%, ETA {}s]   ",
+                done * 100.0,
+                eta.as_secs()
+            ));
+        }
+        let mut stderr = io::stderr();
+        let _ = stderr.write_all(line.as_bytes());
+        let _ = stderr.flush();
+    }
+
+    fn finish(&self, _progress: &Progress) {
+        let mut stderr = io::stderr();
+        let _ = write!(stderr, "\r{:79}\r", "");
+        let _ = stderr.flush();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal tracking
+// ---------------------------------------------------------------------------
+
+struct TrackerState {
+    bytes_read: u64,
+    bytes_written: u64,
+    last_update: Option<Instant>,
+}
+
+/// Shared byte counters for one file operation, forwarding throttled
+/// snapshots to the installed sink.
+pub(crate) struct ProgressTracker {
+    sink: Arc<dyn ProgressSink>,
+    total_bytes: Option<u64>,
+    start: Instant,
+    state: Mutex<TrackerState>,
+}
+
+impl ProgressTracker {
+    fn snapshot(&self, state: &TrackerState) -> Progress {
+        Progress {
+            bytes_read: state.bytes_read,
+            bytes_written: state.bytes_written,
+            total_bytes: self.total_bytes,
+            elapsed: self.start.elapsed(),
+        }
+    }
+
+    fn record(&self, read: usize, written: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.bytes_read += read as u64;
+        state.bytes_written += written as u64;
+        let now = Instant::now();
+        let due = match state.last_update {
+            None => true,
+            Some(last) => now.duration_since(last) >= Duration::from_nanos(REFRESH_RATE_NS),
+        };
+        if due {
+            state.last_update = Some(now);
+            let progress = self.snapshot(&state);
+            drop(state);
+            self.sink.update(&progress);
+        }
+    }
+
+    /// Reports the final counters to the sink.
+    pub(crate) fn finish(&self) {
+        let progress = self.snapshot(&self.state.lock().unwrap());
+        self.sink.finish(&progress);
+    }
+}
+
+/// Starts tracking one file operation if `prefs` has a sink installed.
+///
+/// The source size is taken from the file metadata; it is unknown for stdin,
+/// non-regular files, and pseudo-files that stat as empty.
+pub(crate) fn start(prefs: &Prefs, src_filename: &str) -> Option<Arc<ProgressTracker>> {
+    let sink = prefs.progress_sink.clone()?;
+    let total_bytes = src_size_hint(src_filename);
+    Some(Arc::new(ProgressTracker {
+        sink,
+        total_bytes,
+        start: Instant::now(),
+        state: Mutex::new(TrackerState {
+            bytes_read: 0,
+            bytes_written: 0,
+            last_update: None,
+        }),
+    }))
+}
+
+/// Reports completion to the tracker, if any.
+pub(crate) fn finish(tracker: Option<&Arc<ProgressTracker>>) {
+    if let Some(t) = tracker {
+        t.finish();
+    }
+}
+
+/// Counts `n` source bytes consumed without going through a wrapped reader
+/// (e.g. blocks taken straight from a memory-mapped input).
+#[cfg(all(feature = "mmap", unix))]
+pub(crate) fn record_read(tracker: Option<&Arc<ProgressTracker>>, n: usize) {
+    if let (Some(t), true) = (tracker, n > 0) {
+        t.record(n, 0);
+    }
+}
+
+struct ProgressReader<R> {
+    inner: R,
+    tracker: Arc<ProgressTracker>,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            self.tracker.record(n, 0);
+        }
+        Ok(n)
+    }
+}
+
+struct ProgressWriter<W> {
+    inner: W,
+    tracker: Arc<ProgressTracker>,
+}
+
+impl<W: Write> Write for ProgressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        if n > 0 {
+            self.tracker.record(0, n);
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Wraps `reader` so that bytes read are counted by `tracker`.
+pub(crate) fn wrap_reader<'a>(
+    reader: Box<dyn Read + 'a>,
+    tracker: Option<&Arc<ProgressTracker>>,
+) -> Box<dyn Read + 'a> {
+    match tracker {
+        Some(t) => Box::new(ProgressReader {
+            inner: reader,
+            tracker: Arc::clone(t),
+        }),
+        None => reader,
+    }
+}
+
+/// Wraps `writer` so that bytes written are counted by `tracker`.
+pub(crate) fn wrap_writer<'a>(
+    writer: Box<dyn Write + 'a>,
+    tracker: Option<&Arc<ProgressTracker>>,
+) -> Box<dyn Write + 'a> {
+    match tracker {
+        Some(t) => Box::new(ProgressWriter {
+            inner: writer,
+            tracker: Arc::clone(t),
+        }),
+        None => writer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratio_and_eta() {
+        let p = Progress {
+            bytes_read: 100,
+            bytes_written: 25,
+            total_bytes: Some(400),
+            elapsed: Duration::from_secs(1),
+        };
+        assert!((p.ratio() - 0.25).abs() < 1e-9);
+        assert_eq!(p.fraction_done(), Some(0.25));
+        assert_eq!(p.eta(), Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn unknown_total_has_no_eta() {
+        let p = Progress {
+            bytes_read: 100,
+            bytes_written: 25,
+            total_bytes: None,
+            elapsed: Duration::from_secs(1),
+        };
+        assert_eq!(p.fraction_done(), None);
+        assert_eq!(p.eta(), None);
+    }
+}
@@ -0,0 +1,215 @@
+//! Read-ahead buffering for compression and decompression inputs.
+//!
+//! The read loops ask for whatever the format needs next: a 4-byte magic
+//! number or block header, then a block of up to 4 MB.  Behind a small
+//! `BufReader` every header costs a syscall of its own, and at the fast
+//! levels on NVMe that syscall overhead, not the codec, sets the pace.
+//!
+//! [`ReadAhead`] keeps one page-aligned buffer of
+//! [`Prefs::io_buffer_size`](crate::io::Prefs::io_buffer_size) bytes.  A read
+//! that misses the buffer is issued as a single `read_vectored` into the
+//! caller's slice followed by the buffer, so the caller's bytes land in place
+//! and the read-ahead arrives in the same syscall.  Reads at least as large
+//! as the buffer go straight to the inner reader.
+
+use std::io::{self, IoSliceMut, Read};
+
+/// Alignment of the read-ahead buffer (one page on common targets).
+pub const BUFFER_ALIGN: usize = 4096;
+
+/// A reader that fills the caller's buffer and a page-aligned read-ahead
+/// buffer with one vectored read.
+///
+/// ```
+/// use lz4::io::read_ahead::ReadAhead;
+/// use std::io::Read;
+///
+/// let mut r = ReadAhead::with_capacity(&b"header+payload"[..], 4096);
+/// let mut magic = [0u8; 6];
+/// r.read_exact(&mut magic).unwrap();
+/// assert_eq!(&magic, b"header");
+/// assert_eq!(r.buffered(), 8);
+/// ```
+pub struct ReadAhead<R: Read> {
+    inner: R,
+    storage: Vec<u8>,
+    /// Offset of the first aligned byte in `storage`.
+    start: usize,
+    capacity: usize,
+    pos: usize,
+    filled: usize,
+}
+
+impl<R: Read> ReadAhead<R> {
+    /// Wraps `inner` with a read-ahead buffer of `capacity` bytes (at least 1).
+    pub fn with_capacity(inner: R, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let storage = vec![0u8; capacity + BUFFER_ALIGN - 1];
+        let start = storage.as_ptr().align_offset(BUFFER_ALIGN);
+        ReadAhead {
+            inner,
+            storage,
+            start,
+            capacity,
+            pos: 0,
+            filled: 0,
+        }
+    }
+
+    /// Size of the read-ahead buffer in bytes.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of bytes read ahead and not yet returned.
+    pub fn buffered(&self) -> usize {
+        self.filled - self.pos
+    }
+
+    /// The wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns the wrapped reader; buffered bytes are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ReadAhead<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let buf = &mut self.storage[self.start..self.start + self.capacity];
+        if self.pos < self.filled {
+            let n = out.len().min(self.filled - self.pos);
+            out[..n].copy_from_slice(&buf[self.pos..self.pos + n]);
+            self.pos += n;
+            return Ok(n);
+        }
+        if out.is_empty() {
+            return Ok(0);
+        }
+        if out.len() >= self.capacity {
+            return self.inner.read(out);
+        }
+        let want = out.len();
+        let n = self
+            .inner
+            .read_vectored(&mut [IoSliceMut::new(out), IoSliceMut::new(buf)])?;
+        if n > want {
+            self.pos = 0;
+            self.filled = n - want;
+            return Ok(want);
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serves `data` in reads of at most `step` bytes and records each
+    /// request's total length.
+    struct Chunked<'a> {
+        data: &'a [u8],
+        step: usize,
+        requests: Vec<usize>,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            self.requests.push(out.len());
+            let n = out.len().min(self.step).min(self.data.len());
+            out[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+
+        fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+            self.requests.push(bufs.iter().map(|b| b.len()).sum());
+            let mut total = 0;
+            for b in bufs.iter_mut() {
+                let n = b.len().min(self.step - total).min(self.data.len());
+                b[..n].copy_from_slice(&self.data[..n]);
+                self.data = &self.data[n..];
+                total += n;
+                if n < b.len() {
+                    break;
+                }
+            }
+            Ok(total)
+        }
+    }
+
+    fn pattern(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 + i / 251) as u8).collect()
+    }
+
+    #[test]
+    fn buffer_is_page_aligned() {
+        let r = ReadAhead::with_capacity(io::empty(), 10_000);
+        assert_eq!(r.capacity(), 10_000);
+        let ptr = r.storage[r.start..].as_ptr() as usize;
+        assert_eq!(ptr % BUFFER_ALIGN, 0);
+    }
+
+    #[test]
+    fn small_reads_share_one_vectored_read() {
+        let data = pattern(1000);
+        let inner = Chunked {
+            data: &data,
+            step: usize::MAX,
+            requests: Vec::new(),
+        };
+        let mut r = ReadAhead::with_capacity(inner, 4096);
+        let mut out = Vec::new();
+        let mut header = [0u8; 4];
+        while r.read_exact(&mut header).is_ok() {
+            out.extend_from_slice(&header);
+        }
+        assert_eq!(out, data);
+        // One vectored read for everything, then the EOF probe.
+        assert_eq!(r.get_ref().requests, vec![4 + 4096, 4 + 4096]);
+    }
+
+    #[test]
+    fn large_reads_bypass_the_buffer() {
+        let data = pattern(20_000);
+        let inner = Chunked {
+            data: &data,
+            step: usize::MAX,
+            requests: Vec::new(),
+        };
+        let mut r = ReadAhead::with_capacity(inner, 4096);
+        let mut block = vec![0u8; 8192];
+        r.read_exact(&mut block).unwrap();
+        assert_eq!(block, data[..8192]);
+        assert_eq!(r.buffered(), 0);
+        assert_eq!(r.get_ref().requests, vec![8192]);
+    }
+
+    #[test]
+    fn short_inner_reads_reassemble_exactly() {
+        let data = pattern(5000);
+        for step in [1, 3, 100, 4097] {
+            let inner = Chunked {
+                data: &data,
+                step,
+                requests: Vec::new(),
+            };
+            let mut r = ReadAhead::with_capacity(inner, 512);
+            let mut out = Vec::new();
+            let mut sizes = [1usize, 4, 700, 13].iter().cycle();
+            loop {
+                let mut chunk = vec![0u8; *sizes.next().unwrap()];
+                let n = r.read(&mut chunk).unwrap();
+                if n == 0 {
+                    break;
+                }
+                out.extend_from_slice(&chunk[..n]);
+            }
+            assert_eq!(out, data, "step {step}");
+        }
+    }
+}
@@ -14,26 +14,68 @@
 //! | `bench`      | Throughput benchmarking infrastructure. |
 //! | `xxhash`     | XXH32 content-checksum wrapper. |
 //! | `lorem`      | Deterministic lorem ipsum generator (benchmark corpus). |
+//! | `corpus`     | Seeded synthetic corpora: lorem, logs, JSON, tunable-entropy binary. |
+//! | `dict`       | Dictionary size rule (`effective_dict`) shared by all dictionary APIs. |
 //! | `timefn`     | Monotonic high-resolution timer. |
 //! | `threadpool` | Fixed-size work-stealing thread pool. |
 //! | `config`     | Compile-time configuration constants. |
 //! | `util`       | File enumeration and sizing utilities. |
+//! | `compressed` | `Lz4Compressed<T, F>` serde wrapper (`serde` feature). |
+//! | `conformance` | Frame-format conformance vectors: generator and checker. |
+//! | `verify`     | Round trips against the reference C library (`verify` feature). |
+//!
+//! # Component features
+//!
+//! `block`, `hc`, `frame`, `bench` and `cli` (all on by default) gate the
+//! modules of the same name; `cli` also gates `io`, and `file` needs both
+//! `frame` and `hc`.  Without `hc`, `frame` keeps its decoder and encodes
+//! through the fast-only `frame::safe` encoder.
+//!
+//! # `forbid-unsafe`
+//!
+//! Building with `--features forbid-unsafe` compiles
+//! out every module that relies on raw pointers or FFI (`bench`, `cli`,
+//! `file`, `hc`, `io`, `threadpool`, `util`, and the streaming parts of
+//! `block` and `frame`) and applies `#![forbid(unsafe_code)]` to what remains:
+//! the one-shot block and frame codecs (`block::safe`, `frame::safe`), frame
+//! headers, skippable frames, XXH32, and the synthetic corpora.
+
+#![cfg_attr(feature = "forbid-unsafe", forbid(unsafe_code))]
 
 pub mod config;
+pub mod corpus;
+pub mod dict;
 pub mod lorem;
 pub mod timefn;
 
-#[cfg(feature = "c-abi")]
+#[cfg(all(feature = "c-abi", not(feature = "forbid-unsafe")))]
 pub mod abi;
+#[cfg(all(feature = "bench", not(feature = "forbid-unsafe")))]
 pub mod bench;
+#[cfg(feature = "block")]
 pub mod block;
+#[cfg(all(feature = "cli", not(any(feature = "forbid-unsafe", wasm_unknown))))]
 pub mod cli;
+#[cfg(feature = "serde")]
+pub mod compressed;
+#[cfg(all(feature = "frame", feature = "hc", not(feature = "forbid-unsafe")))]
+pub mod conformance;
+#[cfg(all(feature = "frame", feature = "hc", not(feature = "forbid-unsafe")))]
 pub mod file;
+#[cfg(feature = "frame")]
 pub mod frame;
+#[cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
 pub mod hc;
+#[cfg(all(feature = "cli", not(any(feature = "forbid-unsafe", wasm_unknown))))]
 pub mod io;
+#[cfg(not(feature = "forbid-unsafe"))]
 pub mod threadpool;
+#[cfg(not(feature = "forbid-unsafe"))]
 pub mod util;
+#[cfg(all(feature = "verify", not(feature = "forbid-unsafe")))]
+pub mod verify;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
 pub mod xxhash;
 
 // ── Version constants (mirrors lz4.h lines 131–143) ──────────────────────────
@@ -62,6 +104,7 @@ pub const LZ4_DISTANCE_MAX: usize = 65_535;
 pub const COMPRESS_INPLACE_MARGIN: usize = LZ4_DISTANCE_MAX + 32;
 
 /// Returns the size in bytes of the internal stream state (LZ4_sizeofState()).
+#[cfg(all(feature = "block", not(feature = "forbid-unsafe")))]
 pub fn size_of_state() -> i32 {
     core::mem::size_of::<block::types::StreamStateInternal>() as i32
 }
@@ -82,18 +125,28 @@ pub fn compress_inplace_buffer_size(max_compressed_size: usize) -> usize {
 }
 
 // ── Top-level re-exports ──────────────────────────────────────────────────────
-pub use block::compress::compress_default as lz4_compress_default;
-pub use block::decompress_api::decompress_safe as lz4_decompress_safe;
+#[cfg(feature = "block")]
+pub use block::compress_default as lz4_compress_default;
+#[cfg(feature = "block")]
+pub use block::decompress_safe as lz4_decompress_safe;
 
 // Block API — one-shot compression (needed by e2e tests)
+#[cfg(feature = "block")]
 pub use block::{
-    compress_bound, compress_dest_size, compress_fast, decompress_safe_partial,
-    decompress_safe_using_dict, Lz4Error, LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX,
-    LZ4_MAX_INPUT_SIZE,
+    compress_bound, compress_fast, decompress_safe_partial, Lz4Error, LZ4_ACCELERATION_DEFAULT,
+    LZ4_ACCELERATION_MAX, LZ4_MAX_INPUT_SIZE,
 };
+#[cfg(all(feature = "block", not(feature = "forbid-unsafe")))]
+pub use block::{compress_dest_size, decompress_safe_using_dict};
 
 // Error types
+#[cfg(all(feature = "block", not(feature = "forbid-unsafe")))]
 pub use block::decompress_core::DecompressError;
+#[cfg(all(feature = "block", feature = "forbid-unsafe"))]
+pub use block::DecompressError;
 
 // Frame API convenience re-exports
-pub use frame::{lz4f_compress_frame, lz4f_decompress};
+#[cfg(feature = "frame")]
+pub use frame::lz4f_compress_frame;
+#[cfg(all(feature = "frame", not(feature = "forbid-unsafe")))]
+pub use frame::lz4f_decompress;
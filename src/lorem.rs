@@ -458,9 +458,9 @@ impl<'a> GenCtx<'a> {
     /// Emit the canonical opening sentence ("Lorem ipsum dolor sit amet, …")
     /// using the first 19 words of the fixed word pool.
     fn generate_first_sentence(&mut self, pool: &WordPool) {
-        for i in 0..18usize {
+        for (i, word) in K_WORDS.iter().enumerate().take(18) {
             let sep: &[u8] = if i == 4 || i == 7 { b", " } else { b" " };
-            let word = K_WORDS[i].as_bytes();
+            let word = word.as_bytes();
             let word_len = pool.word_lens[i];
             self.generate_word(&word[..word_len], sep, i == 0);
         }
@@ -10,23 +10,37 @@
 //! 1. [`detect_alias`] inspects `argv[0]` to infer an initial mode
 //!    (e.g. `unlz4` implies decompress).
 //! 2. [`parse_args`] processes all flags and builds a [`ParsedArgs`] value.
-//! 3. [`run`] dispatches to the appropriate I/O operation and returns an exit code.
+//! 3. [`run`] dispatches to the appropriate I/O operation; a failure comes back
+//!    as a [`CliError`] whose category selects the exit code (usage = 1, I/O = 2,
+//!    corrupt data = 3, checksum mismatch = 4, blocks skipped by `--recover` = 5).
 //!
 //! All heap allocations are released by Rust’s RAII; there is no explicit
 //! `free` or `goto _cleanup`.
 
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
 use std::io::IsTerminal;
 
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
 use lz4::cli::args::{parse_args, ParsedArgs};
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
 use lz4::cli::constants::{display_level, set_display_level, LZ4_EXTENSION};
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
+use lz4::cli::error::{CliError, EXIT_OK};
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
 use lz4::cli::help::wait_enter;
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
 use lz4::cli::init::detect_alias;
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
 use lz4::cli::op_mode::{determine_op_mode, OpMode};
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
 use lz4::config::MULTITHREAD;
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
 use lz4::io::{
     compress_filename, compress_filename_legacy, compress_multiple_filenames,
-    compress_multiple_filenames_legacy, decompress_filename, decompress_multiple_filenames,
-    display_compressed_files_info, set_notification_level, STDIN_MARK, STDOUT_MARK,
+    compress_multiple_filenames_legacy, compress_multiple_filenames_to_dir, decompress_filename,
+    decompress_multiple_filenames, display_compressed_files_info,
+    display_compressed_files_info_checked, set_notification_level, test_multiple_filenames,
+    STDIN_MARK, STDOUT_MARK,
 };
 
 // ── Post-parse dispatch and cleanup (lz4cli.c lines 704-887) ─────────────────
@@ -36,8 +50,9 @@ use lz4::io::{
 /// Corresponds to the post-argument-parsing section of C `main()` (lz4cli.c lines 704–887).
 /// All resources are released automatically via Rust's RAII drop.
 ///
-/// Returns the process exit code (0 = success, non-zero = error).
-fn run(args: ParsedArgs) -> i32 {
+/// Returns the failure, if any, as a [`CliError`]; `main` maps it to an exit code.
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
+fn run(args: ParsedArgs) -> Result<(), CliError> {
     // Unpack all relevant fields from ParsedArgs.
     let mut prefs = args.prefs;
     let mut op_mode = args.op_mode;
@@ -51,7 +66,10 @@ fn run(args: ParsedArgs) -> i32 {
     let mut input_filename: Option<String> = args.input_filename;
     let mut output_filename: Option<String> = args.output_filename;
     let dictionary_filename = args.dictionary_filename;
+    let join_filename = args.join_filename;
     let mut in_file_names: Vec<String> = args.in_file_names;
+    let files_from = args.files_from;
+    let output_dir = args.output_dir;
     let block_size = args.block_size;
     let mut bench_config = args.bench_config;
     let exe_name = args.exe_name;
@@ -60,6 +78,12 @@ fn run(args: ParsedArgs) -> i32 {
     #[cfg(feature = "recursive")]
     let recursive = args.recursive;
 
+    // `-r` recreates the source tree under --output-dir instead of flattening.
+    #[cfg(feature = "recursive")]
+    let mirror_tree = recursive;
+    #[cfg(not(feature = "recursive"))]
+    let mirror_tree = false;
+
     // Mirrors dynNameSpace in C — keeps the auto-generated output filename alive
     // until end of function (freed automatically on drop).
     let mut _output_filename_storage: Option<String> = None;
@@ -79,7 +103,7 @@ fn run(args: ParsedArgs) -> i32 {
     // ── MT worker count warning (lz4cli.c lines 723–726) ──────────────────
     // #if !LZ4IO_MULTITHREAD: warn when nb_workers > 1 but MT is disabled.
     if !MULTITHREAD && nb_workers > 1 {
-        lz4::displaylevel!(
+        lz4::displaywarning!(
             2,
             "warning: this executable doesn't support multithreading \n"
         );
@@ -87,7 +111,89 @@ fn run(args: ParsedArgs) -> i32 {
 
     // ── Block size info (lz4cli.c lines 727–728) ───────────────────────────
     if op_mode == OpMode::Compress || op_mode == OpMode::Bench {
-        lz4::displaylevel!(4, "Blocks size : {} KB\n", block_size >> 10);
+        if prefs.block_size_policy == lz4::frame::BlockSizePolicy::Auto {
+            lz4::displaylevel!(4, "Blocks size : auto\n");
+        } else {
+            lz4::displaylevel!(4, "Blocks size : {} KB\n", block_size >> 10);
+        }
+    }
+
+    // ── --output-dir only names the outputs of a multi-file compression ────
+    if output_dir.is_some()
+        && (join_filename.is_some()
+            || !matches!(op_mode, OpMode::Auto | OpMode::Compress | OpMode::SelfTest)
+            || legacy_format)
+    {
+        return Err(CliError::usage(
+            "--output-dir only applies to frame compression of multiple files",
+        ));
+    }
+
+    // ── --self-test: write and/or check conformance vectors ───────────────
+    if op_mode == OpMode::SelfTest {
+        use lz4::conformance;
+        use std::path::Path;
+        let check_dir = input_filename
+            .as_deref()
+            .or(in_file_names.first().map(|s| s.as_str()));
+        let report = match (output_dir.as_deref(), check_dir) {
+            (Some(_), Some(_)) => {
+                return Err(CliError::usage(
+                    "--self-test takes a directory to check or --output-dir, not both",
+                ))
+            }
+            (Some(dir), None) => {
+                let n = conformance::write_vectors(Path::new(dir)).map_err(CliError::Io)?;
+                lz4::displaylevel!(2, "{} conformance vectors written to {} \n", n, dir);
+                conformance::verify_dir(Path::new(dir)).map_err(CliError::Io)?
+            }
+            (None, Some(dir)) => conformance::verify_dir(Path::new(dir)).map_err(CliError::Io)?,
+            (None, None) => conformance::self_test(),
+        };
+        for (name, e) in &report.failed {
+            lz4::displaylevel!(1, "{}: {} \n", name, e);
+        }
+        for name in &report.missing {
+            lz4::displaylevel!(1, "{}: missing \n", name);
+        }
+        lz4::displaylevel!(
+            2,
+            "{} vectors passed, {} failed, {} missing \n",
+            report.passed,
+            report.failed.len(),
+            report.missing.len()
+        );
+        if main_pause {
+            wait_enter();
+        }
+        return if report.is_ok() {
+            Ok(())
+        } else {
+            Err(CliError::Corrupt(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "conformance check failed",
+            )))
+        };
+    }
+
+    // ── --files-from: append the listed paths to the inputs ───────────────
+    if let Some(ref list) = files_from {
+        let names = if list == STDIN_MARK {
+            if std::io::stdin().is_terminal() {
+                return Err(CliError::usage("refusing to read from a console"));
+            }
+            lz4::util::read_file_list(std::io::stdin().lock())
+        } else {
+            std::fs::File::open(list).and_then(lz4::util::read_file_list)
+        }
+        .map_err(|e| CliError::Io(std::io::Error::new(e.kind(), format!("{}: {}", list, e))))?;
+        lz4::displaylevel!(4, "{} file names read from {}\n", names.len(), list);
+        in_file_names.extend(names);
+        if in_file_names.is_empty() {
+            // An empty list must not fall back to stdin → stdout.
+            lz4::displaylevel!(2, "No input files listed in {}\n", list);
+            return Ok(());
+        }
     }
 
     // ── Multiple inputs: set input_filename from first entry (lines 730–738) ─
@@ -113,10 +219,7 @@ fn run(args: ParsedArgs) -> i32 {
                         .map(|p| p.to_string_lossy().into_owned())
                         .collect();
                 }
-                Err(e) => {
-                    eprintln!("lz4: {}", e);
-                    return 1;
-                }
+                Err(e) => return Err(CliError::Io(e)),
             }
         }
     }
@@ -124,8 +227,7 @@ fn run(args: ParsedArgs) -> i32 {
     // ── Dictionary file setup (lz4cli.c lines 741–748) ────────────────────
     if let Some(ref dict) = dictionary_filename {
         if dict.as_str() == STDIN_MARK && std::io::stdin().is_terminal() {
-            lz4::displaylevel!(1, "refusing to read from a console\n");
-            std::process::exit(1);
+            return Err(CliError::usage("refusing to read from a console"));
         }
         prefs.set_dictionary_filename(Some(dict.as_str()));
     }
@@ -134,6 +236,11 @@ fn run(args: ParsedArgs) -> i32 {
     if op_mode == OpMode::Bench {
         bench_config.set_notification_level(display_level());
         let file_refs: Vec<&str> = in_file_names.iter().map(|s| s.as_str()).collect();
+        if bench_config.synthetic_entropy.is_some() && !file_refs.is_empty() {
+            return Err(CliError::usage(
+                "--synthetic cannot be combined with input files",
+            ));
+        }
         let result = lz4::bench::bench_files(
             &file_refs,
             c_level,
@@ -144,11 +251,29 @@ fn run(args: ParsedArgs) -> i32 {
         if main_pause {
             wait_enter();
         }
-        return if result.is_ok() { 0 } else { 1 };
+        return result.map_err(CliError::from);
+    }
+
+    // ── Archive dispatch (--join / --extract) ────────────────────────────────
+    if join_filename.is_some() || op_mode == OpMode::Extract {
+        let result = if let Some(ref archive) = join_filename {
+            let srcs: Vec<&str> = in_file_names.iter().map(|s| s.as_str()).collect();
+            lz4::io::archive::join_files(&srcs, archive, c_level, &prefs).map(|_| ())
+        } else if let Some(ref archive) = input_filename {
+            let out_dir = output_filename.as_deref().unwrap_or(".");
+            lz4::io::archive::extract_archive(archive, out_dir, &prefs).map(|_| ())
+        } else {
+            return Err(CliError::usage("--extract requires an archive file"));
+        };
+        if main_pause {
+            wait_enter();
+        }
+        return result.map_err(CliError::from);
     }
 
     // ── Test mode setup (lz4cli.c lines 758–762) ───────────────────────────
-    if op_mode == OpMode::Test {
+    let test_mode = op_mode == OpMode::Test;
+    if test_mode {
         prefs.set_test_mode(true);
         output_filename = Some(lz4::io::NUL_MARK.to_owned());
         op_mode = OpMode::Decompress;
@@ -159,8 +284,7 @@ fn run(args: ParsedArgs) -> i32 {
 
     // ── Refuse stdin from console (lz4cli.c lines 770–774) ────────────────
     if input_filename == STDIN_MARK && std::io::stdin().is_terminal() {
-        lz4::displaylevel!(1, "refusing to read from a console\n");
-        std::process::exit(1);
+        return Err(CliError::usage("refusing to read from a console"));
     }
 
     // ── Auto stdout when reading stdin (lz4cli.c lines 776–779) ──────────
@@ -186,15 +310,14 @@ fn run(args: ParsedArgs) -> i32 {
                 _output_filename_storage = Some(base.to_owned());
                 output_filename = Some(base.to_owned());
             } else {
-                lz4::displaylevel!(1, "Cannot determine an output filename \n");
                 lz4::cli::help::print_usage(&exe_name);
-                std::process::exit(1);
+                return Err(CliError::usage("cannot determine an output filename"));
             }
         }
     }
 
     // ── List mode: add input_filename to file list (lz4cli.c lines 810–813) ─
-    if op_mode == OpMode::List {
+    if op_mode == OpMode::List || op_mode == OpMode::Info {
         if !multiple_inputs {
             in_file_names.push(input_filename.clone());
         }
@@ -210,18 +333,24 @@ fn run(args: ParsedArgs) -> i32 {
     // ── Refuse console output (lz4cli.c lines 815–820) ────────────────────
     if output_filename == STDOUT_MARK
         && op_mode != OpMode::List
+        && op_mode != OpMode::Info
         && std::io::stdout().is_terminal()
         && !force_stdout
     {
-        lz4::displaylevel!(1, "refusing to write to console without -c \n");
-        std::process::exit(1);
+        return Err(CliError::usage("refusing to write to console without -c"));
+    }
+
+    // ── gzip-compat: like `gzip -c`, writing to stdout keeps the source ───
+    if prefs.gzip_compat && output_filename == STDOUT_MARK {
+        prefs.set_remove_src_file(false);
     }
 
     // ── Display level downgrade (lz4cli.c lines 821–824) ──────────────────
     if output_filename == STDOUT_MARK && display_level() == 2 {
         set_display_level(1);
     }
-    if multiple_inputs && display_level() == 2 {
+    // Test mode keeps level 2, where it reports each file that passed.
+    if multiple_inputs && display_level() == 2 && !test_mode {
         set_display_level(1);
     }
 
@@ -235,11 +364,26 @@ fn run(args: ParsedArgs) -> i32 {
     if in_file_names.is_empty() {
         multiple_inputs = false;
     }
+    if output_dir.is_some()
+        && (op_mode != OpMode::Compress || !multiple_inputs || output_filename == STDOUT_MARK)
+    {
+        return Err(CliError::usage(
+            "--output-dir needs input files to compress and cannot be combined with -c or -d",
+        ));
+    }
 
     // ── Operation dispatch (lz4cli.c lines 833–887) ────────────────────────
-    let operation_result: i32 = if op_mode == OpMode::Decompress {
+    let operation_result: Result<(), CliError> = if op_mode == OpMode::Decompress {
         // -- Decompress (lz4cli.c lines 833–845) --
-        if multiple_inputs {
+        if test_mode {
+            // Every input is tested and reported, even after a failure.
+            let srcs: Vec<&str> = if multiple_inputs {
+                in_file_names.iter().map(|s| s.as_str()).collect()
+            } else {
+                vec![input_filename.as_str()]
+            };
+            test_multiple_filenames(&srcs, &prefs).map_err(CliError::from)
+        } else if multiple_inputs {
             let dec_extension: &str = if output_filename == STDOUT_MARK {
                 STDOUT_MARK
             } else if output_filename == lz4::io::NUL_MARK {
@@ -249,21 +393,31 @@ fn run(args: ParsedArgs) -> i32 {
             };
             let srcs: Vec<&str> = in_file_names.iter().map(|s| s.as_str()).collect();
             match decompress_multiple_filenames(&srcs, dec_extension, &prefs) {
-                Ok(()) => 0,
-                Err(_) => 1,
+                Ok(()) => Ok(()),
+                Err(e) => Err(e.into()),
             }
         } else {
             match decompress_filename(&input_filename, &output_filename, &prefs) {
-                Ok(_) => 0,
-                Err(_) => 1,
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.into()),
             }
         }
     } else if op_mode == OpMode::List {
         // -- List (lz4cli.c line 847) --
+        // The report is printed line by line with `println!`; let a closed
+        // pipe end the process quietly instead of panicking.
+        restore_default_sigpipe();
         let srcs: Vec<&str> = in_file_names.iter().map(|s| s.as_str()).collect();
         match display_compressed_files_info(&srcs) {
-            Ok(()) => 0,
-            Err(_) => 1,
+            Ok(()) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    } else if op_mode == OpMode::Info {
+        restore_default_sigpipe();
+        let srcs: Vec<&str> = in_file_names.iter().map(|s| s.as_str()).collect();
+        match display_compressed_files_info_checked(&srcs) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(e.into()),
         }
     } else {
         // -- Compress (default; lz4cli.c lines 848–887) --
@@ -303,18 +457,35 @@ fn run(args: ParsedArgs) -> i32 {
                 };
                 let srcs: Vec<&str> = in_file_names.iter().map(|s| s.as_str()).collect();
                 match compress_multiple_filenames_legacy(&srcs, leg_ext, c_level, &prefs) {
-                    Ok(()) => 0,
-                    Err(_) => 1,
+                    Ok(()) => Ok(()),
+                    Err(e) => Err(e.into()),
                 }
             } else {
                 match compress_filename_legacy(&input_filename, &output_filename, c_level, &prefs) {
-                    Ok(_) => 0,
-                    Err(_) => 1,
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(e.into()),
                 }
             }
         } else {
             // Standard LZ4 frame format (lz4cli.c lines 878–887).
-            if multiple_inputs {
+            if let (true, Some(dir)) = (multiple_inputs, output_dir.as_deref()) {
+                let srcs: Vec<&str> = in_file_names.iter().map(|s| s.as_str()).collect();
+                match compress_multiple_filenames_to_dir(
+                    &srcs,
+                    dir,
+                    mirror_tree,
+                    LZ4_EXTENSION,
+                    c_level,
+                    &prefs,
+                ) {
+                    Ok(0) => Ok(()),
+                    Ok(missed) => Err(CliError::Io(std::io::Error::other(format!(
+                        "{} file(s) could not be compressed",
+                        missed
+                    )))),
+                    Err(e) => Err(e.into()),
+                }
+            } else if multiple_inputs {
                 let comp_ext: &str = if output_filename == STDOUT_MARK {
                     STDOUT_MARK
                 } else {
@@ -322,13 +493,17 @@ fn run(args: ParsedArgs) -> i32 {
                 };
                 let srcs: Vec<&str> = in_file_names.iter().map(|s| s.as_str()).collect();
                 match compress_multiple_filenames(&srcs, comp_ext, c_level, &prefs) {
-                    Ok(missed) => missed as i32,
-                    Err(_) => 1,
+                    Ok(0) => Ok(()),
+                    Ok(missed) => Err(CliError::Io(std::io::Error::other(format!(
+                        "{} file(s) could not be compressed",
+                        missed
+                    )))),
+                    Err(e) => Err(e.into()),
                 }
             } else {
                 match compress_filename(&input_filename, &output_filename, c_level, &prefs) {
-                    Ok(_) => 0,
-                    Err(_) => 1,
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(e.into()),
                 }
             }
         }
@@ -338,6 +513,14 @@ fn run(args: ParsedArgs) -> i32 {
     // C: if (main_pause) waitEnter(); free(dynNameSpace); free(fileNamesBuf);
     //    LZ4IO_freePreferences(prefs); free((void*)inFileNames);
     // In Rust all heap allocations are freed automatically by Drop.
+    let stat_warnings = lz4::io::file_io::stat_warnings();
+    if stat_warnings > 0 {
+        lz4::displaywarning!(
+            2,
+            "lz4: attributes not fully restored on {} file(s) \n",
+            stat_warnings
+        );
+    }
     if main_pause {
         wait_enter();
     }
@@ -347,6 +530,7 @@ fn run(args: ParsedArgs) -> i32 {
 
 // ── Entry point ───────────────────────────────────────────────────────────────
 
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
 fn main() {
     // argv[0] → alias detection (lz4cli.c lines 412–439).
     let argv0 = std::env::args().next().unwrap_or_else(|| "lz4".to_owned());
@@ -355,18 +539,62 @@ fn main() {
     // Argument parsing loop (lz4cli.c lines 442–703).
     let args = match parse_args(init) {
         Ok(a) => a,
-        Err(e) => {
-            eprintln!("lz4: {}", e);
-            std::process::exit(1);
-        }
+        Err(e) => exit_with(CliError::from(e)),
     };
 
     // Help / version flags set exit_early; the caller should exit 0.
     if args.exit_early {
-        std::process::exit(0);
+        std::process::exit(EXIT_OK);
     }
 
     // Post-parse dispatch and cleanup (lz4cli.c lines 704–893).
-    let exit_code = run(args);
-    std::process::exit(exit_code);
+    match run(args) {
+        Ok(()) => std::process::exit(EXIT_OK),
+        Err(e) => exit_with(e),
+    }
+}
+
+/// Give `SIGPIPE` back its default action (terminate) for report-style output.
+///
+/// Rust starts programs with `SIGPIPE` ignored, which turns a closed pipe into
+/// an `EPIPE` error.  The compress and decompress paths rely on that to stop
+/// cleanly (see [`lz4::io::stdout_closed`]); `--list` has no cleanup to do.
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
+fn restore_default_sigpipe() {
+    #[cfg(unix)]
+    // SAFETY: installing the default disposition for SIGPIPE has no
+    // preconditions and no other thread is changing signal handlers.
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+}
+
+/// Report `err` on stderr and terminate with its exit code.
+///
+/// I/O failures are already reported where they happen (with the file name),
+/// so they are only repeated at higher verbosity.
+#[cfg(not(any(feature = "forbid-unsafe", wasm_unknown)))]
+fn exit_with(err: CliError) -> ! {
+    // The reader of our stdout went away (`lz4 -dc big.lz4 | head`): stop
+    // quietly and report success, as Unix filters do.
+    if lz4::io::stdout_closed() {
+        std::process::exit(EXIT_OK);
+    }
+    match err {
+        CliError::Io(ref e) => lz4::displaylevel!(3, "lz4: {} \n", e),
+        _ => lz4::displayerror!("lz4: {}", err),
+    }
+    std::process::exit(err.exit_code());
+}
+
+#[cfg(feature = "forbid-unsafe")]
+fn main() {
+    eprintln!("lz4: built with the `forbid-unsafe` feature, which has no command-line interface");
+    std::process::exit(1);
+}
+
+#[cfg(all(wasm_unknown, not(feature = "forbid-unsafe")))]
+fn main() {
+    eprintln!("lz4: the command-line interface is not available on wasm32-unknown-unknown");
+    std::process::exit(1);
 }
@@ -9,9 +9,15 @@
 //! entries report a symlink `file_type()` rather than the target's type and are
 //! excluded from the result. This prevents infinite loops from cyclic symlinks.
 //! A symlink passed directly as a non-directory input is forwarded as-is.
+//!
+//! [`read_file_list`] parses the path lists that `--files-from` reads, as
+//! written by `find -print` or `find -print0`.
+//!
+//! [`mirror_path`] maps a source path to its place under `--output-dir` when
+//! `-r` recreates the source tree there.
 
-use std::io;
-use std::path::{Path, PathBuf};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
 
 use walkdir::WalkDir;
 
@@ -53,6 +59,56 @@ pub fn create_file_list(inputs: &[&Path]) -> io::Result<Vec<PathBuf>> {
     Ok(result)
 }
 
+/// Read a list of paths from `reader`, one per line or NUL-terminated.
+///
+/// If the input contains a NUL byte, entries are separated by NUL only, so
+/// names may contain newlines (`find -print0`).  Otherwise they are separated
+/// by `\n`, and a trailing `\r` is dropped from each line.  Empty entries are
+/// skipped.  An entry that is not valid UTF-8 is an
+/// [`io::ErrorKind::InvalidData`] error.
+pub fn read_file_list<R: Read>(mut reader: R) -> io::Result<Vec<String>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let nul_separated = data.contains(&0);
+    data.split(|&b| if nul_separated { b == 0 } else { b == b'\n' })
+        .map(|entry| {
+            if nul_separated {
+                entry
+            } else {
+                entry.strip_suffix(b"\r").unwrap_or(entry)
+            }
+        })
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            String::from_utf8(entry.to_vec()).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "file list entry is not valid UTF-8: {}",
+                        String::from_utf8_lossy(entry)
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Place `src` under `out_dir`, keeping its directory structure.
+///
+/// Only the normal components of `src` are kept: a root or drive prefix, `.`
+/// and `..` are dropped, so the result never escapes `out_dir`
+/// (`/data/logs/a.txt` and `../logs/a.txt` both become `out_dir/…/logs/a.txt`).
+/// No suffix is added and nothing is created on disk.
+pub fn mirror_path(src: &Path, out_dir: &Path) -> PathBuf {
+    let mut dst = out_dir.to_path_buf();
+    for component in src.components() {
+        if let Component::Normal(part) = component {
+            dst.push(part);
+        }
+    }
+    dst
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,4 +218,41 @@ mod tests {
         // appear from the directory walk: total 3 entries.
         assert_eq!(list.len(), 3);
     }
+
+    #[test]
+    fn mirror_path_keeps_only_normal_components() {
+        let out = Path::new("out");
+        assert_eq!(
+            mirror_path(Path::new("src/sub/a.txt"), out),
+            Path::new("out/src/sub/a.txt")
+        );
+        assert_eq!(
+            mirror_path(Path::new("./src/../sub/a.txt"), out),
+            Path::new("out/src/sub/a.txt")
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            mirror_path(Path::new("/var/log/a.txt"), out),
+            Path::new("out/var/log/a.txt")
+        );
+    }
+
+    #[test]
+    fn read_file_list_newline_separated() {
+        let list = read_file_list(&b"a.txt\r\n\nsub/b c.txt\nlast"[..]).unwrap();
+        assert_eq!(list, ["a.txt", "sub/b c.txt", "last"]);
+    }
+
+    #[test]
+    fn read_file_list_nul_separated_keeps_newlines() {
+        let list = read_file_list(&b"one\ntwo\0three\0\0"[..]).unwrap();
+        assert_eq!(list, ["one\ntwo", "three"]);
+        assert!(read_file_list(&b""[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_file_list_rejects_invalid_utf8() {
+        let err = read_file_list(&b"ok\n\xff\xfe\n"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
 }
@@ -5,10 +5,14 @@
 //!
 //! - [`is_reg_file`]   — true if a path refers to a regular file
 //! - [`is_directory`]  — true if a path refers to a directory
+//! - [`file_kind`]     — the [`FileKind`] of a path: regular, directory,
+//!   FIFO, character or block device, socket
 //! - [`is_reg_fd`]     — true if a raw file descriptor refers to a regular
-//!                       file (available on POSIX and Windows targets)
+//!   file (available on POSIX and Windows targets)
 //! - [`set_file_stat`] — apply modification time, ownership (POSIX), and
-//!                       permission bits to a regular file
+//!   permission bits to a regular file
+//! - [`set_file_attrs`] — the same, with the modification time and owner
+//!   optional
 //!
 //! Ownership and permission operations use the [`filetime`] and [`nix`] crates
 //! on POSIX targets and `libc` on Windows.
@@ -16,7 +20,7 @@
 use std::fs;
 use std::io;
 use std::path::Path;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use filetime::FileTime;
 
@@ -26,24 +30,45 @@ use std::os::unix::io::RawFd;
 #[cfg(windows)]
 use libc;
 
+/// Timestamp granularity of the coarsest common filesystem (FAT stores
+/// modification times in 2-second steps).
+pub const MTIME_GRANULARITY: Duration = Duration::from_secs(2);
+
 /// Apply modification time, ownership, and permission bits to a regular file.
 ///
-/// Returns `Err` if `path` is not a regular file. Attribute operations are
-/// applied in order; the first failure is returned immediately.
+/// Returns `Err` if `path` is not a regular file. Every attribute is
+/// attempted even when an earlier one fails; the first failure is returned.
+/// See [`set_file_attrs`] for how timestamps and ownership are handled.
 ///
 /// # Parameters
 /// * `mtime` — desired last-modification time
 /// * `uid`   — desired owner UID (POSIX only; ignored on other targets)
 /// * `gid`   — desired owner GID (POSIX only; ignored on other targets)
 /// * `mode`  — permission bits; only the lower 12 bits are applied
-///             (`mode & 0o7777`), i.e. rwxrwxrwx plus the setuid/setgid/sticky
-///             bits. On Windows only the read-only bit is honoured.
+///   (`mode & 0o7777`), i.e. rwxrwxrwx plus the setuid/setgid/sticky
+///   bits. On Windows only the read-only bit is honoured.
 pub fn set_file_stat(
     path: &Path,
     mtime: SystemTime,
     uid: u32,
     gid: u32,
     mode: u32,
+) -> io::Result<()> {
+    set_file_attrs(path, Some(mtime), Some((uid, gid)), mode)
+}
+
+/// Like [`set_file_stat`], but leaves the timestamps alone when `mtime` is
+/// `None` and the owner alone when `owner` (`(uid, gid)`) is `None`.
+///
+/// A timestamp the filesystem rejects is retried once rounded down to
+/// [`MTIME_GRANULARITY`], which FAT and some NFS servers accept where a
+/// nanosecond value fails.  A `chown` refused with `EPERM` is not an error:
+/// unprivileged users cannot give files away, and `cp -p` ignores it too.
+pub fn set_file_attrs(
+    path: &Path,
+    mtime: Option<SystemTime>,
+    owner: Option<(u32, u32)>,
+    mode: u32,
 ) -> io::Result<()> {
     if !is_reg_file(path) {
         return Err(io::Error::new(
@@ -52,41 +77,85 @@ pub fn set_file_stat(
         ));
     }
 
-    // Set modification time; access time is updated to now (current wall-clock).
-    // filetime handles the platform-specific syscall (utimensat on POSIX, SetFileTime on Windows).
-    let atime = FileTime::from_system_time(SystemTime::now());
-    let ft_mtime = FileTime::from_system_time(mtime);
-    filetime::set_file_times(path, atime, ft_mtime)?;
+    let mut first_err: Option<io::Error> = None;
+    let mut note = |r: io::Result<()>| {
+        if let Err(e) = r {
+            first_err.get_or_insert(e);
+        }
+    };
+
+    if let Some(mtime) = mtime {
+        note(set_mtime_tolerant(path, mtime));
+    }
 
     // Copy ownership — POSIX only (chown is absent on Windows).
     #[cfg(unix)]
-    {
+    if let Some((uid, gid)) = owner {
+        use nix::errno::Errno;
         use nix::unistd::{chown, Gid, Uid};
-        chown(path, Some(Uid::from_raw(uid)), Some(Gid::from_raw(gid))).map_err(io::Error::from)?;
+        match chown(path, Some(Uid::from_raw(uid)), Some(Gid::from_raw(gid))) {
+            Ok(()) | Err(Errno::EPERM) => {}
+            Err(e) => note(Err(io::Error::from(e))),
+        }
     }
 
     // Suppress "unused variable" warnings on non-Unix targets.
     #[cfg(not(unix))]
-    let _ = (uid, gid);
+    let _ = owner;
 
     // Apply the lower 12 permission bits (rwxrwxrwx + setuid/setgid/sticky).
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o7777))?;
+        note(fs::set_permissions(
+            path,
+            fs::Permissions::from_mode(mode & 0o7777),
+        ));
     }
     #[cfg(windows)]
     {
         // Windows does not support full POSIX mode bits; honour read-only bit only.
         let readonly = (mode & 0o200) == 0;
-        let mut perms = fs::metadata(path)?.permissions();
-        perms.set_readonly(readonly);
-        fs::set_permissions(path, perms)?;
+        note(fs::metadata(path).and_then(|m| {
+            let mut perms = m.permissions();
+            perms.set_readonly(readonly);
+            fs::set_permissions(path, perms)
+        }));
     }
     #[cfg(not(any(unix, windows)))]
     let _ = mode;
 
-    Ok(())
+    match first_err {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Sets the modification time, retrying at [`MTIME_GRANULARITY`] when the
+/// exact value is rejected; access time is updated to now.
+fn set_mtime_tolerant(path: &Path, mtime: SystemTime) -> io::Result<()> {
+    // filetime handles the platform-specific syscall (utimensat on POSIX, SetFileTime on Windows).
+    let atime = FileTime::from_system_time(SystemTime::now());
+    match filetime::set_file_times(path, atime, FileTime::from_system_time(mtime)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let coarse = round_down_mtime(mtime);
+            if coarse == mtime {
+                return Err(e);
+            }
+            filetime::set_file_times(path, atime, FileTime::from_system_time(coarse)).map_err(|_| e)
+        }
+    }
+}
+
+/// Rounds `t` down to a multiple of [`MTIME_GRANULARITY`] since the Unix
+/// epoch; times before the epoch are returned unchanged.
+fn round_down_mtime(t: SystemTime) -> SystemTime {
+    let step = MTIME_GRANULARITY.as_secs();
+    match t.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(d) => SystemTime::UNIX_EPOCH + Duration::from_secs(d.as_secs() / step * step),
+        Err(_) => t,
+    }
 }
 
 /// Returns `true` if the raw file descriptor `fd` refers to a regular file.
@@ -103,9 +172,7 @@ pub fn is_reg_fd(fd: RawFd) -> bool {
     use std::os::unix::io::BorrowedFd;
     // SAFETY: fd has been verified to be non-negative.
     match fstat(unsafe { BorrowedFd::borrow_raw(fd) }) {
-        Ok(stat) => {
-            (stat.st_mode as u32) & (SFlag::S_IFMT.bits() as u32) == SFlag::S_IFREG.bits() as u32
-        }
+        Ok(stat) => stat.st_mode & SFlag::S_IFMT.bits() == SFlag::S_IFREG.bits(),
         Err(_) => false,
     }
 }
@@ -139,6 +206,67 @@ pub fn is_reg_file(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// The type of a filesystem entry, as reported by [`file_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// A regular file.
+    Regular,
+    /// A directory.
+    Directory,
+    /// A named pipe (FIFO), including the `/dev/fd/N` pipes created by shell
+    /// process substitution.
+    Fifo,
+    /// A character device such as `/dev/zero` or a terminal.
+    CharDevice,
+    /// A block device such as a disk or partition.
+    BlockDevice,
+    /// A Unix domain socket.
+    Socket,
+    /// Anything else the platform reports.
+    Other,
+}
+
+impl FileKind {
+    /// `true` for everything but regular files and directories: entries whose
+    /// size is unknown up front and whose metadata describes the device, not
+    /// the data read from it.
+    pub fn is_special(self) -> bool {
+        !matches!(self, FileKind::Regular | FileKind::Directory)
+    }
+}
+
+/// Returns the [`FileKind`] of `path`, following symlinks, or `None` if it
+/// cannot be queried (e.g. it does not exist).
+///
+/// Only [`FileKind::Regular`], [`FileKind::Directory`] and
+/// [`FileKind::Other`] are reported on non-Unix targets.
+pub fn file_kind(path: &Path) -> Option<FileKind> {
+    let ft = fs::metadata(path).ok()?.file_type();
+    if ft.is_file() {
+        return Some(FileKind::Regular);
+    }
+    if ft.is_dir() {
+        return Some(FileKind::Directory);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if ft.is_fifo() {
+            return Some(FileKind::Fifo);
+        }
+        if ft.is_char_device() {
+            return Some(FileKind::CharDevice);
+        }
+        if ft.is_block_device() {
+            return Some(FileKind::BlockDevice);
+        }
+        if ft.is_socket() {
+            return Some(FileKind::Socket);
+        }
+    }
+    Some(FileKind::Other)
+}
+
 /// Returns `true` if `path` refers to a directory.
 ///
 /// Returns `false` for regular files, symlinks, special files, and paths that
@@ -202,6 +330,33 @@ mod tests {
         assert!(!is_directory(Path::new("/nonexistent/__lz4_test_dir__")));
     }
 
+    // ── file_kind ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn file_kind_regular_and_directory() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("file.txt");
+        File::create(&path).unwrap();
+        assert_eq!(file_kind(&path), Some(FileKind::Regular));
+        assert_eq!(file_kind(dir.path()), Some(FileKind::Directory));
+        assert_eq!(file_kind(&dir.path().join("missing")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_kind_special_files() {
+        let dir = TempDir::new().unwrap();
+        let fifo = dir.path().join("pipe");
+        nix::unistd::mkfifo(&fifo, nix::sys::stat::Mode::S_IRWXU).unwrap();
+        assert_eq!(file_kind(&fifo), Some(FileKind::Fifo));
+        assert!(FileKind::Fifo.is_special());
+        assert_eq!(
+            file_kind(Path::new("/dev/null")),
+            Some(FileKind::CharDevice)
+        );
+        assert!(!FileKind::Regular.is_special());
+    }
+
     // ── is_reg_fd ────────────────────────────────────────────────────────────
 
     /// stdin (fd 0) is a terminal / pipe in test environments, not a regular file.
@@ -280,4 +435,28 @@ mod tests {
             "mtime deviation {diff:?} exceeds 1-second tolerance"
         );
     }
+
+    /// With no mtime or owner, set_file_attrs leaves them as they were.
+    #[test]
+    fn set_file_attrs_without_mtime_keeps_timestamp() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("keep_mtime.txt");
+        File::create(&path).unwrap();
+        let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        filetime::set_file_mtime(&path, FileTime::from_system_time(old)).unwrap();
+
+        set_file_attrs(&path, None, None, 0o600).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), old);
+    }
+
+    #[test]
+    fn round_down_mtime_uses_fat_granularity() {
+        let t = SystemTime::UNIX_EPOCH + Duration::new(1_000_000_001, 999_999_999);
+        assert_eq!(
+            round_down_mtime(t),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000)
+        );
+        let whole = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        assert_eq!(round_down_mtime(whole), whole);
+    }
 }
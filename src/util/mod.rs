@@ -2,11 +2,13 @@
 //!
 //! Submodules:
 //! - [`cores`]       — CPU core counting via [`std::thread::available_parallelism`]
-//! - [`file_status`] — file-type queries (`is_reg_file`, `is_directory`, `is_reg_fd`)
-//!                     and metadata mutation (`set_file_stat`)
+//! - [`file_status`] — file-type queries (`is_reg_file`, `is_directory`, `file_kind`,
+//!   `is_reg_fd`)
+//!   and metadata mutation (`set_file_stat`, `set_file_attrs`)
 //! - [`file_size`]   — file size queries (`get_file_size`, `get_open_file_size`,
-//!                     `get_total_file_size`)
-//! - [`file_list`]   — recursive directory expansion into a flat `Vec<PathBuf>`
+//!   `get_total_file_size`)
+//! - [`file_list`]   — recursive directory expansion into a flat `Vec<PathBuf>`,
+//!   `--files-from` list parsing, and `--output-dir` tree mirroring
 //!
 //! The most commonly needed symbols are re-exported at the `util` module level.
 
@@ -21,14 +23,16 @@ pub mod file_status;
 
 pub use cores::count_cores;
 
-pub use file_status::{is_directory, is_reg_file, set_file_stat};
+pub use file_status::{
+    file_kind, is_directory, is_reg_file, set_file_attrs, set_file_stat, FileKind,
+};
 
 #[cfg(unix)]
 pub use file_status::is_reg_fd;
 
 pub use file_size::{get_file_size, get_open_file_size, get_total_file_size};
 
-pub use file_list::create_file_list;
+pub use file_list::{create_file_list, mirror_path, read_file_list};
 
 // ── String helpers ────────────────────────────────────────────────────────────
 
@@ -0,0 +1,387 @@
+//! Differential checks against the reference C implementation.
+//!
+//! [`cross_check`] compresses a buffer with this crate and decodes it with
+//! the C library (LZ4 1.10.0, built by the `lz4-sys` crate), then compresses
+//! it with the C library and decodes it here.  It covers every encoding that
+//! reaches the wire: fast and accelerated blocks, HC levels, and frames with
+//! each block size, linked and independent blocks, block and content
+//! checksums, and content sizes.  Every decode must give the input back
+//! exactly.
+//!
+//! Requires the `verify` feature.  The `compat_tests` suite runs
+//! [`cross_check`] over random and structured inputs with proptest.
+
+use std::fmt;
+
+use lz4_sys as c;
+
+use crate::block::{compress_bound, compress_fast, decompress_safe, LZ4_MAX_INPUT_SIZE};
+use crate::frame::{
+    decompress_frame_into, decompress_frame_to_vec, lz4f_compress_frame, lz4f_compress_frame_bound,
+    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, FrameInfo, Preferences,
+};
+use crate::hc::compress_hc_to_vec;
+
+/// A round trip through the reference implementation that did not give its
+/// input back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossCheckError {
+    /// Mode and direction, e.g. `"frame 64KB linked level 9: lz4r -> C"`.
+    pub case: String,
+    /// The call that failed, or where the output first differs.
+    pub detail: String,
+}
+
+impl fmt::Display for CrossCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.case, self.detail)
+    }
+}
+
+impl std::error::Error for CrossCheckError {}
+
+/// A block encoder available on both sides.
+#[derive(Debug, Clone, Copy)]
+enum BlockCodec {
+    Fast(i32),
+    Hc(i32),
+}
+
+const BLOCK_CODECS: [BlockCodec; 5] = [
+    BlockCodec::Fast(1),
+    BlockCodec::Fast(8),
+    BlockCodec::Hc(2),
+    BlockCodec::Hc(9),
+    BlockCodec::Hc(12),
+];
+
+/// Round-trips `data` between this crate and the reference C library in
+/// both directions, for every block codec and frame layout.
+///
+/// Blocks are skipped for inputs above
+/// [`LZ4_MAX_INPUT_SIZE`](crate::block::LZ4_MAX_INPUT_SIZE); frames are
+/// always checked.
+///
+/// # Errors
+///
+/// The first round trip that fails or does not reproduce `data`.
+///
+/// ```
+/// let text = b"to be or not to be, that is the question; ".repeat(100);
+/// lz4::verify::cross_check(&text).unwrap();
+/// ```
+pub fn cross_check(data: &[u8]) -> Result<(), CrossCheckError> {
+    if data.len() <= LZ4_MAX_INPUT_SIZE as usize {
+        for codec in BLOCK_CODECS {
+            let block = ours_block(data, codec);
+            check(data, ref_block_decompress(&block, data.len()), || {
+                format!("block {codec:?}: lz4r -> C")
+            })?;
+            let block = ref_block_compress(data, codec);
+            check(data, ours_block_decompress(&block, data.len()), || {
+                format!("block {codec:?}: C -> lz4r")
+            })?;
+        }
+    }
+
+    for prefs in frame_matrix(data.len()) {
+        let case = |dir: &str| {
+            let info = &prefs.frame_info;
+            format!(
+                "frame {:?} {:?} level {} block crc {:?} content crc {:?} size {}: {dir}",
+                info.block_size_id,
+                info.block_mode,
+                prefs.compression_level,
+                info.block_checksum_flag,
+                info.content_checksum_flag,
+                info.content_size,
+            )
+        };
+        let frame = ours_frame(data, &prefs).map_err(|detail| CrossCheckError {
+            case: case("lz4r compress"),
+            detail,
+        })?;
+        check(data, ref_frame_decompress(&frame), || case("lz4r -> C"))?;
+
+        let frame = ref_frame_compress(data, &prefs).map_err(|detail| CrossCheckError {
+            case: case("C compress"),
+            detail,
+        })?;
+        let streamed = decompress_frame_to_vec(&frame).map_err(|e| e.to_string());
+        check(data, streamed, || case("C -> lz4r"))?;
+        let mut buf = vec![0u8; data.len()];
+        let direct = decompress_frame_into(&mut buf, &frame)
+            .map(|n| buf[..n].to_vec())
+            .map_err(|e| format!("{e:?}"));
+        check(data, direct, || case("C -> lz4r decompress_frame_into"))?;
+    }
+    Ok(())
+}
+
+/// Every frame layout, at a fast and an HC level.  Content sizes are the
+/// real length, as both encoders write whatever they are given.
+fn frame_matrix(len: usize) -> Vec<Preferences> {
+    let mut out = Vec::new();
+    for block_size_id in [
+        BlockSizeId::Max64Kb,
+        BlockSizeId::Max256Kb,
+        BlockSizeId::Max4Mb,
+    ] {
+        for block_mode in [BlockMode::Linked, BlockMode::Independent] {
+            for checks in [false, true] {
+                for compression_level in [0, 9] {
+                    let (block_crc, content_crc) = if checks {
+                        (BlockChecksum::Enabled, ContentChecksum::Enabled)
+                    } else {
+                        (BlockChecksum::Disabled, ContentChecksum::Disabled)
+                    };
+                    out.push(Preferences {
+                        frame_info: FrameInfo {
+                            block_size_id,
+                            block_mode,
+                            block_checksum_flag: block_crc,
+                            content_checksum_flag: content_crc,
+                            content_size: if checks { len as u64 } else { 0 },
+                            ..Default::default()
+                        },
+                        compression_level,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+    out
+}
+
+fn check(
+    data: &[u8],
+    decoded: Result<Vec<u8>, String>,
+    case: impl FnOnce() -> String,
+) -> Result<(), CrossCheckError> {
+    let detail = match decoded {
+        Ok(out) if out == data => return Ok(()),
+        Ok(out) => match data.iter().zip(&out).position(|(a, b)| a != b) {
+            Some(at) => format!("output differs at byte {at}"),
+            None => format!("decoded {} bytes, expected {}", out.len(), data.len()),
+        },
+        Err(e) => e,
+    };
+    Err(CrossCheckError {
+        case: case(),
+        detail,
+    })
+}
+
+// ── This crate ───────────────────────────────────────────────────────────────
+
+fn ours_block(data: &[u8], codec: BlockCodec) -> Vec<u8> {
+    match codec {
+        BlockCodec::Fast(acceleration) => {
+            let mut out = vec![0u8; compress_bound(data.len() as i32) as usize];
+            let n = compress_fast(data, &mut out, acceleration).unwrap_or(0);
+            out.truncate(n);
+            out
+        }
+        BlockCodec::Hc(level) => compress_hc_to_vec(data, level),
+    }
+}
+
+fn ours_block_decompress(block: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    let mut out = vec![0u8; size];
+    let n = decompress_safe(block, &mut out).map_err(|e| format!("{e:?}"))?;
+    out.truncate(n);
+    Ok(out)
+}
+
+fn ours_frame(data: &[u8], prefs: &Preferences) -> Result<Vec<u8>, String> {
+    let mut out = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(prefs))];
+    let n = lz4f_compress_frame(&mut out, data, Some(prefs)).map_err(|e| format!("{e:?}"))?;
+    out.truncate(n);
+    Ok(out)
+}
+
+// ── Reference C library ──────────────────────────────────────────────────────
+
+fn ref_block_compress(data: &[u8], codec: BlockCodec) -> Vec<u8> {
+    let len = data.len() as i32;
+    // SAFETY: plain C call on an input length within LZ4_MAX_INPUT_SIZE.
+    let bound = unsafe { c::LZ4_compressBound(len) };
+    let mut out = vec![0u8; bound as usize];
+    // The C encoders compute `src + len - MFLIMIT`, which wraps around for
+    // an empty slice's dangling pointer; give them a real address instead.
+    let pad = [0u8; 1];
+    let src = if data.is_empty() {
+        pad.as_ptr()
+    } else {
+        data.as_ptr()
+    };
+    let (src, dst) = (src.cast(), out.as_mut_ptr().cast());
+    // SAFETY: `src` is valid for `len` bytes and `dst` for `bound` bytes.
+    let n = unsafe {
+        match codec {
+            BlockCodec::Fast(acceleration) => {
+                c::LZ4_compress_fast(src, dst, len, bound, acceleration)
+            }
+            BlockCodec::Hc(level) => c::LZ4_compress_HC(src, dst, len, bound, level),
+        }
+    };
+    out.truncate(n.max(0) as usize);
+    out
+}
+
+fn ref_block_decompress(block: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    let mut out = vec![0u8; size];
+    // SAFETY: `block` is valid for its length and `out` for `size` bytes.
+    let n = unsafe {
+        c::LZ4_decompress_safe(
+            block.as_ptr().cast(),
+            out.as_mut_ptr().cast(),
+            block.len() as i32,
+            size as i32,
+        )
+    };
+    if n < 0 {
+        return Err(format!("LZ4_decompress_safe returned {n}"));
+    }
+    out.truncate(n as usize);
+    Ok(out)
+}
+
+fn ref_error(call: &str, code: c::size_t) -> Option<String> {
+    // SAFETY: LZ4F_isError and LZ4F_getErrorName accept any code and return
+    // a static NUL-terminated string.
+    unsafe {
+        (c::LZ4F_isError(code) != 0).then(|| {
+            let name = std::ffi::CStr::from_ptr(c::LZ4F_getErrorName(code));
+            format!("{call}: {}", name.to_string_lossy())
+        })
+    }
+}
+
+fn ref_prefs(prefs: &Preferences) -> c::LZ4FPreferences {
+    let info = &prefs.frame_info;
+    c::LZ4FPreferences {
+        frame_info: c::LZ4FFrameInfo {
+            block_size_id: match info.block_size_id {
+                BlockSizeId::Default => c::BlockSize::Default,
+                BlockSizeId::Max64Kb => c::BlockSize::Max64KB,
+                BlockSizeId::Max256Kb => c::BlockSize::Max256KB,
+                BlockSizeId::Max1Mb => c::BlockSize::Max1MB,
+                BlockSizeId::Max4Mb => c::BlockSize::Max4MB,
+            },
+            block_mode: match info.block_mode {
+                BlockMode::Linked => c::BlockMode::Linked,
+                BlockMode::Independent => c::BlockMode::Independent,
+            },
+            content_checksum_flag: match info.content_checksum_flag {
+                ContentChecksum::Disabled => c::ContentChecksum::NoChecksum,
+                ContentChecksum::Enabled => c::ContentChecksum::ChecksumEnabled,
+            },
+            frame_type: c::FrameType::Frame,
+            content_size: info.content_size,
+            dict_id: 0,
+            block_checksum_flag: match info.block_checksum_flag {
+                BlockChecksum::Disabled => c::BlockChecksum::NoBlockChecksum,
+                BlockChecksum::Enabled => c::BlockChecksum::BlockChecksumEnabled,
+            },
+        },
+        compression_level: prefs.compression_level.max(0) as u32,
+        auto_flush: 0,
+        favor_dec_speed: prefs.favor_dec_speed as u32,
+        reserved: [0; 3],
+    }
+}
+
+/// Compresses `data` as one frame through the C streaming API
+/// (`LZ4F_compressBegin` / `Update` / `End`), fed in 100 000-byte pieces so
+/// blocks span update calls.
+fn ref_frame_compress(data: &[u8], prefs: &Preferences) -> Result<Vec<u8>, String> {
+    const PIECE: usize = 100_000;
+    let cprefs = ref_prefs(prefs);
+    let mut ctx = c::LZ4FCompressionContext(std::ptr::null_mut());
+    // SAFETY: every pointer below is a live buffer of the stated length, and
+    // `ctx` is freed on every path once created.
+    unsafe {
+        if let Some(e) = ref_error(
+            "LZ4F_createCompressionContext",
+            c::LZ4F_createCompressionContext(&mut ctx, c::LZ4F_VERSION),
+        ) {
+            return Err(e);
+        }
+        let bound = c::LZ4F_compressBound(PIECE, &cprefs) + 64;
+        let mut out = Vec::new();
+        let mut buf = vec![0u8; bound];
+        let result = (|| {
+            let n = c::LZ4F_compressBegin(ctx, buf.as_mut_ptr(), bound, &cprefs);
+            if let Some(e) = ref_error("LZ4F_compressBegin", n) {
+                return Err(e);
+            }
+            out.extend_from_slice(&buf[..n]);
+            for piece in data.chunks(PIECE) {
+                let n = c::LZ4F_compressUpdate(
+                    ctx,
+                    buf.as_mut_ptr(),
+                    bound,
+                    piece.as_ptr(),
+                    piece.len(),
+                    std::ptr::null(),
+                );
+                if let Some(e) = ref_error("LZ4F_compressUpdate", n) {
+                    return Err(e);
+                }
+                out.extend_from_slice(&buf[..n]);
+            }
+            let n = c::LZ4F_compressEnd(ctx, buf.as_mut_ptr(), bound, std::ptr::null());
+            if let Some(e) = ref_error("LZ4F_compressEnd", n) {
+                return Err(e);
+            }
+            out.extend_from_slice(&buf[..n]);
+            Ok(())
+        })();
+        c::LZ4F_freeCompressionContext(ctx);
+        result.map(|()| out)
+    }
+}
+
+/// Decodes one frame through `LZ4F_decompress` with a 64 KiB output window.
+fn ref_frame_decompress(frame: &[u8]) -> Result<Vec<u8>, String> {
+    let mut ctx = c::LZ4FDecompressionContext(std::ptr::null_mut());
+    // SAFETY: as in `ref_frame_compress`.
+    unsafe {
+        if let Some(e) = ref_error(
+            "LZ4F_createDecompressionContext",
+            c::LZ4F_createDecompressionContext(&mut ctx, c::LZ4F_VERSION),
+        ) {
+            return Err(e);
+        }
+        let mut out = Vec::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut pos = 0;
+        let result = loop {
+            let mut dst_len = buf.len();
+            let mut src_len = frame.len() - pos;
+            let hint = c::LZ4F_decompress(
+                ctx,
+                buf.as_mut_ptr(),
+                &mut dst_len,
+                frame[pos..].as_ptr(),
+                &mut src_len,
+                std::ptr::null(),
+            );
+            if let Some(e) = ref_error("LZ4F_decompress", hint) {
+                break Err(e);
+            }
+            out.extend_from_slice(&buf[..dst_len]);
+            pos += src_len;
+            if hint == 0 {
+                break Ok(out);
+            }
+            if src_len == 0 && dst_len == 0 {
+                break Err("LZ4F_decompress: frame is truncated".to_owned());
+            }
+        };
+        c::LZ4F_freeDecompressionContext(ctx);
+        result
+    }
+}
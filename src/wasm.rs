@@ -0,0 +1,91 @@
+//! JavaScript bindings (`wasm-bindgen` feature).
+//!
+//! Exports one-shot frame compression and decompression to JavaScript, so a
+//! web app can read and write `.lz4` data with this crate instead of an
+//! emscripten build of the C library.  Both functions take and return
+//! `Uint8Array`s and throw an `Error` on failure:
+//!
+//! ```js
+//! import init, { compress_frame, decompress_frame } from "./pkg/lz4.js";
+//! await init();
+//! const packed = compress_frame(bytes);          // level 0 (fast)
+//! const smaller = compress_frame(bytes, 9);      // HC level 9
+//! const unpacked = decompress_frame(packed);
+//! const bounded = decompress_frame(packed, 1 << 20);
+//! ```
+//!
+//! Build for the web with
+//!
+//! ```sh
+//! cargo rustc --lib --release --target wasm32-unknown-unknown \
+//!     --features wasm-bindgen --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/lz4.wasm
+//! ```
+//!
+//! On `wasm32-unknown-unknown` the file-based `io` and `cli` modules are not
+//! compiled, and the benchmark timers are unavailable; the block, HC and
+//! frame codecs work as on any other target.
+
+use wasm_bindgen::prelude::*;
+
+use crate::frame::{
+    decompress_frame_to_vec, decompress_frame_to_vec_with_limit, lz4f_compress_frame,
+    lz4f_compress_frame_bound, Lz4FError, Preferences,
+};
+
+/// Compress `data` into one LZ4 frame at `level` (default 0; 3 and above
+/// select HC, negative values trade ratio for speed).
+#[wasm_bindgen]
+pub fn compress_frame(data: &[u8], level: Option<i32>) -> Result<Vec<u8>, JsError> {
+    encode(data, level.unwrap_or(0)).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Decompress the LZ4 frame in `data`.  With `max_output_size`, frames that
+/// would decode to more bytes than that are rejected instead of being
+/// allocated.
+#[wasm_bindgen]
+pub fn decompress_frame(data: &[u8], max_output_size: Option<u32>) -> Result<Vec<u8>, JsError> {
+    decode(data, max_output_size).map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn encode(data: &[u8], level: i32) -> Result<Vec<u8>, Lz4FError> {
+    let prefs = Preferences {
+        compression_level: level,
+        ..Default::default()
+    };
+    let mut out = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(&prefs))];
+    let n = lz4f_compress_frame(&mut out, data, Some(&prefs))?;
+    out.truncate(n);
+    Ok(out)
+}
+
+fn decode(data: &[u8], max_output_size: Option<u32>) -> std::io::Result<Vec<u8>> {
+    match max_output_size {
+        Some(limit) => decompress_frame_to_vec_with_limit(data, u64::from(limit)),
+        None => decompress_frame_to_vec(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_across_levels() {
+        let data: Vec<u8> = b"wasm bindings round trip ".repeat(400);
+        for level in [-5, 0, 9, 12] {
+            let packed = encode(&data, level).unwrap();
+            assert!(packed.len() < data.len());
+            assert_eq!(decode(&packed, None).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn output_limit_is_enforced() {
+        let data = vec![7u8; 10_000];
+        let packed = encode(&data, 0).unwrap();
+        assert_eq!(decode(&packed, Some(10_000)).unwrap(), data);
+        assert!(decode(&packed, Some(9_999)).is_err());
+        assert!(decode(b"not a frame", None).is_err());
+    }
+}
@@ -3,8 +3,13 @@
 //!
 //! Only XXH32 is needed: `lz4frame` uses it exclusively for content checksums.
 
+use std::io::{self, Read};
+
 pub use xxhash_rust::xxh32::Xxh32 as Xxh32State;
 
+/// Read buffer for [`xxh32_of_reader`].
+const READ_CHUNK: usize = 64 * 1024;
+
 /// One-shot XXH32 hash — equivalent to the C `XXH32(data, len, seed)` function.
 ///
 /// # Test vectors
@@ -13,3 +18,22 @@ pub use xxhash_rust::xxh32::Xxh32 as Xxh32State;
 pub fn xxh32_oneshot(data: &[u8], seed: u32) -> u32 {
     xxhash_rust::xxh32::xxh32(data, seed)
 }
+
+/// XXH32 (seed 0) of everything `reader` yields until end of file.
+///
+/// Reads through a 64 KiB buffer, retrying on [`io::ErrorKind::Interrupted`].
+/// The value is what an LZ4 frame stores as its content checksum and what
+/// `xxhsum -H32` prints for the same bytes, so a decompressed file can be
+/// compared against either without loading it into memory.
+pub fn xxh32_of_reader(mut reader: impl Read) -> io::Result<u32> {
+    let mut state = Xxh32State::new(0);
+    let mut buf = vec![0u8; READ_CHUNK];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(state.digest()),
+            Ok(n) => state.update(&buf[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
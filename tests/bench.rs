@@ -1,3 +1,5 @@
+#![cfg(all(feature = "bench", not(feature = "forbid-unsafe")))]
+
 #[path = "bench/api.rs"]
 mod api;
 #[path = "bench/compress_strategy.rs"]
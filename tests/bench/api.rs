@@ -14,6 +14,7 @@
 //   - dict_file exactly LZ4_MAX_DICT_SIZE → entire file loaded
 //   - bench_files with nonexistent file path → Err
 //   - bench_files_separately: each file benchmarked independently; one error does not skip others
+//   - dict_size_sweep: re-runs the corpus per dictionary tail size, incl. sizes above the dict
 
 use lz4::bench::bench_files;
 use lz4::bench::config::BenchConfig;
@@ -218,6 +219,33 @@ fn bench_files_dict_exactly_max_dict_size_ok() {
     );
 }
 
+#[test]
+fn bench_files_dict_size_sweep_ok() {
+    let dict_content: Vec<u8> = (0u8..=255).cycle().take(LZ4_MAX_DICT_SIZE).collect();
+    let (_tmp_dict, dict_path) = make_temp_file(&dict_content);
+    let data: Vec<u8> = (0u8..=255).cycle().take(65536).collect();
+    let (_tmp_a, path_a) = make_temp_file(&data);
+    let (_tmp_b, path_b) = make_temp_file(&data);
+
+    let mut config = quiet_config();
+    // 0 benchmarks without a dictionary; 1 MiB is clamped to the 64 KiB loaded.
+    config.set_dict_size_sweep(vec![0, 4096, 16384, 1 << 20]);
+    let result = bench_files(&[&path_a], 1, 2, Some(&dict_path), &config);
+    assert!(
+        result.is_ok(),
+        "dict size sweep must succeed: {:?}",
+        result.err()
+    );
+
+    config.set_bench_separately(true);
+    let result = bench_files(&[&path_a, &path_b], 1, 1, Some(&dict_path), &config);
+    assert!(
+        result.is_ok(),
+        "separate dict size sweep must succeed: {:?}",
+        result.err()
+    );
+}
+
 // ── File dispatch paths ───────────────────────────────────────────────────────
 
 #[test]
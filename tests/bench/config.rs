@@ -73,6 +73,7 @@ fn lz4_max_dict_size_is_64kb() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn max_memory_is_positive_and_reasonable() {
     // MAX_MEMORY must be > 0 and at most 4 GiB on 32-bit or larger on 64-bit
     assert!(MAX_MEMORY > 0, "MAX_MEMORY must be positive");
@@ -248,7 +249,7 @@ fn setters_return_mut_self_for_chaining() {
     let mut cfg = BenchConfig::default();
     cfg.set_notification_level(3)
         .set_nb_seconds(5)
-        .set_block_size(1 * MB)
+        .set_block_size(MB)
         .set_additional_param(7)
         .set_bench_separately(true)
         .set_decode_only(true)
@@ -29,7 +29,7 @@ fn frame_decompressor_new_does_not_panic() {
 #[test]
 fn frame_decompressor_default_does_not_panic() {
     // Default should also be available (derived)
-    let _dec = FrameDecompressor::default();
+    let _dec = FrameDecompressor;
 }
 
 #[test]
@@ -76,6 +76,8 @@ fn bench_result_is_debug_clone() {
         compress_speed_mb_s: 500.0,
         decompress_speed_mb_s: 1000.0,
         c_level: 1,
+        compress_joules_per_gb: None,
+        decompress_joules_per_gb: None,
     };
     let cloned = r.clone();
     assert_eq!(cloned.src_size, 100);
@@ -1,9 +1,19 @@
+#![cfg(all(feature = "block", not(feature = "forbid-unsafe")))]
+
 #[path = "block/compress.rs"]
 mod compress;
+#[path = "block/compressor.rs"]
+mod compressor;
 #[path = "block/decompress_api.rs"]
 mod decompress_api;
 #[path = "block/decompress_core.rs"]
 mod decompress_core;
+#[path = "block/estimate.rs"]
+mod estimate;
+#[path = "block/policy.rs"]
+mod policy;
+#[path = "block/safe.rs"]
+mod safe;
 #[path = "block/stream.rs"]
 mod stream;
 #[path = "block/types.rs"]
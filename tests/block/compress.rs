@@ -11,8 +11,9 @@
 
 use lz4::block::compress::{
     compress_bound, compress_default, compress_dest_size, compress_dest_size_ext_state,
-    compress_fast, compress_fast_ext_state, compress_fast_ext_state_fast_reset, Lz4Error,
-    LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX, LZ4_MAX_INPUT_SIZE,
+    compress_fast, compress_fast_ext_state, compress_fast_ext_state_fast_reset,
+    compress_fast_seeded, Lz4Error, LZ4_ACCELERATION_DEFAULT, LZ4_ACCELERATION_MAX,
+    LZ4_MAX_INPUT_SIZE,
 };
 use lz4::block::types::StreamStateInternal;
 
@@ -943,11 +944,9 @@ fn compress_dest_size_zero_capacity() {
     let src: Vec<u8> = vec![b'A'; 100];
     let mut dst = vec![0u8; 0];
     let result = compress_dest_size(&src, &mut dst);
-    match result {
-        Ok((_consumed, compressed)) => {
-            assert_eq!(compressed, 0);
-        }
-        Err(_) => {} // error for zero capacity is acceptable
+    // An error for zero capacity is also acceptable.
+    if let Ok((_consumed, compressed)) = result {
+        assert_eq!(compressed, 0);
     }
 }
 
@@ -1082,3 +1081,46 @@ fn fast_reset_large_limited() {
     };
     assert!(result.is_ok());
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// compress_fast_seeded
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn seeded_zero_matches_compress_fast() {
+    let small: Vec<u8> = (0..10_000u32).map(|i| (i * 7 % 97) as u8).collect();
+    let large: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    for src in [&small, &large] {
+        let bound = compress_bound(src.len() as i32) as usize;
+        let mut a = vec![0u8; bound];
+        let mut b = vec![0u8; bound];
+        let na = compress_fast(src, &mut a, 3).unwrap();
+        let nb = compress_fast_seeded(src, &mut b, 3, 0).unwrap();
+        assert_eq!(a[..na], b[..nb]);
+    }
+}
+
+#[test]
+fn seeded_output_round_trips() {
+    let src: Vec<u8> = (0..150_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 27) as u8 ^ (i % 13) as u8)
+        .collect();
+    let mut dst = vec![0u8; compress_bound(src.len() as i32) as usize];
+    for seed in [1u64, 0x5EED, u64::MAX] {
+        let n = compress_fast_seeded(&src, &mut dst, 1, seed).unwrap();
+        assert!(n < src.len());
+        let mut out = vec![0u8; src.len()];
+        let m = lz4::block::decompress_safe(&dst[..n], &mut out).unwrap();
+        assert_eq!(out[..m], src[..]);
+    }
+}
+
+#[test]
+fn seeded_limited_output_reports_too_small() {
+    let src: Vec<u8> = (0..4096u32).map(|i| (i * 31 % 256) as u8).collect();
+    let mut dst = vec![0u8; 16];
+    assert_eq!(
+        compress_fast_seeded(&src, &mut dst, 1, 42),
+        Err(Lz4Error::OutputTooSmall)
+    );
+}
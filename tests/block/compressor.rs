@@ -0,0 +1,196 @@
+// Tests for block::compressor — Compressor
+//
+// Covers:
+//   - Default, acceleration and hash-seed configurations match the free
+//     functions byte for byte
+//   - fill_dest matches compress_dest_size
+//   - Dictionary mode matches a freshly loaded Lz4Stream and round-trips
+//   - max_distance bounds every match offset in the output
+//   - OutputTooSmall without fill_dest; empty input
+
+use lz4::block::decompress_core::decompress_safe_using_dict;
+use lz4::block::{
+    compress_bound, compress_default, compress_dest_size, compress_fast, compress_fast_seeded,
+    decompress_safe, Compressor, Lz4Error, Lz4Stream,
+};
+
+fn sample(n: usize) -> Vec<u8> {
+    (0..n)
+        .map(|i| b"the quick brown fox jumps over the lazy dog "[i % 44] ^ ((i / 1500) % 7) as u8)
+        .collect()
+}
+
+fn bound(n: usize) -> usize {
+    compress_bound(n as i32) as usize
+}
+
+/// Largest match offset used by a raw block.
+fn max_offset(block: &[u8]) -> usize {
+    let (mut i, mut max) = (0, 0);
+    while i < block.len() {
+        let token = block[i];
+        i += 1;
+        let mut lit = (token >> 4) as usize;
+        if lit == 15 {
+            while block[i] == 255 {
+                lit += 255;
+                i += 1;
+            }
+            lit += block[i] as usize;
+            i += 1;
+        }
+        i += lit;
+        if i >= block.len() {
+            break;
+        }
+        max = max.max(u16::from_le_bytes([block[i], block[i + 1]]) as usize);
+        i += 2;
+        if token & 15 == 15 {
+            while block[i] == 255 {
+                i += 1;
+            }
+            i += 1;
+        }
+    }
+    max
+}
+
+#[test]
+fn matches_free_functions() {
+    for len in [0, 100, 60_000, 300_000] {
+        let src = sample(len);
+        let mut a = vec![0u8; bound(len)];
+        let mut b = vec![0u8; bound(len)];
+
+        let n = compress_default(&src, &mut a).unwrap();
+        let (c, m) = Compressor::new().compress(&src, &mut b).unwrap();
+        assert_eq!((c, &b[..m]), (len, &a[..n]), "default, len {len}");
+
+        let n = compress_fast(&src, &mut a, 8).unwrap();
+        let (_, m) = Compressor::new()
+            .acceleration(8)
+            .compress(&src, &mut b)
+            .unwrap();
+        assert_eq!(&b[..m], &a[..n], "acceleration, len {len}");
+
+        let n = compress_fast_seeded(&src, &mut a, 1, 0xfeed).unwrap();
+        let (_, m) = Compressor::new()
+            .hash_seed(0xfeed)
+            .compress(&src, &mut b)
+            .unwrap();
+        assert_eq!(&b[..m], &a[..n], "seeded, len {len}");
+    }
+
+    // Out-of-range acceleration is clamped like compress_fast.
+    let src = sample(10_000);
+    let mut a = vec![0u8; bound(src.len())];
+    let mut b = vec![0u8; bound(src.len())];
+    let n = Compressor::new().compress(&src, &mut a).unwrap().1;
+    let m = Compressor::new()
+        .acceleration(-5)
+        .compress(&src, &mut b)
+        .unwrap()
+        .1;
+    assert_eq!(&a[..n], &b[..m]);
+}
+
+#[test]
+fn fill_dest_matches_compress_dest_size() {
+    let src = sample(200_000);
+    for cap in [1, 16, 1000, 40_000] {
+        let mut a = vec![0u8; cap];
+        let mut b = vec![0u8; cap];
+        let expected = compress_dest_size(&src, &mut a).unwrap();
+        let got = Compressor::new()
+            .fill_dest(true)
+            .compress(&src, &mut b)
+            .unwrap();
+        assert_eq!(got, expected, "cap {cap}");
+        assert_eq!(a, b);
+
+        let mut out = vec![0u8; got.0];
+        assert_eq!(decompress_safe(&b[..got.1], &mut out).unwrap(), got.0);
+        assert_eq!(out, &src[..got.0]);
+    }
+}
+
+#[test]
+fn dictionary_matches_stream_and_round_trips() {
+    let dict = sample(50_000);
+    let src = sample(20_000);
+    let mut dst = vec![0u8; bound(src.len())];
+    let (_, n) = Compressor::new()
+        .dictionary(&dict)
+        .compress(&src, &mut dst)
+        .unwrap();
+
+    let mut stream = Lz4Stream::new();
+    stream.load_dict(&dict);
+    let mut expected = vec![0u8; bound(src.len())];
+    let m = stream.compress_fast_continue(&src, &mut expected, 1) as usize;
+    assert_eq!(&dst[..n], &expected[..m]);
+
+    let plain = Compressor::new().compress(&src, &mut expected).unwrap().1;
+    assert!(n < plain, "dictionary should help: {n} vs {plain}");
+
+    let mut out = vec![0u8; src.len()];
+    decompress_safe_using_dict(&dst[..n], &mut out, &dict).unwrap();
+    assert_eq!(out, src);
+
+    // Dictionary and fill_dest combine.
+    let mut small = vec![0u8; 100];
+    let (c, w) = Compressor::new()
+        .dictionary(&dict)
+        .fill_dest(true)
+        .compress(&src, &mut small)
+        .unwrap();
+    assert!(c > 0 && c < src.len() && w <= 100, "{c} {w}");
+    let mut out = vec![0u8; c];
+    decompress_safe_using_dict(&small[..w], &mut out, &dict).unwrap();
+    assert_eq!(out, &src[..c]);
+}
+
+#[test]
+fn max_distance_bounds_offsets() {
+    // Repeats every 10 000 bytes, so only long offsets find matches.
+    let unit: Vec<u8> = (0..10_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+        .collect();
+    let src: Vec<u8> = unit.iter().cycle().take(200_000).copied().collect();
+    let mut dst = vec![0u8; bound(src.len())];
+
+    let (_, full) = Compressor::new().compress(&src, &mut dst).unwrap();
+    assert!(max_offset(&dst[..full]) > 4096);
+
+    for limit in [4096, 16_384, 65_535] {
+        let (_, n) = Compressor::new()
+            .max_distance(limit)
+            .compress(&src, &mut dst)
+            .unwrap();
+        assert!(max_offset(&dst[..n]) <= limit as usize, "limit {limit}");
+        let mut out = vec![0u8; src.len()];
+        decompress_safe(&dst[..n], &mut out).unwrap();
+        assert_eq!(out, src);
+        if limit == 4096 {
+            assert!(n > full, "a 4 KB window cannot reach the repeats");
+        }
+    }
+}
+
+#[test]
+fn errors_and_empty_input() {
+    let src = sample(5000);
+    let mut tiny = vec![0u8; 10];
+    assert_eq!(
+        Compressor::new().compress(&src, &mut tiny),
+        Err(Lz4Error::OutputTooSmall)
+    );
+    assert_eq!(
+        Compressor::new().compress(&src, &mut []),
+        Err(Lz4Error::OutputTooSmall)
+    );
+
+    let mut one = [0xAAu8; 1];
+    assert_eq!(Compressor::new().compress(&[], &mut one), Ok((0, 1)));
+    assert_eq!(one, [0]);
+}
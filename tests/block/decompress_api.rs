@@ -5,6 +5,7 @@
 //   - BlockDecompressError re-export
 //   - Lz4StreamDecode: new(), default(), struct fields initialisation
 //   - decompress_safe: basic, edge cases, variable-length literals, error paths
+//   - decompress_safe_uninit: MaybeUninit output, parity with decompress_safe
 //   - decompress_safe_partial: partial decode, clamping, error paths
 //   - set_stream_decode: dictionary configuration and context reset
 //   - decoder_ring_buffer_size: valid inputs, minimum block size, over-limit
@@ -12,6 +13,7 @@
 //   - decompress_safe_partial_force_ext_dict: partial external-dict decode
 //   - decompress_safe_using_dict: no-dict fallback, adjacent prefix, ext-dict
 //   - decompress_safe_partial_using_dict: partial variants of the above
+//   - decompress_safe_using_split_dict: prefix-only, prefix + ext, bad prefix_len
 //   - decompress_safe_continue: first call, contiguous rolling, buffer-wrap paths
 //   - Round-trip tests through the API
 
@@ -20,9 +22,11 @@ use lz4::block::decompress_api::{
     decoder_ring_buffer_size, decompress_safe, decompress_safe_continue,
     decompress_safe_force_ext_dict, decompress_safe_partial,
     decompress_safe_partial_force_ext_dict, decompress_safe_partial_using_dict,
-    decompress_safe_using_dict, set_stream_decode, BlockDecompressError, Lz4StreamDecode,
-    LZ4_MAX_INPUT_SIZE,
+    decompress_safe_uninit, decompress_safe_using_dict, decompress_safe_using_split_dict,
+    set_stream_decode, BlockDecompressError, Lz4RingDecoder, Lz4StreamDecode, LZ4_MAX_INPUT_SIZE,
 };
+use lz4::block::stream::Lz4Stream;
+use std::mem::MaybeUninit;
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Minimal hand-crafted LZ4 blocks (all-literal sequences, no matches)
@@ -212,6 +216,49 @@ fn decompress_safe_api_round_trip_pattern() {
     assert_eq!(&dst[..n], input.as_slice());
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// decompress_safe_uninit
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn decompress_safe_uninit_matches_decompress_safe() {
+    // Long repetitive input exercises literal wildcopies, small-offset and
+    // large-offset match copies.
+    let input: Vec<u8> = b"abcabcabcd the quick brown fox, the quick brown fox! "
+        .iter()
+        .cycle()
+        .take(100_000)
+        .copied()
+        .collect();
+    let compressed = compress_input(&input);
+
+    let mut out: Vec<u8> = Vec::with_capacity(input.len());
+    let n = decompress_safe_uninit(&compressed, &mut out.spare_capacity_mut()[..input.len()])
+        .expect("uninit decode failed");
+    // SAFETY: decompress_safe_uninit initialised the first n bytes.
+    unsafe { out.set_len(n) };
+    assert_eq!(out, input);
+}
+
+#[test]
+fn decompress_safe_uninit_too_small_is_error() {
+    let compressed = compress_input(&[7u8; 1000]);
+    let mut out = [MaybeUninit::<u8>::uninit(); 999];
+    assert_eq!(
+        decompress_safe_uninit(&compressed, &mut out),
+        Err(BlockDecompressError::MalformedInput)
+    );
+}
+
+#[test]
+fn decompress_safe_uninit_garbage_is_error() {
+    let mut out = [MaybeUninit::<u8>::uninit(); 64];
+    assert_eq!(
+        decompress_safe_uninit(&[0xFFu8; 8], &mut out),
+        Err(BlockDecompressError::MalformedInput)
+    );
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // decompress_safe_partial
 // ─────────────────────────────────────────────────────────────────────────────
@@ -971,3 +1018,168 @@ fn decompress_safe_continue_large_prefix_triggers_prefix64k() {
     .expect("extra block via prefix64k failed");
     assert_eq!(&buf[n_large..n_large + n_extra], extra_payload.as_ref());
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// decompress_safe_using_split_dict
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Compress `src` with `dict` loaded into a fresh stream.
+fn compress_with_dict(dict: &[u8], src: &[u8]) -> Vec<u8> {
+    let mut stream = Lz4Stream::new();
+    stream.load_dict(dict);
+    let mut dst = vec![0u8; compress_bound(src.len() as i32) as usize];
+    let n = stream.compress_fast_continue(src, &mut dst, 1);
+    assert!(n > 0);
+    dst.truncate(n as usize);
+    dst
+}
+
+#[test]
+fn decompress_safe_using_split_dict_prefix_and_ext() {
+    // The input repeats material from both the older and the newer segment,
+    // so matches must resolve into each of them.
+    let ext: Vec<u8> = b"older history: the quick brown fox ".repeat(20);
+    let prefix: Vec<u8> = b"recent history: jumps over the lazy dog ".repeat(20);
+    let mut input = prefix[..200].to_vec();
+    input.extend_from_slice(&ext[..300]);
+    input.extend_from_slice(&prefix[100..400]);
+
+    let dict = [ext.as_slice(), prefix.as_slice()].concat();
+    let compressed = compress_with_dict(&dict, &input);
+    assert!(compressed.len() < input.len());
+
+    let mut window = prefix.clone();
+    window.resize(prefix.len() + input.len(), 0);
+    let n = decompress_safe_using_split_dict(&compressed, &mut window, prefix.len(), &ext)
+        .expect("split dict decode failed");
+    assert_eq!(n, input.len());
+    assert_eq!(&window[prefix.len()..], input.as_slice());
+    assert_eq!(&window[..prefix.len()], prefix.as_slice());
+}
+
+#[test]
+fn decompress_safe_using_split_dict_prefix_only() {
+    let prefix: Vec<u8> = b"abcdefgh".repeat(16);
+    let input = prefix[..64].to_vec();
+    let compressed = compress_with_dict(&prefix, &input);
+
+    let mut window = prefix.clone();
+    window.resize(prefix.len() + input.len(), 0);
+    let n = decompress_safe_using_split_dict(&compressed, &mut window, prefix.len(), &[])
+        .expect("prefix-only decode failed");
+    assert_eq!(&window[prefix.len()..prefix.len() + n], input.as_slice());
+}
+
+#[test]
+fn decompress_safe_using_split_dict_no_dict_matches_decompress_safe() {
+    let mut window = [0u8; 5];
+    let n = decompress_safe_using_split_dict(BLOCK_HELLO, &mut window, 0, &[]).unwrap();
+    assert_eq!(&window[..n], b"Hello");
+}
+
+#[test]
+fn decompress_safe_using_split_dict_prefix_len_out_of_range_is_error() {
+    let mut window = [0u8; 4];
+    let result = decompress_safe_using_split_dict(BLOCK_A, &mut window, 5, b"dict");
+    assert_eq!(result, Err(BlockDecompressError::MalformedInput));
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Lz4RingDecoder
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// 600 KB built from a 48 KB noise pattern repeated with sparse edits, so
+/// most matches reach tens of kilobytes back into earlier blocks.
+fn long_range_input() -> Vec<u8> {
+    let mut x = 0x9E37_79B9_7F4A_7C15u64;
+    let pattern: Vec<u8> = (0..48 * 1024)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x as u8
+        })
+        .collect();
+    (0..600 * 1024)
+        .map(|i| {
+            let b = pattern[i % pattern.len()];
+            if i % 997 == 0 {
+                b ^ 0x5A
+            } else {
+                b
+            }
+        })
+        .collect()
+}
+
+/// Compress `input` as dependent blocks whose sizes cycle through `sizes`.
+fn compress_dependent_blocks(input: &[u8], sizes: &[usize]) -> Vec<Vec<u8>> {
+    let mut stream = Lz4Stream::new();
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+    for &size in sizes.iter().cycle() {
+        if pos >= input.len() {
+            break;
+        }
+        let end = (pos + size).min(input.len());
+        let mut dst = vec![0u8; compress_bound((end - pos) as i32) as usize];
+        let n = stream.compress_fast_continue(&input[pos..end], &mut dst, 1);
+        assert!(n > 0);
+        dst.truncate(n as usize);
+        blocks.push(dst);
+        pos = end;
+    }
+    blocks
+}
+
+#[test]
+fn ring_decoder_round_trips_across_wraparounds() {
+    let input = long_range_input();
+    let sizes = [8192, 1000, 4096, 8192, 123, 7777];
+    let blocks = compress_dependent_blocks(&input, &sizes);
+    // Dependent blocks must actually be smaller than the data they encode.
+    assert!(blocks.iter().map(Vec::len).sum::<usize>() < input.len() / 2);
+
+    let mut dec = Lz4RingDecoder::new(8192).unwrap();
+    assert_eq!(dec.capacity(), decoder_ring_buffer_size(8192).unwrap());
+    let mut out = Vec::with_capacity(input.len());
+    for block in &blocks {
+        out.extend_from_slice(dec.decode_next(block).unwrap());
+    }
+    assert!(out.len() > 5 * dec.capacity(), "ring never wrapped");
+    assert_eq!(out, input);
+}
+
+#[test]
+fn ring_decoder_small_blocks_and_reset() {
+    let input = long_range_input();
+    let input = &input[..200 * 1024];
+    let blocks = compress_dependent_blocks(input, &[16, 300, 64]);
+
+    let mut dec = Lz4RingDecoder::new(300).unwrap();
+    for _ in 0..2 {
+        let mut out = Vec::with_capacity(input.len());
+        for block in &blocks {
+            out.extend_from_slice(dec.decode_next(block).unwrap());
+        }
+        assert_eq!(out, input);
+        dec.reset();
+    }
+}
+
+#[test]
+fn ring_decoder_rejects_oversized_block() {
+    let src = vec![b'z'; 5000];
+    let compressed = compress_input(&src);
+    let mut dec = Lz4RingDecoder::new(4096).unwrap();
+    assert_eq!(dec.max_block_size(), 4096);
+    assert_eq!(
+        dec.decode_next(&compressed),
+        Err(BlockDecompressError::MalformedInput)
+    );
+}
+
+#[test]
+fn ring_decoder_new_rejects_block_size_above_limit() {
+    assert!(Lz4RingDecoder::new(LZ4_MAX_INPUT_SIZE + 1).is_none());
+}
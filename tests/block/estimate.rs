@@ -0,0 +1,47 @@
+// Tests for block::estimate — compression-ratio estimation without output
+//
+// The estimate should track the real fast encoder closely across data
+// shapes: highly repetitive, text-like, mixed, and incompressible.
+
+use lz4::block::{compress_block_to_vec, estimate_block_size, estimate_compressibility};
+use lz4::lorem::gen_buffer;
+
+fn noise(len: usize) -> Vec<u8> {
+    let mut x = 0x9E37_79B9_7F4A_7C15u64;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            (x >> 32) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn estimate_tracks_real_output() {
+    let mut mixed = gen_buffer(100_000, 2);
+    mixed.extend(noise(100_000));
+    let corpora = [
+        gen_buffer(500_000, 1),
+        b"0123456789".repeat(20_000),
+        mixed,
+        noise(200_000),
+    ];
+    for (i, data) in corpora.iter().enumerate() {
+        let real = compress_block_to_vec(data).len() as f64;
+        let est = estimate_block_size(data) as f64;
+        assert!(
+            (est - real).abs() / real < 0.10,
+            "corpus {i}: estimate {est} vs real {real}"
+        );
+    }
+}
+
+#[test]
+fn compressibility_orders_inputs() {
+    let text = estimate_compressibility(&gen_buffer(200_000, 4));
+    let random = estimate_compressibility(&noise(200_000));
+    assert!(text > 1.5, "text {text}");
+    assert!(random < 1.0, "random {random}");
+}
@@ -0,0 +1,145 @@
+// Tests for block::policy — DecodePolicy enforcement
+//
+//   - the default policy decodes exactly like decompress_safe
+//   - each limit rejects blocks that exceed it, accepts blocks at the limit
+//   - violations leave dst untouched and are distinct from malformed input
+
+use lz4::block::{
+    check_policy, compress_bound, compress_default, decompress_safe, decompress_safe_with_policy,
+    DecodePolicy, PolicyError, PolicyViolation,
+};
+use lz4::lorem::gen_buffer;
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut c = vec![0u8; compress_bound(data.len() as i32) as usize];
+    let n = compress_default(data, &mut c).unwrap();
+    c.truncate(n);
+    c
+}
+
+/// Eight literals with a 4-byte match at `offset`, then eight literals.
+fn block_with_offset(offset: u16) -> Vec<u8> {
+    let mut b = vec![0x80];
+    b.extend_from_slice(b"abcdefgh");
+    b.extend_from_slice(&offset.to_le_bytes());
+    b.push(0x80);
+    b.extend_from_slice(b"the tail");
+    b
+}
+
+#[test]
+fn default_policy_matches_decompress_safe() {
+    for data in [gen_buffer(100_000, 3), vec![7u8; 50_000], b"short".to_vec()] {
+        let c = compress(&data);
+        let mut a = vec![0u8; data.len()];
+        let mut b = vec![0u8; data.len()];
+        assert_eq!(
+            decompress_safe_with_policy(&c, &mut a, &DecodePolicy::default()),
+            decompress_safe(&c, &mut b).map_err(|_| PolicyError::Malformed)
+        );
+        assert_eq!(a, data);
+    }
+}
+
+#[test]
+fn max_offset_rejects_far_matches() {
+    let src = block_with_offset(8);
+    let mut dst = [0u8; 20];
+    let at_limit = DecodePolicy {
+        max_offset: 8,
+        ..DecodePolicy::default()
+    };
+    assert_eq!(
+        decompress_safe_with_policy(&src, &mut dst, &at_limit),
+        Ok(20)
+    );
+    assert_eq!(&dst, b"abcdefghabcdthe tail");
+
+    let tight = DecodePolicy {
+        max_offset: 7,
+        ..DecodePolicy::default()
+    };
+    let mut dst = [0u8; 20];
+    assert_eq!(
+        decompress_safe_with_policy(&src, &mut dst, &tight),
+        Err(PolicyError::Violation(PolicyViolation::Offset {
+            sequence: 0,
+            offset: 8
+        }))
+    );
+    assert_eq!(dst, [0u8; 20], "violation must not write output");
+}
+
+#[test]
+fn max_literal_run_covers_final_sequence() {
+    // A single literals-only sequence of 1000 bytes: 15 + 255 * 3 + 220.
+    let mut c = vec![0xF0, 255, 255, 255, 220];
+    c.extend(std::iter::repeat_n(b'x', 1000));
+    let policy = DecodePolicy {
+        max_literal_run: 999,
+        ..DecodePolicy::default()
+    };
+    assert_eq!(
+        check_policy(&c, &policy),
+        Err(PolicyError::Violation(PolicyViolation::LiteralRun {
+            sequence: 0,
+            len: 1000
+        }))
+    );
+    let policy = DecodePolicy {
+        max_literal_run: 1000,
+        ..DecodePolicy::default()
+    };
+    assert_eq!(check_policy(&c, &policy), Ok(()));
+}
+
+#[test]
+fn max_sequences_bounds_token_count() {
+    // Two sequences: the match and the final literal run.
+    let src = block_with_offset(4);
+    let two = DecodePolicy {
+        max_sequences: 2,
+        ..DecodePolicy::default()
+    };
+    assert_eq!(check_policy(&src, &two), Ok(()));
+    let one = DecodePolicy {
+        max_sequences: 1,
+        ..DecodePolicy::default()
+    };
+    assert_eq!(
+        check_policy(&src, &one),
+        Err(PolicyError::Violation(PolicyViolation::TooManySequences {
+            limit: 1
+        }))
+    );
+}
+
+#[test]
+fn malformed_input_is_not_a_violation() {
+    let strict = DecodePolicy {
+        max_offset: 16,
+        max_literal_run: 16,
+        max_sequences: 4,
+    };
+    let mut dst = [0u8; 64];
+    // Truncated literal run.
+    assert_eq!(
+        decompress_safe_with_policy(&[0x80, b'a'], &mut dst, &strict),
+        Err(PolicyError::Malformed)
+    );
+    // Zero offset.
+    assert_eq!(
+        decompress_safe_with_policy(&block_with_offset(0), &mut dst, &strict),
+        Err(PolicyError::Malformed)
+    );
+    // Offset within policy but before the start of the output.
+    assert_eq!(
+        decompress_safe_with_policy(&block_with_offset(12), &mut dst, &strict),
+        Err(PolicyError::Malformed)
+    );
+    // Valid block, output buffer too small.
+    assert_eq!(
+        decompress_safe_with_policy(&block_with_offset(8), &mut dst[..10], &strict),
+        Err(PolicyError::Malformed)
+    );
+}
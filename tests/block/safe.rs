@@ -0,0 +1,171 @@
+// Tests for block::safe — the unsafe-free block codec behind `forbid-unsafe`
+//
+// Cross-checks the safe codec against the pointer-based port in both
+// directions:
+//   - safe compress   → native decompress_safe
+//   - native compress → safe decompress_safe
+//   - partial decoding and dictionary decoding parity
+//   - acceleration, incompressible data, and malformed input
+//   - decompressed_size: exact sizes, dictionary blocks, malformed input
+
+use lz4::block::compress::{compress_bound, compress_default, compress_fast};
+use lz4::block::decompress_api::{decompress_safe, decompress_safe_partial};
+use lz4::block::safe;
+use lz4::block::Lz4Stream;
+use lz4::lorem::gen_buffer;
+
+fn corpora() -> Vec<Vec<u8>> {
+    let noise: Vec<u8> = (0..70_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 11) as u8)
+        .collect();
+    vec![
+        Vec::new(),
+        b"x".to_vec(),
+        b"twelve bytes".to_vec(),
+        vec![0u8; 100_000],
+        gen_buffer(200_000, 7),
+        b"abcd".repeat(40_000),
+        noise,
+    ]
+}
+
+#[test]
+fn safe_compress_native_decompress() {
+    for data in corpora() {
+        for accel in [1, 4, 100] {
+            let mut c = vec![0u8; compress_bound(data.len() as i32) as usize];
+            let n = safe::compress_fast(&data, &mut c, accel).unwrap();
+            let mut d = vec![0u8; data.len()];
+            assert_eq!(decompress_safe(&c[..n], &mut d), Ok(data.len()));
+            assert_eq!(d, data, "len {} accel {}", data.len(), accel);
+        }
+    }
+}
+
+#[test]
+fn native_compress_safe_decompress() {
+    for data in corpora() {
+        let mut c = vec![0u8; compress_bound(data.len() as i32) as usize];
+        let n = compress_fast(&data, &mut c, 1).unwrap();
+        let mut d = vec![0u8; data.len()];
+        assert_eq!(safe::decompress_safe(&c[..n], &mut d), Ok(data.len()));
+        assert_eq!(d, data);
+    }
+}
+
+#[test]
+fn ratio_close_to_native() {
+    let data = gen_buffer(1 << 20, 3);
+    let mut c = vec![0u8; compress_bound(data.len() as i32) as usize];
+    let native = compress_default(&data, &mut c).unwrap();
+    let safe_len = safe::compress_default(&data, &mut c).unwrap();
+    // Same greedy parser, but the hash table and match-search heuristics
+    // differ from the native encoder; allow 10%.
+    assert!(
+        safe_len <= native + native / 10,
+        "safe {} vs native {}",
+        safe_len,
+        native
+    );
+}
+
+#[test]
+fn partial_matches_native() {
+    let data = gen_buffer(50_000, 11);
+    let mut c = vec![0u8; compress_bound(data.len() as i32) as usize];
+    let n = compress_default(&data, &mut c).unwrap();
+    for target in [0, 1, 17, 1000, 49_999] {
+        let mut a = vec![0u8; data.len()];
+        let mut b = vec![0u8; data.len()];
+        let got_native = decompress_safe_partial(&c[..n], &mut a, target).unwrap();
+        let got_safe = safe::decompress_safe_partial(&c[..n], &mut b, target).unwrap();
+        assert!(got_safe >= target.min(data.len()), "target {target}");
+        let common = got_native.min(got_safe);
+        assert_eq!(&b[..got_safe], &data[..got_safe]);
+        assert_eq!(&a[..common], &b[..common]);
+    }
+}
+
+#[test]
+fn decompress_with_dict() {
+    let dict = gen_buffer(32 * 1024, 5);
+    let mut data = dict[1000..9000].to_vec();
+    data.extend_from_slice(b"fresh tail that is not in the dictionary at all");
+
+    let mut stream = Lz4Stream::new();
+    stream.load_dict(&dict);
+    let mut c = vec![0u8; compress_bound(data.len() as i32) as usize];
+    let n = stream.compress_fast_continue(&data, &mut c, 1);
+    assert!(n > 0);
+    let n = n as usize;
+
+    let mut d = vec![0u8; data.len()];
+    assert_eq!(
+        safe::decompress_safe_with_dict(&c[..n], &mut d, &dict),
+        Ok(data.len())
+    );
+    assert_eq!(d, data);
+    // Without the dictionary the back-references are out of range.
+    assert!(safe::decompress_safe(&c[..n], &mut d).is_err());
+}
+
+#[test]
+fn malformed_inputs_rejected() {
+    let mut dst = vec![0u8; 64];
+    // Empty input.
+    assert!(safe::decompress_safe(&[], &mut dst).is_err());
+    // Literal run longer than the input.
+    assert!(safe::decompress_safe(&[0x50, b'a'], &mut dst).is_err());
+    // Offset reaching before the start of the output.
+    assert!(safe::decompress_safe(&[0x10, b'a', 0x05, 0x00, 0x00], &mut dst).is_err());
+    // Output too small for the literals.
+    assert!(safe::decompress_safe(&[0x50, 1, 2, 3, 4, 5], &mut dst[..3]).is_err());
+}
+
+#[test]
+fn decompressed_size_is_exact() {
+    for data in corpora() {
+        let mut c = vec![0u8; compress_bound(data.len() as i32) as usize];
+        let n = compress_default(&data, &mut c).unwrap();
+        assert_eq!(lz4::block::decompressed_size(&c[..n]), Ok(data.len()));
+        let n = safe::compress_default(&data, &mut c).unwrap();
+        assert_eq!(safe::decompressed_size(&c[..n]), Ok(data.len()));
+    }
+
+    // Offsets into a dictionary do not affect the size.
+    let dict = gen_buffer(32 * 1024, 5);
+    let data = dict[4000..20_000].to_vec();
+    let mut stream = Lz4Stream::new();
+    stream.load_dict(&dict);
+    let mut c = vec![0u8; compress_bound(data.len() as i32) as usize];
+    let n = stream.compress_fast_continue(&data, &mut c, 1) as usize;
+    assert!(n > 0 && n < data.len() / 10);
+    assert_eq!(safe::decompressed_size(&c[..n]), Ok(data.len()));
+}
+
+#[test]
+fn decompressed_size_rejects_malformed_blocks() {
+    let data = gen_buffer(10_000, 9);
+    let mut c = vec![0u8; compress_bound(data.len() as i32) as usize];
+    let n = compress_default(&data, &mut c).unwrap();
+    // Every truncation either fails or describes a shorter block; none can
+    // claim the full size.
+    for cut in 0..n {
+        assert_ne!(
+            safe::decompressed_size(&c[..cut]),
+            Ok(data.len()),
+            "cut {cut}"
+        );
+    }
+    assert!(safe::decompressed_size(&[]).is_err());
+    // Literal run longer than the input.
+    assert!(safe::decompressed_size(&[0x50, b'a']).is_err());
+    // Zero offset.
+    assert!(safe::decompressed_size(&[0x10, b'a', 0x00, 0x00, 0x00]).is_err());
+    // Block ending on a match.
+    assert!(safe::decompressed_size(&[0x10, b'a', 0x01, 0x00]).is_err());
+    // Unterminated length extension.
+    assert!(safe::decompressed_size(&[0xF0, 255, 255]).is_err());
+    // A lone empty literal run is the empty block.
+    assert_eq!(safe::decompressed_size(&[0x00]), Ok(0));
+}
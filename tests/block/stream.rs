@@ -8,11 +8,15 @@
 //     too-small dict returns 0
 //   - Lz4Stream::attach_dictionary() — None detaches; empty dict detaches;
 //     non-empty dict attaches; bumps current_offset from zero
+//   - stream::attach_dictionary() — one dict stream shared by many streams
 //   - Lz4Stream::renorm_dict() — only triggers above 0x80000000 boundary
 //   - Lz4Stream::save_dict() — copies last ≤64 KB, updates history pointer
 //   - Lz4Stream::compress_fast_continue() — basic round-trip, prefix mode,
 //     multi-block streaming, output-too-small returns 0
 //   - Lz4Stream::compress_force_ext_dict() — ext-dict path smoke-test
+//   - Lz4Stream::set_max_distance() — range check, survives reset, bounds
+//     emitted offsets; windowed decoding rejects blocks that exceed it
+//   - Lz4Stream::export() / import() — mid-stream session handoff
 //
 // Note: `Lz4Stream::internal` is `pub(crate)`, so integration tests cannot
 // access it.  All assertions are therefore through the public API (compression
@@ -333,6 +337,64 @@ fn attach_dictionary_bumps_current_offset_when_zero() {
     );
 }
 
+#[test]
+fn attach_dictionary_free_fn_shares_one_dict_across_streams() {
+    use lz4::block::decompress_core::decompress_safe_using_dict;
+    use lz4::block::stream::attach_dictionary;
+
+    let dict: Vec<u8> = b"{\"user\":\"alice\",\"action\":\"login\",\"status\":\"ok\"}".repeat(40);
+    let mut dict_stream = Lz4Stream::new();
+    dict_stream.load_dict(&dict);
+
+    let messages: [&[u8]; 3] = [
+        b"{\"user\":\"alice\",\"action\":\"logout\",\"status\":\"ok\"}",
+        b"{\"user\":\"bob\",\"action\":\"login\",\"status\":\"ok\"}",
+        b"{\"user\":\"carol\",\"action\":\"login\",\"status\":\"denied\"}",
+    ];
+    for msg in messages {
+        let mut plain = Lz4Stream::new();
+        let mut dst_plain = make_dst(msg.len());
+        let n_plain = plain.compress_fast_continue(msg, &mut dst_plain, 1);
+
+        let mut working = Lz4Stream::new();
+        unsafe { attach_dictionary(&mut working, Some(&dict_stream)) };
+        let mut dst = make_dst(msg.len());
+        let n = working.compress_fast_continue(msg, &mut dst, 1);
+        assert!(n > 0 && n < n_plain, "dict {n} vs plain {n_plain}");
+
+        let mut out = vec![0u8; msg.len()];
+        let d = decompress_safe_using_dict(&dst[..n as usize], &mut out, &dict).unwrap();
+        assert_eq!(&out[..d], msg);
+    }
+}
+
+#[test]
+fn attach_dictionary_free_fn_none_detaches() {
+    use lz4::block::stream::attach_dictionary;
+
+    let dict = vec![0x5Au8; 4096];
+    let mut dict_stream = Lz4Stream::new();
+    dict_stream.load_dict(&dict);
+    let src = vec![0x5Au8; 300];
+
+    let mut plain = Lz4Stream::new();
+    let mut expected = make_dst(src.len());
+    let n_expected = plain.compress_fast_continue(&src, &mut expected, 1);
+
+    let mut working = Lz4Stream::new();
+    unsafe {
+        attach_dictionary(&mut working, Some(&dict_stream));
+        attach_dictionary(&mut working, None);
+    }
+    let mut dst = make_dst(src.len());
+    let n = working.compress_fast_continue(&src, &mut dst, 1);
+    // Detached: no dictionary is referenced, so plain decoding works.
+    let mut out = vec![0u8; src.len()];
+    let d = lz4::block::decompress_core::decompress_safe(&dst[..n as usize], &mut out).unwrap();
+    assert_eq!(out[..d], src[..]);
+    assert_eq!(n, n_expected);
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // renorm_dict()
 // ─────────────────────────────────────────────────────────────────────────────
@@ -897,3 +959,169 @@ fn compress_fast_continue_small_incompressible_data() {
     let n = stream.compress_fast_continue(&src, &mut dst, 1);
     assert!(n > 0, "incompressible data must still produce output");
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// set_hash_seed
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn hash_seed_survives_reset_and_round_trips_with_dict() {
+    let dict: Vec<u8> = (0..32_768u32).map(|i| (i % 199) as u8).collect();
+    let src: Vec<u8> = (0..20_000u32).map(|i| ((i + 50) % 199) as u8).collect();
+
+    let mut stream = Lz4Stream::new();
+    assert_eq!(stream.hash_seed(), 0);
+    stream.set_hash_seed(0xC0FFEE);
+    stream.reset();
+    assert_eq!(stream.hash_seed(), 0xC0FFEE);
+    stream.load_dict(&dict);
+    assert_eq!(stream.hash_seed(), 0xC0FFEE);
+
+    let mut dst = make_dst(src.len());
+    let n = stream.compress_fast_continue(&src, &mut dst, 1);
+    assert!(n > 0 && (n as usize) < src.len() / 10);
+
+    let mut out = vec![0u8; src.len()];
+    let m = lz4::block::decompress_core::decompress_safe_using_dict(
+        &dst[..n as usize],
+        &mut out,
+        &dict,
+    )
+    .unwrap();
+    assert_eq!(out[..m], src[..]);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// set_max_distance
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Largest match offset in a raw block.
+fn max_offset(block: &[u8]) -> usize {
+    let (mut i, mut max) = (0, 0);
+    while i < block.len() {
+        let token = block[i];
+        i += 1;
+        let mut lit = (token >> 4) as usize;
+        if lit == 15 {
+            while block[i] == 255 {
+                lit += 255;
+                i += 1;
+            }
+            lit += block[i] as usize;
+            i += 1;
+        }
+        i += lit;
+        if i >= block.len() {
+            break;
+        }
+        max = max.max(u16::from_le_bytes([block[i], block[i + 1]]) as usize);
+        i += 2;
+        if token & 15 == 15 {
+            while block[i] == 255 {
+                i += 1;
+            }
+            i += 1;
+        }
+    }
+    max
+}
+
+#[test]
+fn set_max_distance_validates_and_survives_reset() {
+    use lz4::block::{Lz4Error, LZ4_DISTANCE_MAX};
+
+    let mut stream = Lz4Stream::new();
+    assert_eq!(stream.max_distance(), LZ4_DISTANCE_MAX);
+    assert_eq!(
+        stream.set_max_distance(0),
+        Err(Lz4Error::InvalidMaxDistance)
+    );
+    assert_eq!(
+        stream.set_max_distance(LZ4_DISTANCE_MAX + 1),
+        Err(Lz4Error::InvalidMaxDistance)
+    );
+    assert_eq!(stream.max_distance(), LZ4_DISTANCE_MAX);
+
+    stream.set_max_distance(4096).unwrap();
+    stream.reset();
+    stream.set_hash_seed(7);
+    stream.reset_fast();
+    assert_eq!(stream.max_distance(), 4096);
+}
+
+#[test]
+fn max_distance_bounds_offsets_and_windowed_decode_enforces_it() {
+    use lz4::block::decompress_core::decompress_safe_using_dict_windowed;
+
+    // A 3000-byte pseudo-random unit repeated: every match is 3000 back.
+    let mut seed = 1u32;
+    let unit: Vec<u8> = (0..3000)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as u8
+        })
+        .collect();
+    let src = unit.repeat(8);
+
+    let mut wide = Lz4Stream::new();
+    let mut wide_dst = make_dst(src.len());
+    let n = wide.compress_fast_continue(&src, &mut wide_dst, 1) as usize;
+    assert!(n > 0 && n < src.len() / 4);
+    let wide_block = &wide_dst[..n];
+    assert_eq!(max_offset(wide_block), 3000);
+
+    let mut narrow = Lz4Stream::new();
+    narrow.set_max_distance(2048).unwrap();
+    let mut narrow_dst = make_dst(src.len());
+    let m = narrow.compress_fast_continue(&src, &mut narrow_dst, 1) as usize;
+    assert!(m > 0);
+    let narrow_block = &narrow_dst[..m];
+    assert!(max_offset(narrow_block) <= 2048);
+
+    let mut out = vec![0u8; src.len()];
+    let k = decompress_safe_using_dict_windowed(narrow_block, &mut out, &[], 2048).unwrap();
+    assert_eq!(out[..k], src[..]);
+    assert!(decompress_safe_using_dict_windowed(wide_block, &mut out, &[], 2048).is_err());
+    let k = decompress_safe_using_dict_windowed(wide_block, &mut out, &[], 3000).unwrap();
+    assert_eq!(out[..k], src[..]);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// export / import
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A session handed over mid-stream keeps referencing the earlier blocks, and
+/// a keyed hash travels with it.
+#[test]
+fn export_import_hands_off_a_session() {
+    let text: Vec<u8> = (0..60_000u32)
+        .flat_map(|i| format!("{} ", (i * 31) % 997).into_bytes())
+        .collect();
+    let (first, rest) = text.split_at(40_000);
+    let second = &rest[..30_000];
+
+    let mut stream = Lz4Stream::new();
+    stream.set_hash_seed(42);
+    let mut block1 = make_dst(first.len());
+    let n1 = stream.compress_fast_continue(first, &mut block1, 1);
+    assert!(n1 > 0);
+    let snapshot = stream.export().expect("export");
+    drop(stream);
+
+    let mut history = vec![0u8; 64 * KB];
+    let mut resumed = Lz4Stream::new();
+    resumed.import(&snapshot, &mut history).expect("import");
+    assert_eq!(resumed.hash_seed(), 42);
+    let mut block2 = make_dst(second.len());
+    let n2 = resumed.compress_fast_continue(second, &mut block2, 1);
+    assert!(n2 > 0 && (n2 as usize) < second.len() / 2);
+
+    let mut out = vec![0u8; second.len()];
+    let m = lz4::block::decompress_core::decompress_safe_using_dict(
+        &block2[..n2 as usize],
+        &mut out,
+        first,
+    )
+    .unwrap();
+    assert_eq!(&out[..m], second);
+}
@@ -13,11 +13,12 @@
 //   - Hash-table put/get/clear round-trip correctly
 
 use lz4::block::types::{
-    clear_hash, count, get_index_on_hash, get_position_on_hash, hash4, hash5, hash_position,
-    memcpy_using_offset, nb_common_bytes, prepare_table, put_index_on_hash, put_position_on_hash,
-    read16, read32, read_arch, read_le16, read_le32, wild_copy32, wild_copy8, write16, write32,
-    write_le16, DictDirective, DictIssueDirective, LimitedOutputDirective, StreamStateInternal,
-    TableType, DEC64TABLE, FASTLOOP_SAFE_DISTANCE, GB, INC32TABLE, KB, LASTLITERALS, LZ4_64KLIMIT,
+    clear_hash, count, get_index_on_hash, get_position_on_hash, hash4, hash4_keyed, hash5,
+    hash5_keyed, hash_position, memcpy_using_offset, nb_common_bytes, prepare_table,
+    put_index_on_hash, put_position_on_hash, read16, read32, read_arch, read_le16, read_le32,
+    wild_copy32, wild_copy8, write16, write32, write_le16, DictDirective, DictIssueDirective,
+    HashKey, LimitedOutputDirective, StreamStateInternal, TableType, DEC64TABLE,
+    FASTLOOP_SAFE_DISTANCE, GB, INC32TABLE, KB, LASTLITERALS, LZ4_64KLIMIT,
     LZ4_DISTANCE_ABSOLUTE_MAX, LZ4_DISTANCE_MAX, LZ4_HASHLOG, LZ4_HASHTABLESIZE, LZ4_HASH_SIZE_U32,
     LZ4_MEMORY_USAGE, LZ4_MIN_LENGTH, LZ4_SKIP_TRIGGER, MATCH_SAFEGUARD_DISTANCE, MB, MFLIMIT,
     MINMATCH, ML_BITS, ML_MASK, RUN_BITS, RUN_MASK, WILDCOPYLENGTH,
@@ -357,9 +358,9 @@ fn memcpy_using_offset_offset2_replicates_pattern() {
         let dst_end = dst_ptr.add(copy_len);
         memcpy_using_offset(dst_ptr, src_ptr, dst_end, 2);
     }
-    for i in 0..copy_len {
+    for (i, &b) in dst_buf[..copy_len].iter().enumerate() {
         let expected = if i % 2 == 0 { 0x11u8 } else { 0x22u8 };
-        assert_eq!(dst_buf[i], expected, "offset=2 at index {i}");
+        assert_eq!(b, expected, "offset=2 at index {i}");
     }
 }
 
@@ -587,13 +588,8 @@ fn put_and_get_position_byptr() {
     let mut table = [core::ptr::null::<u8>(); LZ4_HASH_SIZE_U32];
     let h = 5u32;
     unsafe {
-        put_position_on_hash(
-            ptr,
-            h,
-            table.as_mut_ptr() as *mut *const u8,
-            TableType::ByPtr,
-        );
-        let got = get_position_on_hash(h, table.as_ptr() as *const *const u8, TableType::ByPtr);
+        put_position_on_hash(ptr, h, table.as_mut_ptr(), TableType::ByPtr);
+        let got = get_position_on_hash(h, table.as_ptr(), TableType::ByPtr);
         assert_eq!(got, ptr);
     }
 }
@@ -713,8 +709,8 @@ fn memcpy_using_offset_offset_3() {
 #[test]
 fn memcpy_using_offset_offset_5() {
     let mut buf = vec![0u8; 256];
-    for i in 0..5 {
-        buf[i] = (i as u8 + 1) * 10;
+    for (i, b) in buf[..5].iter_mut().enumerate() {
+        *b = (i as u8 + 1) * 10;
     }
     let dst_start = 5usize;
     let copy_len = 40usize;
@@ -733,8 +729,8 @@ fn memcpy_using_offset_offset_5() {
 #[test]
 fn memcpy_using_offset_offset_6() {
     let mut buf = vec![0u8; 256];
-    for i in 0..6 {
-        buf[i] = (i as u8 + 1) * 11;
+    for (i, b) in buf[..6].iter_mut().enumerate() {
+        *b = (i as u8 + 1) * 11;
     }
     let dst_start = 6usize;
     let copy_len = 48usize;
@@ -753,8 +749,8 @@ fn memcpy_using_offset_offset_6() {
 #[test]
 fn memcpy_using_offset_offset_7() {
     let mut buf = vec![0u8; 256];
-    for i in 0..7 {
-        buf[i] = (i as u8 + 1) * 13;
+    for (i, b) in buf[..7].iter_mut().enumerate() {
+        *b = (i as u8 + 1) * 13;
     }
     let dst_start = 7usize;
     let copy_len = 56usize;
@@ -774,8 +770,8 @@ fn memcpy_using_offset_offset_7() {
 fn memcpy_using_offset_offset_8_fast_path() {
     // offset >= 8 uses the simple non-overlapping copy path
     let mut buf = vec![0u8; 256];
-    for i in 0..8 {
-        buf[i] = (i as u8 + 1) * 17;
+    for (i, b) in buf[..8].iter_mut().enumerate() {
+        *b = (i as u8 + 1) * 17;
     }
     let dst_start = 8usize;
     let copy_len = 32usize;
@@ -794,8 +790,8 @@ fn memcpy_using_offset_offset_8_fast_path() {
 #[test]
 fn memcpy_using_offset_offset_16_large() {
     let mut buf = vec![0u8; 512];
-    for i in 0..16 {
-        buf[i] = (i as u8) * 7;
+    for (i, b) in buf[..16].iter_mut().enumerate() {
+        *b = (i as u8) * 7;
     }
     let dst_start = 16usize;
     let copy_len = 128usize;
@@ -895,3 +891,53 @@ fn count_10_matching_exercises_word_plus_u16_tail() {
     let n = unsafe { count(a.as_ptr(), b.as_ptr(), a.as_ptr().add(16)) };
     assert_eq!(n, 10);
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Keyed hashes (HashKey)
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn hash_key_zero_seed_is_reference_hash() {
+    assert_eq!(HashKey::from_seed(0), HashKey::DEFAULT);
+    assert_eq!(HashKey::default(), HashKey::DEFAULT);
+    for seq in [0u64, 1, 0xDEAD_BEEF, 0x12_3456_789A, u64::MAX] {
+        assert_eq!(
+            hash5_keyed(seq, TableType::ByU32, HashKey::DEFAULT),
+            hash5(seq, TableType::ByU32)
+        );
+        assert_eq!(
+            hash4_keyed(seq as u32, TableType::ByU16, HashKey::DEFAULT),
+            hash4(seq as u32, TableType::ByU16)
+        );
+    }
+}
+
+#[test]
+fn hash_key_multipliers_are_odd_and_seed_dependent() {
+    let a = HashKey::from_seed(1);
+    let b = HashKey::from_seed(2);
+    assert_ne!(a, b);
+    assert_ne!(a, HashKey::DEFAULT);
+    for key in [a, b, HashKey::from_seed(u64::MAX)] {
+        assert_eq!(key.prime4 & 1, 1);
+        assert_eq!(key.prime5 & 1, 1);
+    }
+}
+
+#[test]
+fn keyed_hash_spreads_reference_collisions() {
+    // Collect 5-byte sequences that all land in slot 0 of the reference hash,
+    // as an attacker would, and check a keyed hash scatters them.
+    let colliding: Vec<u64> = (1u64..)
+        .filter(|&seq| hash5(seq, TableType::ByU32) == 0)
+        .take(256)
+        .collect();
+    let key = HashKey::from_seed(0x5EED);
+    let mut slots: Vec<u32> = colliding
+        .iter()
+        .map(|&seq| hash5_keyed(seq, TableType::ByU32, key))
+        .collect();
+    slots.sort_unstable();
+    slots.dedup();
+    assert!(slots.len() > 200, "only {} distinct slots", slots.len());
+}
@@ -1,3 +1,5 @@
+#![cfg(all(feature = "cli", not(feature = "forbid-unsafe")))]
+
 #[path = "cli/arg_utils.rs"]
 mod arg_utils;
 #[path = "cli/args.rs"]
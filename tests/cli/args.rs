@@ -219,6 +219,21 @@ fn short_t_test_flag() {
     assert_eq!(p.op_mode, OpMode::Test);
 }
 
+#[test]
+fn test_mode_takes_every_file_as_input() {
+    // -t implies -m; a file named before the flag is tested first.
+    let p = parse(&["-t", "a.lz4", "b.lz4"]);
+    assert!(p.multiple_inputs);
+    assert_eq!(p.in_file_names, ["a.lz4", "b.lz4"]);
+    let p = parse(&["a.lz4", "--test", "b.lz4", "c.lz4"]);
+    assert_eq!(p.in_file_names, ["a.lz4", "b.lz4", "c.lz4"]);
+    assert_eq!(p.input_filename, None);
+    // stdin is not a file name.
+    let p = parse(&["-", "-t"]);
+    assert_eq!(p.input_filename.as_deref(), Some(STDIN_MARK));
+    assert!(p.in_file_names.is_empty());
+}
+
 #[test]
 fn list_mode_enables_multiple_inputs() {
     // --list also enables multiple_inputs (lz4cli.c line 480)
@@ -465,6 +480,18 @@ fn long_favor_dec_speed() {
     assert!(p.prefs.favor_dec_speed);
 }
 
+#[test]
+fn long_progress_installs_sink() {
+    let p = parse(&["--progress"]);
+    assert!(p.prefs.progress_sink.is_some());
+}
+
+#[test]
+fn long_log_format_rejects_unknown_value() {
+    let err = parse_err(&["--log-format=xml"]);
+    assert!(err.contains("--log-format"), "got: {err}");
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Sparse file support (lz4cli.c lines 482–486)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -1,11 +1,13 @@
 // Integration tests for task-035: src/main.rs — Post-Parse Dispatch and Cleanup (Chunk 7)
 //
 // Verifies parity with lz4cli.c lines 704–887:
-//   - Test mode: `-t` sets test_mode, output=NUL, op=Decompress
+//   - Test mode: `-t` sets test_mode, output=NUL, op=Decompress; every file
+//     is tested and reported OK/FAILED, followed by a count summary
 //   - Auto output filename: compress → input + ".lz4", decompress → strip ".lz4"
 //   - Single-file compress / decompress round-trip
 //   - Stdin → stdout piping (binary data)
 //   - Multiple-input compress/decompress
+//   - `--output-dir`, flat and with `-r` mirroring the source tree
 //   - List mode (-l / --list)
 //   - Error exit when decompressing without ".lz4" extension and no -o
 //   - Refuse stdin if stdin is a terminal (verified via pipe)
@@ -19,7 +21,7 @@
 
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 use tempfile::TempDir;
@@ -42,7 +44,7 @@ fn setup_input(content: &[u8]) -> (TempDir, PathBuf) {
 }
 
 /// Compress `input` with the binary and return the `.lz4` output path.
-fn compress_file(input: &PathBuf) -> PathBuf {
+fn compress_file(input: &Path) -> PathBuf {
     let output = input.with_extension("txt.lz4");
     let status = Command::new(lz4_bin())
         .args(["-f", input.to_str().unwrap(), output.to_str().unwrap()])
@@ -315,6 +317,52 @@ fn test_mode_corrupt_archive_exits_nonzero() {
     assert_ne!(status.code(), Some(0), "corrupt archive must exit non-zero");
 }
 
+#[test]
+fn test_mode_reports_each_file_and_a_summary() {
+    // lz4 -t good bad missing good → all four tested, exit non-zero.
+    let (dir, input) = setup_input(b"per-file test mode");
+    let good = compress_file(&input);
+    let bad = dir.path().join("bad.lz4");
+    fs::write(&bad, b"not an lz4 frame").unwrap();
+    let missing = dir.path().join("missing.lz4");
+    let output = Command::new(lz4_bin())
+        .arg("-t")
+        .args([&good, &bad, &missing, &good])
+        .output()
+        .expect("spawn lz4 -t");
+    assert_eq!(
+        output.status.code(),
+        Some(3),
+        "first failure is corrupt data"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // Progress updates share lines with the results, separated by `\r`.
+    let result = |path: &Path, verdict: &str| {
+        stderr
+            .split(['\n', '\r'])
+            .any(|l| l.starts_with(path.to_str().unwrap()) && l.contains(&format!(": {verdict}")))
+    };
+    assert!(result(&good, "OK"), "{stderr}");
+    assert!(result(&bad, "FAILED (Unrecognized header"), "{stderr}");
+    assert!(result(&missing, "FAILED ("), "{stderr}");
+    assert!(
+        stderr.contains("4 file(s) tested: 2 OK, 2 FAILED"),
+        "{stderr}"
+    );
+
+    let output = Command::new(lz4_bin())
+        .args(["-t"])
+        .args([&good, &good])
+        .output()
+        .expect("spawn lz4 -t");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("2 file(s) tested: 2 OK, 0 FAILED"),
+        "{stderr}"
+    );
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Stdin → stdout piping  (lz4cli.c lines 764–779)
 // When input == STDIN_MARK (pipe) and no output file → output = STDOUT_MARK.
@@ -419,6 +467,63 @@ fn compress_multiple_files() {
     );
 }
 
+#[test]
+fn output_dir_collects_multiple_outputs() {
+    // lz4 -f --output-dir OUT a.txt b.txt → OUT/a.txt.lz4, OUT/b.txt.lz4
+    let dir = TempDir::new().unwrap();
+    let file1 = dir.path().join("a.txt");
+    let file2 = dir.path().join("b.txt");
+    fs::write(&file1, b"file one").unwrap();
+    fs::write(&file2, b"file two").unwrap();
+    let out = dir.path().join("out");
+    let status = Command::new(lz4_bin())
+        .args(["-f", "--output-dir", out.to_str().unwrap()])
+        .args([file1.to_str().unwrap(), file2.to_str().unwrap()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .expect("spawn --output-dir");
+    assert!(status.success());
+    assert!(out.join("a.txt.lz4").exists());
+    assert!(out.join("b.txt.lz4").exists());
+    assert!(!dir.path().join("a.txt.lz4").exists());
+}
+
+#[cfg(feature = "recursive")]
+#[test]
+fn recursive_output_dir_mirrors_tree() {
+    // lz4 -r --output-dir OUT src → OUT/src/sub/c.txt.lz4 (run from the
+    // temp dir so the mirrored path is relative)
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src/sub")).unwrap();
+    fs::write(dir.path().join("src/a.txt"), b"top").unwrap();
+    fs::write(dir.path().join("src/sub/c.txt"), b"nested").unwrap();
+    let status = Command::new(lz4_bin())
+        .current_dir(dir.path())
+        .args(["-r", "-f", "--output-dir=out", "src"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .expect("spawn -r --output-dir");
+    assert!(status.success());
+    assert!(dir.path().join("out/src/a.txt.lz4").exists());
+    assert!(dir.path().join("out/src/sub/c.txt.lz4").exists());
+    assert!(!dir.path().join("src/sub/c.txt.lz4").exists());
+}
+
+#[test]
+fn output_dir_rejected_when_decompressing() {
+    let (dir, input) = setup_input(b"data");
+    let status = Command::new(lz4_bin())
+        .args(["-d", "--output-dir", dir.path().to_str().unwrap()])
+        .arg(input.to_str().unwrap())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .expect("spawn -d --output-dir");
+    assert_eq!(status.code(), Some(1));
+}
+
 #[test]
 fn decompress_multiple_files() {
     // lz4 -d -m -f file1.lz4 file2.lz4 → file1 and file2 recreated
@@ -4,13 +4,10 @@
 //   - print_usage       → usage() — brief usage to stderr
 //   - print_usage_advanced → usage_advanced() — welcome banner + advanced options
 //   - print_long_help   → usage_longhelp() — full long-form help
-//   - error_out         → errorOut() — prints to stderr then exits 1
-//   - print_bad_usage   → badusage() — prints "Incorrect parameters" then exits 1
 //   - wait_enter        → waitEnter() — prompt and read a line
 //
-// Note: error_out and print_bad_usage call std::process::exit(1) and cannot be
-// called directly in integration tests. They are tested via subprocess spawning
-// using test helper functions. wait_enter blocks on stdin and is tested similarly.
+// Note: wait_enter blocks on stdin, so it is tested via subprocess spawning
+// using a test helper function.
 
 use lz4::cli::constants::set_lz4c_legacy_commands;
 use lz4::cli::help::{print_long_help, print_usage, print_usage_advanced};
@@ -20,23 +17,6 @@ use lz4::cli::help::{print_long_help, print_usage, print_usage_advanced};
 // The parent test spawns the test binary filtered to this specific test name.
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Subprocess helper: calls error_out when LZ4_TEST_ERROR_OUT env var is set.
-/// This test is a no-op when run normally; it exits(1) only in the child process.
-#[test]
-fn subprocess_helper_error_out() {
-    if std::env::var("LZ4_TEST_ERROR_OUT").is_ok() {
-        lz4::cli::help::error_out("test error");
-    }
-}
-
-/// Subprocess helper: calls print_bad_usage when LZ4_TEST_BAD_USAGE env var is set.
-#[test]
-fn subprocess_helper_bad_usage() {
-    if std::env::var("LZ4_TEST_BAD_USAGE").is_ok() {
-        lz4::cli::help::print_bad_usage("lz4");
-    }
-}
-
 /// Subprocess helper: calls wait_enter with null stdin when LZ4_TEST_WAIT_ENTER is set.
 #[test]
 fn subprocess_helper_wait_enter() {
@@ -147,58 +127,6 @@ fn print_long_help_does_not_panic_with_path_name() {
     print_long_help("/usr/bin/lz4");
 }
 
-// ─────────────────────────────────────────────────────────────────────────────
-// error_out  (mirrors C errorOut() lz4cli.c lines 104–107)
-// Calls process::exit(1) — tested via subprocess.
-// ─────────────────────────────────────────────────────────────────────────────
-
-#[test]
-fn error_out_exits_with_code_1() {
-    // Spawn a child process that runs subprocess_helper_error_out with the env var set.
-    // Mirrors `errorOut` which always calls exit(1).
-    let exe = std::env::current_exe().expect("could not find test executable");
-    let output = std::process::Command::new(&exe)
-        .args([
-            "help::subprocess_helper_error_out",
-            "--exact",
-            "--nocapture",
-        ])
-        .env("LZ4_TEST_ERROR_OUT", "1")
-        .output()
-        .expect("failed to spawn subprocess");
-    assert_eq!(
-        output.status.code(),
-        Some(1),
-        "error_out must exit with code 1"
-    );
-}
-
-// ─────────────────────────────────────────────────────────────────────────────
-// print_bad_usage  (mirrors C badusage() lz4cli.c lines 248–253)
-// Calls process::exit(1) — tested via subprocess.
-// ─────────────────────────────────────────────────────────────────────────────
-
-#[test]
-fn print_bad_usage_exits_with_code_1() {
-    // Spawn a child process that runs subprocess_helper_bad_usage with env var set.
-    // Mirrors `badusage` which always calls exit(1).
-    let exe = std::env::current_exe().expect("could not find test executable");
-    let output = std::process::Command::new(&exe)
-        .args([
-            "help::subprocess_helper_bad_usage",
-            "--exact",
-            "--nocapture",
-        ])
-        .env("LZ4_TEST_BAD_USAGE", "1")
-        .output()
-        .expect("failed to spawn subprocess");
-    assert_eq!(
-        output.status.code(),
-        Some(1),
-        "print_bad_usage must exit with code 1"
-    );
-}
-
 // ─────────────────────────────────────────────────────────────────────────────
 // wait_enter  (mirrors C waitEnter() lz4cli.c lines 256–260)
 // Reads a line from stdin — tested with closed stdin (EOF) via subprocess.
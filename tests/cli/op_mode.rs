@@ -30,7 +30,7 @@ fn op_mode_copy_clone() {
     // OpMode must implement Copy and Clone (used across call boundaries in lz4cli.c).
     let a = OpMode::Compress;
     let b = a; // Copy
-    let c = a.clone(); // Clone
+    let c = a; // Clone
     assert_eq!(a, b);
     assert_eq!(a, c);
 }
@@ -182,3 +182,30 @@ fn init_c_level_empty_env_var_returns_default() {
     // Empty string → default.
     assert_eq!(init_c_level_from(Some("")), LZ4_CLEVEL_DEFAULT);
 }
+
+#[test]
+fn init_env_values_with_trailing_text_return_default() {
+    // Like the C CLI, the whole value must be a number.
+    assert_eq!(init_c_level_from(Some("9x")), LZ4_CLEVEL_DEFAULT);
+    assert_eq!(init_c_level_from(Some("9 ")), LZ4_CLEVEL_DEFAULT);
+    assert_eq!(
+        init_nb_workers_from(Some("4 threads")),
+        LZ4_NBWORKERS_DEFAULT
+    );
+}
+
+#[test]
+fn init_env_values_accept_size_suffixes() {
+    assert_eq!(init_nb_workers_from(Some("1K")), 1024);
+}
+
+#[test]
+fn init_env_values_too_large_return_default() {
+    // Previously these wrapped around to small values.
+    assert_eq!(init_c_level_from(Some("4294967305")), LZ4_CLEVEL_DEFAULT);
+    assert_eq!(init_nb_workers_from(Some("8G")), LZ4_NBWORKERS_DEFAULT);
+    assert_eq!(
+        init_nb_workers_from(Some("99999999999999999999999")),
+        LZ4_NBWORKERS_DEFAULT
+    );
+}
@@ -0,0 +1,124 @@
+// Integration tests for the `serde` feature: compressed::Lz4Compressed.
+//
+//   - Round-trip through an outer JSON document with a JSON inner format
+//   - The stored blob is a standard LZ4 frame and is smaller than the raw encoding
+//   - Corrupt blobs and inner-format errors surface as deserialization errors
+//   - Blobs decoding past BlobFormat::MAX_DECOMPRESSED_SIZE are refused
+
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+
+use lz4::compressed::{BlobFormat, Lz4Compressed};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+struct Json;
+
+impl BlobFormat for Json {
+    type Error = serde_json::Error;
+
+    fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Self::Error> {
+        serde_json::to_vec(value)
+    }
+
+    fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Self::Error> {
+        serde_json::from_slice(bytes)
+    }
+}
+
+type Doc = BTreeMap<String, Vec<u32>>;
+
+fn sample_doc() -> Doc {
+    (0..50)
+        .map(|i| (format!("series-{i:02}"), (0..200).map(|v| v % 17).collect()))
+        .collect()
+}
+
+#[test]
+fn round_trips_through_outer_format() {
+    let wrapped: Lz4Compressed<Doc, Json> = Lz4Compressed::new(sample_doc());
+    let text = serde_json::to_string(&wrapped).unwrap();
+    let back: Lz4Compressed<Doc, Json> = serde_json::from_str(&text).unwrap();
+    assert_eq!(back, wrapped);
+    assert_eq!(back.into_inner(), sample_doc());
+}
+
+#[test]
+fn blob_is_an_lz4_frame_smaller_than_raw() {
+    let doc = sample_doc();
+    let raw = serde_json::to_vec(&doc).unwrap();
+    let wrapped: Lz4Compressed<Doc, Json> = doc.into();
+    // serde_json writes byte strings as arrays of integers.
+    let blob: Vec<u8> = serde_json::from_value(serde_json::to_value(&wrapped).unwrap()).unwrap();
+    assert_eq!(&blob[..4], &[0x04, 0x22, 0x4D, 0x18]);
+    assert!(
+        blob.len() < raw.len() / 4,
+        "{} vs {}",
+        blob.len(),
+        raw.len()
+    );
+    assert_eq!(lz4::frame::decompress_frame_to_vec(&blob).unwrap(), raw);
+}
+
+#[test]
+fn corrupt_blob_is_a_deserialize_error() {
+    let wrapped: Lz4Compressed<Doc, Json> = Lz4Compressed::new(sample_doc());
+    let mut blob: Vec<u8> =
+        serde_json::from_value(serde_json::to_value(&wrapped).unwrap()).unwrap();
+    let last = blob.len() - 1;
+    blob[last] ^= 0xFF; // content checksum
+    let text = serde_json::to_string(&blob).unwrap();
+    assert!(serde_json::from_str::<Lz4Compressed<Doc, Json>>(&text).is_err());
+}
+
+#[test]
+fn inner_format_mismatch_is_a_deserialize_error() {
+    let wrapped: Lz4Compressed<Vec<String>, Json> =
+        Lz4Compressed::new(vec!["not".into(), "numbers".into()]);
+    let text = serde_json::to_string(&wrapped).unwrap();
+    assert!(serde_json::from_str::<Lz4Compressed<Vec<u32>, Json>>(&text).is_err());
+}
+
+#[test]
+fn deref_exposes_the_value() {
+    let mut wrapped: Lz4Compressed<Vec<u32>, Json> = vec![1, 2, 3].into();
+    wrapped.push(4);
+    assert_eq!(wrapped.len(), 4);
+    assert_eq!(format!("{:?}", wrapped), "Lz4Compressed([1, 2, 3, 4])");
+}
+
+/// [`Json`] with a 1 KiB decoded-size cap.
+struct SmallJson;
+
+impl BlobFormat for SmallJson {
+    type Error = serde_json::Error;
+    const MAX_DECOMPRESSED_SIZE: u64 = 1024;
+
+    fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Self::Error> {
+        serde_json::to_vec(value)
+    }
+
+    fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Self::Error> {
+        serde_json::from_slice(bytes)
+    }
+}
+
+#[test]
+fn blob_over_the_size_limit_is_refused() {
+    assert_eq!(
+        <Json as BlobFormat>::MAX_DECOMPRESSED_SIZE,
+        lz4::compressed::DEFAULT_MAX_DECOMPRESSED_SIZE
+    );
+    let small: Lz4Compressed<Vec<u32>, SmallJson> = vec![7; 10].into();
+    let text = serde_json::to_string(&small).unwrap();
+    let back: Lz4Compressed<Vec<u32>, SmallJson> = serde_json::from_str(&text).unwrap();
+    assert_eq!(back, small);
+
+    // About 2 KiB of JSON, compressed to a few dozen bytes.
+    let large: Lz4Compressed<Vec<u32>, Json> = vec![7; 1000].into();
+    let text = serde_json::to_string(&large).unwrap();
+    assert!(serde_json::from_str::<Lz4Compressed<Vec<u32>, Json>>(&text).is_ok());
+    let err = serde_json::from_str::<Lz4Compressed<Vec<u32>, SmallJson>>(&text).unwrap_err();
+    assert!(err.to_string().contains("OutputLimitExceeded"), "{err}");
+}
@@ -0,0 +1,157 @@
+// Integration tests for src/corpus.rs — seeded synthetic corpora
+//
+// Covers:
+//   - Every generator returns exactly `size` bytes, including 0 and odd sizes
+//   - Output is deterministic per seed and differs across seeds
+//   - `lorem` matches `lorem::gen_buffer`
+//   - Text corpora are line-oriented ASCII in the documented shape
+//   - `binary` compressibility follows `bits_per_byte`
+//   - `compressible` ratio falls steadily as `entropy` rises
+//   - `Corpus::generate` dispatches to the matching generator
+
+#![cfg(feature = "block")]
+
+use lz4::corpus::{binary, compressible, json_records, log_lines, lorem, Corpus};
+
+const ALL: [Corpus; 5] = [
+    Corpus::Lorem,
+    Corpus::Logs,
+    Corpus::Json,
+    Corpus::Binary { bits_per_byte: 3 },
+    Corpus::Binary { bits_per_byte: 8 },
+];
+
+fn compressed_len(data: &[u8]) -> usize {
+    let mut dst = vec![0u8; lz4::compress_bound(data.len() as i32) as usize];
+    lz4::lz4_compress_default(data, &mut dst).unwrap()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Size and determinism
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn generators_return_exact_size() {
+    for corpus in ALL {
+        for size in [0, 1, 7, 100, 4096, 100_001] {
+            assert_eq!(corpus.generate(size, 1).len(), size, "{corpus:?} {size}");
+        }
+    }
+}
+
+#[test]
+fn generators_are_deterministic_per_seed() {
+    for corpus in ALL {
+        assert_eq!(corpus.generate(10_000, 7), corpus.generate(10_000, 7));
+        assert_ne!(
+            corpus.generate(10_000, 7),
+            corpus.generate(10_000, 8),
+            "{corpus:?}"
+        );
+    }
+}
+
+/// A shorter buffer is a prefix of a longer one from the same seed.
+#[test]
+fn line_corpora_are_prefix_stable() {
+    let long = log_lines(50_000, 3);
+    assert_eq!(&long[..12_345], log_lines(12_345, 3).as_slice());
+    let long = json_records(50_000, 3);
+    assert_eq!(&long[..12_345], json_records(12_345, 3).as_slice());
+}
+
+#[test]
+fn lorem_matches_gen_buffer() {
+    assert_eq!(lorem(65_536, 5), lz4::lorem::gen_buffer(65_536, 5));
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Shape
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn log_lines_shape() {
+    let text = String::from_utf8(log_lines(20_000, 9)).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    for line in &lines[..lines.len() - 1] {
+        assert!(line.starts_with("2024-03-01T"), "{line}");
+        assert!(line.contains(" [worker-"), "{line}");
+        assert!(line.contains(" status="), "{line}");
+    }
+}
+
+#[test]
+fn json_records_shape() {
+    let text = String::from_utf8(json_records(20_000, 9)).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    for (i, line) in lines[..lines.len() - 1].iter().enumerate() {
+        assert!(
+            line.starts_with(&format!("{{\"id\":{i},\"name\":\"")),
+            "{line}"
+        );
+        assert!(line.ends_with("]}"), "{line}");
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Compressibility
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn binary_entropy_controls_ratio() {
+    const SIZE: usize = 256 * 1024;
+    assert!(binary(SIZE, 1, 0).iter().all(|&b| b == binary(1, 1, 0)[0]));
+    let sizes: Vec<usize> = [0, 2, 8]
+        .iter()
+        .map(|&bits| compressed_len(&binary(SIZE, 1, bits)))
+        .collect();
+    assert!(sizes[0] < SIZE / 100, "{sizes:?}");
+    assert!(sizes[0] < sizes[1] && sizes[1] < sizes[2], "{sizes:?}");
+    assert!(
+        sizes[2] >= SIZE,
+        "8 bits per byte must not compress: {sizes:?}"
+    );
+    // Values above 8 are capped.
+    assert_eq!(binary(1000, 4, 8), binary(1000, 4, 40));
+}
+
+#[test]
+fn compressible_ratio_follows_entropy() {
+    for size in [0, 1, 4095, 100_001] {
+        assert_eq!(compressible(size, 2, 0.3).len(), size);
+    }
+    assert_eq!(compressible(50_000, 4, 0.3), compressible(50_000, 4, 0.3));
+    assert_ne!(compressible(50_000, 4, 0.3), compressible(50_000, 5, 0.3));
+
+    let len = 1 << 20;
+    let ratios: Vec<f64> = [0.0, 0.25, 0.5, 0.75, 1.0]
+        .iter()
+        .map(|&e| len as f64 / compressed_len(&compressible(len, 1, e)) as f64)
+        .collect();
+    for pair in ratios.windows(2) {
+        assert!(pair[0] > pair[1] * 1.1, "{ratios:?}");
+    }
+    assert!(ratios[0] > 5.0, "{ratios:?}");
+    assert!(ratios[4] < 1.01, "{ratios:?}");
+    // Out-of-range entropy is clamped.
+    assert_eq!(compressible(9000, 1, 3.0), compressible(9000, 1, 1.0));
+}
+
+#[test]
+fn text_corpora_compress() {
+    const SIZE: usize = 256 * 1024;
+    for corpus in [Corpus::Lorem, Corpus::Logs, Corpus::Json] {
+        let n = compressed_len(&corpus.generate(SIZE, 2));
+        assert!(n < SIZE * 3 / 4, "{corpus:?}: {n}");
+    }
+}
+
+#[test]
+fn corpus_generate_dispatches() {
+    assert_eq!(Corpus::Logs.generate(5000, 4), log_lines(5000, 4));
+    assert_eq!(Corpus::Json.generate(5000, 4), json_records(5000, 4));
+    assert_eq!(
+        Corpus::Binary { bits_per_byte: 5 }.generate(5000, 4),
+        binary(5000, 4, 5)
+    );
+}
@@ -6,6 +6,8 @@
 //   io, bench) is declared in lib.rs
 // - Key runtime dependencies (anyhow) are available and functional
 
+#![cfg(feature = "frame")]
+
 // Test that the crate root compiles and links successfully.
 // This is the minimal parity check for the placeholder lib.rs.
 #[test]
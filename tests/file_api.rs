@@ -10,6 +10,8 @@
 //   - lz4_read_frame          → LZ4_readFile  convenience
 //   - Sticky errored flag (C: errCode), Drop finalisation
 
+#![cfg(all(feature = "frame", feature = "hc", not(feature = "forbid-unsafe")))]
+
 use lz4::file::{lz4_read_frame, lz4_write_frame, Lz4ReadFile, Lz4WriteFile};
 use lz4::frame::types::{BlockSizeId, ContentChecksum, FrameInfo, Preferences};
 use std::io::{Cursor, Read, Write};
@@ -1,12 +1,34 @@
+#![cfg(all(feature = "frame", feature = "hc", not(feature = "forbid-unsafe")))]
+
+#[path = "frame/aligned.rs"]
+mod aligned;
+#[path = "frame/append.rs"]
+mod append;
 #[path = "frame/cdict.rs"]
 mod cdict;
 #[path = "frame/compress.rs"]
 mod compress;
+#[path = "frame/conformance.rs"]
+mod conformance;
 #[path = "frame/decompress.rs"]
 mod decompress;
+#[path = "frame/deterministic.rs"]
+mod deterministic;
 #[path = "frame/header.rs"]
 mod header;
+#[path = "frame/iter.rs"]
+mod iter;
 #[path = "frame/oneshot.rs"]
 mod oneshot;
+#[path = "frame/pool.rs"]
+mod pool;
+#[path = "frame/safe.rs"]
+mod safe;
+#[path = "frame/skippable.rs"]
+mod skippable;
+#[path = "frame/stress.rs"]
+mod stress;
 #[path = "frame/types.rs"]
 mod types;
+#[path = "frame/wire.rs"]
+mod wire;
@@ -0,0 +1,87 @@
+// Integration tests for src/frame/aligned.rs — aligned staging buffers
+//
+// Covers:
+//   - `AlignedBuf` starts on a `BUFFER_ALIGN` boundary, zero-filled
+//   - `resize` keeps contents and fills new bytes like `Vec::resize`
+//   - The compression context's `tmp_buf` and the decompression context's
+//     `tmp_in` / `tmp_out_buffer` are aligned once allocated
+//
+// Run with `LZ4R_BUFFER_ALIGN=4096` to check page alignment.
+
+use lz4::frame::types::LZ4F_VERSION;
+use lz4::frame::{
+    lz4f_compress_begin, lz4f_compress_bound, lz4f_create_compression_context,
+    lz4f_create_decompression_context, lz4f_decompress, AlignedBuf, BlockChecksum, FrameInfo,
+    Preferences, BUFFER_ALIGN,
+};
+
+fn is_aligned(buf: &[u8]) -> bool {
+    (buf.as_ptr() as usize).is_multiple_of(BUFFER_ALIGN)
+}
+
+#[test]
+fn buffer_alignment_is_a_power_of_two() {
+    assert!(BUFFER_ALIGN.is_power_of_two());
+}
+
+#[test]
+fn aligned_buf_is_aligned_and_zeroed() {
+    let buf = AlignedBuf::zeroed(10_000);
+    assert_eq!(buf.len(), 10_000);
+    assert!(is_aligned(&buf));
+    assert!(buf.iter().all(|&b| b == 0));
+    assert!(AlignedBuf::new().is_empty());
+}
+
+#[test]
+fn aligned_buf_resize_matches_vec() {
+    let mut buf = AlignedBuf::zeroed(4);
+    let mut vec = vec![0u8; 4];
+    buf.copy_from_slice(b"lz4!");
+    vec.copy_from_slice(b"lz4!");
+    for (len, fill) in [(100_000, 1), (3, 2), (50, 3)] {
+        buf.resize(len, fill);
+        vec.resize(len, fill);
+        assert_eq!(*buf, *vec);
+        assert!(is_aligned(&buf));
+    }
+}
+
+#[test]
+fn context_staging_buffers_are_aligned() {
+    let prefs = Preferences {
+        frame_info: FrameInfo {
+            block_checksum_flag: BlockChecksum::Enabled,
+            ..FrameInfo::default()
+        },
+        ..Preferences::default()
+    };
+    let mut cctx = lz4f_create_compression_context(LZ4F_VERSION).unwrap();
+    let mut header = vec![0u8; lz4f_compress_bound(0, Some(&prefs))];
+    lz4f_compress_begin(&mut cctx, &mut header, Some(&prefs)).unwrap();
+    assert!(!cctx.tmp_buf.is_empty());
+    assert!(is_aligned(&cctx.tmp_buf));
+
+    // Small input chunks and a tiny output buffer take both staging paths.
+    let data = lz4::corpus::log_lines(200_000, 1);
+    let frame = lz4::frame::compress_frame_to_vec(&data);
+    let mut dctx = lz4f_create_decompression_context(LZ4F_VERSION).unwrap();
+    let mut out = Vec::new();
+    let mut dst = [0u8; 512];
+    let mut pos = 0;
+    while pos < frame.len() {
+        let end = (pos + 1000).min(frame.len());
+        let (read, written, _) =
+            lz4f_decompress(&mut dctx, Some(&mut dst), &frame[pos..end], None).unwrap();
+        out.extend_from_slice(&dst[..written]);
+        pos += read;
+        if read == 0 && written == 0 {
+            break;
+        }
+    }
+    assert_eq!(out, data);
+    let (tmp_in, tmp_out) = dctx.staging_buffers();
+    assert!(!tmp_in.is_empty() && !tmp_out.is_empty());
+    assert!(is_aligned(tmp_in));
+    assert!(is_aligned(tmp_out));
+}
@@ -0,0 +1,127 @@
+// Tests for frame::append — append_frame / check_frame_boundary
+//
+// Covers:
+//   - Appending to an empty file and to existing frames; every frame decodes
+//   - Skippable frames, legacy frames, block checksums and content size
+//   - Truncated frames, trailing garbage and bad header checksums are refused
+//     without touching the file
+
+use std::io::{Cursor, ErrorKind};
+
+use lz4::frame::skippable::skippable_frame_bound;
+use lz4::frame::types::LZ4F_VERSION;
+use lz4::frame::{
+    append_frame, check_frame_boundary, compress_frame_to_vec, lz4f_create_decompression_context,
+    lz4f_decompress, write_skippable_frame, BlockChecksum, BlockSizeId, ContentChecksum, FrameInfo,
+    Preferences,
+};
+
+/// Decodes every frame in `src` back to back, skipping skippable frames.
+fn decode_all(src: &[u8]) -> Vec<u8> {
+    let mut dctx = lz4f_create_decompression_context(LZ4F_VERSION).unwrap();
+    let mut out = Vec::new();
+    let mut buf = vec![0u8; 1 << 16];
+    let mut pos = 0;
+    while pos < src.len() {
+        let (consumed, written, _) =
+            lz4f_decompress(&mut dctx, Some(&mut buf), &src[pos..], None).unwrap();
+        out.extend_from_slice(&buf[..written]);
+        pos += consumed;
+        assert!(consumed > 0 || written > 0, "decoder stalled at {pos}");
+    }
+    out
+}
+
+fn text(n: usize) -> Vec<u8> {
+    (0..n)
+        .map(|i| b"log line\n"[i % 9] ^ (i / 997) as u8)
+        .collect()
+}
+
+#[test]
+fn append_to_empty_and_existing_files() {
+    let mut file = Cursor::new(Vec::new());
+    assert_eq!(check_frame_boundary(&mut file).unwrap(), 0);
+
+    let a = text(1000);
+    let b = text(200_000);
+    let n = append_frame(&mut file, &a, None).unwrap();
+    assert_eq!(n, file.get_ref().len() as u64);
+    let m = append_frame(&mut file, &b, None).unwrap();
+    assert_eq!(n + m, file.get_ref().len() as u64);
+    assert_eq!(file.position(), n + m);
+
+    let mut expected = a.clone();
+    expected.extend_from_slice(&b);
+    assert_eq!(decode_all(file.get_ref()), expected);
+}
+
+#[test]
+fn append_after_skippable_and_flagged_frames() {
+    let mut stream = compress_frame_to_vec(b"first");
+    let mut skip = vec![0u8; skippable_frame_bound(3)];
+    write_skippable_frame(&mut skip, 5, b"tag").unwrap();
+    stream.extend_from_slice(&skip);
+
+    let prefs = Preferences {
+        frame_info: FrameInfo {
+            block_size_id: BlockSizeId::Max64Kb,
+            block_checksum_flag: BlockChecksum::Enabled,
+            content_checksum_flag: ContentChecksum::Enabled,
+            content_size: 300_000,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let data = text(300_000);
+    let mut file = Cursor::new(stream);
+    append_frame(&mut file, &data, Some(&prefs)).unwrap();
+    append_frame(&mut file, b"last", None).unwrap();
+
+    let len = file.get_ref().len() as u64;
+    assert_eq!(check_frame_boundary(&mut file).unwrap(), len);
+    let mut expected = b"first".to_vec();
+    expected.extend_from_slice(&data);
+    expected.extend_from_slice(b"last");
+    assert_eq!(decode_all(file.get_ref()), expected);
+}
+
+#[test]
+fn legacy_frames_are_a_valid_boundary() {
+    let mut stream = 0x184C_2102u32.to_le_bytes().to_vec();
+    stream.extend_from_slice(&5u32.to_le_bytes());
+    stream.extend_from_slice(b"\x40abcd");
+    let mut file = Cursor::new(stream);
+    append_frame(&mut file, b"after legacy", None).unwrap();
+    let len = file.get_ref().len() as u64;
+    assert_eq!(check_frame_boundary(&mut file).unwrap(), len);
+
+    // A truncated legacy block is still refused.
+    let mut cut = file.into_inner();
+    cut.truncate(10);
+    assert!(check_frame_boundary(&mut Cursor::new(cut)).is_err());
+}
+
+#[test]
+fn damaged_files_are_left_untouched() {
+    let frame = compress_frame_to_vec(&text(5000));
+    let mut cases: Vec<(&str, Vec<u8>)> = Vec::new();
+    for cut in [1, 4, 5, frame.len() / 2] {
+        cases.push(("truncated", frame[..frame.len() - cut].to_vec()));
+    }
+    let mut garbage = frame.clone();
+    garbage.extend_from_slice(b"junk");
+    cases.push(("trailing garbage", garbage));
+    let mut bad_hc = frame.clone();
+    bad_hc[6] ^= 0xFF;
+    cases.push(("bad header checksum", bad_hc));
+    cases.push(("not lz4", b"plain text".to_vec()));
+
+    for (what, bytes) in cases {
+        let mut file = Cursor::new(bytes.clone());
+        let err = append_frame(&mut file, b"new", None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData, "{what}");
+        assert!(err.to_string().contains("frame boundary"), "{what}: {err}");
+        assert_eq!(file.get_ref(), &bytes, "{what}");
+    }
+}
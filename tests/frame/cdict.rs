@@ -3,6 +3,8 @@
 // Verifies behavioural parity with lz4frame.c v1.10.0, lines 527–590:
 //   `LZ4F_createCDict` / `LZ4F_createCDict_advanced` → `Lz4FCDict::create`
 //   `LZ4F_freeCDict`                                 → (Drop on Box<Lz4FCDict>)
+//   (extension)                                      → `Lz4FCDict::create_with_level`
+//   (extension)                                      → `Lz4FCDict::create_from_samples`
 //
 // All tests operate on the public API only.
 
@@ -265,3 +267,189 @@ fn assert_send_sync<T: Send + Sync>() {}
 fn lz4f_cdict_implements_send_and_sync() {
     assert_send_sync::<Lz4FCDict>();
 }
+
+// ═════════════════════════════════════════════════════════════════════════════
+// Lz4FCDict::create_with_level
+// ═════════════════════════════════════════════════════════════════════════════
+
+fn dict_sample() -> (Vec<u8>, Vec<u8>) {
+    // Pseudo-random dictionary: the source only compresses through it.
+    let mut x = 0x1234_5678u32;
+    let dict: Vec<u8> = (0..32 * 1024)
+        .map(|_| {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (x >> 24) as u8
+        })
+        .collect();
+    let mut src = dict[4096..12_288].to_vec();
+    src.extend_from_slice(&dict[20_000..24_000]);
+    for i in 0..400u32 {
+        src.extend_from_slice(format!("id={} v={};", i * 7 % 113, i % 29).as_bytes());
+    }
+    (dict, src)
+}
+
+fn compress_with(cdict: &Lz4FCDict, src: &[u8], level: Option<i32>) -> Vec<u8> {
+    use lz4::frame::compress::{lz4f_compress_frame_using_cdict, lz4f_create_compression_context};
+    use lz4::frame::header::lz4f_compress_frame_bound;
+    use lz4::frame::types::{Preferences, LZ4F_VERSION};
+
+    let prefs = level.map(|compression_level| Preferences {
+        compression_level,
+        ..Default::default()
+    });
+    let mut cctx = lz4f_create_compression_context(LZ4F_VERSION).unwrap();
+    let mut dst = vec![0u8; lz4f_compress_frame_bound(src.len(), prefs.as_ref())];
+    let n = lz4f_compress_frame_using_cdict(&mut cctx, &mut dst, src, cdict, prefs.as_ref())
+        .expect("compress with cdict");
+    dst.truncate(n);
+    dst
+}
+
+fn decompress_with(frame: &[u8], dict: &[u8], len: usize) -> Vec<u8> {
+    use lz4::frame::decompress::{lz4f_create_decompression_context, lz4f_decompress_using_dict};
+    use lz4::frame::types::LZ4F_VERSION;
+
+    let mut dctx = lz4f_create_decompression_context(LZ4F_VERSION).unwrap();
+    let mut out = vec![0u8; len];
+    let (_, written, hint) =
+        lz4f_decompress_using_dict(&mut dctx, Some(&mut out), frame, dict, None).unwrap();
+    assert_eq!(hint, 0, "frame should be fully decoded");
+    out.truncate(written);
+    out
+}
+
+/// A CDict digested at one level round-trips at fast, HC and optimal levels.
+#[test]
+fn create_with_level_round_trips_at_every_level() {
+    let (dict, src) = dict_sample();
+    for cdict_level in [1, 9, 12] {
+        let cdict = Lz4FCDict::create_with_level(&dict, cdict_level).unwrap();
+        for level in [1, 3, 6, 9, 10, 12] {
+            let frame = compress_with(&cdict, &src, Some(level));
+            assert_eq!(
+                decompress_with(&frame, &dict, src.len()),
+                src,
+                "cdict level {cdict_level}, frame level {level}"
+            );
+        }
+    }
+}
+
+/// Without preferences, frames are compressed at the CDict's level.
+#[test]
+fn create_with_level_sets_default_frame_level() {
+    let (dict, src) = dict_sample();
+    let cdict = Lz4FCDict::create_with_level(&dict, 9).unwrap();
+    assert_eq!(cdict.level(), 9);
+    let implicit = compress_with(&cdict, &src, None);
+    assert_eq!(implicit, compress_with(&cdict, &src, Some(9)));
+    assert_ne!(implicit, compress_with(&cdict, &src, Some(0)));
+    assert_eq!(decompress_with(&implicit, &dict, src.len()), src);
+
+    let plain = Lz4FCDict::create(&dict).unwrap();
+    assert_eq!(plain.level(), 0);
+    assert_eq!(
+        compress_with(&plain, &src, None),
+        compress_with(&plain, &src, Some(0))
+    );
+}
+
+/// The HC tables are actually used: HC output with the dictionary is smaller
+/// than HC output without it.
+#[test]
+fn create_with_level_hc_tables_are_used() {
+    let (dict, src) = dict_sample();
+    let cdict = Lz4FCDict::create_with_level(&dict, 9).unwrap();
+    let empty = Lz4FCDict::create_with_level(&[], 9).unwrap();
+    let with_dict = compress_with(&cdict, &src, None);
+    let without = compress_with(&empty, &src, None);
+    assert!(
+        with_dict.len() < without.len(),
+        "{} !< {}",
+        with_dict.len(),
+        without.len()
+    );
+}
+
+/// The CDict owns its dictionary: the caller's buffer may be dropped (and its
+/// memory reused) before the CDict is used.
+#[test]
+fn create_does_not_borrow_caller_buffer() {
+    let (dict, src) = dict_sample();
+    let cdict = Lz4FCDict::create(&dict.clone()).unwrap();
+    let _reuse = vec![0u8; dict.len()];
+    let frame = compress_with(&cdict, &src, Some(1));
+    let reference = compress_with(&Lz4FCDict::create(&dict).unwrap(), &src, Some(1));
+    assert_eq!(frame, reference);
+    assert_eq!(decompress_with(&frame, &dict, src.len()), src);
+}
+
+// ═════════════════════════════════════════════════════════════════════════════
+// Lz4FCDict::create_from_samples  (extension)
+// ═════════════════════════════════════════════════════════════════════════════
+
+fn random_bytes(seed: u32, len: usize) -> Vec<u8> {
+    let mut x = seed;
+    (0..len)
+        .map(|_| {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (x >> 24) as u8
+        })
+        .collect()
+}
+
+/// A single sample that fits is used verbatim.
+#[test]
+fn create_from_samples_single_sample_is_verbatim() {
+    let sample = cycling_dict(1000);
+    let cdict = Lz4FCDict::create_from_samples(&[&sample], MAX_DICT_SIZE).unwrap();
+    assert_eq!(cdict.content(), sample.as_slice());
+}
+
+/// The result never exceeds `max_size` nor the 64 KB frame limit, and always
+/// ends with the newest sample.
+#[test]
+fn create_from_samples_respects_limits() {
+    let a = random_bytes(1, 40_000);
+    let b = random_bytes(2, 40_000);
+    let samples: [&[u8]; 2] = [&a, &b];
+
+    let small = Lz4FCDict::create_from_samples(&samples, 50_000).unwrap();
+    assert_eq!(small.content().len(), 50_000);
+    assert!(small.content().ends_with(&b));
+    assert!(small.content().starts_with(&a[30_000..]));
+
+    let big = Lz4FCDict::create_from_samples(&samples, 1 << 20).unwrap();
+    assert_eq!(big.content().len(), MAX_DICT_SIZE);
+    assert!(big.content().ends_with(&b));
+}
+
+/// Repeated samples do not crowd older distinct samples out of the window,
+/// so the packed dictionary beats trimming a naive concatenation.
+#[test]
+fn create_from_samples_beats_naive_concatenation() {
+    let a = random_bytes(3, 20_000);
+    let b = random_bytes(4, 20_000);
+    let c = random_bytes(5, 20_000);
+    let samples: [&[u8]; 5] = [&a, &b, &b, &b, &c];
+
+    let mut src = a[1000..9000].to_vec();
+    src.extend_from_slice(&b[5000..9000]);
+    src.extend_from_slice(&c[2000..6000]);
+
+    let packed = Lz4FCDict::create_from_samples(&samples, MAX_DICT_SIZE).unwrap();
+    let naive = Lz4FCDict::create(&samples.concat()).unwrap();
+    let packed_frame = compress_with(&packed, &src, Some(1));
+    let naive_frame = compress_with(&naive, &src, Some(1));
+    assert!(
+        packed_frame.len() < naive_frame.len(),
+        "{} !< {}",
+        packed_frame.len(),
+        naive_frame.len()
+    );
+    assert_eq!(
+        decompress_with(&packed_frame, packed.content(), src.len()),
+        src
+    );
+}
@@ -6,19 +6,29 @@
 //   - Streaming update: `lz4f_compress_update`, `lz4f_uncompressed_update`, `lz4f_flush`, `lz4f_compress_end`
 //   - Bound calculation: `lz4f_compress_bound`
 //   - One-shot: `lz4f_compress_frame`, `lz4f_compress_frame_using_cdict`
+//   - One-shot into a writer: `lz4f_compress_frame_to_writer`
+//   - Fixed-size output: `compress_frame_dest_size`
+//   - Per-block hook: `Lz4FCCtx::set_block_filter`
+//   - Context reuse: `Lz4FCCtx::reset_for_reuse`
+//   - Debug-build misuse panics: use after `lz4f_compress_end`, interleaved
+//     frames
+//   - `Preferences::favor_dec_speed` at optimal-parser levels, one-shot and
+//     fixed-size
 //   - Constants: `LZ4F_MAGIC_NUMBER`, `LZ4F_VERSION`
 
 use lz4::frame::compress::{
-    lz4f_compress_begin, lz4f_compress_begin_using_dict, lz4f_compress_bound, lz4f_compress_end,
-    lz4f_compress_frame, lz4f_compress_frame_using_cdict, lz4f_compress_update,
-    lz4f_create_compression_context, lz4f_flush, lz4f_free_compression_context,
-    lz4f_uncompressed_update, CompressOptions, LZ4F_MAGIC_NUMBER, LZ4F_VERSION,
+    compress_frame_dest_size, lz4f_compress_begin, lz4f_compress_begin_using_dict,
+    lz4f_compress_begin_with_pledged_size, lz4f_compress_bound, lz4f_compress_end,
+    lz4f_compress_frame, lz4f_compress_frame_to_writer, lz4f_compress_frame_using_cdict,
+    lz4f_compress_update, lz4f_create_compression_context, lz4f_flush,
+    lz4f_free_compression_context, lz4f_uncompressed_update, CompressOptions, LZ4F_MAGIC_NUMBER,
+    LZ4F_VERSION,
 };
 use lz4::frame::decompress::{lz4f_decompress, lz4f_reset_decompression_context, Lz4FDCtx};
 use lz4::frame::header::lz4f_compress_frame_bound;
 use lz4::frame::types::{
-    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, FrameInfo, Lz4FCCtx, Preferences,
-    MAX_FH_SIZE,
+    BlockChecksum, BlockDecision, BlockMode, BlockSizeId, ContentChecksum, FrameInfo, Lz4FCCtx,
+    Lz4FError, Preferences, MAX_FH_SIZE,
 };
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -248,6 +258,94 @@ fn compress_begin_using_dict_dst_too_small() {
     assert!(lz4f_compress_begin_using_dict(&mut cctx, &mut dst, &dict, None).is_err());
 }
 
+/// Streams `data` in 7000-byte updates after beginning with `dict`, then
+/// scribbles over the caller's dictionary before any block is compressed.
+fn stream_with_dict(data: &[u8], dict: &[u8], prefs: &Preferences) -> Vec<u8> {
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut out = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(prefs)) + 64];
+    let mut caller_dict = dict.to_vec();
+    let mut pos =
+        lz4f_compress_begin_using_dict(&mut cctx, &mut out, &caller_dict, Some(prefs)).unwrap();
+    caller_dict.iter_mut().for_each(|b| *b = !*b);
+    drop(caller_dict);
+    for chunk in data.chunks(7000) {
+        pos += lz4f_compress_update(&mut cctx, &mut out[pos..], chunk, None).unwrap();
+    }
+    pos += lz4f_compress_end(&mut cctx, &mut out[pos..], None).unwrap();
+    out.truncate(pos);
+    out
+}
+
+fn decode_with_dict(frame: &[u8], dict: &[u8], capacity: usize) -> Vec<u8> {
+    let mut dctx = Lz4FDCtx::new(LZ4F_VERSION);
+    let mut out = vec![0u8; capacity];
+    let (used, written, hint) =
+        lz4::frame::lz4f_decompress_using_dict(&mut dctx, Some(&mut out), frame, dict, None)
+            .unwrap();
+    assert_eq!((used, hint), (frame.len(), 0));
+    out.truncate(written);
+    out
+}
+
+/// The dictionary is copied at begin and used by later update calls, for
+/// both block modes and both codecs.
+#[test]
+fn compress_begin_using_dict_streams_against_its_own_copy() {
+    let dict: Vec<u8> = (0..100_000u32)
+        .map(|i| ((i * 31 % 253) ^ (i / 500)) as u8)
+        .collect();
+    // Two 64 KB blocks; the first repeats the dictionary's tail.
+    let mut data = dict[dict.len() - 60_000..].to_vec();
+    data.extend(repetitive_bytes(70_000));
+    for level in [1, 9] {
+        for mode in [BlockMode::Linked, BlockMode::Independent] {
+            let mut prefs = Preferences {
+                compression_level: level,
+                ..Default::default()
+            };
+            prefs.frame_info.block_mode = mode;
+            let with_dict = stream_with_dict(&data, &dict, &prefs);
+            let without = stream_with_dict(&data, &[], &prefs);
+            assert!(
+                with_dict.len() * 4 < without.len(),
+                "level {level} {mode:?}: {} vs {}",
+                with_dict.len(),
+                without.len()
+            );
+            assert_eq!(
+                decode_with_dict(&with_dict, &dict, data.len()),
+                data,
+                "level {level} {mode:?}"
+            );
+        }
+    }
+}
+
+/// With independent blocks only the first block sees the dictionary, so a
+/// later block that repeats it gains nothing.
+#[test]
+fn compress_begin_using_dict_independent_uses_dict_for_first_block_only() {
+    let mut seed = 1u32;
+    let dict: Vec<u8> = (0..60_000)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 24) as u8
+        })
+        .collect();
+    let mut prefs = Preferences::default();
+    prefs.frame_info.block_mode = BlockMode::Independent;
+    let first = stream_with_dict(&dict, &dict, &prefs);
+    let mut two_blocks = repetitive_bytes(65_536);
+    two_blocks.extend_from_slice(&dict);
+    let second = stream_with_dict(&two_blocks, &dict, &prefs);
+    assert!(first.len() < 1024, "first block: {}", first.len());
+    assert!(second.len() > 60_000, "second block: {}", second.len());
+    assert_eq!(
+        decode_with_dict(&second, &dict, two_blocks.len()),
+        two_blocks
+    );
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // lz4f_compress_bound
 // ─────────────────────────────────────────────────────────────────────────────
@@ -405,7 +503,10 @@ fn compress_update_stable_src_no_panic() {
     let mut dst = vec![0u8; frame_bound];
     let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
     let mut pos = lz4f_compress_begin(&mut cctx, &mut dst, Some(&prefs)).unwrap();
-    let opts = CompressOptions { stable_src: true };
+    let opts = CompressOptions {
+        stable_src: true,
+        ..Default::default()
+    };
     pos += lz4f_compress_update(&mut cctx, &mut dst[pos..], &src, Some(&opts)).unwrap();
     pos += lz4f_compress_end(&mut cctx, &mut dst[pos..], Some(&opts)).unwrap();
     assert!(pos > 0);
@@ -532,6 +633,37 @@ fn flush_emits_buffered_data() {
     assert!(flush_written > 0, "flush must emit the buffered block");
 }
 
+/// Regression: an HC linked block too small to compress (stored raw) must
+/// stay in the match history, or later blocks encode wrong offsets.
+#[test]
+fn flush_tiny_hc_linked_blocks_round_trip() {
+    let prefs = Preferences {
+        compression_level: 9,
+        auto_flush: false,
+        ..Default::default()
+    };
+    let src = repetitive_bytes(4096);
+    let mut dst = vec![0u8; lz4f_compress_frame_bound(src.len(), Some(&prefs)) * 2];
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut pos = lz4f_compress_begin(&mut cctx, &mut dst, Some(&prefs)).unwrap();
+    // Alternate 4-byte and 60-byte blocks, each flushed on its own.
+    let mut offset = 0;
+    for len in [4usize, 60].into_iter().cycle() {
+        if offset == src.len() {
+            break;
+        }
+        let end = (offset + len).min(src.len());
+        pos += lz4f_compress_update(&mut cctx, &mut dst[pos..], &src[offset..end], None).unwrap();
+        pos += lz4f_flush(&mut cctx, &mut dst[pos..], None).unwrap();
+        offset = end;
+    }
+    pos += lz4f_compress_end(&mut cctx, &mut dst[pos..], None).unwrap();
+    assert_eq!(
+        lz4::frame::decompress_frame_to_vec(&dst[..pos]).unwrap(),
+        src
+    );
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // lz4f_compress_end
 // ─────────────────────────────────────────────────────────────────────────────
@@ -643,6 +775,81 @@ fn compress_end_content_size_mismatch_returns_err() {
     assert!(result.is_err(), "size mismatch must return an error");
 }
 
+/// A short frame is rejected before its end mark is written.
+#[test]
+fn compress_end_short_of_pledge_writes_nothing() {
+    let mut dst = vec![0u8; 1024];
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let pos = lz4f_compress_begin_with_pledged_size(&mut cctx, &mut dst, None, 100).unwrap();
+    lz4f_compress_update(&mut cctx, &mut dst[pos..], b"short", None).unwrap();
+    let mut tail = [0xAAu8; 64];
+    assert_eq!(
+        lz4f_compress_end(&mut cctx, &mut tail, None),
+        Err(Lz4FError::FrameSizeWrong)
+    );
+    assert!(tail.iter().all(|&b| b == 0xAA));
+
+    // The context is ready for a new frame afterwards.
+    let mut pos = lz4f_compress_begin(&mut cctx, &mut dst, None).unwrap();
+    pos += lz4f_compress_update(&mut cctx, &mut dst[pos..], b"fresh", None).unwrap();
+    assert!(lz4f_compress_end(&mut cctx, &mut dst[pos..], None).is_ok());
+}
+
+/// Input beyond the pledge is refused before it is compressed.
+#[test]
+fn compress_update_over_pledge_returns_frame_size_wrong() {
+    let src = repetitive_bytes(1000);
+    let mut dst = vec![0u8; lz4f_compress_frame_bound(src.len(), None) + 64];
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut pos = lz4f_compress_begin_with_pledged_size(&mut cctx, &mut dst, None, 600).unwrap();
+    pos += lz4f_compress_update(&mut cctx, &mut dst[pos..], &src[..500], None).unwrap();
+    assert_eq!(
+        lz4f_compress_update(&mut cctx, &mut dst[pos..], &src[500..], None),
+        Err(Lz4FError::FrameSizeWrong)
+    );
+    assert_eq!(
+        lz4f_uncompressed_update(&mut cctx, &mut dst[pos..], &src[500..], None),
+        Err(Lz4FError::FrameSizeWrong)
+    );
+    // The rejected calls left the frame intact: finishing the pledge works.
+    pos += lz4f_compress_update(&mut cctx, &mut dst[pos..], &src[500..600], None).unwrap();
+    pos += lz4f_compress_end(&mut cctx, &mut dst[pos..], None).unwrap();
+    dst.truncate(pos);
+    assert_eq!(
+        lz4::frame::decompress_frame_to_vec(&dst).unwrap(),
+        &src[..600]
+    );
+}
+
+/// The pledge lands in the header and overrides `prefs.frame_info.content_size`.
+#[test]
+fn pledged_size_is_written_as_content_size() {
+    let src = cycling_bytes(70_000);
+    let prefs = Preferences {
+        frame_info: FrameInfo {
+            content_size: 5,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut dst = vec![0u8; lz4f_compress_frame_bound(src.len(), Some(&prefs)) + 64];
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut pos =
+        lz4f_compress_begin_with_pledged_size(&mut cctx, &mut dst, Some(&prefs), src.len() as u64)
+            .unwrap();
+    for chunk in src.chunks(30_000) {
+        pos += lz4f_compress_update(&mut cctx, &mut dst[pos..], chunk, None).unwrap();
+    }
+    pos += lz4f_compress_end(&mut cctx, &mut dst[pos..], None).unwrap();
+    dst.truncate(pos);
+    assert_eq!(dst[4] & 0x08, 0x08, "content-size flag");
+    assert_eq!(
+        u64::from_le_bytes(dst[6..14].try_into().unwrap()),
+        src.len() as u64
+    );
+    assert_eq!(lz4::frame::decompress_frame_to_vec(&dst).unwrap(), src);
+}
+
 /// Parity: content size correctly declared → compress_end succeeds.
 #[test]
 fn compress_end_content_size_correct_succeeds() {
@@ -851,6 +1058,47 @@ fn compress_frame_using_null_cdict_matches_compress_frame() {
     );
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// lz4f_compress_frame_to_writer
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// The writer variant must emit exactly the bytes of the buffer-based one-shot.
+#[test]
+fn compress_frame_to_writer_matches_compress_frame() {
+    let src = repetitive_bytes(300_000);
+    for block_mode in [BlockMode::Independent, BlockMode::Linked] {
+        let prefs = Preferences {
+            frame_info: FrameInfo {
+                block_mode,
+                block_size_id: BlockSizeId::Max64Kb,
+                content_checksum_flag: ContentChecksum::Enabled,
+                block_checksum_flag: BlockChecksum::Enabled,
+                content_size: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut dst = vec![0u8; lz4f_compress_frame_bound(src.len(), Some(&prefs))];
+        let n = lz4f_compress_frame(&mut dst, &src, Some(&prefs)).unwrap();
+
+        let mut out = Vec::new();
+        let written = lz4f_compress_frame_to_writer(&mut out, &src, Some(&prefs)).unwrap();
+        assert_eq!(written, out.len() as u64);
+        assert_eq!(out, &dst[..n], "{block_mode:?}");
+    }
+}
+
+/// Default preferences and an empty source still produce a decodable frame.
+#[test]
+fn compress_frame_to_writer_default_prefs_round_trip() {
+    for len in [0usize, 1, 70_000] {
+        let src = cycling_bytes(len);
+        let mut out = Vec::new();
+        lz4f_compress_frame_to_writer(&mut out, &src, None).unwrap();
+        assert_eq!(lz4::frame::decompress_frame_to_vec(&out).unwrap(), src);
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Streaming vs one-shot parity
 // ─────────────────────────────────────────────────────────────────────────────
@@ -877,7 +1125,10 @@ fn streaming_single_update_matches_one_shot() {
 
     let mut streaming = vec![0u8; bound];
     let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
-    let opts = CompressOptions { stable_src: true };
+    let opts = CompressOptions {
+        stable_src: true,
+        ..Default::default()
+    };
     let mut pos = lz4f_compress_begin(&mut cctx, &mut streaming, Some(&prefs)).unwrap();
     pos += lz4f_compress_update(&mut cctx, &mut streaming[pos..], &src, Some(&opts)).unwrap();
     pos += lz4f_compress_end(&mut cctx, &mut streaming[pos..], Some(&opts)).unwrap();
@@ -1442,3 +1693,604 @@ fn hc_linked_blocks_multiblock() {
     let (_, dw, _) = lz4f_decompress(&mut dctx, Some(&mut out), &dst[..written], None).unwrap();
     assert_eq!(&out[..dw], &all_data[..]);
 }
+
+/// `Preferences::compression_attempts` overrides the HC search budget for
+/// both linked and independent blocks; 0 keeps the level default.
+#[test]
+fn hc_compression_attempts_changes_ratio() {
+    let data = lz4::lorem::gen_buffer(300_000, 5);
+    for block_mode in [BlockMode::Linked, BlockMode::Independent] {
+        let frame = |attempts: u32| {
+            let prefs = Preferences {
+                frame_info: FrameInfo {
+                    block_mode,
+                    block_size_id: BlockSizeId::Max64Kb,
+                    ..Default::default()
+                },
+                compression_level: 11,
+                compression_attempts: attempts,
+                ..Default::default()
+            };
+            let mut dst = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(&prefs))];
+            let n = lz4f_compress_frame(&mut dst, &data, Some(&prefs)).unwrap();
+            dst.truncate(n);
+            assert_eq!(lz4::frame::decompress_frame_to_vec(&dst).unwrap(), data);
+            dst.len()
+        };
+        let default = frame(0);
+        assert!(frame(1) > default, "{block_mode:?}");
+        assert!(frame(4096) <= default, "{block_mode:?}");
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// CompressOptions::pad_to
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn padding_size_reaches_next_multiple() {
+    use lz4::frame::compress::lz4f_padding_size;
+    assert_eq!(lz4f_padding_size(4096, 4096), 0);
+    assert_eq!(lz4f_padding_size(100, 4096), 3996);
+    // Fewer than 8 bytes short: a whole extra alignment unit is added.
+    assert_eq!(lz4f_padding_size(4095, 4096), 4097);
+    assert_eq!(lz4f_padding_size(5, 2), 9);
+    assert_eq!(lz4f_padding_size(123, 0), 0);
+    assert_eq!(lz4f_padding_size(123, 1), 0);
+}
+
+#[test]
+fn pad_to_aligns_streamed_frame() {
+    use lz4::frame::compress::LZ4F_PADDING_NIBBLE;
+    let src = repetitive_bytes(300_000);
+    let prefs = Preferences {
+        frame_info: FrameInfo {
+            block_size_id: BlockSizeId::Max64Kb,
+            content_checksum_flag: ContentChecksum::Enabled,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    for alignment in [512usize, 4096, 1 << 20] {
+        let mut dst = vec![0u8; lz4f_compress_bound(src.len(), Some(&prefs)) + alignment + 7];
+        let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+        let opts = CompressOptions::default().pad_to(alignment);
+        let mut pos = lz4f_compress_begin(&mut cctx, &mut dst, Some(&prefs)).unwrap();
+        for chunk in src.chunks(70_000) {
+            pos += lz4f_compress_update(&mut cctx, &mut dst[pos..], chunk, Some(&opts)).unwrap();
+        }
+        pos += lz4f_flush(&mut cctx, &mut dst[pos..], Some(&opts)).unwrap();
+        let frame_end = pos;
+        pos += lz4f_compress_end(&mut cctx, &mut dst[pos..], Some(&opts)).unwrap();
+        assert_eq!(pos % alignment, 0, "alignment {alignment}");
+
+        // The padding is one skippable frame, ignored by the decoder.
+        let data_end = (frame_end..pos)
+            .find(|&i| dst[i..i + 4] == [0x5F, 0x2A, 0x4D, 0x18])
+            .expect("padding frame");
+        let pad = lz4::frame::read_skippable_frame(&dst[data_end..pos]).unwrap();
+        assert_eq!(pad.user_magic_nibble, LZ4F_PADDING_NIBBLE);
+        assert_eq!(data_end + pad.frame_size(), pos);
+        assert!(pad.payload.iter().all(|&b| b == 0));
+        let mut dctx = Lz4FDCtx::new(LZ4F_VERSION);
+        let mut out = vec![0u8; src.len()];
+        let (consumed, written, hint) =
+            lz4f_decompress(&mut dctx, Some(&mut out), &dst[..pos], None).unwrap();
+        assert_eq!((consumed, written, hint), (data_end, src.len(), 0));
+        assert_eq!(out, src);
+        let (consumed, written, hint) =
+            lz4f_decompress(&mut dctx, Some(&mut out), &dst[data_end..pos], None).unwrap();
+        assert_eq!((consumed, written, hint), (pos - data_end, 0, 0));
+    }
+}
+
+#[test]
+fn pad_to_without_room_fails() {
+    let src = repetitive_bytes(1000);
+    let mut dst = vec![0u8; lz4f_compress_bound(src.len(), None) + 8192];
+    let start = |cctx: &mut Lz4FCCtx, dst: &mut [u8]| {
+        let pos = lz4f_compress_begin(cctx, dst, None).unwrap();
+        pos + lz4f_compress_update(cctx, &mut dst[pos..], &src, None).unwrap()
+    };
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let pos = start(&mut cctx, &mut dst);
+    let unpadded_end = lz4f_compress_end(&mut cctx, &mut dst[pos..], None).unwrap();
+
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let pos = start(&mut cctx, &mut dst);
+    let opts = CompressOptions::default().pad_to(4096);
+    assert_eq!(
+        lz4f_compress_end(
+            &mut cctx,
+            &mut dst[pos..pos + unpadded_end + 100],
+            Some(&opts)
+        ),
+        Err(lz4::frame::Lz4FError::DstMaxSizeTooSmall)
+    );
+}
+
+#[test]
+fn pad_to_without_room_keeps_buffered_block() {
+    use lz4::frame::lz4f_compress_bound_padded;
+    let src = repetitive_bytes(1000);
+    let opts = CompressOptions::default().pad_to(4096);
+    let mut dst = vec![0u8; lz4f_compress_bound_padded(src.len(), None, 4096)];
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut pos = lz4f_compress_begin(&mut cctx, &mut dst, None).unwrap();
+    pos += lz4f_compress_update(&mut cctx, &mut dst[pos..], &src, None).unwrap();
+
+    // Too small for the padding: nothing is flushed, so a retry still works.
+    assert_eq!(
+        lz4f_compress_end(&mut cctx, &mut dst[pos..pos + 1100], Some(&opts)),
+        Err(lz4::frame::Lz4FError::DstMaxSizeTooSmall)
+    );
+    pos += lz4f_compress_end(&mut cctx, &mut dst[pos..], Some(&opts)).unwrap();
+    assert_eq!(pos % 4096, 0);
+    assert_eq!(
+        lz4::frame::decompress_frame_to_vec(&dst[..pos]).unwrap(),
+        src
+    );
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Preferences::archival
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Assert the FLG/BD bytes and content-size field carry every archival flag.
+fn assert_archival_header(frame: &[u8], content_size: u64) {
+    assert_eq!(&frame[..4], &LZ4F_MAGIC_NUMBER.to_le_bytes());
+    let flg = frame[4];
+    assert_eq!(flg >> 6, 0b01, "version");
+    assert_ne!(flg & 0x20, 0, "block independence");
+    assert_ne!(flg & 0x10, 0, "block checksum");
+    assert_ne!(flg & 0x08, 0, "content size");
+    assert_ne!(flg & 0x04, 0, "content checksum");
+    assert_eq!(flg & 0x01, 0, "dict id");
+    assert_eq!(frame[5], 0x50, "BD: 256 KB blocks");
+    let size = u64::from_le_bytes(frame[6..14].try_into().unwrap());
+    assert_eq!(size, content_size);
+}
+
+#[test]
+fn archival_preset_fields() {
+    let p = Preferences::archival();
+    assert_eq!(p.frame_info.block_size_id, BlockSizeId::Max256Kb);
+    assert_eq!(p.frame_info.block_mode, BlockMode::Independent);
+    assert_eq!(p.frame_info.content_checksum_flag, ContentChecksum::Enabled);
+    assert_eq!(p.frame_info.block_checksum_flag, BlockChecksum::Enabled);
+    assert_eq!(p.frame_info.content_size, 0, "the caller sets the size");
+    assert_eq!(p.compression_level, 0);
+}
+
+#[test]
+fn archival_preset_one_shot_header() {
+    let src = repetitive_bytes(600 * 1024);
+    let mut prefs = Preferences::archival();
+    prefs.frame_info.content_size = src.len() as u64;
+    let mut dst = vec![0u8; lz4f_compress_frame_bound(src.len(), Some(&prefs))];
+    let n = lz4f_compress_frame(&mut dst, &src, Some(&prefs)).unwrap();
+    assert_archival_header(&dst[..n], src.len() as u64);
+
+    let out = lz4::frame::decompress_frame_to_vec(&dst[..n]).unwrap();
+    assert_eq!(out, src);
+}
+
+#[test]
+fn archival_for_records_every_flag() {
+    let src = repetitive_bytes(300 * 1024);
+    let prefs = Preferences::archival_for(src.len() as u64);
+    assert_eq!(prefs.frame_info.content_size, src.len() as u64);
+    let mut dst = vec![0u8; lz4f_compress_frame_bound(src.len(), Some(&prefs))];
+    let n = lz4f_compress_frame(&mut dst, &src, Some(&prefs)).unwrap();
+    assert_archival_header(&dst[..n], src.len() as u64);
+    assert_eq!(lz4::frame::decompress_frame_to_vec(&dst[..n]).unwrap(), src);
+}
+
+#[test]
+fn archival_preset_streaming_header() {
+    let src = cycling_bytes(100 * 1024);
+    let prefs = Preferences::archival();
+    let mut dst = vec![0u8; lz4f_compress_bound(src.len(), Some(&prefs)) + MAX_FH_SIZE];
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut pos =
+        lz4f_compress_begin_with_pledged_size(&mut cctx, &mut dst, Some(&prefs), src.len() as u64)
+            .unwrap();
+    pos += lz4f_compress_update(&mut cctx, &mut dst[pos..], &src, None).unwrap();
+    pos += lz4f_compress_end(&mut cctx, &mut dst[pos..], None).unwrap();
+    assert_archival_header(&dst[..pos], src.len() as u64);
+
+    let out = lz4::frame::decompress_frame_to_vec(&dst[..pos]).unwrap();
+    assert_eq!(out, src);
+}
+
+#[test]
+fn archival_preset_streams_without_a_size() {
+    let src = cycling_bytes(100 * 1024);
+    let prefs = Preferences::archival();
+    let mut dst = vec![0u8; lz4f_compress_bound(src.len(), Some(&prefs)) + MAX_FH_SIZE];
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut pos = lz4f_compress_begin(&mut cctx, &mut dst, Some(&prefs)).unwrap();
+    pos += lz4f_compress_update(&mut cctx, &mut dst[pos..], &src, None).unwrap();
+    pos += lz4f_compress_end(&mut cctx, &mut dst[pos..], None).unwrap();
+
+    let out = lz4::frame::decompress_frame_to_vec(&dst[..pos]).unwrap();
+    assert_eq!(out, src);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// compress_frame_dest_size
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// `favor_dec_speed` changes the frames the optimal parser (levels 10–12)
+/// emits, for linked and independent blocks, and leaves level 9 alone.
+#[test]
+fn favor_dec_speed_reaches_optimal_parser() {
+    let src = lz4::corpus::log_lines(300_000, 7);
+    let frame = |level: i32, block_mode: BlockMode, favor_dec_speed: bool| {
+        let prefs = Preferences {
+            frame_info: FrameInfo {
+                block_mode,
+                block_size_id: BlockSizeId::Max64Kb,
+                ..Default::default()
+            },
+            compression_level: level,
+            favor_dec_speed,
+            ..Default::default()
+        };
+        let mut dst = default_dst(src.len());
+        let n = lz4f_compress_frame(&mut dst, &src, Some(&prefs)).unwrap();
+        dst.truncate(n);
+        assert_eq!(lz4::frame::decompress_frame_to_vec(&dst).unwrap(), src);
+        dst
+    };
+    for block_mode in [BlockMode::Linked, BlockMode::Independent] {
+        for level in [10, 12] {
+            assert_ne!(
+                frame(level, block_mode, false),
+                frame(level, block_mode, true),
+                "{block_mode:?} level {level}"
+            );
+        }
+        assert_eq!(frame(9, block_mode, false), frame(9, block_mode, true));
+    }
+}
+
+/// Every packet-sized budget yields a valid frame of at most that size that
+/// decodes to the reported prefix.
+#[test]
+fn dest_size_frame_fits_and_decodes_to_prefix() {
+    let src = repetitive_bytes(300 * 1024);
+    for budget in [19, 20, 64, 1400, 9000, 70_000] {
+        let mut dst = vec![0u8; budget];
+        let (consumed, written) = compress_frame_dest_size(&mut dst, &src, None).unwrap();
+        assert!(written <= budget, "{budget}");
+        assert!(consumed > 0, "{budget}");
+        let out = lz4::frame::decompress_frame_to_vec(&dst[..written]).unwrap();
+        assert_eq!(out, &src[..consumed], "{budget}");
+    }
+}
+
+/// Compressible input packs far more than the budget; a packet-sized frame
+/// is filled to within a few bytes.
+#[test]
+fn dest_size_fills_the_budget() {
+    let src = repetitive_bytes(1 << 20);
+    let mut dst = vec![0u8; 1400];
+    let (consumed, written) = compress_frame_dest_size(&mut dst, &src, None).unwrap();
+    assert!(consumed > 10 * 1400, "{consumed}");
+    assert!(written >= 1400 - 16, "{written}");
+}
+
+/// Input that fits whole produces the same content as `lz4f_compress_frame`.
+#[test]
+fn dest_size_consumes_everything_when_it_fits() {
+    let src = repetitive_bytes(200 * 1024);
+    let mut dst = default_dst(src.len());
+    let (consumed, written) = compress_frame_dest_size(&mut dst, &src, None).unwrap();
+    assert_eq!(consumed, src.len());
+    let out = lz4::frame::decompress_frame_to_vec(&dst[..written]).unwrap();
+    assert_eq!(out, src);
+}
+
+/// Incompressible data is stored, so the frame still carries nearly a full
+/// budget of input.
+#[test]
+fn dest_size_stores_incompressible_blocks() {
+    let mut x: u32 = 7;
+    let src: Vec<u8> = (0..100_000)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            x as u8
+        })
+        .collect();
+    let mut dst = vec![0u8; 5000];
+    let (consumed, written) = compress_frame_dest_size(&mut dst, &src, None).unwrap();
+    assert_eq!(written, 5000);
+    assert_eq!(consumed, 5000 - 7 - 4 - 4);
+    assert_eq!(dst[7 + 3] & 0x80, 0x80, "stored block");
+    let out = lz4::frame::decompress_frame_to_vec(&dst[..written]).unwrap();
+    assert_eq!(out, &src[..consumed]);
+}
+
+/// Checksums, content size and HC levels are honoured; the content size is
+/// the consumed length.
+#[test]
+fn dest_size_honours_prefs() {
+    let src = repetitive_bytes(1 << 20);
+    for level in [0, 9] {
+        let prefs = Preferences {
+            frame_info: FrameInfo {
+                block_size_id: BlockSizeId::Max64Kb,
+                block_mode: BlockMode::Linked,
+                block_checksum_flag: BlockChecksum::Enabled,
+                content_checksum_flag: ContentChecksum::Enabled,
+                content_size: 1,
+                ..FrameInfo::default()
+            },
+            compression_level: level,
+            ..Preferences::default()
+        };
+        let mut dst = vec![0u8; 3000];
+        let (consumed, written) = compress_frame_dest_size(&mut dst, &src, Some(&prefs)).unwrap();
+        assert!(written <= 3000);
+        let flg = dst[4];
+        assert_ne!(flg & 0x20, 0, "blocks are independent");
+        assert_eq!(flg & 0x1C, 0x1C, "checksums and content size");
+        let size = u64::from_le_bytes(dst[6..14].try_into().unwrap());
+        assert_eq!(size, consumed as u64);
+        let out = lz4::frame::decompress_frame_to_vec(&dst[..written]).unwrap();
+        assert_eq!(out, &src[..consumed], "level {level}");
+    }
+}
+
+#[test]
+fn dest_size_edge_cases() {
+    // Too small for an empty frame: 7-byte header plus end mark.
+    assert_eq!(
+        compress_frame_dest_size(&mut [0u8; 10], b"abc", None),
+        Err(Lz4FError::DstMaxSizeTooSmall)
+    );
+    // Room for an empty frame only, even with a content size requested.
+    let prefs = Preferences {
+        frame_info: FrameInfo {
+            content_size: 1,
+            ..FrameInfo::default()
+        },
+        ..Preferences::default()
+    };
+    let mut dst = [0u8; 19];
+    let (consumed, written) = compress_frame_dest_size(&mut dst, b"", Some(&prefs)).unwrap();
+    assert_eq!((consumed, written), (0, 11));
+    assert!(lz4::frame::decompress_frame_to_vec(&dst[..written])
+        .unwrap()
+        .is_empty());
+    // A shortened window cannot be honoured.
+    let prefs = Preferences {
+        max_distance: 4096,
+        ..Preferences::default()
+    };
+    assert_eq!(
+        compress_frame_dest_size(&mut [0u8; 100], b"abc", Some(&prefs)),
+        Err(Lz4FError::ParameterInvalid)
+    );
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Block filter
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Compress `src` as one frame through `cctx`, returning the frame bytes.
+fn frame_with(cctx: &mut Lz4FCCtx, src: &[u8], prefs: &Preferences) -> Vec<u8> {
+    let mut dst = vec![0u8; lz4f_compress_frame_bound(src.len(), Some(prefs))];
+    let mut pos = lz4f_compress_begin(cctx, &mut dst, Some(prefs)).expect("begin");
+    pos += lz4f_compress_update(cctx, &mut dst[pos..], src, None).expect("update");
+    pos += lz4f_compress_end(cctx, &mut dst[pos..], None).expect("end");
+    dst.truncate(pos);
+    dst
+}
+
+fn prefs_64k(block_mode: BlockMode, content_checksum_flag: ContentChecksum) -> Preferences {
+    Preferences {
+        frame_info: FrameInfo {
+            block_size_id: BlockSizeId::Max64Kb,
+            block_mode,
+            content_checksum_flag,
+            block_checksum_flag: BlockChecksum::Enabled,
+            ..FrameInfo::default()
+        },
+        ..Preferences::default()
+    }
+}
+
+/// The filter sees every block with its candidate, and `Store` keeps the
+/// input verbatim in a frame that still decodes.
+#[test]
+fn block_filter_store_forces_uncompressed_blocks() {
+    let src = repetitive_bytes(200 * 1024);
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut cctx = lz4f_create_compression_context(LZ4F_VERSION).unwrap();
+    let log = seen.clone();
+    cctx.set_block_filter(move |block, candidate| {
+        log.lock()
+            .unwrap()
+            .push((block.len(), candidate.map(<[u8]>::len)));
+        BlockDecision::Store
+    });
+    let prefs = prefs_64k(BlockMode::Linked, ContentChecksum::Enabled);
+    let frame = frame_with(&mut cctx, &src, &prefs);
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 4);
+    assert!(seen.iter().all(|&(_, c)| c.is_some_and(|n| n < 4096)));
+    assert!(frame.len() > src.len(), "all blocks stored");
+    assert_eq!(lz4::frame::decompress_frame_to_vec(&frame).unwrap(), src);
+}
+
+/// `Skip` drops blocks from independent frames without a content checksum,
+/// and degrades to `Store` where dropping would break the frame.
+#[test]
+fn block_filter_skip() {
+    let src = repetitive_bytes(4 * 64 * 1024);
+    let mut cctx = lz4f_create_compression_context(LZ4F_VERSION).unwrap();
+    let mut n = 0;
+    cctx.set_block_filter(move |_, _| {
+        n += 1;
+        if n % 2 == 0 {
+            BlockDecision::Skip
+        } else {
+            BlockDecision::Auto
+        }
+    });
+
+    let prefs = prefs_64k(BlockMode::Independent, ContentChecksum::Disabled);
+    let frame = frame_with(&mut cctx, &src, &prefs);
+    let out = lz4::frame::decompress_frame_to_vec(&frame).unwrap();
+    assert_eq!(out.len(), 2 * 64 * 1024);
+
+    for (mode, checksum) in [
+        (BlockMode::Linked, ContentChecksum::Disabled),
+        (BlockMode::Independent, ContentChecksum::Enabled),
+    ] {
+        let frame = frame_with(&mut cctx, &src, &prefs_64k(mode, checksum));
+        assert_eq!(lz4::frame::decompress_frame_to_vec(&frame).unwrap(), src);
+    }
+
+    cctx.clear_block_filter();
+    assert!(frame_with(&mut cctx, &src, &prefs).len() < src.len() / 10);
+}
+
+/// The fixed-size encoder honours `favor_dec_speed` as well.
+#[test]
+fn dest_size_honours_favor_dec_speed() {
+    let src = lz4::corpus::log_lines(200_000, 3);
+    let pack = |favor_dec_speed: bool| {
+        let prefs = Preferences {
+            compression_level: 12,
+            favor_dec_speed,
+            ..Default::default()
+        };
+        let mut dst = vec![0u8; 20_000];
+        let (consumed, written) = compress_frame_dest_size(&mut dst, &src, Some(&prefs)).unwrap();
+        dst.truncate(written);
+        let out = lz4::frame::decompress_frame_to_vec(&dst).unwrap();
+        assert_eq!(out, &src[..consumed]);
+        dst
+    };
+    assert_ne!(pack(false), pack(true));
+}
+
+/// A reset abandons the frame in progress: the next frame is identical to
+/// one from a fresh context, and updates before it starts are refused.
+#[test]
+fn reset_for_reuse_abandons_frame_in_progress() {
+    let src = lz4::corpus::log_lines(300_000, 5);
+    let mut dict_cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    for (mode, level) in [(BlockMode::Linked, 1), (BlockMode::Independent, 9)] {
+        let mut prefs = prefs_64k(mode, ContentChecksum::Enabled);
+        prefs.compression_level = level;
+        let expected = frame_with(&mut Lz4FCCtx::new(LZ4F_VERSION), &src, &prefs);
+
+        let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+        let mut dst = vec![0u8; lz4f_compress_frame_bound(src.len(), Some(&prefs))];
+        let pos = lz4f_compress_begin(&mut cctx, &mut dst, Some(&prefs)).unwrap();
+        lz4f_compress_update(&mut cctx, &mut dst[pos..], &src[..100_000], None).unwrap();
+        cctx.reset_for_reuse(&prefs).unwrap();
+        assert_eq!(
+            lz4f_compress_update(&mut cctx, &mut dst, &src[..10], None),
+            Err(Lz4FError::CompressionStateUninitialized)
+        );
+        assert_eq!(
+            frame_with(&mut cctx, &src, &prefs),
+            expected,
+            "{mode:?}/{level}"
+        );
+
+        // A dictionary from an abandoned frame does not leak into the next.
+        let pos =
+            lz4f_compress_begin_using_dict(&mut dict_cctx, &mut dst, &src[..4096], Some(&prefs))
+                .unwrap();
+        lz4f_compress_update(&mut dict_cctx, &mut dst[pos..], &src[..1000], None).unwrap();
+        dict_cctx.reset_for_reuse(&prefs).unwrap();
+        assert_eq!(
+            frame_with(&mut dict_cctx, &src, &prefs),
+            expected,
+            "{mode:?}/{level}"
+        );
+    }
+}
+
+/// Begins a frame on `cctx` and feeds it `src`, leaving it open.
+fn open_frame(cctx: &mut Lz4FCCtx, dst: &mut [u8], src: &[u8]) -> usize {
+    let prefs = prefs_64k(BlockMode::Linked, ContentChecksum::Enabled);
+    let pos = lz4f_compress_begin(cctx, dst, Some(&prefs)).unwrap();
+    pos + lz4f_compress_update(cctx, &mut dst[pos..], src, None).unwrap()
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "lz4f_compress_update called after lz4f_compress_end finished frame #2")]
+fn update_after_end_panics_in_debug() {
+    let src = cycling_bytes(1000);
+    let prefs = prefs_64k(BlockMode::Linked, ContentChecksum::Enabled);
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    frame_with(&mut cctx, &src, &prefs);
+    frame_with(&mut cctx, &src, &prefs);
+    let mut dst = vec![0u8; 4096];
+    let _ = lz4f_compress_update(&mut cctx, &mut dst, &src, None);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "lz4f_compress_end called after lz4f_compress_end finished frame #1")]
+fn end_after_end_panics_in_debug() {
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut dst = vec![0u8; 4096];
+    let pos = open_frame(&mut cctx, &mut dst, &cycling_bytes(1000));
+    let pos = pos + lz4f_compress_end(&mut cctx, &mut dst[pos..], None).unwrap();
+    let _ = lz4f_compress_end(&mut cctx, &mut dst[pos..], None);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(
+    expected = "lz4f_compress_begin called while frame #1 on this context is still open (1000 bytes in)"
+)]
+fn interleaved_begin_panics_in_debug() {
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut first = vec![0u8; 4096];
+    open_frame(&mut cctx, &mut first, &cycling_bytes(1000));
+    let mut second = vec![0u8; 4096];
+    let _ = lz4f_compress_begin(&mut cctx, &mut second, None);
+}
+
+/// Sequences the debug checks must not flag: a begin that replaces an empty
+/// frame, a begin after a failed call, a begin after `reset_for_reuse`, and
+/// updates refused before any begin.
+#[test]
+fn legitimate_context_reuse_does_not_panic() {
+    let src = cycling_bytes(1000);
+    let prefs = prefs_64k(BlockMode::Linked, ContentChecksum::Enabled);
+    let expected = frame_with(&mut Lz4FCCtx::new(LZ4F_VERSION), &src, &prefs);
+    let mut dst = vec![0u8; 4096];
+
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    assert_eq!(
+        lz4f_compress_update(&mut cctx, &mut dst, &src, None),
+        Err(Lz4FError::CompressionStateUninitialized)
+    );
+    lz4f_compress_begin(&mut cctx, &mut dst, Some(&prefs)).unwrap();
+    assert_eq!(frame_with(&mut cctx, &src, &prefs), expected);
+
+    open_frame(&mut cctx, &mut dst, &src);
+    assert_eq!(
+        lz4f_compress_update(&mut cctx, &mut [0u8; 8], &cycling_bytes(100_000), None),
+        Err(Lz4FError::DstMaxSizeTooSmall)
+    );
+    assert_eq!(frame_with(&mut cctx, &src, &prefs), expected);
+
+    open_frame(&mut cctx, &mut dst, &src);
+    cctx.reset_for_reuse(&prefs).unwrap();
+    assert_eq!(frame_with(&mut cctx, &src, &prefs), expected);
+}
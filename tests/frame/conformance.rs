@@ -0,0 +1,116 @@
+// Tests for conformance — frame-format conformance vectors
+//
+//   - the in-memory corpus passes its own checker
+//   - write_vectors / verify_dir round trip through a directory
+//   - the header really carries each requested option (block size included)
+//   - vectors without a dictionary decode with the plain one-shot API
+//   - tampered, renamed and missing vectors are reported
+
+use lz4::conformance::{
+    self, check_vector, content, dictionary, encode, verify_dir, write_vectors, VectorError,
+    VectorSpec, CONTENT_FILE, DICT_ID,
+};
+use lz4::frame::types::LZ4F_VERSION;
+use lz4::frame::{decompress_frame_to_vec, lz4f_get_frame_info, Lz4FDCtx};
+
+#[test]
+fn self_test_passes() {
+    let report = conformance::self_test();
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(report.passed, 256);
+}
+
+#[test]
+fn written_vectors_verify() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(write_vectors(dir.path()).unwrap(), 256);
+    let report = verify_dir(dir.path()).unwrap();
+    assert!(report.is_ok(), "{:?} {:?}", report.failed, report.missing);
+    assert_eq!(report.passed, 256);
+}
+
+#[test]
+fn headers_carry_the_requested_options() {
+    let (content, dict) = (content(), dictionary());
+    for spec in VectorSpec::all().into_iter().filter(|s| !s.skippable) {
+        let frame = encode(&spec, &content, &dict).unwrap();
+        let mut dctx = Lz4FDCtx::new(LZ4F_VERSION);
+        let (info, _, _) = lz4f_get_frame_info(&mut dctx, &frame).unwrap();
+        assert_eq!(info.block_size_id, spec.block_size_id, "{}", spec.name());
+        assert_eq!(info.block_mode, spec.block_mode, "{}", spec.name());
+        assert_eq!(info.dict_id, if spec.dict { DICT_ID } else { 0 });
+        if !spec.dict {
+            assert_eq!(decompress_frame_to_vec(&frame).unwrap(), content);
+        }
+    }
+}
+
+#[test]
+fn checker_reports_bad_vectors() {
+    let dir = tempfile::tempdir().unwrap();
+    write_vectors(dir.path()).unwrap();
+    let path = |name: &str| dir.path().join(format!("{name}.lz4"));
+
+    // A 64 KB vector stored under a 256 KB name.
+    std::fs::rename(
+        path("b5-linked-bc0-cc1-sz0-dict0-skip0"),
+        dir.path().join("spare"),
+    )
+    .unwrap();
+    std::fs::copy(
+        path("b4-linked-bc0-cc1-sz0-dict0-skip0"),
+        path("b5-linked-bc0-cc1-sz0-dict0-skip0"),
+    )
+    .unwrap();
+    // A corrupted payload byte, caught by the content checksum.
+    let victim = path("b4-indep-bc0-cc1-sz1-dict1-skip0");
+    let mut bytes = std::fs::read(&victim).unwrap();
+    let mid = bytes.len() / 2;
+    bytes[mid] ^= 0x40;
+    std::fs::write(&victim, bytes).unwrap();
+    // A vector that is gone.
+    std::fs::remove_file(path("b7-indep-bc1-cc1-sz1-dict1-skip1")).unwrap();
+
+    let report = verify_dir(dir.path()).unwrap();
+    assert_eq!(report.passed, 253);
+    assert_eq!(report.missing, ["b7-indep-bc1-cc1-sz1-dict1-skip1"]);
+    assert_eq!(report.failed.len(), 2);
+    assert!(report.failed.contains(&(
+        "b5-linked-bc0-cc1-sz0-dict0-skip0".to_owned(),
+        VectorError::Header {
+            field: "block size"
+        }
+    )));
+    assert!(report
+        .failed
+        .iter()
+        .any(|(name, _)| name == "b4-indep-bc0-cc1-sz1-dict1-skip0"));
+}
+
+#[test]
+fn skippable_prefix_must_match_the_name() {
+    let (content, dict) = (content(), dictionary());
+    let spec = VectorSpec::parse("b4-linked-bc0-cc0-sz0-dict0-skip1").unwrap();
+    let with_skip = encode(&spec, &content, &dict).unwrap();
+    let plain = VectorSpec {
+        skippable: false,
+        ..spec
+    };
+    assert_eq!(
+        check_vector(&plain, &with_skip, &content, &dict),
+        Err(VectorError::Skippable)
+    );
+    let without_skip = encode(&plain, &content, &dict).unwrap();
+    assert_eq!(
+        check_vector(&spec, &without_skip, &content, &dict),
+        Err(VectorError::Skippable)
+    );
+}
+
+#[test]
+fn verify_dir_needs_the_content_file() {
+    let dir = tempfile::tempdir().unwrap();
+    write_vectors(dir.path()).unwrap();
+    std::fs::remove_file(dir.path().join(CONTENT_FILE)).unwrap();
+    assert!(verify_dir(dir.path()).is_err());
+}
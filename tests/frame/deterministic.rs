@@ -0,0 +1,211 @@
+// Tests for `Preferences::deterministic`.
+//
+// A deterministic frame must depend only on the input and the preferences:
+// not on how the input is split across `compress_update` calls, on
+// `stable_src`, or on what the context compressed before.  The golden
+// digests pin the exact bytes on 64-bit little-endian targets; apart from
+// level 2, whose mid-strategy encoder is new in 1.10, they are the bytes the
+// reference `lz4` 1.9.4 CLI writes for `-<level> -B4 [-BD] --no-frame-crc`.
+
+use lz4::frame::compress::{
+    lz4f_compress_begin, lz4f_compress_bound, lz4f_compress_end, lz4f_compress_update,
+    lz4f_create_compression_context, CompressOptions, LZ4F_VERSION,
+};
+use lz4::frame::decompress_frame_to_vec;
+use lz4::frame::types::{BlockMode, BlockSizeId, FrameInfo, Lz4FCCtx, Preferences};
+use lz4::xxhash::xxh32_oneshot;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Helpers
+// ─────────────────────────────────────────────────────────────────────────────
+
+const LEVELS: [i32; 7] = [-4, 0, 1, 2, 3, 9, 12];
+
+/// 600 KB of mixed text and noise: compressible, with long- and short-range
+/// matches across 64 KB block boundaries.
+fn corpus() -> Vec<u8> {
+    let mut x = 0x2545_f491u32;
+    let mut out = Vec::with_capacity(600 * 1024);
+    let mut i = 0u32;
+    while out.len() < 600 * 1024 {
+        x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        if (x >> 16).is_multiple_of(7) {
+            out.extend_from_slice(&x.to_le_bytes());
+        } else {
+            out.extend_from_slice(format!("k{}={};", (x >> 20) % 977, i % 13).as_bytes());
+        }
+        i += 1;
+    }
+    out.truncate(600 * 1024);
+    out
+}
+
+fn prefs(level: i32, block_mode: BlockMode) -> Preferences {
+    Preferences {
+        frame_info: FrameInfo {
+            block_size_id: BlockSizeId::Max64Kb,
+            block_mode,
+            ..Default::default()
+        },
+        compression_level: level,
+        auto_flush: true,
+        deterministic: true,
+        ..Default::default()
+    }
+}
+
+/// Compress `src` with `cctx`, feeding it in chunks cycling through `chunks`.
+fn compress_chunked(
+    cctx: &mut Lz4FCCtx,
+    src: &[u8],
+    prefs: &Preferences,
+    chunks: &[usize],
+    stable_src: bool,
+) -> Vec<u8> {
+    let opts = CompressOptions {
+        stable_src,
+        ..Default::default()
+    };
+    let mut out = vec![0u8; 32];
+    let n = lz4f_compress_begin(cctx, &mut out, Some(prefs)).unwrap();
+    out.truncate(n);
+    let mut pos = 0;
+    for &chunk in chunks.iter().cycle() {
+        if pos == src.len() {
+            break;
+        }
+        let chunk = chunk.min(src.len() - pos);
+        let mut buf = vec![0u8; lz4f_compress_bound(chunk, Some(prefs))];
+        let n = lz4f_compress_update(cctx, &mut buf, &src[pos..pos + chunk], Some(&opts)).unwrap();
+        out.extend_from_slice(&buf[..n]);
+        pos += chunk;
+    }
+    let mut buf = vec![0u8; lz4f_compress_bound(0, Some(prefs))];
+    let n = lz4f_compress_end(cctx, &mut buf, None).unwrap();
+    out.extend_from_slice(&buf[..n]);
+    out
+}
+
+fn compress_fresh(src: &[u8], prefs: &Preferences, chunks: &[usize], stable_src: bool) -> Vec<u8> {
+    let mut cctx = lz4f_create_compression_context(LZ4F_VERSION).unwrap();
+    compress_chunked(&mut cctx, src, prefs, chunks, stable_src)
+}
+
+// ═════════════════════════════════════════════════════════════════════════════
+// Golden outputs
+// ═════════════════════════════════════════════════════════════════════════════
+
+/// XXH32 of the frame for each level, with linked blocks.
+const GOLDEN_LINKED: [(i32, u32); 7] = [
+    (-4, 0x8b70_b24b),
+    (0, 0xddc9_ffc5),
+    (1, 0xddc9_ffc5),
+    (2, 0xa85a_8f33),
+    (3, 0xf12c_acc4),
+    (9, 0x3cb7_1c43),
+    (12, 0x4268_651f),
+];
+
+/// XXH32 of the frame for each level, with independent blocks.
+const GOLDEN_INDEPENDENT: [(i32, u32); 7] = [
+    (-4, 0x8e1f_afd6),
+    (0, 0xe2f6_f20e),
+    (1, 0xe2f6_f20e),
+    (2, 0x4457_9e58),
+    (3, 0x9f58_7dfe),
+    (9, 0x5862_6d5a),
+    (12, 0x7442_86ce),
+];
+
+#[cfg(all(target_pointer_width = "64", target_endian = "little"))]
+#[test]
+fn golden_outputs() {
+    let src = corpus();
+    for (mode, goldens) in [
+        (BlockMode::Linked, GOLDEN_LINKED),
+        (BlockMode::Independent, GOLDEN_INDEPENDENT),
+    ] {
+        for (level, digest) in goldens {
+            let frame = compress_fresh(&src, &prefs(level, mode), &[src.len()], false);
+            assert_eq!(
+                xxh32_oneshot(&frame, 0),
+                digest,
+                "{mode:?} level {level}: golden digest changed"
+            );
+        }
+    }
+}
+
+// ═════════════════════════════════════════════════════════════════════════════
+// Independence from call pattern and context history
+// ═════════════════════════════════════════════════════════════════════════════
+
+/// Splitting the input differently, or declaring it stable, changes nothing.
+#[test]
+fn output_ignores_input_chunking() {
+    let src = corpus();
+    for mode in [BlockMode::Linked, BlockMode::Independent] {
+        for level in LEVELS {
+            let p = prefs(level, mode);
+            let whole = compress_fresh(&src, &p, &[src.len()], false);
+            for (chunks, stable_src) in [
+                (&[1000usize, 7777][..], false),
+                (&[65_536], false),
+                (&[100_000, 3], false),
+                (&[65_536], true),
+                (&[100_000, 3], true),
+                (&[src.len()], true),
+            ] {
+                assert!(
+                    compress_fresh(&src, &p, chunks, stable_src) == whole,
+                    "{mode:?} level {level}, chunks {chunks:?}, stable_src {stable_src}"
+                );
+            }
+            assert_eq!(decompress_frame_to_vec(&whole).unwrap(), src);
+        }
+    }
+}
+
+/// A context that compressed other frames, at other levels, produces the
+/// same bytes as a fresh one.
+#[test]
+fn output_ignores_context_history() {
+    let src = corpus();
+    let other: Vec<u8> = src.iter().rev().copied().collect();
+    for mode in [BlockMode::Linked, BlockMode::Independent] {
+        for level in LEVELS {
+            let p = prefs(level, mode);
+            let fresh = compress_fresh(&src, &p, &[src.len()], false);
+            let mut cctx = lz4f_create_compression_context(LZ4F_VERSION).unwrap();
+            for warm_level in [12, 0, 2] {
+                let warm = Preferences {
+                    deterministic: false,
+                    ..prefs(warm_level, mode)
+                };
+                compress_chunked(&mut cctx, &other, &warm, &[30_000], false);
+            }
+            assert!(
+                compress_chunked(&mut cctx, &src, &p, &[src.len()], false) == fresh,
+                "{mode:?} level {level}"
+            );
+        }
+    }
+}
+
+/// The one-shot compressor agrees with the streaming one.
+#[test]
+fn one_shot_matches_streaming() {
+    use lz4::frame::compress::lz4f_compress_frame;
+    use lz4::frame::header::lz4f_compress_frame_bound;
+
+    let src = corpus();
+    for level in [0, 9] {
+        let p = prefs(level, BlockMode::Linked);
+        let mut dst = vec![0u8; lz4f_compress_frame_bound(src.len(), Some(&p))];
+        let n = lz4f_compress_frame(&mut dst, &src, Some(&p)).unwrap();
+        assert!(
+            dst[..n] == compress_fresh(&src, &p, &[4096], false)[..],
+            "level {level}"
+        );
+    }
+}
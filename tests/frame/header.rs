@@ -8,15 +8,19 @@
 //   - `lz4f_header_checksum`        → LZ4F_headerChecksum
 //   - `lz4f_compress_bound_internal` → LZ4F_compressBound_internal
 //   - `lz4f_compress_frame_bound`  → LZ4F_compressFrameBound
+//   - `block_size_from_id`, `max_block_size_of`, `lz4f_decompress_bound`
+//     (Rust-only buffer-sizing helpers)
+//   - `HeaderParser` (Rust-only incremental header decoding)
 
 use lz4::frame::header::{
-    lz4f_compress_bound_internal, lz4f_compress_frame_bound, lz4f_compression_level_max,
-    lz4f_get_block_size, lz4f_header_checksum, lz4f_optimal_bsid, read_le32, read_le64, write_le32,
-    write_le64, LZ4HC_CLEVEL_MAX,
+    block_size_from_id, estimate_frame_size, lz4f_compress_bound_internal,
+    lz4f_compress_frame_bound, lz4f_compression_level_max, lz4f_decompress_bound,
+    lz4f_get_block_size, lz4f_header_checksum, lz4f_optimal_bsid, max_block_size_of, read_le32,
+    read_le64, write_le32, write_le64, HeaderParser, HeaderStatus, LZ4HC_CLEVEL_MAX,
 };
 use lz4::frame::types::{
-    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, FrameInfo, Preferences, BF_SIZE,
-    BH_SIZE, MAX_FH_SIZE,
+    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, FrameInfo, FrameType, Lz4FError,
+    Preferences, BF_SIZE, BH_SIZE, MAX_FH_SIZE,
 };
 use lz4::xxhash::xxh32_oneshot;
 
@@ -544,3 +548,249 @@ fn compress_frame_bound_monotone_with_src_size() {
     assert!(small <= medium);
     assert!(medium <= large);
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// estimate_frame_size
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// For independent fast-level frames the estimate matches the real frame
+/// closely; overhead (header, checksums, end mark) is counted exactly.
+#[test]
+fn estimate_frame_size_tracks_real_frames() {
+    let text = lz4::lorem::gen_buffer(700_000, 12);
+    for checks in [false, true] {
+        let mut prefs = Preferences::default();
+        prefs.frame_info.block_mode = BlockMode::Independent;
+        prefs.frame_info.block_size_id = BlockSizeId::Max256Kb;
+        prefs.frame_info.content_size = checks as u64;
+        if checks {
+            prefs.frame_info.block_checksum_flag = BlockChecksum::Enabled;
+            prefs.frame_info.content_checksum_flag = ContentChecksum::Enabled;
+        }
+        let mut dst = vec![0u8; lz4f_compress_frame_bound(text.len(), Some(&prefs))];
+        let real = lz4::frame::lz4f_compress_frame(&mut dst, &text, Some(&prefs)).unwrap();
+        let est = estimate_frame_size(&text, Some(&prefs));
+        let diff = (est as f64 - real as f64).abs() / real as f64;
+        assert!(diff < 0.10, "estimate {est} vs real {real}");
+    }
+}
+
+/// An empty input costs exactly the header plus the end mark.
+#[test]
+fn estimate_frame_size_empty_and_incompressible() {
+    assert_eq!(estimate_frame_size(&[], None), 7 + BH_SIZE);
+    // Incompressible blocks are stored raw, so the estimate is the input plus
+    // the header, two 64 KB block headers, and the end mark.
+    let mut x = 1u32;
+    let noise: Vec<u8> = (0..100_000)
+        .map(|_| {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (x >> 24) as u8
+        })
+        .collect();
+    let est = estimate_frame_size(&noise, None);
+    assert_eq!(est, noise.len() + 7 + 3 * BH_SIZE);
+}
+
+// ---------------------------------------------------------------------------
+// block_size_from_id / max_block_size_of / lz4f_decompress_bound
+// ---------------------------------------------------------------------------
+
+/// Builds a frame header with the given FLG/BD bytes and optional fields.
+fn frame_header(flg: u8, bd: u8, extra: &[u8]) -> Vec<u8> {
+    let mut h = 0x184D_2204u32.to_le_bytes().to_vec();
+    h.extend_from_slice(&[flg, bd]);
+    h.extend_from_slice(extra);
+    let hc = lz4f_header_checksum(&h[4..]);
+    h.push(hc);
+    h
+}
+
+#[test]
+fn block_size_from_id_matches_get_block_size() {
+    for id in [
+        BlockSizeId::Max64Kb,
+        BlockSizeId::Max256Kb,
+        BlockSizeId::Max1Mb,
+        BlockSizeId::Max4Mb,
+    ] {
+        assert_eq!(Some(block_size_from_id(id)), lz4f_get_block_size(id));
+    }
+    assert_eq!(block_size_from_id(BlockSizeId::Default), 64 * 1024);
+
+    let info = FrameInfo {
+        block_size_id: BlockSizeId::Max1Mb,
+        ..Default::default()
+    };
+    assert_eq!(max_block_size_of(&info), 1 << 20);
+    assert_eq!(max_block_size_of(&FrameInfo::default()), 64 * 1024);
+}
+
+#[test]
+fn decompress_bound_reads_block_size_from_header() {
+    for (bsid, size) in [(4u8, 64 << 10), (5, 256 << 10), (6, 1 << 20), (7, 4 << 20)] {
+        let h = frame_header(0x40, bsid << 4, &[]);
+        assert_eq!(lz4f_decompress_bound(&h), Ok(size));
+    }
+    // Content size and dict ID extend the header; trailing bytes are ignored.
+    let mut h = frame_header(0x49, 0x70, &[0u8; 12]);
+    h.extend_from_slice(b"block data");
+    assert_eq!(lz4f_decompress_bound(&h), Ok(4 << 20));
+
+    let skippable = [0x50, 0x2A, 0x4D, 0x18, 0, 0, 0, 0];
+    assert_eq!(lz4f_decompress_bound(&skippable), Ok(0));
+}
+
+#[test]
+fn decompress_bound_rejects_bad_headers() {
+    let good = frame_header(0x48, 0x40, &[0u8; 8]);
+    assert_eq!(
+        lz4f_decompress_bound(&good[..good.len() - 1]),
+        Err(Lz4FError::FrameHeaderIncomplete)
+    );
+    assert_eq!(
+        lz4f_decompress_bound(b"PK\x03\x04zip"),
+        Err(Lz4FError::FrameTypeUnknown)
+    );
+    let mut bad_hc = good.clone();
+    *bad_hc.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        lz4f_decompress_bound(&bad_hc),
+        Err(Lz4FError::HeaderChecksumInvalid)
+    );
+    assert_eq!(
+        lz4f_decompress_bound(&frame_header(0x80, 0x40, &[])),
+        Err(Lz4FError::HeaderVersionWrong)
+    );
+    assert_eq!(
+        lz4f_decompress_bound(&frame_header(0x40, 0x30, &[])),
+        Err(Lz4FError::MaxBlockSizeInvalid)
+    );
+}
+
+// ---------------------------------------------------------------------------
+// HeaderParser
+// ---------------------------------------------------------------------------
+
+/// Feeds `bytes` one at a time; returns the info and the index of the byte
+/// that completed the header.
+fn feed_bytewise(parser: &mut HeaderParser, bytes: &[u8]) -> (FrameInfo, usize) {
+    for (i, b) in bytes.iter().enumerate() {
+        match parser.feed(std::slice::from_ref(b)).unwrap() {
+            HeaderStatus::Done(info, used) => {
+                assert_eq!(used, 1);
+                return (info, i);
+            }
+            HeaderStatus::NeedMoreBytes(n) => assert!(n >= 1),
+        }
+    }
+    panic!("header never completed");
+}
+
+#[test]
+fn header_parser_byte_by_byte() {
+    let mut extra = 1234u64.to_le_bytes().to_vec();
+    extra.extend_from_slice(&0xCAFEu32.to_le_bytes());
+    let mut h = frame_header(0x40 | 0x20 | 0x10 | 0x08 | 0x04 | 0x01, 0x50, &extra);
+    assert_eq!(h.len(), MAX_FH_SIZE);
+    h.extend_from_slice(b"block data follows");
+
+    let mut parser = HeaderParser::new();
+    let (info, last) = feed_bytewise(&mut parser, &h);
+    assert_eq!(last, MAX_FH_SIZE - 1);
+    assert_eq!(info.frame_type, FrameType::Frame);
+    assert_eq!(info.block_size_id, BlockSizeId::Max256Kb);
+    assert_eq!(info.block_mode, BlockMode::Independent);
+    assert_eq!(info.block_checksum_flag, BlockChecksum::Enabled);
+    assert_eq!(info.content_checksum_flag, ContentChecksum::Enabled);
+    assert_eq!(info.content_size, 1234);
+    assert_eq!(info.dict_id, 0xCAFE);
+
+    // Once done, further input is left alone.
+    assert!(matches!(parser.feed(b"more"), Ok(HeaderStatus::Done(_, 0))));
+}
+
+#[test]
+fn header_parser_reports_bytes_needed_and_consumed() {
+    let mut h = frame_header(0x48, 0x70, &7u64.to_le_bytes());
+    h.extend_from_slice(&[1, 2, 3]);
+
+    let mut parser = HeaderParser::new();
+    assert!(matches!(
+        parser.feed(&[]),
+        Ok(HeaderStatus::NeedMoreBytes(4))
+    ));
+    assert!(matches!(
+        parser.feed(&h[..2]),
+        Ok(HeaderStatus::NeedMoreBytes(2))
+    ));
+    // Magic and FLG known: the header is 15 bytes long.
+    assert!(matches!(
+        parser.feed(&h[2..6]),
+        Ok(HeaderStatus::NeedMoreBytes(9))
+    ));
+    match parser.feed(&h[6..]).unwrap() {
+        HeaderStatus::Done(info, used) => {
+            assert_eq!(used, 15 - 6);
+            assert_eq!(info.content_size, 7);
+            assert_eq!(info.block_size_id, BlockSizeId::Max4Mb);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+
+    parser.reset();
+    assert!(matches!(parser.feed(&h), Ok(HeaderStatus::Done(_, 15))));
+}
+
+#[test]
+fn header_parser_classifies_skippable_frames() {
+    let mut s = 0x184D_2A5Au32.to_le_bytes().to_vec();
+    s.extend_from_slice(&300u32.to_le_bytes());
+    s.extend_from_slice(b"payload");
+    let mut parser = HeaderParser::new();
+    let (info, last) = feed_bytewise(&mut parser, &s);
+    assert_eq!(last, 7);
+    assert_eq!(info.frame_type, FrameType::SkippableFrame);
+    assert_eq!(info.content_size, 300);
+}
+
+#[test]
+fn header_parser_rejects_bad_headers() {
+    let mut parser = HeaderParser::new();
+    assert_eq!(
+        parser.feed(b"PK\x03\x04zip").unwrap_err(),
+        Lz4FError::FrameTypeUnknown
+    );
+    // The error sticks until reset.
+    assert_eq!(parser.feed(b"").unwrap_err(), Lz4FError::FrameTypeUnknown);
+
+    let mut bad_hc = frame_header(0x40, 0x40, &[]);
+    *bad_hc.last_mut().unwrap() ^= 1;
+    for (bytes, err) in [
+        (bad_hc, Lz4FError::HeaderChecksumInvalid),
+        (frame_header(0x80, 0x40, &[]), Lz4FError::HeaderVersionWrong),
+        (
+            frame_header(0x40, 0x30, &[]),
+            Lz4FError::MaxBlockSizeInvalid,
+        ),
+        (frame_header(0x42, 0x40, &[]), Lz4FError::ReservedFlagSet),
+    ] {
+        parser.reset();
+        assert_eq!(parser.feed(&bytes).unwrap_err(), err);
+    }
+
+    // The version check needs only the FLG byte.
+    parser.reset();
+    assert_eq!(
+        parser
+            .feed(&frame_header(0x80, 0x40, &[])[..5])
+            .unwrap_err(),
+        Lz4FError::HeaderVersionWrong
+    );
+
+    let mut lenient = HeaderParser::new().allow_unknown_future_flags(true);
+    assert!(matches!(
+        lenient.feed(&frame_header(0x42, 0x41, &[])),
+        Ok(HeaderStatus::Done(_, 7))
+    ));
+}
@@ -0,0 +1,75 @@
+// Tests for frame::iter — FrameIter
+//
+// Covers:
+//   - Yields one Vec per frame; skippable frames are skipped, empty frames kept
+//   - Multi-block frames and clean end of input
+//   - A truncated or corrupt frame yields one error and ends iteration
+//   - into_inner leaves the reader just after the last frame consumed
+
+use std::io::ErrorKind;
+
+use lz4::frame::{compress_frame_to_vec, compress_to_writer, write_skippable_frame, FrameIter};
+
+fn skippable(payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; 8 + payload.len()];
+    write_skippable_frame(&mut out, 3, payload).unwrap();
+    out
+}
+
+#[test]
+fn yields_each_frame_and_skips_skippable_ones() {
+    let big: Vec<u8> = (0..1_000_000u32).map(|i| (i % 241) as u8).collect();
+    let mut stream = skippable(b"header");
+    stream.extend(compress_frame_to_vec(b"first"));
+    stream.extend(skippable(&[0u8; 100_000]));
+    stream.extend(compress_frame_to_vec(b""));
+    compress_to_writer(&mut stream, &big, None).unwrap();
+
+    let mut iter = FrameIter::new(&stream[..]);
+    assert_eq!(iter.next().unwrap().unwrap(), b"first");
+    assert_eq!(iter.next().unwrap().unwrap(), b"");
+    assert_eq!(iter.next().unwrap().unwrap(), big);
+    assert!(iter.next().is_none());
+    assert!(iter.next().is_none());
+    assert_eq!(iter.frames(), 3);
+}
+
+#[test]
+fn empty_input_yields_nothing() {
+    assert_eq!(FrameIter::new(&[][..]).count(), 0);
+    assert_eq!(FrameIter::new(&skippable(b"only")[..]).count(), 0);
+}
+
+#[test]
+fn errors_end_iteration() {
+    let mut stream = compress_frame_to_vec(b"good");
+    let second = compress_frame_to_vec(&[b'z'; 5000]);
+    stream.extend_from_slice(&second[..second.len() - 2]);
+
+    let results: Vec<_> = FrameIter::new(&stream[..]).collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap(), b"good");
+    assert_eq!(
+        results[1].as_ref().unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+
+    let mut garbage = compress_frame_to_vec(b"good");
+    garbage.extend_from_slice(b"garbage!");
+    let results: Vec<_> = FrameIter::new(&garbage[..]).collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results[1].as_ref().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn into_inner_stops_after_last_frame() {
+    let mut stream = compress_frame_to_vec(b"one");
+    stream.extend(compress_frame_to_vec(b"two"));
+    let mut iter = FrameIter::new(&stream[..]);
+    assert_eq!(iter.next().unwrap().unwrap(), b"one");
+    let rest = iter.into_inner();
+    assert_eq!(rest, &compress_frame_to_vec(b"two")[..]);
+}
@@ -1,12 +1,18 @@
-// Tests for frame::compress_frame_to_vec / decompress_frame_to_vec
+// Tests for frame::compress_frame_to_vec / decompress_frame_to_vec / compress_to_writer
 //
 // These exercise the one-shot helpers in src/frame/mod.rs to cover:
 //   - Normal round-trip for various data sizes
+//   - Block-by-block streaming into a writer (with and without the `mmap` hints)
 //   - Empty input
 //   - Error path (invalid compressed data)
 //   - Stall/no-progress loop exit in decompress_frame_to_vec
+//   - read_frame: hint-sized reads, concatenated and skippable frames,
+//     truncated and corrupt input
 
-use lz4::frame::{compress_frame_to_vec, decompress_frame_to_vec};
+use lz4::frame::{
+    compress_frame_to_vec, compress_to_writer, decompress_frame_to_vec, read_frame,
+    write_skippable_frame, BlockChecksum, BlockSizeId, ContentChecksum, FrameInfo, Preferences,
+};
 
 #[test]
 fn compress_decompress_roundtrip_small() {
@@ -52,9 +58,9 @@ fn decompress_frame_to_vec_truncated_frame() {
     let result = decompress_frame_to_vec(truncated);
     // Should either error or return partial/empty result
     // The stall-detection logic should kick in when no progress is made
-    match result {
-        Ok(v) => assert!(v.len() < data.len()),
-        Err(_) => {} // error is also acceptable
+    // An error is also acceptable.
+    if let Ok(v) = result {
+        assert!(v.len() < data.len());
     }
 }
 
@@ -93,3 +99,119 @@ fn compress_decompress_roundtrip_all_zeros() {
     let decompressed = decompress_frame_to_vec(&compressed).unwrap();
     assert_eq!(decompressed, data);
 }
+
+#[test]
+fn compress_to_writer_roundtrip_multi_block() {
+    // 64 KiB blocks over ~300 KiB: several full windows plus a partial tail.
+    let data: Vec<u8> = (0..300_007u32).map(|i| (i % 251) as u8).collect();
+    let mut prefs = Preferences::default();
+    prefs.frame_info.block_size_id = BlockSizeId::Max64Kb;
+    let mut out = Vec::new();
+    let n = compress_to_writer(&mut out, &data, Some(&prefs)).unwrap();
+    assert_eq!(n, out.len() as u64);
+    assert!(out.len() < data.len());
+    assert_eq!(decompress_frame_to_vec(&out).unwrap(), data);
+}
+
+#[test]
+fn compress_to_writer_roundtrip_empty() {
+    let mut out = Vec::new();
+    let n = compress_to_writer(&mut out, &[], None).unwrap();
+    assert_eq!(n, out.len() as u64);
+    assert!(decompress_frame_to_vec(&out).unwrap().is_empty());
+}
+
+#[test]
+fn compress_to_writer_propagates_write_errors() {
+    struct Broken;
+    impl std::io::Write for Broken {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("broken"))
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    assert!(compress_to_writer(&mut Broken, b"data", None).is_err());
+}
+
+/// Records the size of every read request.
+struct CountingReader<'a> {
+    data: &'a [u8],
+    reads: Vec<usize>,
+}
+
+impl std::io::Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads.push(buf.len());
+        let n = buf.len().min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn read_frame_issues_one_read_per_block() {
+    let data: Vec<u8> = (0..300_000u32)
+        .map(|i| (i % 251) as u8 ^ (i / 4099) as u8)
+        .collect();
+    let prefs = Preferences {
+        frame_info: FrameInfo {
+            block_size_id: BlockSizeId::Max64Kb,
+            block_checksum_flag: BlockChecksum::Enabled,
+            content_checksum_flag: ContentChecksum::Enabled,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut frame = Vec::new();
+    compress_to_writer(&mut frame, &data, Some(&prefs)).unwrap();
+    frame.extend_from_slice(b"trailing");
+
+    let mut reader = CountingReader {
+        data: &frame,
+        reads: Vec::new(),
+    };
+    let mut out = Vec::new();
+    assert_eq!(
+        read_frame(&mut reader, &mut out).unwrap(),
+        data.len() as u64
+    );
+    assert_eq!(out, data);
+    // Nothing past the frame was read.
+    assert_eq!(reader.data, b"trailing");
+    // Header, the first block header, then one read per block; the last one
+    // also takes the end mark and content checksum.
+    assert_eq!(reader.reads.len(), 2 + 5, "reads: {:?}", reader.reads);
+}
+
+#[test]
+fn read_frame_walks_concatenated_and_skippable_frames() {
+    let mut stream = compress_frame_to_vec(b"one");
+    let mut skip = [0u8; 8 + 5];
+    write_skippable_frame(&mut skip, 0, b"meta!").unwrap();
+    stream.extend_from_slice(&skip);
+    stream.extend(compress_frame_to_vec(b""));
+    stream.extend(compress_frame_to_vec(b"three"));
+
+    let mut reader = &stream[..];
+    let mut out = Vec::new();
+    let sizes: Vec<u64> = (0..4)
+        .map(|_| read_frame(&mut reader, &mut out).unwrap())
+        .collect();
+    assert_eq!(sizes, [3, 0, 0, 5]);
+    assert!(reader.is_empty());
+    assert_eq!(out, b"onethree");
+}
+
+#[test]
+fn read_frame_reports_truncation_and_corruption() {
+    let frame = compress_frame_to_vec(&[b'x'; 1000]);
+    for cut in [1, 4, frame.len() - 3] {
+        let err = read_frame(&frame[..frame.len() - cut], std::io::sink()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof, "cut {cut}");
+    }
+    let err = read_frame(&b"not an lz4 frame"[..], std::io::sink()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,226 @@
+// Integration tests for src/frame/pool.rs — pooled staging buffers
+//
+// Covers:
+//   - `SizeClassPool` serves requests from eighth-of-a-power-of-two classes,
+//     reuses larger idle buffers up to twice the request, and caps idle
+//     buffers per class
+//   - Compression and decompression contexts built `with_pool` take their
+//     staging buffers from the pool and give them back on drop
+//   - A second context reuses the first one's buffers; output is unchanged
+//   - `with_pooled_buffers` routes the one-shot decode helpers through a
+//     pool on the current thread only, and restores the previous scope
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use lz4::frame::types::LZ4F_VERSION;
+use lz4::frame::{
+    lz4f_compress_begin, lz4f_compress_bound, lz4f_compress_end, lz4f_compress_update,
+    lz4f_decompress, read_frame, with_pooled_buffers, BlockMode, BlockSizeId, BufferPool,
+    FrameInfo, Lz4FCCtx, Lz4FDCtx, Preferences, SizeClassPool,
+};
+
+/// Counts how many `get` calls the inner pool could not serve.
+struct Counting {
+    inner: SizeClassPool,
+    gets: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl Counting {
+    fn new() -> Arc<Self> {
+        Arc::new(Counting {
+            inner: SizeClassPool::new(4),
+            gets: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        })
+    }
+}
+
+impl BufferPool for Counting {
+    fn get(&self, size: usize) -> Vec<u8> {
+        self.gets.fetch_add(1, Ordering::Relaxed);
+        if self.inner.idle() == 0 {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        self.inner.get(size)
+    }
+
+    fn put(&self, buf: Vec<u8>) {
+        self.inner.put(buf)
+    }
+}
+
+fn prefs() -> Preferences {
+    Preferences {
+        frame_info: FrameInfo {
+            block_size_id: BlockSizeId::Max1Mb,
+            block_mode: BlockMode::Linked,
+            ..FrameInfo::default()
+        },
+        ..Preferences::default()
+    }
+}
+
+fn compress_with(cctx: &mut Lz4FCCtx, src: &[u8]) -> Vec<u8> {
+    let p = prefs();
+    let mut dst = vec![0u8; lz4f_compress_bound(src.len(), Some(&p)) + 64];
+    let mut pos = lz4f_compress_begin(cctx, &mut dst, Some(&p)).unwrap();
+    for chunk in src.chunks(100_000) {
+        pos += lz4f_compress_update(cctx, &mut dst[pos..], chunk, None).unwrap();
+    }
+    pos += lz4f_compress_end(cctx, &mut dst[pos..], None).unwrap();
+    dst.truncate(pos);
+    dst
+}
+
+/// Small input and output chunks so both staging buffers are used.
+fn decompress_with(dctx: &mut Lz4FDCtx, frame: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut dst = vec![0u8; 4096];
+    let mut pos = 0;
+    loop {
+        let end = (pos + 3000).min(frame.len());
+        let (read, written, hint) =
+            lz4f_decompress(dctx, Some(&mut dst), &frame[pos..end], None).unwrap();
+        out.extend_from_slice(&dst[..written]);
+        pos += read;
+        if hint == 0 {
+            return out;
+        }
+    }
+}
+
+#[test]
+fn size_class_pool_recycles_by_class() {
+    let pool = SizeClassPool::new(2);
+    let buf = pool.get(1000);
+    assert!(buf.capacity() >= 1024);
+    let ptr = buf.as_ptr();
+    pool.put(buf);
+    assert_eq!(pool.idle(), 1);
+    // A request from half the buffer's size up gets the same allocation back.
+    let again = pool.get(600);
+    assert_eq!(again.as_ptr(), ptr);
+    assert!(again.is_empty());
+    pool.put(again);
+    // A smaller one does not tie it up.
+    let smaller = pool.get(400);
+    assert_ne!(smaller.as_ptr(), ptr);
+    // A larger request needs the next class, not the next power of two.
+    let larger = pool.get(1025);
+    assert!(larger.capacity() >= 1025 && larger.capacity() < 2048);
+    assert_ne!(larger.as_ptr(), ptr);
+
+    for _ in 0..5 {
+        pool.put(Vec::with_capacity(4096));
+    }
+    // Two 4096-byte buffers plus the 1024-byte one still idle.
+    assert_eq!(pool.idle(), 3, "capped per class");
+    pool.put(Vec::new());
+    assert_eq!(pool.idle(), 3);
+}
+
+#[test]
+fn size_class_pool_keeps_max_block_buffers_near_size() {
+    let pool = SizeClassPool::new(1);
+    // A 4 MiB block plus 64 KiB of history and alignment slack.
+    let size = (4 << 20) + (64 << 10) + 4095;
+    let buf = pool.get(size);
+    assert!(buf.capacity() >= size);
+    assert!(buf.capacity() <= size + size / 8, "{}", buf.capacity());
+    let ptr = buf.as_ptr();
+    pool.put(buf);
+    let again = pool.get(size);
+    assert_eq!(again.as_ptr(), ptr);
+}
+
+#[test]
+fn compression_contexts_share_pooled_buffers() {
+    let src = lz4::corpus::log_lines(3_000_000, 2);
+    let pool = Counting::new();
+
+    let first = {
+        let mut cctx = Lz4FCCtx::with_pool(LZ4F_VERSION, pool.clone());
+        compress_with(&mut cctx, &src)
+    };
+    assert_eq!(pool.inner.idle(), 1, "buffer returned on drop");
+    let second = {
+        let mut cctx = Lz4FCCtx::with_pool(LZ4F_VERSION, pool.clone());
+        compress_with(&mut cctx, &src)
+    };
+    assert_eq!(pool.gets.load(Ordering::Relaxed), 2);
+    assert_eq!(pool.misses.load(Ordering::Relaxed), 1);
+    assert_eq!(first, second);
+    assert_eq!(first, compress_with(&mut Lz4FCCtx::new(LZ4F_VERSION), &src));
+}
+
+#[test]
+fn decompression_contexts_share_pooled_buffers() {
+    let src = lz4::corpus::json_records(3_000_000, 2);
+    let frame = compress_with(&mut Lz4FCCtx::new(LZ4F_VERSION), &src);
+    let pool = Counting::new();
+    for _ in 0..3 {
+        let mut dctx = Lz4FDCtx::with_pool(LZ4F_VERSION, pool.clone());
+        assert_eq!(decompress_with(&mut dctx, &frame), src);
+    }
+    // tmp_in and tmp_out_buffer: allocated once, then recycled.
+    assert_eq!(pool.gets.load(Ordering::Relaxed), 6);
+    assert_eq!(pool.misses.load(Ordering::Relaxed), 2);
+    assert_eq!(pool.inner.idle(), 2);
+}
+
+#[test]
+fn one_shot_decoders_use_the_scoped_pool() {
+    let src = lz4::corpus::log_lines(2_500_000, 3);
+    let frame = compress_with(&mut Lz4FCCtx::new(LZ4F_VERSION), &src);
+    let pool = Counting::new();
+
+    with_pooled_buffers(pool.clone(), || {
+        assert_eq!(lz4::frame::decompress_frame_to_vec(&frame).unwrap(), src);
+    });
+    let gets = pool.gets.load(Ordering::Relaxed);
+    let misses = pool.misses.load(Ordering::Relaxed);
+    assert!(gets >= 2, "context and output scratch pooled");
+
+    // Later calls are served entirely from what the first one gave back.
+    with_pooled_buffers(pool.clone(), || {
+        for _ in 0..3 {
+            let out = lz4::frame::decompress_frame_to_vec_with_limit(&frame, 1 << 30).unwrap();
+            assert_eq!(out, src);
+            let mut sink = Vec::new();
+            read_frame(&frame[..], &mut sink).unwrap();
+            assert_eq!(sink, src);
+        }
+    });
+    assert!(pool.gets.load(Ordering::Relaxed) > gets);
+    assert!(pool.misses.load(Ordering::Relaxed) <= misses + 2);
+
+    // Outside the scope the pool is not touched.
+    let gets = pool.gets.load(Ordering::Relaxed);
+    lz4::frame::decompress_frame_to_vec(&frame).unwrap();
+    assert_eq!(pool.gets.load(Ordering::Relaxed), gets);
+}
+
+#[test]
+fn pooled_scopes_nest_and_restore() {
+    let frame = lz4::frame::compress_frame_to_vec(b"nested scopes");
+    let outer = Counting::new();
+    let inner = Counting::new();
+    with_pooled_buffers(outer.clone(), || {
+        with_pooled_buffers(inner.clone(), || {
+            lz4::frame::decompress_frame_to_vec(&frame).unwrap();
+        });
+        assert!(inner.gets.load(Ordering::Relaxed) > 0);
+        assert_eq!(outer.gets.load(Ordering::Relaxed), 0);
+        lz4::frame::decompress_frame_to_vec(&frame).unwrap();
+        assert!(outer.gets.load(Ordering::Relaxed) > 0);
+    });
+
+    // A panic inside the scope still restores it.
+    let caught = std::panic::catch_unwind(|| with_pooled_buffers(inner.clone(), || panic!("x")));
+    assert!(caught.is_err());
+    let gets = inner.gets.load(Ordering::Relaxed);
+    lz4::frame::decompress_frame_to_vec(&frame).unwrap();
+    assert_eq!(inner.gets.load(Ordering::Relaxed), gets);
+}
@@ -0,0 +1,239 @@
+// Tests for frame::safe — the unsafe-free one-shot frame codec
+//
+// Frames produced by the safe encoder must decode with the native decoder
+// (and vice versa), across block sizes, block modes, checksums, and
+// compression levels, including HC frames from the native encoder.
+// decompress_frame_into decodes the same frames into a caller-sized slice and
+// reports a slice that is too small as SrcSizeTooLarge.
+
+use lz4::frame::safe;
+use lz4::frame::{
+    decompress_frame_into, decompress_frame_to_vec, lz4f_compress_frame, lz4f_compress_frame_bound,
+    write_skippable_frame, BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, Lz4FError,
+    Preferences,
+};
+use lz4::lorem::gen_buffer;
+
+fn prefs_matrix() -> Vec<Preferences> {
+    let mut out = Vec::new();
+    for bsid in [
+        BlockSizeId::Max64Kb,
+        BlockSizeId::Max256Kb,
+        BlockSizeId::Max4Mb,
+    ] {
+        for mode in [BlockMode::Linked, BlockMode::Independent] {
+            for checks in [false, true] {
+                let mut p = Preferences::default();
+                p.frame_info.block_size_id = bsid;
+                p.frame_info.block_mode = mode;
+                p.frame_info.content_size = checks as u64;
+                if checks {
+                    p.frame_info.block_checksum_flag = BlockChecksum::Enabled;
+                    p.frame_info.content_checksum_flag = ContentChecksum::Enabled;
+                }
+                out.push(p);
+            }
+        }
+    }
+    out
+}
+
+fn native_frame(data: &[u8], prefs: &Preferences) -> Vec<u8> {
+    let mut dst = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(prefs))];
+    let n = lz4f_compress_frame(&mut dst, data, Some(prefs)).unwrap();
+    dst.truncate(n);
+    dst
+}
+
+fn safe_frame(data: &[u8], prefs: &Preferences) -> Vec<u8> {
+    let mut dst = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(prefs))];
+    let n = safe::lz4f_compress_frame(&mut dst, data, Some(prefs)).unwrap();
+    dst.truncate(n);
+    dst
+}
+
+#[test]
+fn safe_frames_decode_natively() {
+    let data = gen_buffer(300_000, 21);
+    for prefs in prefs_matrix() {
+        let frame = safe_frame(&data, &prefs);
+        assert_eq!(
+            decompress_frame_to_vec(&frame).unwrap(),
+            data,
+            "{:?}",
+            prefs.frame_info
+        );
+    }
+}
+
+#[test]
+fn native_frames_decode_safely() {
+    let data = gen_buffer(300_000, 22);
+    for prefs in prefs_matrix() {
+        for level in [-5, 0, 9] {
+            let mut p = prefs;
+            p.compression_level = level;
+            let frame = native_frame(&data, &p);
+            assert_eq!(
+                safe::decompress_frame_to_vec(&frame).unwrap(),
+                data,
+                "level {} {:?}",
+                level,
+                p.frame_info
+            );
+        }
+    }
+}
+
+#[test]
+fn empty_and_incompressible_inputs() {
+    let noise: Vec<u8> = (0..100_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect();
+    for data in [Vec::new(), noise] {
+        let frame = safe::compress_frame_to_vec(&data);
+        assert_eq!(decompress_frame_to_vec(&frame).unwrap(), data);
+        assert_eq!(safe::decompress_frame_to_vec(&frame).unwrap(), data);
+    }
+}
+
+#[test]
+fn corrupted_block_checksum_rejected() {
+    let mut prefs = Preferences::default();
+    prefs.frame_info.block_checksum_flag = BlockChecksum::Enabled;
+    let data = gen_buffer(10_000, 4);
+    let mut frame = native_frame(&data, &prefs);
+    let mid = frame.len() / 2;
+    frame[mid] ^= 0x01;
+    assert!(safe::decompress_frame_to_vec(&frame).is_err());
+}
+
+#[test]
+fn truncated_frame_matches_native() {
+    let data = gen_buffer(300_000, 9);
+    let mut prefs = Preferences::default();
+    prefs.frame_info.block_size_id = BlockSizeId::Max64Kb;
+    let frame = native_frame(&data, &prefs);
+    for cut in [3, 10, frame.len() / 3, frame.len() - 5] {
+        assert_eq!(
+            safe::decompress_frame_to_vec(&frame[..cut]).unwrap(),
+            decompress_frame_to_vec(&frame[..cut]).unwrap(),
+            "cut at {cut}"
+        );
+    }
+}
+
+#[test]
+fn output_limit_matches_native() {
+    let data = gen_buffer(300_000, 12);
+    let mut with_size = Preferences::default();
+    with_size.frame_info.content_size = 1;
+    let mut without_size = Preferences::default();
+    without_size.frame_info.block_size_id = BlockSizeId::Max64Kb;
+    for prefs in [with_size, without_size] {
+        let frame = native_frame(&data, &prefs);
+        for limit in [0, 70_000, data.len() as u64 - 1] {
+            assert!(safe::decompress_frame_to_vec_with_limit(&frame, limit).is_err());
+            assert!(lz4::frame::decompress_frame_to_vec_with_limit(&frame, limit).is_err());
+        }
+        assert_eq!(
+            safe::decompress_frame_to_vec_with_limit(&frame, data.len() as u64).unwrap(),
+            data
+        );
+    }
+}
+
+#[test]
+fn decompress_into_matches_to_vec() {
+    let data = gen_buffer(300_000, 31);
+    for prefs in prefs_matrix() {
+        for level in [0, 9] {
+            let mut p = prefs;
+            p.compression_level = level;
+            let frame = native_frame(&data, &p);
+            for extra in [0, 1000] {
+                let mut dst = vec![0u8; data.len() + extra];
+                let n = decompress_frame_into(&mut dst, &frame).unwrap();
+                assert_eq!(&dst[..n], &data[..], "level {level} {:?}", p.frame_info);
+            }
+        }
+    }
+}
+
+#[test]
+fn decompress_into_short_destination() {
+    let noise: Vec<u8> = (0..100_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect();
+    let text = gen_buffer(300_000, 32);
+    for data in [text, noise] {
+        for content_size in [0, 1] {
+            for mode in [BlockMode::Linked, BlockMode::Independent] {
+                let mut prefs = Preferences::default();
+                prefs.frame_info.block_size_id = BlockSizeId::Max64Kb;
+                prefs.frame_info.block_mode = mode;
+                prefs.frame_info.content_size = content_size;
+                let frame = native_frame(&data, &prefs);
+                for short in [0, 1, 65_536, data.len() - 1] {
+                    let mut dst = vec![0u8; short];
+                    assert_eq!(
+                        decompress_frame_into(&mut dst, &frame),
+                        Err(Lz4FError::SrcSizeTooLarge),
+                        "{short} bytes, {:?}",
+                        prefs.frame_info
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn decompress_into_rejects_damage() {
+    let data = gen_buffer(200_000, 33);
+    let mut prefs = Preferences::default();
+    prefs.frame_info.block_size_id = BlockSizeId::Max64Kb;
+    prefs.frame_info.content_checksum_flag = ContentChecksum::Enabled;
+    let frame = native_frame(&data, &prefs);
+    let mut dst = vec![0u8; data.len()];
+
+    assert_eq!(
+        decompress_frame_into(&mut dst, &frame[..3]),
+        Err(Lz4FError::FrameHeaderIncomplete)
+    );
+    assert_eq!(
+        decompress_frame_into(&mut dst, &frame[..6]),
+        Err(Lz4FError::FrameHeaderIncomplete)
+    );
+    for cut in [frame.len() / 2, frame.len() - 4, frame.len() - 1] {
+        assert_eq!(
+            decompress_frame_into(&mut dst, &frame[..cut]),
+            Err(Lz4FError::DecompressionFailed),
+            "cut at {cut}"
+        );
+    }
+    let mut bad = frame.clone();
+    *bad.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        decompress_frame_into(&mut dst, &bad),
+        Err(Lz4FError::ContentChecksumInvalid)
+    );
+    assert_eq!(
+        decompress_frame_into(&mut dst, b"not a frame"),
+        Err(Lz4FError::FrameTypeUnknown)
+    );
+}
+
+#[test]
+fn decompress_into_skippable_and_trailing_bytes() {
+    let mut skippable = vec![0u8; 16];
+    write_skippable_frame(&mut skippable, 0, b"metadata").unwrap();
+    let mut dst = [0u8; 16];
+    assert_eq!(decompress_frame_into(&mut dst, &skippable), Ok(0));
+
+    let mut frame = native_frame(b"hello, hello, hello", &Preferences::default());
+    frame.extend_from_slice(&skippable);
+    let mut dst = [0u8; 19];
+    assert_eq!(decompress_frame_into(&mut dst, &frame), Ok(19));
+    assert_eq!(&dst, b"hello, hello, hello");
+}
@@ -0,0 +1,99 @@
+// Tests for frame::skippable — write_skippable_frame / read_skippable_frame
+//
+// Covers:
+//   - Round-trip for every user magic nibble
+//   - Frames embedded before and after an LZ4 frame are ignored by the decoder
+//   - Error paths: short destination, truncated input, non-skippable magic
+
+use lz4::frame::skippable::{is_skippable_magic, skippable_frame_bound};
+use lz4::frame::types::LZ4F_VERSION;
+use lz4::frame::{
+    compress_frame_to_vec, decompress_frame_to_vec, lz4f_create_decompression_context,
+    lz4f_decompress, read_skippable_frame, write_skippable_frame, Lz4FError,
+};
+
+fn skippable_vec(nibble: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; skippable_frame_bound(payload.len())];
+    let n = write_skippable_frame(&mut out, nibble, payload).unwrap();
+    assert_eq!(n, out.len());
+    out
+}
+
+#[test]
+fn roundtrip_all_nibbles() {
+    for nibble in 0..16u8 {
+        let payload = vec![nibble; nibble as usize * 3];
+        let frame = skippable_vec(nibble, &payload);
+        assert!(is_skippable_magic(u32::from_le_bytes(
+            frame[..4].try_into().unwrap()
+        )));
+        let parsed = read_skippable_frame(&frame).unwrap();
+        assert_eq!(parsed.user_magic_nibble, nibble);
+        assert_eq!(parsed.payload, &payload[..]);
+        assert_eq!(parsed.frame_size(), frame.len());
+    }
+}
+
+#[test]
+fn read_ignores_trailing_bytes() {
+    let mut buf = skippable_vec(1, b"index");
+    buf.extend_from_slice(b"trailing");
+    let parsed = read_skippable_frame(&buf).unwrap();
+    assert_eq!(parsed.payload, b"index");
+    assert_eq!(&buf[parsed.frame_size()..], b"trailing");
+}
+
+#[test]
+fn decoder_skips_embedded_metadata() {
+    let data = b"payload that the decoder must reproduce exactly".repeat(20);
+    let mut stream = skippable_vec(0, b"leading signature");
+    stream.extend_from_slice(&compress_frame_to_vec(&data));
+    stream.extend_from_slice(&skippable_vec(0xF, b"trailing index"));
+
+    // Decode every frame in the stream, the way `lz4 -d` does.
+    let mut dctx = lz4f_create_decompression_context(LZ4F_VERSION).unwrap();
+    let mut out = Vec::new();
+    let mut dst = vec![0u8; 64 * 1024];
+    let mut pos = 0;
+    while pos < stream.len() {
+        let (consumed, written, _) =
+            lz4f_decompress(&mut dctx, Some(&mut dst), &stream[pos..], None).unwrap();
+        assert!(consumed > 0 || written > 0, "decoder stalled at {pos}");
+        out.extend_from_slice(&dst[..written]);
+        pos += consumed;
+    }
+    assert_eq!(out, data);
+
+    // The one-shot helper stops after the first frame, so metadata placed
+    // after the data is never a problem for it.
+    let mut tail_only = compress_frame_to_vec(&data);
+    tail_only.extend_from_slice(&skippable_vec(0xF, b"trailing index"));
+    assert_eq!(decompress_frame_to_vec(&tail_only).unwrap(), data);
+}
+
+#[test]
+fn write_rejects_short_destination() {
+    let mut buf = [0u8; 10];
+    assert_eq!(
+        write_skippable_frame(&mut buf, 0, b"abc"),
+        Err(Lz4FError::DstMaxSizeTooSmall)
+    );
+}
+
+#[test]
+fn read_rejects_truncated_and_foreign_frames() {
+    let frame = skippable_vec(2, b"abcdef");
+    assert_eq!(
+        read_skippable_frame(&frame[..5]),
+        Err(Lz4FError::FrameHeaderIncomplete)
+    );
+    assert_eq!(
+        read_skippable_frame(&frame[..frame.len() - 1]),
+        Err(Lz4FError::FrameSizeWrong)
+    );
+    let mut huge = frame.clone();
+    huge[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(read_skippable_frame(&huge), Err(Lz4FError::FrameSizeWrong));
+    let lz4 = compress_frame_to_vec(b"not skippable");
+    assert_eq!(read_skippable_frame(&lz4), Err(Lz4FError::FrameTypeUnknown));
+}
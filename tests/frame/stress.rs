@@ -0,0 +1,257 @@
+// Generative stress test for the frame codec.
+//
+// Each case draws random preferences (block size, block mode, block and
+// content checksums, declared content size, level, auto-flush, favor_dec_speed,
+// dictionary), a data shape from lz4::corpus, and chunking patterns for both
+// sides: input slices fed to compress_update with optional lz4f_flush calls in
+// between, and input/output buffer sizes for the decoder.  Every frame must
+// decode back to its input, and frames without a dictionary must also decode
+// through the one-shot API.
+//
+// The run is reproducible: cases derive from a base seed.  A failure message
+// names the seed and case index; replay it with
+//
+//   LZ4_STRESS_SEED=<seed> LZ4_STRESS_CASES=<index + 1> cargo test --test frame stress
+//
+// LZ4_STRESS_CASES also raises the case count for longer soak runs.
+
+use lz4::corpus::Corpus;
+use lz4::frame::compress::{
+    lz4f_compress_begin, lz4f_compress_begin_using_dict, lz4f_compress_bound, lz4f_compress_end,
+    lz4f_compress_update, lz4f_flush, LZ4F_VERSION,
+};
+use lz4::frame::decompress::{lz4f_decompress_using_dict, Lz4FDCtx};
+use lz4::frame::decompress_frame_to_vec;
+use lz4::frame::types::{
+    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, FrameInfo, Lz4FCCtx, Preferences,
+};
+
+const DEFAULT_SEED: u64 = 0x4C5A_3446_5354_5253;
+const DEFAULT_CASES: usize = 2000;
+
+/// SplitMix64: small, fast, and good enough to pick test parameters.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+
+    /// A size spread over several orders of magnitude, at most `max`.
+    fn size(&mut self, max: usize) -> usize {
+        let bound = self.pick(&[16, 300, 5_000, 70_000, max]).min(max);
+        self.below(bound + 1)
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Everything one case needs, drawn from the case's own generator.
+#[derive(Debug)]
+struct Case {
+    prefs: Preferences,
+    corpus: Corpus,
+    data_len: usize,
+    data_seed: u32,
+    dict_len: Option<usize>,
+    flush_percent: usize,
+    max_src_chunk: usize,
+    max_in_chunk: usize,
+    max_out_chunk: usize,
+}
+
+impl Case {
+    fn draw(rng: &mut Rng) -> Case {
+        let corpus = rng.pick(&[
+            Corpus::Lorem,
+            Corpus::Logs,
+            Corpus::Json,
+            Corpus::Binary { bits_per_byte: 2 },
+            Corpus::Binary { bits_per_byte: 8 },
+        ]);
+        // Mostly small inputs, with the occasional one spanning several blocks.
+        let data_len = if rng.chance(3) {
+            rng.below(600_001)
+        } else {
+            rng.size(70_000)
+        };
+        // Chunks may be tiny, but not so tiny that one case makes more than a
+        // few hundred calls on either side.
+        let min_chunk = data_len / 256;
+        // The HC optimal parser is slow in debug builds; keep its inputs small.
+        let level = if data_len > 64 * 1024 {
+            rng.pick(&[-20, -1, 0, 1, 2, 3, 6])
+        } else {
+            rng.pick(&[-20, -1, 0, 1, 2, 3, 6, 9, 10, 12])
+        };
+        let prefs = Preferences {
+            frame_info: FrameInfo {
+                block_size_id: rng.pick(&[
+                    BlockSizeId::Default,
+                    BlockSizeId::Max64Kb,
+                    BlockSizeId::Max256Kb,
+                    BlockSizeId::Max1Mb,
+                    BlockSizeId::Max4Mb,
+                ]),
+                block_mode: rng.pick(&[BlockMode::Linked, BlockMode::Independent]),
+                content_checksum_flag: rng
+                    .pick(&[ContentChecksum::Disabled, ContentChecksum::Enabled]),
+                block_checksum_flag: rng.pick(&[BlockChecksum::Disabled, BlockChecksum::Enabled]),
+                content_size: if rng.chance(50) { data_len as u64 } else { 0 },
+                ..FrameInfo::default()
+            },
+            compression_level: level,
+            auto_flush: rng.chance(50),
+            favor_dec_speed: rng.chance(25),
+            ..Preferences::default()
+        };
+        Case {
+            prefs,
+            corpus,
+            data_len,
+            data_seed: rng.next_u64() as u32,
+            dict_len: rng.chance(25).then(|| 1 + rng.size(80_000)),
+            flush_percent: rng.pick(&[0, 0, 10, 50]),
+            max_src_chunk: 1 + min_chunk.max(rng.size(300_000)),
+            max_in_chunk: 1 + min_chunk.max(rng.size(100_000)),
+            max_out_chunk: 1 + min_chunk.max(rng.size(300_000)),
+        }
+    }
+}
+
+/// Compresses `data` as `case` describes, in random slices.
+fn compress(case: &Case, rng: &mut Rng, data: &[u8], dict: Option<&[u8]>) -> Vec<u8> {
+    let prefs = &case.prefs;
+    let mut cctx = Lz4FCCtx::new(LZ4F_VERSION);
+    let mut out = Vec::new();
+    let mut buf = vec![0u8; lz4f_compress_bound(0, Some(prefs)) + 32];
+
+    let n = match dict {
+        Some(dict) => lz4f_compress_begin_using_dict(&mut cctx, &mut buf, dict, Some(prefs)),
+        None => lz4f_compress_begin(&mut cctx, &mut buf, Some(prefs)),
+    }
+    .expect("compress_begin");
+    out.extend_from_slice(&buf[..n]);
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let end = (pos + 1 + rng.below(case.max_src_chunk)).min(data.len());
+        let bound = lz4f_compress_bound(end - pos, Some(prefs));
+        if buf.len() < bound {
+            buf.resize(bound, 0);
+        }
+        let n = lz4f_compress_update(&mut cctx, &mut buf, &data[pos..end], None)
+            .expect("compress_update");
+        out.extend_from_slice(&buf[..n]);
+        pos = end;
+        if rng.chance(case.flush_percent) {
+            let n = lz4f_flush(&mut cctx, &mut buf, None).expect("flush");
+            out.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    let n = lz4f_compress_end(&mut cctx, &mut buf, None).expect("compress_end");
+    out.extend_from_slice(&buf[..n]);
+    out
+}
+
+/// Decodes `frame` through random input slices and output buffer sizes.
+fn decompress(case: &Case, rng: &mut Rng, frame: &[u8], dict: &[u8]) -> Vec<u8> {
+    let mut dctx = Lz4FDCtx::new(LZ4F_VERSION);
+    let mut out = Vec::new();
+    let mut dst = vec![0u8; case.max_out_chunk];
+    let mut pos = 0;
+    loop {
+        let end = (pos + 1 + rng.below(case.max_in_chunk)).min(frame.len());
+        let cap = 1 + rng.below(case.max_out_chunk);
+        let (consumed, written, hint) = lz4f_decompress_using_dict(
+            &mut dctx,
+            Some(&mut dst[..cap]),
+            &frame[pos..end],
+            dict,
+            None,
+        )
+        .expect("decompress");
+        out.extend_from_slice(&dst[..written]);
+        pos += consumed;
+        if hint == 0 {
+            assert_eq!(pos, frame.len(), "frame ended early");
+            return out;
+        }
+        assert!(
+            consumed > 0 || written > 0 || pos < frame.len(),
+            "decoder stalled at {pos} of {}",
+            frame.len()
+        );
+    }
+}
+
+fn run_case(seed: u64, index: usize) {
+    let mut rng = Rng(seed ^ (index as u64).wrapping_mul(0xA24B_AED4_963E_E407));
+    let case = Case::draw(&mut rng);
+    let data = case.corpus.generate(case.data_len, case.data_seed);
+    let dict = case
+        .dict_len
+        .map(|len| Corpus::Lorem.generate(len, case.data_seed ^ 0xD1C7));
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let frame = compress(&case, &mut rng, &data, dict.as_deref());
+        let decoded = decompress(&case, &mut rng, &frame, dict.as_deref().unwrap_or(&[]));
+        assert!(decoded == data, "round trip differs");
+        if dict.is_none() {
+            assert!(
+                decompress_frame_to_vec(&frame).unwrap() == data,
+                "one-shot decode differs"
+            );
+        }
+    }));
+    if let Err(e) = result {
+        let msg = e
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| e.downcast_ref::<&str>().copied())
+            .unwrap_or("panic");
+        panic!("stress case {index} (LZ4_STRESS_SEED={seed}) failed: {msg}\n{case:#?}");
+    }
+}
+
+#[test]
+fn stress_random_round_trips() {
+    let seed = env_or("LZ4_STRESS_SEED", DEFAULT_SEED);
+    let cases = env_or("LZ4_STRESS_CASES", DEFAULT_CASES);
+    for index in 0..cases {
+        run_case(seed, index);
+    }
+}
+
+#[test]
+fn stress_cases_are_reproducible() {
+    let draw = |index: u64| {
+        let mut rng = Rng(DEFAULT_SEED ^ index.wrapping_mul(0xA24B_AED4_963E_E407));
+        format!("{:?}", Case::draw(&mut rng))
+    };
+    assert_eq!(draw(7), draw(7));
+    assert_ne!(draw(7), draw(8));
+}
@@ -10,13 +10,12 @@
 //   - Lz4FError::is_error() returns false for OkNoError, true for all others
 //   - lz4f_is_error() boundary matches C LZ4F_isError logic
 //   - lz4f_get_error_name() mirrors C LZ4F_getErrorName output
-//   - DecompressStage discriminants match C dStage_t values and ordering holds
 //   - FrameInfo / Preferences Default initialisation has zero fields
 
 use lz4::frame::types::{
     lz4f_get_error_name, lz4f_is_error, BlockChecksum, BlockCompressMode, BlockMode, BlockSizeId,
-    ContentChecksum, CtxType, DecompressStage, FrameInfo, FrameType, Lz4FError, Preferences,
-    BF_SIZE, BH_SIZE, LZ4F_BLOCKUNCOMPRESSED_FLAG, LZ4F_VERSION, MAX_FH_SIZE, MIN_FH_SIZE,
+    ContentChecksum, CtxType, FrameInfo, FrameType, Lz4FError, Preferences, BF_SIZE, BH_SIZE,
+    LZ4F_BLOCKUNCOMPRESSED_FLAG, LZ4F_VERSION, MAX_FH_SIZE, MIN_FH_SIZE,
 };
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -240,71 +239,6 @@ fn preferences_is_copy() {
     assert_eq!(a.compression_level, b.compression_level);
 }
 
-// ─────────────────────────────────────────────────────────────────────────────
-// DecompressStage — discriminants (lz4frame.c:1248-1258) and ordering
-// ─────────────────────────────────────────────────────────────────────────────
-
-#[test]
-fn decompress_stage_discriminants() {
-    assert_eq!(DecompressStage::GetFrameHeader as u32, 0);
-    assert_eq!(DecompressStage::StoreFrameHeader as u32, 1);
-    assert_eq!(DecompressStage::Init as u32, 2);
-    assert_eq!(DecompressStage::GetBlockHeader as u32, 3);
-    assert_eq!(DecompressStage::StoreBlockHeader as u32, 4);
-    assert_eq!(DecompressStage::CopyDirect as u32, 5);
-    assert_eq!(DecompressStage::GetBlockChecksum as u32, 6);
-    assert_eq!(DecompressStage::GetCBlock as u32, 7);
-    assert_eq!(DecompressStage::StoreCBlock as u32, 8);
-    assert_eq!(DecompressStage::FlushOut as u32, 9);
-    assert_eq!(DecompressStage::GetSuffix as u32, 10);
-    assert_eq!(DecompressStage::StoreSuffix as u32, 11);
-    assert_eq!(DecompressStage::GetSFrameSize as u32, 12);
-    assert_eq!(DecompressStage::StoreSFrameSize as u32, 13);
-    assert_eq!(DecompressStage::SkipSkippable as u32, 14);
-}
-
-#[test]
-fn decompress_stage_default_is_get_frame_header() {
-    let d: DecompressStage = Default::default();
-    assert_eq!(d, DecompressStage::GetFrameHeader);
-}
-
-#[test]
-fn decompress_stage_ordering_le_init() {
-    // C code uses comparisons like `dStage <= dstage_init` (== Init == 2)
-    // All stages 0-2 must compare ≤ Init; stages 3+ must compare > Init.
-    assert!(DecompressStage::GetFrameHeader <= DecompressStage::Init);
-    assert!(DecompressStage::StoreFrameHeader <= DecompressStage::Init);
-    assert!(DecompressStage::Init <= DecompressStage::Init);
-    assert!(DecompressStage::GetBlockHeader > DecompressStage::Init);
-    assert!(DecompressStage::SkipSkippable > DecompressStage::Init);
-}
-
-#[test]
-fn decompress_stage_ordering_monotone() {
-    // Each stage must be strictly less than the next
-    let stages = [
-        DecompressStage::GetFrameHeader,
-        DecompressStage::StoreFrameHeader,
-        DecompressStage::Init,
-        DecompressStage::GetBlockHeader,
-        DecompressStage::StoreBlockHeader,
-        DecompressStage::CopyDirect,
-        DecompressStage::GetBlockChecksum,
-        DecompressStage::GetCBlock,
-        DecompressStage::StoreCBlock,
-        DecompressStage::FlushOut,
-        DecompressStage::GetSuffix,
-        DecompressStage::StoreSuffix,
-        DecompressStage::GetSFrameSize,
-        DecompressStage::StoreSFrameSize,
-        DecompressStage::SkipSkippable,
-    ];
-    for w in stages.windows(2) {
-        assert!(w[0] < w[1], "{:?} should be < {:?}", w[0], w[1]);
-    }
-}
-
 // ─────────────────────────────────────────────────────────────────────────────
 // Lz4FError::error_name — byte-identical to C LZ4F_errorStrings[] (lz4frame.c:286-316)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -0,0 +1,85 @@
+// Tests for frame::wire — encode_prefixed_block / decode_prefixed_block
+//
+// Covers:
+//   - Round-trip across sizes, and the block matching compress_block_to_vec
+//   - Smaller than a frame for short messages
+//   - The size limit is enforced before decoding
+//   - Error paths: short prefix, corrupt block, size prefix that disagrees
+//     with the block
+
+use lz4::block::compress_block_to_vec;
+use lz4::frame::wire::{prefixed_block_bound, prefixed_block_size, PREFIX_SIZE};
+use lz4::frame::{compress_frame_to_vec, decode_prefixed_block, encode_prefixed_block, Lz4FError};
+
+fn sample(len: usize) -> Vec<u8> {
+    (0..len as u32)
+        .map(|i| ((i % 97) ^ (i / 13)) as u8)
+        .collect()
+}
+
+#[test]
+fn round_trip_across_sizes() {
+    for len in [0, 1, 12, 13, 1000, 65_536, 300_001] {
+        let data = sample(len);
+        let msg = encode_prefixed_block(&data).unwrap();
+        assert!(msg.len() <= prefixed_block_bound(len), "len {len}");
+        assert_eq!(prefixed_block_size(&msg), Ok(len));
+        assert_eq!(&msg[PREFIX_SIZE..], &compress_block_to_vec(&data)[..]);
+        assert_eq!(decode_prefixed_block(&msg, len).unwrap(), data, "len {len}");
+    }
+}
+
+#[test]
+fn smaller_than_a_frame() {
+    let data = b"GET /status";
+    let msg = encode_prefixed_block(data).unwrap();
+    assert!(msg.len() < compress_frame_to_vec(data).len());
+}
+
+#[test]
+fn declared_size_above_limit_is_refused() {
+    let data = sample(5000);
+    let msg = encode_prefixed_block(&data).unwrap();
+    assert_eq!(
+        decode_prefixed_block(&msg, 4999),
+        Err(Lz4FError::OutputLimitExceeded)
+    );
+
+    // A forged prefix is refused without allocating the claimed size.
+    let mut forged = msg.clone();
+    forged[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(
+        decode_prefixed_block(&forged, 1 << 20),
+        Err(Lz4FError::OutputLimitExceeded)
+    );
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    assert_eq!(
+        decode_prefixed_block(&[1, 0, 0], 10),
+        Err(Lz4FError::FrameHeaderIncomplete)
+    );
+    assert_eq!(
+        prefixed_block_size(&[]),
+        Err(Lz4FError::FrameHeaderIncomplete)
+    );
+
+    let data = sample(2000);
+    let msg = encode_prefixed_block(&data).unwrap();
+    // Truncated block.
+    assert_eq!(
+        decode_prefixed_block(&msg[..msg.len() - 3], 2000),
+        Err(Lz4FError::DecompressionFailed)
+    );
+    // Prefix larger or smaller than what the block decodes to.
+    for declared in [1999u32, 2001] {
+        let mut wrong = msg.clone();
+        wrong[..4].copy_from_slice(&declared.to_le_bytes());
+        assert_eq!(
+            decode_prefixed_block(&wrong, 4000),
+            Err(Lz4FError::DecompressionFailed),
+            "declared {declared}"
+        );
+    }
+}
@@ -0,0 +1,249 @@
+// Allocation tests for frame decompression (frame::decompress).
+//
+// Lives in its own test binary because it installs a counting global
+// allocator.  Counts are per thread, so parallel tests do not interfere.
+//
+//   - Staging buffers are sized at header time: a frame allocates a fixed
+//     number of times however many blocks it has, on the direct, buffered
+//     (StoreCBlock) and tmp-out paths
+//   - A context reused for a second frame of the same geometry allocates nothing
+//   - decompress_frame_into allocates nothing at all
+//   - reset_keep_buffers, even mid-frame, frees and allocates nothing, and the
+//     next frame reuses the kept buffers
+//   - Lz4FCCtx::reset_for_reuse sizes the encoder up front: the frames after
+//     it allocate nothing, and resetting a warm context, even mid-frame,
+//     allocates nothing either
+
+#![cfg(all(feature = "frame", feature = "hc", not(feature = "forbid-unsafe")))]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use lz4::frame::types::LZ4F_VERSION;
+use lz4::frame::{
+    compress_to_writer, decompress_frame_into, lz4f_compress_begin, lz4f_compress_end,
+    lz4f_compress_frame_bound, lz4f_compress_update, lz4f_create_compression_context,
+    lz4f_create_decompression_context, lz4f_decompress, lz4f_reset_decompression_context,
+    BlockChecksum, BlockMode, BlockSizeId, ContentChecksum, FrameInfo, Lz4FCCtx, Lz4FDCtx,
+    Preferences,
+};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCS.try_with(|n| n.set(n.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocs() -> usize {
+    ALLOCS.with(Cell::get)
+}
+
+fn frame(mode: BlockMode) -> (Vec<u8>, Vec<u8>) {
+    // Short repeats over noise: blocks compress, but stay well over the
+    // 1000-byte input chunks used below.
+    let mut x: u32 = 1;
+    let data: Vec<u8> = (0..3_000_000u32)
+        .map(|i| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            if i % 64 < 24 {
+                b"0123456789abcdefghijklmn"[(i % 64) as usize]
+            } else {
+                x as u8
+            }
+        })
+        .collect();
+    let prefs = Preferences {
+        frame_info: FrameInfo {
+            block_size_id: BlockSizeId::Max64Kb,
+            block_mode: mode,
+            block_checksum_flag: BlockChecksum::Enabled,
+            content_checksum_flag: ContentChecksum::Enabled,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    compress_to_writer(&mut out, &data, Some(&prefs)).unwrap();
+    (data, out)
+}
+
+/// Decodes `src` fed `in_chunk` bytes at a time into `out_chunk`-byte
+/// windows of `out`; returns the allocations made while decoding.
+fn decode(
+    dctx: &mut Lz4FDCtx,
+    src: &[u8],
+    out: &mut [u8],
+    in_chunk: usize,
+    out_chunk: usize,
+) -> usize {
+    let before = allocs();
+    let (mut ip, mut op) = (0, 0);
+    loop {
+        let in_end = (ip + in_chunk).min(src.len());
+        let out_end = (op + out_chunk).min(out.len());
+        let (consumed, written, hint) =
+            lz4f_decompress(dctx, Some(&mut out[op..out_end]), &src[ip..in_end], None).unwrap();
+        ip += consumed;
+        op += written;
+        if hint == 0 {
+            break;
+        }
+    }
+    assert_eq!(ip, src.len());
+    assert_eq!(op, out.len());
+    allocs() - before
+}
+
+#[test]
+fn block_decoding_does_not_allocate() {
+    for mode in [BlockMode::Independent, BlockMode::Linked] {
+        let (data, src) = frame(mode);
+        assert!(src.len() > data.len() / 2);
+        let mut out = vec![0u8; data.len()];
+        // (input chunk, output window): whole blocks straight into dst,
+        // blocks split across calls, and output narrower than a block.
+        for (in_chunk, out_chunk) in [(1 << 20, 1 << 22), (1000, 1 << 22), (1 << 20, 10_000)] {
+            let mut dctx = lz4f_create_decompression_context(LZ4F_VERSION).unwrap();
+            let first = decode(&mut dctx, &src, &mut out, in_chunk, out_chunk);
+            assert_eq!(out, data);
+            // tmp_in, tmp_out and the linked-mode history, once per frame.
+            assert!(
+                first <= 3,
+                "{mode:?} {in_chunk}/{out_chunk}: {first} allocations"
+            );
+
+            out.fill(0);
+            let second = decode(&mut dctx, &src, &mut out, in_chunk, out_chunk);
+            assert_eq!(out, data);
+            assert_eq!(second, 0, "{mode:?} {in_chunk}/{out_chunk}: reused context");
+        }
+    }
+}
+
+#[test]
+fn decompress_frame_into_does_not_allocate() {
+    for mode in [BlockMode::Independent, BlockMode::Linked] {
+        let (data, src) = frame(mode);
+        let mut out = vec![0u8; data.len()];
+        let before = allocs();
+        let n = decompress_frame_into(&mut out, &src).unwrap();
+        assert_eq!(allocs() - before, 0, "{mode:?}");
+        assert_eq!(n, data.len());
+        assert_eq!(out, data);
+    }
+}
+
+#[test]
+fn reset_keep_buffers_is_allocation_free() {
+    for mode in [BlockMode::Independent, BlockMode::Linked] {
+        let (data, src) = frame(mode);
+        let mut out = vec![0u8; data.len()];
+        let mut dctx = lz4f_create_decompression_context(LZ4F_VERSION).unwrap();
+        decode(&mut dctx, &src, &mut out, 1 << 20, 10_000);
+
+        // Abandon the next frame partway, with output staged in tmp_out.
+        let mut part = vec![0u8; 1000];
+        lz4f_decompress(&mut dctx, Some(&mut part), &src[..src.len() / 2], None).unwrap();
+        let capacity = dctx.buffer_capacity();
+
+        let before = allocs();
+        dctx.reset_keep_buffers();
+        lz4f_reset_decompression_context(&mut dctx);
+        assert_eq!(allocs() - before, 0, "{mode:?}: reset");
+        assert_eq!(dctx.buffer_capacity(), capacity, "{mode:?}");
+
+        out.fill(0);
+        let n = decode(&mut dctx, &src, &mut out, 1 << 20, 10_000);
+        assert_eq!(out, data);
+        assert_eq!(n, 0, "{mode:?}: frame after reset");
+    }
+}
+
+/// Compresses `data` as one frame into `dst`, `chunk` bytes per update;
+/// returns the frame length and the allocations made.
+fn encode(
+    cctx: &mut Lz4FCCtx,
+    prefs: &Preferences,
+    data: &[u8],
+    dst: &mut [u8],
+    chunk: usize,
+) -> (usize, usize) {
+    let before = allocs();
+    let mut pos = lz4f_compress_begin(cctx, dst, Some(prefs)).unwrap();
+    for piece in data.chunks(chunk) {
+        pos += lz4f_compress_update(cctx, &mut dst[pos..], piece, None).unwrap();
+    }
+    pos += lz4f_compress_end(cctx, &mut dst[pos..], None).unwrap();
+    (pos, allocs() - before)
+}
+
+#[test]
+fn reset_for_reuse_is_allocation_free() {
+    for (mode, level) in [
+        (BlockMode::Independent, 1),
+        (BlockMode::Linked, 1),
+        (BlockMode::Linked, 9),
+    ] {
+        let (data, _) = frame(mode);
+        let prefs = Preferences {
+            frame_info: FrameInfo {
+                block_size_id: BlockSizeId::Max256Kb,
+                block_mode: mode,
+                content_checksum_flag: ContentChecksum::Enabled,
+                ..Default::default()
+            },
+            compression_level: level,
+            ..Default::default()
+        };
+        let mut dst = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(&prefs))];
+        let mut out = vec![0u8; data.len()];
+        let mut cctx = lz4f_create_compression_context(lz4::frame::types::LZ4F_VERSION).unwrap();
+
+        cctx.reset_for_reuse(&prefs).unwrap();
+        let capacity = cctx.buffer_capacity();
+        assert!(capacity > 0, "{mode:?}/{level}: sized up front");
+        let (n, a) = encode(&mut cctx, &prefs, &data, &mut dst, 100_000);
+        assert_eq!(a, 0, "{mode:?}/{level}: frame after reset_for_reuse");
+        assert_eq!(
+            decompress_frame_into(&mut out, &dst[..n]).unwrap(),
+            data.len()
+        );
+        assert_eq!(out, data);
+
+        // Abandon the next frame partway, with input staged in tmp_buf.
+        lz4f_compress_begin(&mut cctx, &mut dst, Some(&prefs)).unwrap();
+        lz4f_compress_update(&mut cctx, &mut dst[100..], &data[..300_000], None).unwrap();
+        let before = allocs();
+        cctx.reset_for_reuse(&prefs).unwrap();
+        assert_eq!(allocs() - before, 0, "{mode:?}/{level}: reset");
+        assert_eq!(cctx.buffer_capacity(), capacity, "{mode:?}/{level}");
+
+        out.fill(0);
+        let (n, a) = encode(&mut cctx, &prefs, &data, &mut dst, 100_000);
+        assert_eq!(a, 0, "{mode:?}/{level}: frame after mid-frame reset");
+        assert_eq!(
+            decompress_frame_into(&mut out, &dst[..n]).unwrap(),
+            data.len()
+        );
+        assert_eq!(out, data);
+    }
+}
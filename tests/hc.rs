@@ -1,3 +1,5 @@
+#![cfg(all(feature = "hc", not(feature = "forbid-unsafe")))]
+
 #[path = "hc/api.rs"]
 mod api;
 #[path = "hc/compress.rs"]
@@ -6,6 +8,8 @@ mod compress;
 mod dispatch;
 #[path = "hc/encode.rs"]
 mod encode;
+#[path = "hc/ext_dict.rs"]
+mod ext_dict;
 #[path = "hc/lz4mid.rs"]
 mod lz4mid;
 #[path = "hc/search.rs"]
@@ -4,6 +4,7 @@
 //   `LZ4_sizeofStateHC`                     → `sizeof_state_hc`
 //   `LZ4_createStreamHC` / Drop             → `Lz4StreamHc::create` / Drop
 //   `LZ4_initStreamHC`                      → `init_stream_hc`
+//   `LZ4_initStreamHC` (caller's buffer)    → `init_stream_hc_in`, `init_stream_hc_in_buffer`
 //   `LZ4_compress_HC_extStateHC_fastReset`  → `compress_hc_ext_state_fast_reset`
 //   `LZ4_compress_HC_extStateHC`            → `compress_hc_ext_state`
 //   `LZ4_compress_HC`                       → `compress_hc`
@@ -15,20 +16,26 @@
 //   `LZ4_loadDictHC`                        → `load_dict_hc`
 //   `LZ4_attach_HC_dictionary`              → `attach_hc_dictionary`
 //   `LZ4_compress_HC_continue`              → `compress_hc_continue`
-//   `LZ4_compress_HC_continue_destSize`     → `compress_hc_continue_dest_size`
+//   `LZ4_compress_HC_continue_destSize`     → `compress_hc_continue_dest_size`,
+//                                              `compress_continue_fitting`
 //   `LZ4_saveDictHC`                        → `save_dict_hc`
+//   (extension)                             → `Lz4StreamHc::compress_block_with_history`
+//   (extension)                             → `compress_hc_bound`, `compress_hc_to_vec`
 //
 // All tests operate on the public API only; internal fields of Lz4StreamHc
 // (which are pub(crate)) are not accessed directly.
 
 use lz4::block::decompress_api::decompress_safe;
+use lz4::block::decompress_core::decompress_safe_using_dict;
 use lz4::hc::api::{
-    attach_hc_dictionary, compress_hc, compress_hc_continue, compress_hc_continue_dest_size,
-    compress_hc_dest_size, compress_hc_ext_state, compress_hc_ext_state_fast_reset,
-    favor_decompression_speed, init_stream_hc, load_dict_hc, reset_stream_hc, reset_stream_hc_fast,
-    save_dict_hc, set_compression_level, sizeof_state_hc, Lz4StreamHc,
+    attach_hc_dictionary, compress_continue_fitting, compress_hc, compress_hc_bound,
+    compress_hc_continue, compress_hc_continue_dest_size, compress_hc_dest_size,
+    compress_hc_ext_state, compress_hc_ext_state_fast_reset, compress_hc_to_vec,
+    favor_decompression_speed, init_stream_hc, init_stream_hc_in, init_stream_hc_in_buffer,
+    load_dict_hc, reset_stream_hc, reset_stream_hc_fast, save_dict_hc, set_compression_level,
+    sizeof_state_hc, Lz4StreamHc,
 };
-use lz4::hc::types::{HcCCtxInternal, LZ4HC_CLEVEL_DEFAULT, LZ4HC_CLEVEL_MAX};
+use lz4::hc::types::{CompressionStrategy, HcCCtxInternal, LZ4HC_CLEVEL_DEFAULT, LZ4HC_CLEVEL_MAX};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Helpers
@@ -141,6 +148,62 @@ fn init_stream_hc_sets_default_level_for_compression() {
     assert_eq!(recovered, src);
 }
 
+// ═════════════════════════════════════════════════════════════════════════════
+// init_stream_hc_in / init_stream_hc_in_buffer  (LZ4_initStreamHC on a buffer)
+// ═════════════════════════════════════════════════════════════════════════════
+
+/// Compress `src` at `level` with `state`, returning the block.
+fn compress_with_state(state: &mut Lz4StreamHc, src: &[u8], level: i32) -> Vec<u8> {
+    let mut dst = vec![0u8; src.len() + 64];
+    let n = unsafe {
+        compress_hc_ext_state(
+            state,
+            src.as_ptr(),
+            dst.as_mut_ptr(),
+            src.len() as i32,
+            dst.len() as i32,
+            level,
+        )
+    };
+    assert!(n > 0);
+    dst.truncate(n as usize);
+    dst
+}
+
+/// A state initialised in an uninitialised slot matches a heap-created one.
+#[test]
+fn init_stream_hc_in_matches_create() {
+    let src: Vec<u8> = (0..20_000u32)
+        .map(|i| ((i % 251) ^ (i / 97)) as u8)
+        .collect();
+    let mut slot = Box::<Lz4StreamHc>::new_uninit();
+    let placed = init_stream_hc_in(&mut slot);
+    assert_eq!(placed.forced_strategy(), None);
+    assert_eq!(placed.search_depth(), 0);
+    let mut heap = Lz4StreamHc::create().unwrap();
+    for level in [2, 9, 12] {
+        let a = compress_with_state(placed, &src, level);
+        let b = compress_with_state(&mut heap, &src, level);
+        assert_eq!(a, b, "level {level}");
+        assert_eq!(roundtrip_decompress(&a, a.len(), src.len()), src);
+    }
+}
+
+/// A raw buffer works when large enough and aligned, and is refused otherwise.
+#[test]
+fn init_stream_hc_in_buffer_checks_size_and_alignment() {
+    let align = core::mem::align_of::<Lz4StreamHc>();
+    let mut arena = vec![0xA5u8; sizeof_state_hc() + 2 * align];
+    let off = arena.as_ptr().align_offset(align);
+    let src = repeated_input(4096);
+
+    assert!(init_stream_hc_in_buffer(&mut arena[off + 1..]).is_none());
+    assert!(init_stream_hc_in_buffer(&mut arena[off..off + sizeof_state_hc() - 1]).is_none());
+    let state = init_stream_hc_in_buffer(&mut arena[off..]).unwrap();
+    let block = compress_with_state(state, &src, LZ4HC_CLEVEL_DEFAULT);
+    assert_eq!(roundtrip_decompress(&block, block.len(), src.len()), src);
+}
+
 // ═════════════════════════════════════════════════════════════════════════════
 // set_compression_level  (LZ4_setCompressionLevel)
 // ═════════════════════════════════════════════════════════════════════════════
@@ -734,6 +797,33 @@ fn compress_hc_dest_size_tiny_dst_returns_zero() {
     assert_eq!(n, 0, "impossible to compress into 1 byte");
 }
 
+/// Like `LZ4_compress_HC_destSize`, the call re-initialises the state, so
+/// parser settings made beforehand do not carry over.
+#[test]
+fn compress_hc_dest_size_resets_parser_settings() {
+    let src = lz4::corpus::log_lines(60_000, 3);
+    let pack = |stream: &mut Lz4StreamHc| {
+        let mut dst = vec![0u8; 8_000];
+        let mut src_size = src.len() as i32;
+        let n = unsafe {
+            compress_hc_dest_size(
+                stream,
+                src.as_ptr(),
+                dst.as_mut_ptr(),
+                &mut src_size,
+                dst.len() as i32,
+                LZ4HC_CLEVEL_MAX,
+            )
+        };
+        dst.truncate(n as usize);
+        (src_size, dst)
+    };
+    let mut tuned = Lz4StreamHc::create().unwrap();
+    favor_decompression_speed(&mut tuned, true);
+    tuned.set_search_depth(4);
+    assert_eq!(pack(&mut tuned), pack(&mut Lz4StreamHc::create().unwrap()));
+}
+
 // ═════════════════════════════════════════════════════════════════════════════
 // load_dict_hc  (LZ4_loadDictHC)
 // ═════════════════════════════════════════════════════════════════════════════
@@ -1152,8 +1242,8 @@ fn dict_and_source_data(dict_size: usize, src_size: usize) -> (Vec<u8>, Vec<u8>)
     let dict: Vec<u8> = (0..dict_size).map(|i| (i % 251) as u8).collect();
     let mut src: Vec<u8> = (0..src_size).map(|i| (i % 251) as u8).collect();
     // Add some variation in the second half
-    for i in src_size / 2..src_size {
-        src[i] = ((i * 7 + 13) % 256) as u8;
+    for (i, b) in src.iter_mut().enumerate().skip(src_size / 2) {
+        *b = ((i * 7 + 13) % 256) as u8;
     }
     (dict, src)
 }
@@ -1564,7 +1654,7 @@ fn save_dict_hc_larger_than_prefix() {
     // Request more than was compressed
     let saved = unsafe { save_dict_hc(&mut stream, save_buf.as_mut_ptr(), save_buf.len() as i32) };
     assert!(
-        saved >= 0 && saved <= 64,
+        (0..=64).contains(&saved),
         "saved must not exceed prefix: {saved}"
     );
 }
@@ -1994,8 +2084,8 @@ fn compress_hash_chain_three_match_overlap() {
     src[10..19].copy_from_slice(pattern);
     src[18..27].copy_from_slice(pattern);
     // Fill in gaps
-    for i in 27..512 {
-        src[i] = ((i * 7) % 256) as u8;
+    for (i, b) in src.iter_mut().enumerate().take(512).skip(27) {
+        *b = ((i * 7) % 256) as u8;
     }
     // Add more instances of the pattern to strengthen matches
     for offset in (50..400).step_by(16) {
@@ -2050,8 +2140,8 @@ fn repeating_pattern_across_dict_prefix_boundary() {
 fn ext_dict_repeating_pattern_exercises_search() {
     // Two-byte repeating pattern to exercise pattern-analysis in search.rs
     let mut block1 = vec![0u8; 4096];
-    for i in 0..4096 {
-        block1[i] = if i % 2 == 0 { 0xAB } else { 0xCD };
+    for (i, b) in block1.iter_mut().enumerate() {
+        *b = if i % 2 == 0 { 0xAB } else { 0xCD };
     }
     let mut block2 = block1.clone();
     // Slight variation in block2 to force partial matching
@@ -2427,8 +2517,8 @@ fn compress_hc_short_pattern_level_12() {
 fn compress_hc_continue_overlap_detection() {
     // Use a single large buffer and compress overlapping segments
     let mut buf = vec![0u8; 256 * 1024];
-    for i in 0..buf.len() {
-        buf[i] = (i % 251) as u8;
+    for (i, b) in buf.iter_mut().enumerate() {
+        *b = (i % 251) as u8;
     }
 
     let mut stream = Lz4StreamHc::create().unwrap();
@@ -2441,7 +2531,7 @@ fn compress_hc_continue_overlap_detection() {
             &mut stream,
             buf.as_ptr(),
             dst1.as_mut_ptr(),
-            (64 * 1024) as i32,
+            64 * 1024,
             dst1.len() as i32,
         )
     };
@@ -2455,7 +2545,7 @@ fn compress_hc_continue_overlap_detection() {
             &mut stream,
             buf.as_ptr().add(offset),
             dst1.as_mut_ptr(),
-            (64 * 1024) as i32,
+            64 * 1024,
             dst1.len() as i32,
         )
     };
@@ -2467,7 +2557,7 @@ fn compress_hc_continue_overlap_detection() {
             &mut stream,
             buf.as_ptr().add(128 * 1024),
             dst1.as_mut_ptr(),
-            (64 * 1024) as i32,
+            64 * 1024,
             dst1.len() as i32,
         )
     };
@@ -2545,3 +2635,517 @@ fn compress_hc_continue_save_dict_cycle() {
     }
     assert_eq!(all_compressed.len(), 8);
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Lz4StreamHc::set_search_depth
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn compress_with_depth(data: &[u8], level: i32, depth: u32) -> usize {
+    let mut state = Lz4StreamHc::create().unwrap();
+    state.set_search_depth(depth);
+    let mut dst = vec![0u8; data.len() + 1024];
+    let n = unsafe {
+        compress_hc_ext_state_fast_reset(
+            &mut state,
+            data.as_ptr(),
+            dst.as_mut_ptr(),
+            data.len() as i32,
+            dst.len() as i32,
+            level,
+        )
+    };
+    assert!(n > 0);
+    assert_eq!(
+        roundtrip_decompress(&dst, n as usize, data.len()),
+        data,
+        "level {level} depth {depth}"
+    );
+    n as usize
+}
+
+/// A shallow search budget costs ratio on both the hash-chain and optimal
+/// parsers; 0 reproduces the level default exactly.
+#[test]
+fn search_depth_trades_ratio() {
+    let data = lz4::lorem::gen_buffer(256 * 1024, 17);
+    for level in [9, 12] {
+        let default = compress_with_depth(&data, level, 0);
+        let shallow = compress_with_depth(&data, level, 1);
+        assert!(shallow > default, "level {level}: {shallow} <= {default}");
+    }
+    let mut plain = vec![0u8; data.len() + 1024];
+    let n = unsafe {
+        compress_hc(
+            data.as_ptr(),
+            plain.as_mut_ptr(),
+            data.len() as i32,
+            plain.len() as i32,
+            12,
+        )
+    };
+    assert_eq!(compress_with_depth(&data, 12, 0), n as usize);
+}
+
+/// The override is a caller setting: fast resets keep it, full inits clear it.
+#[test]
+fn search_depth_reset_semantics() {
+    let mut state = Lz4StreamHc::create().unwrap();
+    assert_eq!(state.search_depth(), 0);
+    state.set_search_depth(7);
+    reset_stream_hc_fast(&mut state, 10);
+    set_compression_level(&mut state, 12);
+    assert_eq!(state.search_depth(), 7);
+    init_stream_hc(&mut state);
+    assert_eq!(state.search_depth(), 0);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Lz4StreamHc::force_strategy
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn compress_forced(data: &[u8], level: i32, strategy: Option<CompressionStrategy>) -> Vec<u8> {
+    let mut state = Lz4StreamHc::create().unwrap();
+    state.force_strategy(strategy);
+    let mut dst = vec![0u8; data.len() + 1024];
+    let n = unsafe {
+        compress_hc_ext_state_fast_reset(
+            &mut state,
+            data.as_ptr(),
+            dst.as_mut_ptr(),
+            data.len() as i32,
+            dst.len() as i32,
+            level,
+        )
+    };
+    assert!(n > 0);
+    dst.truncate(n as usize);
+    assert_eq!(
+        roundtrip_decompress(&dst, dst.len(), data.len()),
+        data,
+        "level {level} strategy {strategy:?}"
+    );
+    dst
+}
+
+/// A forced strategy runs that engine with its own level's parameters when the
+/// level already selects it, else with a representative level's parameters.
+#[test]
+fn force_strategy_pins_engine() {
+    let data = lz4::lorem::gen_buffer(128 * 1024, 23);
+    for (level, own) in [
+        (2, CompressionStrategy::Mid),
+        (9, CompressionStrategy::Hc),
+        (12, CompressionStrategy::Optimal),
+    ] {
+        assert_eq!(
+            compress_forced(&data, level, Some(own)),
+            compress_forced(&data, level, None),
+            "level {level}"
+        );
+    }
+    let natural = |level| compress_forced(&data, level, None);
+    assert_eq!(
+        compress_forced(&data, 12, Some(CompressionStrategy::Mid)),
+        natural(2)
+    );
+    assert_eq!(
+        compress_forced(&data, 2, Some(CompressionStrategy::Hc)),
+        natural(LZ4HC_CLEVEL_DEFAULT)
+    );
+    assert_eq!(
+        compress_forced(&data, 3, Some(CompressionStrategy::Optimal)),
+        natural(10)
+    );
+}
+
+/// `Fast` produces exactly what the fast block compressor does.
+#[test]
+fn force_strategy_fast_matches_block_compressor() {
+    let data = lz4::lorem::gen_buffer(100_000, 5);
+    let mut expected = vec![0u8; lz4::block::compress_bound(data.len() as i32) as usize];
+    let n = lz4::block::compress_default(&data, &mut expected).unwrap();
+    expected.truncate(n);
+    for level in [1, 9, 12] {
+        assert_eq!(
+            compress_forced(&data, level, Some(CompressionStrategy::Fast)),
+            expected
+        );
+    }
+}
+
+/// Streaming works with every forced strategy, including `Fast` blocks
+/// interleaved with blocks that reference history, and `FillOutput` mode.
+#[test]
+fn force_strategy_streaming_roundtrip() {
+    let data = lz4::lorem::gen_buffer(96 * 1024, 31);
+    for strategy in [
+        CompressionStrategy::Fast,
+        CompressionStrategy::Mid,
+        CompressionStrategy::Hc,
+        CompressionStrategy::Optimal,
+    ] {
+        let mut state = Lz4StreamHc::create().unwrap();
+        reset_stream_hc_fast(&mut state, 9);
+        state.force_strategy(Some(strategy));
+        let mut decoded = Vec::new();
+        for (i, chunk) in data.chunks(32 * 1024).enumerate() {
+            let mut dst = vec![0u8; chunk.len() + 1024];
+            let n = if i == 1 {
+                let mut src_size = chunk.len() as i32;
+                let n = unsafe {
+                    compress_hc_continue_dest_size(
+                        &mut state,
+                        chunk.as_ptr(),
+                        dst.as_mut_ptr(),
+                        &mut src_size,
+                        dst.len() as i32,
+                    )
+                };
+                assert_eq!(src_size as usize, chunk.len(), "{strategy:?}");
+                n
+            } else {
+                unsafe {
+                    compress_hc_continue(
+                        &mut state,
+                        chunk.as_ptr(),
+                        dst.as_mut_ptr(),
+                        chunk.len() as i32,
+                        dst.len() as i32,
+                    )
+                }
+            };
+            assert!(n > 0, "{strategy:?} block {i}");
+            let mut out = vec![0u8; chunk.len()];
+            let written = lz4::block::decompress_core::decompress_safe_using_dict(
+                &dst[..n as usize],
+                &mut out,
+                &decoded,
+            )
+            .unwrap();
+            assert_eq!(&out[..written], chunk, "{strategy:?} block {i}");
+            decoded.extend_from_slice(chunk);
+        }
+    }
+}
+
+/// The override is a caller setting: fast resets keep it, full inits clear it.
+#[test]
+fn force_strategy_reset_semantics() {
+    let mut state = Lz4StreamHc::create().unwrap();
+    assert_eq!(state.forced_strategy(), None);
+    state.force_strategy(Some(CompressionStrategy::Optimal));
+    reset_stream_hc_fast(&mut state, 3);
+    set_compression_level(&mut state, 5);
+    assert_eq!(state.forced_strategy(), Some(CompressionStrategy::Optimal));
+    init_stream_hc(&mut state);
+    assert_eq!(state.forced_strategy(), None);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Lz4StreamHc::compress_batch
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Each batch item is an independent block identical to a one-shot
+/// `compress_hc` of the same input.
+#[test]
+fn compress_batch_matches_one_shot() {
+    let corpus = lz4::lorem::gen_buffer(40_000, 3);
+    let inputs: Vec<&[u8]> = vec![&corpus[..10_000], &[], &corpus[5_000..], b"tiny"];
+    let mut state = Lz4StreamHc::create().unwrap();
+    let blocks = state.compress_batch(&inputs, 9);
+    assert_eq!(blocks.len(), inputs.len());
+    for (src, block) in inputs.iter().zip(&blocks) {
+        let mut one = vec![0u8; src.len() + 64];
+        let n = unsafe {
+            compress_hc(
+                src.as_ptr(),
+                one.as_mut_ptr(),
+                src.len() as i32,
+                one.len() as i32,
+                9,
+            )
+        };
+        assert_eq!(&one[..n as usize], &block[..]);
+        assert_eq!(
+            roundtrip_decompress(block, block.len(), src.len()),
+            src.to_vec()
+        );
+    }
+}
+
+/// An attached dictionary is shared by every item in the batch.
+#[test]
+fn compress_batch_reuses_attached_dictionary() {
+    let dict = lz4::lorem::gen_buffer(32 * 1024, 8);
+    let mut dict_stream = Lz4StreamHc::create().unwrap();
+    reset_stream_hc(&mut dict_stream, 9);
+    unsafe { load_dict_hc(&mut dict_stream, dict.as_ptr(), dict.len() as i32) };
+
+    let inputs: Vec<&[u8]> = vec![&dict[100..2_100], &dict[20_000..23_000], &dict[..500]];
+    let mut state = Lz4StreamHc::create().unwrap();
+    reset_stream_hc_fast(&mut state, 9);
+    unsafe { attach_hc_dictionary(&mut state, Some(&*dict_stream as *const Lz4StreamHc)) };
+    let with_dict = state.compress_batch(&inputs, 9);
+
+    let mut plain_state = Lz4StreamHc::create().unwrap();
+    let plain = plain_state.compress_batch(&inputs, 9);
+
+    for ((src, block), plain) in inputs.iter().zip(&with_dict).zip(&plain) {
+        assert!(
+            block.len() < plain.len() / 4,
+            "{} vs {}",
+            block.len(),
+            plain.len()
+        );
+        let mut out = vec![0u8; src.len()];
+        let n = lz4::block::safe::decompress_safe_with_dict(block, &mut out, &dict).unwrap();
+        assert_eq!(&out[..n], *src);
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Lz4StreamHc::compress_block_with_history
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Blocks staged through two rotating pool buffers chain through their
+/// explicit history and decode with it as the dictionary.
+#[test]
+fn block_with_history_chains_rotated_buffers() {
+    const BLOCK: usize = 16 * 1024;
+    let corpus = lz4::lorem::gen_buffer(8 * BLOCK, 4);
+    let mut pool = [vec![0u8; BLOCK], vec![0u8; BLOCK]];
+    let mut state = Lz4StreamHc::create().unwrap();
+    reset_stream_hc(&mut state, 9);
+    let mut dst = vec![0u8; BLOCK + 1024];
+    let (mut chained, mut alone) = (0, 0);
+    for (i, chunk) in corpus.chunks(BLOCK).enumerate() {
+        let (front, back) = pool.split_at_mut(1);
+        let (cur, prev) = if i % 2 == 0 {
+            (&mut front[0], &back[0])
+        } else {
+            (&mut back[0], &front[0])
+        };
+        cur.copy_from_slice(chunk);
+        let history: &[u8] = if i == 0 { &[] } else { prev };
+        let n = state.compress_block_with_history(cur, history, &mut dst);
+        assert!(n > 0);
+        let mut out = vec![0u8; BLOCK];
+        let written = decompress_safe_using_dict(&dst[..n], &mut out, history).unwrap();
+        assert_eq!(&out[..written], chunk, "block {i}");
+        chained += n;
+        alone += state.compress_block_with_history(cur, &[], &mut dst);
+    }
+    assert!(chained < alone, "{chained} vs {alone}");
+}
+
+/// With no history the block matches a one-shot `compress_hc`; only the last
+/// 64 KB of a longer history are used.
+#[test]
+fn block_with_history_edge_cases() {
+    let corpus = lz4::lorem::gen_buffer(200_000, 5);
+    let src = &corpus[150_000..160_000];
+    let mut state = Lz4StreamHc::create().unwrap();
+    reset_stream_hc(&mut state, 9);
+
+    let mut dst = vec![0u8; 12_000];
+    let n = state.compress_block_with_history(src, &[], &mut dst);
+    let mut one = vec![0u8; 12_000];
+    let m = unsafe {
+        compress_hc(
+            src.as_ptr(),
+            one.as_mut_ptr(),
+            src.len() as i32,
+            one.len() as i32,
+            9,
+        )
+    };
+    assert_eq!(&dst[..n], &one[..m as usize]);
+
+    let long = &corpus[..150_000];
+    let n = state.compress_block_with_history(src, long, &mut dst);
+    let mut out = vec![0u8; src.len()];
+    let tail = &long[long.len() - 64 * 1024..];
+    let written = decompress_safe_using_dict(&dst[..n], &mut out, tail).unwrap();
+    assert_eq!(&out[..written], src);
+
+    // A destination that is too small fails without spoiling the stream.
+    assert_eq!(
+        state.compress_block_with_history(src, long, &mut [0u8; 16]),
+        0
+    );
+    assert_eq!(state.compress_block_with_history(src, long, &mut dst), n);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// compress_continue_fitting
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Compresses `src` with `compress_continue_fitting` into `cap`-byte blocks,
+/// continuing each call at the first unconsumed byte (in place, or from a
+/// fresh copy of the remainder when `copy_rest`), and decodes the chain with
+/// each block's history being everything decoded before it.
+fn fitting_round_trip(src: &[u8], cap: usize, level: i32, copy_rest: bool) -> usize {
+    let mut state = Lz4StreamHc::create().unwrap();
+    reset_stream_hc(&mut state, level);
+    // Copies must outlive the stream's references to them.
+    let mut copies: Vec<Vec<u8>> = Vec::new();
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+    while pos < src.len() {
+        let input = if copy_rest && pos > 0 {
+            copies.push(src[pos..].to_vec());
+            copies.last().unwrap().as_slice()
+        } else {
+            &src[pos..]
+        };
+        let mut dst = vec![0u8; cap];
+        let (consumed, written) = compress_continue_fitting(&mut state, input, &mut dst);
+        assert!(
+            consumed > 0 && written > 0 && written <= cap,
+            "stalled at {pos}"
+        );
+        dst.truncate(written);
+        blocks.push((dst, consumed));
+        pos += consumed;
+    }
+
+    let mut out = vec![0u8; src.len()];
+    let mut done = 0;
+    for (block, consumed) in &blocks {
+        let (history, rest) = out.split_at_mut(done);
+        let n = decompress_safe_using_dict(block, &mut rest[..*consumed], history)
+            .unwrap_or_else(|e| panic!("block at {done}: {e:?}"));
+        assert_eq!(n, *consumed);
+        done += n;
+    }
+    assert_eq!(out, src, "level {level}, cap {cap}, copy_rest {copy_rest}");
+    blocks.len()
+}
+
+fn fitting_sample(n: usize) -> Vec<u8> {
+    let words = [
+        &b"alpha "[..],
+        b"beta ",
+        b"gamma ",
+        b"delta ",
+        b"epsilon ",
+        b"zeta\n",
+    ];
+    let mut out = Vec::with_capacity(n);
+    let mut x: u32 = 12345;
+    while out.len() < n {
+        x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        out.extend_from_slice(words[(x >> 16) as usize % words.len()]);
+        if x.is_multiple_of(7) {
+            out.push((x >> 8) as u8);
+        }
+    }
+    out.truncate(n);
+    out
+}
+
+/// Partial consumption never writes past `dst` and reports exact sizes.
+#[test]
+fn compress_continue_fitting_partial_consumption() {
+    let src = fitting_sample(50_000);
+    for level in [2, 4, 9, 12] {
+        let mut state = Lz4StreamHc::create().unwrap();
+        reset_stream_hc(&mut state, level);
+        let mut dst = vec![0xA5u8; 1000 + 16];
+        let (consumed, written) = compress_continue_fitting(&mut state, &src, &mut dst[..1000]);
+        assert!(consumed > 0 && consumed < src.len(), "level {level}");
+        assert!(written > 990 && written <= 1000, "level {level}: {written}");
+        assert!(dst[1000..].iter().all(|&b| b == 0xA5), "level {level}");
+        let mut out = vec![0u8; consumed];
+        assert_eq!(decompress_safe(&dst[..written], &mut out), Ok(consumed));
+        assert_eq!(out, &src[..consumed]);
+    }
+}
+
+#[test]
+fn compress_continue_fitting_history_is_consistent() {
+    let src = fitting_sample(100_000);
+    for level in [2, 9, 10] {
+        for cap in [300, 4096] {
+            for copy_rest in [false, true] {
+                assert!(fitting_round_trip(&src, cap, level, copy_rest) > 1);
+            }
+        }
+    }
+    // Large blocks keep using the history: fewer of them than without it.
+    let chained = fitting_round_trip(&src, 4096, 9, false);
+    let mut fresh = 0;
+    let mut pos = 0;
+    while pos < src.len() {
+        let mut state = Lz4StreamHc::create().unwrap();
+        reset_stream_hc(&mut state, 9);
+        let mut dst = vec![0u8; 4096];
+        pos += compress_continue_fitting(&mut state, &src[pos..], &mut dst).0;
+        fresh += 1;
+    }
+    assert!(chained < fresh, "{chained} vs {fresh}");
+}
+
+// ═════════════════════════════════════════════════════════════════════════════
+// compress_hc_bound / compress_hc_to_vec  (extension)
+// ═════════════════════════════════════════════════════════════════════════════
+
+#[test]
+fn compress_hc_bound_equals_compress_bound() {
+    for n in [0usize, 1, 15, 255, 65_536, 1 << 24, 0x7E00_0000] {
+        assert_eq!(
+            compress_hc_bound(n),
+            lz4::block::compress_bound(n as i32) as usize
+        );
+    }
+    assert_eq!(compress_hc_bound(0x7E00_0001), 0);
+    assert_eq!(compress_hc_bound(usize::MAX), 0);
+}
+
+#[test]
+fn compress_hc_to_vec_round_trips_at_every_level() {
+    // Incompressible input is the worst case for the bound.
+    let noise: Vec<u8> = (0..20_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect();
+    for src in [Vec::new(), repeated_input(50_000), noise] {
+        for level in [-1, 1, 2, 9, LZ4HC_CLEVEL_MAX, 99] {
+            let c = compress_hc_to_vec(&src, level);
+            assert!(!c.is_empty(), "level {level}");
+            assert!(c.len() <= compress_hc_bound(src.len()));
+            assert_eq!(roundtrip_decompress(&c, c.len(), src.len()), src);
+        }
+    }
+    // Same bytes as the pointer API.
+    let src = repeated_input(10_000);
+    let mut dst = vec![0u8; compress_hc_bound(src.len())];
+    let n = unsafe {
+        compress_hc(
+            src.as_ptr(),
+            dst.as_mut_ptr(),
+            src.len() as i32,
+            dst.len() as i32,
+            LZ4HC_CLEVEL_DEFAULT,
+        )
+    };
+    assert_eq!(
+        compress_hc_to_vec(&src, LZ4HC_CLEVEL_DEFAULT),
+        &dst[..n as usize]
+    );
+}
+
+#[test]
+fn compress_hc_to_vec_handles_inputs_shorter_than_mflimit() {
+    // The match-search limits of these inputs fall before the buffer.
+    let src = repeated_input(16);
+    for len in 0..=src.len() {
+        for level in [2, LZ4HC_CLEVEL_DEFAULT, LZ4HC_CLEVEL_MAX] {
+            let c = compress_hc_to_vec(&src[..len], level);
+            assert_eq!(
+                roundtrip_decompress(&c, c.len(), len),
+                &src[..len],
+                "len {len}, level {level}"
+            );
+        }
+    }
+}
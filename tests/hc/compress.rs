@@ -206,7 +206,7 @@ fn lz4hc_optimal_clone_copy() {
         litlen: 3,
     };
     let b = a; // Copy
-    let c = a.clone(); // Clone
+    let c = a; // Clone
     assert_eq!(b.price, 10);
     assert_eq!(c.off, 5);
     assert_eq!(c.mlen, 4);
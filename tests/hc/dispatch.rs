@@ -151,8 +151,8 @@ fn is_compatible_reflexive() {
 #[test]
 fn set_external_dict_basic_state() {
     // Build a fake prefix window: 128 bytes.
-    let prefix = vec![0u8; 128];
-    let new_block_data = vec![0u8; 64];
+    let prefix = [0u8; 128];
+    let new_block_data = [0u8; 64];
 
     unsafe {
         let mut ctx = HcCCtxInternal::new();
@@ -200,8 +200,8 @@ fn set_external_dict_basic_state() {
 #[test]
 fn set_external_dict_short_prefix_no_insert() {
     // 3-byte prefix — too short for insert (requires >= 4 bytes).
-    let prefix = vec![0xAA_u8; 3];
-    let new_block = vec![0xBB_u8; 32];
+    let prefix = [0xAA_u8; 3];
+    let new_block = [0xBB_u8; 32];
 
     unsafe {
         let mut ctx = HcCCtxInternal::new();
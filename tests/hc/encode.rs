@@ -258,7 +258,7 @@ fn large_match_exactly_mlmask_plus_minmatch() {
     // token low nibble = 0xF; extension: remaining = 15-15 = 0 → one byte 0x00
     // literal_length = 0
     // Output: token(1) + offset(2) + ext_byte(1) = 4 bytes
-    let input = vec![0xDD_u8; 64];
+    let input = [0xDD_u8; 64];
     let mut output = make_output(64);
 
     unsafe {
@@ -351,7 +351,7 @@ fn large_match_510_fast_path() {
 #[test]
 fn offset_large_little_endian() {
     // offset = 0x1234 = 4660  → LE bytes [0x34, 0x12]
-    let input = vec![0xFF_u8; 64];
+    let input = [0xFF_u8; 64];
     let mut output = make_output(64);
 
     unsafe {
@@ -385,7 +385,7 @@ fn offset_large_little_endian() {
 #[test]
 fn offset_max_65535() {
     // Maximum offset = 65535 = 0xFFFF → LE bytes [0xFF, 0xFF]
-    let input = vec![0x00_u8; 64];
+    let input = [0x00_u8; 64];
     let mut output = make_output(64);
 
     unsafe {
@@ -612,7 +612,7 @@ fn token_nibbles_combined_correctly() {
 fn error_type_traits() {
     let e = Lz4HcError::OutputTooSmall;
     let e2 = e; // Copy
-    let e3 = e.clone(); // Clone
+    let e3 = e; // Clone
     assert_eq!(e, e2); // PartialEq
     assert_eq!(e, e3);
     // Debug formatting does not panic
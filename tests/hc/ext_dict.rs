@@ -0,0 +1,128 @@
+// Ratio harness for HC streaming in ext-dict mode.
+//
+// Covers:
+//   - `compress_hc_continue` over blocks that are contiguous in memory
+//     (prefix mode) versus blocks copied one at a time into a reused buffer
+//     with `save_dict_hc` in between, so every block after the first sees
+//     its history as an external dictionary
+//   - Both streams decode to the input
+//   - For blocks of at least 64 KiB, where the external dictionary holds the
+//     whole window, ext-dict output stays within a small tolerance of the
+//     contiguous output at every HC level, guarding the dispatch and search
+//     ext-dict paths against silent ratio regressions
+//
+// Smaller blocks are not compared: an external dictionary is only the
+// previous segment, as in the reference, so their window is inherently
+// shorter.
+
+use lz4::block::decompress_core::decompress_safe_using_dict;
+use lz4::corpus::{compressible, json_records, log_lines};
+use lz4::hc::api::{
+    compress_hc_bound, compress_hc_continue, reset_stream_hc, save_dict_hc, Lz4StreamHc,
+};
+
+/// Lz4Mid, the first and last HC chain levels, and the optimal parser
+/// with and without full-update search.
+const LEVELS: [i32; 6] = [2, 3, 9, 10, 11, 12];
+
+/// Largest allowed growth of ext-dict output over contiguous output for the
+/// HC chain and optimal parser levels (3 and up).
+const TOLERANCE: f64 = 0.002;
+
+/// Same, for the Lz4Mid level (2).  Like the reference it does not index the
+/// tail of the old segment when switching to an external dictionary, so it
+/// loses a few matches at each block boundary.
+const TOLERANCE_MID: f64 = 0.03;
+
+/// Compresses `data` in `block_size` blocks with one HC stream.
+///
+/// Contiguous: blocks are compressed in place, so the stream's history is
+/// the input just before each block.  Ext-dict: each block is copied into
+/// the same reused buffer and the history is moved aside with
+/// `save_dict_hc` after every block, so each later block sees its 64 KiB of
+/// history as an external dictionary.
+fn compress_stream(data: &[u8], block_size: usize, level: i32, ext_dict: bool) -> Vec<Vec<u8>> {
+    let mut stream = Lz4StreamHc::create().expect("alloc");
+    reset_stream_hc(&mut stream, level);
+    let mut input = vec![0u8; block_size];
+    let mut history = vec![0u8; 64 * 1024];
+    data.chunks(block_size)
+        .map(|block| {
+            let src = if ext_dict {
+                input[..block.len()].copy_from_slice(block);
+                &input[..block.len()]
+            } else {
+                block
+            };
+            let mut dst = vec![0u8; compress_hc_bound(block.len())];
+            // SAFETY: `data`, `input` and `history` outlive the stream, and
+            // `input` is only rewritten after `save_dict_hc` has moved the
+            // history out of it.
+            let n = unsafe {
+                let n = compress_hc_continue(
+                    &mut stream,
+                    src.as_ptr(),
+                    dst.as_mut_ptr(),
+                    src.len() as i32,
+                    dst.len() as i32,
+                );
+                if ext_dict {
+                    save_dict_hc(&mut stream, history.as_mut_ptr(), history.len() as i32);
+                }
+                n
+            };
+            assert!(n > 0, "level {level}: compress_hc_continue failed");
+            dst.truncate(n as usize);
+            dst
+        })
+        .collect()
+}
+
+/// Decodes linked blocks into one buffer, each against the output before it.
+fn decode_stream(compressed: &[Vec<u8>], total: usize, block_size: usize) -> Vec<u8> {
+    let mut out = vec![0u8; total];
+    let mut pos = 0;
+    for block in compressed {
+        let len = block_size.min(total - pos);
+        let (history, rest) = out.split_at_mut(pos);
+        let n = decompress_safe_using_dict(block, &mut rest[..len], history).expect("decode");
+        assert_eq!(n, len);
+        pos += len;
+    }
+    assert_eq!(pos, total);
+    out
+}
+
+/// `(contiguous, ext_dict)` compressed sizes of `data` cut into
+/// `block_size` blocks.
+fn sizes(data: &[u8], block_size: usize, level: i32) -> (usize, usize) {
+    let a = compress_stream(data, block_size, level, false);
+    let b = compress_stream(data, block_size, level, true);
+    assert_eq!(decode_stream(&a, data.len(), block_size), data);
+    assert_eq!(decode_stream(&b, data.len(), block_size), data);
+    let total = |v: &[Vec<u8>]| v.iter().map(Vec::len).sum();
+    (total(&a), total(&b))
+}
+
+#[test]
+fn ext_dict_ratio_tracks_contiguous() {
+    let corpora: [(&str, Vec<u8>); 3] = [
+        ("logs", log_lines(256 * 1024, 1)),
+        ("json", json_records(256 * 1024, 2)),
+        ("mixed", compressible(256 * 1024, 3, 0.5)),
+    ];
+    for (name, data) in &corpora {
+        for block_size in [64 * 1024, 96 * 1024] {
+            for level in LEVELS {
+                let (contiguous, ext_dict) = sizes(data, block_size, level);
+                let growth = ext_dict as f64 / contiguous as f64 - 1.0;
+                let limit = if level == 2 { TOLERANCE_MID } else { TOLERANCE };
+                assert!(
+                    growth <= limit,
+                    "{name}, {block_size}-byte blocks, level {level}: \
+                     ext-dict {ext_dict} vs contiguous {contiguous} bytes"
+                );
+            }
+        }
+    }
+}
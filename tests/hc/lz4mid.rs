@@ -37,6 +37,7 @@ fn match_default_is_zero() {
 }
 
 #[test]
+#[allow(clippy::clone_on_copy)]
 fn match_copy_clone() {
     let m = Match {
         off: 10,
@@ -72,6 +73,7 @@ fn dict_search_mode_eq() {
 }
 
 #[test]
+#[allow(clippy::clone_on_copy)]
 fn dict_search_mode_copy_clone() {
     let a = DictSearchMode::Ext;
     let b = a; // Copy
@@ -281,7 +283,7 @@ fn lz4mid_compress_negative_max_output_size_returns_zero() {
     // src_size.  We test the equivalent guard via negative max_output_size,
     // which exercises the same `if *src_size_ptr < 0 || max_output_size < 0`
     // branch without triggering the debug assertion.
-    let src = vec![0u8; 16];
+    let src = [0u8; 16];
     let mut dst = vec![0u8; 32];
     let mut src_size = 1i32;
     let mut ctx = make_compress_ctx(src.as_ptr());
@@ -304,7 +306,7 @@ fn lz4mid_compress_negative_max_output_size_returns_zero() {
 #[test]
 fn lz4mid_compress_oversized_src_returns_zero() {
     // src_size > LZ4_MAX_INPUT_SIZE (0x7E000000) must return 0.
-    let src = vec![0u8; 16];
+    let src = [0u8; 16];
     let mut dst = vec![0u8; 32];
     let mut src_size = 0x7F00_0000i32; // > 0x7E000000
     let mut ctx = make_compress_ctx(src.as_ptr());
@@ -388,7 +390,7 @@ fn lz4mid_compress_compressible_data_smaller_than_input() {
 #[test]
 fn lz4mid_compress_limited_output_tiny_buffer_returns_zero() {
     // Output buffer too small → must return 0 (compression failure).
-    let src = vec![0xCDu8; 64];
+    let src = [0xCDu8; 64];
     let mut dst = vec![0u8; 4]; // way too small
     let mut src_size = src.len() as i32;
 
@@ -413,7 +415,7 @@ fn lz4mid_compress_limited_output_tiny_buffer_returns_zero() {
 #[test]
 fn lz4mid_compress_src_size_ptr_updated() {
     // After a successful compress, *src_size_ptr must reflect bytes consumed.
-    let src = vec![0x55u8; 128];
+    let src = [0x55u8; 128];
     let mut dst = vec![0u8; 256];
     let original_size = src.len() as i32;
     let mut src_size = original_size;
@@ -445,7 +447,7 @@ fn lz4mid_compress_src_size_ptr_updated() {
 #[test]
 fn lz4mid_compress_not_limited_produces_valid_output() {
     // NotLimited: even with a buffer just barely large enough, should succeed.
-    let src = vec![0x77u8; 32];
+    let src = [0x77u8; 32];
     // Worst-case expansion for 32 bytes ≈ 32 + 32/255 + 1 ≈ 34 bytes.
     let mut dst = vec![0u8; 64];
     let mut src_size = src.len() as i32;
@@ -176,7 +176,7 @@ fn count_pattern_exact_4_byte_match() {
 #[test]
 fn count_pattern_multi_word_all_matching() {
     // 32 bytes of the same repeating byte
-    let buf = vec![0x77u8; 32];
+    let buf = [0x77u8; 32];
     unsafe {
         let p = buf.as_ptr();
         let end = p.add(32);
@@ -188,7 +188,7 @@ fn count_pattern_multi_word_all_matching() {
 #[test]
 fn count_pattern_stops_at_mismatch() {
     // 8 matching bytes then a mismatch
-    let mut buf = vec![0x55u8; 9];
+    let mut buf = [0x55u8; 9];
     buf[8] = 0xFF; // mismatch
     unsafe {
         let p = buf.as_ptr();
@@ -288,7 +288,7 @@ fn repeat_state_equality() {
 fn repeat_state_copy_clone() {
     let a = RepeatState::Confirmed;
     let b = a; // Copy
-    let c = a.clone(); // Clone
+    let c = a; // Clone
     assert_eq!(b, RepeatState::Confirmed);
     assert_eq!(c, RepeatState::Confirmed);
 }
@@ -322,7 +322,7 @@ fn hc_favor_discriminant_values() {
 fn hc_favor_copy_clone() {
     let a = HcFavor::DecompressionSpeed;
     let b = a;
-    let c = a.clone();
+    let c = a;
     assert_eq!(b, HcFavor::DecompressionSpeed);
     assert_eq!(c, HcFavor::DecompressionSpeed);
 }
@@ -1,3 +1,6 @@
+#![cfg(all(feature = "cli", not(feature = "forbid-unsafe")))]
+#![allow(clippy::field_reassign_with_default)]
+
 #[path = "io/api.rs"]
 mod api;
 #[path = "io/compress_frame.rs"]
@@ -20,5 +23,7 @@ mod file_info;
 mod file_io;
 #[path = "io/prefs.rs"]
 mod prefs;
+#[path = "io/progress.rs"]
+mod progress;
 #[path = "io/sparse.rs"]
 mod sparse;
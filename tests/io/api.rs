@@ -23,13 +23,15 @@
 //   `lz4::io::compress_multiple_filenames_legacy`
 //   `lz4::io::decompress_filename`         — LZ4IO_decompressFilename
 //   `lz4::io::decompress_multiple_filenames`
+//   `lz4::io::test_multiple_filenames`     — `lz4 -t` over several files
 //   `lz4::io::display_compressed_files_info`
+//   `lz4::io::compress_fd` / `decompress_fd` — caller-owned descriptors (Unix)
 
 use lz4::io::Prefs;
 use lz4::io::{compress_filename, decompress_filename};
 use lz4::io::{compress_filename_legacy, compress_multiple_filenames_legacy};
 use lz4::io::{compress_multiple_filenames, decompress_multiple_filenames};
-use lz4::io::{default_nb_workers, set_notification_level};
+use lz4::io::{default_nb_workers, set_notification_level, test_multiple_filenames};
 use lz4::io::{
     CompressedFileInfo, LEGACY_MAGICNUMBER, LZ4IO_MAGICNUMBER, LZ4IO_SKIPPABLE0,
     LZ4IO_SKIPPABLEMASK, NULL_OUTPUT, NUL_MARK, STDIN_MARK, STDOUT_MARK,
@@ -344,6 +346,33 @@ fn decompress_multiple_filenames_empty_list_succeeds() {
     decompress_multiple_filenames(&[], ".lz4", &prefs).expect("empty list must succeed");
 }
 
+#[test]
+fn test_multiple_filenames_tests_every_file() {
+    // A failure does not stop the run: the summary counts all files, the
+    // first failure is the source, and no source is removed.
+    let dir = tempfile::tempdir().unwrap();
+    let frame = make_frame_stream(&vec![b'x'; 200_000]);
+    let good = dir.path().join("good.lz4");
+    let truncated = dir.path().join("truncated.lz4");
+    let missing = dir.path().join("missing.lz4");
+    fs::write(&good, &frame).unwrap();
+    fs::write(&truncated, &frame[..frame.len() - 6]).unwrap();
+    let mut prefs = Prefs::default();
+    prefs.set_test_mode(true);
+    prefs.set_remove_src_file(true);
+
+    let paths = [&truncated, &good, &missing, &good];
+    let srcs: Vec<&str> = paths.iter().map(|p| p.to_str().unwrap()).collect();
+    let err = test_multiple_filenames(&srcs, &prefs).expect_err("two files fail");
+    assert_eq!(err.to_string(), "4 file(s) tested: 2 OK, 2 FAILED");
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    let first = std::error::Error::source(err.get_ref().unwrap()).unwrap();
+    assert!(first.to_string().contains("Truncated"), "{first}");
+    assert!(good.exists() && truncated.exists());
+
+    test_multiple_filenames(&srcs[1..2], &prefs).expect("a valid file passes");
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // compress_filename_legacy / compress_multiple_filenames_legacy
 // ─────────────────────────────────────────────────────────────────────────────
@@ -467,3 +496,100 @@ fn display_compressed_files_info_empty_list_succeeds() {
     let result = lz4::io::display_compressed_files_info(&[]);
     assert!(result.is_ok(), "empty list must succeed");
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// compress_fd / decompress_fd — caller-owned descriptors (Unix)
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(unix)]
+mod descriptors {
+    use super::*;
+    use lz4::frame::{HeaderParser, HeaderStatus};
+    use lz4::io::{compress_fd, decompress_fd};
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::os::unix::net::UnixStream;
+
+    fn sample(len: usize) -> Vec<u8> {
+        b"descriptor payload, "
+            .iter()
+            .cycle()
+            .take(len)
+            .copied()
+            .collect()
+    }
+
+    /// Spawns a reader draining `peer`, so writes into its pair never block.
+    fn drain(mut peer: UnixStream) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            peer.read_to_end(&mut buf).unwrap();
+            buf
+        })
+    }
+
+    #[test]
+    fn round_trip_through_socketpairs() {
+        let original = sample(3 << 20);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("in.bin");
+        fs::write(&path, &original).unwrap();
+        let prefs = Prefs::default();
+
+        let (ours, peer) = UnixStream::pair().unwrap();
+        let reader = drain(peer);
+        let input = fs::File::open(&path).unwrap();
+        let stats = compress_fd(&input, &ours, 1, &prefs).unwrap();
+        drop(ours);
+        let compressed = reader.join().unwrap();
+        assert_eq!(stats.bytes_in, original.len() as u64);
+        assert_eq!(stats.bytes_out, compressed.len() as u64);
+
+        let (mut ours, peer) = UnixStream::pair().unwrap();
+        let writer = std::thread::spawn(move || ours.write_all(&compressed));
+        let (out, out_peer) = UnixStream::pair().unwrap();
+        let decoded = drain(out_peer);
+        let stats = decompress_fd(&peer, &out, &prefs).unwrap();
+        drop(out);
+        writer.join().unwrap().unwrap();
+        assert_eq!(stats.decompressed_bytes, original.len() as u64);
+        assert_eq!(decoded.join().unwrap(), original);
+    }
+
+    #[test]
+    fn descriptors_stay_open_and_offsets_advance() {
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().join("src.bin");
+        let lz4_path = dir.path().join("src.bin.lz4");
+        let body = sample(100_000);
+        let mut contents = b"HEADER".to_vec();
+        contents.extend_from_slice(&body);
+        fs::write(&src_path, &contents).unwrap();
+
+        // Compression starts at the input's offset, and the declared content
+        // size counts only what follows it.
+        let mut input = fs::File::open(&src_path).unwrap();
+        input.seek(SeekFrom::Start(6)).unwrap();
+        let mut output = fs::File::create(&lz4_path).unwrap();
+        let mut prefs = Prefs::default();
+        prefs.content_size_flag = true;
+        compress_fd(&input, &output, 1, &prefs).unwrap();
+        assert_eq!(input.stream_position().unwrap(), contents.len() as u64);
+        output.write_all(b"trailer").unwrap();
+        drop(output);
+
+        let frame = fs::read(&lz4_path).unwrap();
+        match HeaderParser::new().feed(&frame).unwrap() {
+            HeaderStatus::Done(info, _) => assert_eq!(info.content_size, body.len() as u64),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(frame.ends_with(b"trailer"));
+
+        let input = fs::File::open(&lz4_path).unwrap();
+        let out_path = dir.path().join("out.bin");
+        let output = fs::File::create(&out_path).unwrap();
+        // Data after the last frame is ignored, as `lz4 -d` does.
+        let stats = decompress_fd(&input, &output, &Prefs::default()).unwrap();
+        assert_eq!(stats.decompressed_bytes, body.len() as u64);
+        assert_eq!(fs::read(&out_path).unwrap(), body);
+    }
+}
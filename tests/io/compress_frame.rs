@@ -22,6 +22,10 @@
 //   - compress_filename_round_trip_large: multi-block path recovers original
 //   - compress_filename_empty: empty source produces valid (small) LZ4 frame
 //   - compress_filename_nonexistent_src: returns Err
+//   - compress_filename_copies_metadata: mtime and mode follow the source
+//     unless restore_times is off
+//   - compress_filename_fifo_source: a FIFO streams without -f; no content
+//     size, no metadata copy, not removed by --rm
 //   - compress_filename_bad_dst_dir: returns Err for unwritable path
 //   - compress_filename_stats_bytes_in: bytes_in matches source size
 //   - compress_filename_hc_level: HC level >= 3 produces valid output
@@ -33,13 +37,15 @@
 //   - compress_multiple_filenames_missing_counted: missed count incremented
 //   - compress_multiple_filenames_all_bad: all files missed, Ok(n)
 //   - compress_multiple_filenames_outputs_valid: output is decompressible
+//   - compress_multiple_filenames_to_dir_mirrors_tree: nested outputs, dirs created
+//   - compress_multiple_filenames_to_dir_flat_collision: second same-name source missed
 //   - compress_frame_chunk_basic: returns non-zero for compressible input
 //   - compress_frame_chunk_prefix: with prefix_data path returns output
 //   - compress_frame_chunk_empty_src: returns 0 bytes for empty input
 
 use lz4::io::compress_frame::{
     compress_filename, compress_filename_ext, compress_frame_chunk, compress_multiple_filenames,
-    CfcParameters, CompressResources, CompressStats,
+    compress_multiple_filenames_to_dir, CfcParameters, CompressResources, CompressStats,
 };
 use lz4::io::prefs::Prefs;
 
@@ -84,6 +90,7 @@ fn compress_stats_fields_accessible() {
     let s = CompressStats {
         bytes_in: 1234,
         bytes_out: 567,
+        block_size_downgrade: None,
     };
     assert_eq!(s.bytes_in, 1234);
     assert_eq!(s.bytes_out, 567);
@@ -95,9 +102,10 @@ fn compress_stats_clone_copy() {
     let s = CompressStats {
         bytes_in: 10,
         bytes_out: 5,
+        block_size_downgrade: None,
     };
     let s2 = s; // Copy
-    let s3 = s.clone(); // Clone
+    let s3 = s; // Clone
     assert_eq!(s2.bytes_in, 10);
     assert_eq!(s3.bytes_out, 5);
 }
@@ -258,6 +266,85 @@ fn compress_filename_nonexistent_src_returns_err() {
     assert!(result.is_err(), "expected Err for nonexistent source");
 }
 
+/// A FIFO is compressed as a stream without `-f`: the frame declares no
+/// content size, the pipe's attributes are not copied, and `--rm` leaves it
+/// alone.
+#[cfg(unix)]
+#[test]
+fn compress_filename_fifo_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let fifo = dir.path().join("pipe");
+    nix::unistd::mkfifo(&fifo, nix::sys::stat::Mode::S_IRWXU).unwrap();
+    let data = lz4::lorem::gen_buffer(300_000, 5);
+
+    let writer = {
+        let fifo = fifo.clone();
+        let data = data.clone();
+        std::thread::spawn(move || std::fs::write(&fifo, &data).unwrap())
+    };
+    let dst = dir.path().join("pipe.lz4");
+    let prefs = Prefs::builder()
+        .force(false)
+        .content_size(true)
+        .remove_src_file(true)
+        .build();
+    let stats = compress_filename(fifo.to_str().unwrap(), dst.to_str().unwrap(), 1, &prefs)
+        .expect("a FIFO must be compressed without -f");
+    writer.join().unwrap();
+
+    assert_eq!(stats.bytes_in, data.len() as u64);
+    let compressed = std::fs::read(&dst).unwrap();
+    assert_eq!(compressed[4] & 0x08, 0, "no content size for a FIFO");
+    assert_eq!(
+        lz4::frame::decompress_frame_to_vec(&compressed).unwrap(),
+        data
+    );
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(&dst).unwrap().permissions().mode();
+    assert_ne!(mode & 0o777, 0o700, "FIFO mode must not be copied");
+    assert!(fifo.exists(), "--rm must not remove a FIFO");
+}
+
+/// The output gets the source's mtime and permissions; with restore_times
+/// off it keeps its own (current) mtime.
+#[test]
+fn compress_filename_copies_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("meta.bin");
+    std::fs::write(&src, b"metadata metadata metadata").unwrap();
+    let old = filetime::FileTime::from_unix_time(1_000_000_000, 0);
+    filetime::set_file_mtime(&src, old).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&src, std::fs::Permissions::from_mode(0o640)).unwrap();
+    }
+    let mtime = |p: &std::path::Path| {
+        filetime::FileTime::from_last_modification_time(&std::fs::metadata(p).unwrap())
+    };
+
+    let kept = dir.path().join("kept.lz4");
+    compress_filename(
+        src.to_str().unwrap(),
+        kept.to_str().unwrap(),
+        1,
+        &Prefs::default(),
+    )
+    .unwrap();
+    assert_eq!(mtime(&kept), old);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&kept).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    let fresh = dir.path().join("fresh.lz4");
+    let prefs = Prefs::builder().restore_times(false).build();
+    compress_filename(src.to_str().unwrap(), fresh.to_str().unwrap(), 1, &prefs).unwrap();
+    assert!(mtime(&fresh) > old);
+}
+
 /// Unwritable destination directory → Err.
 #[test]
 fn compress_filename_bad_dst_dir_returns_err() {
@@ -1501,3 +1588,98 @@ fn compress_real_file_copies_stat() {
         .expect("real file compress must succeed");
     assert!(dst.exists());
 }
+
+/// Inputs large enough to be memory-mapped under the `mmap` feature decode
+/// to the original bytes in both block modes (and through buffered reads
+/// without the feature).
+#[test]
+fn compress_filename_large_file_round_trip_both_block_modes() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("large.bin");
+    let data: Vec<u8> = (0..9 * 1024 * 1024 + 123u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 27) as u8 ^ (i / 4096) as u8)
+        .collect();
+    std::fs::write(&src, &data).unwrap();
+    for independent in [true, false] {
+        let dst = dir.path().join(format!("large-{independent}.lz4"));
+        let mut prefs = Prefs::default();
+        prefs.block_independence = independent;
+        prefs.block_checksum = true;
+        let stats =
+            compress_filename(src.to_str().unwrap(), dst.to_str().unwrap(), 1, &prefs).unwrap();
+        assert_eq!(stats.bytes_in, data.len() as u64);
+        let compressed = std::fs::read(&dst).unwrap();
+        let decoded = lz4::frame::decompress_frame_to_vec(&compressed).unwrap();
+        assert!(decoded == data, "independent = {independent}");
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// compress_multiple_filenames_to_dir
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Mirrored outputs keep the source tree, creating directories as needed.
+#[test]
+fn compress_multiple_filenames_to_dir_mirrors_tree() {
+    let dir = tempfile::tempdir().unwrap();
+    let src_root = dir.path().join("src");
+    std::fs::create_dir_all(src_root.join("a/b")).unwrap();
+    std::fs::write(src_root.join("top.txt"), b"top level").unwrap();
+    std::fs::write(src_root.join("a/b/deep.txt"), b"two levels down").unwrap();
+    let out = dir.path().join("out");
+
+    let top = src_root.join("top.txt");
+    let deep = src_root.join("a/b/deep.txt");
+    let prefs = Prefs::default();
+    let missed = compress_multiple_filenames_to_dir(
+        &[top.to_str().unwrap(), deep.to_str().unwrap()],
+        out.to_str().unwrap(),
+        true,
+        ".lz4",
+        1,
+        &prefs,
+    )
+    .unwrap();
+    assert_eq!(missed, 0);
+
+    let mirrored = lz4::util::mirror_path(&src_root, &out);
+    let frame = std::fs::read(mirrored.join("a/b/deep.txt.lz4")).unwrap();
+    assert_eq!(
+        lz4::frame::decompress_frame_to_vec(&frame).unwrap(),
+        b"two levels down"
+    );
+    assert!(mirrored.join("top.txt.lz4").exists());
+    assert!(!src_root.join("top.txt.lz4").exists());
+}
+
+/// Flattened sources sharing a file name: the first is written, the second
+/// is counted as missed instead of overwriting it.
+#[test]
+fn compress_multiple_filenames_to_dir_flat_collision() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("x")).unwrap();
+    std::fs::create_dir_all(dir.path().join("y")).unwrap();
+    let first = dir.path().join("x/same.txt");
+    let second = dir.path().join("y/same.txt");
+    std::fs::write(&first, b"from x").unwrap();
+    std::fs::write(&second, b"from y").unwrap();
+    let out = dir.path().join("out");
+
+    let prefs = Prefs::default();
+    let missed = compress_multiple_filenames_to_dir(
+        &[first.to_str().unwrap(), second.to_str().unwrap()],
+        out.to_str().unwrap(),
+        false,
+        ".lz4",
+        1,
+        &prefs,
+    )
+    .unwrap();
+    assert_eq!(missed, 1);
+
+    let frame = std::fs::read(out.join("same.txt.lz4")).unwrap();
+    assert_eq!(
+        lz4::frame::decompress_frame_to_vec(&frame).unwrap(),
+        b"from x"
+    );
+}
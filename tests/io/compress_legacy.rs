@@ -485,7 +485,7 @@ fn legacy_result_clone_copy() {
         bytes_written: 50,
     };
     let r2 = r; // Copy
-    let r3 = r.clone(); // Clone
+    let r3 = r; // Clone
     assert_eq!(r2.bytes_read, 100);
     assert_eq!(r3.bytes_written, 50);
 }
@@ -546,6 +546,38 @@ fn mt_linked_blocks_roundtrip() {
     assert_eq!(in_size, data.len() as u64);
 }
 
+/// Multi-chunk MT output decodes to the input in linked mode, where each
+/// chunk takes the previous chunk's tail as its prefix (a borrowed slice when
+/// the input is memory-mapped).
+#[test]
+fn mt_linked_blocks_multi_chunk_decodes() {
+    let dir = TempDir::new().unwrap();
+    let src_path = dir.path().join("linked3.bin");
+    let dst_path = dir.path().join("linked3.lz4");
+    let data: Vec<u8> = (0..(CHUNK_SIZE * 3 + 777) as u32)
+        .map(|i| ((i / 3) % 253) as u8 ^ (i >> 16) as u8)
+        .collect();
+    std::fs::write(&src_path, &data).unwrap();
+
+    let mut prefs = make_prefs(3);
+    prefs.block_independence = false;
+    let mut ress = make_ress(&prefs);
+    let mut in_size = 0u64;
+    compress_filename_mt(
+        &mut in_size,
+        &mut ress,
+        src_path.to_str().unwrap(),
+        dst_path.to_str().unwrap(),
+        1,
+        &prefs,
+    )
+    .unwrap();
+    assert_eq!(in_size, data.len() as u64);
+    let compressed = std::fs::read(&dst_path).unwrap();
+    let decoded = lz4::frame::decompress_frame_to_vec(&compressed).unwrap();
+    assert!(decoded == data);
+}
+
 /// MT compression with content checksum enabled.
 /// Exercises XXH32 accumulation and checksum finalization.
 #[test]
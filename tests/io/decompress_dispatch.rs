@@ -87,6 +87,70 @@ fn decompress_filename_frame_small() {
     assert_eq!(stats.decompressed_bytes as usize, original.len());
 }
 
+#[test]
+fn decompress_filename_flush_every_round_trip() {
+    let original = cycling_bytes(300_000);
+    let src_dir = tempfile::tempdir().unwrap();
+    let src = src_dir.path().join("flush.lz4");
+    let dst = src_dir.path().join("flush.raw");
+    fs::write(&src, make_frame_stream(&original)).unwrap();
+
+    let prefs = Prefs::builder().flush_every(4096).build();
+    let stats = decompress_filename(src.to_str().unwrap(), dst.to_str().unwrap(), &prefs)
+        .expect("decompress_filename should succeed");
+
+    assert_eq!(fs::read(&dst).unwrap(), original);
+    assert_eq!(stats.decompressed_bytes as usize, original.len());
+}
+
+#[test]
+fn decompress_filename_small_io_buffer_round_trip() {
+    let original = cycling_bytes(300_000);
+    let src_dir = tempfile::tempdir().unwrap();
+    let src = src_dir.path().join("small_buf.lz4");
+    let dst = src_dir.path().join("small_buf.raw");
+    fs::write(&src, make_frame_stream(&original)).unwrap();
+
+    let prefs = Prefs::builder().io_buffer_size(4096).build();
+    decompress_filename(src.to_str().unwrap(), dst.to_str().unwrap(), &prefs)
+        .expect("decompress_filename should succeed");
+
+    assert_eq!(fs::read(&dst).unwrap(), original);
+}
+
+#[test]
+fn decompress_filename_restores_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("meta.lz4");
+    fs::write(&src, make_frame_stream(b"metadata")).unwrap();
+    let old = filetime::FileTime::from_unix_time(1_000_000_000, 0);
+    filetime::set_file_mtime(&src, old).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&src, fs::Permissions::from_mode(0o600)).unwrap();
+    }
+    let mtime = |p: &std::path::Path| {
+        filetime::FileTime::from_last_modification_time(&fs::metadata(p).unwrap())
+    };
+
+    let restored = dir.path().join("restored");
+    let prefs = Prefs::default();
+    decompress_filename(src.to_str().unwrap(), restored.to_str().unwrap(), &prefs).unwrap();
+    assert_eq!(mtime(&restored), old);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&restored).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let fresh = dir.path().join("fresh");
+    let prefs = Prefs::builder().restore_times(false).build();
+    decompress_filename(src.to_str().unwrap(), fresh.to_str().unwrap(), &prefs).unwrap();
+    assert!(mtime(&fresh) > old);
+}
+
 #[test]
 fn decompress_filename_frame_empty() {
     // Empty payload: frame format must produce an empty output file.
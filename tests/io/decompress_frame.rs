@@ -316,3 +316,48 @@ fn decompress_truncated_frame() {
     let result = decompress_lz4f(&mut src, &mut output, &prefs, &mut res);
     assert!(result.is_err(), "truncated frame should return error");
 }
+
+/// With `flush_every` set, a multi-worker `Prefs` still decodes block by block
+/// on the single-threaded path, so no write carries more than one 64 KiB
+/// output chunk and the consumer sees data as soon as each block is ready.
+#[test]
+fn flush_every_streams_block_by_block() {
+    use lz4::frame::compress::lz4f_compress_frame;
+    use lz4::frame::header::lz4f_compress_frame_bound;
+    use lz4::frame::types::Preferences;
+    use std::io::{self, Write};
+
+    struct Recorder {
+        data: Vec<u8>,
+        largest_write: usize,
+    }
+    impl Write for Recorder {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.largest_write = self.largest_write.max(buf.len());
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Independent 256 KB blocks: eligible for the MT decoder.
+    let prefs_frame = Preferences::archival();
+    let original: Vec<u8> = (0..2 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+    let mut frame = vec![0u8; lz4f_compress_frame_bound(original.len(), Some(&prefs_frame))];
+    let n = lz4f_compress_frame(&mut frame, &original, Some(&prefs_frame)).unwrap();
+    let (_, body) = split_magic(&frame[..n]);
+
+    let mut prefs = mt_prefs();
+    prefs.set_flush_every(1);
+    let mut res = make_resources(&prefs);
+    let mut out = Recorder {
+        data: Vec::new(),
+        largest_write: 0,
+    };
+    let mut src = body;
+    decompress_lz4f(&mut src, &mut out, &prefs, &mut res).unwrap();
+    assert_eq!(out.data, original);
+    assert!(out.largest_write <= 64 * 1024, "{}", out.largest_write);
+}
@@ -7,10 +7,12 @@
 //   - display_compressed_files_info() rejects non-regular files (lines 2845–2897)
 //   - display_compressed_files_info() rejects missing files
 //   - display_compressed_files_info() succeeds on valid LZ4 frames
+//   - quick_check_file() decodes first/last blocks and flags corruption (--info)
 
 use lz4::frame::types::{BlockMode, BlockSizeId};
 use lz4::io::file_info::{
-    block_type_id, display_compressed_files_info, CompressedFileInfo, FrameType,
+    block_type_id, display_compressed_files_info, display_compressed_files_info_checked,
+    quick_check_file, CompressedFileInfo, FrameType, QuickCheckReport,
 };
 use std::io::Write;
 use tempfile::NamedTempFile;
@@ -1503,12 +1505,12 @@ fn display_info_multiple_real_files() {
 
     let src1 = dir.path().join("multi1.bin");
     let dst1 = dir.path().join("multi1.lz4");
-    std::fs::write(&src1, &[0xAA; 5000]).unwrap();
+    std::fs::write(&src1, [0xAA; 5000]).unwrap();
     compress_filename(src1.to_str().unwrap(), dst1.to_str().unwrap(), 1, &prefs).unwrap();
 
     let src2 = dir.path().join("multi2.bin");
     let dst2 = dir.path().join("multi2.lz4");
-    std::fs::write(&src2, &[0xBB; 10000]).unwrap();
+    std::fs::write(&src2, [0xBB; 10000]).unwrap();
     compress_filename(src2.to_str().unwrap(), dst2.to_str().unwrap(), 1, &prefs).unwrap();
 
     let paths = [dst1.to_str().unwrap(), dst2.to_str().unwrap()];
@@ -1661,8 +1663,8 @@ fn display_info_multiple_files_p6() {
     let dst1 = dir.path().join("multi1.lz4");
     let src2 = dir.path().join("multi2.bin");
     let dst2 = dir.path().join("multi2.lz4");
-    std::fs::write(&src1, &vec![b'A'; 1000]).unwrap();
-    std::fs::write(&src2, &vec![b'B'; 2000]).unwrap();
+    std::fs::write(&src1, vec![b'A'; 1000]).unwrap();
+    std::fs::write(&src2, vec![b'B'; 2000]).unwrap();
     let prefs = Prefs::default();
     compress_filename(src1.to_str().unwrap(), dst1.to_str().unwrap(), 1, &prefs).unwrap();
     compress_filename(src2.to_str().unwrap(), dst2.to_str().unwrap(), 1, &prefs).unwrap();
@@ -1807,3 +1809,137 @@ fn display_info_skippable_then_standard() {
     let result = display_compressed_files_info(&[p]);
     assert!(result.is_ok());
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// quick_check_file — --info spot check
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Multi-block 64 KB frame with block checksums in the given block mode.
+fn build_multi_block_frame(src: &[u8], block_mode: BlockMode) -> Vec<u8> {
+    use lz4::frame::compress::lz4f_compress_frame;
+    use lz4::frame::types::{BlockChecksum, FrameInfo, Preferences};
+    let prefs = Preferences {
+        frame_info: FrameInfo {
+            block_size_id: BlockSizeId::Max64Kb,
+            block_mode,
+            block_checksum_flag: BlockChecksum::Enabled,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut dst = vec![0u8; src.len() * 2 + 1024];
+    let n = lz4f_compress_frame(&mut dst, src, Some(&prefs)).expect("compress_frame");
+    dst.truncate(n);
+    dst
+}
+
+fn multi_block_input() -> Vec<u8> {
+    (0..300_000u32).map(|i| (i * 31 % 253) as u8).collect()
+}
+
+fn temp_with(bytes: &[u8]) -> NamedTempFile {
+    let mut tmp = NamedTempFile::new().expect("tempfile");
+    tmp.write_all(bytes).expect("write");
+    tmp
+}
+
+#[test]
+fn quick_check_independent_frame_decodes_first_and_last() {
+    let frame = build_multi_block_frame(&multi_block_input(), BlockMode::Independent);
+    let tmp = temp_with(&frame);
+    let report = quick_check_file(tmp.path().to_str().unwrap()).expect("quick check");
+    assert_eq!(
+        report,
+        QuickCheckReport {
+            frames: 1,
+            blocks_checked: 2,
+            blocks_skipped: 0,
+        }
+    );
+}
+
+#[test]
+fn quick_check_linked_frame_skips_last_block() {
+    let frame = build_multi_block_frame(&multi_block_input(), BlockMode::Linked);
+    let tmp = temp_with(&frame);
+    let report = quick_check_file(tmp.path().to_str().unwrap()).expect("quick check");
+    assert_eq!(report.blocks_checked, 1);
+    assert_eq!(report.blocks_skipped, 1);
+}
+
+#[test]
+fn quick_check_counts_every_frame_family() {
+    let mut bytes = build_lz4f_frame(b"small frame, single block");
+    bytes.extend(build_skippable_frame(b"user metadata"));
+    bytes.extend(build_legacy_frame(&multi_block_input()));
+    let tmp = temp_with(&bytes);
+    let report = quick_check_file(tmp.path().to_str().unwrap()).expect("quick check");
+    assert_eq!(report.frames, 3);
+    assert_eq!(report.blocks_checked, 2);
+}
+
+#[test]
+fn quick_check_detects_corrupt_last_block() {
+    let mut frame = build_multi_block_frame(&multi_block_input(), BlockMode::Independent);
+    // Flip a byte inside the last block's payload (before its checksum,
+    // the end mark, and the content checksum).
+    let idx = frame.len() - 4 - 4 - 4 - 10;
+    frame[idx] ^= 0xFF;
+    let tmp = temp_with(&frame);
+    let err = quick_check_file(tmp.path().to_str().unwrap()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn quick_check_block_checksum_mismatch_is_a_checksum_failure() {
+    use lz4::cli::error::{CliError, EXIT_CHECKSUM};
+    let mut frame = build_multi_block_frame(&multi_block_input(), BlockMode::Independent);
+    // Flip a byte of the last block's stored checksum: the payload still
+    // decodes, so only the checksum can catch it.
+    let idx = frame.len() - 4 - 4 - 1;
+    frame[idx] ^= 0xFF;
+    let tmp = temp_with(&frame);
+    let err = quick_check_file(tmp.path().to_str().unwrap()).unwrap_err();
+    assert!(err.to_string().contains("block checksum"), "got {}", err);
+    assert_eq!(CliError::from(err).exit_code(), EXIT_CHECKSUM);
+}
+
+#[test]
+fn quick_check_hashes_stored_blocks() {
+    use lz4::cli::error::{CliError, EXIT_CHECKSUM};
+    // Incompressible input: every block is stored uncompressed.
+    let mut x = 0x9E37_79B9u32;
+    let noise: Vec<u8> = (0..200_000)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            x as u8
+        })
+        .collect();
+    let mut frame = build_multi_block_frame(&noise, BlockMode::Independent);
+    let tmp = temp_with(&frame);
+    let report = quick_check_file(tmp.path().to_str().unwrap()).expect("quick check");
+    assert_eq!(report.blocks_checked, 2);
+
+    let idx = frame.len() - 4 - 4 - 4 - 10;
+    frame[idx] ^= 0xFF;
+    let tmp = temp_with(&frame);
+    let err = quick_check_file(tmp.path().to_str().unwrap()).unwrap_err();
+    assert_eq!(CliError::from(err).exit_code(), EXIT_CHECKSUM);
+}
+
+#[test]
+fn quick_check_detects_truncation() {
+    let frame = build_multi_block_frame(&multi_block_input(), BlockMode::Independent);
+    let tmp = temp_with(&frame[..frame.len() - 100]);
+    let err = quick_check_file(tmp.path().to_str().unwrap()).unwrap_err();
+    assert!(err.to_string().contains("truncated"), "got {}", err);
+}
+
+#[test]
+fn quick_check_rejects_unknown_format() {
+    let tmp = temp_with(b"definitely not an lz4 file");
+    assert!(quick_check_file(tmp.path().to_str().unwrap()).is_err());
+    assert!(display_compressed_files_info_checked(&[tmp.path().to_str().unwrap()]).is_err());
+}
@@ -10,9 +10,10 @@
 //   - `LZ4IO_blockMode_t`          → `BlockMode` enum
 //   - `cpuLoad_sec`                → `cpu_load_sec`
 
+use lz4::frame::ChecksumPolicy;
 use lz4::io::prefs::{
-    cpu_load_sec, default_nb_workers, display_level, set_notification_level, BlockMode, Prefs,
-    CACHELINE, DISPLAY_LEVEL, GB, KB, LEGACY_BLOCKSIZE, LEGACY_MAGICNUMBER,
+    cpu_load_sec, default_nb_workers, display_level, set_notification_level, BlockMode,
+    IoPrefsBuilder, Prefs, CACHELINE, DISPLAY_LEVEL, GB, KB, LEGACY_BLOCKSIZE, LEGACY_MAGICNUMBER,
     LZ4IO_BLOCKSIZEID_DEFAULT, LZ4IO_MAGICNUMBER, LZ4IO_SKIPPABLE0, LZ4IO_SKIPPABLEMASK,
     LZ4_MAX_DICT_SIZE, MAGICNUMBER_SIZE, MB, MIN_STREAM_BUFSIZE, REFRESH_RATE_NS,
 };
@@ -138,7 +139,7 @@ fn block_mode_equality() {
 fn block_mode_copy_clone() {
     let a = BlockMode::Independent;
     let b = a;
-    let c = a.clone();
+    let c = a;
     assert_eq!(b, BlockMode::Independent);
     assert_eq!(c, BlockMode::Independent);
 }
@@ -701,3 +702,65 @@ fn prefs_debug_does_not_panic() {
     let p = Prefs::default();
     let _ = format!("{:?}", p);
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// IoPrefsBuilder
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn builder_without_setters_matches_default() {
+    let built = IoPrefsBuilder::new().build();
+    let def = Prefs::default();
+    assert_eq!(built.block_size_id, def.block_size_id);
+    assert_eq!(built.block_size, def.block_size);
+    assert_eq!(built.overwrite, def.overwrite);
+    assert_eq!(built.stream_checksum, def.stream_checksum);
+    assert_eq!(built.sparse_file_support, def.sparse_file_support);
+    assert_eq!(built.nb_workers, def.nb_workers);
+}
+
+#[test]
+fn builder_applies_every_option() {
+    let p = Prefs::builder()
+        .block_size_id(4)
+        .block_mode(BlockMode::Linked)
+        .block_checksum(true)
+        .stream_checksum(false)
+        .content_size(true)
+        .sparse(false)
+        .overwrite(false)
+        .nb_workers(3)
+        .test_mode(true)
+        .pass_through(true)
+        .dictionary_filename(Some("dict.bin"))
+        .remove_src_file(true)
+        .favor_dec_speed(true)
+        .checksum_policy(ChecksumPolicy::REQUIRE)
+        .build();
+    assert_eq!(p.block_size_id, 4);
+    assert_eq!(p.block_size, 64 * KB);
+    assert!(!p.block_independence);
+    assert!(p.block_checksum);
+    assert!(!p.stream_checksum);
+    assert!(p.content_size_flag);
+    assert_eq!(p.sparse_file_support, 0);
+    assert!(!p.overwrite);
+    assert_eq!(p.nb_workers, 3);
+    assert!(p.test_mode);
+    assert!(p.pass_through);
+    assert!(p.use_dictionary);
+    assert_eq!(p.dictionary_filename.as_deref(), Some("dict.bin"));
+    assert!(p.remove_src_file);
+    assert!(p.favor_dec_speed);
+    assert_eq!(p.checksum_policy, ChecksumPolicy::REQUIRE);
+}
+
+#[test]
+fn builder_reuses_setter_validation() {
+    // Invalid block-size IDs are ignored; raw sizes and worker counts are clamped.
+    let p = Prefs::builder().block_size_id(5).block_size_id(9).build();
+    assert_eq!(p.block_size_id, 5);
+    let p = Prefs::builder().block_size(1).nb_workers(0).build();
+    assert_eq!(p.block_size, 32);
+    assert_eq!(p.nb_workers, 1);
+}
@@ -0,0 +1,194 @@
+// Integration tests for src/io/progress.rs — progress reporting callbacks.
+//
+// Coverage:
+//   - Progress::ratio / fraction_done / eta arithmetic
+//   - Prefs::set_progress_sink and IoPrefsBuilder::progress_sink
+//   - compress_filename reports bytes read/written and finishes once
+//   - decompress_filename reports compressed bytes read and decoded bytes written
+//   - compress_filename_legacy reports through the same sink
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use lz4::io::compress_frame::compress_filename;
+use lz4::io::compress_legacy::compress_filename_legacy;
+use lz4::io::decompress_dispatch::decompress_filename;
+use lz4::io::prefs::Prefs;
+use lz4::io::progress::{Progress, ProgressSink};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Helpers
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct Recorder {
+    updates: Mutex<Vec<Progress>>,
+    finished: Mutex<Vec<Progress>>,
+}
+
+impl ProgressSink for Recorder {
+    fn update(&self, progress: &Progress) {
+        self.updates.lock().unwrap().push(*progress);
+    }
+
+    fn finish(&self, progress: &Progress) {
+        self.finished.lock().unwrap().push(*progress);
+    }
+}
+
+fn sample_data() -> Vec<u8> {
+    (0..300_000u32).map(|i| (i % 97) as u8).collect()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Progress arithmetic
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn progress_ratio_zero_before_input() {
+    assert_eq!(Progress::default().ratio(), 0.0);
+}
+
+#[test]
+fn progress_eta_extrapolates_throughput() {
+    let p = Progress {
+        bytes_read: 50,
+        bytes_written: 10,
+        total_bytes: Some(200),
+        elapsed: Duration::from_secs(2),
+    };
+    assert_eq!(p.fraction_done(), Some(0.25));
+    assert_eq!(p.eta(), Some(Duration::from_secs(6)));
+}
+
+#[test]
+fn progress_empty_source_is_done() {
+    let p = Progress {
+        total_bytes: Some(0),
+        ..Progress::default()
+    };
+    assert_eq!(p.fraction_done(), Some(1.0));
+    assert_eq!(p.eta(), None);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Prefs wiring
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn prefs_default_has_no_sink() {
+    assert!(Prefs::default().progress_sink.is_none());
+}
+
+#[test]
+fn set_progress_sink_returns_installed_state() {
+    let mut prefs = Prefs::default();
+    assert!(prefs.set_progress_sink(Some(Arc::new(Recorder::default()))));
+    assert!(!prefs.set_progress_sink(None));
+}
+
+#[test]
+fn builder_installs_sink() {
+    let prefs = Prefs::builder()
+        .progress_sink(Arc::new(Recorder::default()))
+        .build();
+    assert!(prefs.progress_sink.is_some());
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// File operations
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[test]
+fn compress_filename_reports_progress() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("in.bin");
+    let dst = dir.path().join("out.lz4");
+    let data = sample_data();
+    std::fs::write(&src, &data).unwrap();
+
+    let sink = Arc::new(Recorder::default());
+    let prefs = Prefs::builder().progress_sink(sink.clone()).build();
+    compress_filename(src.to_str().unwrap(), dst.to_str().unwrap(), 1, &prefs).unwrap();
+
+    assert!(!sink.updates.lock().unwrap().is_empty());
+    let finished = sink.finished.lock().unwrap();
+    assert_eq!(finished.len(), 1);
+    let last = finished[0];
+    assert_eq!(last.bytes_read, data.len() as u64);
+    assert_eq!(last.total_bytes, Some(data.len() as u64));
+    assert_eq!(last.bytes_written, std::fs::metadata(&dst).unwrap().len());
+    assert!(last.ratio() < 1.0);
+    assert_eq!(last.eta(), Some(Duration::ZERO));
+}
+
+/// Large inputs (memory-mapped under the `mmap` feature) still count every
+/// source byte.
+#[test]
+fn compress_filename_reports_progress_for_large_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("in.bin");
+    let dst = dir.path().join("out.lz4");
+    let data: Vec<u8> = (0..6 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+    std::fs::write(&src, &data).unwrap();
+
+    let sink = Arc::new(Recorder::default());
+    let prefs = Prefs::builder().progress_sink(sink.clone()).build();
+    compress_filename(src.to_str().unwrap(), dst.to_str().unwrap(), 1, &prefs).unwrap();
+
+    let finished = sink.finished.lock().unwrap();
+    assert_eq!(finished.len(), 1);
+    assert_eq!(finished[0].bytes_read, data.len() as u64);
+    assert_eq!(
+        finished[0].bytes_written,
+        std::fs::metadata(&dst).unwrap().len()
+    );
+}
+
+#[test]
+fn decompress_filename_reports_progress() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("in.bin");
+    let cmp = dir.path().join("in.bin.lz4");
+    let out = dir.path().join("out.bin");
+    let data = sample_data();
+    std::fs::write(&src, &data).unwrap();
+    compress_filename(
+        src.to_str().unwrap(),
+        cmp.to_str().unwrap(),
+        1,
+        &Prefs::default(),
+    )
+    .unwrap();
+
+    let sink = Arc::new(Recorder::default());
+    let prefs = Prefs::builder().progress_sink(sink.clone()).build();
+    decompress_filename(cmp.to_str().unwrap(), out.to_str().unwrap(), &prefs).unwrap();
+
+    let finished = sink.finished.lock().unwrap();
+    assert_eq!(finished.len(), 1);
+    let cmp_len = std::fs::metadata(&cmp).unwrap().len();
+    assert_eq!(finished[0].bytes_read, cmp_len);
+    assert_eq!(finished[0].total_bytes, Some(cmp_len));
+    assert_eq!(finished[0].bytes_written, data.len() as u64);
+    assert_eq!(std::fs::read(&out).unwrap(), data);
+}
+
+#[test]
+fn compress_filename_legacy_reports_progress() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("in.bin");
+    let dst = dir.path().join("out.lz4");
+    let data = sample_data();
+    std::fs::write(&src, &data).unwrap();
+
+    let sink = Arc::new(Recorder::default());
+    let prefs = Prefs::builder().progress_sink(sink.clone()).build();
+    let res =
+        compress_filename_legacy(src.to_str().unwrap(), dst.to_str().unwrap(), 1, &prefs).unwrap();
+
+    let finished = sink.finished.lock().unwrap();
+    assert_eq!(finished.len(), 1);
+    assert_eq!(finished[0].bytes_read, res.bytes_read);
+    assert_eq!(finished[0].bytes_written, res.bytes_written);
+}
@@ -205,7 +205,7 @@ mod unix_fwrite_sparse {
         let zeros = vec![0u8; WORD * 4]; // 4 full words, all zero
         let skips = fwrite_sparse(&mut f, &zeros, SPARSE_SEGMENT_SIZE, 0, true).unwrap();
         assert_eq!(skips, zeros.len() as u64, "all zeros must be accumulated");
-        let pos = f.seek(SeekFrom::Current(0)).unwrap();
+        let pos = f.stream_position().unwrap();
         assert_eq!(
             pos, 0,
             "no seek/write should have occurred for all-zero buffer"
@@ -218,7 +218,7 @@ mod unix_fwrite_sparse {
         let mut f = tempfile::tempfile().unwrap();
         let skips = fwrite_sparse(&mut f, &[0u8], SPARSE_SEGMENT_SIZE, 0, true).unwrap();
         assert_eq!(skips, 1);
-        let pos = f.seek(SeekFrom::Current(0)).unwrap();
+        let pos = f.stream_position().unwrap();
         assert_eq!(pos, 0);
     }
 
@@ -415,7 +415,7 @@ mod unix_fwrite_sparse {
         let buf = vec![0u8; 3];
         let skips = fwrite_sparse(&mut f, &buf, SPARSE_SEGMENT_SIZE, 0, true).unwrap();
         assert_eq!(skips, 3);
-        let pos = f.seek(SeekFrom::Current(0)).unwrap();
+        let pos = f.stream_position().unwrap();
         assert_eq!(pos, 0);
     }
 
@@ -431,7 +431,7 @@ mod unix_fwrite_sparse {
             skips, initial_skips,
             "empty buffer must not modify stored_skips"
         );
-        let pos = f.seek(SeekFrom::Current(0)).unwrap();
+        let pos = f.stream_position().unwrap();
         assert_eq!(pos, 0, "empty buffer must not move the file pointer");
     }
 
@@ -10,6 +10,8 @@
 //   - Top-level re-exports (lz4_compress_default, lz4_decompress_safe,
 //     lz4f_compress_frame, lz4f_decompress) are callable
 
+#![cfg(all(feature = "block", not(feature = "forbid-unsafe")))]
+
 use lz4::{
     compress_inplace_buffer_size, decompress_inplace_buffer_size, decompress_inplace_margin,
     size_of_state, version_number, version_string, COMPRESS_INPLACE_MARGIN, LZ4_DISTANCE_MAX,
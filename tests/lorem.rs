@@ -66,7 +66,7 @@ fn gen_buffer_contains_only_printable_ascii() {
     let buf = gen_buffer(1024, 99);
     for &byte in &buf {
         assert!(
-            byte == b'\n' || (byte >= b' ' && byte <= b'~'),
+            byte == b'\n' || (b' '..=b'~').contains(&byte),
             "unexpected byte 0x{:02x} in lorem output",
             byte
         );
@@ -195,7 +195,7 @@ fn gen_block_output_contains_only_printable_ascii() {
     let n = gen_block(&mut buf, 3, true, true);
     for &byte in &buf[..n] {
         assert!(
-            byte == b'\n' || (byte >= b' ' && byte <= b'~'),
+            byte == b'\n' || (b' '..=b'~').contains(&byte),
             "unexpected byte 0x{:02x} in gen_block output",
             byte
         );
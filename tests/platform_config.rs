@@ -5,6 +5,8 @@
 //   - build.rs emits `cargo:rustc-cfg=has_sparse_files` on Unix targets
 //   - MULTITHREAD corresponds to LZ4IO_MULTITHREAD (off by default, feature-gated)
 
+#![allow(clippy::assertions_on_constants)]
+
 use lz4::config;
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -16,6 +16,8 @@
 //   - Jobs run concurrently (parallel execution across threads)
 //   - Closure captures are moved into the job correctly (void* replacement)
 
+#![cfg(not(feature = "forbid-unsafe"))]
+
 use lz4::threadpool::TPool;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Barrier};
@@ -1,3 +1,5 @@
+#![cfg(not(feature = "forbid-unsafe"))]
+
 #[path = "util/api.rs"]
 mod api;
 #[path = "util/cores.rs"]
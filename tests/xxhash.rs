@@ -5,8 +5,13 @@
 //   - One-shot hashing (`xxh32_oneshot`) matches C `XXH32(data, len, seed)`
 //   - Streaming API via `Xxh32State` matches C XXH32_reset/update/digest cycle
 //   - Known reference vectors from the xxHash spec are satisfied
+//   - `xxh32_of_reader` matches the one-shot hash and a frame's content checksum
 
-use lz4::xxhash::{xxh32_oneshot, Xxh32State};
+#![cfg(feature = "frame")]
+
+use std::io::{self, Read};
+
+use lz4::xxhash::{xxh32_of_reader, xxh32_oneshot, Xxh32State};
 
 // ---------------------------------------------------------------------------
 // One-shot: basic functionality
@@ -277,3 +282,90 @@ fn lz4frame_content_checksum_pattern() {
         "lz4frame content-checksum pattern must match one-shot reference"
     );
 }
+
+// ---------------------------------------------------------------------------
+// xxh32_of_reader
+// ---------------------------------------------------------------------------
+
+/// Yields at most 1000 bytes per read and is interrupted before every read
+/// that starts on a 4000-byte boundary.
+struct Choppy<'a> {
+    data: &'a [u8],
+    pos: usize,
+    interrupted: bool,
+}
+
+impl Read for Choppy<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos.is_multiple_of(4000) && !self.interrupted && self.pos < self.data.len() {
+            self.interrupted = true;
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        self.interrupted = false;
+        let n = buf.len().min(1000).min(self.data.len() - self.pos);
+        buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn of_reader_matches_oneshot() {
+    let data: Vec<u8> = (0..200_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    for len in [0, 1, 15, 16, 65_536, 65_537, data.len()] {
+        let want = xxh32_oneshot(&data[..len], 0);
+        assert_eq!(xxh32_of_reader(&data[..len]).unwrap(), want, "len {len}");
+        let choppy = Choppy {
+            data: &data[..len],
+            pos: 0,
+            interrupted: false,
+        };
+        assert_eq!(xxh32_of_reader(choppy).unwrap(), want, "choppy len {len}");
+    }
+}
+
+/// Values printed by `xxhsum -H32` (seed 0) for the same bytes.
+#[test]
+fn of_reader_matches_xxhsum() {
+    assert_eq!(xxh32_of_reader(&b""[..]).unwrap(), 0x02CC_5D05);
+    assert_eq!(xxh32_of_reader(&b"abc"[..]).unwrap(), 0x32D1_53FF);
+    assert_eq!(
+        xxh32_of_reader(&b"abc"[..]).unwrap(),
+        xxh32_oneshot(b"abc", 0)
+    );
+}
+
+#[test]
+fn of_reader_propagates_errors() {
+    struct Broken;
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+    }
+    let err = xxh32_of_reader(Broken).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
+
+/// The hash of the original content equals the checksum stored at the end of
+/// a frame compressed with content checksums.
+#[test]
+fn of_reader_matches_frame_content_checksum() {
+    use lz4::frame::{
+        lz4f_compress_frame, lz4f_compress_frame_bound, ContentChecksum, FrameInfo, Preferences,
+    };
+
+    let data = b"content checksum of a streamed file ".repeat(3000);
+    let prefs = Preferences {
+        frame_info: FrameInfo {
+            content_checksum_flag: ContentChecksum::Enabled,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut frame = vec![0u8; lz4f_compress_frame_bound(data.len(), Some(&prefs))];
+    let n = lz4f_compress_frame(&mut frame, &data, Some(&prefs)).unwrap();
+    frame.truncate(n);
+    let stored = u32::from_le_bytes(frame[frame.len() - 4..].try_into().unwrap());
+    assert_eq!(xxh32_of_reader(&data[..]).unwrap(), stored);
+}